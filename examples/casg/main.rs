@@ -53,7 +53,9 @@ fn main() {
 
     // Export the modified world excerpt to the given save file directory
     println!("Exporting to {:?}", output_directory);
-    excerpt.to_save((x, y, z).into(), Path::new(output_directory));
+    excerpt
+        .to_save((x, y, z).into(), Path::new(output_directory))
+        .unwrap_or_else(|err| panic!("Export failed: {}", err));
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -6,6 +6,7 @@ use std::path::Path;
 use mcprogedit::block::*;
 use mcprogedit::colour::Colour;
 use mcprogedit::coordinates::BlockCoord;
+use mcprogedit::shape::{Line, Shape};
 use mcprogedit::world_excerpt::WorldExcerpt;
 
 fn main() {
@@ -34,94 +35,47 @@ fn main() {
 
     // Modify the world excerpt:
     // Replace all solid blocks along the edge of the excerpt, with red concrete.
-    fn replace_solid(excerpt: &mut WorldExcerpt, at: (i64, i64, i64), replacement: Block) {
-        if let Some(block) = excerpt.block_at(at.into()) {
-            if block.is_solid() {
-                excerpt.set_block_at(at.into(), replacement);
-            }
-        }
-    }
-
-    // Surfaces in the xy plane
-    for x in 0..x_len {
-        for y in 0..y_len {
-            replace_solid(
-                &mut excerpt,
-                (x, y, 0),
-                Block::concrete_with_colour(Colour::Red),
-            );
-            replace_solid(
-                &mut excerpt,
-                (x, y, z_len - 1),
-                Block::concrete_with_colour(Colour::Red),
-            );
-        }
-    }
-
-    // Surfaces in the xz plane
-    for x in 0..x_len {
-        for z in 0..z_len {
-            replace_solid(
-                &mut excerpt,
-                (x, 0, z),
-                Block::concrete_with_colour(Colour::Red),
-            );
-            replace_solid(
-                &mut excerpt,
-                (x, y_len - 1, z),
-                Block::concrete_with_colour(Colour::Red),
-            );
-        }
-    }
-
-    // Surfaces in the yz plane
-    for y in 0..y_len {
-        for z in 0..z_len {
-            replace_solid(
-                &mut excerpt,
-                (0, y, z),
-                Block::concrete_with_colour(Colour::Red),
-            );
-            replace_solid(
-                &mut excerpt,
-                (x_len - 1, y, z),
-                Block::concrete_with_colour(Colour::Red),
-            );
+    let (x_max, y_max, z_max) = (x_len - 1, y_len - 1, z_len - 1);
+    for (at, block) in excerpt.iter_mut() {
+        let on_surface =
+            at.0 == 0 || at.0 == x_max || at.1 == 0 || at.1 == y_max || at.2 == 0 || at.2 == z_max;
+        if on_surface && block.is_solid() {
+            *block = Block::concrete_with_colour(Colour::Red);
         }
     }
 
     // Wireframe
-    for x in 0..x_len {
-        set_border_block(&mut excerpt, &(x, 0, 0).into());
-        set_border_block(&mut excerpt, &(x, y_len - 1, 0).into());
-        set_border_block(&mut excerpt, &(x, 0, z_len - 1).into());
-        set_border_block(&mut excerpt, &(x, y_len - 1, z_len - 1).into());
-    }
-    for y in 0..y_len {
-        set_border_block(&mut excerpt, &(0, y, 0).into());
-        set_border_block(&mut excerpt, &(x_len - 1, y, 0).into());
-        set_border_block(&mut excerpt, &(0, y, z_len - 1).into());
-        set_border_block(&mut excerpt, &(x_len - 1, y, z_len - 1).into());
-    }
-    for z in 0..z_len {
-        set_border_block(&mut excerpt, &(0, 0, z).into());
-        set_border_block(&mut excerpt, &(x_len - 1, 0, z).into());
-        set_border_block(&mut excerpt, &(0, y_len - 1, z).into());
-        set_border_block(&mut excerpt, &(x_len - 1, y_len - 1, z).into());
+    let edges = [
+        ((0, 0, 0), (x_max, 0, 0)),
+        ((0, y_max, 0), (x_max, y_max, 0)),
+        ((0, 0, z_max), (x_max, 0, z_max)),
+        ((0, y_max, z_max), (x_max, y_max, z_max)),
+        ((0, 0, 0), (0, y_max, 0)),
+        ((x_max, 0, 0), (x_max, y_max, 0)),
+        ((0, 0, z_max), (0, y_max, z_max)),
+        ((x_max, 0, z_max), (x_max, y_max, z_max)),
+        ((0, 0, 0), (0, 0, z_max)),
+        ((x_max, 0, 0), (x_max, 0, z_max)),
+        ((0, y_max, 0), (0, y_max, z_max)),
+        ((x_max, y_max, 0), (x_max, y_max, z_max)),
+    ];
+    for (from, to) in edges.iter() {
+        Line::new((*from).into(), (*to).into()).fill(&mut excerpt, &border_block);
     }
 
     // Export the modified world excerpt to the given save file directory
     println!("Exporting to {:?}", output_directory);
-    excerpt.to_save((x, y, z).into(), Path::new(output_directory));
+    excerpt
+        .to_save((x, y, z).into(), Path::new(output_directory))
+        .unwrap_or_else(|err| panic!("Export failed: {}", err));
 }
 
-fn set_border_block(excerpt: &mut WorldExcerpt, at: &BlockCoord) {
-    let block = if (at.0 + at.1 + at.2) % 4 <= 1 {
+fn border_block(at: BlockCoord) -> Block {
+    if (at.0 + at.1 + at.2) % 4 <= 1 {
         Block::concrete_with_colour(Colour::Yellow)
     } else {
         Block::concrete_with_colour(Colour::Black)
-    };
-    excerpt.set_block_at(*at, block);
+    }
 }
 
 fn parse_i64_or_exit(string: &str) -> i64 {
@@ -0,0 +1,671 @@
+//! Bills of materials, listing the items needed to build a world excerpt.
+//!
+//! Each block is mapped to the item used for placing it. Blocks spanning
+//! several positions, such as doors, beds and tall flowers, are counted once,
+//! while double slabs count as two slab items. Fluids, fire, portals and
+//! technical blocks such as piston heads are skipped, as they are not placed
+//! from items. Blocks with no corresponding item in [`ItemKind`] are listed
+//! separately, by block name, in [`BillOfMaterials::unmapped()`].
+//!
+//! [`BillOfMaterials::base_materials()`] breaks the items further down into
+//! base materials, using a small set of crafting recipes.
+
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+
+use crate::block::*;
+use crate::block_state::BlockState;
+use crate::item::ItemKind;
+use crate::material::*;
+use crate::world_excerpt::WorldExcerpt;
+
+/// Items and item counts needed to build a world excerpt.
+///
+/// Created with
+/// [`WorldExcerpt::bill_of_materials()`](crate::world_excerpt::WorldExcerpt::bill_of_materials).
+///
+/// # Examples
+/// ```
+/// use mcprogedit::block::Block;
+/// use mcprogedit::item::ItemKind;
+/// use mcprogedit::material::WoodMaterial;
+/// use mcprogedit::world_excerpt::WorldExcerpt;
+///
+/// let mut excerpt = WorldExcerpt::new(3, 1, 1);
+/// excerpt.set_block_at((0, 0, 0).into(), Block::oak_planks());
+/// excerpt.set_block_at((1, 0, 0).into(), Block::oak_planks());
+/// excerpt.set_block_at((2, 0, 0).into(), Block::WaterSource);
+///
+/// let bill = excerpt.bill_of_materials(false);
+/// assert_eq!(bill.count(&ItemKind::Planks(WoodMaterial::Oak)), 2);
+/// assert_eq!(bill.items().len(), 1);
+///
+/// let base = bill.base_materials();
+/// assert_eq!(base, vec![(ItemKind::Log(WoodMaterial::Oak), 0.5)]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BillOfMaterials {
+    items: Vec<(ItemKind, u64)>,
+    unmapped: BTreeMap<String, u64>,
+}
+
+impl BillOfMaterials {
+    /// Lists the items needed to place every block of `excerpt`.
+    ///
+    /// If `include_inventories` is set, the contents of chests, furnaces,
+    /// jukeboxes and other blocks holding items are included as well.
+    pub fn of(excerpt: &WorldExcerpt, include_inventories: bool) -> Self {
+        let mut bill = Self::default();
+
+        for (_, block) in excerpt.iter() {
+            match needed_for(block) {
+                Needed::Nothing => (),
+                Needed::Item(kind, count) => bill.add(kind, count),
+                Needed::Unmapped => {
+                    let name = BlockState::latest(block).name;
+                    *bill.unmapped.entry(name).or_insert(0) += 1;
+                }
+            }
+            if include_inventories {
+                bill.add_contents_of(block);
+            }
+        }
+
+        bill
+    }
+
+    /// Returns the needed items and their counts, in order of first appearance.
+    pub fn items(&self) -> &[(ItemKind, u64)] {
+        &self.items
+    }
+
+    /// Returns the number of items of the given kind needed.
+    pub fn count(&self, kind: &ItemKind) -> u64 {
+        self.items
+            .iter()
+            .find(|(other, _)| other == kind)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// Returns the counts of blocks for which no item is known, by block name.
+    pub fn unmapped(&self) -> &BTreeMap<String, u64> {
+        &self.unmapped
+    }
+
+    /// Breaks the needed items down into base materials, using crafting recipes.
+    ///
+    /// Items are replaced by their ingredients until only items without a known
+    /// recipe remain, e.g. logs, cobblestone and iron ingots. Quantities are
+    /// fractional, since recipes often yield several items. Dyes are left out,
+    /// and sticks are made from oak planks regardless of the wood actually used.
+    pub fn base_materials(&self) -> Vec<(ItemKind, f64)> {
+        let mut base = Vec::new();
+        for (kind, count) in &self.items {
+            add_base_materials(&mut base, kind, *count as f64);
+        }
+        base
+    }
+
+    fn add(&mut self, kind: ItemKind, count: u64) {
+        match self.items.iter_mut().find(|(other, _)| *other == kind) {
+            Some((_, total)) => *total += count,
+            None => self.items.push((kind, count)),
+        }
+    }
+
+    fn add_contents_of(&mut self, block: &Block) {
+        let inventory = match block {
+            Block::BlastFurnace(furnace) | Block::Furnace(furnace) | Block::Smoker(furnace) => {
+                &furnace.items
+            }
+            Block::BrewingStand(stand) => &stand.items,
+            Block::Chest(chest) | Block::TrappedChest(chest) => &chest.items,
+            Block::Dispenser(dispenser) => &dispenser.items,
+            Block::Dropper(dropper) => &dropper.items,
+            Block::Hopper(hopper) => &hopper.items,
+            Block::ShulkerBox(shulker_box) => &shulker_box.items,
+            Block::Jukebox(jukebox) => {
+                if let Some(record) = &jukebox.record {
+                    self.add(record.kind().clone(), 1);
+                }
+                return;
+            }
+            Block::Lectern {
+                book: Some(book), ..
+            } => {
+                self.add(book.book.kind().clone(), 1);
+                return;
+            }
+            _ => return,
+        };
+        for (item, count) in inventory.items() {
+            self.add(item.kind().clone(), count.max(0) as u64);
+        }
+    }
+}
+
+/// What is needed for placing a single block.
+enum Needed {
+    Nothing,
+    Item(ItemKind, u64),
+    Unmapped,
+}
+
+fn needed_for(block: &Block) -> Needed {
+    let kind = match block {
+        // Not placed from items
+        Block::None
+        | Block::Unknown(_)
+        | Block::Air
+        | Block::CaveAir
+        | Block::BubbleColumn { .. }
+        | Block::EndGateway
+        | Block::EndPortal
+        | Block::Fire { .. }
+        | Block::FrostedIce
+        | Block::Lava { .. }
+        | Block::LavaSource
+        | Block::NetherPortal { .. }
+        | Block::PistonHead { .. }
+        | Block::SoulFire { .. }
+        | Block::StickyPistonHead { .. }
+        | Block::Water { .. }
+        | Block::WaterSource => return Needed::Nothing,
+
+        // Counted at one of their positions only
+        Block::Bed(Bed {
+            end: BedEnd::Head, ..
+        })
+        | Block::Door(Door {
+            half: DoorHalf::Upper,
+            ..
+        })
+        | Block::Flower(Flower::LilacTop)
+        | Block::Flower(Flower::PeonyTop)
+        | Block::Flower(Flower::RoseBushTop)
+        | Block::Flower(Flower::SunflowerTop)
+        | Block::Grass(Grass::LargeFernTop)
+        | Block::Grass(Grass::TallGrassTop) => return Needed::Nothing,
+
+        Block::Slab(Slab {
+            material,
+            position: SlabVariant::Double,
+            ..
+        }) => return Needed::Item(ItemKind::Slab(*material), 2),
+        Block::Snow { thickness } => {
+            return Needed::Item(ItemKind::SnowLayer, thickness.get() as u64)
+        }
+
+        Block::Andesite => ItemKind::Andesite,
+        Block::Anvil { damage, .. } => ItemKind::Anvil(*damage),
+        Block::Banner(_) => ItemKind::Banner,
+        Block::Barrier => ItemKind::Barrier,
+        Block::Beacon(_) => ItemKind::Beacon,
+        Block::Bed(bed) => ItemKind::Bed(bed.colour),
+        Block::Bedrock => ItemKind::Bedrock,
+        Block::Beetroots { .. } => ItemKind::Seeds(SeedMaterial::Beetroot),
+        Block::BlockOfCoal => ItemKind::BlockOfCoal,
+        Block::BlockOfDiamond => ItemKind::BlockOfDiamond,
+        Block::BlockOfEmerald => ItemKind::BlockOfEmerald,
+        Block::BlockOfGold => ItemKind::BlockOfGold,
+        Block::BlockOfIron => ItemKind::BlockOfIron,
+        Block::BlockOfQuartz => ItemKind::BlockOfQuartz,
+        Block::BlockOfRedstone => ItemKind::BlockOfRedstone,
+        Block::BlockOfSlime => ItemKind::SlimeBlock,
+        Block::BoneBlock { .. } => ItemKind::BoneBlock,
+        Block::Bookshelf => ItemKind::Bookshelf,
+        Block::BrewingStand(_) => ItemKind::BrewingStand,
+        Block::BrickBlock => ItemKind::BrickBlock,
+        Block::BrownMushroom => ItemKind::BrownMushroom,
+        Block::BrownMushroomBlock { .. } | Block::BrownMushroomStem { .. } => {
+            ItemKind::BrownMushroomBlock
+        }
+        Block::Button(material, _) => ItemKind::Button(*material),
+        Block::Cactus { .. } => ItemKind::Cactus,
+        Block::Cake { .. } => ItemKind::Cake,
+        Block::Carpet { colour } => ItemKind::Carpet(*colour),
+        Block::Carrots { .. } => ItemKind::Carrot,
+        Block::CarvedPumpkin { .. } | Block::Pumpkin { .. } => ItemKind::Pumpkin,
+        Block::Cauldron { .. } => ItemKind::Cauldron,
+        Block::Chest(_) => ItemKind::Chest,
+        Block::ChiseledQuartzBlock => ItemKind::ChiseledQuartzBlock,
+        Block::ChiseledRedSandstone => ItemKind::ChiseledRedSandstone,
+        Block::ChiseledSandstone => ItemKind::ChiseledSandstone,
+        Block::ChiseledStoneBricks => ItemKind::ChiseledStoneBricks,
+        Block::ChorusFlower { .. } => ItemKind::ChorusFlower,
+        Block::ChorusPlant => ItemKind::ChorusPlant,
+        Block::Clay => ItemKind::ClayBlock,
+        Block::CoalOre => ItemKind::CoalOre,
+        Block::CoarseDirt => ItemKind::CoarseDirt,
+        Block::Cobblestone => ItemKind::Cobblestone,
+        Block::Cobweb => ItemKind::Cobweb,
+        Block::CocoaBeans { .. } => ItemKind::CocoaBeans,
+        Block::CommandBlock(command_block) => match command_block.variant {
+            CommandBlockVariant::ChainedCommandBlock => ItemKind::ChainCommandBlock,
+            CommandBlockVariant::CommandBlock => ItemKind::CommandBlock,
+            CommandBlockVariant::RepeatingCommandBlock => ItemKind::RepeatingCommandBlock,
+        },
+        Block::Concrete { colour } => ItemKind::Concrete(Some(*colour)),
+        Block::ConcretePowder { colour } => ItemKind::ConcretePowder(Some(*colour)),
+        Block::CrackedStoneBricks => ItemKind::CrackedStoneBricks,
+        Block::CraftingTable => ItemKind::CraftingTable,
+        Block::CutRedSandstone => ItemKind::CutRedSandstone,
+        Block::CutSandstone => ItemKind::CutSandstone,
+        Block::DarkPrismarine => ItemKind::DarkPrismarine,
+        Block::DaylightDetector | Block::InvertedDaylightDetector => ItemKind::DaylightDetector,
+        Block::DeadBush => ItemKind::DeadBush,
+        Block::DiamondOre => ItemKind::DiamondOre,
+        Block::Diorite => ItemKind::Diorite,
+        Block::Dirt => ItemKind::Dirt,
+        Block::Dispenser(_) => ItemKind::Dispenser,
+        Block::Door(door) => ItemKind::Door(door.material),
+        Block::DragonEgg => ItemKind::DragonEgg,
+        Block::Dropper(_) => ItemKind::Dropper,
+        Block::EmeraldOre => ItemKind::EmeraldOre,
+        Block::EnchantingTable { .. } => ItemKind::EnchantingTable,
+        Block::EndPortalFrame { .. } => ItemKind::EndPortalFrame,
+        Block::EndRod { .. } => ItemKind::EndRod,
+        Block::EndStone => ItemKind::EndStone,
+        Block::EndStoneBricks => ItemKind::EndBricks,
+        Block::EnderChest { .. } => ItemKind::EnderChest,
+        Block::Farmland { .. } => ItemKind::Farmland,
+        Block::Fence { material, .. } => ItemKind::Fence(*material),
+        Block::FenceGate { material, .. } => ItemKind::FenceGate(*material),
+        Block::Flower(flower) => ItemKind::Flower(*flower),
+        Block::FlowerPot(_) => ItemKind::FlowerPot,
+        Block::Furnace(_) => ItemKind::Furnace,
+        Block::Glass { colour } => ItemKind::Glass(*colour),
+        Block::GlassPane { colour, .. } => ItemKind::GlassPane(*colour),
+        Block::GlazedTerracotta(terracotta) => ItemKind::GlazedTerracotta(Some(terracotta.colour)),
+        Block::Glowstone => ItemKind::Glowstone,
+        Block::GoldOre => ItemKind::GoldOre,
+        Block::Granite => ItemKind::Granite,
+        Block::Grass(Grass::Grass) => ItemKind::Grass,
+        Block::Grass(Grass::LargeFernBottom) => ItemKind::LargeFern,
+        Block::Grass(Grass::TallGrassBottom) => ItemKind::DoubleTallgrass,
+        Block::GrassBlock => ItemKind::GrassBlock,
+        Block::GrassPath => ItemKind::GrassPath,
+        Block::Gravel => ItemKind::Gravel,
+        Block::HayBale { .. } => ItemKind::HayBale,
+        Block::Head(_) => ItemKind::MobHead,
+        Block::Hopper(_) => ItemKind::Hopper,
+        Block::Ice => ItemKind::Ice,
+        Block::InfestedChiseledStoneBricks => ItemKind::InfestedChiseledStoneBricks,
+        Block::InfestedCobblestone => ItemKind::InfestedCobblestone,
+        Block::InfestedCrackedStoneBricks => ItemKind::InfestedCrackedStoneBricks,
+        Block::InfestedMossyStoneBricks => ItemKind::InfestedMossyStoneBricks,
+        Block::InfestedStone => ItemKind::InfestedStone,
+        Block::InfestedStoneBricks => ItemKind::InfestedStoneBricks,
+        Block::IronBars { .. } => ItemKind::IronBars,
+        Block::IronOre => ItemKind::IronOre,
+        Block::JackOLantern { .. } => ItemKind::JackOLantern,
+        Block::Jukebox(_) => ItemKind::Jukebox,
+        Block::Ladder { .. } => ItemKind::Ladder,
+        Block::LapisLazuliBlock => ItemKind::LapisLazuliBlock,
+        Block::LapisLazuliOre => ItemKind::LapisLazuliOre,
+        Block::Leaves { material, .. } => match material {
+            LeavesMaterial::Acacia => ItemKind::AcaciaLeaves,
+            LeavesMaterial::Birch => ItemKind::BirchLeaves,
+            LeavesMaterial::DarkOak => ItemKind::DarkOakLeaves,
+            LeavesMaterial::Jungle => ItemKind::JungleLeaves,
+            LeavesMaterial::Oak => ItemKind::OakLeaves,
+            LeavesMaterial::Spruce => ItemKind::SpruceLeaves,
+        },
+        Block::Lever(..) => ItemKind::Lever,
+        Block::LilyPad => ItemKind::LilyPad,
+        Block::Log(Log {
+            material,
+            alignment: Some(_),
+            stripped: false,
+        }) => ItemKind::Log(*material),
+        Block::MagmaBlock => ItemKind::Magma,
+        Block::Melon => ItemKind::MelonBlock,
+        Block::MelonStem { .. } => ItemKind::Seeds(SeedMaterial::Melon),
+        Block::MossyCobblestone => ItemKind::MossyCobblestone,
+        Block::MossyStoneBricks => ItemKind::MossyStoneBricks,
+        Block::Mycelium => ItemKind::Mycelium,
+        Block::NetherBricks => ItemKind::NetherBrickBlock,
+        Block::NetherWart { .. } => ItemKind::NetherWart,
+        Block::NetherWartBlock => ItemKind::NetherWartBlock,
+        Block::Netherrack => ItemKind::Netherrack,
+        Block::Noteblock(_) => ItemKind::Noteblock,
+        Block::Observer { .. } => ItemKind::Observer,
+        Block::Obsidian => ItemKind::Obsidian,
+        Block::PackedIce => ItemKind::PackedIce,
+        Block::Piston { .. } => ItemKind::Piston,
+        Block::Planks { material } => ItemKind::Planks(*material),
+        Block::Podzol => ItemKind::Podzol,
+        Block::PolishedAndesite => ItemKind::PolishedAndesite,
+        Block::PolishedDiorite => ItemKind::PolishedDiorite,
+        Block::PolishedGranite => ItemKind::PolishedGranite,
+        Block::Potatoes { .. } => ItemKind::Potato,
+        Block::PressurePlate { material } => ItemKind::PressurePlate(*material),
+        Block::Prismarine => ItemKind::Prismarine,
+        Block::PrismarineBricks => ItemKind::PrismarineBricks,
+        Block::PumpkinStem { .. } => ItemKind::Seeds(SeedMaterial::Pumpkin),
+        Block::PurpurBlock => ItemKind::PurpurBlock,
+        Block::PurpurPillar { .. } => ItemKind::PurpurPillar,
+        Block::QuartzOre => ItemKind::QuartzOre,
+        Block::QuartzPillar { .. } => ItemKind::PillarQuartzBlock,
+        Block::Rail { variant, .. } => match variant {
+            RailType::Activator => ItemKind::ActivatorRail,
+            RailType::Detector => ItemKind::DetectorRail,
+            RailType::Normal => ItemKind::Rail,
+            RailType::Powered => ItemKind::PoweredRail,
+        },
+        Block::RedMushroom => ItemKind::RedMushroom,
+        Block::RedMushroomBlock { .. } | Block::RedMushroomStem { .. } => {
+            ItemKind::RedMushroomBlock
+        }
+        Block::RedNetherBricks => ItemKind::RedNetherBrick,
+        Block::RedSand => ItemKind::RedSand,
+        Block::RedSandstone => ItemKind::RedSandstone,
+        Block::RedstoneComparator { .. } | Block::RedstoneSubtractor { .. } => {
+            ItemKind::RedstoneComparator
+        }
+        Block::RedstoneLamp => ItemKind::RedstoneLamp,
+        Block::RedstoneOre => ItemKind::RedstoneOre,
+        Block::RedstoneRepeater(_) => ItemKind::RedstoneRepeater,
+        Block::RedstoneTorch { .. } => ItemKind::RedstoneTorch,
+        Block::RedstoneWire => ItemKind::Redstone,
+        Block::Sand => ItemKind::Sand,
+        Block::Sandstone => ItemKind::Sandstone,
+        Block::Sapling { material, .. } => ItemKind::Sapling(*material),
+        Block::SeaLantern => ItemKind::SeaLantern,
+        Block::ShulkerBox(shulker_box) => ItemKind::ShulkerBox(shulker_box.colour),
+        Block::Sign(sign) => ItemKind::Sign(sign.material),
+        Block::Slab(slab) => ItemKind::Slab(slab.material),
+        Block::SmoothQuartz => ItemKind::SmoothQuartzBlock,
+        Block::SmoothRedSandstone => ItemKind::SmoothRedSandstone,
+        Block::SmoothSandstone => ItemKind::SmoothSandstone,
+        Block::SnowBlock => ItemKind::Snow,
+        Block::SoulSand => ItemKind::SoulSand,
+        Block::Spawner => ItemKind::MobSpawner,
+        Block::Sponge => ItemKind::Sponge,
+        Block::Stairs(stair) => ItemKind::Stairs(stair.material),
+        Block::StickyPiston { .. } => ItemKind::StickyPiston,
+        Block::Stone => ItemKind::Stone,
+        Block::StoneBricks => ItemKind::StoneBricks,
+        Block::StructureBlock => ItemKind::StructureBlock,
+        Block::StructureVoid => ItemKind::StructureVoid,
+        Block::SugarCane { .. } => ItemKind::SugarCane,
+        Block::Terracotta { colour } => ItemKind::Terracotta(*colour),
+        Block::TNT => ItemKind::TNT,
+        Block::Torch { .. } => ItemKind::Torch,
+        Block::Trapdoor(trapdoor) => ItemKind::Trapdoor(trapdoor.material),
+        Block::TrappedChest(_) => ItemKind::TrappedChest,
+        Block::Tripwire => ItemKind::String,
+        Block::TripwireHook { .. } => ItemKind::TripwireHook,
+        Block::Vines(_) => ItemKind::Vines,
+        Block::Wall {
+            material: WallMaterial::Cobblestone,
+            ..
+        } => ItemKind::CobblestoneWall,
+        Block::Wall {
+            material: WallMaterial::MossyCobblestone,
+            ..
+        } => ItemKind::MossyCobblestoneWall,
+        Block::WetSponge => ItemKind::WetSponge,
+        Block::Wheat { .. } => ItemKind::Seeds(SeedMaterial::Wheat),
+        Block::Wool { colour } => ItemKind::Wool(*colour),
+        _ => return Needed::Unmapped,
+    };
+    Needed::Item(kind, 1)
+}
+
+fn add_base_materials(base: &mut Vec<(ItemKind, f64)>, kind: &ItemKind, quantity: f64) {
+    match recipe(kind) {
+        Some(ingredients) => {
+            for (ingredient, per_item) in ingredients {
+                add_base_materials(base, &ingredient, quantity * per_item);
+            }
+        }
+        None => match base.iter_mut().find(|(other, _)| other == kind) {
+            Some((_, total)) => *total += quantity,
+            None => base.push((kind.clone(), quantity)),
+        },
+    }
+}
+
+/// Returns the ingredients needed for crafting a single item of the given
+/// kind, or `None` if it is a base material.
+fn recipe(kind: &ItemKind) -> Option<Vec<(ItemKind, f64)>> {
+    const STICK: (ItemKind, f64) = (ItemKind::Planks(WoodMaterial::Oak), 0.5);
+    let ingredients = match kind {
+        // Wood
+        ItemKind::Planks(material) => vec![(ItemKind::Log(*material), 0.25)],
+        ItemKind::Stick => vec![STICK],
+        ItemKind::Fence(material) => match WoodMaterial::try_from(Material::from(*material)) {
+            // 3 fences from 4 planks and 2 sticks
+            Ok(wood) => vec![(ItemKind::Planks(wood), 5.0 / 3.0)],
+            // 6 fences from 4 nether bricks and 2 nether brick items
+            Err(_) => vec![
+                (ItemKind::NetherBrickBlock, 4.0 / 6.0),
+                (ItemKind::NetherBrick, 2.0 / 6.0),
+            ],
+        },
+        ItemKind::FenceGate(material) => vec![(ItemKind::Planks(*material), 4.0)],
+        ItemKind::Sign(material) => vec![(ItemKind::Planks(*material), 6.5 / 3.0)],
+        ItemKind::Door(DoorMaterial::Iron) => vec![(ItemKind::Ingot(IngotMaterial::Iron), 2.0)],
+        ItemKind::Door(material) => vec![(planks_of(*material)?, 2.0)],
+        ItemKind::Trapdoor(DoorMaterial::Iron) => {
+            vec![(ItemKind::Ingot(IngotMaterial::Iron), 4.0)]
+        }
+        ItemKind::Trapdoor(material) => vec![(planks_of(*material)?, 3.0)],
+        ItemKind::Button(material) => vec![(block_of(Material::from(*material))?, 1.0)],
+        ItemKind::PressurePlate(PressurePlateMaterial::Gold) => {
+            vec![(ItemKind::Ingot(IngotMaterial::Gold), 2.0)]
+        }
+        ItemKind::PressurePlate(PressurePlateMaterial::Iron) => {
+            vec![(ItemKind::Ingot(IngotMaterial::Iron), 2.0)]
+        }
+        ItemKind::PressurePlate(material) => vec![(block_of(Material::from(*material))?, 2.0)],
+        ItemKind::Stairs(material) => vec![(block_of(Material::from(*material))?, 1.5)],
+        ItemKind::Slab(material) => vec![(block_of(Material::from(*material))?, 0.5)],
+        ItemKind::Bookshelf => vec![
+            (ItemKind::Planks(WoodMaterial::Oak), 6.0),
+            (ItemKind::Book, 3.0),
+        ],
+        ItemKind::Chest => vec![(ItemKind::Planks(WoodMaterial::Oak), 8.0)],
+        ItemKind::CraftingTable => vec![(ItemKind::Planks(WoodMaterial::Oak), 4.0)],
+        ItemKind::Ladder => vec![(ItemKind::Stick, 7.0 / 3.0)],
+        ItemKind::TrappedChest => vec![(ItemKind::Chest, 1.0), (ItemKind::TripwireHook, 1.0)],
+        ItemKind::TripwireHook => vec![
+            (ItemKind::Ingot(IngotMaterial::Iron), 0.5),
+            (ItemKind::Stick, 0.5),
+            (ItemKind::Planks(WoodMaterial::Oak), 0.5),
+        ],
+
+        // Light and redstone
+        ItemKind::Torch => vec![(ItemKind::Coal, 0.25), (ItemKind::Stick, 0.25)],
+        ItemKind::RedstoneTorch => vec![(ItemKind::Redstone, 1.0), (ItemKind::Stick, 1.0)],
+        ItemKind::Lever => vec![(ItemKind::Cobblestone, 1.0), (ItemKind::Stick, 1.0)],
+        ItemKind::Rail => vec![
+            (ItemKind::Ingot(IngotMaterial::Iron), 6.0 / 16.0),
+            (ItemKind::Stick, 1.0 / 16.0),
+        ],
+        ItemKind::Furnace => vec![(ItemKind::Cobblestone, 8.0)],
+
+        // Stone and bricks
+        ItemKind::StoneBricks => vec![(ItemKind::Stone, 1.0)],
+        ItemKind::BrickBlock => vec![(ItemKind::Brick, 4.0)],
+        ItemKind::NetherBrickBlock => vec![(ItemKind::NetherBrick, 4.0)],
+        ItemKind::Sandstone => vec![(ItemKind::Sand, 4.0)],
+        ItemKind::RedSandstone => vec![(ItemKind::RedSand, 4.0)],
+        ItemKind::BlockOfQuartz => vec![(ItemKind::NetherQuartz, 4.0)],
+
+        // Storage blocks
+        ItemKind::BlockOfCoal => vec![(ItemKind::Coal, 9.0)],
+        ItemKind::BlockOfDiamond => vec![(ItemKind::Diamond, 9.0)],
+        ItemKind::BlockOfEmerald => vec![(ItemKind::Emerald, 9.0)],
+        ItemKind::BlockOfGold => vec![(ItemKind::Ingot(IngotMaterial::Gold), 9.0)],
+        ItemKind::BlockOfIron => vec![(ItemKind::Ingot(IngotMaterial::Iron), 9.0)],
+        ItemKind::BlockOfRedstone => vec![(ItemKind::Redstone, 9.0)],
+        ItemKind::HayBale => vec![(ItemKind::Wheat, 9.0)],
+        ItemKind::LapisLazuliBlock => vec![(ItemKind::LapisLazuli, 9.0)],
+        ItemKind::SlimeBlock => vec![(ItemKind::Slimeball, 9.0)],
+        ItemKind::IronBars => vec![(ItemKind::Ingot(IngotMaterial::Iron), 6.0 / 16.0)],
+
+        // Coloured blocks, not counting dyes
+        ItemKind::Carpet(colour) => vec![(ItemKind::Wool(*colour), 2.0 / 3.0)],
+        ItemKind::GlassPane(colour) => vec![(ItemKind::Glass(*colour), 6.0 / 16.0)],
+        ItemKind::Concrete(colour) => vec![(ItemKind::ConcretePowder(*colour), 1.0)],
+        ItemKind::ConcretePowder(_) => vec![(ItemKind::Sand, 0.5), (ItemKind::Gravel, 0.5)],
+
+        // Snow
+        ItemKind::Snow => vec![(ItemKind::Snowball, 4.0)],
+        ItemKind::SnowLayer => vec![(ItemKind::Snow, 0.5)],
+
+        _ => return None,
+    };
+    Some(ingredients)
+}
+
+/// Returns the planks of the given door material, if it is a kind of wood.
+fn planks_of(material: DoorMaterial) -> Option<ItemKind> {
+    WoodMaterial::try_from(Material::from(material))
+        .ok()
+        .map(ItemKind::Planks)
+}
+
+/// Returns the full block item that stairs, slabs, buttons and pressure
+/// plates of the given material are crafted from.
+fn block_of(material: Material) -> Option<ItemKind> {
+    if let Ok(wood) = WoodMaterial::try_from(material) {
+        return Some(ItemKind::Planks(wood));
+    }
+    let kind = match material {
+        Material::Andesite => ItemKind::Andesite,
+        Material::Brick => ItemKind::BrickBlock,
+        Material::Cobblestone => ItemKind::Cobblestone,
+        Material::CutRedSandstone => ItemKind::CutRedSandstone,
+        Material::CutSandstone => ItemKind::CutSandstone,
+        Material::DarkPrismarine => ItemKind::DarkPrismarine,
+        Material::Diorite => ItemKind::Diorite,
+        Material::EndStoneBrick => ItemKind::EndBricks,
+        Material::Granite => ItemKind::Granite,
+        Material::MossyCobblestone => ItemKind::MossyCobblestone,
+        Material::MossyStoneBrick => ItemKind::MossyStoneBricks,
+        Material::NetherBrick => ItemKind::NetherBrickBlock,
+        Material::PolishedAndesite => ItemKind::PolishedAndesite,
+        Material::PolishedDiorite => ItemKind::PolishedDiorite,
+        Material::PolishedGranite => ItemKind::PolishedGranite,
+        Material::Prismarine => ItemKind::Prismarine,
+        Material::PrismarineBrick => ItemKind::PrismarineBricks,
+        Material::Purpur => ItemKind::PurpurBlock,
+        Material::Quartz => ItemKind::BlockOfQuartz,
+        Material::RedNetherBrick => ItemKind::RedNetherBrick,
+        Material::RedSandstone => ItemKind::RedSandstone,
+        Material::Sandstone => ItemKind::Sandstone,
+        Material::SmoothQuartz => ItemKind::SmoothQuartzBlock,
+        Material::SmoothRedSandstone => ItemKind::SmoothRedSandstone,
+        Material::SmoothSandstone => ItemKind::SmoothSandstone,
+        Material::Stone => ItemKind::Stone,
+        Material::StoneBrick => ItemKind::StoneBricks,
+        _ => return None,
+    };
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_ints::Int0Through15;
+    use crate::colour::Colour;
+    use crate::inventory::Inventory;
+    use crate::item::{Item, Recording};
+    use crate::positioning::{Edge8, Surface4};
+
+    fn door(half: DoorHalf) -> Block {
+        Block::Door(Door {
+            material: DoorMaterial::Spruce,
+            facing: Surface4::North,
+            half,
+            hinged_at: Hinge::Left,
+            open: false,
+        })
+    }
+
+    fn bed(end: BedEnd) -> Block {
+        Block::Bed(Bed {
+            colour: Colour::Red,
+            facing: Surface4::East,
+            end,
+        })
+    }
+
+    #[test]
+    fn multi_block_structures_and_skipped_blocks() {
+        let mut excerpt = WorldExcerpt::new(4, 2, 1);
+        excerpt.set_block_at((0, 0, 0).into(), door(DoorHalf::Lower));
+        excerpt.set_block_at((0, 1, 0).into(), door(DoorHalf::Upper));
+        excerpt.set_block_at((1, 0, 0).into(), bed(BedEnd::Foot));
+        excerpt.set_block_at((2, 0, 0).into(), bed(BedEnd::Head));
+        excerpt.set_block_at(
+            (3, 0, 0).into(),
+            Block::Slab(Slab {
+                material: SlabMaterial::Cobblestone,
+                position: SlabVariant::Double,
+                waterlogged: false,
+            }),
+        );
+        excerpt.set_block_at((1, 1, 0).into(), Block::WaterSource);
+        excerpt.set_block_at(
+            (2, 1, 0).into(),
+            Block::Fire {
+                age: Int0Through15::MIN,
+            },
+        );
+        excerpt.set_block_at((3, 1, 0).into(), Block::SmithingTable);
+
+        let bill = excerpt.bill_of_materials(false);
+        assert_eq!(bill.count(&ItemKind::Door(DoorMaterial::Spruce)), 1);
+        assert_eq!(bill.count(&ItemKind::Bed(Colour::Red)), 1);
+        assert_eq!(bill.count(&ItemKind::Slab(SlabMaterial::Cobblestone)), 2);
+        assert_eq!(bill.items().len(), 3);
+        assert_eq!(bill.unmapped()["minecraft:smithing_table"], 1);
+    }
+
+    #[test]
+    fn inventories_are_optional() {
+        let mut items = Inventory::new();
+        items.insert(0, Item::new_record(Recording::Cat), 1);
+        let mut excerpt = WorldExcerpt::new(1, 1, 1);
+        excerpt.set_block_at(
+            (0, 0, 0).into(),
+            Block::Chest(Box::new(Chest {
+                facing: Surface4::South,
+                variant: None,
+                waterlogged: false,
+                custom_name: None,
+                lock: None,
+                items,
+            })),
+        );
+
+        let record = ItemKind::Record(Recording::Cat);
+        assert_eq!(excerpt.bill_of_materials(false).count(&record), 0);
+        let bill = excerpt.bill_of_materials(true);
+        assert_eq!(bill.count(&record), 1);
+        assert_eq!(bill.count(&ItemKind::Chest), 1);
+    }
+
+    #[test]
+    fn base_materials_follow_recipes() {
+        let mut excerpt = WorldExcerpt::new(2, 1, 1);
+        for x in 0..2 {
+            excerpt.set_block_at(
+                (x, 0, 0).into(),
+                Block::Stairs(Stair {
+                    material: StairMaterial::Birch,
+                    position: Edge8::DownNorth,
+                    waterlogged: false,
+                }),
+            );
+        }
+
+        let base = excerpt.bill_of_materials(false).base_materials();
+        // 2 stairs = 3 planks = 0.75 logs
+        assert_eq!(base, vec![(ItemKind::Log(WoodMaterial::Birch), 0.75)]);
+    }
+}
@@ -204,3 +204,64 @@ impl From<Biome> for u8 {
         }
     }
 }
+
+impl Biome {
+    /// Numeric id used in saves from 1.13 (17w47a) up to 1.18 (21w37a).
+    ///
+    /// Biomes that were never generated ([`Biome::None`]) are saved as plains.
+    pub(crate) fn numeric_id(self) -> i32 {
+        match self {
+            Biome::None => 1,
+            biome => u8::from(biome) as i32,
+        }
+    }
+
+    /// Namespaced name used in saves from 1.18 (21w43a).
+    ///
+    /// Biomes that were removed in 1.18 are named after the biomes they were
+    /// merged into, and [`Biome::None`] is saved as plains.
+    pub(crate) fn modern_name(self) -> &'static str {
+        match self {
+            Biome::None | Biome::Plains => "minecraft:plains",
+            Biome::Ocean => "minecraft:ocean",
+            Biome::Desert | Biome::DesertHills | Biome::DesertM => "minecraft:desert",
+            Biome::ExtremeHills | Biome::ExtremeHillsEdge => "minecraft:windswept_hills",
+            Biome::Forest | Biome::ForestHills => "minecraft:forest",
+            Biome::Taiga | Biome::TaigaHills | Biome::TaigaM => "minecraft:taiga",
+            Biome::Swampland | Biome::SwamplandM => "minecraft:swamp",
+            Biome::River => "minecraft:river",
+            Biome::Hell => "minecraft:nether_wastes",
+            Biome::TheEnd => "minecraft:the_end",
+            Biome::FrozenOcean => "minecraft:frozen_ocean",
+            Biome::FrozenRiver => "minecraft:frozen_river",
+            Biome::IcePlains | Biome::IceMountains => "minecraft:snowy_plains",
+            Biome::MushroomIsland | Biome::MushroomIslandShore => "minecraft:mushroom_fields",
+            Biome::Beach => "minecraft:beach",
+            Biome::Jungle | Biome::JungleHills | Biome::JungleM => "minecraft:jungle",
+            Biome::JungleEdge | Biome::JungleEdgeM => "minecraft:sparse_jungle",
+            Biome::DeepOcean => "minecraft:deep_ocean",
+            Biome::StoneBeach => "minecraft:stony_shore",
+            Biome::ColdBeach => "minecraft:snowy_beach",
+            Biome::BirchForest | Biome::BirchForestHills => "minecraft:birch_forest",
+            Biome::RoofedForest | Biome::RoofedForestM => "minecraft:dark_forest",
+            Biome::ColdTaiga | Biome::ColdDaigaHills | Biome::ColdTaigaM => "minecraft:snowy_taiga",
+            Biome::MegaTaiga | Biome::MegaTaigaHills => "minecraft:old_growth_pine_taiga",
+            Biome::ExtremeHillsPlus => "minecraft:windswept_forest",
+            Biome::Savanna => "minecraft:savanna",
+            Biome::SavannaPlateau => "minecraft:savanna_plateau",
+            Biome::Mesa | Biome::MesaPlateau | Biome::MesaPlateauM => "minecraft:badlands",
+            Biome::MesaPlateauF | Biome::MesaPlateauFM => "minecraft:wooded_badlands",
+            Biome::TheVoid => "minecraft:the_void",
+            Biome::SunflowerPlains => "minecraft:sunflower_plains",
+            Biome::ExtremeHillsM | Biome::ExtremeHillsPlusM => "minecraft:windswept_gravelly_hills",
+            Biome::FlowerForest => "minecraft:flower_forest",
+            Biome::IcePlainsSpikes => "minecraft:ice_spikes",
+            Biome::BirchForestM | Biome::BirchForestHillsM => "minecraft:old_growth_birch_forest",
+            Biome::MegaSpruceTaiga | Biome::RedwoodTaigaHillsM => {
+                "minecraft:old_growth_spruce_taiga"
+            }
+            Biome::SavannaM | Biome::SavannaPlateauM => "minecraft:windswept_savanna",
+            Biome::MesaBryce => "minecraft:eroded_badlands",
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 mod banner;
@@ -14,10 +15,13 @@ mod glazed_terracotta;
 mod head;
 mod hopper;
 mod noteblock;
+mod query;
 mod redstone_repeater;
 mod shulker_box;
 mod sign;
 mod stair;
+mod swap;
+mod transform;
 mod trapdoor;
 mod vines;
 
@@ -38,6 +42,7 @@ pub use self::glazed_terracotta::*;
 pub use self::head::*;
 pub use self::hopper::*;
 pub use self::noteblock::*;
+pub use self::query::*;
 pub use self::redstone_repeater::*;
 pub use self::shulker_box::*;
 pub use self::sign::*;
@@ -45,20 +50,21 @@ pub use self::stair::*;
 pub use self::trapdoor::*;
 pub use self::vines::*;
 
+use crate::block_entity::{BlockEntity, CommonTags};
 use crate::bounded_ints::*;
 use crate::colour::*;
 use crate::item::Item;
 use crate::material::*;
 use crate::positioning::*;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SlabVariant {
     Bottom,
     Double,
     Top,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Slab {
     pub material: SlabMaterial,
     pub position: SlabVariant,
@@ -71,7 +77,7 @@ impl Slab {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum RailType {
     Activator,
     Detector,
@@ -79,7 +85,7 @@ pub enum RailType {
     Powered,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum RailShape {
     EastWest,
     NorthEast,
@@ -124,9 +130,46 @@ impl RailShape {
             Self::NorthEast => 9,
         }
     }
+
+    /// Returns the shape after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let mut shape = *self;
+        for _ in 0..rotation.clockwise_quarter_turns() {
+            shape = match shape {
+                Self::EastWest => Self::NorthSouth,
+                Self::NorthEast => Self::SouthEast,
+                Self::NorthSouth => Self::EastWest,
+                Self::NorthWest => Self::NorthEast,
+                Self::SouthEast => Self::SouthWest,
+                Self::SouthWest => Self::NorthWest,
+                Self::AscendingEast => Self::AscendingSouth,
+                Self::AscendingNorth => Self::AscendingEast,
+                Self::AscendingSouth => Self::AscendingWest,
+                Self::AscendingWest => Self::AscendingNorth,
+            };
+        }
+        shape
+    }
+
+    /// Returns the shape after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        let shape = match self {
+            Self::NorthEast => Self::NorthWest,
+            Self::NorthWest => Self::NorthEast,
+            Self::SouthEast => Self::SouthWest,
+            Self::SouthWest => Self::SouthEast,
+            Self::AscendingEast => Self::AscendingWest,
+            Self::AscendingWest => Self::AscendingEast,
+            shape => *shape,
+        };
+        match axis {
+            Axis2::X => shape,
+            Axis2::Z => shape.rotated(Rotation::Clockwise180),
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Flower {
     Allium,
     AzureBluet,
@@ -151,7 +194,7 @@ pub enum Flower {
     WitherRose,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Grass {
     Fern,
     Grass,
@@ -161,14 +204,14 @@ pub enum Grass {
     TallGrassTop,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Seagrass {
     Seagrass,
     TallSeagrassBottom,
     TallSeagrassTop,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum AnvilDamage {
     Intact,
     SlightlyDamaged,
@@ -187,7 +230,7 @@ impl From<i16> for AnvilDamage {
 }
 
 /// Growth and attachment state for Melon and Pumpkin stems.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum StemState {
     /// Stem has not yet produced any fruit, or the fruit has been removed.
     Growing(Int0Through7),
@@ -195,14 +238,14 @@ pub enum StemState {
     Attached(Surface4),
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BambooLeaves {
     None,
     Small,
     Large,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Log {
     pub material: WoodMaterial,
     /// Logs with no alignment have bark (or stripped pattern) on all sides.
@@ -219,26 +262,66 @@ impl Log {
 pub type HoneyLevel = Int0Through5;
 pub type FireFace = DirectionFlags6;
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CommandBlockVariant {
     ChainedCommandBlock,
     CommandBlock,
     RepeatingCommandBlock,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct CommandBlock {
     pub variant: CommandBlockVariant,
     pub conditional: bool,
     pub facing: Surface6,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Jukebox {
     pub record: Option<Item>,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+impl Jukebox {
+    pub(crate) fn to_block_entity(&self, at: (i32, i32, i32)) -> BlockEntity {
+        let (x, y, z) = at;
+        BlockEntity::Jukebox {
+            common: CommonTags {
+                id: "minecraft:jukebox".into(),
+                x,
+                y,
+                z,
+                keep_packed: false,
+            },
+            record: self.record.clone(),
+        }
+    }
+}
+
+/// Book placed on a lectern, along with the page it is open at.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct LecternBook {
+    pub book: Item,
+    pub page: i32,
+}
+
+impl LecternBook {
+    /// Generates the block entity of a lectern holding `book`, if any.
+    pub(crate) fn to_block_entity(book: Option<&Self>, at: (i32, i32, i32)) -> BlockEntity {
+        let (x, y, z) = at;
+        BlockEntity::Lectern {
+            common: CommonTags {
+                id: "minecraft:lectern".into(),
+                x,
+                y,
+                z,
+                keep_packed: false,
+            },
+            book: book.map(|book| (book.book.clone(), book.page)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum OnOffState {
     On,
     Off,
@@ -250,10 +333,10 @@ pub enum OnOffState {
 /// Blocks with attributes can be created directly, or through the use of helper functions.
 /// Some of the more complex blocks have their own data structures, that are put inside
 /// the corresponding enum variant (often boxed.)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Block {
     None,
-    Unknown(Option<u16>),
+    Unknown(#[serde(with = "crate::block_state::unknown_id")] Option<u16>),
     Air,
     AncientDebris,
     Anvil {
@@ -522,7 +605,8 @@ pub enum Block {
     },
     Lectern {
         facing: Surface4,
-    }, // TODO add block entity (and possibly "has book" bool)
+        book: Option<Box<LecternBook>>,
+    },
     Lever(SurfaceRotation12, OnOffState),
     LilyPad,
     LodeStone,
@@ -932,6 +1016,25 @@ impl Block {
         Self::ConcretePowder { colour }
     }
 
+    /// Returns the colour of the block, if it has one.
+    pub fn colour(&self) -> Option<Colour> {
+        match self {
+            Self::Banner(banner) => Some(banner.colour),
+            Self::Bed(bed) => Some(bed.colour),
+            Self::Carpet { colour } => Some(*colour),
+            Self::Concrete { colour } => Some(*colour),
+            Self::ConcretePowder { colour } => Some(*colour),
+            Self::Glass { colour } => *colour,
+            Self::GlassPane { colour, .. } => *colour,
+            Self::GlazedTerracotta(gt) => Some(gt.colour),
+            Self::ShulkerBox(shulker_box) => shulker_box.colour,
+            Self::Sign(sign) => Some(sign.colour),
+            Self::Terracotta { colour } => *colour,
+            Self::Wool { colour } => Some(*colour),
+            _ => None,
+        }
+    }
+
     /// Returns a dark oak fence.
     pub const fn dark_oak_fence() -> Self {
         Self::Fence {
@@ -1197,6 +1300,11 @@ impl Block {
         matches!(self, Self::Hopper(_))
     }
 
+    /// Returns true if the block is a log.
+    pub fn is_log(&self) -> bool {
+        matches!(self, Self::Log(_))
+    }
+
     /// Returns true if the block is an observer.
     pub fn is_observer(&self) -> bool {
         matches!(self, Self::Observer { .. })
@@ -1412,6 +1520,33 @@ impl Block {
         matches!(self, Self::Wall { .. })
     }
 
+    /// Returns true if the block is waterlogged.
+    pub fn is_waterlogged(&self) -> bool {
+        match self {
+            Self::Campfire { waterlogged, .. }
+            | Self::Conduit { waterlogged }
+            | Self::Coral { waterlogged, .. }
+            | Self::CoralFan { waterlogged, .. }
+            | Self::EnderChest { waterlogged, .. }
+            | Self::Fence { waterlogged, .. }
+            | Self::GlassPane { waterlogged, .. }
+            | Self::IronBars { waterlogged }
+            | Self::Ladder { waterlogged, .. }
+            | Self::Scaffolding { waterlogged }
+            | Self::SeaPickle { waterlogged, .. }
+            | Self::SoulCampfire { waterlogged, .. }
+            | Self::Wall { waterlogged, .. } => *waterlogged,
+            Self::Chest(chest) | Self::TrappedChest(chest) => chest.waterlogged,
+            Self::Head(head) => head.waterlogged,
+            Self::Hopper(hopper) => hopper.waterlogged,
+            Self::Sign(sign) => sign.waterlogged,
+            Self::Slab(slab) => slab.waterlogged,
+            Self::Stairs(stair) => stair.waterlogged,
+            Self::Trapdoor(trapdoor) => trapdoor.waterlogged,
+            _ => false,
+        }
+    }
+
     /// Returns a jack o'lantern facing in the given direction.
     pub fn jack_o_lantern(facing: Direction) -> Self {
         Self::JackOLantern {
@@ -1529,6 +1664,30 @@ impl Block {
         Self::Lever(SurfaceRotation12::try_from(facing).unwrap(), OnOffState::On)
     }
 
+    /// Returns the material the block is made of, if it has one.
+    pub fn material(&self) -> Option<Material> {
+        match self {
+            Self::Button(mat, _) => Some((*mat).into()),
+            Self::Coral { material, .. } => Some((*material).into()),
+            Self::CoralBlock { material, .. } => Some((*material).into()),
+            Self::CoralFan { material, .. } => Some((*material).into()),
+            Self::Door(door) => Some(door.material.into()),
+            Self::Fence { material, .. } => Some((*material).into()),
+            Self::FenceGate { material, .. } => Some((*material).into()),
+            Self::Leaves { material, .. } => Some((*material).into()),
+            Self::Log(log) => Some(log.material.into()),
+            Self::Planks { material, .. } => Some((*material).into()),
+            Self::PressurePlate { material, .. } => Some((*material).into()),
+            Self::Sapling { material, .. } => Some((*material).into()),
+            Self::Sign(sign) => Some(sign.material.into()),
+            Self::Slab(slab) => Some(slab.material.into()),
+            Self::Stairs(stair) => Some(stair.material.into()),
+            Self::Trapdoor(trapdoor) => Some(trapdoor.material.into()),
+            Self::Wall { material, .. } => Some((*material).into()),
+            _ => None,
+        }
+    }
+
     /// Returns a nether brick fence.
     pub const fn nether_brick_fence() -> Self {
         Self::Fence {
@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -6,7 +7,7 @@ use crate::colour::*;
 use crate::positioning::*;
 
 /// Banner "block".
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Banner {
     /// Base colour of the banner.
     pub colour: Colour,
@@ -63,13 +64,13 @@ impl TryFrom<Block> for Banner {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ColouredPattern {
     pub colour: Colour,
     pub pattern: BannerPattern,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum BannerPattern {
     BaseColor,               // b (base)
     Base,                    // bs (bottom stripe)
@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -5,7 +6,7 @@ use crate::block_entity::{BlockEntity, CommonTags};
 use crate::status_effect::StatusEffect;
 
 /// Beacon "block".
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Beacon {
     pub lock: Option<String>,
     pub levels: i32, // TODO change type to integer with valid range
@@ -1,10 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::colour::Colour;
 use crate::positioning::{Direction, Surface4};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Bed {
     pub colour: Colour,
     pub facing: Surface4,
@@ -32,7 +33,7 @@ impl TryFrom<Block> for Bed {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum BedEnd {
     Foot,
     Head,
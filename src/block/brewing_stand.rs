@@ -1,10 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::inventory::Inventory;
 
 /// Represents the state of a brewing stand block.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct BrewingStand {
     pub custom_name: Option<String>,
     pub lock: Option<String>,
@@ -1,11 +1,12 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::block_entity::{BlockEntity, ChestTags, CommonTags};
 use crate::inventory::Inventory;
-use crate::positioning::{Direction, Surface4};
+use crate::positioning::{Direction, Rotation, Surface4};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Chest {
     pub facing: Surface4,
     pub variant: Option<ChestVariant>,
@@ -20,6 +21,17 @@ impl Chest {
         facing == self.facing.into()
     }
 
+    /// The side at which the other half of a double chest is, if this chest
+    /// were the given half of it. A "left" chest has its other half to the
+    /// clockwise side of its facing.
+    pub(crate) fn other_half_side(&self, variant: &ChestVariant) -> Option<Surface4> {
+        match variant {
+            ChestVariant::Left => Some(self.facing.rotated(Rotation::Clockwise90)),
+            ChestVariant::Right => Some(self.facing.rotated(Rotation::Counterclockwise90)),
+            ChestVariant::Single => None,
+        }
+    }
+
     pub(crate) fn to_block_entity(&self, at: (i32, i32, i32)) -> BlockEntity {
         let (x, y, z) = at;
         BlockEntity::Chest {
@@ -32,7 +44,7 @@ impl Chest {
                     keep_packed: false,
                 },
                 custom_name: self.custom_name.clone(),
-                lock: self.custom_name.clone(),
+                lock: self.lock.clone(),
                 items: self.items.clone(),
                 loot_table: None,      // TODO
                 loot_table_seed: None, // TODO
@@ -53,7 +65,7 @@ impl TryFrom<Block> for Chest {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ChestVariant {
     Left,
     Right,
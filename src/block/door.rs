@@ -1,10 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::material::{DoorMaterial, Material};
 use crate::positioning::{Direction, Surface4};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Door {
     pub material: DoorMaterial,
     pub facing: Surface4,
@@ -66,7 +67,7 @@ impl TryFrom<Block> for Door {
 }
 
 /// Doors are two blocks high. Which block is this?
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum DoorHalf {
     /// Bottom block of the door
     Lower,
@@ -77,7 +78,7 @@ pub enum DoorHalf {
 /// For doors, what way they are hinged. Left/Right relative to the direction
 /// the door is Facing. (E.g. if Facing North, Left means on the West side,
 /// and Right means on the East side.)
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Hinge {
     Left,
     Right,
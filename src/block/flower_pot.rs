@@ -1,8 +1,9 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct FlowerPot {
     pub(crate) plant: Option<PottedPlant>,
 }
@@ -48,7 +49,7 @@ impl From<FlowerPot> for Block {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum PottedPlant {
     AcaciaSapling,
     Allium,
@@ -1,10 +1,12 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
+use crate::block_entity::{BlockEntity, CommonTags, FurnaceTags};
 use crate::inventory::Inventory;
 use crate::positioning::{Direction, Surface4};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Furnace {
     pub facing: Surface4,
     pub lit: bool,
@@ -20,6 +22,27 @@ impl Furnace {
     pub fn has_facing_of(&self, facing: Direction) -> bool {
         facing == self.facing.into()
     }
+
+    pub(crate) fn to_block_entity(&self, at: (i32, i32, i32)) -> BlockEntity {
+        let (x, y, z) = at;
+        BlockEntity::Furnace {
+            tags: FurnaceTags {
+                common: CommonTags {
+                    id: "minecraft:furnace".into(),
+                    x,
+                    y,
+                    z,
+                    keep_packed: false,
+                },
+                custom_name: self.custom_name.clone(),
+                lock: self.lock.clone(),
+                items: self.items.clone(),
+                burn_time: self.burn_time,
+                cook_time: self.cook_time,
+                cook_time_total: self.cook_time_total,
+            },
+        }
+    }
 }
 
 impl TryFrom<Block> for Furnace {
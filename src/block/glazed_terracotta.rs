@@ -1,10 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::colour::Colour;
 use crate::positioning::{Direction, Surface4};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct GlazedTerracotta {
     pub(crate) colour: Colour,
     pub(crate) facing: Surface4,
@@ -1,9 +1,10 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::positioning::{Direction, WallOrRotatedOnFloor};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Head {
     pub(crate) variant: HeadVariant,
     pub(crate) placement: WallOrRotatedOnFloor,
@@ -28,7 +29,7 @@ impl Head {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum HeadVariant {
     CreeperHead,
     DragonHead,
@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -5,7 +6,7 @@ use crate::block_entity::{BlockEntity, ChestTags, CommonTags};
 use crate::inventory::Inventory;
 use crate::positioning::{Direction, Surface5};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Hopper {
     pub facing: Surface5,
     pub waterlogged: bool,
@@ -31,7 +32,7 @@ impl Hopper {
                     keep_packed: false,
                 },
                 custom_name: self.custom_name.clone(),
-                lock: self.custom_name.clone(),
+                lock: self.lock.clone(),
                 items: self.items.clone(),
                 loot_table: None,      // TODO
                 loot_table_seed: None, // TODO
@@ -1,9 +1,10 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::block_entity::{BlockEntity, CommonTags};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Noteblock {
     pub pitch: Pitch,
 }
@@ -40,7 +41,7 @@ impl TryFrom<Block> for Noteblock {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Pitch {
     Fs0 = 0,
     G0,
@@ -134,7 +135,7 @@ impl Pitch {
 
 // TODO put somewhere suitable
 // TODO utility functions
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Instrument {
     Banjo,
     Basedrum,
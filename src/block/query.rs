@@ -0,0 +1,152 @@
+use crate::block::Block;
+use crate::colour::Colour;
+use crate::material::Material;
+use crate::positioning::Direction;
+
+/// Decides whether a block is of interest, e.g. for replacing or finding blocks.
+///
+/// Implemented for [`BlockQuery`], and for closures taking a block and
+/// returning whether it matches.
+pub trait BlockPredicate {
+    fn matches(&self, block: &Block) -> bool;
+}
+
+impl<F> BlockPredicate for F
+where
+    F: Fn(&Block) -> bool,
+{
+    fn matches(&self, block: &Block) -> bool {
+        self(block)
+    }
+}
+
+impl BlockPredicate for BlockQuery {
+    fn matches(&self, block: &Block) -> bool {
+        BlockQuery::matches(self, block)
+    }
+}
+
+impl BlockPredicate for &BlockQuery {
+    fn matches(&self, block: &Block) -> bool {
+        BlockQuery::matches(self, block)
+    }
+}
+
+/// A composable description of which blocks to match.
+///
+/// Queries are built from the block predicates, such as
+/// [`Block::has_material_of()`] and [`Block::is_solid()`], and combined with
+/// [`and()`](BlockQuery::and), [`or()`](BlockQuery::or) and `!`.
+///
+/// # Examples
+/// ```
+/// use mcprogedit::block::{Block, BlockQuery};
+/// use mcprogedit::material::Material;
+/// use mcprogedit::positioning::Axis3;
+///
+/// // Any oak-material block that is not a log
+/// let query = BlockQuery::Material(Material::Oak).and(!BlockQuery::Is(Block::is_log));
+///
+/// assert!(query.matches(&Block::oak_planks()));
+/// assert!(!query.matches(&Block::oak_log(Axis3::Y)));
+/// assert!(!query.matches(&Block::spruce_planks()));
+/// ```
+#[derive(Clone, Debug)]
+pub enum BlockQuery {
+    /// Matches every block.
+    Any,
+    /// Matches blocks equal to the given block.
+    Exactly(Block),
+    /// Matches blocks made of the given material.
+    Material(Material),
+    /// Matches blocks with the given colour.
+    Colour(Colour),
+    /// Matches blocks facing in the given direction.
+    Facing(Direction),
+    /// Matches blocks for which the given function returns true,
+    /// e.g. `BlockQuery::Is(Block::is_stairs)`.
+    Is(fn(&Block) -> bool),
+    /// Matches blocks not matched by the inner query.
+    Not(Box<BlockQuery>),
+    /// Matches blocks matched by all of the inner queries.
+    All(Vec<BlockQuery>),
+    /// Matches blocks matched by at least one of the inner queries.
+    AnyOf(Vec<BlockQuery>),
+}
+
+impl BlockQuery {
+    /// Returns true if `block` is matched by the query.
+    pub fn matches(&self, block: &Block) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exactly(other) => block == other,
+            Self::Material(material) => block.has_material_of(*material),
+            Self::Colour(colour) => block.has_colour_of(*colour),
+            Self::Facing(direction) => block.has_facing_of(*direction),
+            Self::Is(predicate) => predicate(block),
+            Self::Not(query) => !query.matches(block),
+            Self::All(queries) => queries.iter().all(|query| query.matches(block)),
+            Self::AnyOf(queries) => queries.iter().any(|query| query.matches(block)),
+        }
+    }
+
+    /// Returns a query matching blocks matched by both queries.
+    pub fn and(self, other: Self) -> Self {
+        match self {
+            Self::All(mut queries) => {
+                queries.push(other);
+                Self::All(queries)
+            }
+            query => Self::All(vec![query, other]),
+        }
+    }
+
+    /// Returns a query matching blocks matched by either query.
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Self::AnyOf(mut queries) => {
+                queries.push(other);
+                Self::AnyOf(queries)
+            }
+            query => Self::AnyOf(vec![query, other]),
+        }
+    }
+}
+
+impl std::ops::Not for BlockQuery {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        match self {
+            Self::Not(query) => *query,
+            query => Self::Not(Box::new(query)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::positioning::Axis3;
+
+    #[test]
+    fn combined_queries() {
+        let stairs_or_red = BlockQuery::Is(Block::is_stairs).or(BlockQuery::Colour(Colour::Red));
+        assert!(stairs_or_red.matches(&Block::concrete_with_colour(Colour::Red)));
+        assert!(!stairs_or_red.matches(&Block::concrete_with_colour(Colour::Blue)));
+
+        let north_gates = BlockQuery::Facing(Direction::North)
+            .and(BlockQuery::Material(Material::Birch))
+            .and(!BlockQuery::Exactly(Block::birch_fence_gate_opened(
+                Direction::North,
+            )));
+        assert!(north_gates.matches(&Block::birch_fence_gate(Direction::North)));
+        assert!(!north_gates.matches(&Block::birch_fence_gate_opened(Direction::North)));
+        assert!(!north_gates.matches(&Block::birch_fence_gate(Direction::South)));
+        assert!(!north_gates.matches(&Block::oak_fence_gate(Direction::North)));
+
+        assert!(BlockQuery::Any.matches(&Block::Air));
+        assert!((!!BlockQuery::Is(Block::is_log)).matches(&Block::oak_log(Axis3::X)));
+        assert!((|block: &Block| block == &Block::Air).matches(&Block::Air));
+    }
+}
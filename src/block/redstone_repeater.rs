@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -34,7 +35,7 @@ use crate::positioning::{Direction, DirectionError, Surface4};
 /// // Turn the repeater into a proper block:
 /// let repeater_block: Block = repeater.into();
 /// ```
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct RedstoneRepeater {
     pub(crate) facing: Surface4,
     pub(crate) delay: Int1Through4,
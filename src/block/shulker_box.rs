@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -6,7 +7,7 @@ use crate::colour::Colour;
 use crate::inventory::Inventory;
 use crate::positioning::{Direction, Surface6};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct ShulkerBox {
     pub colour: Option<Colour>,
     pub facing: Surface6,
@@ -36,7 +37,7 @@ impl ShulkerBox {
                     keep_packed: false,
                 },
                 custom_name: self.custom_name.clone(),
-                lock: self.custom_name.clone(),
+                lock: self.lock.clone(),
                 items: self.items.clone(),
                 loot_table: None,      // TODO
                 loot_table_seed: None, // TODO
@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -5,7 +6,7 @@ use crate::colour::Colour;
 use crate::material::{Material, WoodMaterial};
 use crate::positioning::{Direction, WallOrRotatedOnFloor};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Sign {
     pub material: WoodMaterial,
     pub placement: WallOrRotatedOnFloor,
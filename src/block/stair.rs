@@ -1,3 +1,4 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
@@ -7,7 +8,7 @@ use crate::positioning::{Direction, Edge8};
 /// Stair shape is not configurable, as it depend on neighbouring stairs.
 /// Stair shape is either automatically calculated on save, or the block is
 /// flagged for update so that it will be automatically corrected in-game.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Stair {
     pub material: StairMaterial,
     pub position: Edge8,
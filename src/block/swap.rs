@@ -0,0 +1,190 @@
+use std::convert::TryFrom;
+
+use crate::block::*;
+
+/// Sets `material` to `to` if it is `from`, and `to` exists for its family of blocks.
+fn swap_material<M>(material: &mut M, from: Material, to: Material)
+where
+    M: Copy + Into<Material> + TryFrom<Material>,
+{
+    if (*material).into() == from {
+        if let Ok(swapped) = M::try_from(to) {
+            *material = swapped;
+        }
+    }
+}
+
+fn swap_colour(colour: &mut Colour, from: Colour, to: Colour) {
+    if *colour == from {
+        *colour = to;
+    }
+}
+
+fn swap_optional_colour(colour: &mut Option<Colour>, from: Colour, to: Colour) {
+    if let Some(colour) = colour {
+        swap_colour(colour, from, to);
+    }
+}
+
+impl Block {
+    /// Returns the block with its material changed from `from` to `to`.
+    ///
+    /// All other properties, such as orientation, are kept. Blocks not made
+    /// of `from`, and blocks for which there is no `to` variant (e.g. stone
+    /// doors) are returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::material::Material;
+    /// use mcprogedit::positioning::Direction;
+    ///
+    /// let gate = Block::oak_fence_gate_opened(Direction::West);
+    /// assert_eq!(
+    ///     gate.material_swapped(Material::Oak, Material::Spruce),
+    ///     Block::spruce_fence_gate_opened(Direction::West),
+    /// );
+    /// ```
+    pub fn material_swapped(&self, from: Material, to: Material) -> Self {
+        let mut block = self.clone();
+        match &mut block {
+            Self::Button(material, _) => swap_material(material, from, to),
+            Self::Coral { material, .. }
+            | Self::CoralBlock { material, .. }
+            | Self::CoralFan { material, .. } => swap_material(material, from, to),
+            Self::Door(door) => swap_material(&mut door.material, from, to),
+            Self::Fence { material, .. } => swap_material(material, from, to),
+            Self::FenceGate { material, .. } | Self::Planks { material } => {
+                swap_material(material, from, to)
+            }
+            Self::Leaves { material, .. } => swap_material(material, from, to),
+            Self::Log(log) => swap_material(&mut log.material, from, to),
+            Self::PressurePlate { material } => swap_material(material, from, to),
+            Self::Sapling { material, .. } => swap_material(material, from, to),
+            Self::Sign(sign) => swap_material(&mut sign.material, from, to),
+            Self::Slab(slab) => swap_material(&mut slab.material, from, to),
+            Self::Stairs(stair) => swap_material(&mut stair.material, from, to),
+            Self::Trapdoor(trapdoor) => swap_material(&mut trapdoor.material, from, to),
+            Self::Wall { material, .. } => swap_material(material, from, to),
+            _ => (),
+        }
+        block
+    }
+
+    /// Returns the block with its colour changed from `from` to `to`.
+    ///
+    /// All other properties, such as orientation, are kept. For banners, the
+    /// colours of the patterns are swapped as well as the base colour.
+    /// Uncoloured blocks, e.g. plain glass, are returned unchanged.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::colour::Colour;
+    ///
+    /// let wool = Block::Wool { colour: Colour::Red };
+    /// assert_eq!(
+    ///     wool.colour_swapped(Colour::Red, Colour::Blue),
+    ///     Block::Wool { colour: Colour::Blue },
+    /// );
+    /// ```
+    pub fn colour_swapped(&self, from: Colour, to: Colour) -> Self {
+        let mut block = self.clone();
+        match &mut block {
+            Self::Banner(banner) => {
+                swap_colour(&mut banner.colour, from, to);
+                for pattern in &mut banner.patterns {
+                    swap_colour(&mut pattern.colour, from, to);
+                }
+            }
+            Self::Bed(bed) => swap_colour(&mut bed.colour, from, to),
+            Self::Carpet { colour }
+            | Self::Concrete { colour }
+            | Self::ConcretePowder { colour }
+            | Self::Wool { colour } => swap_colour(colour, from, to),
+            Self::Glass { colour }
+            | Self::GlassPane { colour, .. }
+            | Self::Terracotta { colour } => swap_optional_colour(colour, from, to),
+            Self::GlazedTerracotta(terracotta) => swap_colour(&mut terracotta.colour, from, to),
+            Self::ShulkerBox(shulker_box) => {
+                swap_optional_colour(&mut shulker_box.colour, from, to)
+            }
+            Self::Sign(sign) => swap_colour(&mut sign.colour, from, to),
+            _ => (),
+        }
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn material_swap_keeps_state() {
+        let stair = Block::Stairs(Stair {
+            material: StairMaterial::Oak,
+            position: Edge8::DownWest,
+            waterlogged: true,
+        });
+        assert_eq!(
+            stair.material_swapped(Material::Oak, Material::DarkOak),
+            Block::Stairs(Stair {
+                material: StairMaterial::DarkOak,
+                position: Edge8::DownWest,
+                waterlogged: true,
+            })
+        );
+
+        let log = Block::oak_log(Axis3::X);
+        assert_eq!(
+            log.material_swapped(Material::Oak, Material::Birch),
+            Block::birch_log(Axis3::X)
+        );
+        assert_eq!(log.material_swapped(Material::Spruce, Material::Birch), log);
+
+        // There are no stone doors, so the door is left as is
+        let door = Block::Door(Door {
+            material: DoorMaterial::Oak,
+            facing: Surface4::North,
+            half: DoorHalf::Upper,
+            hinged_at: Hinge::Left,
+            open: false,
+        });
+        assert_eq!(door.material_swapped(Material::Oak, Material::Stone), door);
+    }
+
+    #[test]
+    fn colour_swap_includes_banner_patterns() {
+        let banner = Block::Banner(Box::new(Banner {
+            colour: Colour::Red,
+            custom_name: None,
+            placement: WallOrRotatedOnFloor::Wall(Surface4::East),
+            patterns: vec![
+                ColouredPattern {
+                    colour: Colour::Red,
+                    pattern: BannerPattern::Cross,
+                },
+                ColouredPattern {
+                    colour: Colour::White,
+                    pattern: BannerPattern::Saltire,
+                },
+            ],
+        }));
+        match banner.colour_swapped(Colour::Red, Colour::Blue) {
+            Block::Banner(banner) => {
+                assert_eq!(banner.colour, Colour::Blue);
+                assert_eq!(banner.patterns[0].colour, Colour::Blue);
+                assert_eq!(banner.patterns[1].colour, Colour::White);
+                assert_eq!(banner.placement, WallOrRotatedOnFloor::Wall(Surface4::East));
+            }
+            block => panic!("Unexpected block {:?}", block),
+        }
+
+        let plain_glass = Block::Glass { colour: None };
+        assert_eq!(
+            plain_glass.colour_swapped(Colour::Red, Colour::Blue),
+            plain_glass
+        );
+    }
+}
@@ -0,0 +1,345 @@
+use crate::block::*;
+
+/// A change of orientation, applied to every orientation carrying part of a block.
+#[derive(Clone, Copy)]
+enum Transform {
+    Rotate(Rotation),
+    Mirror(Axis2),
+}
+
+trait Transformable: Sized {
+    fn rotated(&self, rotation: Rotation) -> Self;
+    fn mirrored(&self, axis: Axis2) -> Self;
+
+    fn transform(&mut self, transform: Transform) {
+        *self = match transform {
+            Transform::Rotate(rotation) => self.rotated(rotation),
+            Transform::Mirror(axis) => self.mirrored(axis),
+        }
+    }
+}
+
+macro_rules! transformable {
+    ($($orientation:ty),*) => {
+        $(
+            impl Transformable for $orientation {
+                fn rotated(&self, rotation: Rotation) -> Self {
+                    <$orientation>::rotated(self, rotation)
+                }
+
+                fn mirrored(&self, axis: Axis2) -> Self {
+                    <$orientation>::mirrored(self, axis)
+                }
+            }
+        )*
+    };
+}
+
+transformable!(
+    BellPosition,
+    DirectionFlags6,
+    Edge8,
+    JigsawBlockOrientation,
+    RailShape,
+    Surface4,
+    Surface5,
+    Surface6,
+    SurfaceRotation12,
+    WallOrRotatedOnFloor
+);
+
+impl Transformable for Axis2 {
+    fn rotated(&self, rotation: Rotation) -> Self {
+        Axis2::rotated(self, rotation)
+    }
+
+    fn mirrored(&self, _axis: Axis2) -> Self {
+        *self
+    }
+}
+
+impl Transformable for Axis3 {
+    fn rotated(&self, rotation: Rotation) -> Self {
+        Axis3::rotated(self, rotation)
+    }
+
+    fn mirrored(&self, _axis: Axis2) -> Self {
+        *self
+    }
+}
+
+impl<T: Transformable> Transformable for Option<T> {
+    fn rotated(&self, rotation: Rotation) -> Self {
+        self.as_ref()
+            .map(|orientation| orientation.rotated(rotation))
+    }
+
+    fn mirrored(&self, axis: Axis2) -> Self {
+        self.as_ref().map(|orientation| orientation.mirrored(axis))
+    }
+}
+
+impl Block {
+    /// Returns the block as it would be after rotating its surroundings
+    /// around the vertical axis.
+    ///
+    /// Facing, attachment, alignment and connections are all rotated.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::positioning::{Direction, Rotation};
+    ///
+    /// let gate = Block::oak_fence_gate(Direction::North);
+    /// assert_eq!(gate.rotated(Rotation::Clockwise90), Block::oak_fence_gate(Direction::East));
+    /// ```
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        self.transformed(Transform::Rotate(rotation))
+    }
+
+    /// Returns the block as it would be after mirroring its surroundings
+    /// along the given axis.
+    ///
+    /// Mirroring along the X axis swaps east and west, mirroring along the Z
+    /// axis swaps north and south. Left and right are swapped for door hinges
+    /// and the halves of double chests.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        self.transformed(Transform::Mirror(axis))
+    }
+
+    fn transformed(&self, transform: Transform) -> Self {
+        let mut block = self.clone();
+        match &mut block {
+            Self::Anvil { facing, .. }
+            | Self::Beehive { facing, .. }
+            | Self::BeeNest { facing, .. }
+            | Self::Campfire { facing, .. }
+            | Self::CarvedPumpkin { facing }
+            | Self::CocoaBeans { facing, .. }
+            | Self::EndPortalFrame { facing, .. }
+            | Self::EnderChest { facing, .. }
+            | Self::FenceGate { facing, .. }
+            | Self::JackOLantern { facing }
+            | Self::Ladder { facing, .. }
+            | Self::Lectern { facing, .. }
+            | Self::Loom { facing }
+            | Self::Pumpkin { facing }
+            | Self::RedstoneComparator { facing }
+            | Self::RedstoneSubtractor { facing }
+            | Self::SoulCampfire { facing, .. }
+            | Self::StoneCutter { facing }
+            | Self::TripwireHook { facing } => facing.transform(transform),
+            Self::MelonStem {
+                state: StemState::Attached(facing),
+            }
+            | Self::PumpkinStem {
+                state: StemState::Attached(facing),
+            } => facing.transform(transform),
+            Self::Barrel { facing }
+            | Self::EndRod { facing }
+            | Self::Observer { facing }
+            | Self::Piston { facing, .. }
+            | Self::PistonHead { facing }
+            | Self::StickyPiston { facing, .. }
+            | Self::StickyPistonHead { facing } => facing.transform(transform),
+            Self::Button(_, facing) => facing.transform(transform),
+            Self::CoralFan { facing, .. } => facing.transform(transform),
+            Self::RedstoneTorch { attached }
+            | Self::SoulTorch { attached }
+            | Self::Torch { attached } => attached.transform(transform),
+            Self::Basalt { alignment }
+            | Self::BoneBlock { alignment }
+            | Self::HayBale { alignment }
+            | Self::PolishedBasalt { alignment }
+            | Self::PurpurPillar { alignment }
+            | Self::QuartzPillar { alignment } => alignment.transform(transform),
+            Self::Log(log) => log.alignment.transform(transform),
+            Self::NetherPortal { alignment } => alignment.transform(transform),
+            Self::Bell { position } => position.transform(transform),
+            Self::BrownMushroomBlock {
+                cap_directions: directions,
+            }
+            | Self::BrownMushroomStem {
+                stem_directions: directions,
+            }
+            | Self::RedMushroomBlock {
+                cap_directions: directions,
+            }
+            | Self::RedMushroomStem {
+                stem_directions: directions,
+            }
+            | Self::SoulFire {
+                burning_faces: directions,
+                ..
+            } => directions.transform(transform),
+            Self::Vines(vines) => vines.anchored_at.transform(transform),
+            Self::GrindStone(placement) | Self::Lever(placement, _) => {
+                placement.transform(transform)
+            }
+            Self::JigsawBlock { orientation } => orientation.transform(transform),
+            Self::Rail { shape, .. } => shape.transform(transform),
+            Self::Banner(banner) => banner.placement.transform(transform),
+            Self::Sign(sign) => sign.placement.transform(transform),
+            Self::Head(head) => head.placement.transform(transform),
+            Self::Bed(bed) => bed.facing.transform(transform),
+            Self::GlazedTerracotta(terracotta) => terracotta.facing.transform(transform),
+            Self::RedstoneRepeater(repeater) => repeater.facing.transform(transform),
+            Self::BlastFurnace(furnace) | Self::Furnace(furnace) | Self::Smoker(furnace) => {
+                furnace.facing.transform(transform)
+            }
+            Self::CommandBlock(command_block) => command_block.facing.transform(transform),
+            Self::Dispenser(dispenser) => dispenser.facing.transform(transform),
+            Self::Dropper(dropper) => dropper.facing.transform(transform),
+            Self::Hopper(hopper) => hopper.facing.transform(transform),
+            Self::ShulkerBox(shulker_box) => shulker_box.facing.transform(transform),
+            Self::Stairs(stair) => stair.position.transform(transform),
+            Self::Trapdoor(trapdoor) => trapdoor.hinge_at.transform(transform),
+            Self::Door(door) => {
+                door.facing.transform(transform);
+                if let Transform::Mirror(_) = transform {
+                    door.hinged_at = match door.hinged_at {
+                        Hinge::Left => Hinge::Right,
+                        Hinge::Right => Hinge::Left,
+                    };
+                }
+            }
+            Self::Chest(chest) | Self::TrappedChest(chest) => {
+                chest.facing.transform(transform);
+                if let Transform::Mirror(_) = transform {
+                    chest.variant = match chest.variant {
+                        Some(ChestVariant::Left) => Some(ChestVariant::Right),
+                        Some(ChestVariant::Right) => Some(ChestVariant::Left),
+                        Some(ChestVariant::Single) => Some(ChestVariant::Single),
+                        None => None,
+                    };
+                }
+            }
+            _ => (),
+        }
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ROTATIONS: [Rotation; 4] = [
+        Rotation::None,
+        Rotation::Clockwise90,
+        Rotation::Clockwise180,
+        Rotation::Counterclockwise90,
+    ];
+
+    #[test]
+    fn four_quarter_turns_is_identity() {
+        let blocks = [
+            Block::Stairs(Stair {
+                material: StairMaterial::Oak,
+                position: Edge8::UpNorth,
+                waterlogged: false,
+            }),
+            Block::Rail {
+                variant: RailType::Normal,
+                shape: RailShape::AscendingSouth,
+            },
+            Block::Bell {
+                position: BellPosition::DoubleSideWest,
+            },
+            Block::JigsawBlock {
+                orientation: JigsawBlockOrientation::UpEast,
+            },
+            Block::Vines(Vines {
+                anchored_at: DirectionFlags6 {
+                    east: true,
+                    down: false,
+                    north: true,
+                    south: false,
+                    up: true,
+                    west: false,
+                },
+            }),
+        ];
+        for block in &blocks {
+            for rotation in &ROTATIONS {
+                let mut rotated = block.clone();
+                for _ in 0..4 {
+                    rotated = rotated.rotated(*rotation);
+                }
+                assert_eq!(&rotated, block);
+                assert_eq!(rotated.mirrored(Axis2::X).mirrored(Axis2::X), *block,);
+            }
+        }
+    }
+
+    #[test]
+    fn directional_blocks_follow_rotation() {
+        let sign = Block::Sign(Box::new(Sign {
+            material: WoodMaterial::Oak,
+            placement: WallOrRotatedOnFloor::Floor(Direction16::NorthNorthEast),
+            waterlogged: false,
+            colour: Colour::Black,
+            text1: String::new(),
+            text2: String::new(),
+            text3: String::new(),
+            text4: String::new(),
+        }));
+        match sign.rotated(Rotation::Clockwise90) {
+            Block::Sign(sign) => assert_eq!(
+                sign.placement,
+                WallOrRotatedOnFloor::Floor(Direction16::EastSouthEast)
+            ),
+            block => panic!("Unexpected block {:?}", block),
+        }
+
+        let rail = Block::Rail {
+            variant: RailType::Powered,
+            shape: RailShape::NorthEast,
+        };
+        assert_eq!(
+            rail.rotated(Rotation::Counterclockwise90),
+            Block::Rail {
+                variant: RailType::Powered,
+                shape: RailShape::NorthWest,
+            }
+        );
+        assert_eq!(
+            rail.mirrored(Axis2::Z),
+            Block::Rail {
+                variant: RailType::Powered,
+                shape: RailShape::SouthEast,
+            }
+        );
+
+        assert_eq!(
+            Block::QuartzPillar {
+                alignment: Axis3::X
+            }
+            .rotated(Rotation::Clockwise90),
+            Block::QuartzPillar {
+                alignment: Axis3::Z
+            }
+        );
+    }
+
+    #[test]
+    fn mirroring_swaps_door_hinges() {
+        let door = Block::Door(Door {
+            material: DoorMaterial::Oak,
+            facing: Surface4::East,
+            half: DoorHalf::Lower,
+            hinged_at: Hinge::Left,
+            open: false,
+        });
+        assert_eq!(
+            door.mirrored(Axis2::X),
+            Block::Door(Door {
+                material: DoorMaterial::Oak,
+                facing: Surface4::West,
+                half: DoorHalf::Lower,
+                hinged_at: Hinge::Right,
+                open: false,
+            })
+        );
+    }
+}
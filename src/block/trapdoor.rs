@@ -1,10 +1,11 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::material::{DoorMaterial, Material};
 use crate::positioning::{Direction, Edge8};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Trapdoor {
     pub(crate) material: DoorMaterial,
     pub(crate) hinge_at: Edge8,
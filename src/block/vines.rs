@@ -1,9 +1,10 @@
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 use crate::block::Block;
 use crate::positioning::{Direction, DirectionFlags6};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Vines {
     // NB should attach to all neighbouring blocks by default
     pub(crate) anchored_at: DirectionFlags6,
@@ -1,5 +1,6 @@
 use crate::block::Block;
-use crate::height_map::HeightMap;
+use crate::height_map::{HeightMap, HeightMapKind};
+use crate::positioning::{Axis2, Rotation};
 
 #[derive(Clone, Debug)]
 pub struct BlockCuboid {
@@ -58,6 +59,66 @@ impl BlockCuboid {
         }
     }
 
+    /// Iterates over the coordinates and blocks of the block cuboid.
+    ///
+    /// Blocks are visited in storage order; by x, then z, then y coordinate.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize, usize), &Block)> + '_ {
+        let (y_dim, z_dim) = (self.y_dim, self.z_dim);
+        self.blocks
+            .iter()
+            .enumerate()
+            .map(move |(index, block)| (coordinates(index, y_dim, z_dim), block))
+    }
+
+    /// Iterates over the coordinates and mutable blocks of the block cuboid,
+    /// in the same order as [`iter()`](Self::iter).
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = ((usize, usize, usize), &mut Block)> + '_ {
+        let (y_dim, z_dim) = (self.y_dim, self.z_dim);
+        self.blocks
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, block)| (coordinates(index, y_dim, z_dim), block))
+    }
+
+    /// Iterates over the coordinates and blocks of the block cuboid, in parallel.
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(
+        &self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = ((usize, usize, usize), &Block)> + '_
+    {
+        use rayon::prelude::*;
+
+        let (y_dim, z_dim) = (self.y_dim, self.z_dim);
+        self.blocks
+            .par_iter()
+            .enumerate()
+            .map(move |(index, block)| (coordinates(index, y_dim, z_dim), block))
+    }
+
+    /// Iterates over the vertical columns of blocks, by x, then z coordinate.
+    ///
+    /// Each column is given by its (x, z) coordinates and its blocks,
+    /// from the bottom up.
+    pub fn columns(&self) -> impl Iterator<Item = ((usize, usize), &[Block])> + '_ {
+        let z_dim = self.z_dim;
+        self.blocks
+            .chunks(usize::max(self.y_dim, 1))
+            .enumerate()
+            .map(move |(index, column)| ((index / z_dim, index % z_dim), column))
+    }
+
+    /// Iterates over the (x, z) coordinates and blocks of the horizontal
+    /// layer at height `y`, by x, then z coordinate.
+    pub fn layer(&self, y: usize) -> impl Iterator<Item = ((usize, usize), &Block)> + '_ {
+        let z_dim = self.z_dim;
+        let layer = if y < self.y_dim { &self.blocks[y..] } else { &[] };
+        layer
+            .iter()
+            .step_by(usize::max(self.y_dim, 1))
+            .enumerate()
+            .map(move |(index, block)| ((index / z_dim, index % z_dim), block))
+    }
+
     /// Paste the contents of a different BlockCuboid into this BlockCuboid.
     ///
     /// The corner of `other` with the lowest numbered coordinates, is aligned at block
@@ -67,6 +128,16 @@ impl BlockCuboid {
     /// Empty blocks ([`Block::None`](crate::block::Block::None)) are not copied over,
     /// allowing for pasting other selection shapes than rectangular cuboids.
     pub fn paste(&mut self, offset: (i64, i64, i64), other: &Self) {
+        self.paste_observed(offset, other, |_, _, _| ());
+    }
+
+    /// Paste the contents of a different BlockCuboid into this BlockCuboid,
+    /// calling `observer` with the coordinates, old block and new block of
+    /// every block that is overwritten.
+    pub fn paste_observed<F>(&mut self, offset: (i64, i64, i64), other: &Self, mut observer: F)
+    where
+        F: FnMut((usize, usize, usize), &Block, &Block),
+    {
         // Calculate the spans relative to self, for where blocks are to be pasted in.
         let min = (
             i64::max(0, offset.0) as usize, // x
@@ -87,6 +158,9 @@ impl BlockCuboid {
                     let from_z = (to_z as i64 - offset.2) as usize;
                     if let Some(block) = other.block_at((from_x, from_y, from_z)) {
                         if *block != Block::None {
+                            if let Some(old_block) = self.block_at((to_x, to_y, to_z)) {
+                                observer((to_x, to_y, to_z), old_block, block);
+                            }
                             self.insert((to_x, to_y, to_z), block.clone());
                         }
                     } else {
@@ -141,33 +215,21 @@ impl BlockCuboid {
     /// Generate and return a height map for the block cuboid, relative to the bottom
     /// layer of blocks in the block cuboid.
     pub fn height_map(&self) -> HeightMap {
-        let mut height_map = HeightMap::new((self.x_dim, self.z_dim));
-
-        for x in 0..self.x_dim {
-            for z in 0..self.z_dim {
-                let mut height = 0;
-
-                // Quckly drill down through the air
-                for y in (0..self.y_dim).rev() {
-                    if let Some(Block::Air) = self.block_at((x, y as usize, z)) {
-                    } else {
-                        height = y;
-                        break;
-                    }
-                }
+        self.height_map_of(HeightMapKind::LightBlocking)
+    }
 
-                // Accurately find the first opaque block
-                for y in (0..=height).rev() {
-                    if let Some(block) = self.block_at((x, y as usize, z)) {
-                        if block.is_affecting_sky_light_old() {
-                            height = y + 1;
-                            break;
-                        }
-                    }
-                }
+    /// Generate a height map of the given kind, holding for each column the
+    /// height just above the highest block tracked by that kind of height map.
+    pub fn height_map_of(&self, kind: HeightMapKind) -> HeightMap {
+        let mut height_map = HeightMap::new((self.x_dim, self.z_dim));
 
-                height_map.set_height((x, z), height as u32);
-            }
+        for ((x, z), column) in self.columns() {
+            let height = column
+                .iter()
+                .rposition(|block| kind.counts(block))
+                .map(|y| y + 1)
+                .unwrap_or(0);
+            height_map.set_height((x, z), height as u32);
         }
 
         height_map
@@ -175,11 +237,22 @@ impl BlockCuboid {
 
     /// Replace all occurrences of the given block with the given replacement.
     pub fn replace(&mut self, search_for: &Block, replace_with: &Block) {
+        self.replace_observed(search_for, replace_with, |_, _, _| ());
+    }
+
+    /// Replace all occurrences of the given block with the given replacement,
+    /// calling `observer` with the coordinates, old block and new block of
+    /// every block that is replaced.
+    pub fn replace_observed<F>(&mut self, search_for: &Block, replace_with: &Block, mut observer: F)
+    where
+        F: FnMut((usize, usize, usize), &Block, &Block),
+    {
         for x in 0..self.x_dim {
             for y in 0..self.y_dim {
                 for z in 0..self.z_dim {
                     if let Some(found_block) = self.block_at((x, y, z)) {
                         if *search_for == *found_block {
+                            observer((x, y, z), found_block, replace_with);
                             self.insert((x, y, z), replace_with.clone());
                         }
                     }
@@ -188,6 +261,44 @@ impl BlockCuboid {
         }
     }
 
+    /// Returns a copy of the block cuboid rotated around the vertical axis,
+    /// with the blocks themselves rotated as well.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let (x_dim, z_dim) = rotation.rotated_dim((self.x_dim, self.z_dim));
+        let mut rotated = Self::new((x_dim, self.y_dim, z_dim));
+        for x in 0..self.x_dim {
+            for z in 0..self.z_dim {
+                let (to_x, to_z) = rotation.rotated_position((x, z), (self.x_dim, self.z_dim));
+                for y in 0..self.y_dim {
+                    if let Some(block) = self.block_at((x, y, z)) {
+                        rotated.insert((to_x, y, to_z), block.rotated(rotation));
+                    }
+                }
+            }
+        }
+        rotated
+    }
+
+    /// Returns a copy of the block cuboid mirrored along the given axis,
+    /// with the blocks themselves mirrored as well.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        let mut mirrored = Self::new(self.dim());
+        for x in 0..self.x_dim {
+            for z in 0..self.z_dim {
+                let (to_x, to_z) = match axis {
+                    Axis2::X => (self.x_dim - 1 - x, z),
+                    Axis2::Z => (x, self.z_dim - 1 - z),
+                };
+                for y in 0..self.y_dim {
+                    if let Some(block) = self.block_at((x, y, z)) {
+                        mirrored.insert((to_x, y, to_z), block.mirrored(axis));
+                    }
+                }
+            }
+        }
+        mirrored
+    }
+
     fn index(&self, (x, y, z): (usize, usize, usize)) -> Option<usize> {
         if x >= self.x_dim || y >= self.y_dim || z >= self.z_dim {
             None
@@ -196,3 +307,9 @@ impl BlockCuboid {
         }
     }
 }
+
+/// Returns the coordinates of the block at `index` in the list of blocks.
+fn coordinates(index: usize, y_dim: usize, z_dim: usize) -> (usize, usize, usize) {
+    let column = index / y_dim;
+    (column / z_dim, index % y_dim, column % z_dim)
+}
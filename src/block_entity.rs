@@ -7,6 +7,7 @@ use crate::colour::Colour;
 use crate::coordinates::BlockCoord;
 use crate::inventory::Inventory;
 use crate::item::Item;
+use crate::mc_version::McVersion;
 use crate::nbt_lookup::*;
 use crate::positioning::{Direction16, Surface4};
 use crate::status_effect::StatusEffect;
@@ -197,6 +198,7 @@ impl BlockEntity {
                 "minecraft:smoker" => Self::smoker_from_nbt_value(value),
                 "minecraft:soul_campfire" => Self::soul_campfire_from_nbt_value(value),
                 "minecraft:structure_block" => Self::structure_block_from_nbt_value(value),
+                "minecraft:trapped_chest" => Self::chest_from_nbt_value(value),
                 _ => {
                     eprintln!("Unknown tile entity ID: {}", id);
                     BlockEntity::Unknown { id: Some(id) }
@@ -207,7 +209,7 @@ impl BlockEntity {
         }
     }
 
-    pub fn to_nbt_value(&self) -> Option<nbt::Value> {
+    pub fn to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
         match self {
             Self::Banner { .. } => self.banner_to_nbt_value(),
             Self::Barrel { .. } => self.barrel_to_nbt_value(),
@@ -218,27 +220,27 @@ impl BlockEntity {
             Self::BlastFurnace { .. } => self.blast_furnace_to_nbt_value(),
             Self::BrewingStand { .. } => self.brewing_stand_to_nbt_value(),
             //Self::Campfire { .. } => self.campfire_to_nbt_value(),
-            Self::Chest { .. } => self.chest_to_nbt_value(),
+            Self::Chest { .. } => self.chest_to_nbt_value(data_version),
             Self::Comparator { .. } => self.comparator_to_nbt_value(),
             Self::CommandBlock { .. } => self.command_block_to_nbt_value(),
             //Self::Conduit { .. } => self.conduit_to_nbt_value(),
             Self::DaylightDetector { .. } => self.daylight_detector_to_nbt_value(),
-            Self::Dispenser { .. } => self.dispenser_to_nbt_value(),
-            Self::Dropper { .. } => self.dropper_to_nbt_value(),
+            Self::Dispenser { .. } => self.dispenser_to_nbt_value(data_version),
+            Self::Dropper { .. } => self.dropper_to_nbt_value(data_version),
             Self::EnchantingTable { .. } => self.enchanting_table_to_nbt_value(),
             Self::EnderChest { .. } => self.ender_chest_to_nbt_value(),
             Self::EndGateway { .. } => self.end_gateway_to_nbt_value(),
             Self::EndPortal { .. } => self.end_portal_to_nbt_value(),
             Self::FlowerPot { .. } => self.flower_pot_to_nbt_value(),
-            Self::Furnace { .. } => self.furnace_to_nbt_value(),
-            Self::Hopper { .. } => self.hopper_to_nbt_value(),
+            Self::Furnace { .. } => self.furnace_to_nbt_value(data_version),
+            Self::Hopper { .. } => self.hopper_to_nbt_value(data_version),
             //Self::Jigsaw { .. } => self.jigsaw_to_nbt_value(),
-            Self::Jukebox { .. } => self.jukebox_to_nbt_value(),
-            Self::Lectern { .. } => self.lectern_to_nbt_value(),
+            Self::Jukebox { .. } => self.jukebox_to_nbt_value(data_version),
+            Self::Lectern { .. } => self.lectern_to_nbt_value(data_version),
             Self::MobSpawner { .. } => self.mob_spawner_to_nbt_value(),
             Self::Noteblock { .. } => self.noteblock_to_nbt_value(),
             Self::Piston { .. } => self.piston_to_nbt_value(),
-            Self::ShulkerBox { .. } => self.shulker_box_to_nbt_value(),
+            Self::ShulkerBox { .. } => self.shulker_box_to_nbt_value(data_version),
             Self::Sign { .. } => self.sign_to_nbt_value(),
             Self::Skull { .. } => self.skull_to_nbt_value(),
             Self::Smoker { .. } => self.smoker_to_nbt_value(),
@@ -461,11 +463,11 @@ impl BlockEntity {
         }
     }
 
-    fn chest_to_nbt_value(&self) -> Option<nbt::Value> {
+    fn chest_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
         let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 5);
 
         if let Self::Chest { tags } = self {
-            for (key, value) in tags.to_nbt_values() {
+            for (key, value) in tags.to_nbt_values(data_version) {
                 entity.insert(key, value);
             }
             Some(nbt::Value::Compound(entity))
@@ -525,11 +527,11 @@ impl BlockEntity {
         }
     }
 
-    fn dispenser_to_nbt_value(&self) -> Option<nbt::Value> {
+    fn dispenser_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
         let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 5);
 
         if let Self::Dispenser { tags } = self {
-            for (key, value) in tags.to_nbt_values() {
+            for (key, value) in tags.to_nbt_values(data_version) {
                 entity.insert(key, value);
             }
             Some(nbt::Value::Compound(entity))
@@ -544,11 +546,11 @@ impl BlockEntity {
         }
     }
 
-    fn dropper_to_nbt_value(&self) -> Option<nbt::Value> {
+    fn dropper_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
         let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 5);
 
         if let Self::Dropper { tags } = self {
-            for (key, value) in tags.to_nbt_values() {
+            for (key, value) in tags.to_nbt_values(data_version) {
                 entity.insert(key, value);
             }
             Some(nbt::Value::Compound(entity))
@@ -673,9 +675,17 @@ impl BlockEntity {
         }
     }
 
-    fn furnace_to_nbt_value(&self) -> Option<nbt::Value> {
-        // TODO
-        unimplemented!()
+    fn furnace_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
+        let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 6);
+
+        if let Self::Furnace { tags } = self {
+            for (key, value) in tags.to_nbt_values(data_version) {
+                entity.insert(key, value);
+            }
+            Some(nbt::Value::Compound(entity))
+        } else {
+            None
+        }
     }
 
     fn hopper_from_nbt_value(value: &nbt::Value) -> Self {
@@ -684,11 +694,11 @@ impl BlockEntity {
         }
     }
 
-    fn hopper_to_nbt_value(&self) -> Option<nbt::Value> {
+    fn hopper_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
         let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 5);
 
         if let Self::Hopper { tags } = self {
-            for (key, value) in tags.to_nbt_values() {
+            for (key, value) in tags.to_nbt_values(data_version) {
                 entity.insert(key, value);
             }
             Some(nbt::Value::Compound(entity))
@@ -716,9 +726,23 @@ impl BlockEntity {
         }
     }
 
-    fn jukebox_to_nbt_value(&self) -> Option<nbt::Value> {
-        // TODO
-        unimplemented!()
+    fn jukebox_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
+        let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 1);
+
+        if let Self::Jukebox { common, record } = self {
+            for (key, value) in common.to_nbt_values() {
+                entity.insert(key, value);
+            }
+            if let Some(record) = record
+                .as_ref()
+                .and_then(|record| record.to_nbt_value(data_version))
+            {
+                entity.insert("RecordItem".into(), record);
+            }
+            Some(nbt::Value::Compound(entity))
+        } else {
+            None
+        }
     }
 
     fn lectern_from_nbt_value(value: &nbt::Value) -> Self {
@@ -731,9 +755,23 @@ impl BlockEntity {
         }
     }
 
-    fn lectern_to_nbt_value(&self) -> Option<nbt::Value> {
-        // TODO
-        unimplemented!()
+    fn lectern_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
+        let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 2);
+
+        if let Self::Lectern { common, book } = self {
+            for (key, value) in common.to_nbt_values() {
+                entity.insert(key, value);
+            }
+            if let Some((book, page)) = book {
+                if let Some(book) = book.to_nbt_value(data_version) {
+                    entity.insert("Book".into(), book);
+                    entity.insert("Page".into(), nbt::Value::Int(*page));
+                }
+            }
+            Some(nbt::Value::Compound(entity))
+        } else {
+            None
+        }
     }
 
     fn mob_spawner_from_nbt_value(_value: &nbt::Value) -> Self {
@@ -790,11 +828,11 @@ impl BlockEntity {
         }
     }
 
-    fn shulker_box_to_nbt_value(&self) -> Option<nbt::Value> {
+    fn shulker_box_to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
         let mut entity: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(5 + 5);
 
         if let Self::ShulkerBox { tags } = self {
-            for (key, value) in tags.to_nbt_values() {
+            for (key, value) in tags.to_nbt_values(data_version) {
                 entity.insert(key, value);
             }
             Some(nbt::Value::Compound(entity))
@@ -986,7 +1024,7 @@ impl ChestTags {
         }
     }
 
-    fn to_nbt_values(&self) -> Vec<(String, nbt::Value)> {
+    fn to_nbt_values(&self, data_version: McVersion) -> Vec<(String, nbt::Value)> {
         let mut nbt_values = self.common.to_nbt_values();
         if let Some(name) = &self.custom_name {
             nbt_values.push(("CustomName".into(), nbt::Value::String(name.clone())));
@@ -994,7 +1032,7 @@ impl ChestTags {
         if let Some(lock) = &self.lock {
             nbt_values.push(("Lock".into(), nbt::Value::String(lock.clone())));
         }
-        nbt_values.push(("Items".into(), self.items.to_nbt_value()));
+        nbt_values.push(("Items".into(), self.items.to_nbt_value(data_version)));
         // TODO support for loot tables
         // TODO support for loot tables
         nbt_values
@@ -1004,7 +1042,7 @@ impl ChestTags {
 // Tags present for all "furnace similar" block entities, e.g. Furnace, Smoker, etc.
 #[derive(Clone, Debug)]
 pub struct FurnaceTags {
-    common: CommonTags,
+    pub(crate) common: CommonTags,
     pub custom_name: Option<String>,
     pub lock: Option<String>,
     pub items: Inventory,
@@ -1032,6 +1070,21 @@ impl FurnaceTags {
             cook_time_total: nbt_value_lookup_short(value, "CookTimeTotal").unwrap(),
         }
     }
+
+    fn to_nbt_values(&self, data_version: McVersion) -> Vec<(String, nbt::Value)> {
+        let mut nbt_values = self.common.to_nbt_values();
+        if let Some(name) = &self.custom_name {
+            nbt_values.push(("CustomName".into(), nbt::Value::String(name.clone())));
+        }
+        if let Some(lock) = &self.lock {
+            nbt_values.push(("Lock".into(), nbt::Value::String(lock.clone())));
+        }
+        nbt_values.push(("Items".into(), self.items.to_nbt_value(data_version)));
+        nbt_values.push(("BurnTime".into(), nbt::Value::Short(self.burn_time)));
+        nbt_values.push(("CookTime".into(), nbt::Value::Short(self.cook_time)));
+        nbt_values.push(("CookTimeTotal".into(), nbt::Value::Short(self.cook_time_total)));
+        nbt_values
+    }
 }
 
 // All block entities have a root TAG_Compound that contains the common tags
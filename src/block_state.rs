@@ -0,0 +1,2457 @@
+//! Block states, as found in the chunk palettes of post-flattening saves.
+//!
+//! Block names and properties are those of the latest supported Minecraft
+//! version. Names that were changed along the way are translated when reading
+//! from or writing to a save of an older version. Properties that are left out
+//! are given their default values by the game.
+//!
+//! States without a [`Block`] counterpart are kept in an interner, and are
+//! represented as `Block::Unknown(Some(id))` with `id` from 0x1000 and up, so
+//! that they are written back unchanged. The ids are only valid within the
+//! process that read the states, so such blocks are serialised by name and
+//! properties instead.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::fmt::Debug;
+use std::mem::Discriminant;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+
+use crate::block::*;
+use crate::bounded_ints::*;
+use crate::colour::Colour;
+use crate::inventory::Inventory;
+use crate::material::*;
+use crate::mc_version::McVersion;
+use crate::positioning::*;
+
+/// Lowest `Block::Unknown` id used for interned block states.
+///
+/// Lower ids are left for the numeric block ids of pre-flattening saves.
+const UNKNOWN_STATE_BASE: u16 = 0x1000;
+
+/// Block states read from saves, that have no `Block` counterpart.
+static UNKNOWN_STATES: Lazy<Mutex<Vec<BlockState>>> = Lazy::new(|| Mutex::new(Vec::new()));
+
+/// A block name and its properties, e.g. `minecraft:oak_stairs[facing=east,half=top]`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub(crate) struct BlockState {
+    /// Namespaced block name.
+    pub name: String,
+    pub properties: BTreeMap<String, String>,
+}
+
+/// Formats the block state as e.g. `minecraft:oak_stairs[facing=east,half=top]`.
+impl std::fmt::Display for BlockState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.properties.is_empty() {
+            let properties: Vec<String> = self
+                .properties
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            write!(f, "[{}]", properties.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+impl BlockState {
+    /// Creates a block state with the given name in the "minecraft" namespace.
+    fn new(name: &str) -> Self {
+        Self {
+            name: format!("minecraft:{}", name),
+            properties: BTreeMap::new(),
+        }
+    }
+
+    /// The block state of air.
+    pub fn air() -> Self {
+        Self::new("air")
+    }
+
+    /// Adds (or replaces) a property.
+    fn with<V: ToString>(mut self, key: &str, value: V) -> Self {
+        self.properties.insert(key.into(), value.to_string());
+        self
+    }
+
+    /// Creates a block state from a palette entry compound.
+    pub fn from_nbt_value(value: &nbt::Value) -> Self {
+        let mut name = String::from("minecraft:air");
+        let mut properties = BTreeMap::new();
+        if let nbt::Value::Compound(entry) = value {
+            if let Some(nbt::Value::String(entry_name)) = entry.get("Name") {
+                name = entry_name.clone();
+            }
+            if let Some(nbt::Value::Compound(entry_properties)) = entry.get("Properties") {
+                for (key, value) in entry_properties {
+                    if let nbt::Value::String(value) = value {
+                        properties.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        Self { name, properties }
+    }
+
+    /// Creates a palette entry compound from the block state.
+    pub fn to_nbt_value(&self) -> nbt::Value {
+        let mut entry = nbt::Map::new();
+        entry.insert("Name".into(), nbt::Value::String(self.name.clone()));
+        if !self.properties.is_empty() {
+            let properties = self
+                .properties
+                .iter()
+                .map(|(key, value)| (key.clone(), nbt::Value::String(value.clone())))
+                .collect();
+            entry.insert("Properties".into(), nbt::Value::Compound(properties));
+        }
+        nbt::Value::Compound(entry)
+    }
+
+    /// Returns true for air, cave air, and void air.
+    pub fn is_air(&self) -> bool {
+        matches!(
+            self.name.as_str(),
+            "minecraft:air" | "minecraft:cave_air" | "minecraft:void_air"
+        )
+    }
+
+    /// Block state used for `block` in saves of the given version.
+    pub fn from_block(block: &Block, version: McVersion) -> Self {
+        if let Block::Unknown(Some(id)) = block {
+            if let Some(state) = interned(*id) {
+                return state;
+            }
+        }
+        let mut state = Self::from_block_latest(block);
+        state.rename_for(version);
+        state
+    }
+
+    /// Block state used for `block` in saves of the latest supported version.
+    pub fn latest(block: &Block) -> Self {
+        if let Block::Unknown(Some(id)) = block {
+            if let Some(state) = interned(*id) {
+                return state;
+            }
+        }
+        Self::from_block_latest(block)
+    }
+
+    /// Adds neighbour-derived properties, as given by
+    /// [`ConnectionStates`](crate::connections::ConnectionStates), translated
+    /// for saves of the given version.
+    pub fn with_derived(
+        mut self,
+        properties: &BTreeMap<String, String>,
+        version: McVersion,
+    ) -> Self {
+        for (key, value) in properties {
+            let value = match (self.name.as_str(), key.as_str(), value.as_str()) {
+                // Wall sides were plain connections before they got a height
+                (name, "north", side)
+                | (name, "east", side)
+                | (name, "south", side)
+                | (name, "west", side)
+                    if name.ends_with("_wall") && version < version_of("20w06a") =>
+                {
+                    if side == "none" {
+                        "false"
+                    } else {
+                        "true"
+                    }
+                }
+                (
+                    "minecraft:note_block",
+                    "instrument",
+                    "banjo" | "bit" | "cow_bell" | "didgeridoo" | "iron_xylophone" | "pling",
+                ) if version < version_of("19w09a") => "harp",
+                (_, _, value) => value,
+            };
+            self.properties.insert(key.clone(), value.into());
+        }
+        self
+    }
+
+    /// Block represented by the block state, as found in a save of the given version.
+    pub fn to_block(&self, version: McVersion) -> Block {
+        let mut state = self.clone();
+        state.rename_from(version);
+        state
+            .to_block_latest()
+            .unwrap_or_else(|| Block::Unknown(Some(intern(self))))
+    }
+
+    /// Translates a latest version state into the corresponding state of `version`.
+    fn rename_for(&mut self, version: McVersion) {
+        let renamed = match self.name.as_str() {
+            "minecraft:short_grass" if version < version_of("23w40a") => "minecraft:grass",
+            "minecraft:dirt_path" if version < version_of("20w45a") => "minecraft:grass_path",
+            "minecraft:water_cauldron" if version < version_of("20w46a") => "minecraft:cauldron",
+            "minecraft:oak_sign" if version < version_of("19w08a") => "minecraft:sign",
+            "minecraft:oak_wall_sign" if version < version_of("19w08a") => "minecraft:wall_sign",
+            "minecraft:smooth_stone_slab" if version < version_of("18w43a") => {
+                "minecraft:stone_slab"
+            }
+            _ => return,
+        };
+        self.name = renamed.into();
+    }
+
+    /// Translates a state of `version` into the corresponding latest version state.
+    fn rename_from(&mut self, version: McVersion) {
+        let renamed = match self.name.as_str() {
+            "minecraft:grass" if version < version_of("23w40a") => "minecraft:short_grass",
+            "minecraft:grass_path" if version < version_of("20w45a") => "minecraft:dirt_path",
+            "minecraft:cauldron" if version < version_of("20w46a") => {
+                match self.properties.remove("level").as_deref() {
+                    None | Some("0") => return,
+                    Some(level) => {
+                        self.properties.insert("level".into(), level.into());
+                        "minecraft:water_cauldron"
+                    }
+                }
+            }
+            "minecraft:sign" if version < version_of("19w08a") => "minecraft:oak_sign",
+            "minecraft:wall_sign" if version < version_of("19w08a") => "minecraft:oak_wall_sign",
+            "minecraft:stone_slab" if version < version_of("18w43a") => {
+                "minecraft:smooth_stone_slab"
+            }
+            _ => return,
+        };
+        self.name = renamed.into();
+    }
+
+    fn from_block_latest(block: &Block) -> Self {
+        if let Some(name) = SIMPLE_BLOCK_NAMES.get(&std::mem::discriminant(block)) {
+            return Self::new(name);
+        }
+
+        match block {
+            Block::None | Block::Unknown(_) => Self::new("air"),
+            Block::Anvil { facing, damage } => Self::new(match damage {
+                AnvilDamage::Intact => "anvil",
+                AnvilDamage::SlightlyDamaged => "chipped_anvil",
+                AnvilDamage::VeryDamaged => "damaged_anvil",
+            })
+            .with("facing", name_of(facing)),
+            Block::Bamboo {
+                growth_stage,
+                leaves,
+                stage,
+            } => Self::new("bamboo")
+                .with("age", growth_stage.get())
+                .with("leaves", name_of(leaves))
+                .with("stage", stage.get()),
+            Block::Banner(banner) => match banner.placement {
+                WallOrRotatedOnFloor::Floor(direction) => {
+                    Self::new(&format!("{}_banner", name_of(banner.colour)))
+                        .with("rotation", u8::from(direction))
+                }
+                WallOrRotatedOnFloor::Wall(facing) => {
+                    Self::new(&format!("{}_wall_banner", name_of(banner.colour)))
+                        .with("facing", name_of(facing))
+                }
+            },
+            Block::Barrel { facing } => Self::new("barrel").with("facing", name_of(facing)),
+            Block::Basalt { alignment } => Self::new("basalt").with("axis", name_of(alignment)),
+            Block::Beacon(_) => Self::new("beacon"),
+            Block::Beetroots { growth_stage } => {
+                Self::new("beetroots").with("age", growth_stage.get())
+            }
+            Block::Beehive {
+                facing,
+                honey_level,
+            } => Self::new("beehive")
+                .with("facing", name_of(facing))
+                .with("honey_level", honey_level.get()),
+            Block::BeeNest {
+                facing,
+                honey_level,
+            } => Self::new("bee_nest")
+                .with("facing", name_of(facing))
+                .with("honey_level", honey_level.get()),
+            Block::Bell { position } => {
+                let (attachment, facing) = bell_attachment(*position);
+                Self::new("bell")
+                    .with("attachment", attachment)
+                    .with("facing", name_of(facing))
+            }
+            Block::Bed(bed) => Self::new(&format!("{}_bed", name_of(bed.colour)))
+                .with("facing", name_of(bed.facing))
+                .with("part", name_of(&bed.end)),
+            Block::BlastFurnace(furnace) => Self::new("blast_furnace")
+                .with("facing", name_of(furnace.facing))
+                .with("lit", furnace.lit),
+            Block::BoneBlock { alignment } => {
+                Self::new("bone_block").with("axis", name_of(alignment))
+            }
+            Block::BrewingStand(_) => Self::new("brewing_stand"),
+            Block::BrownMushroomBlock { cap_directions } => {
+                Self::new("brown_mushroom_block").with_flags(cap_directions, true)
+            }
+            Block::BrownMushroomStem { stem_directions }
+            | Block::RedMushroomStem { stem_directions } => {
+                Self::new("mushroom_stem").with_flags(stem_directions, true)
+            }
+            Block::BubbleColumn { drag_direction } => {
+                Self::new("bubble_column").with("drag", *drag_direction == Surface2::Down)
+            }
+            Block::Button(material, facing) => {
+                let state = Self::new(&format!("{}_button", name_of(material)));
+                match facing {
+                    Surface6::Down => state.with("face", "ceiling"),
+                    Surface6::Up => state.with("face", "floor"),
+                    _ => state.with("face", "wall").with("facing", name_of(facing)),
+                }
+            }
+            Block::Cactus { growth_stage } => Self::new("cactus").with("age", growth_stage.get()),
+            Block::Cake { pieces } => Self::new("cake").with("bites", 7 - pieces.get()),
+            Block::Campfire {
+                facing,
+                lit,
+                waterlogged,
+            } => Self::new("campfire")
+                .with("facing", name_of(facing))
+                .with("lit", lit)
+                .with("waterlogged", waterlogged),
+            Block::Carpet { colour } => Self::new(&format!("{}_carpet", name_of(colour))),
+            Block::Carrots { growth_stage } => Self::new("carrots").with("age", growth_stage.get()),
+            Block::CarvedPumpkin { facing } => {
+                Self::new("carved_pumpkin").with("facing", name_of(facing))
+            }
+            Block::Cauldron { water_level } => match water_level.get() {
+                0 => Self::new("cauldron"),
+                level => Self::new("water_cauldron").with("level", level),
+            },
+            Block::Chest(chest) => Self::chest("chest", chest),
+            Block::ChorusFlower { growth_stage } => {
+                Self::new("chorus_flower").with("age", growth_stage.get())
+            }
+            Block::CocoaBeans {
+                growth_stage,
+                facing,
+            } => Self::new("cocoa")
+                .with("age", growth_stage.get())
+                .with("facing", name_of(facing)),
+            Block::CommandBlock(command_block) => Self::new(match command_block.variant {
+                CommandBlockVariant::ChainedCommandBlock => "chain_command_block",
+                CommandBlockVariant::CommandBlock => "command_block",
+                CommandBlockVariant::RepeatingCommandBlock => "repeating_command_block",
+            })
+            .with("conditional", command_block.conditional)
+            .with("facing", name_of(command_block.facing)),
+            Block::Composter { fullness } => Self::new("composter").with("level", fullness.get()),
+            Block::Concrete { colour } => Self::new(&format!("{}_concrete", name_of(colour))),
+            Block::ConcretePowder { colour } => {
+                Self::new(&format!("{}_concrete_powder", name_of(colour)))
+            }
+            Block::Conduit { waterlogged } => Self::new("conduit").with("waterlogged", waterlogged),
+            Block::Coral {
+                material,
+                dead,
+                waterlogged,
+            } => Self::new(&format!(
+                "{}{}_coral",
+                dead_prefix(*dead),
+                name_of(material)
+            ))
+            .with("waterlogged", waterlogged),
+            Block::CoralBlock { material, dead } => Self::new(&format!(
+                "{}{}_coral_block",
+                dead_prefix(*dead),
+                name_of(material)
+            )),
+            Block::CoralFan {
+                material,
+                facing,
+                dead,
+                waterlogged,
+            } => match facing {
+                Surface5::Down => Self::new(&format!(
+                    "{}{}_coral_fan",
+                    dead_prefix(*dead),
+                    name_of(material)
+                )),
+                _ => Self::new(&format!(
+                    "{}{}_coral_wall_fan",
+                    dead_prefix(*dead),
+                    name_of(material)
+                ))
+                .with("facing", name_of(facing)),
+            }
+            .with("waterlogged", waterlogged),
+            Block::Dispenser(dispenser) => {
+                Self::new("dispenser").with("facing", name_of(dispenser.facing))
+            }
+            Block::Door(door) => Self::new(&format!("{}_door", name_of(door.material)))
+                .with("facing", name_of(opposite4(door.facing)))
+                .with("half", name_of(&door.half))
+                .with("hinge", name_of(&door.hinged_at))
+                .with("open", door.open),
+            Block::Dropper(dropper) => Self::new("dropper").with("facing", name_of(dropper.facing)),
+            Block::EnchantingTable { .. } => Self::new("enchanting_table"),
+            Block::EndPortalFrame { facing, has_eye } => Self::new("end_portal_frame")
+                .with("eye", has_eye)
+                .with("facing", name_of(facing)),
+            Block::EndRod { facing } => Self::new("end_rod").with("facing", name_of(facing)),
+            Block::EnderChest {
+                facing,
+                waterlogged,
+            } => Self::new("ender_chest")
+                .with("facing", name_of(facing))
+                .with("waterlogged", waterlogged),
+            Block::Farmland { wetness } => Self::new("farmland").with("moisture", wetness.get()),
+            Block::Fence {
+                material,
+                waterlogged,
+            } => {
+                Self::new(&format!("{}_fence", name_of(material))).with("waterlogged", waterlogged)
+            }
+            Block::FenceGate {
+                material,
+                facing,
+                open,
+            } => Self::new(&format!("{}_fence_gate", name_of(material)))
+                .with("facing", name_of(facing))
+                .with("open", open),
+            Block::Fire { age } => Self::new("fire").with("age", age.get()),
+            Block::Flower(flower) => {
+                let (name, half) = flower_name(*flower);
+                let state = Self::new(&name);
+                match half {
+                    Some(half) => state.with("half", half),
+                    None => state,
+                }
+            }
+            Block::FlowerPot(pot) => match pot.plant {
+                Some(plant) => Self::new(&format!("potted_{}", potted_plant_name(plant))),
+                None => Self::new("flower_pot"),
+            },
+            Block::Furnace(furnace) => Self::new("furnace")
+                .with("facing", name_of(furnace.facing))
+                .with("lit", furnace.lit),
+            Block::Glass { colour } => match colour {
+                Some(colour) => Self::new(&format!("{}_stained_glass", name_of(colour))),
+                None => Self::new("glass"),
+            },
+            Block::GlassPane {
+                colour,
+                waterlogged,
+            } => match colour {
+                Some(colour) => Self::new(&format!("{}_stained_glass_pane", name_of(colour))),
+                None => Self::new("glass_pane"),
+            }
+            .with("waterlogged", waterlogged),
+            Block::GlazedTerracotta(terracotta) => {
+                Self::new(&format!("{}_glazed_terracotta", name_of(terracotta.colour)))
+                    .with("facing", name_of(terracotta.facing))
+            }
+            Block::Grass(grass) => match grass {
+                Grass::Fern => Self::new("fern"),
+                Grass::Grass => Self::new("short_grass"),
+                Grass::LargeFernBottom => Self::new("large_fern").with("half", "lower"),
+                Grass::LargeFernTop => Self::new("large_fern").with("half", "upper"),
+                Grass::TallGrassBottom => Self::new("tall_grass").with("half", "lower"),
+                Grass::TallGrassTop => Self::new("tall_grass").with("half", "upper"),
+            },
+            Block::GrindStone(rotation) => Self::new("grindstone").with_face(*rotation),
+            Block::HayBale { alignment } => Self::new("hay_block").with("axis", name_of(alignment)),
+            Block::Head(head) => {
+                let name = name_of(head.variant);
+                match head.placement {
+                    WallOrRotatedOnFloor::Floor(direction) => {
+                        Self::new(&name).with("rotation", u8::from(direction))
+                    }
+                    WallOrRotatedOnFloor::Wall(facing) => {
+                        Self::new(&wall_variant_name(&name)).with("facing", name_of(facing))
+                    }
+                }
+                .with("waterlogged", head.waterlogged)
+            }
+            Block::Hopper(hopper) => Self::new("hopper")
+                .with("facing", name_of(hopper.facing))
+                .with("waterlogged", hopper.waterlogged),
+            Block::DaylightDetector => Self::new("daylight_detector").with("inverted", false),
+            Block::InvertedDaylightDetector => {
+                Self::new("daylight_detector").with("inverted", true)
+            }
+            Block::IronBars { waterlogged } => {
+                Self::new("iron_bars").with("waterlogged", waterlogged)
+            }
+            Block::JackOLantern { facing } => {
+                Self::new("jack_o_lantern").with("facing", name_of(facing))
+            }
+            Block::JigsawBlock { orientation } => {
+                Self::new("jigsaw").with("orientation", name_of(orientation))
+            }
+            Block::Jukebox(jukebox) => {
+                Self::new("jukebox").with("has_record", jukebox.record.is_some())
+            }
+            Block::Kelp { growth_stage } => Self::new("kelp").with("age", growth_stage.get()),
+            Block::Ladder {
+                facing,
+                waterlogged,
+            } => Self::new("ladder")
+                .with("facing", name_of(facing))
+                .with("waterlogged", waterlogged),
+            Block::Lantern { mounted_at } => {
+                Self::new("lantern").with("hanging", *mounted_at == Surface2::Up)
+            }
+            Block::Lava { falling, level } => {
+                Self::new("lava").with("level", fluid_level(*falling, level.get()))
+            }
+            Block::LavaSource => Self::new("lava").with("level", 0),
+            Block::Leaves {
+                material,
+                distance_to_trunk,
+                persistent,
+            } => Self::new(&format!("{}_leaves", name_of(material)))
+                .with(
+                    "distance",
+                    distance_to_trunk
+                        .map(|distance| distance.get())
+                        .unwrap_or(1),
+                )
+                .with("persistent", persistent),
+            Block::Lectern { facing, book } => Self::new("lectern")
+                .with("facing", name_of(facing))
+                .with("has_book", book.is_some()),
+            Block::Lever(rotation, state) => Self::new("lever")
+                .with_face(*rotation)
+                .with("powered", *state == OnOffState::On),
+            Block::Log(log) => {
+                Self::new(&log_name(log)).with("axis", name_of(log.alignment.unwrap_or(Axis3::Y)))
+            }
+            Block::Loom { facing } => Self::new("loom").with("facing", name_of(facing)),
+            Block::MelonStem { state } => Self::stem("melon_stem", state),
+            Block::NetherPortal { alignment } => {
+                Self::new("nether_portal").with("axis", name_of(alignment.unwrap_or(Axis2::X)))
+            }
+            Block::NetherWart { growth_stage } => {
+                Self::new("nether_wart").with("age", growth_stage.get())
+            }
+            Block::Noteblock(noteblock) => {
+                Self::new("note_block").with("note", noteblock.pitch.to_i8())
+            }
+            Block::Observer { facing } => Self::new("observer").with("facing", name_of(facing)),
+            Block::Piston { facing, extended } => Self::new("piston")
+                .with("extended", extended)
+                .with("facing", name_of(facing)),
+            Block::PistonHead { facing } => Self::new("piston_head")
+                .with("facing", name_of(facing))
+                .with("type", "normal"),
+            Block::Planks { material } => Self::new(&format!("{}_planks", name_of(material))),
+            Block::PolishedBasalt { alignment } => {
+                Self::new("polished_basalt").with("axis", name_of(alignment))
+            }
+            Block::Potatoes { growth_stage } => {
+                Self::new("potatoes").with("age", growth_stage.get())
+            }
+            Block::PressurePlate { material } => Self::new(&match material {
+                PressurePlateMaterial::Gold => "light_weighted_pressure_plate".into(),
+                PressurePlateMaterial::Iron => "heavy_weighted_pressure_plate".into(),
+                _ => format!("{}_pressure_plate", name_of(material)),
+            }),
+            Block::Pumpkin { .. } => Self::new("pumpkin"),
+            Block::PumpkinStem { state } => Self::stem("pumpkin_stem", state),
+            Block::PurpurPillar { alignment } => {
+                Self::new("purpur_pillar").with("axis", name_of(alignment))
+            }
+            Block::QuartzPillar { alignment } => {
+                Self::new("quartz_pillar").with("axis", name_of(alignment))
+            }
+            Block::Rail { variant, shape } => Self::new(match variant {
+                RailType::Activator => "activator_rail",
+                RailType::Detector => "detector_rail",
+                RailType::Normal => "rail",
+                RailType::Powered => "powered_rail",
+            })
+            .with("shape", name_of(shape)),
+            Block::RedMushroomBlock { cap_directions } => {
+                Self::new("red_mushroom_block").with_flags(cap_directions, true)
+            }
+            Block::RedstoneComparator { facing } => Self::new("comparator")
+                .with("facing", name_of(opposite4(*facing)))
+                .with("mode", "compare"),
+            Block::RedstoneRepeater(repeater) => Self::new("repeater")
+                .with("delay", repeater.delay.get())
+                .with("facing", name_of(opposite4(repeater.facing))),
+            Block::RedstoneSubtractor { facing } => Self::new("comparator")
+                .with("facing", name_of(opposite4(*facing)))
+                .with("mode", "subtract"),
+            Block::RedstoneTorch { attached } => Self::torch("redstone_", *attached),
+            Block::RespawnAnchor { charges } => {
+                Self::new("respawn_anchor").with("charges", charges.get())
+            }
+            Block::Sapling {
+                material,
+                growth_stage,
+            } => match material {
+                SaplingMaterial::Bamboo => Self::new("bamboo_sapling"),
+                _ => Self::new(&format!("{}_sapling", name_of(material)))
+                    .with("stage", growth_stage.get()),
+            },
+            Block::Scaffolding { waterlogged } => {
+                Self::new("scaffolding").with("waterlogged", waterlogged)
+            }
+            Block::SeaPickle { count, waterlogged } => Self::new("sea_pickle")
+                .with("pickles", count.get())
+                .with("waterlogged", waterlogged),
+            Block::Seagrass { variant } => match variant {
+                Seagrass::Seagrass => Self::new("seagrass"),
+                Seagrass::TallSeagrassBottom => Self::new("tall_seagrass").with("half", "lower"),
+                Seagrass::TallSeagrassTop => Self::new("tall_seagrass").with("half", "upper"),
+            },
+            Block::ShulkerBox(shulker_box) => Self::new(&match shulker_box.colour {
+                Some(colour) => format!("{}_shulker_box", name_of(colour)),
+                None => "shulker_box".into(),
+            })
+            .with("facing", name_of(shulker_box.facing)),
+            Block::Sign(sign) => match sign.placement {
+                WallOrRotatedOnFloor::Floor(direction) => {
+                    Self::new(&format!("{}_sign", name_of(sign.material)))
+                        .with("rotation", u8::from(direction))
+                }
+                WallOrRotatedOnFloor::Wall(facing) => {
+                    Self::new(&format!("{}_wall_sign", name_of(sign.material)))
+                        .with("facing", name_of(facing))
+                }
+            }
+            .with("waterlogged", sign.waterlogged),
+            Block::Slab(slab) => Self::new(&format!("{}_slab", name_of(slab.material)))
+                .with("type", name_of(slab.position))
+                .with("waterlogged", slab.waterlogged),
+            Block::Smoker(furnace) => Self::new("smoker")
+                .with("facing", name_of(furnace.facing))
+                .with("lit", furnace.lit),
+            Block::Snow { thickness } => Self::new("snow").with("layers", thickness.get()),
+            Block::SoulCampfire {
+                facing,
+                lit,
+                waterlogged,
+            } => Self::new("soul_campfire")
+                .with("facing", name_of(facing))
+                .with("lit", lit)
+                .with("waterlogged", waterlogged),
+            Block::SoulFire { .. } => Self::new("soul_fire"),
+            Block::SoulLantern { mounted_at } => {
+                Self::new("soul_lantern").with("hanging", *mounted_at == Surface2::Up)
+            }
+            Block::SoulTorch { attached } => Self::torch("soul_", *attached),
+            Block::Stairs(stair) => {
+                let (half, facing) = edge_parts(stair.position);
+                Self::new(&format!("{}_stairs", name_of(stair.material)))
+                    .with("facing", name_of(facing))
+                    .with("half", half)
+                    .with("waterlogged", stair.waterlogged)
+            }
+            Block::StickyPiston { facing, extended } => Self::new("sticky_piston")
+                .with("extended", extended)
+                .with("facing", name_of(facing)),
+            Block::StickyPistonHead { facing } => Self::new("piston_head")
+                .with("facing", name_of(facing))
+                .with("type", "sticky"),
+            Block::StoneCutter { facing } => {
+                Self::new("stonecutter").with("facing", name_of(facing))
+            }
+            Block::SugarCane { growth_stage } => {
+                Self::new("sugar_cane").with("age", growth_stage.get())
+            }
+            Block::SweetBerryBush { growth_stage } => {
+                Self::new("sweet_berry_bush").with("age", growth_stage.get())
+            }
+            Block::Terracotta { colour } => match colour {
+                Some(colour) => Self::new(&format!("{}_terracotta", name_of(colour))),
+                None => Self::new("terracotta"),
+            },
+            Block::Torch { attached } => Self::torch("", *attached),
+            Block::Trapdoor(trapdoor) => {
+                let (half, hinge_side) = edge_parts(trapdoor.hinge_at);
+                Self::new(&format!("{}_trapdoor", name_of(trapdoor.material)))
+                    .with("facing", name_of(opposite4(hinge_side)))
+                    .with("half", half)
+                    .with("open", trapdoor.open)
+                    .with("waterlogged", trapdoor.waterlogged)
+            }
+            Block::TrappedChest(chest) => Self::chest("trapped_chest", chest),
+            Block::TripwireHook { facing } => {
+                Self::new("tripwire_hook").with("facing", name_of(facing))
+            }
+            Block::TurtleEgg { count, age } => Self::new("turtle_egg")
+                .with("eggs", count.get())
+                .with("hatch", age.get()),
+            Block::TwistingVines { growth_stage } => {
+                Self::new("twisting_vines").with("age", growth_stage.get())
+            }
+            Block::Vines(vines) => Self::new("vine").with_flags(&vines.anchored_at, false),
+            Block::Wall {
+                material,
+                waterlogged,
+            } => Self::new(&format!("{}_wall", name_of(material))).with("waterlogged", waterlogged),
+            Block::Water { falling, level } => {
+                Self::new("water").with("level", fluid_level(*falling, level.get()))
+            }
+            Block::WaterSource => Self::new("water").with("level", 0),
+            Block::WeepingVines { growth_stage } => {
+                Self::new("weeping_vines").with("age", growth_stage.get())
+            }
+            Block::Wheat { growth_stage } => Self::new("wheat").with("age", growth_stage.get()),
+            Block::Wool { colour } => Self::new(&format!("{}_wool", name_of(colour))),
+            // Plain blocks are handled through SIMPLE_BLOCK_NAMES
+            _ => Self::new("air"),
+        }
+    }
+
+    fn to_block_latest(&self) -> Option<Block> {
+        let name = self.name.strip_prefix("minecraft:")?;
+        if let Some(block) = SIMPLE_BLOCKS_BY_NAME.get(name) {
+            return Some((*block).clone());
+        }
+
+        let block = match name {
+            "anvil" | "chipped_anvil" | "damaged_anvil" => Block::Anvil {
+                facing: self.parsed("facing", &SURFACE4)?,
+                damage: match name {
+                    "anvil" => AnvilDamage::Intact,
+                    "chipped_anvil" => AnvilDamage::SlightlyDamaged,
+                    _ => AnvilDamage::VeryDamaged,
+                },
+            },
+            "bamboo" => Block::Bamboo {
+                growth_stage: Int0Through1::new_saturating(self.int("age")),
+                leaves: self.parsed("leaves", &BAMBOO_LEAVES)?,
+                stage: Int0Through1::new_saturating(self.int("stage")),
+            },
+            "barrel" => Block::Barrel {
+                facing: self.parsed("facing", &SURFACE6)?,
+            },
+            "basalt" => Block::Basalt {
+                alignment: self.parsed("axis", &AXIS3)?,
+            },
+            "beacon" => Block::Beacon(Box::new(Beacon {
+                lock: None,
+                levels: 0,
+                primary: None,
+                secondary: None,
+            })),
+            "beetroots" => Block::Beetroots {
+                growth_stage: Int0Through3::new_saturating(self.int("age")),
+            },
+            "beehive" | "bee_nest" => {
+                let facing = self.parsed("facing", &SURFACE4)?;
+                let honey_level = HoneyLevel::new_saturating(self.int("honey_level"));
+                if name == "beehive" {
+                    Block::Beehive {
+                        facing,
+                        honey_level,
+                    }
+                } else {
+                    Block::BeeNest {
+                        facing,
+                        honey_level,
+                    }
+                }
+            }
+            "bell" => Block::Bell {
+                position: bell_position(
+                    self.prop("attachment")?,
+                    self.parsed("facing", &SURFACE4)?,
+                )?,
+            },
+            "blast_furnace" | "furnace" | "smoker" => {
+                let furnace = Box::new(Furnace {
+                    facing: self.parsed("facing", &SURFACE4)?,
+                    lit: self.flag("lit"),
+                    custom_name: None,
+                    lock: None,
+                    items: Inventory::new(),
+                    burn_time: 0,
+                    cook_time: 0,
+                    cook_time_total: 0,
+                });
+                match name {
+                    "blast_furnace" => Block::BlastFurnace(furnace),
+                    "furnace" => Block::Furnace(furnace),
+                    _ => Block::Smoker(furnace),
+                }
+            }
+            "bone_block" => Block::BoneBlock {
+                alignment: self.parsed("axis", &AXIS3)?,
+            },
+            "brewing_stand" => Block::BrewingStand(Box::new(BrewingStand {
+                custom_name: None,
+                lock: None,
+                items: Inventory::new(),
+                brew_time: 0,
+                fuel: 0,
+            })),
+            "brown_mushroom_block" => Block::BrownMushroomBlock {
+                cap_directions: self.flags(),
+            },
+            "mushroom_stem" => Block::BrownMushroomStem {
+                stem_directions: self.flags(),
+            },
+            "bubble_column" => Block::BubbleColumn {
+                drag_direction: if self.flag("drag") {
+                    Surface2::Down
+                } else {
+                    Surface2::Up
+                },
+            },
+            "cactus" => Block::Cactus {
+                growth_stage: Int0Through15::new_saturating(self.int("age")),
+            },
+            "cake" => Block::Cake {
+                pieces: Int1Through7::new_saturating(7 - self.int("bites")),
+            },
+            "campfire" | "soul_campfire" => {
+                let facing = self.parsed("facing", &SURFACE4)?;
+                let lit = self.flag("lit");
+                let waterlogged = self.flag("waterlogged");
+                if name == "campfire" {
+                    Block::Campfire {
+                        facing,
+                        lit,
+                        waterlogged,
+                    }
+                } else {
+                    Block::SoulCampfire {
+                        facing,
+                        lit,
+                        waterlogged,
+                    }
+                }
+            }
+            "carrots" => Block::Carrots {
+                growth_stage: Int0Through7::new_saturating(self.int("age")),
+            },
+            "carved_pumpkin" => Block::CarvedPumpkin {
+                facing: self.parsed("facing", &SURFACE4)?,
+            },
+            "cauldron" => Block::Cauldron {
+                water_level: Int0Through3::MIN,
+            },
+            "water_cauldron" => Block::Cauldron {
+                water_level: Int0Through3::new_saturating(self.int("level")),
+            },
+            "chest" => Block::Chest(self.chest_block()?),
+            "trapped_chest" => Block::TrappedChest(self.chest_block()?),
+            "chorus_flower" => Block::ChorusFlower {
+                growth_stage: Int0Through5::new_saturating(self.int("age")),
+            },
+            "cocoa" => Block::CocoaBeans {
+                growth_stage: Int0Through2::new_saturating(self.int("age")),
+                facing: self.parsed("facing", &SURFACE4)?,
+            },
+            "command_block" | "chain_command_block" | "repeating_command_block" => {
+                Block::CommandBlock(CommandBlock {
+                    variant: match name {
+                        "chain_command_block" => CommandBlockVariant::ChainedCommandBlock,
+                        "command_block" => CommandBlockVariant::CommandBlock,
+                        _ => CommandBlockVariant::RepeatingCommandBlock,
+                    },
+                    conditional: self.flag("conditional"),
+                    facing: self.parsed("facing", &SURFACE6)?,
+                })
+            }
+            "composter" => Block::Composter {
+                fullness: Int0Through8::new_saturating(self.int("level")),
+            },
+            "conduit" => Block::Conduit {
+                waterlogged: self.flag("waterlogged"),
+            },
+            "daylight_detector" => {
+                if self.flag("inverted") {
+                    Block::InvertedDaylightDetector
+                } else {
+                    Block::DaylightDetector
+                }
+            }
+            "dispenser" => Block::Dispenser(Box::new(Dispenser {
+                facing: self.parsed("facing", &SURFACE6)?,
+                custom_name: None,
+                lock: None,
+                items: Inventory::new(),
+            })),
+            "dropper" => Block::Dropper(Box::new(Dropper {
+                facing: self.parsed("facing", &SURFACE6)?,
+                custom_name: None,
+                lock: None,
+                items: Inventory::new(),
+            })),
+            "enchanting_table" => Block::EnchantingTable {
+                custom_name: Box::new(None),
+            },
+            "end_portal_frame" => Block::EndPortalFrame {
+                facing: self.parsed("facing", &SURFACE4)?,
+                has_eye: self.flag("eye"),
+            },
+            "end_rod" => Block::EndRod {
+                facing: self.parsed("facing", &SURFACE6)?,
+            },
+            "ender_chest" => Block::EnderChest {
+                facing: self.parsed("facing", &SURFACE4)?,
+                waterlogged: self.flag("waterlogged"),
+            },
+            "farmland" => Block::Farmland {
+                wetness: Int0Through7::new_saturating(self.int("moisture")),
+            },
+            "fire" => Block::Fire {
+                age: Int0Through15::new_saturating(self.int("age")),
+            },
+            "soul_fire" => Block::SoulFire {
+                age: Int0Through15::MIN,
+                burning_faces: FireFace {
+                    east: false,
+                    down: true,
+                    north: false,
+                    south: false,
+                    up: false,
+                    west: false,
+                },
+            },
+            "flower_pot" => Block::FlowerPot(FlowerPot { plant: None }),
+            "fern" => Block::Grass(Grass::Fern),
+            "short_grass" => Block::Grass(Grass::Grass),
+            "large_fern" => Block::Grass(if self.is_upper() {
+                Grass::LargeFernTop
+            } else {
+                Grass::LargeFernBottom
+            }),
+            "tall_grass" => Block::Grass(if self.is_upper() {
+                Grass::TallGrassTop
+            } else {
+                Grass::TallGrassBottom
+            }),
+            "glass" => Block::Glass { colour: None },
+            "glass_pane" => Block::GlassPane {
+                colour: None,
+                waterlogged: self.flag("waterlogged"),
+            },
+            "grindstone" => Block::GrindStone(self.face()?),
+            "hay_block" => Block::HayBale {
+                alignment: self.parsed("axis", &AXIS3)?,
+            },
+            "hopper" => Block::Hopper(Box::new(Hopper {
+                facing: self.parsed("facing", &SURFACE5)?,
+                waterlogged: self.flag("waterlogged"),
+                custom_name: None,
+                lock: None,
+                items: Inventory::new(),
+            })),
+            "iron_bars" => Block::IronBars {
+                waterlogged: self.flag("waterlogged"),
+            },
+            "jack_o_lantern" => Block::JackOLantern {
+                facing: self.parsed("facing", &SURFACE4)?,
+            },
+            "jigsaw" => Block::JigsawBlock {
+                orientation: self.parsed("orientation", &JIGSAW_ORIENTATIONS)?,
+            },
+            "jukebox" => Block::Jukebox(Box::new(Jukebox { record: None })),
+            "kelp" => Block::Kelp {
+                growth_stage: Int0Through25::new_saturating(self.int("age")),
+            },
+            "ladder" => Block::Ladder {
+                facing: self.parsed("facing", &SURFACE4)?,
+                waterlogged: self.flag("waterlogged"),
+            },
+            "lantern" => Block::Lantern {
+                mounted_at: self.hanging(),
+            },
+            "soul_lantern" => Block::SoulLantern {
+                mounted_at: self.hanging(),
+            },
+            "lava" => match self.int("level") {
+                0 => Block::LavaSource,
+                level => {
+                    let (falling, level) = fluid_parts(level);
+                    Block::Lava { falling, level }
+                }
+            },
+            "water" => match self.int("level") {
+                0 => Block::WaterSource,
+                level => {
+                    let (falling, level) = fluid_parts(level);
+                    Block::Water { falling, level }
+                }
+            },
+            "lectern" => Block::Lectern {
+                facing: self.parsed("facing", &SURFACE4)?,
+                book: None,
+            },
+            "lever" => Block::Lever(
+                self.face()?,
+                if self.flag("powered") {
+                    OnOffState::On
+                } else {
+                    OnOffState::Off
+                },
+            ),
+            "loom" => Block::Loom {
+                facing: self.parsed("facing", &SURFACE4)?,
+            },
+            "melon_stem" | "attached_melon_stem" => Block::MelonStem {
+                state: self.stem_state(name.starts_with("attached_"))?,
+            },
+            "pumpkin_stem" | "attached_pumpkin_stem" => Block::PumpkinStem {
+                state: self.stem_state(name.starts_with("attached_"))?,
+            },
+            "nether_portal" => Block::NetherPortal {
+                alignment: Some(self.parsed("axis", &AXIS2)?),
+            },
+            "nether_wart" => Block::NetherWart {
+                growth_stage: Int0Through3::new_saturating(self.int("age")),
+            },
+            "note_block" => Block::Noteblock(Noteblock {
+                pitch: Pitch::from_value(self.int("note").clamp(0, 24) as u8),
+            }),
+            "observer" => Block::Observer {
+                facing: self.parsed("facing", &SURFACE6)?,
+            },
+            "piston" => Block::Piston {
+                facing: self.parsed("facing", &SURFACE6)?,
+                extended: self.flag("extended"),
+            },
+            "sticky_piston" => Block::StickyPiston {
+                facing: self.parsed("facing", &SURFACE6)?,
+                extended: self.flag("extended"),
+            },
+            "piston_head" => {
+                let facing = self.parsed("facing", &SURFACE6)?;
+                if self.prop("type") == Some("sticky") {
+                    Block::StickyPistonHead { facing }
+                } else {
+                    Block::PistonHead { facing }
+                }
+            }
+            "polished_basalt" => Block::PolishedBasalt {
+                alignment: self.parsed("axis", &AXIS3)?,
+            },
+            "potatoes" => Block::Potatoes {
+                growth_stage: Int0Through7::new_saturating(self.int("age")),
+            },
+            "light_weighted_pressure_plate" => Block::PressurePlate {
+                material: PressurePlateMaterial::Gold,
+            },
+            "heavy_weighted_pressure_plate" => Block::PressurePlate {
+                material: PressurePlateMaterial::Iron,
+            },
+            "pumpkin" => Block::Pumpkin {
+                facing: Surface4::South,
+            },
+            "purpur_pillar" => Block::PurpurPillar {
+                alignment: self.parsed("axis", &AXIS3)?,
+            },
+            "quartz_pillar" => Block::QuartzPillar {
+                alignment: self.parsed("axis", &AXIS3)?,
+            },
+            "rail" | "activator_rail" | "detector_rail" | "powered_rail" => Block::Rail {
+                variant: match name {
+                    "activator_rail" => RailType::Activator,
+                    "detector_rail" => RailType::Detector,
+                    "rail" => RailType::Normal,
+                    _ => RailType::Powered,
+                },
+                shape: self.parsed("shape", &RAIL_SHAPES)?,
+            },
+            "red_mushroom_block" => Block::RedMushroomBlock {
+                cap_directions: self.flags(),
+            },
+            "comparator" => {
+                let facing = opposite4(self.parsed("facing", &SURFACE4)?);
+                if self.prop("mode") == Some("subtract") {
+                    Block::RedstoneSubtractor { facing }
+                } else {
+                    Block::RedstoneComparator { facing }
+                }
+            }
+            "repeater" => Block::RedstoneRepeater(RedstoneRepeater {
+                facing: opposite4(self.parsed("facing", &SURFACE4)?),
+                delay: Int1Through4::new_saturating(self.int("delay")),
+            }),
+            "redstone_torch" | "redstone_wall_torch" => Block::RedstoneTorch {
+                attached: self.torch_attachment()?,
+            },
+            "soul_torch" | "soul_wall_torch" => Block::SoulTorch {
+                attached: self.torch_attachment()?,
+            },
+            "torch" | "wall_torch" => Block::Torch {
+                attached: self.torch_attachment()?,
+            },
+            "respawn_anchor" => Block::RespawnAnchor {
+                charges: Int0Through4::new_saturating(self.int("charges")),
+            },
+            "bamboo_sapling" => Block::Sapling {
+                material: SaplingMaterial::Bamboo,
+                growth_stage: Int0Through1::MIN,
+            },
+            "scaffolding" => Block::Scaffolding {
+                waterlogged: self.flag("waterlogged"),
+            },
+            "sea_pickle" => Block::SeaPickle {
+                count: Int1Through4::new_saturating(self.int("pickles")),
+                waterlogged: self.flag("waterlogged"),
+            },
+            "seagrass" => Block::Seagrass {
+                variant: Seagrass::Seagrass,
+            },
+            "tall_seagrass" => Block::Seagrass {
+                variant: if self.is_upper() {
+                    Seagrass::TallSeagrassTop
+                } else {
+                    Seagrass::TallSeagrassBottom
+                },
+            },
+            "shulker_box" => Block::ShulkerBox(self.shulker_box(None)?),
+            "snow" => Block::Snow {
+                thickness: Int1Through8::new_saturating(self.int("layers")),
+            },
+            "stonecutter" => Block::StoneCutter {
+                facing: self.parsed("facing", &SURFACE4)?,
+            },
+            "sugar_cane" => Block::SugarCane {
+                growth_stage: Int0Through15::new_saturating(self.int("age")),
+            },
+            "sweet_berry_bush" => Block::SweetBerryBush {
+                growth_stage: Int0Through3::new_saturating(self.int("age")),
+            },
+            "terracotta" => Block::Terracotta { colour: None },
+            "tripwire_hook" => Block::TripwireHook {
+                facing: self.parsed("facing", &SURFACE4)?,
+            },
+            "turtle_egg" => Block::TurtleEgg {
+                count: Int1Through4::new_saturating(self.int("eggs")),
+                age: Int0Through2::new_saturating(self.int("hatch")),
+            },
+            "twisting_vines" => Block::TwistingVines {
+                growth_stage: Int0Through25::new_saturating(self.int("age")),
+            },
+            "vine" => Block::Vines(Vines {
+                anchored_at: self.flags(),
+            }),
+            "weeping_vines" => Block::WeepingVines {
+                growth_stage: Int0Through25::new_saturating(self.int("age")),
+            },
+            "wheat" => Block::Wheat {
+                growth_stage: Int0Through7::new_saturating(self.int("age")),
+            },
+            _ => return self.to_block_by_suffix(name),
+        };
+        Some(block)
+    }
+
+    /// Blocks with names made up of a variant (colour, material, etc.) and a block type.
+    fn to_block_by_suffix(&self, name: &str) -> Option<Block> {
+        if let Some(flower) = flower_from_name(name, self.is_upper()) {
+            return Some(Block::Flower(flower));
+        }
+        if let Some(plant) = name.strip_prefix("potted_") {
+            return Some(Block::FlowerPot(FlowerPot {
+                plant: Some(match plant {
+                    "warped_roots" => PottedPlant::Warpedroots,
+                    _ => parsed_name(&unswapped_tulip(plant), &POTTED_PLANTS)?,
+                }),
+            }));
+        }
+        if let Some(base) = name.strip_suffix("_coral_wall_fan") {
+            let (dead, material) = coral_parts(base)?;
+            return Some(Block::CoralFan {
+                material,
+                facing: self.parsed("facing", &SURFACE5)?,
+                dead,
+                waterlogged: self.flag("waterlogged"),
+            });
+        }
+        if let Some(base) = name.strip_suffix("_coral_fan") {
+            let (dead, material) = coral_parts(base)?;
+            return Some(Block::CoralFan {
+                material,
+                facing: Surface5::Down,
+                dead,
+                waterlogged: self.flag("waterlogged"),
+            });
+        }
+        if let Some(base) = name.strip_suffix("_coral_block") {
+            let (dead, material) = coral_parts(base)?;
+            return Some(Block::CoralBlock { material, dead });
+        }
+        if let Some(base) = name.strip_suffix("_coral") {
+            let (dead, material) = coral_parts(base)?;
+            return Some(Block::Coral {
+                material,
+                dead,
+                waterlogged: self.flag("waterlogged"),
+            });
+        }
+        if let Some(log) = log_from_name(name) {
+            return Some(Block::Log(Log {
+                alignment: if log.alignment.is_some() {
+                    Some(self.parsed("axis", &AXIS3)?)
+                } else {
+                    None
+                },
+                ..log
+            }));
+        }
+        if let Some(variant) = HEAD_VARIANTS
+            .iter()
+            .find(|variant| wall_variant_name(&name_of(**variant)) == name)
+        {
+            return Some(Block::Head(Head {
+                variant: *variant,
+                placement: WallOrRotatedOnFloor::Wall(self.parsed("facing", &SURFACE4)?),
+                waterlogged: self.flag("waterlogged"),
+            }));
+        }
+        if let Some(variant) = parsed_name(name, &HEAD_VARIANTS) {
+            return Some(Block::Head(Head {
+                variant,
+                placement: WallOrRotatedOnFloor::Floor(Direction16::from(self.int("rotation"))),
+                waterlogged: self.flag("waterlogged"),
+            }));
+        }
+
+        // Coloured blocks
+        if let Some(colour) = name.strip_suffix("_stained_glass_pane") {
+            return Some(Block::GlassPane {
+                colour: Some(parsed_name(colour, &COLOURS)?),
+                waterlogged: self.flag("waterlogged"),
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_stained_glass") {
+            return Some(Block::Glass {
+                colour: Some(parsed_name(colour, &COLOURS)?),
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_glazed_terracotta") {
+            return Some(Block::GlazedTerracotta(GlazedTerracotta {
+                colour: parsed_name(colour, &COLOURS)?,
+                facing: self.parsed("facing", &SURFACE4)?,
+            }));
+        }
+        if let Some(colour) = name.strip_suffix("_terracotta") {
+            return Some(Block::Terracotta {
+                colour: Some(parsed_name(colour, &COLOURS)?),
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_concrete_powder") {
+            return Some(Block::ConcretePowder {
+                colour: parsed_name(colour, &COLOURS)?,
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_concrete") {
+            return Some(Block::Concrete {
+                colour: parsed_name(colour, &COLOURS)?,
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_carpet") {
+            return Some(Block::Carpet {
+                colour: parsed_name(colour, &COLOURS)?,
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_wool") {
+            return Some(Block::Wool {
+                colour: parsed_name(colour, &COLOURS)?,
+            });
+        }
+        if let Some(colour) = name.strip_suffix("_shulker_box") {
+            return Some(Block::ShulkerBox(
+                self.shulker_box(Some(parsed_name(colour, &COLOURS)?))?,
+            ));
+        }
+        if let Some(colour) = name.strip_suffix("_bed") {
+            return Some(Block::Bed(Bed {
+                colour: parsed_name(colour, &COLOURS)?,
+                facing: self.parsed("facing", &SURFACE4)?,
+                end: self.parsed("part", &BED_ENDS)?,
+            }));
+        }
+        if let Some(colour) = name.strip_suffix("_wall_banner") {
+            return Some(Block::Banner(Box::new(Banner {
+                colour: parsed_name(colour, &COLOURS)?,
+                custom_name: None,
+                placement: WallOrRotatedOnFloor::Wall(self.parsed("facing", &SURFACE4)?),
+                patterns: Vec::new(),
+            })));
+        }
+        if let Some(colour) = name.strip_suffix("_banner") {
+            return Some(Block::Banner(Box::new(Banner {
+                colour: parsed_name(colour, &COLOURS)?,
+                custom_name: None,
+                placement: WallOrRotatedOnFloor::Floor(Direction16::from(self.int("rotation"))),
+                patterns: Vec::new(),
+            })));
+        }
+
+        // Blocks of different materials
+        if let Some(material) = name.strip_suffix("_wall_sign") {
+            return Some(self.sign(
+                parsed_name(material, &WOOD_MATERIALS)?,
+                WallOrRotatedOnFloor::Wall(self.parsed("facing", &SURFACE4)?),
+            ));
+        }
+        if let Some(material) = name.strip_suffix("_sign") {
+            return Some(self.sign(
+                parsed_name(material, &WOOD_MATERIALS)?,
+                WallOrRotatedOnFloor::Floor(Direction16::from(self.int("rotation"))),
+            ));
+        }
+        if let Some(material) = name.strip_suffix("_slab") {
+            return Some(Block::Slab(Slab {
+                material: parsed_name(material, &SLAB_MATERIALS)?,
+                position: self.parsed("type", &SLAB_VARIANTS)?,
+                waterlogged: self.flag("waterlogged"),
+            }));
+        }
+        if let Some(material) = name.strip_suffix("_stairs") {
+            let facing = self.parsed("facing", &SURFACE4)?;
+            return Some(Block::Stairs(Stair {
+                material: parsed_name(material, &STAIR_MATERIALS)?,
+                position: edge(self.prop("half") == Some("top"), facing),
+                waterlogged: self.flag("waterlogged"),
+            }));
+        }
+        if let Some(material) = name.strip_suffix("_fence_gate") {
+            return Some(Block::FenceGate {
+                material: parsed_name(material, &WOOD_MATERIALS)?,
+                facing: self.parsed("facing", &SURFACE4)?,
+                open: self.flag("open"),
+            });
+        }
+        if let Some(material) = name.strip_suffix("_fence") {
+            return Some(Block::Fence {
+                material: parsed_name(material, &FENCE_MATERIALS)?,
+                waterlogged: self.flag("waterlogged"),
+            });
+        }
+        if let Some(material) = name.strip_suffix("_wall") {
+            return Some(Block::Wall {
+                material: parsed_name(material, &WALL_MATERIALS)?,
+                waterlogged: self.flag("waterlogged"),
+            });
+        }
+        if let Some(material) = name.strip_suffix("_planks") {
+            return Some(Block::Planks {
+                material: parsed_name(material, &WOOD_MATERIALS)?,
+            });
+        }
+        if let Some(material) = name.strip_suffix("_button") {
+            return Some(Block::Button(
+                parsed_name(material, &BUTTON_MATERIALS)?,
+                match self.prop("face") {
+                    Some("ceiling") => Surface6::Down,
+                    Some("floor") => Surface6::Up,
+                    _ => self.parsed("facing", &SURFACE6)?,
+                },
+            ));
+        }
+        if let Some(material) = name.strip_suffix("_pressure_plate") {
+            return Some(Block::PressurePlate {
+                material: parsed_name(material, &PRESSURE_PLATE_MATERIALS)?,
+            });
+        }
+        if let Some(material) = name.strip_suffix("_trapdoor") {
+            let hinge_side = opposite4(self.parsed("facing", &SURFACE4)?);
+            return Some(Block::Trapdoor(Trapdoor {
+                material: parsed_name(material, &DOOR_MATERIALS)?,
+                hinge_at: edge(self.prop("half") == Some("top"), hinge_side),
+                open: self.flag("open"),
+                waterlogged: self.flag("waterlogged"),
+            }));
+        }
+        if let Some(material) = name.strip_suffix("_door") {
+            return Some(Block::Door(Door {
+                material: parsed_name(material, &DOOR_MATERIALS)?,
+                facing: opposite4(self.parsed("facing", &SURFACE4)?),
+                half: self.parsed("half", &DOOR_HALVES)?,
+                hinged_at: self.parsed("hinge", &HINGES)?,
+                open: self.flag("open"),
+            }));
+        }
+        if let Some(material) = name.strip_suffix("_leaves") {
+            return Some(Block::Leaves {
+                material: parsed_name(material, &LEAVES_MATERIALS)?,
+                distance_to_trunk: Some(Int0Through7::new_saturating(self.int("distance"))),
+                persistent: self.flag("persistent"),
+            });
+        }
+        if let Some(material) = name.strip_suffix("_sapling") {
+            return Some(Block::Sapling {
+                material: parsed_name(material, &SAPLING_MATERIALS)?,
+                growth_stage: Int0Through1::new_saturating(self.int("stage")),
+            });
+        }
+
+        None
+    }
+
+    fn prop(&self, key: &str) -> Option<&str> {
+        self.properties.get(key).map(String::as_str)
+    }
+
+    /// Boolean property value, false if missing.
+    fn flag(&self, key: &str) -> bool {
+        self.prop(key) == Some("true")
+    }
+
+    /// Integer property value, 0 if missing or invalid.
+    fn int(&self, key: &str) -> i8 {
+        self.prop(key)
+            .and_then(|value| i8::from_str(value).ok())
+            .unwrap_or(0)
+    }
+
+    fn parsed<T: Clone + Debug>(&self, key: &str, candidates: &[T]) -> Option<T> {
+        parsed_name(self.prop(key)?, candidates)
+    }
+
+    fn is_upper(&self) -> bool {
+        self.prop("half") == Some("upper")
+    }
+
+    fn hanging(&self) -> Surface2 {
+        if self.flag("hanging") {
+            Surface2::Up
+        } else {
+            Surface2::Down
+        }
+    }
+
+    fn with_flags(self, flags: &DirectionFlags6, with_vertical: bool) -> Self {
+        let state = self
+            .with("east", flags.east)
+            .with("north", flags.north)
+            .with("south", flags.south)
+            .with("up", flags.up)
+            .with("west", flags.west);
+        if with_vertical {
+            state.with("down", flags.down)
+        } else {
+            state
+        }
+    }
+
+    fn flags(&self) -> DirectionFlags6 {
+        DirectionFlags6 {
+            east: self.flag("east"),
+            down: self.flag("down"),
+            north: self.flag("north"),
+            south: self.flag("south"),
+            up: self.flag("up"),
+            west: self.flag("west"),
+        }
+    }
+
+    /// Sets the "face" and "facing" properties of levers, grindstones, etc.
+    fn with_face(self, rotation: SurfaceRotation12) -> Self {
+        let direction = Direction::from(rotation);
+        match rotation {
+            SurfaceRotation12::East
+            | SurfaceRotation12::North
+            | SurfaceRotation12::South
+            | SurfaceRotation12::West => {
+                self.with("face", "wall").with("facing", name_of(direction))
+            }
+            _ => {
+                let (half, side) = edge_parts(Edge8::try_from(direction).unwrap());
+                let face = if half == "top" { "floor" } else { "ceiling" };
+                self.with("face", face)
+                    .with("facing", name_of(opposite4(side)))
+            }
+        }
+    }
+
+    fn face(&self) -> Option<SurfaceRotation12> {
+        let facing = self.parsed("facing", &SURFACE4)?;
+        let direction = match self.prop("face") {
+            Some("floor") => Direction::from(edge(true, opposite4(facing))),
+            Some("ceiling") => Direction::from(edge(false, opposite4(facing))),
+            _ => Direction::from(facing),
+        };
+        SurfaceRotation12::try_from(direction).ok()
+    }
+
+    fn torch(prefix: &str, attached: Surface5) -> Self {
+        match Surface4::try_from(Direction::from(attached)) {
+            Ok(side) => {
+                Self::new(&format!("{}wall_torch", prefix)).with("facing", name_of(opposite4(side)))
+            }
+            Err(_) => Self::new(&format!("{}torch", prefix)),
+        }
+    }
+
+    fn torch_attachment(&self) -> Option<Surface5> {
+        if self.name.ends_with("wall_torch") {
+            let side = opposite4(self.parsed("facing", &SURFACE4)?);
+            Surface5::try_from(Direction::from(side)).ok()
+        } else {
+            Some(Surface5::Down)
+        }
+    }
+
+    fn chest(name: &str, chest: &Chest) -> Self {
+        let state = Self::new(name)
+            .with("facing", name_of(chest.facing))
+            .with("waterlogged", chest.waterlogged);
+        match &chest.variant {
+            Some(variant) => state.with("type", name_of(variant)),
+            None => state,
+        }
+    }
+
+    fn chest_block(&self) -> Option<Box<Chest>> {
+        Some(Box::new(Chest {
+            facing: self.parsed("facing", &SURFACE4)?,
+            variant: self
+                .prop("type")
+                .and_then(|variant| parsed_name(variant, &CHEST_VARIANTS)),
+            waterlogged: self.flag("waterlogged"),
+            custom_name: None,
+            lock: None,
+            items: Inventory::new(),
+        }))
+    }
+
+    fn shulker_box(&self, colour: Option<Colour>) -> Option<Box<ShulkerBox>> {
+        Some(Box::new(ShulkerBox {
+            colour,
+            facing: self.parsed("facing", &SURFACE6)?,
+            custom_name: None,
+            lock: None,
+            items: Inventory::new(),
+        }))
+    }
+
+    fn sign(&self, material: WoodMaterial, placement: WallOrRotatedOnFloor) -> Block {
+        Block::Sign(Box::new(Sign {
+            material,
+            placement,
+            waterlogged: self.flag("waterlogged"),
+            colour: Colour::Black,
+            text1: String::new(),
+            text2: String::new(),
+            text3: String::new(),
+            text4: String::new(),
+        }))
+    }
+
+    fn stem(name: &str, state: &StemState) -> Self {
+        match state {
+            StemState::Growing(age) => Self::new(name).with("age", age.get()),
+            StemState::Attached(facing) => {
+                Self::new(&format!("attached_{}", name)).with("facing", name_of(facing))
+            }
+        }
+    }
+
+    fn stem_state(&self, attached: bool) -> Option<StemState> {
+        if attached {
+            Some(StemState::Attached(self.parsed("facing", &SURFACE4)?))
+        } else {
+            Some(StemState::Growing(Int0Through7::new_saturating(
+                self.int("age"),
+            )))
+        }
+    }
+}
+
+/// Returns the interned block state with the given `Block::Unknown` id.
+fn interned(id: u16) -> Option<BlockState> {
+    let index = id.checked_sub(UNKNOWN_STATE_BASE)? as usize;
+    UNKNOWN_STATES.lock().unwrap().get(index).cloned()
+}
+
+/// Returns true if `id` is the `Block::Unknown` id of an interned block state.
+pub(crate) fn is_interned(id: u16) -> bool {
+    interned(id).is_some()
+}
+
+/// Interns the block state, and returns its `Block::Unknown` id.
+fn intern(state: &BlockState) -> u16 {
+    let mut states = UNKNOWN_STATES.lock().unwrap();
+    let index = match states.iter().position(|known| known == state) {
+        Some(index) => index,
+        None => {
+            states.push(state.clone());
+            states.len() - 1
+        }
+    };
+    UNKNOWN_STATE_BASE
+        .checked_add(index as u16)
+        .unwrap_or_else(|| panic!("Too many unknown block states"))
+}
+
+/// Serialisation of `Block::Unknown` ids, by name and properties for interned
+/// block states, and by number for the numeric block ids of pre-flattening saves.
+pub(crate) mod unknown_id {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserializer, Serializer};
+    use serde_derive::{Deserialize, Serialize};
+
+    use super::{intern, interned, BlockState};
+
+    #[derive(Deserialize, Serialize)]
+    #[serde(untagged)]
+    enum UnknownId {
+        State {
+            name: String,
+            properties: BTreeMap<String, String>,
+        },
+        Id(Option<u16>),
+    }
+
+    pub fn serialize<S: Serializer>(id: &Option<u16>, serializer: S) -> Result<S::Ok, S::Error> {
+        match id.and_then(interned) {
+            Some(BlockState { name, properties }) => {
+                serde::Serialize::serialize(&UnknownId::State { name, properties }, serializer)
+            }
+            None => serde::Serialize::serialize(&UnknownId::Id(*id), serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<u16>, D::Error> {
+        let unknown_id: UnknownId = serde::Deserialize::deserialize(deserializer)?;
+        Ok(match unknown_id {
+            UnknownId::State { name, properties } => Some(intern(&BlockState { name, properties })),
+            UnknownId::Id(id) => id,
+        })
+    }
+}
+
+fn version_of(name: &str) -> McVersion {
+    McVersion::from_str(name).unwrap()
+}
+
+/// Lower case, underscore separated name of an enum variant, e.g. "light_gray" for
+/// `Colour::LightGray`.
+pub(crate) fn name_of<T: Debug>(value: T) -> String {
+    let debug = format!("{:?}", value);
+    let mut name = String::with_capacity(debug.len() + 4);
+    for (index, character) in debug.chars().enumerate() {
+        if character.is_uppercase() {
+            if index > 0 {
+                name.push('_');
+            }
+            name.extend(character.to_lowercase());
+        } else {
+            name.push(character);
+        }
+    }
+    name
+}
+
+pub(crate) fn parsed_name<T: Clone + Debug>(name: &str, candidates: &[T]) -> Option<T> {
+    candidates
+        .iter()
+        .find(|candidate| name_of(candidate) == name)
+        .cloned()
+}
+
+fn opposite4(side: Surface4) -> Surface4 {
+    match side {
+        Surface4::East => Surface4::West,
+        Surface4::North => Surface4::South,
+        Surface4::South => Surface4::North,
+        Surface4::West => Surface4::East,
+    }
+}
+
+/// Splits an edge into its "half" property value and its horizontal side.
+fn edge_parts(edge: Edge8) -> (&'static str, Surface4) {
+    match edge {
+        Edge8::DownEast => ("bottom", Surface4::East),
+        Edge8::DownNorth => ("bottom", Surface4::North),
+        Edge8::DownSouth => ("bottom", Surface4::South),
+        Edge8::DownWest => ("bottom", Surface4::West),
+        Edge8::UpEast => ("top", Surface4::East),
+        Edge8::UpNorth => ("top", Surface4::North),
+        Edge8::UpSouth => ("top", Surface4::South),
+        Edge8::UpWest => ("top", Surface4::West),
+    }
+}
+
+fn edge(top: bool, side: Surface4) -> Edge8 {
+    match (top, side) {
+        (false, Surface4::East) => Edge8::DownEast,
+        (false, Surface4::North) => Edge8::DownNorth,
+        (false, Surface4::South) => Edge8::DownSouth,
+        (false, Surface4::West) => Edge8::DownWest,
+        (true, Surface4::East) => Edge8::UpEast,
+        (true, Surface4::North) => Edge8::UpNorth,
+        (true, Surface4::South) => Edge8::UpSouth,
+        (true, Surface4::West) => Edge8::UpWest,
+    }
+}
+
+/// Fluid "level" property value; 0 is a source block, 8 and up are falling fluids.
+fn fluid_level(falling: bool, level: i8) -> i8 {
+    let falling = if falling { 8 } else { 0 };
+    falling | (8 - level)
+}
+
+fn fluid_parts(fluid_level: i8) -> (bool, Int1Through7) {
+    let falling = fluid_level & 0x8 == 0x8;
+    let level = Int1Through7::new_saturating(8 - (fluid_level & 0x7));
+    (falling, level)
+}
+
+fn bell_attachment(position: BellPosition) -> (&'static str, Surface4) {
+    match position {
+        BellPosition::UpEast => ("ceiling", Surface4::East),
+        BellPosition::UpNorth => ("ceiling", Surface4::North),
+        BellPosition::UpSouth => ("ceiling", Surface4::South),
+        BellPosition::UpWest => ("ceiling", Surface4::West),
+        BellPosition::SideEast => ("single_wall", Surface4::East),
+        BellPosition::SideNorth => ("single_wall", Surface4::North),
+        BellPosition::SideSouth => ("single_wall", Surface4::South),
+        BellPosition::SideWest => ("single_wall", Surface4::West),
+        BellPosition::DoubleSideEast => ("double_wall", Surface4::East),
+        BellPosition::DoubleSideNorth => ("double_wall", Surface4::North),
+        BellPosition::DoubleSideSouth => ("double_wall", Surface4::South),
+        BellPosition::DoubleSideWest => ("double_wall", Surface4::West),
+        BellPosition::DownEast => ("floor", Surface4::East),
+        BellPosition::DownNorth => ("floor", Surface4::North),
+        BellPosition::DownSouth => ("floor", Surface4::South),
+        BellPosition::DownWest => ("floor", Surface4::West),
+    }
+}
+
+fn bell_position(attachment: &str, facing: Surface4) -> Option<BellPosition> {
+    BELL_POSITIONS
+        .iter()
+        .find(|position| bell_attachment(**position) == (attachment, facing))
+        .copied()
+}
+
+fn dead_prefix(dead: bool) -> &'static str {
+    if dead {
+        "dead_"
+    } else {
+        ""
+    }
+}
+
+fn coral_parts(base: &str) -> Option<(bool, CoralMaterial)> {
+    match base.strip_prefix("dead_") {
+        Some(material) => Some((true, parsed_name(material, &CORAL_MATERIALS)?)),
+        None => Some((false, parsed_name(base, &CORAL_MATERIALS)?)),
+    }
+}
+
+/// Name of the wall mounted variant of a head or skull, e.g. "zombie_wall_head".
+fn wall_variant_name(name: &str) -> String {
+    match name.rfind('_') {
+        Some(index) => format!("{}_wall{}", &name[..index], &name[index..]),
+        None => name.into(),
+    }
+}
+
+/// Turns e.g. "tulip_red" into "red_tulip", and back.
+fn swapped_tulip(name: &str) -> String {
+    match name.strip_prefix("tulip_") {
+        Some(colour) => format!("{}_tulip", colour),
+        None => name.into(),
+    }
+}
+
+fn unswapped_tulip(name: &str) -> String {
+    match name.strip_suffix("_tulip") {
+        Some(colour) => format!("tulip_{}", colour),
+        None => name.into(),
+    }
+}
+
+pub(crate) fn flower_name(flower: Flower) -> (String, Option<&'static str>) {
+    let name = name_of(flower);
+    if let Some(base) = name.strip_suffix("_bottom") {
+        (base.into(), Some("lower"))
+    } else if let Some(base) = name.strip_suffix("_top") {
+        (base.into(), Some("upper"))
+    } else {
+        (swapped_tulip(&name), None)
+    }
+}
+
+pub(crate) fn flower_from_name(name: &str, upper: bool) -> Option<Flower> {
+    FLOWERS
+        .iter()
+        .find(|flower| {
+            let (flower_name, half) = flower_name(**flower);
+            flower_name == name && (half.is_none() || (half == Some("upper")) == upper)
+        })
+        .copied()
+}
+
+fn potted_plant_name(plant: PottedPlant) -> String {
+    match plant {
+        PottedPlant::Warpedroots => "warped_roots".into(),
+        _ => swapped_tulip(&name_of(plant)),
+    }
+}
+
+/// Name of a log, wood, stem or hyphae block.
+fn log_name(log: &Log) -> String {
+    let nether = matches!(log.material, WoodMaterial::Crimson | WoodMaterial::Warped);
+    let kind = match (nether, log.alignment.is_some()) {
+        (false, true) => "log",
+        (false, false) => "wood",
+        (true, true) => "stem",
+        (true, false) => "hyphae",
+    };
+    let stripped = if log.stripped { "stripped_" } else { "" };
+    format!("{}{}_{}", stripped, name_of(log.material), kind)
+}
+
+/// Log with material and stripping, and with any alignment if it has one.
+fn log_from_name(name: &str) -> Option<Log> {
+    let (stripped, name) = match name.strip_prefix("stripped_") {
+        Some(name) => (true, name),
+        None => (false, name),
+    };
+    let index = name.rfind('_')?;
+    let alignment = match &name[index + 1..] {
+        "log" | "stem" => Some(Axis3::Y),
+        "wood" | "hyphae" => None,
+        _ => return None,
+    };
+    let log = Log {
+        material: parsed_name(&name[..index], &WOOD_MATERIALS)?,
+        alignment,
+        stripped,
+    };
+    if log_name(&log) == format!("{}{}", if stripped { "stripped_" } else { "" }, name) {
+        Some(log)
+    } else {
+        None
+    }
+}
+
+const AXIS2: [Axis2; 2] = [Axis2::X, Axis2::Z];
+const AXIS3: [Axis3; 3] = [Axis3::X, Axis3::Y, Axis3::Z];
+const SURFACE4: [Surface4; 4] = [
+    Surface4::East,
+    Surface4::North,
+    Surface4::South,
+    Surface4::West,
+];
+const SURFACE5: [Surface5; 5] = [
+    Surface5::Down,
+    Surface5::East,
+    Surface5::North,
+    Surface5::South,
+    Surface5::West,
+];
+const SURFACE6: [Surface6; 6] = [
+    Surface6::Down,
+    Surface6::East,
+    Surface6::North,
+    Surface6::South,
+    Surface6::Up,
+    Surface6::West,
+];
+const BAMBOO_LEAVES: [BambooLeaves; 3] =
+    [BambooLeaves::None, BambooLeaves::Small, BambooLeaves::Large];
+const BED_ENDS: [BedEnd; 2] = [BedEnd::Foot, BedEnd::Head];
+const BELL_POSITIONS: [BellPosition; 16] = [
+    BellPosition::UpEast,
+    BellPosition::UpNorth,
+    BellPosition::UpSouth,
+    BellPosition::UpWest,
+    BellPosition::SideEast,
+    BellPosition::SideNorth,
+    BellPosition::SideSouth,
+    BellPosition::SideWest,
+    BellPosition::DoubleSideEast,
+    BellPosition::DoubleSideNorth,
+    BellPosition::DoubleSideSouth,
+    BellPosition::DoubleSideWest,
+    BellPosition::DownEast,
+    BellPosition::DownNorth,
+    BellPosition::DownSouth,
+    BellPosition::DownWest,
+];
+const CHEST_VARIANTS: [ChestVariant; 3] = [
+    ChestVariant::Left,
+    ChestVariant::Right,
+    ChestVariant::Single,
+];
+const DOOR_HALVES: [DoorHalf; 2] = [DoorHalf::Lower, DoorHalf::Upper];
+const HINGES: [Hinge; 2] = [Hinge::Left, Hinge::Right];
+const JIGSAW_ORIENTATIONS: [JigsawBlockOrientation; 12] = [
+    JigsawBlockOrientation::DownEast,
+    JigsawBlockOrientation::DownNorth,
+    JigsawBlockOrientation::DownSouth,
+    JigsawBlockOrientation::DownWest,
+    JigsawBlockOrientation::EastUp,
+    JigsawBlockOrientation::NorthUp,
+    JigsawBlockOrientation::SouthUp,
+    JigsawBlockOrientation::UpEast,
+    JigsawBlockOrientation::UpNorth,
+    JigsawBlockOrientation::UpSouth,
+    JigsawBlockOrientation::UpWest,
+    JigsawBlockOrientation::WestUp,
+];
+const RAIL_SHAPES: [RailShape; 10] = [
+    RailShape::EastWest,
+    RailShape::NorthEast,
+    RailShape::NorthSouth,
+    RailShape::NorthWest,
+    RailShape::SouthEast,
+    RailShape::SouthWest,
+    RailShape::AscendingEast,
+    RailShape::AscendingNorth,
+    RailShape::AscendingSouth,
+    RailShape::AscendingWest,
+];
+const SLAB_VARIANTS: [SlabVariant; 3] =
+    [SlabVariant::Bottom, SlabVariant::Double, SlabVariant::Top];
+pub(crate) const COLOURS: [Colour; 16] = [
+    Colour::White,
+    Colour::Orange,
+    Colour::Magenta,
+    Colour::LightBlue,
+    Colour::Yellow,
+    Colour::Lime,
+    Colour::Pink,
+    Colour::Gray,
+    Colour::LightGray,
+    Colour::Cyan,
+    Colour::Purple,
+    Colour::Blue,
+    Colour::Brown,
+    Colour::Green,
+    Colour::Red,
+    Colour::Black,
+];
+pub(crate) const FLOWERS: [Flower; 21] = [
+    Flower::Allium,
+    Flower::AzureBluet,
+    Flower::BlueOrchid,
+    Flower::Cornflower,
+    Flower::Dandelion,
+    Flower::LilacBottom,
+    Flower::LilacTop,
+    Flower::LilyOfTheValley,
+    Flower::OxeyeDaisy,
+    Flower::PeonyBottom,
+    Flower::PeonyTop,
+    Flower::Poppy,
+    Flower::RoseBushBottom,
+    Flower::RoseBushTop,
+    Flower::SunflowerBottom,
+    Flower::SunflowerTop,
+    Flower::TulipWhite,
+    Flower::TulipOrange,
+    Flower::TulipPink,
+    Flower::TulipRed,
+    Flower::WitherRose,
+];
+const HEAD_VARIANTS: [HeadVariant; 6] = [
+    HeadVariant::CreeperHead,
+    HeadVariant::DragonHead,
+    HeadVariant::PlayerHead,
+    HeadVariant::SkeletonSkull,
+    HeadVariant::WitherSkeletonSkull,
+    HeadVariant::ZombieHead,
+];
+const POTTED_PLANTS: [PottedPlant; 29] = [
+    PottedPlant::AcaciaSapling,
+    PottedPlant::Allium,
+    PottedPlant::AzureBluet,
+    PottedPlant::Bamboo,
+    PottedPlant::BirchSapling,
+    PottedPlant::BlueOrchid,
+    PottedPlant::BrownMushroom,
+    PottedPlant::Cactus,
+    PottedPlant::Cornflower,
+    PottedPlant::CrimsonFungus,
+    PottedPlant::CrimsonRoots,
+    PottedPlant::Dandelion,
+    PottedPlant::DarkOakSapling,
+    PottedPlant::DeadBush,
+    PottedPlant::Fern,
+    PottedPlant::JungleSapling,
+    PottedPlant::LilyOfTheValley,
+    PottedPlant::OakSapling,
+    PottedPlant::OxeyeDaisy,
+    PottedPlant::Poppy,
+    PottedPlant::RedMushroom,
+    PottedPlant::SpruceSapling,
+    PottedPlant::TulipOrange,
+    PottedPlant::TulipPink,
+    PottedPlant::TulipRed,
+    PottedPlant::TulipWhite,
+    PottedPlant::WarpedFungus,
+    PottedPlant::Warpedroots,
+    PottedPlant::WitherRose,
+];
+pub(crate) const BUTTON_MATERIALS: [ButtonMaterial; 10] = [
+    ButtonMaterial::Acacia,
+    ButtonMaterial::Birch,
+    ButtonMaterial::Crimson,
+    ButtonMaterial::DarkOak,
+    ButtonMaterial::Jungle,
+    ButtonMaterial::Oak,
+    ButtonMaterial::PolishedBlackstone,
+    ButtonMaterial::Spruce,
+    ButtonMaterial::Stone,
+    ButtonMaterial::Warped,
+];
+const CORAL_MATERIALS: [CoralMaterial; 5] = [
+    CoralMaterial::Bubble,
+    CoralMaterial::Brain,
+    CoralMaterial::Fire,
+    CoralMaterial::Horn,
+    CoralMaterial::Tube,
+];
+pub(crate) const DOOR_MATERIALS: [DoorMaterial; 9] = [
+    DoorMaterial::Acacia,
+    DoorMaterial::Birch,
+    DoorMaterial::Crimson,
+    DoorMaterial::DarkOak,
+    DoorMaterial::Iron,
+    DoorMaterial::Jungle,
+    DoorMaterial::Oak,
+    DoorMaterial::Spruce,
+    DoorMaterial::Warped,
+];
+pub(crate) const FENCE_MATERIALS: [FenceMaterial; 9] = [
+    FenceMaterial::Acacia,
+    FenceMaterial::Birch,
+    FenceMaterial::Crimson,
+    FenceMaterial::DarkOak,
+    FenceMaterial::Jungle,
+    FenceMaterial::NetherBrick,
+    FenceMaterial::Oak,
+    FenceMaterial::Spruce,
+    FenceMaterial::Warped,
+];
+const LEAVES_MATERIALS: [LeavesMaterial; 6] = [
+    LeavesMaterial::Acacia,
+    LeavesMaterial::Birch,
+    LeavesMaterial::DarkOak,
+    LeavesMaterial::Jungle,
+    LeavesMaterial::Oak,
+    LeavesMaterial::Spruce,
+];
+pub(crate) const PRESSURE_PLATE_MATERIALS: [PressurePlateMaterial; 10] = [
+    PressurePlateMaterial::Acacia,
+    PressurePlateMaterial::Birch,
+    PressurePlateMaterial::Crimson,
+    PressurePlateMaterial::DarkOak,
+    PressurePlateMaterial::Jungle,
+    PressurePlateMaterial::Oak,
+    PressurePlateMaterial::PolishedBlackstone,
+    PressurePlateMaterial::Spruce,
+    PressurePlateMaterial::Stone,
+    PressurePlateMaterial::Warped,
+];
+pub(crate) const SAPLING_MATERIALS: [SaplingMaterial; 6] = [
+    SaplingMaterial::Acacia,
+    SaplingMaterial::Birch,
+    SaplingMaterial::DarkOak,
+    SaplingMaterial::Jungle,
+    SaplingMaterial::Oak,
+    SaplingMaterial::Spruce,
+];
+pub(crate) const SLAB_MATERIALS: [SlabMaterial; 40] = [
+    SlabMaterial::Acacia,
+    SlabMaterial::Andesite,
+    SlabMaterial::Birch,
+    SlabMaterial::Blackstone,
+    SlabMaterial::Brick,
+    SlabMaterial::Cobblestone,
+    SlabMaterial::Crimson,
+    SlabMaterial::CutRedSandstone,
+    SlabMaterial::CutSandstone,
+    SlabMaterial::DarkOak,
+    SlabMaterial::DarkPrismarine,
+    SlabMaterial::Diorite,
+    SlabMaterial::EndStoneBrick,
+    SlabMaterial::Granite,
+    SlabMaterial::Jungle,
+    SlabMaterial::MossyCobblestone,
+    SlabMaterial::MossyStoneBrick,
+    SlabMaterial::NetherBrick,
+    SlabMaterial::Oak,
+    SlabMaterial::PetrifiedOak,
+    SlabMaterial::PolishedAndesite,
+    SlabMaterial::PolishedBlackstone,
+    SlabMaterial::PolishedBlackstoneBrick,
+    SlabMaterial::PolishedDiorite,
+    SlabMaterial::PolishedGranite,
+    SlabMaterial::Prismarine,
+    SlabMaterial::PrismarineBrick,
+    SlabMaterial::Purpur,
+    SlabMaterial::Quartz,
+    SlabMaterial::RedNetherBrick,
+    SlabMaterial::RedSandstone,
+    SlabMaterial::Sandstone,
+    SlabMaterial::SmoothQuartz,
+    SlabMaterial::SmoothRedSandstone,
+    SlabMaterial::SmoothSandstone,
+    SlabMaterial::SmoothStone,
+    SlabMaterial::Spruce,
+    SlabMaterial::Stone,
+    SlabMaterial::StoneBrick,
+    SlabMaterial::Warped,
+];
+pub(crate) const STAIR_MATERIALS: [StairMaterial; 36] = [
+    StairMaterial::Acacia,
+    StairMaterial::Andesite,
+    StairMaterial::Birch,
+    StairMaterial::Blackstone,
+    StairMaterial::Brick,
+    StairMaterial::Cobblestone,
+    StairMaterial::Crimson,
+    StairMaterial::DarkOak,
+    StairMaterial::DarkPrismarine,
+    StairMaterial::Diorite,
+    StairMaterial::EndStoneBrick,
+    StairMaterial::Granite,
+    StairMaterial::Jungle,
+    StairMaterial::MossyCobblestone,
+    StairMaterial::MossyStoneBrick,
+    StairMaterial::NetherBrick,
+    StairMaterial::Oak,
+    StairMaterial::PolishedAndesite,
+    StairMaterial::PolishedBlackstone,
+    StairMaterial::PolishedBlackstoneBrick,
+    StairMaterial::PolishedDiorite,
+    StairMaterial::PolishedGranite,
+    StairMaterial::Prismarine,
+    StairMaterial::PrismarineBrick,
+    StairMaterial::Purpur,
+    StairMaterial::Quartz,
+    StairMaterial::RedNetherBrick,
+    StairMaterial::RedSandstone,
+    StairMaterial::Sandstone,
+    StairMaterial::SmoothQuartz,
+    StairMaterial::SmoothRedSandstone,
+    StairMaterial::SmoothSandstone,
+    StairMaterial::Spruce,
+    StairMaterial::Stone,
+    StairMaterial::StoneBrick,
+    StairMaterial::Warped,
+];
+const WALL_MATERIALS: [WallMaterial; 17] = [
+    WallMaterial::Andesite,
+    WallMaterial::Blackstone,
+    WallMaterial::Brick,
+    WallMaterial::Cobblestone,
+    WallMaterial::Diorite,
+    WallMaterial::EndStoneBrick,
+    WallMaterial::Granite,
+    WallMaterial::MossyCobblestone,
+    WallMaterial::MossyStoneBrick,
+    WallMaterial::NetherBrick,
+    WallMaterial::PolishedBlackstone,
+    WallMaterial::PolishedBlackstoneBrick,
+    WallMaterial::Prismarine,
+    WallMaterial::RedNetherBrick,
+    WallMaterial::RedSandstone,
+    WallMaterial::Sandstone,
+    WallMaterial::StoneBrick,
+];
+pub(crate) const WOOD_MATERIALS: [WoodMaterial; 8] = [
+    WoodMaterial::Acacia,
+    WoodMaterial::Birch,
+    WoodMaterial::Crimson,
+    WoodMaterial::DarkOak,
+    WoodMaterial::Jungle,
+    WoodMaterial::Oak,
+    WoodMaterial::Spruce,
+    WoodMaterial::Warped,
+];
+
+/// Blocks without properties, by name.
+static SIMPLE_BLOCKS: [(&str, Block); 136] = [
+    ("air", Block::Air),
+    ("ancient_debris", Block::AncientDebris),
+    ("andesite", Block::Andesite),
+    ("barrier", Block::Barrier),
+    ("bedrock", Block::Bedrock),
+    ("blackstone", Block::Blackstone),
+    ("coal_block", Block::BlockOfCoal),
+    ("diamond_block", Block::BlockOfDiamond),
+    ("emerald_block", Block::BlockOfEmerald),
+    ("gold_block", Block::BlockOfGold),
+    ("iron_block", Block::BlockOfIron),
+    ("netherite_block", Block::BlockOfNetherite),
+    ("quartz_block", Block::BlockOfQuartz),
+    ("redstone_block", Block::BlockOfRedstone),
+    ("slime_block", Block::BlockOfSlime),
+    ("blue_ice", Block::BlueIce),
+    ("bookshelf", Block::Bookshelf),
+    ("bricks", Block::BrickBlock),
+    ("brown_mushroom", Block::BrownMushroom),
+    ("cartography_table", Block::CartographyTable),
+    ("cave_air", Block::CaveAir),
+    ("chiseled_nether_bricks", Block::ChiseledNetherBricks),
+    (
+        "chiseled_polished_blackstone",
+        Block::ChiseledPolishedBlackstone,
+    ),
+    ("chiseled_quartz_block", Block::ChiseledQuartzBlock),
+    ("chiseled_red_sandstone", Block::ChiseledRedSandstone),
+    ("chiseled_sandstone", Block::ChiseledSandstone),
+    ("chiseled_stone_bricks", Block::ChiseledStoneBricks),
+    ("chorus_plant", Block::ChorusPlant),
+    ("clay", Block::Clay),
+    ("coal_ore", Block::CoalOre),
+    ("coarse_dirt", Block::CoarseDirt),
+    ("cobblestone", Block::Cobblestone),
+    ("cobweb", Block::Cobweb),
+    ("cracked_nether_bricks", Block::CrackedNetherBricks),
+    (
+        "cracked_polished_blackstone_bricks",
+        Block::CrackedPolishedBlackstoneBricks,
+    ),
+    ("cracked_stone_bricks", Block::CrackedStoneBricks),
+    ("crafting_table", Block::CraftingTable),
+    ("crimson_fungus", Block::CrimsonFungus),
+    ("crimson_nylium", Block::CrimsonNylium),
+    ("crimson_roots", Block::CrimsonRoots),
+    ("crying_obsidian", Block::CryingObsidian),
+    ("cut_red_sandstone", Block::CutRedSandstone),
+    ("cut_sandstone", Block::CutSandstone),
+    ("dark_prismarine", Block::DarkPrismarine),
+    ("dead_bush", Block::DeadBush),
+    ("diamond_ore", Block::DiamondOre),
+    ("diorite", Block::Diorite),
+    ("dirt", Block::Dirt),
+    ("dirt_path", Block::GrassPath),
+    ("dragon_egg", Block::DragonEgg),
+    ("dried_kelp_block", Block::DriedKelpBlock),
+    ("emerald_ore", Block::EmeraldOre),
+    ("end_gateway", Block::EndGateway),
+    ("end_portal", Block::EndPortal),
+    ("end_stone", Block::EndStone),
+    ("end_stone_bricks", Block::EndStoneBricks),
+    ("fletching_table", Block::FletchingTable),
+    ("frosted_ice", Block::FrostedIce),
+    ("gilded_blackstone", Block::GildedBlackstone),
+    ("glowstone", Block::Glowstone),
+    ("gold_ore", Block::GoldOre),
+    ("granite", Block::Granite),
+    ("grass_block", Block::GrassBlock),
+    ("gravel", Block::Gravel),
+    ("honey_block", Block::HoneyBlock),
+    ("honeycomb_block", Block::HoneycombBlock),
+    ("ice", Block::Ice),
+    (
+        "infested_chiseled_stone_bricks",
+        Block::InfestedChiseledStoneBricks,
+    ),
+    ("infested_cobblestone", Block::InfestedCobblestone),
+    (
+        "infested_cracked_stone_bricks",
+        Block::InfestedCrackedStoneBricks,
+    ),
+    (
+        "infested_mossy_stone_bricks",
+        Block::InfestedMossyStoneBricks,
+    ),
+    ("infested_stone", Block::InfestedStone),
+    ("infested_stone_bricks", Block::InfestedStoneBricks),
+    ("iron_ore", Block::IronOre),
+    ("lapis_block", Block::LapisLazuliBlock),
+    ("lapis_ore", Block::LapisLazuliOre),
+    ("lily_pad", Block::LilyPad),
+    ("lodestone", Block::LodeStone),
+    ("magma_block", Block::MagmaBlock),
+    ("melon", Block::Melon),
+    ("mossy_cobblestone", Block::MossyCobblestone),
+    ("mossy_stone_bricks", Block::MossyStoneBricks),
+    ("mycelium", Block::Mycelium),
+    ("nether_bricks", Block::NetherBricks),
+    ("nether_gold_ore", Block::NetherGoldOre),
+    ("nether_quartz_ore", Block::QuartzOre),
+    ("nether_sprouts", Block::NetherSprouts),
+    ("nether_wart_block", Block::NetherWartBlock),
+    ("netherrack", Block::Netherrack),
+    ("obsidian", Block::Obsidian),
+    ("packed_ice", Block::PackedIce),
+    ("podzol", Block::Podzol),
+    ("polished_andesite", Block::PolishedAndesite),
+    ("polished_blackstone", Block::PolishedBlackstone),
+    (
+        "polished_blackstone_bricks",
+        Block::PolishedBlackstoneBricks,
+    ),
+    ("polished_diorite", Block::PolishedDiorite),
+    ("polished_granite", Block::PolishedGranite),
+    ("prismarine", Block::Prismarine),
+    ("prismarine_bricks", Block::PrismarineBricks),
+    ("purpur_block", Block::PurpurBlock),
+    ("quartz_bricks", Block::QuartzBricks),
+    ("red_mushroom", Block::RedMushroom),
+    ("red_nether_bricks", Block::RedNetherBricks),
+    ("red_sand", Block::RedSand),
+    ("red_sandstone", Block::RedSandstone),
+    ("redstone_lamp", Block::RedstoneLamp),
+    ("redstone_ore", Block::RedstoneOre),
+    ("redstone_wire", Block::RedstoneWire),
+    ("sand", Block::Sand),
+    ("sandstone", Block::Sandstone),
+    ("sea_lantern", Block::SeaLantern),
+    ("shroomlight", Block::Shroomlight),
+    ("smithing_table", Block::SmithingTable),
+    ("smooth_quartz", Block::SmoothQuartz),
+    ("smooth_red_sandstone", Block::SmoothRedSandstone),
+    ("smooth_sandstone", Block::SmoothSandstone),
+    ("smooth_stone", Block::SmoothStone),
+    ("snow_block", Block::SnowBlock),
+    ("soul_sand", Block::SoulSand),
+    ("soul_soil", Block::SoulSoil),
+    ("spawner", Block::Spawner),
+    ("sponge", Block::Sponge),
+    ("stone", Block::Stone),
+    ("stone_bricks", Block::StoneBricks),
+    ("structure_block", Block::StructureBlock),
+    ("structure_void", Block::StructureVoid),
+    ("target", Block::Target),
+    ("tnt", Block::TNT),
+    ("tripwire", Block::Tripwire),
+    ("twisting_vines_plant", Block::TwistingVinesPlant),
+    ("warped_fungus", Block::WarpedFungus),
+    ("warped_nylium", Block::WarpedNyliym),
+    ("warped_roots", Block::WarpedRoots),
+    ("warped_wart_block", Block::WarpedWartBlock),
+    ("weeping_vines_plant", Block::WeepingVinesPlant),
+    ("wet_sponge", Block::WetSponge),
+];
+
+static SIMPLE_BLOCKS_BY_NAME: Lazy<HashMap<&'static str, &'static Block>> = Lazy::new(|| {
+    SIMPLE_BLOCKS
+        .iter()
+        .map(|(name, block)| (*name, block))
+        .collect()
+});
+
+static SIMPLE_BLOCK_NAMES: Lazy<HashMap<Discriminant<Block>, &'static str>> = Lazy::new(|| {
+    SIMPLE_BLOCKS
+        .iter()
+        .map(|(name, block)| (std::mem::discriminant(block), *name))
+        .collect()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(block: Block) {
+        let version = McVersion::from_str("1.16.5").unwrap();
+        let state = BlockState::from_block(&block, version);
+        assert_eq!(state.to_block(version), block, "state: {:?}", state);
+    }
+
+    #[test]
+    fn unknown_states_are_serialised_by_name() {
+        let version = McVersion::from_str("1.16.5").unwrap();
+        let mut properties = BTreeMap::new();
+        properties.insert("powered".to_string(), "true".to_string());
+        let state = BlockState {
+            name: "othermod:machine".into(),
+            properties,
+        };
+        let block = state.to_block(version);
+        let id = match block {
+            Block::Unknown(Some(id)) => id,
+            _ => panic!("Unexpected block {:?}", block),
+        };
+
+        let json = serde_json::to_string(&block).unwrap();
+        assert!(json.contains("othermod:machine"));
+        assert!(!json.contains(&id.to_string()));
+        assert_eq!(serde_json::from_str::<Block>(&json).unwrap(), block);
+
+        // As serialised by another process, with its own interned ids
+        let json = r#"{"Unknown":{"name":"othermod:generator","properties":{"facing":"north"}}}"#;
+        let block: Block = serde_json::from_str(json).unwrap();
+        let state = BlockState::from_block(&block, version);
+        assert_eq!(state.name, "othermod:generator");
+        assert_eq!(state.properties["facing"], "north");
+
+        // Numeric ids of pre-flattening saves are kept as they are
+        for block in &[Block::Unknown(Some(7)), Block::Unknown(None)] {
+            let json = serde_json::to_string(block).unwrap();
+            assert_eq!(&serde_json::from_str::<Block>(&json).unwrap(), block);
+        }
+    }
+
+    #[test]
+    fn blocks_survive_conversion_to_block_states() {
+        round_trip(Block::Stone);
+        round_trip(Block::GrassPath);
+        round_trip(Block::DaylightDetector);
+        round_trip(Block::InvertedDaylightDetector);
+        round_trip(Block::bottom_slab(Material::SmoothStone));
+        round_trip(Block::acacia_log(Axis3::X));
+        round_trip(Block::Log(Log {
+            material: WoodMaterial::Crimson,
+            alignment: None,
+            stripped: true,
+        }));
+        round_trip(Block::Flower(Flower::TulipPink));
+        round_trip(Block::Flower(Flower::PeonyTop));
+        round_trip(Block::FlowerPot(FlowerPot::new_with_plant(
+            PottedPlant::Warpedroots,
+        )));
+        round_trip(Block::Stairs(Stair {
+            material: StairMaterial::StoneBrick,
+            position: Edge8::UpWest,
+            waterlogged: false,
+        }));
+        round_trip(Block::Trapdoor(Trapdoor {
+            material: DoorMaterial::Iron,
+            hinge_at: Edge8::DownNorth,
+            open: true,
+            waterlogged: false,
+        }));
+        round_trip(Block::Lever(
+            SurfaceRotation12::UpFacingEast,
+            OnOffState::On,
+        ));
+        round_trip(Block::Torch {
+            attached: Surface5::West,
+        });
+        round_trip(Block::Water {
+            falling: true,
+            level: Int1Through7::new(3).unwrap(),
+        });
+        round_trip(Block::Wool {
+            colour: Colour::LightGray,
+        });
+    }
+
+    #[test]
+    fn renamed_blocks_use_names_of_save_version() {
+        let old = McVersion::from_str("1.13.2").unwrap();
+        let state = BlockState::from_block(&Block::bottom_slab(Material::SmoothStone), old);
+        assert_eq!(state.name, "minecraft:stone_slab");
+        let state = BlockState::from_block(&Block::GrassPath, old);
+        assert_eq!(state.name, "minecraft:grass_path");
+        assert_eq!(state.to_block(old), Block::GrassPath);
+    }
+
+    #[test]
+    fn unknown_block_states_are_preserved() {
+        let version = McVersion::from_str("1.18").unwrap();
+        let state = BlockState::new("deepslate").with("axis", "y");
+        let block = state.to_block(version);
+        assert!(matches!(block, Block::Unknown(Some(_))));
+        assert_eq!(BlockState::from_block(&block, version), state);
+    }
+}
@@ -25,6 +25,54 @@ impl BoundingBox {
     pub fn from_coords(p1: (i64, i64, i64), p2: (i64, i64, i64)) -> Self {
         Self::from_block_coords(p1.into(), p2.into())
     }
+
+    /// The corner with the lowest coordinates.
+    pub fn min(&self) -> BlockCoord {
+        BlockCoord(self.x_min, self.y_min, self.z_min)
+    }
+
+    /// The corner with the highest coordinates.
+    pub fn max(&self) -> BlockCoord {
+        BlockCoord(self.x_max, self.y_max, self.z_max)
+    }
+
+    pub fn contains(&self, at: BlockCoord) -> bool {
+        (self.x_min..=self.x_max).contains(&at.0)
+            && (self.y_min..=self.y_max).contains(&at.1)
+            && (self.z_min..=self.z_max).contains(&at.2)
+    }
+
+    /// Returns the smallest bounding box containing both bounding boxes.
+    pub fn enclosing(&self, other: &Self) -> Self {
+        Self {
+            x_min: i64::min(self.x_min, other.x_min),
+            x_max: i64::max(self.x_max, other.x_max),
+            y_min: i64::min(self.y_min, other.y_min),
+            y_max: i64::max(self.y_max, other.y_max),
+            z_min: i64::min(self.z_min, other.z_min),
+            z_max: i64::max(self.z_max, other.z_max),
+        }
+    }
+
+    /// Returns the overlap of the bounding boxes, or `None` if they do not overlap.
+    pub fn overlap(&self, other: &Self) -> Option<Self> {
+        let overlap = Self {
+            x_min: i64::max(self.x_min, other.x_min),
+            x_max: i64::min(self.x_max, other.x_max),
+            y_min: i64::max(self.y_min, other.y_min),
+            y_max: i64::min(self.y_max, other.y_max),
+            z_min: i64::max(self.z_min, other.z_min),
+            z_max: i64::min(self.z_max, other.z_max),
+        };
+        if overlap.x_min <= overlap.x_max
+            && overlap.y_min <= overlap.y_max
+            && overlap.z_min <= overlap.z_max
+        {
+            Some(overlap)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
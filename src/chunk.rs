@@ -1,18 +1,25 @@
+mod extended_height;
+mod flattening;
+mod palette;
 mod pre_flattening;
 
-use std::str::FromStr;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::time::SystemTime;
 
 use crate::biome::Biome;
-use crate::block::Block;
+use crate::block::{Block, LecternBook};
 use crate::block_cuboid::BlockCuboid;
-use crate::block_entity::BlockEntity;
-use crate::coordinates::ChunkCoord;
-use crate::height_map::HeightMap;
+use crate::block_state::BlockState;
+use crate::connections::ConnectionStates;
+use crate::coordinates::{BlockCoord, ChunkCoord};
+use crate::entity::Entity;
+use crate::height_map::{HeightMap, HeightMapKind};
 use crate::light_cuboid::LightCuboid;
+use crate::lighting::{self, LightRules};
 use crate::mc_version::McVersion;
 use crate::nbt_lookup::*;
-use crate::utils;
+use crate::save_format::ChunkFormat;
+use crate::scheduled_tick::ScheduledTick;
 
 #[derive(Clone)]
 pub enum RawChunkData {
@@ -23,7 +30,7 @@ pub enum RawChunkData {
 }
 
 impl RawChunkData {
-    fn to_nbt(&self) -> nbt::Blob {
+    pub(crate) fn to_nbt(&self) -> nbt::Blob {
         match self {
             RawChunkData::GZip(chunk_data) => {
                 let mut cursor = std::io::Cursor::new(chunk_data);
@@ -44,7 +51,7 @@ impl RawChunkData {
         }
     }
 
-    fn new_zlib(nbt: &nbt::Blob) -> Self {
+    pub(crate) fn new_zlib(nbt: &nbt::Blob) -> Self {
         let mut chunk_data: Vec<u8> = Vec::new();
         nbt.to_zlib_writer(&mut chunk_data)
             .unwrap_or_else(|err| panic!("Bad chunk write: {}", err));
@@ -56,23 +63,61 @@ pub struct Chunk {
     data_version: McVersion,
     global_pos: ChunkCoord,
     _last_update: i64,
-    //entities: HashMap<BlockCoord, Vec<Entity>>,
+    /// Lowest y coordinate of the chunk.
+    min_y: i64,
+    /// Entities of the chunk, positioned in global block coordinates.
+    pub(crate) entities: Vec<Entity>,
+    /// Scheduled block and fluid ticks, positioned in global block coordinates.
+    pub(crate) scheduled_ticks: Vec<ScheduledTick>,
+    /// Height maps as read from the save, by kind.
+    height_maps: BTreeMap<HeightMapKind, HeightMap>,
     pub(crate) blocks: BlockCuboid,
     pub(crate) block_light: LightCuboid,
     pub(crate) sky_light: LightCuboid,
     biomes: Option<Vec<Biome>>,
+    /// Neighbour-derived block state properties, by chunk local coordinates,
+    /// for blocks written in post-flattening chunk formats.
+    connection_states: HashMap<BlockCoord, BTreeMap<String, String>>,
+    /// Chunk data as read from a post-flattening save.
+    original: Option<Box<OriginalChunk>>,
+}
+
+/// Chunk data as read from a post-flattening save, for writing back unchanged
+/// blocks with their original block states and block entities.
+struct OriginalChunk {
+    /// The full chunk NBT, with everything not handled by mcprogedit.
+    nbt: nbt::Blob,
+    /// Blocks as originally decoded from the block states.
+    blocks: BlockCuboid,
+    /// All block states found in the chunk.
+    palette: Vec<BlockState>,
+    /// Index into `palette` for each block, in "y, z, x" order.
+    states: Vec<u16>,
+    /// Block entity NBT, by chunk local coordinates.
+    block_entities: HashMap<BlockCoord, nbt::Value>,
 }
 
 impl Chunk {
-    pub fn new(chunk_position: ChunkCoord) -> Self {
+    /// Creates an empty chunk, for saving in a world of the given version.
+    pub fn new(chunk_position: ChunkCoord, data_version: McVersion) -> Self {
+        let (min_y, height) = match ChunkFormat::for_version(data_version) {
+            Ok(ChunkFormat::ExtendedHeight) => (-64, 384),
+            _ => (0, 256),
+        };
         Chunk {
-            data_version: McVersion::from_str("1.12.2").unwrap(),
+            data_version,
             global_pos: chunk_position,
             _last_update: 0,
-            blocks: BlockCuboid::new((16, 256, 16)),
-            block_light: LightCuboid::new((16, 256, 16)),
-            sky_light: LightCuboid::new((16, 256, 16)),
+            min_y,
+            blocks: BlockCuboid::new((16, height, 16)),
+            block_light: LightCuboid::new((16, height, 16)),
+            sky_light: LightCuboid::new((16, height, 16)),
             biomes: None,
+            entities: Vec::new(),
+            scheduled_ticks: Vec::new(),
+            height_maps: BTreeMap::new(),
+            connection_states: HashMap::new(),
+            original: None,
         }
     }
 
@@ -80,135 +125,224 @@ impl Chunk {
         &self.global_pos
     }
 
-    /// Generates Zlib compressed raw chunk data from the chunk object.
-    // NB only pre-flattening chunk saving as of yet
-    pub fn raw_chunk_zlib(&self) -> RawChunkData {
-        // Time of update is now
-        let last_update = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+    /// Lowest y coordinate of the chunk.
+    pub fn min_y(&self) -> i64 {
+        self.min_y
+    }
 
-        // Biomes needs some extra handling...
-        let biomes: Vec<u8> = match &self.biomes {
-            Some(biomes) => biomes.iter().map(|biome| u8::from(*biome)).collect(),
-            None => vec![Biome::Plains.into(); 256],
-        };
-        let biomes = utils::vec_u8_into_vec_i8(biomes);
-
-        // Various calculations
-        let sections = self.pre_flattening_sections();
-        let tile_entities = self.pre_flattening_tile_entities();
-
-        // Create the Level compund tag
-        let mut level: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(13);
-        level.insert("xPos".into(), nbt::Value::Int(self.global_pos.0 as i32));
-        level.insert("zPos".into(), nbt::Value::Int(self.global_pos.1 as i32));
-        level.insert("LastUpdate".into(), nbt::Value::Long(last_update as i64));
-        level.insert("LightPopulated".into(), nbt::Value::Byte(1));
-        level.insert("TerrainPopulated".into(), nbt::Value::Byte(1));
-        level.insert("V".into(), nbt::Value::Byte(1));
-        level.insert("InhabitedTime".into(), nbt::Value::Long(0));
-        level.insert("Biomes".into(), nbt::Value::ByteArray(biomes));
-        level.insert(
-            "HeightMap".into(),
-            nbt::Value::IntArray(self.height_map().into()),
-        );
-        level.insert("Sections".into(), sections);
-        // TODO Add proper handling of entities, instead of forgetting them:
-        level.insert(
-            "Entities".into(),
-            nbt::Value::List(Vec::<nbt::Value>::new()),
-        );
-        level.insert("TileEntities".into(), tile_entities);
-        // TODO Also insert "TileTicks" (optional)
-
-        // Create and return nbt blob
-        let mut nbt = nbt::Blob::new();
-        nbt.insert("DataVersion", self.data_version.id()).unwrap();
-        nbt.insert("Level", nbt::Value::Compound(level)).unwrap();
-        RawChunkData::new_zlib(&nbt)
-    }
-
-    /// Creates a chunk from raw chunk (NBT) data.
-    // NB only pre-flattening chunk loading as of yet
-    // TODO Move pre-flattening import implementation to the pre-flattening file.
-    pub fn from_raw_chunk_data(data: &RawChunkData) -> Self {
-        let nbt = data.to_nbt();
+    pub fn data_version(&self) -> McVersion {
+        self.data_version
+    }
 
-        const THE_FLATTENING: &str = "17w47a";
-        let data_version = nbt_blob_lookup_int(&nbt, "DataVersion")
-            .map(McVersion::from_id)
-            .unwrap();
-        assert!(data_version < McVersion::from_str(THE_FLATTENING).unwrap());
+    /// Generates Zlib compressed raw chunk data from the chunk object,
+    /// in the chunk format of the chunk's data version.
+    ///
+    /// # Panics
+    /// Panics if chunks of the chunk's data version cannot be written.
+    pub fn raw_chunk_zlib(&self) -> RawChunkData {
+        RawChunkData::new_zlib(&self.to_nbt())
+    }
 
-        let x_pos = nbt_blob_lookup_int(&nbt, "Level/xPos").unwrap();
-        let z_pos = nbt_blob_lookup_int(&nbt, "Level/zPos").unwrap();
-        let global_pos: ChunkCoord = (x_pos.into(), z_pos.into()).into();
+    /// Generates the NBT data of the chunk, in the chunk format of the chunk's data version.
+    ///
+    /// # Panics
+    /// Panics if chunks of the chunk's data version cannot be written.
+    pub(crate) fn to_nbt(&self) -> nbt::Blob {
+        match ChunkFormat::for_writing(self.data_version) {
+            Ok(ChunkFormat::PreFlattening) => self.pre_flattening_nbt(),
+            Ok(ChunkFormat::Flattening) => self.flattening_nbt(),
+            Ok(ChunkFormat::ExtendedHeight) => self.extended_height_nbt(),
+            Err(err) => panic!("Cannot write chunk {:?}: {}", self.global_pos, err),
+        }
+    }
 
-        let _last_update = nbt_blob_lookup_long(&nbt, "Level/LastUpdate").unwrap();
+    /// Prepares a chunk read from an older save for saving as a new chunk of
+    /// `data_version`, spanning `height` blocks upwards from `min_y`.
+    ///
+    /// Blocks and light keep their y coordinates. Blocks outside of the old
+    /// height range are air.
+    pub(crate) fn retarget(&mut self, data_version: McVersion, min_y: i64, height: usize) {
+        let offset = self.min_y - min_y;
+        let dim = (16, height, 16);
 
-        let tile_entities = nbt_blob_lookup(&nbt, "Level/TileEntities")
-            .unwrap_or_else(|| panic!("Level/TileEntities not found"));
-        let mut block_entities = BlockEntity::map_from_nbt_list(&tile_entities);
+        let mut blocks = BlockCuboid::new_filled(dim, Block::Air);
+        blocks.paste((0, offset, 0), &self.blocks);
+        let mut block_light = LightCuboid::new(dim);
+        block_light.paste((0, offset, 0).into(), &self.block_light);
+        let mut sky_light = LightCuboid::new(dim);
+        sky_light.paste((0, offset, 0).into(), &self.sky_light);
 
-        let biomes: Option<Vec<Biome>> = nbt_blob_lookup_byte_array(&nbt, "Level/Biomes")
-            .map(|biomes| biomes.iter().map(|biome| Biome::from(*biome as u8)).collect());
+        self.data_version = data_version;
+        self.min_y = min_y;
+        self.blocks = blocks;
+        self.block_light = block_light;
+        self.sky_light = sky_light;
+        self.height_maps.clear();
+        self.connection_states = self
+            .connection_states
+            .drain()
+            .map(|(at, properties)| (at + BlockCoord(0, offset, 0), properties))
+            .collect();
+        self.original = None;
+    }
 
-        let sections = nbt_blob_lookup_list(&nbt, "Level/Sections")
-            .unwrap_or_else(|| panic!("Level/Sections not found"));
+    /// Adds the neighbour-derived block states of an excerpt pasted into the
+    /// chunk at `offset`, for the blocks that end up within the chunk.
+    pub(crate) fn paste_connection_states(
+        &mut self,
+        offset: (i64, i64, i64),
+        connection_states: &ConnectionStates,
+    ) {
+        let (x_dim, y_dim, z_dim) = self.blocks.dim();
+        for (at, properties) in connection_states.iter() {
+            let at = *at + offset.into();
+            if (0..x_dim as i64).contains(&at.0)
+                && (0..y_dim as i64).contains(&at.1)
+                && (0..z_dim as i64).contains(&at.2)
+            {
+                self.connection_states.insert(at, properties.clone());
+            }
+        }
+    }
 
-        /*
-        let height_map = nbt_blob_lookup(&nbt, "Level/HeightMap")
-            .unwrap_or_else(|| panic!("Level/HeightMap not found"));
-        println!("Height map: {:#?}", height_map);
-        */
+    /// Recomputes the block light and sky light of the chunk, as lit by the sky
+    /// above it, and by the light levels along the facing sides of the
+    /// neighbouring chunks.
+    ///
+    /// `neighbours` are the chunks to the west, east, north and south, in that
+    /// order, or `None` where there is no neighbouring chunk to take light from.
+    pub(crate) fn recompute_light(&mut self, neighbours: [Option<&Chunk>; 4]) {
+        let (_, y_dim, _) = self.blocks.dim();
+        let [west, east, north, south] = neighbours;
+        let light_at = |chunk: Option<&Chunk>, at: BlockCoord| {
+            chunk.and_then(|chunk| {
+                Some((
+                    chunk.block_light.light_level_at(at)?,
+                    chunk.sky_light.light_level_at(at)?,
+                ))
+            })
+        };
+        let border = |at: BlockCoord| match at {
+            BlockCoord(_, y, _) if y == y_dim as i64 => Some((0, 15)),
+            BlockCoord(-1, y, z) => light_at(west, (15, y, z).into()),
+            BlockCoord(16, y, z) => light_at(east, (0, y, z).into()),
+            BlockCoord(x, y, -1) => light_at(north, (x, y, 15).into()),
+            BlockCoord(x, y, 16) => light_at(south, (x, y, 0).into()),
+            _ => None,
+        };
+        let rules = LightRules::for_version(self.data_version);
+        let (block_light, sky_light) = lighting::light(&self.blocks, rules, border);
+        self.block_light = block_light;
+        self.sky_light = sky_light;
+    }
 
-        // Fist pass: Prepare pseudo bock entities for block data that is stored
-        // in one block but used for another. This may cross section boundaries.
-        for section in &sections {
-            block_entities.extend(
-                Chunk::pre_flattening_pseudo_block_entities(section, &global_pos).into_iter(),
-            );
-        }
+    /// The biome of the block column at chunk local coordinates `(x, z)`.
+    fn biome_at(&self, x: usize, z: usize) -> Biome {
+        self.biomes
+            .as_ref()
+            .and_then(|biomes| biomes.get(z * 16 + x).copied())
+            .unwrap_or(Biome::Plains)
+    }
 
-        // Second pass: Collect the full set of (finished) blocks
-        let mut block_cuboid = BlockCuboid::new_filled((16, 256, 16), Block::Air);
-        for section in &sections {
-            // TODO rename to pre_flattening_fill_block_cuboid_from_section
-            Chunk::pre_flattening_section_into_block_cuboid(
-                section,
-                &block_entities,
-                &global_pos,
-                &mut block_cuboid,
-            );
-        }
+    /// Creates a chunk from raw chunk (NBT) data, in the chunk format of the
+    /// data version found in the chunk.
+    ///
+    /// # Panics
+    /// Panics if the data version is missing, or not of a supported version.
+    pub fn from_raw_chunk_data(data: &RawChunkData) -> Self {
+        let nbt = data.to_nbt();
 
-        // Get block light and sky light data out from the sections
-        let mut block_light = LightCuboid::new((16, 256, 16));
-        let mut sky_light = LightCuboid::new((16, 256, 16));
+        let data_version = nbt_blob_lookup_int(&nbt, "DataVersion")
+            .map(McVersion::from_id)
+            .unwrap_or_else(|| panic!("Chunk DataVersion not found"));
 
-        for section in &sections {
-            Chunk::pre_flattening_fill_light_cuboids_from_section(
-                section,
-                &mut block_light,
-                &mut sky_light,
-            );
+        match ChunkFormat::for_version(data_version) {
+            Ok(ChunkFormat::PreFlattening) => Self::from_pre_flattening_nbt(&nbt, data_version),
+            Ok(ChunkFormat::Flattening) => Self::from_flattening_nbt(nbt, data_version),
+            Ok(ChunkFormat::ExtendedHeight) => Self::from_extended_height_nbt(nbt, data_version),
+            Err(err) => panic!("Cannot read chunk: {}", err),
         }
+    }
 
-        // Return chunk
-        Self {
-            data_version,
-            global_pos,
-            _last_update,
-            blocks: block_cuboid,
-            block_light,
-            sky_light,
-            biomes,
+    /// Generates the block entity NBT value for `block`, located at global block
+    /// coordinates `at`, if it has a block entity in the given chunk format.
+    /// Items are stored as in saves of `data_version`.
+    fn block_entity_nbt(
+        block: &Block,
+        at: (i32, i32, i32),
+        format: ChunkFormat,
+        data_version: McVersion,
+    ) -> Option<nbt::Value> {
+        match block {
+            Block::Banner(banner) => banner.to_block_entity(at).to_nbt_value(data_version),
+            Block::Beacon(beacon) => beacon.to_block_entity(at).to_nbt_value(data_version),
+            Block::Chest(chest) => chest.to_block_entity(at).to_nbt_value(data_version),
+            Block::Dispenser(dispenser) => dispenser.to_block_entity(at).to_nbt_value(data_version),
+            Block::Dropper(dropper) => dropper.to_block_entity(at).to_nbt_value(data_version),
+            Block::Furnace(furnace) => furnace.to_block_entity(at).to_nbt_value(data_version),
+            Block::Hopper(hopper) => hopper.to_block_entity(at).to_nbt_value(data_version),
+            Block::Jukebox(jukebox) => jukebox.to_block_entity(at).to_nbt_value(data_version),
+            Block::Lectern { book, .. } => {
+                LecternBook::to_block_entity(book.as_deref(), at).to_nbt_value(data_version)
+            }
+            // Note blocks store their pitch in the block state after the flattening
+            Block::Noteblock(noteblock) if format == ChunkFormat::PreFlattening => {
+                noteblock.to_block_entity(at).to_nbt_value(data_version)
+            }
+            Block::ShulkerBox(shulker_box) => {
+                shulker_box.to_block_entity(at).to_nbt_value(data_version)
+            }
+            Block::TrappedChest(chest) => {
+                let mut value = chest.to_block_entity(at).to_nbt_value(data_version)?;
+                // Trapped chests got their own block entity id with the flattening
+                if format != ChunkFormat::PreFlattening {
+                    if let nbt::Value::Compound(tags) = &mut value {
+                        tags.insert(
+                            "id".into(),
+                            nbt::Value::String("minecraft:trapped_chest".into()),
+                        );
+                    }
+                }
+                Some(value)
+            }
+            // TODO add handling of other blocks with entities
+            _ => None,
         }
     }
 
+    /// Seconds since the Unix epoch, for the LastUpdate tag.
+    fn last_update_now() -> i64 {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64
+    }
+
     fn height_map(&self) -> HeightMap {
         self.blocks.height_map()
     }
+
+    /// The "Heightmaps" compound of post-flattening chunks, computed from the
+    /// blocks, for the height maps of fully generated chunks and any other
+    /// kinds of height maps the chunk was read with.
+    fn height_maps_value(&self, padded: bool) -> nbt::Value {
+        let (_, chunk_height, _) = self.blocks.dim();
+        let kinds: BTreeSet<HeightMapKind> = self
+            .height_maps
+            .keys()
+            .chain(HeightMapKind::FULL_CHUNK.iter())
+            .copied()
+            .collect();
+        let height_maps = kinds
+            .iter()
+            .map(|kind| {
+                let height_map = self.blocks.height_map_of(*kind);
+                (
+                    kind.nbt_name().to_string(),
+                    height_map.to_packed_nbt_value(chunk_height, padded),
+                )
+            })
+            .collect();
+        nbt::Value::Compound(height_maps)
+    }
 }
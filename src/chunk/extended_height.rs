@@ -0,0 +1,166 @@
+//! The chunk format used from Minecraft 1.18 (21w43a), with extended world height.
+
+use crate::chunk::palette::{self, PaletteChunk, PaletteSection};
+use crate::chunk::Chunk;
+use crate::coordinates::ChunkCoord;
+use crate::mc_version::McVersion;
+use crate::nbt_lookup::*;
+use crate::save_format::ChunkFormat;
+use crate::scheduled_tick::{self, TickKind};
+use crate::utils;
+
+impl Chunk {
+    /// Lowest section y index of overworld chunks.
+    const EXTENDED_HEIGHT_MIN_SECTION: i32 = -4;
+    /// Height of overworld chunks.
+    const EXTENDED_HEIGHT_HEIGHT: usize = 384;
+
+    /// Creates a chunk from NBT data in the extended height chunk format.
+    pub(crate) fn from_extended_height_nbt(nbt: nbt::Blob, data_version: McVersion) -> Self {
+        let x_pos = nbt_blob_lookup_int(&nbt, "xPos").unwrap_or_else(|| panic!("xPos not found"));
+        let z_pos = nbt_blob_lookup_int(&nbt, "zPos").unwrap_or_else(|| panic!("zPos not found"));
+        let global_pos: ChunkCoord = (x_pos.into(), z_pos.into()).into();
+
+        let min_section =
+            nbt_blob_lookup_int(&nbt, "yPos").unwrap_or(Self::EXTENDED_HEIGHT_MIN_SECTION);
+        let last_update = nbt_blob_lookup_long(&nbt, "LastUpdate").unwrap_or(0);
+
+        let sections = nbt_blob_lookup_list(&nbt, "sections")
+            .unwrap_or_default()
+            .iter()
+            .map(|section| {
+                PaletteSection::from_nbt_value(
+                    section,
+                    "block_states/palette",
+                    "block_states/data",
+                    true,
+                )
+            })
+            .collect();
+        let block_entities = nbt_blob_lookup_list(&nbt, "block_entities").unwrap_or_default();
+        let scheduled_ticks = palette::scheduled_ticks(&nbt, "block_ticks", "fluid_ticks");
+        let height_maps =
+            palette::height_maps(&nbt, "Heightmaps", Self::EXTENDED_HEIGHT_HEIGHT, true);
+
+        let mut chunk = Self::from_palette_chunk(PaletteChunk {
+            nbt,
+            data_version,
+            global_pos,
+            last_update,
+            min_y: min_section as i64 * 16,
+            height: Self::EXTENDED_HEIGHT_HEIGHT,
+            sections,
+            block_entities,
+        });
+        chunk.scheduled_ticks = scheduled_ticks;
+        chunk.height_maps = height_maps;
+        chunk
+    }
+
+    /// Generates the NBT data of the chunk, in the extended height chunk format.
+    ///
+    /// Everything not handled by mcprogedit (biomes, structure references, etc.)
+    /// is kept as it was when the chunk was read. Light is left for the game to
+    /// recalculate.
+    pub(crate) fn extended_height_nbt(&self) -> nbt::Blob {
+        let (sections, block_entities) = self.palette_sections(ChunkFormat::ExtendedHeight);
+        let sections = palette::merged_sections(
+            self.original_sections("sections"),
+            sections,
+            |y| self.extended_height_new_section(y),
+            |compound, section| {
+                let mut block_states = nbt::Map::new();
+                block_states.insert("palette".into(), section.palette_value());
+                if !section.is_uniform() {
+                    block_states.insert("data".into(), section.data_value(true));
+                }
+                compound.insert("block_states".into(), nbt::Value::Compound(block_states));
+            },
+        );
+
+        let mut nbt = match &self.original {
+            Some(original) => original.nbt.clone(),
+            None => self.extended_height_new_chunk(),
+        };
+        nbt.insert("LastUpdate", Self::last_update_now()).unwrap();
+        nbt.insert("sections", nbt::Value::List(sections)).unwrap();
+        nbt.insert("block_entities", nbt::Value::List(block_entities))
+            .unwrap();
+        nbt.insert(
+            "block_ticks",
+            scheduled_tick::ticks_to_nbt_list(
+                &self.scheduled_ticks,
+                Some(TickKind::Block),
+                self.data_version,
+            ),
+        )
+        .unwrap();
+        nbt.insert(
+            "fluid_ticks",
+            scheduled_tick::ticks_to_nbt_list(
+                &self.scheduled_ticks,
+                Some(TickKind::Fluid),
+                self.data_version,
+            ),
+        )
+        .unwrap();
+        nbt.insert("Heightmaps", self.height_maps_value(true)).unwrap();
+        nbt.insert("isLightOn", nbt::Value::Byte(0)).unwrap();
+        nbt
+    }
+
+    /// Creates the root tags of a chunk that did not exist in the save.
+    fn extended_height_new_chunk(&self) -> nbt::Blob {
+        let mut nbt = nbt::Blob::new();
+        nbt.insert("DataVersion", self.data_version.id()).unwrap();
+        nbt.insert("xPos", self.global_pos.0 as i32).unwrap();
+        nbt.insert("zPos", self.global_pos.1 as i32).unwrap();
+        nbt.insert("yPos", (self.min_y / 16) as i32).unwrap();
+        nbt.insert("Status", "minecraft:full").unwrap();
+        nbt.insert("InhabitedTime", 0i64).unwrap();
+        nbt
+    }
+
+    /// Creates a section compound for a section that did not exist in the save.
+    fn extended_height_new_section(&self, y: i64) -> nbt::Map<String, nbt::Value> {
+        // One biome per 4x4x4 cell, taken from the biome of the block columns
+        let cells: Vec<&'static str> = (0..64)
+            .map(|index| self.biome_at(index % 4 * 4, index / 4 % 4 * 4).modern_name())
+            .collect();
+        let mut palette: Vec<&'static str> = Vec::new();
+        let indexes: Vec<u16> = cells
+            .iter()
+            .map(|name| match palette.iter().position(|entry| entry == name) {
+                Some(index) => index as u16,
+                None => {
+                    palette.push(name);
+                    (palette.len() - 1) as u16
+                }
+            })
+            .collect();
+
+        let mut biomes = nbt::Map::new();
+        biomes.insert(
+            "palette".into(),
+            nbt::Value::List(
+                palette
+                    .iter()
+                    .map(|name| nbt::Value::String((*name).into()))
+                    .collect(),
+            ),
+        );
+        if palette.len() > 1 {
+            let bits = (usize::BITS - (palette.len() - 1).leading_zeros()) as usize;
+            let data = utils::paddedly_packed(&indexes, bits);
+            biomes.insert(
+                "data".into(),
+                nbt::Value::LongArray(data.into_iter().map(|long| long as i64).collect()),
+            );
+        }
+
+        let mut section = nbt::Map::new();
+        section.insert("Y".into(), nbt::Value::Byte(y as i8));
+        section.insert("biomes".into(), nbt::Value::Compound(biomes));
+        section
+    }
+}
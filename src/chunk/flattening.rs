@@ -0,0 +1,168 @@
+//! The chunk format used from "the flattening" (17w47a) through Minecraft 1.17.1.
+
+use std::str::FromStr;
+
+use crate::chunk::palette::{self, PaletteChunk, PaletteSection};
+use crate::chunk::Chunk;
+use crate::coordinates::ChunkCoord;
+use crate::entity;
+use crate::mc_version::McVersion;
+use crate::nbt_lookup::*;
+use crate::save_format::ChunkFormat;
+use crate::scheduled_tick::{self, TickKind};
+
+impl Chunk {
+    /// Creates a chunk from NBT data in the flattening chunk format.
+    pub(crate) fn from_flattening_nbt(nbt: nbt::Blob, data_version: McVersion) -> Self {
+        let x_pos = nbt_blob_lookup_int(&nbt, "Level/xPos")
+            .unwrap_or_else(|| panic!("Level/xPos not found"));
+        let z_pos = nbt_blob_lookup_int(&nbt, "Level/zPos")
+            .unwrap_or_else(|| panic!("Level/zPos not found"));
+        let global_pos: ChunkCoord = (x_pos.into(), z_pos.into()).into();
+
+        let last_update = nbt_blob_lookup_long(&nbt, "Level/LastUpdate").unwrap_or(0);
+
+        let padded = Self::flattening_padded_packing(data_version);
+        let sections = nbt_blob_lookup_list(&nbt, "Level/Sections")
+            .unwrap_or_default()
+            .iter()
+            .map(|section| {
+                PaletteSection::from_nbt_value(section, "Palette", "BlockStates", padded)
+            })
+            .collect();
+        let block_entities = nbt_blob_lookup_list(&nbt, "Level/TileEntities").unwrap_or_default();
+        let entities = entity::entities_from_nbt_list(
+            &nbt_blob_lookup_list(&nbt, "Level/Entities").unwrap_or_default(),
+        );
+        let scheduled_ticks =
+            palette::scheduled_ticks(&nbt, "Level/TileTicks", "Level/LiquidTicks");
+        let height_maps = palette::height_maps(&nbt, "Level/Heightmaps", 256, padded);
+
+        let mut chunk = Self::from_palette_chunk(PaletteChunk {
+            nbt,
+            data_version,
+            global_pos,
+            last_update,
+            min_y: 0,
+            height: 256,
+            sections,
+            block_entities,
+        });
+        chunk.entities = entities;
+        chunk.scheduled_ticks = scheduled_ticks;
+        chunk.height_maps = height_maps;
+        chunk
+    }
+
+    /// Generates the NBT data of the chunk, in the flattening chunk format.
+    ///
+    /// Everything not handled by mcprogedit (biomes, structure references,
+    /// etc.) is kept as it was when the chunk was read. Light is left for the
+    /// game to recalculate.
+    pub(crate) fn flattening_nbt(&self) -> nbt::Blob {
+        let data_version = self.data_version;
+        let padded = Self::flattening_padded_packing(data_version);
+
+        let (sections, block_entities) = self.palette_sections(ChunkFormat::Flattening);
+        let sections = palette::merged_sections(
+            self.original_sections("Level/Sections"),
+            sections,
+            Self::flattening_new_section,
+            |compound, section| {
+                compound.insert("Palette".into(), section.palette_value());
+                compound.insert("BlockStates".into(), section.data_value(padded));
+            },
+        );
+
+        let mut level = match self
+            .original
+            .as_ref()
+            .and_then(|original| original.nbt.get("Level"))
+        {
+            Some(nbt::Value::Compound(level)) => level.clone(),
+            _ => self.flattening_new_level(),
+        };
+        level.insert(
+            "LastUpdate".into(),
+            nbt::Value::Long(Self::last_update_now()),
+        );
+        level.insert("Sections".into(), nbt::Value::List(sections));
+        level.insert("TileEntities".into(), nbt::Value::List(block_entities));
+        // From 20w45a, entities are stored in separate entity region files.
+        if !entity::is_stored_separately(data_version) {
+            level.insert(
+                "Entities".into(),
+                entity::entities_to_nbt_list(&self.entities, data_version),
+            );
+        }
+        level.insert(
+            "TileTicks".into(),
+            scheduled_tick::ticks_to_nbt_list(
+                &self.scheduled_ticks,
+                Some(TickKind::Block),
+                data_version,
+            ),
+        );
+        level.insert(
+            "LiquidTicks".into(),
+            scheduled_tick::ticks_to_nbt_list(
+                &self.scheduled_ticks,
+                Some(TickKind::Fluid),
+                data_version,
+            ),
+        );
+        level.insert("Heightmaps".into(), self.height_maps_value(padded));
+        if data_version >= McVersion::from_str("1.14").unwrap() {
+            level.insert("isLightOn".into(), nbt::Value::Byte(0));
+        } else {
+            level.insert("LightPopulated".into(), nbt::Value::Byte(0));
+        }
+
+        let mut nbt = nbt::Blob::new();
+        nbt.insert("DataVersion", data_version.id()).unwrap();
+        nbt.insert("Level", nbt::Value::Compound(level)).unwrap();
+        nbt
+    }
+
+    /// Creates the Level compound of a chunk that did not exist in the save.
+    fn flattening_new_level(&self) -> nbt::Map<String, nbt::Value> {
+        let status = if self.data_version < McVersion::from_str("18w43a").unwrap() {
+            "postprocessed"
+        } else {
+            "full"
+        };
+        // One biome per column, or per 4x4x4 cell from 19w36a, plains if unknown
+        let biomes = if self.data_version < McVersion::from_str("19w36a").unwrap() {
+            (0..256)
+                .map(|index| self.biome_at(index % 16, index / 16).numeric_id())
+                .collect()
+        } else {
+            (0..1024)
+                .map(|index| self.biome_at(index % 4 * 4, index / 4 % 4 * 4).numeric_id())
+                .collect()
+        };
+
+        let mut level = nbt::Map::new();
+        level.insert("xPos".into(), nbt::Value::Int(self.global_pos.0 as i32));
+        level.insert("zPos".into(), nbt::Value::Int(self.global_pos.1 as i32));
+        level.insert("InhabitedTime".into(), nbt::Value::Long(0));
+        level.insert("Status".into(), nbt::Value::String(status.into()));
+        level.insert("Biomes".into(), nbt::Value::IntArray(biomes));
+        level.insert("Entities".into(), nbt::Value::List(Vec::new()));
+        level
+    }
+
+    /// Creates a section compound for a section that did not exist in the save.
+    fn flattening_new_section(y: i64) -> nbt::Map<String, nbt::Value> {
+        let mut section = nbt::Map::new();
+        section.insert("Y".into(), nbt::Value::Byte(y as i8));
+        section.insert("BlockLight".into(), nbt::Value::ByteArray(vec![0; 2048]));
+        section.insert("SkyLight".into(), nbt::Value::ByteArray(vec![-1; 2048]));
+        section
+    }
+
+    /// Block states are packed with padding from 20w17a, and tightly before that.
+    fn flattening_padded_packing(data_version: McVersion) -> bool {
+        data_version >= McVersion::from_str("20w17a").unwrap()
+    }
+}
@@ -0,0 +1,738 @@
+//! Block state palettes, as used by the post-flattening chunk formats.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::block::*;
+use crate::block_cuboid::BlockCuboid;
+use crate::block_entity::BlockEntity;
+use crate::block_state::BlockState;
+use crate::chunk::{Chunk, OriginalChunk};
+use crate::coordinates::{BlockColumnCoord, BlockCoord, ChunkCoord};
+use crate::height_map::{HeightMap, HeightMapKind};
+use crate::light_cuboid::LightCuboid;
+use crate::mc_version::McVersion;
+use crate::nbt_lookup::*;
+use crate::save_format::ChunkFormat;
+use crate::scheduled_tick::{self, ScheduledTick, TickKind};
+use crate::utils;
+
+/// Number of blocks in a chunk section.
+const SECTION_VOLUME: usize = 16 * 16 * 16;
+
+/// Chunk data read from a post-flattening save.
+pub(super) struct PaletteChunk {
+    pub nbt: nbt::Blob,
+    pub data_version: McVersion,
+    pub global_pos: ChunkCoord,
+    pub last_update: i64,
+    pub min_y: i64,
+    pub height: usize,
+    pub sections: Vec<PaletteSection>,
+    pub block_entities: Vec<nbt::Value>,
+}
+
+/// The block states and light levels of one chunk section.
+pub(super) struct PaletteSection {
+    /// Section y index, i.e. the y coordinate of the section divided by 16.
+    pub y: i64,
+    pub palette: Vec<BlockState>,
+    /// Index into `palette` for each block, in "y, z, x" order.
+    pub states: Vec<u16>,
+    pub block_light: Option<Vec<i8>>,
+    pub sky_light: Option<Vec<i8>>,
+}
+
+impl PaletteSection {
+    /// Reads a chunk section, with its palette and packed block states at the given
+    /// paths. Values are packed with padding if `padded` is true, else tightly.
+    ///
+    /// Sections without block states (storing only light) are read as air.
+    pub fn from_nbt_value(
+        section: &nbt::Value,
+        palette_path: &'static str,
+        data_path: &'static str,
+        padded: bool,
+    ) -> Self {
+        let y = nbt_value_lookup_byte(section, "Y").unwrap_or_else(|| panic!("Section Y not found"))
+            as i64;
+
+        let palette: Vec<BlockState> = match nbt_value_lookup_list(section, palette_path) {
+            Some(palette) if !palette.is_empty() => {
+                palette.iter().map(BlockState::from_nbt_value).collect()
+            }
+            _ => vec![BlockState::air()],
+        };
+
+        let states = match nbt_value_lookup_long_array(section, data_path) {
+            Some(data) if palette.len() > 1 => unpacked_states(&data, palette.len(), padded),
+            _ => vec![0; SECTION_VOLUME],
+        };
+
+        Self {
+            y,
+            palette,
+            states,
+            block_light: nbt_value_lookup_byte_array(section, "BlockLight"),
+            sky_light: nbt_value_lookup_byte_array(section, "SkyLight"),
+        }
+    }
+}
+
+/// The block states of one chunk section, ready for saving.
+pub(super) struct EncodedSection {
+    /// Section y index, i.e. the y coordinate of the section divided by 16.
+    pub y: i64,
+    palette: Vec<BlockState>,
+    states: Vec<u16>,
+}
+
+impl EncodedSection {
+    /// Returns true if all blocks of the section are air.
+    pub fn is_air(&self) -> bool {
+        self.palette.iter().all(BlockState::is_air)
+    }
+
+    /// Returns true if the whole section is of one block state.
+    pub fn is_uniform(&self) -> bool {
+        self.palette.len() == 1
+    }
+
+    pub fn palette_value(&self) -> nbt::Value {
+        nbt::Value::List(self.palette.iter().map(BlockState::to_nbt_value).collect())
+    }
+
+    /// Packed block states, padded if `padded` is true, else tightly packed.
+    pub fn data_value(&self, padded: bool) -> nbt::Value {
+        nbt::Value::LongArray(packed_states(&self.states, self.palette.len(), padded))
+    }
+}
+
+impl Chunk {
+    /// Creates a chunk from the block states and block entities read from a
+    /// post-flattening save.
+    pub(super) fn from_palette_chunk(chunk: PaletteChunk) -> Self {
+        let PaletteChunk {
+            nbt,
+            data_version,
+            global_pos,
+            last_update,
+            min_y,
+            height,
+            sections,
+            block_entities,
+        } = chunk;
+        let dim = (16, height, 16);
+        let chunk_offset: BlockColumnCoord = global_pos.into();
+
+        // Block entities, by chunk local coordinates
+        let block_entities: HashMap<BlockCoord, nbt::Value> = block_entities
+            .into_iter()
+            .filter_map(|value| {
+                let x = nbt_value_lookup_int(&value, "x")? as i64 - chunk_offset.0;
+                let y = nbt_value_lookup_int(&value, "y")? as i64 - min_y;
+                let z = nbt_value_lookup_int(&value, "z")? as i64 - chunk_offset.1;
+                Some((BlockCoord(x, y, z), value))
+            })
+            .collect();
+
+        let mut palette = vec![BlockState::air()];
+        let mut palette_indexes: HashMap<BlockState, u16> = HashMap::new();
+        palette_indexes.insert(BlockState::air(), 0);
+        let mut states = vec![0u16; 16 * height * 16];
+
+        let mut blocks = BlockCuboid::new_filled(dim, Block::Air);
+        let mut block_light = LightCuboid::new(dim);
+        let mut sky_light = LightCuboid::new(dim);
+
+        for section in &sections {
+            let y_offset = section.y * 16 - min_y;
+            if y_offset < 0 || y_offset + 16 > height as i64 {
+                continue;
+            }
+            let y_offset = y_offset as usize;
+
+            let section_blocks: Vec<Block> = section
+                .palette
+                .iter()
+                .map(|state| state.to_block(data_version))
+                .collect();
+            let section_indexes: Vec<u16> = section
+                .palette
+                .iter()
+                .map(|state| add_state(&mut palette, &mut palette_indexes, state))
+                .collect();
+
+            for (index, state) in section.states.iter().take(SECTION_VOLUME).enumerate() {
+                let at = (index % 16, y_offset + index / 256, (index / 16) % 16);
+                let state = *state as usize;
+                let mut block = section_blocks.get(state).cloned().unwrap_or(Block::Air);
+                let coordinates = BlockCoord(at.0 as i64, at.1 as i64, at.2 as i64);
+                if let Some(value) = block_entities.get(&coordinates) {
+                    block = with_block_entity(block, &BlockEntity::from_nbt_value(value));
+                }
+                blocks.insert(at, block);
+                states[Self::state_index(at)] = section_indexes.get(state).cloned().unwrap_or(0);
+            }
+
+            let y_offset = y_offset as i64;
+            for (light, cuboid) in [
+                (&section.block_light, &mut block_light),
+                (&section.sky_light, &mut sky_light),
+            ] {
+                if let Some(light) = light {
+                    for index in 0..SECTION_VOLUME.min(light.len() * 2) {
+                        let level = (light[index / 2] as u8 >> ((index % 2) * 4)) & 0x0F;
+                        let at = (
+                            (index % 16) as i64,
+                            y_offset + (index / 256) as i64,
+                            ((index / 16) % 16) as i64,
+                        );
+                        cuboid.set_light_level_at(at.into(), level);
+                    }
+                }
+            }
+        }
+
+        Self {
+            data_version,
+            global_pos,
+            _last_update: last_update,
+            min_y,
+            blocks: blocks.clone(),
+            block_light,
+            sky_light,
+            biomes: None,
+            entities: Vec::new(),
+            scheduled_ticks: Vec::new(),
+            height_maps: BTreeMap::new(),
+            connection_states: HashMap::new(),
+            original: Some(Box::new(OriginalChunk {
+                nbt,
+                blocks,
+                palette,
+                states,
+                block_entities,
+            })),
+        }
+    }
+
+    /// Generates the block states of all sections of the chunk, and the block
+    /// entities of the chunk, for saving in the given post-flattening chunk format.
+    ///
+    /// Blocks that are unchanged since the chunk was read, keep their original
+    /// block states and block entities.
+    pub(super) fn palette_sections(
+        &self,
+        format: ChunkFormat,
+    ) -> (Vec<EncodedSection>, Vec<nbt::Value>) {
+        let (_, height, _) = self.blocks.dim();
+        let chunk_offset: BlockColumnCoord = self.global_pos.into();
+        let original = self.original.as_deref();
+
+        let mut sections = Vec::with_capacity(height / 16);
+        let mut block_entities = Vec::new();
+
+        for section_index in 0..height / 16 {
+            let mut palette = Vec::new();
+            let mut palette_indexes: HashMap<BlockState, u16> = HashMap::new();
+            let mut from_original: HashMap<u16, u16> = HashMap::new();
+            let mut states = Vec::with_capacity(SECTION_VOLUME);
+
+            for index in 0..SECTION_VOLUME {
+                let at = (
+                    index % 16,
+                    section_index * 16 + index / 256,
+                    (index / 16) % 16,
+                );
+                let block = self.blocks.block_at(at).unwrap_or(&Block::None);
+                let unchanged =
+                    original.filter(|original| original.blocks.block_at(at) == Some(block));
+                let connection_state =
+                    self.connection_states
+                        .get(&BlockCoord(at.0 as i64, at.1 as i64, at.2 as i64));
+
+                let state = match (unchanged, connection_state) {
+                    (_, Some(properties)) => add_state(
+                        &mut palette,
+                        &mut palette_indexes,
+                        &BlockState::from_block(block, self.data_version)
+                            .with_derived(properties, self.data_version),
+                    ),
+                    (Some(original), None) => {
+                        let original_index = original.states[Self::state_index(at)];
+                        *from_original.entry(original_index).or_insert_with(|| {
+                            add_state(
+                                &mut palette,
+                                &mut palette_indexes,
+                                &original.palette[original_index as usize],
+                            )
+                        })
+                    }
+                    (None, None) => add_state(
+                        &mut palette,
+                        &mut palette_indexes,
+                        &BlockState::from_block(block, self.data_version),
+                    ),
+                };
+                states.push(state);
+
+                let block_entity = match unchanged {
+                    Some(original) => original
+                        .block_entities
+                        .get(&BlockCoord(at.0 as i64, at.1 as i64, at.2 as i64))
+                        .cloned(),
+                    None => {
+                        let global_coordinates = (
+                            (chunk_offset.0 + at.0 as i64) as i32,
+                            (self.min_y + at.1 as i64) as i32,
+                            (chunk_offset.1 + at.2 as i64) as i32,
+                        );
+                        Self::block_entity_nbt(block, global_coordinates, format, self.data_version)
+                    }
+                };
+                if let Some(value) = block_entity {
+                    block_entities.push(value);
+                }
+            }
+
+            sections.push(EncodedSection {
+                y: self.min_y / 16 + section_index as i64,
+                palette,
+                states,
+            });
+        }
+
+        (sections, block_entities)
+    }
+
+    /// The section list of the original chunk, as found at `path`.
+    pub(super) fn original_sections(&self, path: &'static str) -> Vec<nbt::Value> {
+        self.original
+            .as_ref()
+            .and_then(|original| nbt_blob_lookup_list(&original.nbt, path))
+            .unwrap_or_default()
+    }
+
+    /// Index into the block states of the chunk, for chunk local coordinates `at`.
+    fn state_index(at: (usize, usize, usize)) -> usize {
+        at.1 * 16 * 16 + at.2 * 16 + at.0
+    }
+}
+
+/// Merges the section compounds of the original chunk with the newly encoded
+/// sections, by letting `update` fill in the block states of each encoded section.
+///
+/// Sections that consist of air only are left out, unless there was an original
+/// section at the same y index. Original sections outside of the encoded range
+/// (e.g. storing light only) are kept as they were.
+pub(super) fn merged_sections<F, G>(
+    original_sections: Vec<nbt::Value>,
+    sections: Vec<EncodedSection>,
+    new_section: G,
+    update: F,
+) -> Vec<nbt::Value>
+where
+    F: Fn(&mut nbt::Map<String, nbt::Value>, &EncodedSection),
+    G: Fn(i64) -> nbt::Map<String, nbt::Value>,
+{
+    let section_y = |section: &nbt::Value| nbt_value_lookup_byte(section, "Y").map(i64::from);
+    let mut originals: HashMap<i64, nbt::Map<String, nbt::Value>> = HashMap::new();
+    let mut merged = Vec::new();
+
+    for section in original_sections {
+        match (section_y(&section), section) {
+            (Some(y), nbt::Value::Compound(compound))
+                if sections.iter().any(|encoded| encoded.y == y) =>
+            {
+                originals.insert(y, compound);
+            }
+            (_, section) => merged.push(section),
+        }
+    }
+
+    for section in sections {
+        let mut compound = match originals.remove(&section.y) {
+            Some(compound) => compound,
+            None if section.is_air() => continue,
+            None => new_section(section.y),
+        };
+        update(&mut compound, &section);
+        merged.push(nbt::Value::Compound(compound));
+    }
+
+    merged
+}
+
+/// Reads the scheduled block ticks and fluid ticks stored at the given paths.
+pub(super) fn scheduled_ticks(
+    nbt: &nbt::Blob,
+    block_ticks_path: &'static str,
+    fluid_ticks_path: &'static str,
+) -> Vec<ScheduledTick> {
+    let mut ticks = scheduled_tick::ticks_from_nbt_list(
+        TickKind::Block,
+        &nbt_blob_lookup_list(nbt, block_ticks_path).unwrap_or_default(),
+    );
+    ticks.extend(scheduled_tick::ticks_from_nbt_list(
+        TickKind::Fluid,
+        &nbt_blob_lookup_list(nbt, fluid_ticks_path).unwrap_or_default(),
+    ));
+    ticks
+}
+
+/// Reads the height maps of the "Heightmaps" compound at `path`, of a chunk
+/// spanning `height` blocks. Height maps of unknown kinds are skipped.
+pub(super) fn height_maps(
+    nbt: &nbt::Blob,
+    path: &'static str,
+    height: usize,
+    padded: bool,
+) -> BTreeMap<HeightMapKind, HeightMap> {
+    match nbt_blob_lookup(nbt, path) {
+        Some(nbt::Value::Compound(height_maps)) => height_maps
+            .into_iter()
+            .filter_map(|(name, value)| {
+                let kind = HeightMapKind::from_nbt_name(&name)?;
+                Some((
+                    kind,
+                    HeightMap::from_packed_nbt_value(value, height, padded),
+                ))
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    }
+}
+
+/// Returns the index of `state` in `palette`, adding it to the palette if missing.
+fn add_state(
+    palette: &mut Vec<BlockState>,
+    palette_indexes: &mut HashMap<BlockState, u16>,
+    state: &BlockState,
+) -> u16 {
+    if let Some(index) = palette_indexes.get(state) {
+        return *index;
+    }
+    let index = palette.len() as u16;
+    palette.push(state.clone());
+    palette_indexes.insert(state.clone(), index);
+    index
+}
+
+/// Number of bits used for storing each block state index, for a palette of `palette_len` entries.
+fn bits_per_state(palette_len: usize) -> usize {
+    let bits = (usize::BITS - palette_len.saturating_sub(1).leading_zeros()) as usize;
+    usize::max(4, bits)
+}
+
+fn unpacked_states(data: &[i64], palette_len: usize, padded: bool) -> Vec<u16> {
+    let data: Vec<u64> = data.iter().map(|long| *long as u64).collect();
+    let bits = bits_per_state(palette_len);
+    let mut states: Vec<u16> = if padded {
+        utils::paddedly_unpacked(&data, bits)
+    } else {
+        utils::tightly_unpacked(&data, bits)
+    };
+    states.resize(SECTION_VOLUME, 0);
+    states
+}
+
+fn packed_states(states: &[u16], palette_len: usize, padded: bool) -> Vec<i64> {
+    let bits = bits_per_state(palette_len);
+    let packed = if padded {
+        utils::paddedly_packed(states, bits)
+    } else {
+        utils::tightly_packed(states, bits)
+    };
+    packed.into_iter().map(|long| long as i64).collect()
+}
+
+/// Fills in the block entity data of `block` from `block_entity`.
+fn with_block_entity(block: Block, block_entity: &BlockEntity) -> Block {
+    match (block, block_entity) {
+        (
+            Block::Banner(mut banner),
+            BlockEntity::Banner {
+                custom_name,
+                patterns,
+                ..
+            },
+        ) => {
+            banner.custom_name = custom_name.clone();
+            banner.patterns = patterns.clone();
+            Block::Banner(banner)
+        }
+        (
+            Block::Beacon(mut beacon),
+            BlockEntity::Beacon {
+                lock,
+                levels,
+                primary,
+                secondary,
+                ..
+            },
+        ) => {
+            beacon.lock = lock.clone();
+            beacon.levels = *levels;
+            beacon.primary = *primary;
+            beacon.secondary = *secondary;
+            Block::Beacon(beacon)
+        }
+        (
+            Block::BrewingStand(mut brewing_stand),
+            BlockEntity::BrewingStand {
+                custom_name,
+                lock,
+                items,
+                brew_time,
+                fuel,
+                ..
+            },
+        ) => {
+            brewing_stand.custom_name = custom_name.clone();
+            brewing_stand.lock = lock.clone();
+            brewing_stand.items = items.clone();
+            brewing_stand.brew_time = *brew_time;
+            brewing_stand.fuel = *fuel;
+            Block::BrewingStand(brewing_stand)
+        }
+        (Block::Chest(mut chest), BlockEntity::Chest { tags }) => {
+            chest.custom_name = tags.custom_name.clone();
+            chest.lock = tags.lock.clone();
+            chest.items = tags.items.clone();
+            Block::Chest(chest)
+        }
+        (Block::TrappedChest(mut chest), BlockEntity::Chest { tags }) => {
+            chest.custom_name = tags.custom_name.clone();
+            chest.lock = tags.lock.clone();
+            chest.items = tags.items.clone();
+            Block::TrappedChest(chest)
+        }
+        (Block::Dispenser(mut dispenser), BlockEntity::Dispenser { tags }) => {
+            dispenser.custom_name = tags.custom_name.clone();
+            dispenser.lock = tags.lock.clone();
+            dispenser.items = tags.items.clone();
+            Block::Dispenser(dispenser)
+        }
+        (Block::Dropper(mut dropper), BlockEntity::Dropper { tags }) => {
+            dropper.custom_name = tags.custom_name.clone();
+            dropper.lock = tags.lock.clone();
+            dropper.items = tags.items.clone();
+            Block::Dropper(dropper)
+        }
+        (Block::EnchantingTable { .. }, BlockEntity::EnchantingTable { custom_name, .. }) => {
+            Block::EnchantingTable {
+                custom_name: Box::new(custom_name.clone()),
+            }
+        }
+        (Block::Hopper(mut hopper), BlockEntity::Hopper { tags }) => {
+            hopper.custom_name = tags.custom_name.clone();
+            hopper.lock = tags.lock.clone();
+            hopper.items = tags.items.clone();
+            Block::Hopper(hopper)
+        }
+        (Block::ShulkerBox(mut shulker_box), BlockEntity::ShulkerBox { tags }) => {
+            shulker_box.custom_name = tags.custom_name.clone();
+            shulker_box.lock = tags.lock.clone();
+            shulker_box.items = tags.items.clone();
+            Block::ShulkerBox(shulker_box)
+        }
+        (Block::Furnace(furnace), BlockEntity::Furnace { tags }) => {
+            Block::Furnace(with_furnace_tags(furnace, tags))
+        }
+        (Block::BlastFurnace(furnace), BlockEntity::BlastFurnace { tags }) => {
+            Block::BlastFurnace(with_furnace_tags(furnace, tags))
+        }
+        (Block::Smoker(furnace), BlockEntity::Smoker { tags }) => {
+            Block::Smoker(with_furnace_tags(furnace, tags))
+        }
+        (Block::Jukebox(mut jukebox), BlockEntity::Jukebox { record, .. }) => {
+            jukebox.record = record.clone();
+            Block::Jukebox(jukebox)
+        }
+        (Block::Lectern { facing, .. }, BlockEntity::Lectern { book, .. }) => Block::Lectern {
+            facing,
+            book: book
+                .clone()
+                .map(|(book, page)| Box::new(LecternBook { book, page })),
+        },
+        (Block::Sign(mut sign), BlockEntity::Sign { colour, text, .. }) => {
+            sign.colour = *colour;
+            // TODO something reasonable instead of JSON text
+            sign.text1 = text.first().cloned().unwrap_or_default();
+            sign.text2 = text.get(1).cloned().unwrap_or_default();
+            sign.text3 = text.get(2).cloned().unwrap_or_default();
+            sign.text4 = text.get(3).cloned().unwrap_or_default();
+            Block::Sign(sign)
+        }
+        (block, _) => block,
+    }
+}
+
+fn with_furnace_tags(
+    mut furnace: Box<Furnace>,
+    tags: &crate::block_entity::FurnaceTags,
+) -> Box<Furnace> {
+    furnace.custom_name = tags.custom_name.clone();
+    furnace.lock = tags.lock.clone();
+    furnace.items = tags.items.clone();
+    furnace.burn_time = tags.burn_time;
+    furnace.cook_time = tags.cook_time;
+    furnace.cook_time_total = tags.cook_time_total;
+    furnace
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::bounded_ints::Int0Through3;
+    use crate::inventory::Inventory;
+    use crate::positioning::{Axis3, Surface4};
+
+    fn round_trip(version: &str, y: usize) {
+        let version = McVersion::from_str(version).unwrap();
+        let mut chunk = Chunk::new((-3, 5).into(), version);
+        let chest = Block::Chest(Box::new(Chest {
+            facing: Surface4::West,
+            variant: None,
+            waterlogged: false,
+            custom_name: Some("Loot".into()),
+            lock: None,
+            items: Inventory::new(),
+        }));
+        chunk.blocks.insert((0, y, 0), Block::Stone);
+        chunk.blocks.insert((1, y, 2), chest.clone());
+        chunk
+            .blocks
+            .insert((15, y + 17, 15), Block::acacia_log(Axis3::Z));
+        chunk.blocks.insert(
+            (4, y, 4),
+            Block::Cauldron {
+                water_level: Int0Through3::new(2).unwrap(),
+            },
+        );
+
+        let mut chunk = Chunk::from_raw_chunk_data(&chunk.raw_chunk_zlib());
+        assert_eq!(chunk.data_version(), version);
+        assert_eq!(chunk.chunk_coordinates(), &(-3, 5).into());
+        assert_eq!(chunk.blocks.block_at((0, y, 0)), Some(&Block::Stone));
+        assert_eq!(chunk.blocks.block_at((1, y, 2)), Some(&chest));
+        assert_eq!(
+            chunk.blocks.block_at((15, y + 17, 15)),
+            Some(&Block::acacia_log(Axis3::Z))
+        );
+        assert_eq!(chunk.blocks.block_at((0, y + 1, 0)), Some(&Block::Air));
+
+        // Height maps are computed from the blocks on writing
+        let motion_blocking = &chunk.height_maps[&HeightMapKind::MotionBlocking];
+        assert_eq!(motion_blocking.height_at((0, 0)), Some(y as u32 + 1));
+        for kind in &HeightMapKind::FULL_CHUNK {
+            assert_eq!(
+                chunk.height_maps[kind].to_vec_u32(),
+                chunk.blocks.height_map_of(*kind).to_vec_u32()
+            );
+        }
+
+        // Changed blocks are written, unchanged ones are kept
+        chunk.blocks.insert((0, y, 0), Block::Dirt);
+        let chunk = Chunk::from_raw_chunk_data(&chunk.raw_chunk_zlib());
+        assert_eq!(chunk.blocks.block_at((0, y, 0)), Some(&Block::Dirt));
+        assert_eq!(chunk.blocks.block_at((1, y, 2)), Some(&chest));
+        assert_eq!(
+            chunk.blocks.block_at((4, y, 4)),
+            Some(&Block::Cauldron {
+                water_level: Int0Through3::new(2).unwrap(),
+            })
+        );
+    }
+
+    #[test]
+    fn flattening_chunks_survive_saving_and_loading() {
+        round_trip("1.13.2", 10);
+        round_trip("1.16.5", 10);
+    }
+
+    #[test]
+    fn books_survive_chests_and_lecterns() {
+        let lore = crate::item::Item::new_written_book(
+            "The Tale",
+            "Archivist",
+            &"Once upon a time, far away. ".repeat(40),
+        );
+        let notes = crate::item::Item::new_writable_book("Remember the treasure");
+        let mut items = Inventory::new();
+        items.insert(0, lore.clone(), 1);
+        items.insert(1, notes, 1);
+        let chest = Block::Chest(Box::new(Chest {
+            facing: Surface4::North,
+            variant: None,
+            waterlogged: false,
+            custom_name: None,
+            lock: None,
+            items,
+        }));
+        let lectern = Block::Lectern {
+            facing: Surface4::South,
+            book: Some(Box::new(LecternBook {
+                book: lore,
+                page: 2,
+            })),
+        };
+
+        for version in &["1.14.4", "1.16.5"] {
+            let mut chunk = Chunk::new((0, 0).into(), McVersion::from_str(version).unwrap());
+            chunk.blocks.insert((1, 5, 1), chest.clone());
+            chunk.blocks.insert((2, 5, 1), lectern.clone());
+            let chunk = Chunk::from_raw_chunk_data(&chunk.raw_chunk_zlib());
+            assert_eq!(chunk.blocks.block_at((1, 5, 1)), Some(&chest));
+            assert_eq!(chunk.blocks.block_at((2, 5, 1)), Some(&lectern));
+        }
+    }
+
+    #[test]
+    fn connection_states_are_written_for_the_target_version() {
+        let wall = Block::Wall {
+            material: crate::material::WallMaterial::Cobblestone,
+            waterlogged: false,
+        };
+        let mut excerpt = crate::world_excerpt::WorldExcerpt::new(2, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), wall.clone());
+        excerpt.set_block_at((1, 0, 0).into(), wall.clone());
+        let connection_states = excerpt.resolve_connections();
+
+        for (version, east, west) in &[("1.15.2", "true", "false"), ("1.16.5", "low", "none")] {
+            let mut chunk = Chunk::new((0, 0).into(), McVersion::from_str(version).unwrap());
+            chunk.blocks.insert((3, 20, 3), wall.clone());
+            chunk.blocks.insert((4, 20, 3), wall.clone());
+            chunk.paste_connection_states((3, 20, 3), &connection_states);
+            let (sections, _) = chunk.palette_sections(ChunkFormat::Flattening);
+            let state = sections[1]
+                .palette
+                .iter()
+                .find(|state| state.name == "minecraft:cobblestone_wall")
+                .unwrap();
+            assert_eq!(state.properties["east"], *east);
+            assert_eq!(state.properties["west"], *west);
+        }
+    }
+
+    #[test]
+    fn extended_height_chunks_survive_saving_and_loading() {
+        let chunk = Chunk::new((0, 0).into(), McVersion::from_str("1.20.4").unwrap());
+        assert_eq!(chunk.min_y(), -64);
+        // Local y 10 is at y = -54, below the pre-1.18 world bottom
+        round_trip("1.20.4", 10);
+    }
+
+    #[test]
+    fn packed_states_survive_unpacking() {
+        let states: Vec<u16> = (0..SECTION_VOLUME)
+            .map(|index| (index % 37) as u16)
+            .collect();
+        for padded in [false, true] {
+            let packed = packed_states(&states, 37, padded);
+            assert_eq!(unpacked_states(&packed, 37, padded), states);
+        }
+    }
+}
@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::biome::Biome;
 use crate::block::*;
 use crate::block_cuboid::BlockCuboid;
 use crate::block_entity::BlockEntity;
@@ -7,10 +8,15 @@ use crate::bounded_ints::*;
 use crate::chunk::Chunk;
 use crate::colour::Colour;
 use crate::coordinates::{BlockColumnCoord, BlockCoord, ChunkCoord};
+use crate::entity;
+use crate::height_map::{HeightMap, HeightMapKind};
 use crate::light_cuboid::LightCuboid;
 use crate::material::*;
+use crate::mc_version::McVersion;
 use crate::nbt_lookup::*;
 use crate::positioning::*;
+use crate::save_format::ChunkFormat;
+use crate::scheduled_tick::{self, TickKind};
 use crate::utils;
 
 impl Chunk {
@@ -75,36 +81,14 @@ impl Chunk {
                 for z in 0..z_dim {
                     let block_z = chunk_offset_blocks.1 as i32 + z as i32;
                     let block_coordinates = (block_x, y as i32, block_z);
-                    let tile_entity_nbt = match self.blocks.block_at((x, y, z)) {
-                        None => None,
-                        Some(Block::Banner(banner)) => {
-                            banner.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::Beacon(beacon)) => {
-                            beacon.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::Chest(chest)) => {
-                            chest.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::Dispenser(dispenser)) => {
-                            dispenser.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::Dropper(dropper)) => {
-                            dropper.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::Hopper(hopper)) => {
-                            hopper.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::Noteblock(noteblock)) => {
-                            noteblock.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        Some(Block::ShulkerBox(shulker_box)) => {
-                            shulker_box.to_block_entity(block_coordinates).to_nbt_value()
-                        }
-                        // TODO add handling of other blocks with entities
-                        //Some(block) => { println!("Block found: {:?}", block); None },
-                        _ => None,
-                    };
+                    let tile_entity_nbt = self.blocks.block_at((x, y, z)).and_then(|block| {
+                        Self::block_entity_nbt(
+                            block,
+                            block_coordinates,
+                            ChunkFormat::PreFlattening,
+                            self.data_version,
+                        )
+                    });
                     if let Some(value) = tile_entity_nbt {
                         tile_entities.push(value);
                     }
@@ -114,6 +98,143 @@ impl Chunk {
         nbt::Value::List(tile_entities)
     }
 
+    /// Generates the NBT data of the chunk, in the pre flattening chunk format.
+    pub(crate) fn pre_flattening_nbt(&self) -> nbt::Blob {
+        // Time of update is now
+        let last_update = Self::last_update_now();
+
+        // Biomes needs some extra handling...
+        let biomes: Vec<u8> = match &self.biomes {
+            Some(biomes) => biomes.iter().map(|biome| u8::from(*biome)).collect(),
+            None => vec![Biome::Plains.into(); 256],
+        };
+        let biomes = utils::vec_u8_into_vec_i8(biomes);
+
+        // Various calculations
+        let sections = self.pre_flattening_sections();
+        let tile_entities = self.pre_flattening_tile_entities();
+
+        // Create the Level compund tag
+        let mut level: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(13);
+        level.insert("xPos".into(), nbt::Value::Int(self.global_pos.0 as i32));
+        level.insert("zPos".into(), nbt::Value::Int(self.global_pos.1 as i32));
+        level.insert("LastUpdate".into(), nbt::Value::Long(last_update));
+        level.insert("LightPopulated".into(), nbt::Value::Byte(1));
+        level.insert("TerrainPopulated".into(), nbt::Value::Byte(1));
+        level.insert("V".into(), nbt::Value::Byte(1));
+        level.insert("InhabitedTime".into(), nbt::Value::Long(0));
+        level.insert("Biomes".into(), nbt::Value::ByteArray(biomes));
+        level.insert(
+            "HeightMap".into(),
+            nbt::Value::IntArray(self.height_map().into()),
+        );
+        level.insert("Sections".into(), sections);
+        level.insert(
+            "Entities".into(),
+            entity::entities_to_nbt_list(&self.entities, self.data_version),
+        );
+        level.insert("TileEntities".into(), tile_entities);
+        // Block and fluid ticks are all block ticks before the flattening
+        level.insert(
+            "TileTicks".into(),
+            scheduled_tick::ticks_to_nbt_list(&self.scheduled_ticks, None, self.data_version),
+        );
+
+        // Create and return nbt blob
+        let mut nbt = nbt::Blob::new();
+        nbt.insert("DataVersion", self.data_version.id()).unwrap();
+        nbt.insert("Level", nbt::Value::Compound(level)).unwrap();
+        nbt
+    }
+
+    /// Creates a chunk from NBT data in the pre flattening chunk format.
+    pub(crate) fn from_pre_flattening_nbt(nbt: &nbt::Blob, data_version: McVersion) -> Self {
+        let x_pos = nbt_blob_lookup_int(nbt, "Level/xPos").unwrap();
+        let z_pos = nbt_blob_lookup_int(nbt, "Level/zPos").unwrap();
+        let global_pos: ChunkCoord = (x_pos.into(), z_pos.into()).into();
+
+        let _last_update = nbt_blob_lookup_long(nbt, "Level/LastUpdate").unwrap();
+
+        let tile_entities = nbt_blob_lookup(nbt, "Level/TileEntities")
+            .unwrap_or_else(|| panic!("Level/TileEntities not found"));
+        let mut block_entities = BlockEntity::map_from_nbt_list(&tile_entities);
+
+        let biomes: Option<Vec<Biome>> = nbt_blob_lookup_byte_array(nbt, "Level/Biomes")
+            .map(|biomes| biomes.iter().map(|biome| Biome::from(*biome as u8)).collect());
+
+        let sections = nbt_blob_lookup_list(nbt, "Level/Sections")
+            .unwrap_or_else(|| panic!("Level/Sections not found"));
+
+        let mut entities = entity::entities_from_nbt_list(
+            &nbt_blob_lookup_list(nbt, "Level/Entities").unwrap_or_default(),
+        );
+        for entity in entities.iter_mut() {
+            entity.upgrade_pre_flattening_facing();
+        }
+
+        let scheduled_ticks = scheduled_tick::ticks_from_nbt_list(
+            TickKind::Block,
+            &nbt_blob_lookup_list(nbt, "Level/TileTicks").unwrap_or_default(),
+        );
+
+        let mut height_maps = BTreeMap::new();
+        if let Some(height_map) = nbt_blob_lookup(nbt, "Level/HeightMap") {
+            height_maps.insert(
+                HeightMapKind::LightBlocking,
+                HeightMap::from_nbt_value_with_dim(height_map, (16, 16)),
+            );
+        }
+
+        // Fist pass: Prepare pseudo bock entities for block data that is stored
+        // in one block but used for another. This may cross section boundaries.
+        for section in &sections {
+            block_entities.extend(
+                Chunk::pre_flattening_pseudo_block_entities(section, &global_pos).into_iter(),
+            );
+        }
+
+        // Second pass: Collect the full set of (finished) blocks
+        let mut block_cuboid = BlockCuboid::new_filled((16, 256, 16), Block::Air);
+        for section in &sections {
+            // TODO rename to pre_flattening_fill_block_cuboid_from_section
+            Chunk::pre_flattening_section_into_block_cuboid(
+                section,
+                &block_entities,
+                &global_pos,
+                &mut block_cuboid,
+            );
+        }
+
+        // Get block light and sky light data out from the sections
+        let mut block_light = LightCuboid::new((16, 256, 16));
+        let mut sky_light = LightCuboid::new((16, 256, 16));
+
+        for section in &sections {
+            Chunk::pre_flattening_fill_light_cuboids_from_section(
+                section,
+                &mut block_light,
+                &mut sky_light,
+            );
+        }
+
+        // Return chunk
+        Self {
+            data_version,
+            global_pos,
+            _last_update,
+            min_y: 0,
+            blocks: block_cuboid,
+            block_light,
+            sky_light,
+            biomes,
+            entities,
+            scheduled_ticks,
+            height_maps,
+            connection_states: HashMap::new(),
+            original: None,
+        }
+    }
+
     /// Generates section NBT tags for the blocks in the chunk, and returns them
     /// in an NBT list value ready for inclusion in the pre flattening chunk format.
     pub(crate) fn pre_flattening_sections(&self) -> nbt::Value {
@@ -145,646 +266,7 @@ impl Chunk {
 
                     if let Some(block) = self.blocks.block_at((x as usize, y as usize, z as usize))
                     {
-                        let (block_id, data_value) = match block {
-                            Block::Air => (0, 0),
-                            Block::Stone => (1, 0),
-                            Block::Granite => (1, 1),
-                            Block::PolishedGranite => (1, 2),
-                            Block::Diorite => (1, 3),
-                            Block::PolishedDiorite => (1, 4),
-                            Block::Andesite => (1, 5),
-                            Block::PolishedAndesite => (1, 6),
-                            Block::GrassBlock => (2, 0),
-                            Block::Dirt => (3, 0),
-                            Block::CoarseDirt => (3, 1),
-                            Block::Podzol => (3, 2),
-                            Block::Cobblestone => (4, 0),
-                            Block::Planks { material } => match material {
-                                WoodMaterial::Oak => (5, 0),
-                                WoodMaterial::Spruce => (5, 1),
-                                WoodMaterial::Birch => (5, 2),
-                                WoodMaterial::Jungle => (5, 3),
-                                WoodMaterial::Acacia => (5, 4),
-                                WoodMaterial::DarkOak => (5, 5),
-                                WoodMaterial::Crimson => (5, 0), // Fallback to oak
-                                WoodMaterial::Warped => (5, 0),  // Fallback to oak
-                            },
-                            Block::Sapling {
-                                growth_stage,
-                                material,
-                            } => {
-                                let data = (growth_stage.get() as u8) << 3;
-                                let data = data
-                                    | match material {
-                                        SaplingMaterial::Oak => 0,
-                                        SaplingMaterial::Spruce => 1,
-                                        SaplingMaterial::Birch => 2,
-                                        SaplingMaterial::Jungle => 3,
-                                        SaplingMaterial::Acacia => 4,
-                                        SaplingMaterial::DarkOak => 5,
-                                        SaplingMaterial::Bamboo => 0, // Fallback to oak
-                                    };
-                                (6, data)
-                            }
-                            Block::Bedrock => (7, 0),
-                            Block::WaterSource => (9, 0),
-                            Block::Water { falling, level } => {
-                                let data = if *falling { 0x8 } else { 0 };
-                                let data = data | (8 - (level.get() as u8));
-                                (9, data)
-                            }
-                            Block::LavaSource => (11, 0),
-                            Block::Lava { falling, level } => {
-                                let data = if *falling { 0x8 } else { 0 };
-                                let data = data | (8 - (level.get() as u8));
-                                (11, data)
-                            }
-                            Block::Sand => (12, 0),
-                            Block::RedSand => (12, 1),
-                            Block::Gravel => (13, 0),
-                            Block::GoldOre => (14, 0),
-                            Block::IronOre => (15, 0),
-                            Block::CoalOre => (16, 0),
-                            Block::Log(Log {
-                                material,
-                                alignment,
-                                ..
-                            }) => {
-                                let data = match alignment {
-                                    Some(Axis3::Y) => 0,
-                                    Some(Axis3::X) => 1 << 2,
-                                    Some(Axis3::Z) => 2 << 2,
-                                    None => 3 << 2,
-                                };
-                                match material {
-                                    WoodMaterial::Oak => (17, data),
-                                    WoodMaterial::Spruce => (17, data | 1),
-                                    WoodMaterial::Birch => (17, data | 2),
-                                    WoodMaterial::Jungle => (17, data | 3),
-                                    WoodMaterial::Acacia => (162, data),
-                                    WoodMaterial::DarkOak => (162, data | 1),
-                                    WoodMaterial::Crimson => (17, data), // Fallback to oak
-                                    WoodMaterial::Warped => (17, data),  // Fallback to oak
-                                }
-                            }
-                            Block::Leaves {
-                                material,
-                                persistent,
-                                ..
-                            } => {
-                                let data = if *persistent { 0x4 } else { 0 };
-                                match material {
-                                    LeavesMaterial::Oak => (18, data),
-                                    LeavesMaterial::Spruce => (18, data | 1),
-                                    LeavesMaterial::Birch => (18, data | 2),
-                                    LeavesMaterial::Jungle => (18, data | 3),
-                                    LeavesMaterial::Acacia => (161, data),
-                                    LeavesMaterial::DarkOak => (161, data | 1),
-                                }
-                            }
-                            Block::Sponge => (19, 0),
-                            Block::WetSponge => (19, 1),
-                            Block::Glass { colour } => match colour {
-                                None => (20, 0),
-                                Some(colour) => (95, (*colour as i32) as u8),
-                            },
-                            Block::LapisLazuliOre => (21, 0),
-                            Block::LapisLazuliBlock => (22, 0),
-                            Block::Dispenser(dispenser) => (23, facing6_dunswe(&dispenser.facing)),
-                            Block::Sandstone => (24, 0),
-                            Block::ChiseledSandstone => (24, 1),
-                            Block::SmoothSandstone => (24, 2),
-                            Block::Noteblock(_) => (25, 0),
-                            Block::Bed(bed) => {
-                                let end_data = match bed.end {
-                                    BedEnd::Head => 0x8,
-                                    BedEnd::Foot => 0x0,
-                                };
-                                (26, facing4_swne(&bed.facing) | end_data)
-                            }
-                            Block::Rail { variant, shape, .. } => {
-                                let shape_data = shape.to_value();
-                                match variant {
-                                    RailType::Powered => (27, shape_data),
-                                    RailType::Detector => (28, shape_data),
-                                    RailType::Normal => (66, shape_data),
-                                    RailType::Activator => (157, shape_data),
-                                }
-                            }
-                            Block::StickyPiston {
-                                facing, extended, ..
-                            } => {
-                                let extended_data = match extended {
-                                    true => 0x8,
-                                    false => 0x0,
-                                };
-                                (29, facing6_dunswe(facing) & extended_data)
-                            }
-                            Block::Cobweb => (30, 0),
-                            Block::Grass(grass) => match grass {
-                                Grass::Grass => (31, 1),
-                                Grass::Fern => (31, 2),
-                                Grass::TallGrassBottom => (175, 2),
-                                Grass::TallGrassTop => (175, 8),
-                                Grass::LargeFernBottom => (175, 3),
-                                Grass::LargeFernTop => (175, 8),
-                            },
-                            Block::DeadBush => (32, 0),
-                            Block::Piston {
-                                facing, extended, ..
-                            } => {
-                                let extended_data = match extended {
-                                    true => 0x8,
-                                    false => 0x0,
-                                };
-                                (33, facing6_dunswe(facing) | extended_data)
-                            }
-                            Block::StickyPistonHead { facing } => {
-                                let sticky = 0x8;
-                                (34, sticky | facing6_dunswe(facing))
-                            }
-                            Block::PistonHead { facing } => (34, facing6_dunswe(facing)),
-                            Block::Wool { colour } => (35, (*colour).into()),
-                            // NB 36 "Block moved by Piston" not implemented
-                            Block::Flower(flower) => {
-                                match flower {
-                                    Flower::Dandelion => (37, 0),
-                                    Flower::Poppy => (38, 0),
-                                    Flower::BlueOrchid => (38, 1),
-                                    Flower::Allium => (38, 2),
-                                    Flower::AzureBluet => (38, 3),
-                                    Flower::TulipRed => (38, 4),
-                                    Flower::TulipOrange => (38, 5),
-                                    Flower::TulipWhite => (38, 6),
-                                    Flower::TulipPink => (38, 7),
-                                    Flower::OxeyeDaisy => (38, 8),
-                                    Flower::SunflowerBottom => (175, 0),
-                                    Flower::SunflowerTop => (175, 8),
-                                    Flower::LilacBottom => (175, 1),
-                                    Flower::LilacTop => (175, 8),
-                                    Flower::RoseBushBottom => (175, 4),
-                                    Flower::RoseBushTop => (175, 8),
-                                    Flower::PeonyBottom => (175, 5),
-                                    Flower::PeonyTop => (175, 8),
-                                    Flower::Cornflower
-                                    | Flower::LilyOfTheValley
-                                    | Flower::WitherRose => (37, 0), // Fallback to Dandelion
-                                }
-                            }
-                            Block::BrownMushroom => (39, 0),
-                            Block::RedMushroom => (40, 0),
-                            Block::BlockOfGold => (41, 0),
-                            Block::BlockOfIron => (42, 0),
-                            Block::Slab(slab) => {
-                                let position_data = match slab.position {
-                                    SlabVariant::Top => 0x8,
-                                    _ => 0x0,
-                                };
-                                let data = match slab.material {
-                                    SlabMaterial::SmoothStone
-                                    | SlabMaterial::Oak
-                                    | SlabMaterial::RedSandstone
-                                    | SlabMaterial::Purpur => position_data,
-                                    SlabMaterial::Sandstone | SlabMaterial::Spruce => {
-                                        1 | position_data
-                                    }
-                                    SlabMaterial::PetrifiedOak | SlabMaterial::Birch => {
-                                        2 | position_data
-                                    }
-                                    SlabMaterial::Cobblestone | SlabMaterial::Jungle => {
-                                        3 | position_data
-                                    }
-                                    SlabMaterial::Brick | SlabMaterial::Acacia => 4 | position_data,
-                                    SlabMaterial::StoneBrick | SlabMaterial::DarkOak => {
-                                        5 | position_data
-                                    }
-                                    SlabMaterial::NetherBrick => 6 | position_data,
-                                    SlabMaterial::Quartz => 7 | position_data,
-                                    _ => position_data, // fallback to SmoothStone
-                                };
-                                let block_id = match slab.material {
-                                    SlabMaterial::SmoothStone
-                                    | SlabMaterial::Sandstone
-                                    | SlabMaterial::PetrifiedOak
-                                    | SlabMaterial::Cobblestone
-                                    | SlabMaterial::Brick
-                                    | SlabMaterial::StoneBrick
-                                    | SlabMaterial::NetherBrick
-                                    | SlabMaterial::Quartz => match slab.position {
-                                        SlabVariant::Double => 43,
-                                        _ => 44,
-                                    },
-                                    SlabMaterial::Spruce
-                                    | SlabMaterial::Birch
-                                    | SlabMaterial::Jungle
-                                    | SlabMaterial::Acacia
-                                    | SlabMaterial::DarkOak => match slab.position {
-                                        SlabVariant::Double => 125,
-                                        _ => 126,
-                                    },
-                                    SlabMaterial::RedSandstone => match slab.position {
-                                        SlabVariant::Double => 181,
-                                        _ => 182,
-                                    },
-                                    SlabMaterial::Purpur => match slab.position {
-                                        SlabVariant::Double => 204,
-                                        _ => 205,
-                                    },
-                                    // fallback to SmoothStone
-                                    _ => match slab.position {
-                                        SlabVariant::Double => 43,
-                                        _ => 44,
-                                    },
-                                };
-                                (block_id, data)
-                            }
-                            Block::BrickBlock => (45, 0),
-                            Block::TNT => (46, 0),
-                            Block::Bookshelf => (47, 0),
-                            Block::MossyCobblestone => (48, 0),
-                            Block::Obsidian => (49, 0),
-                            Block::Torch { attached } => (50, facing5_xwensd(attached)),
-                            Block::Fire { age } => (51, age.get() as u8),
-                            // NB 52 mob spawner is not implemented
-                            Block::Stairs(stair) => {
-                                let data = stair.position.into();
-                                match stair.material {
-                                    StairMaterial::Oak => (53, data),
-                                    StairMaterial::Cobblestone => (67, data),
-                                    StairMaterial::Brick => (108, data),
-                                    StairMaterial::StoneBrick => (109, data),
-                                    StairMaterial::NetherBrick => (114, data),
-                                    StairMaterial::Sandstone => (128, data),
-                                    StairMaterial::Spruce => (134, data),
-                                    StairMaterial::Birch => (135, data),
-                                    StairMaterial::Jungle => (136, data),
-                                    StairMaterial::Quartz => (156, data),
-                                    StairMaterial::Acacia => (163, data),
-                                    StairMaterial::DarkOak => (164, data),
-                                    StairMaterial::RedSandstone => (180, data),
-                                    StairMaterial::Purpur => (203, data),
-                                    _ => (53, data), // fallback to oak stairs
-                                }
-                            }
-                            Block::Chest(chest) => (54, facing4_xxnswe(&chest.facing)),
-                            Block::RedstoneWire => (55, 0),
-                            Block::DiamondOre => (56, 0),
-                            Block::BlockOfDiamond => (57, 0),
-                            Block::CraftingTable => (58, 0),
-                            Block::Wheat { growth_stage } => (59, growth_stage.get() as u8),
-                            Block::Farmland { wetness } => (60, wetness.get() as u8),
-                            Block::Furnace(furnace) => {
-                                let block_id = if furnace.lit { 62 } else { 61 };
-                                (block_id, facing4_xxnswe(&furnace.facing))
-                            }
-                            Block::Sign(sign) => match sign.placement {
-                                WallOrRotatedOnFloor::Floor(facing) => (63, facing.into()),
-                                WallOrRotatedOnFloor::Wall(facing) => (68, facing4_xxnswe(&facing)),
-                            },
-                            Block::Door(door) => {
-                                let data = match door.half {
-                                    DoorHalf::Upper => {
-                                        let upper = 0x8;
-                                        let hinge = match door.hinged_at {
-                                            Hinge::Right => 0x1,
-                                            Hinge::Left => 0x0,
-                                        };
-                                        upper | hinge
-                                    }
-                                    DoorHalf::Lower => {
-                                        let lower = 0x0;
-                                        let open = if door.open { 0x4 } else { 0x0 };
-                                        let facing = facing4_wnes(&door.facing);
-                                        lower | open | facing
-                                    }
-                                };
-                                let block_id = match door.material {
-                                    DoorMaterial::Oak => 64,
-                                    DoorMaterial::Iron => 71,
-                                    DoorMaterial::Spruce => 193,
-                                    DoorMaterial::Birch => 194,
-                                    DoorMaterial::Jungle => 195,
-                                    DoorMaterial::Acacia => 196,
-                                    DoorMaterial::DarkOak => 197,
-                                    _ => 64, // fallback to oak door
-                                };
-                                (block_id, data)
-                            }
-                            Block::Ladder { facing, .. } => (65, facing4_xxnswe(facing)),
-                            // 66 normal rail already handled
-                            // 67 cobblestone stairs already handled
-                            // 68 standing sign already handled
-                            Block::Lever(facing, state) => {
-                                let state_data = match state {
-                                    OnOffState::On => 0x8,
-                                    OnOffState::Off => 0x0,
-                                };
-                                let data = state_data | lever_facing(facing);
-                                (69, data)
-                            }
-                            Block::PressurePlate { material } => {
-                                match material {
-                                    PressurePlateMaterial::Stone => (70, 0),
-                                    PressurePlateMaterial::Oak => (72, 0),
-                                    PressurePlateMaterial::Gold => (147, 0),
-                                    PressurePlateMaterial::Iron => (148, 0),
-                                    _ => (72, 0), // fallback to oak pressure plate
-                                }
-                            }
-                            // 71 iron door already handled
-                            // 72 oak pressure plate already handled
-                            Block::RedstoneOre => (73, 0),
-                            // NB 74 lit redstone ore is not implemented
-                            // NB 75 unlit redstone torch is not implemented
-                            Block::RedstoneTorch { attached } => (76, facing5_xwensd(attached)),
-                            Block::Button(material, facing) => {
-                                let data = facing6_dewsnu(facing);
-                                match material {
-                                    ButtonMaterial::Stone => (77, data),
-                                    ButtonMaterial::Oak => (143, data),
-                                    _ => (143, data), // fallback to oak button
-                                }
-                            }
-                            Block::Snow { thickness } => {
-                                let data = (thickness.get() as u8) + 1;
-                                (78, data)
-                            }
-                            Block::Ice => (79, 0),
-                            Block::SnowBlock => (80, 0),
-                            Block::Cactus { growth_stage } => (81, growth_stage.get() as u8),
-                            Block::Clay => (82, 0),
-                            Block::SugarCane { growth_stage } => (83, growth_stage.get() as u8),
-                            Block::Jukebox(_) => (84, 0),
-                            Block::Fence { material, .. } => {
-                                match material {
-                                    FenceMaterial::Oak => (85, 0),
-                                    FenceMaterial::NetherBrick => (113, 0),
-                                    FenceMaterial::Spruce => (188, 0),
-                                    FenceMaterial::Birch => (189, 0),
-                                    FenceMaterial::Jungle => (190, 0),
-                                    FenceMaterial::DarkOak => (191, 0),
-                                    FenceMaterial::Acacia => (192, 0),
-                                    _ => (85, 0), // fallback to oak fence
-                                }
-                            }
-                            Block::Pumpkin { facing } => (86, facing4_swne(facing)),
-                            Block::Netherrack => (87, 0),
-                            Block::SoulSand => (88, 0),
-                            Block::Glowstone => (89, 0),
-                            Block::NetherPortal { .. } => (90, 0),
-                            Block::JackOLantern { facing } => (91, facing4_swne(facing)),
-                            Block::Cake { pieces } => (92, 7 - (pieces.get() as u8)),
-                            Block::RedstoneRepeater(repeater) => {
-                                let delay_data = (repeater.delay.get() as u8) << 2;
-                                let facing_data = facing4_nesw(&repeater.facing);
-                                (93, delay_data | facing_data)
-                            }
-                            // NB 94 powered redstone repeater is not implemented
-                            //       (may be added to Block::RedstoneRepeater in the future)
-                            // 95 coloured class already handled
-                            Block::Trapdoor(trapdoor) => {
-                                let open_data = if trapdoor.open { 0x4 } else { 0x0 };
-                                let hinge_data = trapdoor_hinge_at(&trapdoor.hinge_at);
-                                let data = open_data | hinge_data;
-                                match trapdoor.material {
-                                    DoorMaterial::Oak => (96, data),
-                                    DoorMaterial::Iron => (167, data),
-                                    _ => (96, data), // fallback to oak trapdoor
-                                }
-                            }
-                            Block::InfestedStone => (97, 0),
-                            Block::InfestedCobblestone => (97, 1),
-                            Block::InfestedStoneBricks => (97, 2),
-                            Block::InfestedMossyStoneBricks => (97, 3),
-                            Block::InfestedCrackedStoneBricks => (97, 4),
-                            Block::InfestedChiseledStoneBricks => (97, 5),
-                            Block::StoneBricks => (98, 0),
-                            Block::MossyStoneBricks => (98, 1),
-                            Block::CrackedStoneBricks => (98, 2),
-                            Block::ChiseledStoneBricks => (98, 3),
-                            Block::BrownMushroomStem { stem_directions } => {
-                                (99, mushroom_stems(stem_directions))
-                            }
-                            Block::BrownMushroomBlock { cap_directions } => {
-                                (99, mushroom_caps(cap_directions))
-                            }
-                            Block::RedMushroomStem { stem_directions } => {
-                                (100, mushroom_stems(stem_directions))
-                            }
-                            Block::RedMushroomBlock { cap_directions } => {
-                                (100, mushroom_caps(cap_directions))
-                            }
-                            Block::IronBars { .. } => (101, 0),
-                            Block::GlassPane { colour, .. } => match colour {
-                                None => (102, 0),
-                                Some(colour) => (160, (*colour as i32) as u8),
-                            },
-                            Block::Melon => (103, 0),
-                            Block::PumpkinStem { state } => match state {
-                                StemState::Growing(age) => (104, age.get() as u8),
-                                _ => (104, 7), // fallback to fully grown stem (not attached)
-                            },
-                            Block::MelonStem { state } => match state {
-                                StemState::Growing(age) => (105, age.get() as u8),
-                                _ => (105, 7), // fallback to fully grown stem (not attached)
-                            },
-                            Block::Vines(vines) => {
-                                let mut data = if vines.anchored_at.east { 0x8 } else { 0x0 };
-                                data |= if vines.anchored_at.north { 0x4 } else { 0x0 };
-                                data |= if vines.anchored_at.south { 0x1 } else { 0x0 };
-                                data |= if vines.anchored_at.west { 0x2 } else { 0x0 };
-                                (106, data)
-                            }
-                            Block::FenceGate {
-                                facing,
-                                open,
-                                material,
-                            } => {
-                                let facing_data = facing4_swne(facing);
-                                let open_data = if *open { 0x4 } else { 0x0 };
-                                let data = facing_data | open_data;
-                                match material {
-                                    WoodMaterial::Oak => (107, data),
-                                    WoodMaterial::Spruce => (183, data),
-                                    WoodMaterial::Birch => (184, data),
-                                    WoodMaterial::Jungle => (185, data),
-                                    WoodMaterial::DarkOak => (186, data),
-                                    WoodMaterial::Acacia => (187, data),
-                                    _ => (107, data), // fallback to oak fence gate
-                                }
-                            }
-                            // 108 and 109  brick and stone brick stairs already handled
-                            Block::Mycelium => (110, 0),
-                            Block::LilyPad => (111, 0),
-                            Block::NetherBricks => (112, 0),
-                            // 113 nether brick fence already handled
-                            // 114 nether brick stairs already handled
-                            Block::NetherWart { growth_stage } => (115, growth_stage.get() as u8),
-                            Block::EnchantingTable { .. } => (116, 0),
-                            Block::BrewingStand { .. } => (117, 0),
-                            Block::Cauldron { water_level } => (118, water_level.get() as u8),
-                            Block::EndPortal => (119, 0),
-                            Block::EndPortalFrame { facing, has_eye } => {
-                                let facing_data = facing4_swne(facing);
-                                let has_eye_data = if *has_eye { 0x4 } else { 0x0 };
-                                (120, facing_data | has_eye_data)
-                            }
-                            Block::EndStone => (121, 0),
-                            Block::DragonEgg => (122, 0),
-                            Block::RedstoneLamp => (123, 0),
-                            // NB 124 lit redstone lamp is not implemented
-                            // 125 and 126 wooden slabs already handled
-                            Block::CocoaBeans {
-                                growth_stage,
-                                facing,
-                            } => {
-                                let growth_data = (growth_stage.get() as u8) << 2;
-                                let facing_data = facing4_nesw(facing);
-                                (127, growth_data | facing_data)
-                            }
-                            // 128 sandstone stairs already handled
-                            Block::EmeraldOre => (129, 0),
-                            Block::EnderChest { facing, .. } => (130, facing4_xxnswe(facing)),
-                            Block::TripwireHook { facing } => (131, facing4_swne(facing)),
-                            Block::Tripwire => (132, 0),
-                            Block::BlockOfEmerald => (133, 0),
-                            // 134-136 spruce / birch / jungle stairs already handled
-                            // NB 137 command block is not implemented
-                            Block::Beacon(_) => (138, 0),
-                            Block::Wall { material, .. } => match material {
-                                WallMaterial::Cobblestone => (139, 0),
-                                WallMaterial::MossyCobblestone => (139, 1),
-                                _ => (139, 0), // fallback to cobblestone wall
-                            },
-                            Block::FlowerPot(_) => (140, 0),
-                            Block::Carrots { growth_stage } => (141, growth_stage.get() as u8),
-                            Block::Potatoes { growth_stage } => (142, growth_stage.get() as u8),
-                            // 143 oak button already handled
-                            Block::Head(head) => {
-                                let data = match head.placement {
-                                    WallOrRotatedOnFloor::Floor(_) => 1,
-                                    WallOrRotatedOnFloor::Wall(Surface4::North) => 2,
-                                    WallOrRotatedOnFloor::Wall(Surface4::South) => 3,
-                                    WallOrRotatedOnFloor::Wall(Surface4::West) => 4,
-                                    WallOrRotatedOnFloor::Wall(Surface4::East) => 5,
-                                };
-                                (144, data)
-                            }
-                            Block::Anvil { facing, damage } => {
-                                let facing_data = facing4_swne(facing);
-                                let damage_data = match damage {
-                                    AnvilDamage::Intact => 0b0000,
-                                    AnvilDamage::SlightlyDamaged => 0b0100,
-                                    AnvilDamage::VeryDamaged => 0b1000,
-                                };
-                                (145, facing_data | damage_data)
-                            }
-                            Block::TrappedChest(chest) => (146, facing4_xxnswe(&chest.facing)),
-                            // 147 and 148 gold / iron pressure plate already handled
-                            Block::RedstoneComparator { facing } => (149, facing4_nesw(facing)),
-                            Block::RedstoneSubtractor { facing } => {
-                                (149, 0x4 | facing4_nesw(facing))
-                            }
-                            // NB 150 powered redstone comparator is not implemented
-                            Block::DaylightDetector => (151, 0),
-                            Block::BlockOfRedstone => (152, 0),
-                            Block::QuartzOre => (153, 0),
-                            Block::Hopper(hopper) => (154, facing5_dxnswe(&hopper.facing)),
-                            Block::BlockOfQuartz => (155, 0),
-                            Block::ChiseledQuartzBlock => (155, 1),
-                            Block::QuartzPillar { alignment } => match alignment {
-                                Axis3::Y => (155, 2),
-                                Axis3::X => (155, 3),
-                                Axis3::Z => (155, 4),
-                            },
-                            // 156 quartz stairs already handled
-                            // 157 activator rail already handled
-                            Block::Dropper(dropper) => (158, facing6_dunswe(&dropper.facing)),
-                            Block::Terracotta { colour } => match colour {
-                                Some(colour) => (159, (*colour).into()),
-                                None => (172, 0),
-                            },
-                            // 160 coloured glass pane already handled
-                            // 161 acacia / dark oak leaves already handled
-                            // 162 acacia / dark oak logs already handled
-                            // 163 and 164 acacia / dark oak stairs already handled
-                            Block::BlockOfSlime => (165, 0),
-                            Block::Barrier => (166, 0),
-                            // 167 iron trapdoor already handled
-                            Block::Prismarine => (168, 0),
-                            Block::PrismarineBricks => (168, 1),
-                            Block::DarkPrismarine => (168, 2),
-                            Block::SeaLantern => (169, 0),
-                            Block::HayBale { alignment } => match alignment {
-                                Axis3::Y => (170, 0),
-                                Axis3::X => (170, 4),
-                                Axis3::Z => (170, 8),
-                            },
-                            Block::Carpet { colour } => (171, *colour as u8),
-                            // 172 terracotta (no colour) already handled
-                            Block::BlockOfCoal => (173, 0),
-                            Block::PackedIce => (174, 0),
-                            // 175 double tall plants already handled
-                            Block::Banner(banner) => match banner.placement {
-                                WallOrRotatedOnFloor::Floor(facing) => (176, facing as u8),
-                                WallOrRotatedOnFloor::Wall(facing) => {
-                                    (177, facing4_xxnswe(&facing))
-                                }
-                            },
-                            Block::InvertedDaylightDetector => (178, 0),
-                            Block::RedSandstone => (179, 0),
-                            Block::ChiseledRedSandstone => (179, 1),
-                            Block::SmoothRedSandstone => (179, 2),
-                            // 180 red sandstone stairs already handled
-                            // 181 and 182 red sandstone slabs already handled
-                            // 183-187 spruce / birch / jungle / acacia / dark oak fence gates
-                            //     already handled
-                            // 188-192 spruce / birch / jungle / acacia / dark oak fences
-                            //     already handled
-                            // 193-197 spruce / birch / jungle / acacia / dark oak doors
-                            //     already handled
-                            Block::EndRod { facing } => (198, facing6_dunswe(facing)),
-                            Block::ChorusPlant => (199, 0),
-                            Block::ChorusFlower { growth_stage } => (200, growth_stage.get() as u8),
-                            Block::PurpurBlock => (201, 0),
-                            Block::PurpurPillar { alignment } => match alignment {
-                                Axis3::Y => (202, 0),
-                                Axis3::X => (202, 1),
-                                Axis3::Z => (202, 2),
-                            },
-                            // 203 purpur stairs already handled
-                            // 204 and 205 purpur slabs already handled
-                            Block::EndStoneBricks => (206, 0),
-                            Block::Beetroots { growth_stage } => (207, growth_stage.get() as u8),
-                            Block::GrassPath => (208, 0),
-                            Block::EndGateway => (209, 0),
-                            // NB 210 repeating command block is not implemented
-                            // NB 211 chain command block is not implemented
-                            Block::FrostedIce => (212, 0),
-                            Block::MagmaBlock => (213, 0),
-                            Block::NetherWartBlock => (214, 0),
-                            Block::RedNetherBricks => (215, 0),
-                            Block::BoneBlock { alignment } => match alignment {
-                                Axis3::Y => (216, 0),
-                                Axis3::X => (216, 4),
-                                Axis3::Z => (216, 8),
-                            },
-                            Block::StructureVoid => (217, 0),
-                            Block::Observer { facing } => (218, facing6_dunswe(facing)),
-                            Block::ShulkerBox(shulker_box) => {
-                                let colour = shulker_box.colour.unwrap_or(Colour::Purple);
-                                let block_id = (colour as u8) + 219;
-                                (block_id, 0)
-                            }
-                            Block::GlazedTerracotta(glazed_terracotta) => {
-                                let block_id = (glazed_terracotta.colour as u8) + 235;
-                                (block_id, 0)
-                            }
-                            Block::Concrete { colour } => (251, *colour as u8),
-                            Block::ConcretePowder { colour } => (252, *colour as u8),
-                            // NB 255 structure block is not implemented
-                            _ => (0, 0), // fallback to air
-                        };
+                        let (block_id, data_value) = Self::pre_flattening_block_id(block);
 
                         blocks[index] = block_id;
                         utils::set_nibble(&mut data, data_value, index);
@@ -793,9 +275,7 @@ impl Chunk {
             }
         }
 
-        // TODO Somehow fill block light and sky light with reasonable values...
-        let block_light = vec![0xFFu8; 2048];
-        let sky_light = vec![0xFFu8; 2048];
+        let (block_light, sky_light) = self.pre_flattening_section_light(section_y);
 
         // A section is a TAG_Compound containing:
         // - "Y" TAG_Byte index 0 to 15 (bottom to top)
@@ -810,8 +290,6 @@ impl Chunk {
         // and u8 is much easier to work with for that purpose...
         let blocks = utils::vec_u8_into_vec_i8(blocks);
         let data = utils::vec_u8_into_vec_i8(data);
-        let block_light = utils::vec_u8_into_vec_i8(block_light);
-        let sky_light = utils::vec_u8_into_vec_i8(sky_light);
 
         let mut section = nbt::Map::new();
         section.insert("Y".into(), nbt::Value::Byte(section_y));
@@ -820,7 +298,706 @@ impl Chunk {
         section.insert("BlockLight".into(), nbt::Value::ByteArray(block_light));
         section.insert("SkyLight".into(), nbt::Value::ByteArray(sky_light));
 
-        return nbt::Value::Compound(section);
+        nbt::Value::Compound(section)
+    }
+
+    /// Generates the "BlockLight" and "SkyLight" arrays of a section.
+    fn pre_flattening_section_light(&self, section_y: i8) -> (Vec<i8>, Vec<i8>) {
+        let mut block_light = vec![0u8; 2048];
+        let mut sky_light = vec![0u8; 2048];
+        for x in 0..16 {
+            for z in 0..16 {
+                for y in (section_y as i64 * 16)..(16 + section_y as i64 * 16) {
+                    let index = Self::local_index(section_y as i64, (x, y, z).into());
+                    if let Some(level) = self.block_light.light_level_at((x, y, z).into()) {
+                        utils::set_nibble(&mut block_light, level, index);
+                    }
+                    if let Some(level) = self.sky_light.light_level_at((x, y, z).into()) {
+                        utils::set_nibble(&mut sky_light, level, index);
+                    }
+                }
+            }
+        }
+        (
+            utils::vec_u8_into_vec_i8(block_light),
+            utils::vec_u8_into_vec_i8(sky_light),
+        )
+    }
+
+    /// Replaces the light levels of the sections in `nbt`, the pre flattening
+    /// NBT data this chunk was read from, with the light levels of the chunk.
+    ///
+    /// Everything else is kept as read, so that blocks mcprogedit cannot
+    /// represent are not lost. Sections missing from `nbt` hold only air, and
+    /// are added where block light now reaches into them.
+    pub(crate) fn pre_flattening_nbt_with_light(&self, mut nbt: nbt::Blob) -> nbt::Blob {
+        let mut level = match nbt.get("Level") {
+            Some(nbt::Value::Compound(level)) => level.clone(),
+            _ => panic!("Level not found"),
+        };
+        let mut sections = match level.remove("Sections") {
+            Some(nbt::Value::List(sections)) => sections,
+            _ => Vec::new(),
+        };
+
+        let mut found = [false; 16];
+        for section in &mut sections {
+            if let nbt::Value::Compound(section) = section {
+                let section_y = match section.get("Y") {
+                    Some(nbt::Value::Byte(section_y)) if (0..16).contains(section_y) => *section_y,
+                    _ => continue,
+                };
+                found[section_y as usize] = true;
+                let (block_light, sky_light) = self.pre_flattening_section_light(section_y);
+                section.insert("BlockLight".into(), nbt::Value::ByteArray(block_light));
+                section.insert("SkyLight".into(), nbt::Value::ByteArray(sky_light));
+            }
+        }
+        for section_y in 0..16 {
+            if !found[section_y as usize] {
+                let (block_light, _) = self.pre_flattening_section_light(section_y);
+                if block_light.iter().any(|levels| *levels != 0) {
+                    sections.push(self.pre_flattening_section(section_y));
+                }
+            }
+        }
+
+        level.insert("Sections".into(), nbt::Value::List(sections));
+        nbt.insert("Level", nbt::Value::Compound(level)).unwrap();
+        nbt
+    }
+
+    /// Namespaced id of `block`, as named in the pre flattening chunk format.
+    pub(crate) fn pre_flattening_block_name(block: &Block) -> String {
+        let (block_id, _) = Self::pre_flattening_block_id(block);
+        format!(
+            "minecraft:{}",
+            PRE_FLATTENING_BLOCK_NAMES[block_id as usize]
+        )
+    }
+
+    /// Block id and data value of `block`, in the pre flattening chunk format.
+    ///
+    /// Blocks without a pre flattening representation are given as air.
+    pub(crate) fn pre_flattening_block_id(block: &Block) -> (u8, u8) {
+        return match block {
+            Block::Air => (0, 0),
+            Block::Stone => (1, 0),
+            Block::Granite => (1, 1),
+            Block::PolishedGranite => (1, 2),
+            Block::Diorite => (1, 3),
+            Block::PolishedDiorite => (1, 4),
+            Block::Andesite => (1, 5),
+            Block::PolishedAndesite => (1, 6),
+            Block::GrassBlock => (2, 0),
+            Block::Dirt => (3, 0),
+            Block::CoarseDirt => (3, 1),
+            Block::Podzol => (3, 2),
+            Block::Cobblestone => (4, 0),
+            Block::Planks { material } => match material {
+                WoodMaterial::Oak => (5, 0),
+                WoodMaterial::Spruce => (5, 1),
+                WoodMaterial::Birch => (5, 2),
+                WoodMaterial::Jungle => (5, 3),
+                WoodMaterial::Acacia => (5, 4),
+                WoodMaterial::DarkOak => (5, 5),
+                WoodMaterial::Crimson => (5, 0), // Fallback to oak
+                WoodMaterial::Warped => (5, 0),  // Fallback to oak
+            },
+            Block::Sapling {
+                growth_stage,
+                material,
+            } => {
+                let data = (growth_stage.get() as u8) << 3;
+                let data = data
+                    | match material {
+                        SaplingMaterial::Oak => 0,
+                        SaplingMaterial::Spruce => 1,
+                        SaplingMaterial::Birch => 2,
+                        SaplingMaterial::Jungle => 3,
+                        SaplingMaterial::Acacia => 4,
+                        SaplingMaterial::DarkOak => 5,
+                        SaplingMaterial::Bamboo => 0, // Fallback to oak
+                    };
+                (6, data)
+            }
+            Block::Bedrock => (7, 0),
+            Block::WaterSource => (9, 0),
+            Block::Water { falling, level } => {
+                let data = if *falling { 0x8 } else { 0 };
+                let data = data | (8 - (level.get() as u8));
+                (9, data)
+            }
+            Block::LavaSource => (11, 0),
+            Block::Lava { falling, level } => {
+                let data = if *falling { 0x8 } else { 0 };
+                let data = data | (8 - (level.get() as u8));
+                (11, data)
+            }
+            Block::Sand => (12, 0),
+            Block::RedSand => (12, 1),
+            Block::Gravel => (13, 0),
+            Block::GoldOre => (14, 0),
+            Block::IronOre => (15, 0),
+            Block::CoalOre => (16, 0),
+            Block::Log(Log {
+                material,
+                alignment,
+                ..
+            }) => {
+                let data = match alignment {
+                    Some(Axis3::Y) => 0,
+                    Some(Axis3::X) => 1 << 2,
+                    Some(Axis3::Z) => 2 << 2,
+                    None => 3 << 2,
+                };
+                match material {
+                    WoodMaterial::Oak => (17, data),
+                    WoodMaterial::Spruce => (17, data | 1),
+                    WoodMaterial::Birch => (17, data | 2),
+                    WoodMaterial::Jungle => (17, data | 3),
+                    WoodMaterial::Acacia => (162, data),
+                    WoodMaterial::DarkOak => (162, data | 1),
+                    WoodMaterial::Crimson => (17, data), // Fallback to oak
+                    WoodMaterial::Warped => (17, data),  // Fallback to oak
+                }
+            }
+            Block::Leaves {
+                material,
+                persistent,
+                ..
+            } => {
+                let data = if *persistent { 0x4 } else { 0 };
+                match material {
+                    LeavesMaterial::Oak => (18, data),
+                    LeavesMaterial::Spruce => (18, data | 1),
+                    LeavesMaterial::Birch => (18, data | 2),
+                    LeavesMaterial::Jungle => (18, data | 3),
+                    LeavesMaterial::Acacia => (161, data),
+                    LeavesMaterial::DarkOak => (161, data | 1),
+                }
+            }
+            Block::Sponge => (19, 0),
+            Block::WetSponge => (19, 1),
+            Block::Glass { colour } => match colour {
+                None => (20, 0),
+                Some(colour) => (95, (*colour as i32) as u8),
+            },
+            Block::LapisLazuliOre => (21, 0),
+            Block::LapisLazuliBlock => (22, 0),
+            Block::Dispenser(dispenser) => (23, facing6_dunswe(&dispenser.facing)),
+            Block::Sandstone => (24, 0),
+            Block::ChiseledSandstone => (24, 1),
+            Block::SmoothSandstone => (24, 2),
+            Block::Noteblock(_) => (25, 0),
+            Block::Bed(bed) => {
+                let end_data = match bed.end {
+                    BedEnd::Head => 0x8,
+                    BedEnd::Foot => 0x0,
+                };
+                (26, facing4_swne(&bed.facing) | end_data)
+            }
+            Block::Rail { variant, shape, .. } => {
+                let shape_data = shape.to_value();
+                match variant {
+                    RailType::Powered => (27, shape_data),
+                    RailType::Detector => (28, shape_data),
+                    RailType::Normal => (66, shape_data),
+                    RailType::Activator => (157, shape_data),
+                }
+            }
+            Block::StickyPiston {
+                facing, extended, ..
+            } => {
+                let extended_data = match extended {
+                    true => 0x8,
+                    false => 0x0,
+                };
+                (29, facing6_dunswe(facing) & extended_data)
+            }
+            Block::Cobweb => (30, 0),
+            Block::Grass(grass) => match grass {
+                Grass::Grass => (31, 1),
+                Grass::Fern => (31, 2),
+                Grass::TallGrassBottom => (175, 2),
+                Grass::TallGrassTop => (175, 8),
+                Grass::LargeFernBottom => (175, 3),
+                Grass::LargeFernTop => (175, 8),
+            },
+            Block::DeadBush => (32, 0),
+            Block::Piston {
+                facing, extended, ..
+            } => {
+                let extended_data = match extended {
+                    true => 0x8,
+                    false => 0x0,
+                };
+                (33, facing6_dunswe(facing) | extended_data)
+            }
+            Block::StickyPistonHead { facing } => {
+                let sticky = 0x8;
+                (34, sticky | facing6_dunswe(facing))
+            }
+            Block::PistonHead { facing } => (34, facing6_dunswe(facing)),
+            Block::Wool { colour } => (35, (*colour).into()),
+            // NB 36 "Block moved by Piston" not implemented
+            Block::Flower(flower) => {
+                match flower {
+                    Flower::Dandelion => (37, 0),
+                    Flower::Poppy => (38, 0),
+                    Flower::BlueOrchid => (38, 1),
+                    Flower::Allium => (38, 2),
+                    Flower::AzureBluet => (38, 3),
+                    Flower::TulipRed => (38, 4),
+                    Flower::TulipOrange => (38, 5),
+                    Flower::TulipWhite => (38, 6),
+                    Flower::TulipPink => (38, 7),
+                    Flower::OxeyeDaisy => (38, 8),
+                    Flower::SunflowerBottom => (175, 0),
+                    Flower::SunflowerTop => (175, 8),
+                    Flower::LilacBottom => (175, 1),
+                    Flower::LilacTop => (175, 8),
+                    Flower::RoseBushBottom => (175, 4),
+                    Flower::RoseBushTop => (175, 8),
+                    Flower::PeonyBottom => (175, 5),
+                    Flower::PeonyTop => (175, 8),
+                    Flower::Cornflower | Flower::LilyOfTheValley | Flower::WitherRose => (37, 0), // Fallback to Dandelion
+                }
+            }
+            Block::BrownMushroom => (39, 0),
+            Block::RedMushroom => (40, 0),
+            Block::BlockOfGold => (41, 0),
+            Block::BlockOfIron => (42, 0),
+            Block::Slab(slab) => {
+                let position_data = match slab.position {
+                    SlabVariant::Top => 0x8,
+                    _ => 0x0,
+                };
+                let data = match slab.material {
+                    SlabMaterial::SmoothStone
+                    | SlabMaterial::Oak
+                    | SlabMaterial::RedSandstone
+                    | SlabMaterial::Purpur => position_data,
+                    SlabMaterial::Sandstone | SlabMaterial::Spruce => 1 | position_data,
+                    SlabMaterial::PetrifiedOak | SlabMaterial::Birch => 2 | position_data,
+                    SlabMaterial::Cobblestone | SlabMaterial::Jungle => 3 | position_data,
+                    SlabMaterial::Brick | SlabMaterial::Acacia => 4 | position_data,
+                    SlabMaterial::StoneBrick | SlabMaterial::DarkOak => 5 | position_data,
+                    SlabMaterial::NetherBrick => 6 | position_data,
+                    SlabMaterial::Quartz => 7 | position_data,
+                    _ => position_data, // fallback to SmoothStone
+                };
+                let block_id = match slab.material {
+                    SlabMaterial::SmoothStone
+                    | SlabMaterial::Sandstone
+                    | SlabMaterial::PetrifiedOak
+                    | SlabMaterial::Cobblestone
+                    | SlabMaterial::Brick
+                    | SlabMaterial::StoneBrick
+                    | SlabMaterial::NetherBrick
+                    | SlabMaterial::Quartz => match slab.position {
+                        SlabVariant::Double => 43,
+                        _ => 44,
+                    },
+                    SlabMaterial::Spruce
+                    | SlabMaterial::Birch
+                    | SlabMaterial::Jungle
+                    | SlabMaterial::Acacia
+                    | SlabMaterial::DarkOak => match slab.position {
+                        SlabVariant::Double => 125,
+                        _ => 126,
+                    },
+                    SlabMaterial::RedSandstone => match slab.position {
+                        SlabVariant::Double => 181,
+                        _ => 182,
+                    },
+                    SlabMaterial::Purpur => match slab.position {
+                        SlabVariant::Double => 204,
+                        _ => 205,
+                    },
+                    // fallback to SmoothStone
+                    _ => match slab.position {
+                        SlabVariant::Double => 43,
+                        _ => 44,
+                    },
+                };
+                (block_id, data)
+            }
+            Block::BrickBlock => (45, 0),
+            Block::TNT => (46, 0),
+            Block::Bookshelf => (47, 0),
+            Block::MossyCobblestone => (48, 0),
+            Block::Obsidian => (49, 0),
+            Block::Torch { attached } => (50, facing5_xwensd(attached)),
+            Block::Fire { age } => (51, age.get() as u8),
+            // NB 52 mob spawner is not implemented
+            Block::Stairs(stair) => {
+                let data = stair.position.into();
+                match stair.material {
+                    StairMaterial::Oak => (53, data),
+                    StairMaterial::Cobblestone => (67, data),
+                    StairMaterial::Brick => (108, data),
+                    StairMaterial::StoneBrick => (109, data),
+                    StairMaterial::NetherBrick => (114, data),
+                    StairMaterial::Sandstone => (128, data),
+                    StairMaterial::Spruce => (134, data),
+                    StairMaterial::Birch => (135, data),
+                    StairMaterial::Jungle => (136, data),
+                    StairMaterial::Quartz => (156, data),
+                    StairMaterial::Acacia => (163, data),
+                    StairMaterial::DarkOak => (164, data),
+                    StairMaterial::RedSandstone => (180, data),
+                    StairMaterial::Purpur => (203, data),
+                    _ => (53, data), // fallback to oak stairs
+                }
+            }
+            Block::Chest(chest) => (54, facing4_xxnswe(&chest.facing)),
+            Block::RedstoneWire => (55, 0),
+            Block::DiamondOre => (56, 0),
+            Block::BlockOfDiamond => (57, 0),
+            Block::CraftingTable => (58, 0),
+            Block::Wheat { growth_stage } => (59, growth_stage.get() as u8),
+            Block::Farmland { wetness } => (60, wetness.get() as u8),
+            Block::Furnace(furnace) => {
+                let block_id = if furnace.lit { 62 } else { 61 };
+                (block_id, facing4_xxnswe(&furnace.facing))
+            }
+            Block::Sign(sign) => match sign.placement {
+                WallOrRotatedOnFloor::Floor(facing) => (63, facing.into()),
+                WallOrRotatedOnFloor::Wall(facing) => (68, facing4_xxnswe(&facing)),
+            },
+            Block::Door(door) => {
+                let data = match door.half {
+                    DoorHalf::Upper => {
+                        let upper = 0x8;
+                        let hinge = match door.hinged_at {
+                            Hinge::Right => 0x1,
+                            Hinge::Left => 0x0,
+                        };
+                        upper | hinge
+                    }
+                    DoorHalf::Lower => {
+                        let lower = 0x0;
+                        let open = if door.open { 0x4 } else { 0x0 };
+                        let facing = facing4_wnes(&door.facing);
+                        lower | open | facing
+                    }
+                };
+                let block_id = match door.material {
+                    DoorMaterial::Oak => 64,
+                    DoorMaterial::Iron => 71,
+                    DoorMaterial::Spruce => 193,
+                    DoorMaterial::Birch => 194,
+                    DoorMaterial::Jungle => 195,
+                    DoorMaterial::Acacia => 196,
+                    DoorMaterial::DarkOak => 197,
+                    _ => 64, // fallback to oak door
+                };
+                (block_id, data)
+            }
+            Block::Ladder { facing, .. } => (65, facing4_xxnswe(facing)),
+            // 66 normal rail already handled
+            // 67 cobblestone stairs already handled
+            // 68 standing sign already handled
+            Block::Lever(facing, state) => {
+                let state_data = match state {
+                    OnOffState::On => 0x8,
+                    OnOffState::Off => 0x0,
+                };
+                let data = state_data | lever_facing(facing);
+                (69, data)
+            }
+            Block::PressurePlate { material } => {
+                match material {
+                    PressurePlateMaterial::Stone => (70, 0),
+                    PressurePlateMaterial::Oak => (72, 0),
+                    PressurePlateMaterial::Gold => (147, 0),
+                    PressurePlateMaterial::Iron => (148, 0),
+                    _ => (72, 0), // fallback to oak pressure plate
+                }
+            }
+            // 71 iron door already handled
+            // 72 oak pressure plate already handled
+            Block::RedstoneOre => (73, 0),
+            // NB 74 lit redstone ore is not implemented
+            // NB 75 unlit redstone torch is not implemented
+            Block::RedstoneTorch { attached } => (76, facing5_xwensd(attached)),
+            Block::Button(material, facing) => {
+                let data = facing6_dewsnu(facing);
+                match material {
+                    ButtonMaterial::Stone => (77, data),
+                    ButtonMaterial::Oak => (143, data),
+                    _ => (143, data), // fallback to oak button
+                }
+            }
+            Block::Snow { thickness } => {
+                let data = (thickness.get() as u8) + 1;
+                (78, data)
+            }
+            Block::Ice => (79, 0),
+            Block::SnowBlock => (80, 0),
+            Block::Cactus { growth_stage } => (81, growth_stage.get() as u8),
+            Block::Clay => (82, 0),
+            Block::SugarCane { growth_stage } => (83, growth_stage.get() as u8),
+            Block::Jukebox(_) => (84, 0),
+            Block::Fence { material, .. } => {
+                match material {
+                    FenceMaterial::Oak => (85, 0),
+                    FenceMaterial::NetherBrick => (113, 0),
+                    FenceMaterial::Spruce => (188, 0),
+                    FenceMaterial::Birch => (189, 0),
+                    FenceMaterial::Jungle => (190, 0),
+                    FenceMaterial::DarkOak => (191, 0),
+                    FenceMaterial::Acacia => (192, 0),
+                    _ => (85, 0), // fallback to oak fence
+                }
+            }
+            Block::Pumpkin { facing } => (86, facing4_swne(facing)),
+            Block::Netherrack => (87, 0),
+            Block::SoulSand => (88, 0),
+            Block::Glowstone => (89, 0),
+            Block::NetherPortal { .. } => (90, 0),
+            Block::JackOLantern { facing } => (91, facing4_swne(facing)),
+            Block::Cake { pieces } => (92, 7 - (pieces.get() as u8)),
+            Block::RedstoneRepeater(repeater) => {
+                let delay_data = (repeater.delay.get() as u8) << 2;
+                let facing_data = facing4_nesw(&repeater.facing);
+                (93, delay_data | facing_data)
+            }
+            // NB 94 powered redstone repeater is not implemented
+            //       (may be added to Block::RedstoneRepeater in the future)
+            // 95 coloured class already handled
+            Block::Trapdoor(trapdoor) => {
+                let open_data = if trapdoor.open { 0x4 } else { 0x0 };
+                let hinge_data = trapdoor_hinge_at(&trapdoor.hinge_at);
+                let data = open_data | hinge_data;
+                match trapdoor.material {
+                    DoorMaterial::Oak => (96, data),
+                    DoorMaterial::Iron => (167, data),
+                    _ => (96, data), // fallback to oak trapdoor
+                }
+            }
+            Block::InfestedStone => (97, 0),
+            Block::InfestedCobblestone => (97, 1),
+            Block::InfestedStoneBricks => (97, 2),
+            Block::InfestedMossyStoneBricks => (97, 3),
+            Block::InfestedCrackedStoneBricks => (97, 4),
+            Block::InfestedChiseledStoneBricks => (97, 5),
+            Block::StoneBricks => (98, 0),
+            Block::MossyStoneBricks => (98, 1),
+            Block::CrackedStoneBricks => (98, 2),
+            Block::ChiseledStoneBricks => (98, 3),
+            Block::BrownMushroomStem { stem_directions } => (99, mushroom_stems(stem_directions)),
+            Block::BrownMushroomBlock { cap_directions } => (99, mushroom_caps(cap_directions)),
+            Block::RedMushroomStem { stem_directions } => (100, mushroom_stems(stem_directions)),
+            Block::RedMushroomBlock { cap_directions } => (100, mushroom_caps(cap_directions)),
+            Block::IronBars { .. } => (101, 0),
+            Block::GlassPane { colour, .. } => match colour {
+                None => (102, 0),
+                Some(colour) => (160, (*colour as i32) as u8),
+            },
+            Block::Melon => (103, 0),
+            Block::PumpkinStem { state } => match state {
+                StemState::Growing(age) => (104, age.get() as u8),
+                _ => (104, 7), // fallback to fully grown stem (not attached)
+            },
+            Block::MelonStem { state } => match state {
+                StemState::Growing(age) => (105, age.get() as u8),
+                _ => (105, 7), // fallback to fully grown stem (not attached)
+            },
+            Block::Vines(vines) => {
+                let mut data = if vines.anchored_at.east { 0x8 } else { 0x0 };
+                data |= if vines.anchored_at.north { 0x4 } else { 0x0 };
+                data |= if vines.anchored_at.south { 0x1 } else { 0x0 };
+                data |= if vines.anchored_at.west { 0x2 } else { 0x0 };
+                (106, data)
+            }
+            Block::FenceGate {
+                facing,
+                open,
+                material,
+            } => {
+                let facing_data = facing4_swne(facing);
+                let open_data = if *open { 0x4 } else { 0x0 };
+                let data = facing_data | open_data;
+                match material {
+                    WoodMaterial::Oak => (107, data),
+                    WoodMaterial::Spruce => (183, data),
+                    WoodMaterial::Birch => (184, data),
+                    WoodMaterial::Jungle => (185, data),
+                    WoodMaterial::DarkOak => (186, data),
+                    WoodMaterial::Acacia => (187, data),
+                    _ => (107, data), // fallback to oak fence gate
+                }
+            }
+            // 108 and 109  brick and stone brick stairs already handled
+            Block::Mycelium => (110, 0),
+            Block::LilyPad => (111, 0),
+            Block::NetherBricks => (112, 0),
+            // 113 nether brick fence already handled
+            // 114 nether brick stairs already handled
+            Block::NetherWart { growth_stage } => (115, growth_stage.get() as u8),
+            Block::EnchantingTable { .. } => (116, 0),
+            Block::BrewingStand { .. } => (117, 0),
+            Block::Cauldron { water_level } => (118, water_level.get() as u8),
+            Block::EndPortal => (119, 0),
+            Block::EndPortalFrame { facing, has_eye } => {
+                let facing_data = facing4_swne(facing);
+                let has_eye_data = if *has_eye { 0x4 } else { 0x0 };
+                (120, facing_data | has_eye_data)
+            }
+            Block::EndStone => (121, 0),
+            Block::DragonEgg => (122, 0),
+            Block::RedstoneLamp => (123, 0),
+            // NB 124 lit redstone lamp is not implemented
+            // 125 and 126 wooden slabs already handled
+            Block::CocoaBeans {
+                growth_stage,
+                facing,
+            } => {
+                let growth_data = (growth_stage.get() as u8) << 2;
+                let facing_data = facing4_nesw(facing);
+                (127, growth_data | facing_data)
+            }
+            // 128 sandstone stairs already handled
+            Block::EmeraldOre => (129, 0),
+            Block::EnderChest { facing, .. } => (130, facing4_xxnswe(facing)),
+            Block::TripwireHook { facing } => (131, facing4_swne(facing)),
+            Block::Tripwire => (132, 0),
+            Block::BlockOfEmerald => (133, 0),
+            // 134-136 spruce / birch / jungle stairs already handled
+            // NB 137 command block is not implemented
+            Block::Beacon(_) => (138, 0),
+            Block::Wall { material, .. } => match material {
+                WallMaterial::Cobblestone => (139, 0),
+                WallMaterial::MossyCobblestone => (139, 1),
+                _ => (139, 0), // fallback to cobblestone wall
+            },
+            Block::FlowerPot(_) => (140, 0),
+            Block::Carrots { growth_stage } => (141, growth_stage.get() as u8),
+            Block::Potatoes { growth_stage } => (142, growth_stage.get() as u8),
+            // 143 oak button already handled
+            Block::Head(head) => {
+                let data = match head.placement {
+                    WallOrRotatedOnFloor::Floor(_) => 1,
+                    WallOrRotatedOnFloor::Wall(Surface4::North) => 2,
+                    WallOrRotatedOnFloor::Wall(Surface4::South) => 3,
+                    WallOrRotatedOnFloor::Wall(Surface4::West) => 4,
+                    WallOrRotatedOnFloor::Wall(Surface4::East) => 5,
+                };
+                (144, data)
+            }
+            Block::Anvil { facing, damage } => {
+                let facing_data = facing4_swne(facing);
+                let damage_data = match damage {
+                    AnvilDamage::Intact => 0b0000,
+                    AnvilDamage::SlightlyDamaged => 0b0100,
+                    AnvilDamage::VeryDamaged => 0b1000,
+                };
+                (145, facing_data | damage_data)
+            }
+            Block::TrappedChest(chest) => (146, facing4_xxnswe(&chest.facing)),
+            // 147 and 148 gold / iron pressure plate already handled
+            Block::RedstoneComparator { facing } => (149, facing4_nesw(facing)),
+            Block::RedstoneSubtractor { facing } => (149, 0x4 | facing4_nesw(facing)),
+            // NB 150 powered redstone comparator is not implemented
+            Block::DaylightDetector => (151, 0),
+            Block::BlockOfRedstone => (152, 0),
+            Block::QuartzOre => (153, 0),
+            Block::Hopper(hopper) => (154, facing5_dxnswe(&hopper.facing)),
+            Block::BlockOfQuartz => (155, 0),
+            Block::ChiseledQuartzBlock => (155, 1),
+            Block::QuartzPillar { alignment } => match alignment {
+                Axis3::Y => (155, 2),
+                Axis3::X => (155, 3),
+                Axis3::Z => (155, 4),
+            },
+            // 156 quartz stairs already handled
+            // 157 activator rail already handled
+            Block::Dropper(dropper) => (158, facing6_dunswe(&dropper.facing)),
+            Block::Terracotta { colour } => match colour {
+                Some(colour) => (159, (*colour).into()),
+                None => (172, 0),
+            },
+            // 160 coloured glass pane already handled
+            // 161 acacia / dark oak leaves already handled
+            // 162 acacia / dark oak logs already handled
+            // 163 and 164 acacia / dark oak stairs already handled
+            Block::BlockOfSlime => (165, 0),
+            Block::Barrier => (166, 0),
+            // 167 iron trapdoor already handled
+            Block::Prismarine => (168, 0),
+            Block::PrismarineBricks => (168, 1),
+            Block::DarkPrismarine => (168, 2),
+            Block::SeaLantern => (169, 0),
+            Block::HayBale { alignment } => match alignment {
+                Axis3::Y => (170, 0),
+                Axis3::X => (170, 4),
+                Axis3::Z => (170, 8),
+            },
+            Block::Carpet { colour } => (171, *colour as u8),
+            // 172 terracotta (no colour) already handled
+            Block::BlockOfCoal => (173, 0),
+            Block::PackedIce => (174, 0),
+            // 175 double tall plants already handled
+            Block::Banner(banner) => match banner.placement {
+                WallOrRotatedOnFloor::Floor(facing) => (176, facing as u8),
+                WallOrRotatedOnFloor::Wall(facing) => (177, facing4_xxnswe(&facing)),
+            },
+            Block::InvertedDaylightDetector => (178, 0),
+            Block::RedSandstone => (179, 0),
+            Block::ChiseledRedSandstone => (179, 1),
+            Block::SmoothRedSandstone => (179, 2),
+            // 180 red sandstone stairs already handled
+            // 181 and 182 red sandstone slabs already handled
+            // 183-187 spruce / birch / jungle / acacia / dark oak fence gates
+            //     already handled
+            // 188-192 spruce / birch / jungle / acacia / dark oak fences
+            //     already handled
+            // 193-197 spruce / birch / jungle / acacia / dark oak doors
+            //     already handled
+            Block::EndRod { facing } => (198, facing6_dunswe(facing)),
+            Block::ChorusPlant => (199, 0),
+            Block::ChorusFlower { growth_stage } => (200, growth_stage.get() as u8),
+            Block::PurpurBlock => (201, 0),
+            Block::PurpurPillar { alignment } => match alignment {
+                Axis3::Y => (202, 0),
+                Axis3::X => (202, 1),
+                Axis3::Z => (202, 2),
+            },
+            // 203 purpur stairs already handled
+            // 204 and 205 purpur slabs already handled
+            Block::EndStoneBricks => (206, 0),
+            Block::Beetroots { growth_stage } => (207, growth_stage.get() as u8),
+            Block::GrassPath => (208, 0),
+            Block::EndGateway => (209, 0),
+            // NB 210 repeating command block is not implemented
+            // NB 211 chain command block is not implemented
+            Block::FrostedIce => (212, 0),
+            Block::MagmaBlock => (213, 0),
+            Block::NetherWartBlock => (214, 0),
+            Block::RedNetherBricks => (215, 0),
+            Block::BoneBlock { alignment } => match alignment {
+                Axis3::Y => (216, 0),
+                Axis3::X => (216, 4),
+                Axis3::Z => (216, 8),
+            },
+            Block::StructureVoid => (217, 0),
+            Block::Observer { facing } => (218, facing6_dunswe(facing)),
+            Block::ShulkerBox(shulker_box) => {
+                let colour = shulker_box.colour.unwrap_or(Colour::Purple);
+                let block_id = (colour as u8) + 219;
+                (block_id, 0)
+            }
+            Block::GlazedTerracotta(glazed_terracotta) => {
+                let block_id = (glazed_terracotta.colour as u8) + 235;
+                (block_id, 0)
+            }
+            Block::Concrete { colour } => (251, *colour as u8),
+            Block::ConcretePowder { colour } => (252, *colour as u8),
+            // NB 255 structure block is not implemented
+            _ => (0, 0), // fallback to air
+        };
 
         fn facing4_nesw(facing: &Surface4) -> u8 {
             match facing {
@@ -2413,6 +2590,268 @@ impl Chunk {
     }
 }
 
+/// Names of the pre flattening block ids, without namespace.
+///
+/// Ids 253 and 254 are unused, and named as air.
+const PRE_FLATTENING_BLOCK_NAMES: [&str; 256] = [
+    "air",
+    "stone",
+    "grass",
+    "dirt",
+    "cobblestone",
+    "planks",
+    "sapling",
+    "bedrock",
+    "flowing_water",
+    "water",
+    "flowing_lava",
+    "lava",
+    "sand",
+    "gravel",
+    "gold_ore",
+    "iron_ore",
+    "coal_ore",
+    "log",
+    "leaves",
+    "sponge",
+    "glass",
+    "lapis_ore",
+    "lapis_block",
+    "dispenser",
+    "sandstone",
+    "noteblock",
+    "bed",
+    "golden_rail",
+    "detector_rail",
+    "sticky_piston",
+    "web",
+    "tallgrass",
+    "deadbush",
+    "piston",
+    "piston_head",
+    "wool",
+    "piston_extension",
+    "yellow_flower",
+    "red_flower",
+    "brown_mushroom",
+    "red_mushroom",
+    "gold_block",
+    "iron_block",
+    "double_stone_slab",
+    "stone_slab",
+    "brick_block",
+    "tnt",
+    "bookshelf",
+    "mossy_cobblestone",
+    "obsidian",
+    "torch",
+    "fire",
+    "mob_spawner",
+    "oak_stairs",
+    "chest",
+    "redstone_wire",
+    "diamond_ore",
+    "diamond_block",
+    "crafting_table",
+    "wheat",
+    "farmland",
+    "furnace",
+    "lit_furnace",
+    "standing_sign",
+    "wooden_door",
+    "ladder",
+    "rail",
+    "stone_stairs",
+    "wall_sign",
+    "lever",
+    "stone_pressure_plate",
+    "iron_door",
+    "wooden_pressure_plate",
+    "redstone_ore",
+    "lit_redstone_ore",
+    "unlit_redstone_torch",
+    "redstone_torch",
+    "stone_button",
+    "snow_layer",
+    "ice",
+    "snow",
+    "cactus",
+    "clay",
+    "reeds",
+    "jukebox",
+    "fence",
+    "pumpkin",
+    "netherrack",
+    "soul_sand",
+    "glowstone",
+    "portal",
+    "lit_pumpkin",
+    "cake",
+    "unpowered_repeater",
+    "powered_repeater",
+    "stained_glass",
+    "trapdoor",
+    "monster_egg",
+    "stonebrick",
+    "brown_mushroom_block",
+    "red_mushroom_block",
+    "iron_bars",
+    "glass_pane",
+    "melon_block",
+    "pumpkin_stem",
+    "melon_stem",
+    "vine",
+    "fence_gate",
+    "brick_stairs",
+    "stone_brick_stairs",
+    "mycelium",
+    "waterlily",
+    "nether_brick",
+    "nether_brick_fence",
+    "nether_brick_stairs",
+    "nether_wart",
+    "enchanting_table",
+    "brewing_stand",
+    "cauldron",
+    "end_portal",
+    "end_portal_frame",
+    "end_stone",
+    "dragon_egg",
+    "redstone_lamp",
+    "lit_redstone_lamp",
+    "double_wooden_slab",
+    "wooden_slab",
+    "cocoa",
+    "sandstone_stairs",
+    "emerald_ore",
+    "ender_chest",
+    "tripwire_hook",
+    "tripwire",
+    "emerald_block",
+    "spruce_stairs",
+    "birch_stairs",
+    "jungle_stairs",
+    "command_block",
+    "beacon",
+    "cobblestone_wall",
+    "flower_pot",
+    "carrots",
+    "potatoes",
+    "wooden_button",
+    "skull",
+    "anvil",
+    "trapped_chest",
+    "light_weighted_pressure_plate",
+    "heavy_weighted_pressure_plate",
+    "unpowered_comparator",
+    "powered_comparator",
+    "daylight_detector",
+    "redstone_block",
+    "quartz_ore",
+    "hopper",
+    "quartz_block",
+    "quartz_stairs",
+    "activator_rail",
+    "dropper",
+    "stained_hardened_clay",
+    "stained_glass_pane",
+    "leaves2",
+    "log2",
+    "acacia_stairs",
+    "dark_oak_stairs",
+    "slime",
+    "barrier",
+    "iron_trapdoor",
+    "prismarine",
+    "sea_lantern",
+    "hay_block",
+    "carpet",
+    "hardened_clay",
+    "coal_block",
+    "packed_ice",
+    "double_plant",
+    "standing_banner",
+    "wall_banner",
+    "daylight_detector_inverted",
+    "red_sandstone",
+    "red_sandstone_stairs",
+    "double_stone_slab2",
+    "stone_slab2",
+    "spruce_fence_gate",
+    "birch_fence_gate",
+    "jungle_fence_gate",
+    "dark_oak_fence_gate",
+    "acacia_fence_gate",
+    "spruce_fence",
+    "birch_fence",
+    "jungle_fence",
+    "dark_oak_fence",
+    "acacia_fence",
+    "spruce_door",
+    "birch_door",
+    "jungle_door",
+    "acacia_door",
+    "dark_oak_door",
+    "end_rod",
+    "chorus_plant",
+    "chorus_flower",
+    "purpur_block",
+    "purpur_pillar",
+    "purpur_stairs",
+    "purpur_double_slab",
+    "purpur_slab",
+    "end_bricks",
+    "beetroots",
+    "grass_path",
+    "end_gateway",
+    "repeating_command_block",
+    "chain_command_block",
+    "frosted_ice",
+    "magma",
+    "nether_wart_block",
+    "red_nether_brick",
+    "bone_block",
+    "structure_void",
+    "observer",
+    "white_shulker_box",
+    "orange_shulker_box",
+    "magenta_shulker_box",
+    "light_blue_shulker_box",
+    "yellow_shulker_box",
+    "lime_shulker_box",
+    "pink_shulker_box",
+    "gray_shulker_box",
+    "silver_shulker_box",
+    "cyan_shulker_box",
+    "purple_shulker_box",
+    "blue_shulker_box",
+    "brown_shulker_box",
+    "green_shulker_box",
+    "red_shulker_box",
+    "black_shulker_box",
+    "white_glazed_terracotta",
+    "orange_glazed_terracotta",
+    "magenta_glazed_terracotta",
+    "light_blue_glazed_terracotta",
+    "yellow_glazed_terracotta",
+    "lime_glazed_terracotta",
+    "pink_glazed_terracotta",
+    "gray_glazed_terracotta",
+    "silver_glazed_terracotta",
+    "cyan_glazed_terracotta",
+    "purple_glazed_terracotta",
+    "blue_glazed_terracotta",
+    "brown_glazed_terracotta",
+    "green_glazed_terracotta",
+    "red_glazed_terracotta",
+    "black_glazed_terracotta",
+    "concrete",
+    "concrete_powder",
+    "air",
+    "air",
+    "structure_block",
+];
+
 // FIXME there may be something going on with i8 overflow,
 // which makes the behaviour different from with u8.
 /// Convert byte vector of packed nibbles into byte vector
@@ -2456,4 +2895,45 @@ mod tests {
             vec![0x10, 0x32, 0x54, 0x76]
         );
     }
+
+    #[test]
+    fn recomputed_light_is_written_to_sections() {
+        let mut chunk = Chunk::new((0, 0).into(), McVersion::from_id(1343)); // 1.12.2
+        for x in 0..16 {
+            for z in 0..16 {
+                chunk.blocks.insert((x, 0, z), Block::Stone);
+                chunk.blocks.insert((x, 4, z), Block::Stone);
+            }
+        }
+        chunk.blocks.insert((8, 1, 8), Block::Glowstone);
+        chunk.recompute_light([None, None, None, None]);
+
+        let mut block_light = LightCuboid::new((16, 256, 16));
+        let mut sky_light = LightCuboid::new((16, 256, 16));
+        Chunk::pre_flattening_fill_light_cuboids_from_section(
+            &chunk.pre_flattening_section(0),
+            &mut block_light,
+            &mut sky_light,
+        );
+
+        // Under the roof, lit by glowstone only
+        assert_eq!(block_light.light_level_at((8, 1, 8).into()), Some(15));
+        assert_eq!(block_light.light_level_at((8, 2, 8).into()), Some(14));
+        assert_eq!(block_light.light_level_at((0, 1, 0).into()), Some(0));
+        assert_eq!(sky_light.light_level_at((8, 2, 8).into()), Some(0));
+        // Above the roof, under open sky
+        assert_eq!(sky_light.light_level_at((8, 5, 8).into()), Some(15));
+        assert_eq!(block_light.light_level_at((8, 5, 8).into()), Some(0));
+    }
+
+    #[test]
+    fn height_map_is_read() {
+        let version = McVersion::from_id(1343); // 1.12.2
+        let mut chunk = Chunk::new((0, 0).into(), version);
+        chunk.blocks.insert((3, 40, 5), Block::Stone);
+        let chunk = Chunk::from_pre_flattening_nbt(&chunk.pre_flattening_nbt(), version);
+        let height_map = &chunk.height_maps[&HeightMapKind::LightBlocking];
+        assert_eq!(height_map.height_at((3, 5)), Some(41));
+        assert_eq!(height_map.height_at((0, 0)), Some(0));
+    }
 }
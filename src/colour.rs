@@ -1,4 +1,7 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use serde_derive::{Deserialize, Serialize};
+
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Colour {
     White = 0,
     Orange = 1,
@@ -18,6 +21,30 @@ pub enum Colour {
     Black = 15,
 }
 
+impl Colour {
+    /// Returns the name of the colour as used in block and item ids, e.g. "light_blue".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colour::White => "white",
+            Colour::Orange => "orange",
+            Colour::Magenta => "magenta",
+            Colour::LightBlue => "light_blue",
+            Colour::Yellow => "yellow",
+            Colour::Lime => "lime",
+            Colour::Pink => "pink",
+            Colour::Gray => "gray",
+            Colour::LightGray => "light_gray",
+            Colour::Cyan => "cyan",
+            Colour::Purple => "purple",
+            Colour::Blue => "blue",
+            Colour::Brown => "brown",
+            Colour::Green => "green",
+            Colour::Red => "red",
+            Colour::Black => "black",
+        }
+    }
+}
+
 impl From<i32> for Colour {
     fn from(colour_number: i32) -> Self {
         match colour_number {
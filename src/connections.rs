@@ -0,0 +1,712 @@
+//! Block state properties that are derived from neighbouring blocks.
+//!
+//! Post-flattening saves store e.g. fence connections and stair shapes in the
+//! block state, but mcprogedit leaves them out of its [`Block`] model, as they
+//! follow from the surrounding blocks. Blocks written without them look wrong
+//! in game until they are updated by a neighbour change.
+//!
+//! [`ConnectionStates`] holds these properties for a world excerpt, computed
+//! the same way the game does for:
+//!
+//! * fences, glass panes and iron bars (connections to each side),
+//! * walls (connections to each side and the centre post),
+//! * stairs (corner shapes),
+//! * redstone wire (connections to each side, also up and down a block),
+//! * chests and trapped chests without a set variant (pairing into double chests),
+//! * note blocks (instrument, from the block below).
+//!
+//! Only blocks within the excerpt are taken into account, so blocks along the
+//! excerpt borders do not connect to blocks outside of it.
+//!
+//! Properties are given as in the latest supported Minecraft version, and are
+//! translated for older versions on export.
+
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+
+use crate::block::*;
+use crate::coordinates::BlockCoord;
+use crate::material::{FenceMaterial, Material, WoodMaterial};
+use crate::positioning::*;
+use crate::world_excerpt::WorldExcerpt;
+
+const SIDES: [Surface4; 4] = [
+    Surface4::North,
+    Surface4::East,
+    Surface4::South,
+    Surface4::West,
+];
+
+/// Neighbour-derived block state properties of the blocks of a world excerpt.
+///
+/// Created with
+/// [`WorldExcerpt::resolve_connections()`](crate::world_excerpt::WorldExcerpt::resolve_connections).
+///
+/// # Examples
+/// ```
+/// use mcprogedit::block::Block;
+/// use mcprogedit::material::FenceMaterial;
+/// use mcprogedit::world_excerpt::WorldExcerpt;
+///
+/// let fence = Block::Fence { material: FenceMaterial::Oak, waterlogged: false };
+/// let mut excerpt = WorldExcerpt::new(2, 1, 1);
+/// excerpt.set_block_at((0, 0, 0).into(), fence.clone());
+/// excerpt.set_block_at((1, 0, 0).into(), fence);
+///
+/// let connections = excerpt.resolve_connections();
+/// let properties = connections.get((0, 0, 0).into()).unwrap();
+/// assert_eq!(properties["east"], "true");
+/// assert_eq!(properties["west"], "false");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConnectionStates {
+    properties: HashMap<BlockCoord, BTreeMap<String, String>>,
+}
+
+impl ConnectionStates {
+    /// Computes the neighbour-derived properties of the blocks of `excerpt`.
+    pub fn of(excerpt: &WorldExcerpt) -> Self {
+        let mut states = Self::default();
+        let neighbours = Neighbours { excerpt };
+
+        for (at, block) in excerpt.iter() {
+            let properties = match block {
+                Block::Fence { material, .. } => neighbours.sides(at, |neighbour, side| {
+                    bool_name(fence_connects_to(*material, neighbour, side))
+                }),
+                Block::GlassPane { .. } | Block::IronBars { .. } => {
+                    neighbours.sides(at, |neighbour, _| bool_name(pane_connects_to(neighbour)))
+                }
+                Block::Wall { .. } => wall_properties(&neighbours, at),
+                Block::Stairs(stair) => {
+                    vec![("shape", stair_shape(&neighbours, at, stair).to_string())]
+                }
+                Block::RedstoneWire => wire_properties(&neighbours, at),
+                Block::Chest(chest) | Block::TrappedChest(chest) => {
+                    if chest.variant.is_none() && !states.properties.contains_key(&at) {
+                        states.pair_chest(&neighbours, at, block, chest);
+                    }
+                    continue;
+                }
+                Block::Noteblock(_) => vec![(
+                    "instrument",
+                    instrument(neighbours.at(at, Direction::Down)).to_string(),
+                )],
+                _ => continue,
+            };
+            states.insert(at, properties);
+        }
+
+        states
+    }
+
+    /// Returns the derived properties of the block at `at`, if it has any.
+    pub fn get(&self, at: BlockCoord) -> Option<&BTreeMap<String, String>> {
+        self.properties.get(&at)
+    }
+
+    /// Returns the number of blocks with derived properties.
+    pub fn len(&self) -> usize {
+        self.properties.len()
+    }
+
+    /// Returns true if no block has derived properties.
+    pub fn is_empty(&self) -> bool {
+        self.properties.is_empty()
+    }
+
+    /// Iterates over the blocks with derived properties, and their properties.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&BlockCoord, &BTreeMap<String, String>)> {
+        self.properties.iter()
+    }
+
+    fn insert(&mut self, at: BlockCoord, properties: Vec<(&str, String)>) {
+        let properties = properties
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+        self.properties.insert(at, properties);
+    }
+
+    /// Pairs the chest at `at` with an unpaired neighbour of the same kind and
+    /// facing, if any, the way the game pairs chests that are placed next to
+    /// each other.
+    fn pair_chest(
+        &mut self,
+        neighbours: &Neighbours,
+        at: BlockCoord,
+        block: &Block,
+        chest: &Chest,
+    ) {
+        for (half, variant, other_variant) in &[
+            (ChestVariant::Left, "left", "right"),
+            (ChestVariant::Right, "right", "left"),
+        ] {
+            let side = chest.other_half_side(half).expect("Half of a double chest");
+            let other_at = at + Direction::from(side).offset();
+            let is_partner = match (block, neighbours.excerpt.block_at(other_at)) {
+                (Block::Chest(_), Some(Block::Chest(other)))
+                | (Block::TrappedChest(_), Some(Block::TrappedChest(other))) => {
+                    other.facing == chest.facing
+                        && other.variant.is_none()
+                        && !self.properties.contains_key(&other_at)
+                }
+                _ => false,
+            };
+            if is_partner {
+                self.insert(at, vec![("type", variant.to_string())]);
+                self.insert(other_at, vec![("type", other_variant.to_string())]);
+                return;
+            }
+        }
+        self.insert(at, vec![("type", "single".to_string())]);
+    }
+}
+
+/// Looks up the neighbours of blocks within an excerpt.
+struct Neighbours<'a> {
+    excerpt: &'a WorldExcerpt,
+}
+
+impl<'a> Neighbours<'a> {
+    /// The neighbour of `at` in the given direction, if within the excerpt.
+    fn at(&self, at: BlockCoord, direction: Direction) -> Option<&'a Block> {
+        self.excerpt.block_at(at + direction.offset())
+    }
+
+    /// One property per horizontal side of `at`, with the value given by
+    /// `value` from the neighbour at that side.
+    fn sides<F>(&self, at: BlockCoord, value: F) -> Vec<(&'static str, String)>
+    where
+        F: Fn(Option<&'a Block>, Surface4) -> String,
+    {
+        SIDES
+            .iter()
+            .map(|side| {
+                let neighbour = self.at(at, Direction::from(*side));
+                (side_name(*side), value(neighbour, *side))
+            })
+            .collect()
+    }
+}
+
+fn side_name(side: Surface4) -> &'static str {
+    match side {
+        Surface4::East => "east",
+        Surface4::North => "north",
+        Surface4::South => "south",
+        Surface4::West => "west",
+    }
+}
+
+fn bool_name(value: bool) -> String {
+    value.to_string()
+}
+
+fn is_along_x(side: Surface4) -> bool {
+    matches!(side, Surface4::East | Surface4::West)
+}
+
+/// Returns true for blocks with full, sturdy sides that fences, panes and
+/// walls connect to.
+fn is_sturdy(block: Option<&Block>) -> bool {
+    match block {
+        Some(Block::Barrier)
+        | Some(Block::Cactus { .. })
+        | Some(Block::CarvedPumpkin { .. })
+        | Some(Block::Grass(_))
+        | Some(Block::JackOLantern { .. })
+        | Some(Block::Leaves { .. })
+        | Some(Block::Melon)
+        | Some(Block::Pumpkin { .. })
+        | Some(Block::ShulkerBox(_)) => false,
+        Some(block) => block.is_solid(),
+        None => false,
+    }
+}
+
+/// Returns true if a fence gate is lined up to connect at its `side`.
+fn gate_connects_at(block: Option<&Block>, side: Surface4) -> bool {
+    match block {
+        Some(Block::FenceGate { facing, .. }) => is_along_x(*facing) != is_along_x(side),
+        _ => false,
+    }
+}
+
+fn fence_connects_to(material: FenceMaterial, neighbour: Option<&Block>, side: Surface4) -> bool {
+    let is_wooden =
+        |material: FenceMaterial| WoodMaterial::try_from(Material::from(material)).is_ok();
+    match neighbour {
+        Some(Block::Fence {
+            material: other, ..
+        }) => is_wooden(material) == is_wooden(*other),
+        _ => is_sturdy(neighbour) || gate_connects_at(neighbour, side),
+    }
+}
+
+fn pane_connects_to(neighbour: Option<&Block>) -> bool {
+    matches!(
+        neighbour,
+        Some(Block::GlassPane { .. }) | Some(Block::IronBars { .. }) | Some(Block::Wall { .. })
+    ) || is_sturdy(neighbour)
+}
+
+fn wall_connects_to(neighbour: Option<&Block>, side: Surface4) -> bool {
+    pane_connects_to(neighbour) || gate_connects_at(neighbour, side)
+}
+
+fn wall_properties(neighbours: &Neighbours, at: BlockCoord) -> Vec<(&'static str, String)> {
+    let above_at = at + Direction::Up.offset();
+    let above = neighbours.excerpt.block_at(above_at);
+
+    let heights: Vec<(Surface4, &str)> = SIDES
+        .iter()
+        .map(|side| {
+            let height = if !wall_connects_to(neighbours.at(at, Direction::from(*side)), *side) {
+                "none"
+            } else if is_sturdy(above)
+                || (matches!(above, Some(Block::Wall { .. }))
+                    && wall_connects_to(neighbours.at(above_at, Direction::from(*side)), *side))
+            {
+                "tall"
+            } else {
+                "low"
+            };
+            (*side, height)
+        })
+        .collect();
+
+    // Straight walls have no post, unless the two sides differ in height,
+    // or the post carries something.
+    let height_of = |side: Surface4| heights.iter().find(|(s, _)| *s == side).unwrap().1;
+    let straight = |a: Surface4, b: Surface4, c: Surface4, d: Surface4| {
+        height_of(a) != "none"
+            && height_of(a) == height_of(b)
+            && height_of(c) == "none"
+            && height_of(d) == "none"
+    };
+    let is_straight = straight(
+        Surface4::North,
+        Surface4::South,
+        Surface4::East,
+        Surface4::West,
+    ) || straight(
+        Surface4::East,
+        Surface4::West,
+        Surface4::North,
+        Surface4::South,
+    );
+    let carries_post = matches!(
+        above,
+        Some(Block::Lantern { .. })
+            | Some(Block::RedstoneTorch { .. })
+            | Some(Block::SoulLantern { .. })
+            | Some(Block::SoulTorch { .. })
+            | Some(Block::Torch { .. })
+    );
+
+    let mut properties: Vec<(&'static str, String)> = heights
+        .iter()
+        .map(|(side, height)| (side_name(*side), height.to_string()))
+        .collect();
+    properties.push(("up", bool_name(!is_straight || carries_post)));
+    properties
+}
+
+/// Splits the stair position into whether it is upside down, and its facing.
+fn stair_parts(stair: &Stair) -> (bool, Surface4) {
+    match stair.position {
+        Edge8::DownEast => (false, Surface4::East),
+        Edge8::DownNorth => (false, Surface4::North),
+        Edge8::DownSouth => (false, Surface4::South),
+        Edge8::DownWest => (false, Surface4::West),
+        Edge8::UpEast => (true, Surface4::East),
+        Edge8::UpNorth => (true, Surface4::North),
+        Edge8::UpSouth => (true, Surface4::South),
+        Edge8::UpWest => (true, Surface4::West),
+    }
+}
+
+fn stair_shape(neighbours: &Neighbours, at: BlockCoord, stair: &Stair) -> &'static str {
+    let (top, facing) = stair_parts(stair);
+    let stair_at = |side: Surface4| match neighbours.at(at, Direction::from(side)) {
+        Some(Block::Stairs(other)) => Some(stair_parts(other)),
+        _ => None,
+    };
+    // The shape may only be taken if the stair at `side` does not line up with this one
+    let can_take_shape = |side: Surface4| stair_at(side) != Some((top, facing));
+
+    if let Some((other_top, other_facing)) = stair_at(facing) {
+        if other_top == top
+            && is_along_x(other_facing) != is_along_x(facing)
+            && can_take_shape(opposite(other_facing))
+        {
+            return if other_facing == facing.rotated(Rotation::Counterclockwise90) {
+                "outer_left"
+            } else {
+                "outer_right"
+            };
+        }
+    }
+    if let Some((other_top, other_facing)) = stair_at(opposite(facing)) {
+        if other_top == top
+            && is_along_x(other_facing) != is_along_x(facing)
+            && can_take_shape(other_facing)
+        {
+            return if other_facing == facing.rotated(Rotation::Counterclockwise90) {
+                "inner_left"
+            } else {
+                "inner_right"
+            };
+        }
+    }
+    "straight"
+}
+
+fn opposite(side: Surface4) -> Surface4 {
+    side.rotated(Rotation::Clockwise180)
+}
+
+/// Returns true if redstone wire at the opposite of `side` of `block` connects to it.
+fn wire_connects_to(block: Option<&Block>, side: Surface4) -> bool {
+    match block {
+        Some(Block::RedstoneWire) => true,
+        Some(Block::RedstoneRepeater(repeater)) => is_along_x(repeater.facing) == is_along_x(side),
+        Some(Block::Observer { facing }) => Direction::from(*facing) == Direction::from(side),
+        Some(Block::BlockOfRedstone)
+        | Some(Block::Button(..))
+        | Some(Block::DaylightDetector)
+        | Some(Block::InvertedDaylightDetector)
+        | Some(Block::Lectern { .. })
+        | Some(Block::Lever(..))
+        | Some(Block::PressurePlate { .. })
+        | Some(Block::RedstoneComparator { .. })
+        | Some(Block::RedstoneSubtractor { .. })
+        | Some(Block::RedstoneTorch { .. })
+        | Some(Block::Target)
+        | Some(Block::TrappedChest(_))
+        | Some(Block::TripwireHook { .. })
+        | Some(Block::Rail {
+            variant: RailType::Detector,
+            ..
+        }) => true,
+        _ => false,
+    }
+}
+
+fn wire_properties(neighbours: &Neighbours, at: BlockCoord) -> Vec<(&'static str, String)> {
+    let covered = neighbours
+        .at(at, Direction::Up)
+        .is_some_and(Block::is_solid);
+
+    let mut sides: Vec<(Surface4, &str)> = SIDES
+        .iter()
+        .map(|side| {
+            let side_at = at + Direction::from(*side).offset();
+            let neighbour = neighbours.excerpt.block_at(side_at);
+            let is_wire =
+                |direction| matches!(neighbours.at(side_at, direction), Some(Block::RedstoneWire));
+            let solid_neighbour = neighbour.is_some_and(Block::is_solid);
+
+            let connection = if !covered && solid_neighbour && is_wire(Direction::Up) {
+                "up"
+            } else if wire_connects_to(neighbour, *side)
+                || (!solid_neighbour && is_wire(Direction::Down))
+            {
+                "side"
+            } else {
+                "none"
+            };
+            (*side, connection)
+        })
+        .collect();
+
+    // Wire connected to one side only extends to the opposite side as well,
+    // and unconnected wire is shaped as a cross.
+    let connected = |sides: &[(Surface4, &str)], side: Surface4| {
+        sides
+            .iter()
+            .any(|(s, value)| *s == side && *value != "none")
+    };
+    let none_along_z = !connected(&sides, Surface4::North) && !connected(&sides, Surface4::South);
+    let none_along_x = !connected(&sides, Surface4::East) && !connected(&sides, Surface4::West);
+    for (side, value) in sides.iter_mut() {
+        let extend = if is_along_x(*side) {
+            none_along_z
+        } else {
+            none_along_x
+        };
+        if *value == "none" && extend {
+            *value = "side";
+        }
+    }
+
+    sides
+        .iter()
+        .map(|(side, value)| (side_name(*side), value.to_string()))
+        .collect()
+}
+
+/// The note block instrument played with `below` underneath the note block.
+fn instrument(below: Option<&Block>) -> &'static str {
+    let below = match below {
+        Some(block) => block,
+        None => return "harp",
+    };
+    match below {
+        Block::BlockOfEmerald => "bit",
+        Block::BlockOfGold => "bell",
+        Block::BlockOfIron => "iron_xylophone",
+        Block::BoneBlock { .. } => "xylophone",
+        Block::Clay => "flute",
+        Block::Glowstone => "pling",
+        Block::HayBale { .. } => "banjo",
+        Block::PackedIce => "chime",
+        Block::Pumpkin { .. } => "didgeridoo",
+        Block::SoulSand => "cow_bell",
+        Block::Wool { .. } => "guitar",
+
+        Block::ConcretePowder { .. } | Block::Gravel | Block::RedSand | Block::Sand => "snare",
+        Block::Beacon(_) | Block::Glass { .. } | Block::GlassPane { .. } | Block::SeaLantern => {
+            "hat"
+        }
+
+        Block::Andesite
+        | Block::Bedrock
+        | Block::Blackstone
+        | Block::BlockOfCoal
+        | Block::BlockOfDiamond
+        | Block::BlockOfQuartz
+        | Block::BlockOfRedstone
+        | Block::BrickBlock
+        | Block::ChiseledQuartzBlock
+        | Block::ChiseledRedSandstone
+        | Block::ChiseledSandstone
+        | Block::ChiseledStoneBricks
+        | Block::CoalOre
+        | Block::Cobblestone
+        | Block::Concrete { .. }
+        | Block::CrackedStoneBricks
+        | Block::CutRedSandstone
+        | Block::CutSandstone
+        | Block::DarkPrismarine
+        | Block::DiamondOre
+        | Block::Diorite
+        | Block::EmeraldOre
+        | Block::EndStone
+        | Block::EndStoneBricks
+        | Block::GlazedTerracotta(_)
+        | Block::GoldOre
+        | Block::Granite
+        | Block::IronOre
+        | Block::LapisLazuliBlock
+        | Block::LapisLazuliOre
+        | Block::MagmaBlock
+        | Block::MossyCobblestone
+        | Block::MossyStoneBricks
+        | Block::NetherBricks
+        | Block::Netherrack
+        | Block::Obsidian
+        | Block::PolishedAndesite
+        | Block::PolishedDiorite
+        | Block::PolishedGranite
+        | Block::Prismarine
+        | Block::PrismarineBricks
+        | Block::PurpurBlock
+        | Block::PurpurPillar { .. }
+        | Block::QuartzOre
+        | Block::QuartzPillar { .. }
+        | Block::RedNetherBricks
+        | Block::RedSandstone
+        | Block::RedstoneOre
+        | Block::Sandstone
+        | Block::SmoothQuartz
+        | Block::SmoothRedSandstone
+        | Block::SmoothSandstone
+        | Block::SmoothStone
+        | Block::Stone
+        | Block::StoneBricks
+        | Block::Terracotta { .. } => "basedrum",
+
+        Block::Leaves { .. } | Block::Sapling { .. } => "harp",
+        Block::Bookshelf
+        | Block::Chest(_)
+        | Block::CraftingTable
+        | Block::Jukebox(_)
+        | Block::Noteblock(_)
+        | Block::TrappedChest(_) => "bass",
+        block => match block.material() {
+            Some(material) if WoodMaterial::try_from(material).is_ok() => "bass",
+            _ => "harp",
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::Inventory;
+    use crate::material::{StairMaterial, WallMaterial};
+
+    fn properties(states: &ConnectionStates, at: (i64, i64, i64)) -> Vec<(&str, &str)> {
+        states
+            .get(at.into())
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect()
+    }
+
+    fn stairs(position: Edge8) -> Block {
+        Block::Stairs(Stair {
+            material: StairMaterial::Oak,
+            position,
+            waterlogged: false,
+        })
+    }
+
+    fn chest(facing: Surface4) -> Block {
+        Block::Chest(Box::new(Chest {
+            facing,
+            variant: None,
+            waterlogged: false,
+            custom_name: None,
+            lock: None,
+            items: Inventory::new(),
+        }))
+    }
+
+    #[test]
+    fn fences_panes_and_walls_connect() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 3);
+        excerpt.set_block_at(
+            (1, 0, 1).into(),
+            Block::Fence {
+                material: FenceMaterial::Oak,
+                waterlogged: false,
+            },
+        );
+        excerpt.set_block_at(
+            (0, 0, 1).into(),
+            Block::Fence {
+                material: FenceMaterial::NetherBrick,
+                waterlogged: false,
+            },
+        );
+        excerpt.set_block_at((1, 0, 0).into(), Block::Stone);
+        excerpt.set_block_at((1, 0, 2).into(), Block::oak_fence_gate(Direction::North));
+        excerpt.set_block_at((2, 0, 1).into(), Block::oak_fence_gate(Direction::North));
+        let states = excerpt.resolve_connections();
+        assert_eq!(
+            properties(&states, (1, 0, 1)),
+            vec![
+                ("east", "true"),
+                ("north", "true"),
+                ("south", "false"),
+                ("west", "false"),
+            ]
+        );
+
+        let wall = Block::Wall {
+            material: WallMaterial::Cobblestone,
+            waterlogged: false,
+        };
+        let mut excerpt = WorldExcerpt::new(3, 2, 1);
+        excerpt.set_block_at(
+            (0, 0, 0).into(),
+            Block::GlassPane {
+                colour: None,
+                waterlogged: false,
+            },
+        );
+        excerpt.set_block_at((1, 0, 0).into(), wall.clone());
+        excerpt.set_block_at((2, 0, 0).into(), wall);
+        excerpt.set_block_at((1, 1, 0).into(), Block::Stone);
+        let states = excerpt.resolve_connections();
+        assert_eq!(
+            properties(&states, (1, 0, 0)),
+            vec![
+                ("east", "tall"),
+                ("north", "none"),
+                ("south", "none"),
+                ("up", "false"),
+                ("west", "tall"),
+            ]
+        );
+        assert_eq!(states.get((2, 0, 0).into()).unwrap()["up"], "true");
+        assert_eq!(states.get((0, 0, 0).into()).unwrap()["east"], "true");
+    }
+
+    #[test]
+    fn stair_corners() {
+        // Stairs facing north, with stairs facing west behind them
+        let mut excerpt = WorldExcerpt::new(2, 1, 2);
+        excerpt.set_block_at((1, 0, 1).into(), stairs(Edge8::DownNorth));
+        excerpt.set_block_at((1, 0, 0).into(), stairs(Edge8::DownWest));
+        let states = excerpt.resolve_connections();
+        assert_eq!(states.get((1, 0, 1).into()).unwrap()["shape"], "outer_left");
+
+        // Stairs facing north, with stairs facing east in front of them
+        let mut excerpt = WorldExcerpt::new(2, 1, 2);
+        excerpt.set_block_at((0, 0, 0).into(), stairs(Edge8::DownNorth));
+        excerpt.set_block_at((0, 0, 1).into(), stairs(Edge8::DownEast));
+        let states = excerpt.resolve_connections();
+        assert_eq!(
+            states.get((0, 0, 0).into()).unwrap()["shape"],
+            "inner_right"
+        );
+
+        // Upside down stairs do not form corners with upright ones
+        excerpt.set_block_at((0, 0, 1).into(), stairs(Edge8::UpEast));
+        let states = excerpt.resolve_connections();
+        assert_eq!(states.get((0, 0, 0).into()).unwrap()["shape"], "straight");
+    }
+
+    #[test]
+    fn redstone_wire_shapes() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 3);
+        excerpt.set_block_at((1, 0, 1).into(), Block::RedstoneWire);
+        let states = excerpt.resolve_connections();
+        assert!(states
+            .get((1, 0, 1).into())
+            .unwrap()
+            .values()
+            .all(|value| value == "side"));
+
+        // Wire climbing a block to the east, extending to the west
+        excerpt.set_block_at((2, 0, 1).into(), Block::Stone);
+        excerpt.set_block_at((2, 1, 1).into(), Block::RedstoneWire);
+        let states = excerpt.resolve_connections();
+        assert_eq!(
+            properties(&states, (1, 0, 1)),
+            vec![
+                ("east", "up"),
+                ("north", "none"),
+                ("south", "none"),
+                ("west", "side"),
+            ]
+        );
+    }
+
+    #[test]
+    fn chests_pair_and_note_blocks_get_instruments() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 1);
+        excerpt.set_block_at((0, 0, 0).into(), chest(Surface4::North));
+        excerpt.set_block_at((1, 0, 0).into(), chest(Surface4::North));
+        excerpt.set_block_at((2, 0, 0).into(), chest(Surface4::North));
+        excerpt.set_block_at(
+            (0, 1, 0).into(),
+            Block::Noteblock(Noteblock { pitch: Pitch::A0 }),
+        );
+        excerpt.set_block_at(
+            (1, 1, 0).into(),
+            Block::Noteblock(Noteblock { pitch: Pitch::A0 }),
+        );
+        let states = excerpt.resolve_connections();
+        assert_eq!(states.get((0, 0, 0).into()).unwrap()["type"], "left");
+        assert_eq!(states.get((1, 0, 0).into()).unwrap()["type"], "right");
+        assert_eq!(states.get((2, 0, 0).into()).unwrap()["type"], "single");
+        assert_eq!(states.get((0, 1, 0).into()).unwrap()["instrument"], "bass");
+        assert_eq!(states.len(), 5);
+    }
+}
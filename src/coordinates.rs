@@ -1,4 +1,6 @@
-#[derive(Clone, Copy, Debug, Hash, Ord, PartialEq, PartialOrd, Eq)]
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, Hash, Ord, PartialEq, PartialOrd, Eq, Serialize)]
 pub struct BlockCoord(pub i64, pub i64, pub i64);
 #[derive(Clone, Copy, Debug, Hash, Ord, PartialEq, PartialOrd, Eq)]
 pub struct BlockColumnCoord(pub i64, pub i64);
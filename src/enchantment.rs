@@ -1,10 +1,18 @@
-#[derive(Clone, Debug, PartialEq)]
+use std::str::FromStr;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::mc_version::McVersion;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Enchantment {
     AquaAffinity,
     BaneOfArthropods,
     CurseOfBinding,
     BlastProtection,
+    Breach,
     Channeling,
+    Density,
     DepthStrider,
     Efficiency,
     FeatherFalling,
@@ -35,7 +43,109 @@ pub enum Enchantment {
     Smite,
     SoulSpeed,
     SweepingEdge,
+    SwiftSneak,
     Thorns,
     Unbreaking,
     CurseOfVanishing,
+    WindBurst,
 }
+
+impl Enchantment {
+    /// Enchantment of the given pre-flattening numeric id.
+    pub(crate) fn from_legacy_id(id: i16) -> Option<Self> {
+        ENCHANTMENTS
+            .iter()
+            .find(|(_, _, legacy_id)| *legacy_id == Some(id))
+            .map(|(enchantment, _, _)| *enchantment)
+    }
+
+    /// Numeric id of the enchantment before the flattening, or `None` for
+    /// enchantments added after it.
+    pub(crate) fn legacy_id(self) -> Option<i16> {
+        ENCHANTMENTS
+            .iter()
+            .find(|(enchantment, _, _)| *enchantment == self)
+            .and_then(|(_, _, legacy_id)| *legacy_id)
+    }
+
+    /// Enchantment of the given id, with or without namespace.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim_start_matches("minecraft:");
+        match name {
+            "sweeping" => Some(Enchantment::SweepingEdge),
+            _ => ENCHANTMENTS
+                .iter()
+                .find(|(_, enchantment_name, _)| *enchantment_name == name)
+                .map(|(enchantment, _, _)| *enchantment),
+        }
+    }
+
+    /// Namespaced id of the enchantment in the given Minecraft version.
+    pub(crate) fn name(self, data_version: McVersion) -> String {
+        let name = match self {
+            Enchantment::SweepingEdge if data_version < McVersion::from_str("1.20.5").unwrap() => {
+                "sweeping"
+            }
+            _ => ENCHANTMENTS
+                .iter()
+                .find(|(enchantment, _, _)| *enchantment == self)
+                .map(|(_, name, _)| *name)
+                .unwrap(),
+        };
+        format!("minecraft:{}", name)
+    }
+}
+
+/// Every enchantment, with its modern id and pre-flattening numeric id.
+const ENCHANTMENTS: [(Enchantment, &str, Option<i16>); 42] = [
+    (Enchantment::Protection, "protection", Some(0)),
+    (Enchantment::FireProtection, "fire_protection", Some(1)),
+    (Enchantment::FeatherFalling, "feather_falling", Some(2)),
+    (Enchantment::BlastProtection, "blast_protection", Some(3)),
+    (
+        Enchantment::ProjectileProtection,
+        "projectile_protection",
+        Some(4),
+    ),
+    (Enchantment::Respiration, "respiration", Some(5)),
+    (Enchantment::AquaAffinity, "aqua_affinity", Some(6)),
+    (Enchantment::Thorns, "thorns", Some(7)),
+    (Enchantment::DepthStrider, "depth_strider", Some(8)),
+    (Enchantment::FrostWalker, "frost_walker", Some(9)),
+    (Enchantment::CurseOfBinding, "binding_curse", Some(10)),
+    (Enchantment::SoulSpeed, "soul_speed", None),
+    (Enchantment::SwiftSneak, "swift_sneak", None),
+    (Enchantment::Sharpness, "sharpness", Some(16)),
+    (Enchantment::Smite, "smite", Some(17)),
+    (
+        Enchantment::BaneOfArthropods,
+        "bane_of_arthropods",
+        Some(18),
+    ),
+    (Enchantment::Knokback, "knockback", Some(19)),
+    (Enchantment::FireAspect, "fire_aspect", Some(20)),
+    (Enchantment::Looting, "looting", Some(21)),
+    (Enchantment::SweepingEdge, "sweeping_edge", Some(22)),
+    (Enchantment::Efficiency, "efficiency", Some(32)),
+    (Enchantment::SilkTouch, "silk_touch", Some(33)),
+    (Enchantment::Unbreaking, "unbreaking", Some(34)),
+    (Enchantment::Fortune, "fortune", Some(35)),
+    (Enchantment::Power, "power", Some(48)),
+    (Enchantment::Punch, "punch", Some(49)),
+    (Enchantment::Flame, "flame", Some(50)),
+    (Enchantment::Infinity, "infinity", Some(51)),
+    (Enchantment::LuckOfTheSea, "luck_of_the_sea", Some(61)),
+    (Enchantment::Lure, "lure", Some(62)),
+    (Enchantment::Loyalty, "loyalty", None),
+    (Enchantment::Impaling, "impaling", None),
+    (Enchantment::Riptide, "riptide", None),
+    (Enchantment::Channeling, "channeling", None),
+    (Enchantment::Multishot, "multishot", None),
+    (Enchantment::QuickCharge, "quick_charge", None),
+    (Enchantment::Piercing, "piercing", None),
+    (Enchantment::Density, "density", None),
+    (Enchantment::Breach, "breach", None),
+    (Enchantment::WindBurst, "wind_burst", None),
+    (Enchantment::Mending, "mending", Some(70)),
+    (Enchantment::CurseOfVanishing, "vanishing_curse", Some(71)),
+];
@@ -0,0 +1,723 @@
+//! Entities, such as mobs, item frames, paintings, armor stands and minecarts.
+//!
+//! Only the tags common to all entities are given their own fields. All other
+//! tags, e.g. health, items, or the passengers of a vehicle, are kept as read
+//! from the save, and written back unchanged, except that passengers are moved
+//! along with their vehicle.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use crate::coordinates::BlockCoord;
+use crate::mc_version::McVersion;
+use crate::positioning::{Axis2, Rotation, Surface6};
+use crate::save_format::ChunkFormat;
+
+/// An entity, positioned in block coordinates.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entity {
+    /// Entity id, e.g. "minecraft:cow". Ids are kept as read, and not converted
+    /// between Minecraft versions.
+    pub id: String,
+    /// Position (x, y, z) of the entity.
+    pub position: (f64, f64, f64),
+    /// Velocity (x, y, z) of the entity, in blocks per tick.
+    pub motion: (f64, f64, f64),
+    /// Yaw and pitch of the entity, in degrees.
+    ///
+    /// Yaw is 0 facing south, and increases clockwise as seen from above.
+    /// Pitch is 0 facing the horizon, and positive facing downwards.
+    pub rotation: (f32, f32),
+    pub uuid: Option<u128>,
+    /// Custom name, as stored (a JSON text component from 1.13).
+    pub custom_name: Option<String>,
+    pub on_ground: bool,
+    pub no_gravity: bool,
+    pub silent: bool,
+    pub invulnerable: bool,
+    pub glowing: bool,
+    /// Scoreboard tags of the entity.
+    pub tags: Vec<String>,
+    /// Every other tag of the entity, as read from the save.
+    pub other_tags: nbt::Map<String, nbt::Value>,
+}
+
+impl Entity {
+    /// Creates an entity of type `id` at `position`, with no other properties.
+    pub fn new(id: &str, position: (f64, f64, f64)) -> Self {
+        Self {
+            id: id.to_string(),
+            position,
+            motion: (0.0, 0.0, 0.0),
+            rotation: (0.0, 0.0),
+            uuid: None,
+            custom_name: None,
+            on_ground: false,
+            no_gravity: false,
+            silent: false,
+            invulnerable: false,
+            glowing: false,
+            tags: Vec::new(),
+            other_tags: nbt::Map::new(),
+        }
+    }
+
+    /// Creates an entity from an entity NBT compound, as found in the entity
+    /// lists of chunks.
+    ///
+    /// Returns `None` if the value is not a compound, or lacks an id or position.
+    pub fn from_nbt_value(value: &nbt::Value) -> Option<Self> {
+        let mut tags = match value {
+            nbt::Value::Compound(tags) => tags.clone(),
+            _ => return None,
+        };
+
+        let id = match tags.remove("id") {
+            Some(nbt::Value::String(id)) => id,
+            _ => return None,
+        };
+        let position = match tags.remove("Pos").as_ref().and_then(doubles) {
+            Some((x, y, z)) => (x, y, z),
+            None => return None,
+        };
+        let motion = tags
+            .remove("Motion")
+            .as_ref()
+            .and_then(doubles)
+            .unwrap_or((0.0, 0.0, 0.0));
+        let rotation = match tags.remove("Rotation") {
+            Some(nbt::Value::List(list)) => match list.as_slice() {
+                [nbt::Value::Float(yaw), nbt::Value::Float(pitch)] => (*yaw, *pitch),
+                _ => (0.0, 0.0),
+            },
+            _ => (0.0, 0.0),
+        };
+        let uuid = match (
+            tags.remove("UUID"),
+            tags.remove("UUIDMost"),
+            tags.remove("UUIDLeast"),
+        ) {
+            (Some(nbt::Value::IntArray(ints)), _, _) if ints.len() == 4 => Some(
+                ints.iter()
+                    .fold(0u128, |uuid, int| (uuid << 32) | u128::from(*int as u32)),
+            ),
+            (_, Some(nbt::Value::Long(most)), Some(nbt::Value::Long(least))) => {
+                Some((u128::from(most as u64) << 64) | u128::from(least as u64))
+            }
+            _ => None,
+        };
+        let custom_name = match tags.remove("CustomName") {
+            Some(nbt::Value::String(name)) => Some(name),
+            _ => None,
+        };
+        let mut flag =
+            |name: &str| matches!(tags.remove(name), Some(nbt::Value::Byte(b)) if b != 0);
+        let on_ground = flag("OnGround");
+        let no_gravity = flag("NoGravity");
+        let silent = flag("Silent");
+        let invulnerable = flag("Invulnerable");
+        let glowing = flag("Glowing");
+        let scoreboard_tags = match tags.remove("Tags") {
+            Some(nbt::Value::List(list)) => list
+                .into_iter()
+                .filter_map(|tag| match tag {
+                    nbt::Value::String(tag) => Some(tag),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Some(Self {
+            id,
+            position,
+            motion,
+            rotation,
+            uuid,
+            custom_name,
+            on_ground,
+            no_gravity,
+            silent,
+            invulnerable,
+            glowing,
+            tags: scoreboard_tags,
+            other_tags: tags,
+        })
+    }
+
+    /// Generates the entity NBT compound, for saving in a world of the given version.
+    pub fn to_nbt_value(&self, data_version: McVersion) -> nbt::Value {
+        let mut tags = self.other_tags.clone();
+        if self.is_item_frame() && is_pre_flattening(data_version) {
+            map_facing(&mut tags, true, false, |facing| facing);
+        }
+
+        tags.insert("id".into(), nbt::Value::String(self.id.clone()));
+        let (x, y, z) = self.position;
+        tags.insert("Pos".into(), double_list(&[x, y, z]));
+        let (dx, dy, dz) = self.motion;
+        tags.insert("Motion".into(), double_list(&[dx, dy, dz]));
+        let (yaw, pitch) = self.rotation;
+        tags.insert(
+            "Rotation".into(),
+            nbt::Value::List(vec![nbt::Value::Float(yaw), nbt::Value::Float(pitch)]),
+        );
+
+        if let Some(uuid) = self.uuid {
+            // UUIDs are stored as four ints from 20w12a, and as two longs before that.
+            if data_version >= McVersion::from_str("20w12a").unwrap() {
+                let ints = (0..4)
+                    .rev()
+                    .map(|index| (uuid >> (index * 32)) as u32 as i32)
+                    .collect();
+                tags.insert("UUID".into(), nbt::Value::IntArray(ints));
+            } else {
+                tags.insert("UUIDMost".into(), nbt::Value::Long((uuid >> 64) as i64));
+                tags.insert("UUIDLeast".into(), nbt::Value::Long(uuid as i64));
+            }
+        }
+        if let Some(custom_name) = &self.custom_name {
+            tags.insert("CustomName".into(), nbt::Value::String(custom_name.clone()));
+        }
+
+        tags.insert("OnGround".into(), nbt::Value::Byte(self.on_ground as i8));
+        for (name, flag) in &[
+            ("NoGravity", self.no_gravity),
+            ("Silent", self.silent),
+            ("Invulnerable", self.invulnerable),
+            ("Glowing", self.glowing),
+        ] {
+            if *flag {
+                tags.insert((*name).into(), nbt::Value::Byte(1));
+            }
+        }
+        if !self.tags.is_empty() {
+            tags.insert(
+                "Tags".into(),
+                nbt::Value::List(
+                    self.tags
+                        .iter()
+                        .map(|tag| nbt::Value::String(tag.clone()))
+                        .collect(),
+                ),
+            );
+        }
+
+        nbt::Value::Compound(tags)
+    }
+
+    /// Gives the entity and its passengers new random UUIDs, for copies of
+    /// entities that must not be mistaken for the originals.
+    ///
+    /// Entities without a UUID are left without one, to be given one by
+    /// Minecraft when loaded.
+    pub fn renew_uuids(&mut self) {
+        if self.uuid.is_some() {
+            self.uuid = Some(random_uuid());
+        }
+        renew_passenger_uuids(&mut self.other_tags);
+    }
+
+    /// Coordinates of the block the entity is within.
+    pub fn block_position(&self) -> BlockCoord {
+        let (x, y, z) = self.position;
+        (x.floor() as i64, y.floor() as i64, z.floor() as i64).into()
+    }
+
+    /// Converts the facing of an item frame read from a pre-flattening save,
+    /// where item frames could only face the four horizontal directions, to
+    /// the six directions used from the flattening.
+    pub(crate) fn upgrade_pre_flattening_facing(&mut self) {
+        if self.is_item_frame() {
+            map_facing(&mut self.other_tags, false, true, |facing| facing);
+        }
+    }
+
+    /// Returns true for item frames, which may face any of the six directions,
+    /// rather than only the four horizontal ones.
+    fn is_item_frame(&self) -> bool {
+        matches!(
+            self.id.as_str(),
+            "minecraft:item_frame" | "minecraft:glow_item_frame" | "ItemFrame"
+        )
+    }
+
+    /// Moves the entity by a whole number of blocks.
+    ///
+    /// The block position of hanging entities (item frames and paintings) and
+    /// the passengers riding the entity are moved along with its position.
+    /// Other positions kept in the tags of the entity, such as the home, hive
+    /// or flower position of mobs, the fence knot of a leash, or the beam
+    /// target of an end crystal, are left unchanged.
+    pub fn translate(&mut self, offset: BlockCoord) {
+        let (dx, dy, dz) = (offset.0 as f64, offset.1 as f64, offset.2 as f64);
+        self.position.0 += dx;
+        self.position.1 += dy;
+        self.position.2 += dz;
+        self.map_tile_position(|(x, y, z)| (x + offset.0, y + offset.1, z + offset.2));
+        map_passengers(
+            &mut self.other_tags,
+            &|(x, y, z)| (x + dx, y + dy, z + dz),
+            &|yaw| yaw,
+        );
+    }
+
+    /// Rotates the position and facing of the entity, as part of rotating an
+    /// area of `(x_dim, z_dim)` blocks with its corner at the origin.
+    ///
+    /// Positions are moved as by [`translate`](Self::translate).
+    pub(crate) fn rotate(&mut self, rotation: Rotation, (x_dim, z_dim): (usize, usize)) {
+        let (x_dim, z_dim) = (x_dim as i64, z_dim as i64);
+        let (xf, zf) = (x_dim as f64, z_dim as f64);
+        let rotate_position = |(x, y, z): (f64, f64, f64)| match rotation {
+            Rotation::None => (x, y, z),
+            Rotation::Clockwise90 => (zf - z, y, x),
+            Rotation::Clockwise180 => (xf - x, y, zf - z),
+            Rotation::Counterclockwise90 => (z, y, xf - x),
+        };
+        let yaw_change = match rotation {
+            Rotation::None => 0.0,
+            Rotation::Clockwise90 => 90.0,
+            Rotation::Clockwise180 => 180.0,
+            Rotation::Counterclockwise90 => 270.0,
+        };
+        let rotate_yaw = |yaw: f32| (yaw + yaw_change) % 360.0;
+        self.position = rotate_position(self.position);
+        self.rotation.0 = rotate_yaw(self.rotation.0);
+        self.map_tile_position(|(x, y, z)| match rotation {
+            Rotation::None => (x, y, z),
+            Rotation::Clockwise90 => (z_dim - 1 - z, y, x),
+            Rotation::Clockwise180 => (x_dim - 1 - x, y, z_dim - 1 - z),
+            Rotation::Counterclockwise90 => (z, y, x_dim - 1 - x),
+        });
+        let six_directions = self.is_item_frame();
+        map_facing(
+            &mut self.other_tags,
+            six_directions,
+            six_directions,
+            |facing| facing.rotated(rotation),
+        );
+        map_passengers(&mut self.other_tags, &rotate_position, &rotate_yaw);
+    }
+
+    /// Mirrors the position and facing of the entity, as part of mirroring an
+    /// area of `(x_dim, z_dim)` blocks with its corner at the origin.
+    ///
+    /// Positions are moved as by [`translate`](Self::translate).
+    pub(crate) fn mirror(&mut self, axis: Axis2, (x_dim, z_dim): (usize, usize)) {
+        let (x_dim, z_dim) = (x_dim as i64, z_dim as i64);
+        let mirror_position = |(x, y, z): (f64, f64, f64)| match axis {
+            Axis2::X => (x_dim as f64 - x, y, z),
+            Axis2::Z => (x, y, z_dim as f64 - z),
+        };
+        let mirror_yaw = |yaw: f32| match axis {
+            Axis2::X => -yaw,
+            Axis2::Z => 180.0 - yaw,
+        };
+        self.position = mirror_position(self.position);
+        self.rotation.0 = mirror_yaw(self.rotation.0);
+        self.map_tile_position(|(x, y, z)| match axis {
+            Axis2::X => (x_dim - 1 - x, y, z),
+            Axis2::Z => (x, y, z_dim - 1 - z),
+        });
+        let six_directions = self.is_item_frame();
+        map_facing(
+            &mut self.other_tags,
+            six_directions,
+            six_directions,
+            |facing| facing.mirrored(axis),
+        );
+        map_passengers(&mut self.other_tags, &mirror_position, &mirror_yaw);
+    }
+
+    /// Applies `f` to the block position ("TileX", "TileY", "TileZ") of hanging entities.
+    fn map_tile_position<F>(&mut self, f: F)
+    where
+        F: Fn((i64, i64, i64)) -> (i64, i64, i64),
+    {
+        let tile = |name: &str| match self.other_tags.get(name) {
+            Some(nbt::Value::Int(value)) => Some(*value as i64),
+            _ => None,
+        };
+        if let (Some(x), Some(y), Some(z)) = (tile("TileX"), tile("TileY"), tile("TileZ")) {
+            let (x, y, z) = f((x, y, z));
+            self.other_tags
+                .insert("TileX".into(), nbt::Value::Int(x as i32));
+            self.other_tags
+                .insert("TileY".into(), nbt::Value::Int(y as i32));
+            self.other_tags
+                .insert("TileZ".into(), nbt::Value::Int(z as i32));
+        }
+    }
+}
+
+/// Applies `f` to the facing of a hanging entity, i.e. its "Facing" tag, or the
+/// "facing" tag of paintings from 1.21.
+///
+/// The facing is read as one of six directions if `from_six`, and one of the
+/// four horizontal directions otherwise. It is written likewise by `to_six`,
+/// and left as it was if the result cannot be written.
+fn map_facing<F>(tags: &mut nbt::Map<String, nbt::Value>, from_six: bool, to_six: bool, f: F)
+where
+    F: Fn(Surface6) -> Surface6,
+{
+    for key in &["Facing", "facing"] {
+        if let Some(nbt::Value::Byte(value)) = tags.get_mut(*key) {
+            let facing = facings(from_six).get(*value as usize).copied();
+            if let Some(facing) = facing.map(&f) {
+                if let Some(index) = facings(to_six)
+                    .iter()
+                    .position(|&candidate| candidate == facing)
+                {
+                    *value = index as i8;
+                }
+            }
+        }
+    }
+}
+
+/// Applies `position` and `yaw` to the "Pos" and yaw of the passengers in the
+/// "Passengers" list of `tags`, and to their own passengers in turn.
+fn map_passengers<P, Y>(tags: &mut nbt::Map<String, nbt::Value>, position: &P, yaw: &Y)
+where
+    P: Fn((f64, f64, f64)) -> (f64, f64, f64),
+    Y: Fn(f32) -> f32,
+{
+    if let Some(nbt::Value::List(passengers)) = tags.get_mut("Passengers") {
+        for passenger in passengers {
+            if let nbt::Value::Compound(passenger) = passenger {
+                if let Some((x, y, z)) = passenger.get("Pos").and_then(doubles) {
+                    let (x, y, z) = position((x, y, z));
+                    passenger.insert("Pos".into(), double_list(&[x, y, z]));
+                }
+                if let Some(nbt::Value::List(rotation)) = passenger.get_mut("Rotation") {
+                    if let Some(nbt::Value::Float(passenger_yaw)) = rotation.first_mut() {
+                        *passenger_yaw = yaw(*passenger_yaw);
+                    }
+                }
+                map_passengers(passenger, position, yaw);
+            }
+        }
+    }
+}
+
+/// Gives the passengers in the "Passengers" list of `tags`, and their own
+/// passengers in turn, new random UUIDs, in whichever form they are stored.
+fn renew_passenger_uuids(tags: &mut nbt::Map<String, nbt::Value>) {
+    if let Some(nbt::Value::List(passengers)) = tags.get_mut("Passengers") {
+        for passenger in passengers {
+            if let nbt::Value::Compound(passenger) = passenger {
+                let uuid = random_uuid();
+                if let Some(nbt::Value::IntArray(ints)) = passenger.get_mut("UUID") {
+                    *ints = (0..4)
+                        .rev()
+                        .map(|index| (uuid >> (index * 32)) as u32 as i32)
+                        .collect();
+                }
+                if let Some(nbt::Value::Long(most)) = passenger.get_mut("UUIDMost") {
+                    *most = (uuid >> 64) as i64;
+                }
+                if let Some(nbt::Value::Long(least)) = passenger.get_mut("UUIDLeast") {
+                    *least = uuid as i64;
+                }
+                renew_passenger_uuids(passenger);
+            }
+        }
+    }
+}
+
+/// Generates a random (version 4) UUID.
+fn random_uuid() -> u128 {
+    // Each RandomState hashes with its own random keys.
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let random_half = || {
+        let mut hasher = RandomState::new().build_hasher();
+        count.hash(&mut hasher);
+        SystemTime::now().hash(&mut hasher);
+        u128::from(hasher.finish())
+    };
+    let uuid = (random_half() << 64) | random_half();
+    // Version 4, variant 1 (RFC 4122)
+    uuid & !(0xf << 76) & !(0x3 << 62) | (0x4 << 76) | (0x2 << 62)
+}
+
+/// The facings of hanging entities, in order of their stored value.
+fn facings(six_directions: bool) -> &'static [Surface6] {
+    if six_directions {
+        &[
+            Surface6::Down,
+            Surface6::Up,
+            Surface6::North,
+            Surface6::South,
+            Surface6::West,
+            Surface6::East,
+        ]
+    } else {
+        &[
+            Surface6::South,
+            Surface6::West,
+            Surface6::North,
+            Surface6::East,
+        ]
+    }
+}
+
+/// Returns true if the given version stores chunks in the pre-flattening format.
+fn is_pre_flattening(data_version: McVersion) -> bool {
+    matches!(
+        ChunkFormat::for_version(data_version),
+        Ok(ChunkFormat::PreFlattening)
+    )
+}
+
+/// Returns true if chunks of the given version store their entities in separate
+/// entity region files ("entities/r.X.Z.mca"), as introduced with 20w45a.
+pub(crate) fn is_stored_separately(data_version: McVersion) -> bool {
+    data_version >= McVersion::from_str("20w45a").unwrap()
+}
+
+/// Reads the entities of an NBT list of entity compounds.
+///
+/// Entities that cannot be read are skipped, with a warning.
+pub(crate) fn entities_from_nbt_list(list: &[nbt::Value]) -> Vec<Entity> {
+    list.iter()
+        .filter_map(|value| {
+            let entity = Entity::from_nbt_value(value);
+            if entity.is_none() {
+                eprintln!(
+                    "[warning] Skipping entity without id or position: {:?}",
+                    value
+                );
+            }
+            entity
+        })
+        .collect()
+}
+
+/// Generates an NBT list of entity compounds, for the given version.
+pub(crate) fn entities_to_nbt_list(entities: &[Entity], data_version: McVersion) -> nbt::Value {
+    nbt::Value::List(
+        entities
+            .iter()
+            .map(|entity| entity.to_nbt_value(data_version))
+            .collect(),
+    )
+}
+
+fn doubles(value: &nbt::Value) -> Option<(f64, f64, f64)> {
+    match value {
+        nbt::Value::List(list) => match list.as_slice() {
+            [nbt::Value::Double(x), nbt::Value::Double(y), nbt::Value::Double(z)] => {
+                Some((*x, *y, *z))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn double_list(values: &[f64]) -> nbt::Value {
+    nbt::Value::List(
+        values
+            .iter()
+            .map(|value| nbt::Value::Double(*value))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(name: &str) -> McVersion {
+        McVersion::from_str(name).unwrap()
+    }
+
+    #[test]
+    fn nbt_round_trip_keeps_all_tags() {
+        let mut entity = Entity::new("minecraft:cow", (1.5, 64.0, -3.25));
+        entity.rotation = (90.0, 10.0);
+        entity.uuid = Some(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        entity.custom_name = Some("{\"text\":\"Daisy\"}".into());
+        entity.on_ground = true;
+        entity.tags = vec!["milked".into()];
+        entity
+            .other_tags
+            .insert("Health".into(), nbt::Value::Float(10.0));
+
+        for name in &["1.12.2", "1.16.5"] {
+            let nbt = entity.to_nbt_value(version(name));
+            assert_eq!(Entity::from_nbt_value(&nbt), Some(entity.clone()));
+        }
+
+        let nbt = entity.to_nbt_value(version("1.12.2"));
+        if let nbt::Value::Compound(tags) = nbt {
+            assert!(tags.contains_key("UUIDMost"));
+            assert!(!tags.contains_key("UUID"));
+        }
+    }
+
+    #[test]
+    fn hanging_entities_move_with_their_block() {
+        let mut item_frame = Entity::new("minecraft:item_frame", (2.5, 1.5, 0.03125));
+        for (name, value) in &[("TileX", 2), ("TileY", 1), ("TileZ", 0)] {
+            item_frame
+                .other_tags
+                .insert((*name).into(), nbt::Value::Int(*value));
+        }
+
+        item_frame.translate((10, 0, 20).into());
+        assert_eq!(item_frame.position, (12.5, 1.5, 20.03125));
+        assert_eq!(item_frame.other_tags["TileX"], nbt::Value::Int(12));
+        assert_eq!(item_frame.other_tags["TileZ"], nbt::Value::Int(20));
+        assert_eq!(item_frame.block_position(), (12, 1, 20).into());
+    }
+
+    #[test]
+    fn rotation_turns_position_and_yaw() {
+        // An entity facing south, in a 4 by 2 area
+        let mut entity = Entity::new("minecraft:pig", (0.5, 0.0, 1.5));
+        entity.rotate(Rotation::Clockwise90, (4, 2));
+        assert_eq!(entity.position, (0.5, 0.0, 0.5));
+        // Now facing west
+        assert_eq!(entity.rotation.0, 90.0);
+
+        entity.mirror(Axis2::X, (2, 4));
+        assert_eq!(entity.position, (1.5, 0.0, 0.5));
+        assert_eq!(entity.rotation.0, -90.0);
+    }
+
+    #[test]
+    fn passengers_move_with_their_vehicle() {
+        // A chicken riding a zombie riding a minecart
+        let mut chicken = Entity::new("minecraft:chicken", (0.5, 1.5, 1.5));
+        chicken.rotation = (10.0, 0.0);
+        let mut zombie = Entity::new("minecraft:zombie", (0.5, 0.5, 1.5));
+        zombie.other_tags.insert(
+            "Passengers".into(),
+            nbt::Value::List(vec![chicken.to_nbt_value(version("1.12.2"))]),
+        );
+        let mut minecart = Entity::new("minecraft:minecart", (0.5, 0.0, 1.5));
+        minecart.other_tags.insert(
+            "Passengers".into(),
+            nbt::Value::List(vec![zombie.to_nbt_value(version("1.12.2"))]),
+        );
+        let passenger = |entity: &Entity| match &entity.other_tags["Passengers"] {
+            nbt::Value::List(passengers) => Entity::from_nbt_value(&passengers[0]).unwrap(),
+            _ => panic!("Passengers not found"),
+        };
+
+        minecart.translate((10, 0, 20).into());
+        minecart.rotate(Rotation::Clockwise90, (30, 30));
+        let zombie = passenger(&minecart);
+        assert_eq!(zombie.position, (8.5, 0.5, 10.5));
+        let chicken = passenger(&zombie);
+        assert_eq!(chicken.position, (8.5, 1.5, 10.5));
+        assert_eq!(chicken.rotation.0, 100.0);
+
+        minecart.mirror(Axis2::Z, (30, 30));
+        let chicken = passenger(&passenger(&minecart));
+        assert_eq!(chicken.position, (8.5, 1.5, 19.5));
+        assert_eq!(chicken.rotation.0, 80.0);
+    }
+
+    #[test]
+    fn renewed_uuids_are_distinct() {
+        let original = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut zombie = Entity::new("minecraft:zombie", (0.5, 0.5, 0.5));
+        zombie.uuid = Some(original);
+        let mut horse = zombie.clone();
+        horse.id = "minecraft:horse".into();
+        horse.other_tags.insert(
+            "Passengers".into(),
+            nbt::Value::List(vec![zombie.to_nbt_value(version("1.16.5"))]),
+        );
+
+        let mut copy = horse.clone();
+        copy.renew_uuids();
+        let mut other_copy = horse.clone();
+        other_copy.renew_uuids();
+        let passenger_uuid = |entity: &Entity| match &entity.other_tags["Passengers"] {
+            nbt::Value::List(passengers) => Entity::from_nbt_value(&passengers[0])
+                .unwrap()
+                .uuid
+                .unwrap(),
+            _ => panic!("Passengers not found"),
+        };
+        let uuids = [
+            copy.uuid.unwrap(),
+            passenger_uuid(&copy),
+            other_copy.uuid.unwrap(),
+            passenger_uuid(&other_copy),
+        ];
+        for (index, uuid) in uuids.iter().enumerate() {
+            assert_ne!(*uuid, original);
+            assert!(!uuids[index + 1..].contains(uuid));
+            // Version 4
+            assert_eq!((uuid >> 76) & 0xf, 4);
+        }
+
+        // Entities without a UUID get one from Minecraft.
+        let mut cow = Entity::new("minecraft:cow", (0.5, 0.5, 0.5));
+        cow.renew_uuids();
+        assert_eq!(cow.uuid, None);
+    }
+
+    #[test]
+    fn item_frame_facing_is_rotated_and_mirrored() {
+        // An item frame on the north side of a block, facing south
+        let mut item_frame = Entity::new("minecraft:item_frame", (0.5, 0.5, 1.03125));
+        item_frame
+            .other_tags
+            .insert("Facing".into(), nbt::Value::Byte(3));
+
+        item_frame.rotate(Rotation::Clockwise90, (2, 2));
+        // Now facing west
+        assert_eq!(item_frame.other_tags["Facing"], nbt::Value::Byte(4));
+
+        item_frame.mirror(Axis2::X, (2, 2));
+        // Now facing east
+        assert_eq!(item_frame.other_tags["Facing"], nbt::Value::Byte(5));
+
+        // Facing up is not changed by rotation
+        item_frame
+            .other_tags
+            .insert("Facing".into(), nbt::Value::Byte(1));
+        item_frame.rotate(Rotation::Clockwise180, (2, 2));
+        assert_eq!(item_frame.other_tags["Facing"], nbt::Value::Byte(1));
+    }
+
+    #[test]
+    fn painting_facing_is_rotated() {
+        let mut painting = Entity::new("minecraft:painting", (0.5, 0.5, 0.5));
+        // Facing south
+        painting
+            .other_tags
+            .insert("facing".into(), nbt::Value::Byte(0));
+        painting.rotate(Rotation::Counterclockwise90, (1, 1));
+        // Now facing east
+        assert_eq!(painting.other_tags["facing"], nbt::Value::Byte(3));
+    }
+
+    #[test]
+    fn item_frame_facing_is_horizontal_before_the_flattening() {
+        let mut item_frame = Entity::new("minecraft:item_frame", (0.5, 0.5, 0.5));
+        // Facing west
+        item_frame
+            .other_tags
+            .insert("Facing".into(), nbt::Value::Byte(4));
+
+        let nbt = item_frame.to_nbt_value(version("1.12.2"));
+        let mut read = Entity::from_nbt_value(&nbt).unwrap();
+        assert_eq!(read.other_tags["Facing"], nbt::Value::Byte(1));
+        read.upgrade_pre_flattening_facing();
+        assert_eq!(read, item_frame);
+
+        let nbt = item_frame.to_nbt_value(version("1.13.2"));
+        assert_eq!(Entity::from_nbt_value(&nbt), Some(item_frame));
+    }
+}
@@ -0,0 +1,276 @@
+//! Differences between world excerpts, for reviewing and applying changes.
+//!
+//! An [`ExcerptDiff`] lists every block that differs between two excerpts of the
+//! same size, with its value before and after the change. Block entity data
+//! (chest contents, sign text, etc.) is part of the block value, so changes to
+//! block entities are listed as changes to the blocks holding them.
+
+use std::collections::HashMap;
+
+use serde_derive::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::block::Block;
+use crate::coordinates::BlockCoord;
+use crate::save_format::FormatError;
+use crate::world_excerpt::WorldExcerpt;
+
+/// A single changed block.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BlockChange {
+    /// Coordinates of the block, relative to the excerpt.
+    pub at: BlockCoord,
+    /// The block before the change.
+    pub before: Block,
+    /// The block after the change.
+    pub after: Block,
+}
+
+/// A block in the patch target that does not match the "before" state of the diff.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PatchConflict {
+    /// Coordinates of the block, relative to the patched excerpt or position.
+    pub at: BlockCoord,
+    /// The block the diff expected to find.
+    pub expected: Block,
+    /// The block actually found, or `None` if outside of the target.
+    pub found: Option<Block>,
+}
+
+/// Reason a diff could not be applied.
+#[derive(Error, Debug)]
+pub enum PatchError {
+    #[error("{} block(s) in the target no longer match the diff", .0.len())]
+    Conflicts(Vec<PatchConflict>),
+    #[error(transparent)]
+    Format(#[from] FormatError),
+}
+
+/// The changes made between two world excerpts.
+///
+/// Created with [`WorldExcerpt::diff()`](crate::world_excerpt::WorldExcerpt::diff).
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ExcerptDiff {
+    changes: Vec<BlockChange>,
+}
+
+impl ExcerptDiff {
+    /// Lists the changes between `before` and `after`.
+    ///
+    /// # Panics
+    /// Panics if the excerpts are not of the same size.
+    pub(crate) fn between(before: &WorldExcerpt, after: &WorldExcerpt) -> Self {
+        if before.dim() != after.dim() {
+            panic!(
+                "Cannot diff excerpts of different size: {:?} and {:?}",
+                before.dim(),
+                after.dim()
+            );
+        }
+
+        let (x_dim, y_dim, z_dim) = before.dim();
+        let mut changes = Vec::new();
+        for x in 0..x_dim as i64 {
+            for y in 0..y_dim as i64 {
+                for z in 0..z_dim as i64 {
+                    let at = (x, y, z).into();
+                    if let (Some(old), Some(new)) = (before.block_at(at), after.block_at(at)) {
+                        if old != new {
+                            changes.push(BlockChange {
+                                at,
+                                before: old.clone(),
+                                after: new.clone(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Self { changes }
+    }
+
+    /// The changed blocks, ordered by x, then y, then z coordinate.
+    pub fn changes(&self) -> &[BlockChange] {
+        &self.changes
+    }
+
+    /// Returns true if there are no changes.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Number of changed blocks.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    /// Returns the diff that undoes this diff.
+    pub fn reversed(&self) -> Self {
+        Self {
+            changes: self
+                .changes
+                .iter()
+                .map(|change| BlockChange {
+                    at: change.at,
+                    before: change.after.clone(),
+                    after: change.before.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Applies the changes to `target`.
+    ///
+    /// If any block in `target` does not match the "before" state of the diff,
+    /// nothing is changed, and the mismatching blocks are returned as conflicts.
+    pub fn patch(&self, target: &mut WorldExcerpt) -> Result<(), PatchError> {
+        let conflicts: Vec<PatchConflict> = self
+            .changes
+            .iter()
+            .filter_map(|change| Self::conflict(change, target.block_at(change.at)))
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(PatchError::Conflicts(conflicts));
+        }
+
+        for change in &self.changes {
+            target.set_block_at(change.at, change.after.clone());
+        }
+        Ok(())
+    }
+
+    /// Applies the changes to the world saved at `world_directory`, with the
+    /// excerpt corner with the lowest coordinates at world block coordinates `p`.
+    ///
+    /// Only the changed blocks are written, and entities and scheduled ticks
+    /// in the save are kept. If any block in the save does not
+    /// match the "before" state of the diff, the save is left untouched, and the
+    /// mismatching blocks are returned as conflicts. If the save is of a
+    /// Minecraft version that cannot be written, a format error is returned.
+    pub fn patch_save(
+        &self,
+        p: BlockCoord,
+        world_directory: &std::path::Path,
+    ) -> Result<(), PatchError> {
+        let (min, max) = match self.bounds() {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+
+        let current = WorldExcerpt::from_save(p + min, p + max, world_directory);
+        let conflicts: Vec<PatchConflict> = self
+            .changes
+            .iter()
+            .filter_map(|change| Self::conflict(change, current.block_at(change.at - min)))
+            .collect();
+        if !conflicts.is_empty() {
+            return Err(PatchError::Conflicts(conflicts));
+        }
+
+        // Blocks left as Block::None are not written to the save. Saving
+        // replaces the entities and scheduled ticks within the patched area,
+        // so those found there are written back unchanged.
+        let size = max - min;
+        let mut patch = WorldExcerpt::new(
+            size.0 as usize + 1,
+            size.1 as usize + 1,
+            size.2 as usize + 1,
+        );
+        for change in &self.changes {
+            patch.set_block_at(change.at - min, change.after.clone());
+        }
+        *patch.entities_mut() = current.entities().to_vec();
+        *patch.scheduled_ticks_mut() = current.scheduled_ticks().to_vec();
+        patch.to_save(p + min, world_directory)?;
+        Ok(())
+    }
+
+    /// Lowest and highest coordinates of any change, or `None` if there are no changes.
+    fn bounds(&self) -> Option<(BlockCoord, BlockCoord)> {
+        let first = self.changes.first()?.at;
+        Some(
+            self.changes
+                .iter()
+                .fold((first, first), |(min, max), change| {
+                    let at = change.at;
+                    (
+                        (min.0.min(at.0), min.1.min(at.1), min.2.min(at.2)).into(),
+                        (max.0.max(at.0), max.1.max(at.1), max.2.max(at.2)).into(),
+                    )
+                }),
+        )
+    }
+
+    fn conflict(change: &BlockChange, found: Option<&Block>) -> Option<PatchConflict> {
+        if found == Some(&change.before) {
+            None
+        } else {
+            Some(PatchConflict {
+                at: change.at,
+                expected: change.before.clone(),
+                found: found.cloned(),
+            })
+        }
+    }
+}
+
+impl PatchError {
+    /// The conflicting blocks, by coordinates.
+    pub fn conflicts(&self) -> HashMap<BlockCoord, &PatchConflict> {
+        match self {
+            PatchError::Conflicts(conflicts) => conflicts
+                .iter()
+                .map(|conflict| (conflict.at, conflict))
+                .collect(),
+            PatchError::Format(_) => HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excerpts() -> (WorldExcerpt, WorldExcerpt) {
+        let mut before = WorldExcerpt::new(2, 2, 2);
+        before.set_block_at((0, 0, 0).into(), Block::Stone);
+        before.set_block_at((1, 1, 1).into(), Block::Dirt);
+        let mut after = before.clone();
+        after.set_block_at((0, 0, 0).into(), Block::Cobblestone);
+        after.set_block_at((1, 0, 1).into(), Block::Sand);
+        (before, after)
+    }
+
+    #[test]
+    fn diff_survives_serialisation_and_patches_excerpt() {
+        let (before, after) = excerpts();
+        let diff = before.diff(&after);
+        assert_eq!(diff.len(), 2);
+
+        let json = serde_json::to_string(&diff).unwrap();
+        let diff: ExcerptDiff = serde_json::from_str(&json).unwrap();
+
+        let mut target = before.clone();
+        diff.patch(&mut target).unwrap();
+        assert!(target.diff(&after).is_empty());
+
+        diff.reversed().patch(&mut target).unwrap();
+        assert!(target.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn conflicting_patch_changes_nothing() {
+        let (before, after) = excerpts();
+        let diff = before.diff(&after);
+
+        let mut target = before.clone();
+        target.set_block_at((1, 0, 1).into(), Block::Gravel);
+        let untouched = target.clone();
+
+        let error = diff.patch(&mut target).unwrap_err();
+        let conflicts = error.conflicts();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[&BlockCoord(1, 0, 1)].found, Some(Block::Gravel));
+        assert!(target.diff(&untouched).is_empty());
+    }
+}
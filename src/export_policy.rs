@@ -0,0 +1,798 @@
+//! Downgrading of world excerpts, for export to saves of older Minecraft versions.
+//!
+//! An [`ExportPolicy`] knows which blocks, materials and block properties exist
+//! in the Minecraft version it targets. Blocks that do not exist there are
+//! replaced according to a fallback table, and every replacement is listed in a
+//! [`SubstitutionReport`], so that nothing is silently lost on export.
+//!
+//! ```
+//! use std::str::FromStr;
+//! use mcprogedit::block::Block;
+//! use mcprogedit::export_policy::ExportPolicy;
+//! use mcprogedit::material::WoodMaterial;
+//! use mcprogedit::mc_version::McVersion;
+//! use mcprogedit::world_excerpt::WorldExcerpt;
+//!
+//! let mut excerpt = WorldExcerpt::new(1, 1, 1);
+//! excerpt.set_block_at((0, 0, 0).into(), Block::Planks { material: WoodMaterial::Crimson });
+//!
+//! let policy = ExportPolicy::new(McVersion::from_str("1.12.2").unwrap()).with_fallback(
+//!     Block::Planks { material: WoodMaterial::Crimson },
+//!     Block::Planks { material: WoodMaterial::DarkOak },
+//! );
+//! let report = policy.apply(&mut excerpt);
+//!
+//! assert_eq!(report.len(), 1);
+//! assert_eq!(
+//!     excerpt.block_at((0, 0, 0).into()),
+//!     Some(&Block::Planks { material: WoodMaterial::DarkOak }),
+//! );
+//! ```
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::block::*;
+use crate::block_state;
+use crate::colour::Colour;
+use crate::coordinates::BlockCoord;
+use crate::material::*;
+use crate::mc_version::McVersion;
+use crate::positioning::Surface4;
+use crate::save_format::{ChunkFormat, FormatError};
+use crate::world_excerpt::WorldExcerpt;
+
+/// Maximum number of fallbacks followed for a single block, in case the
+/// fallback table contains cycles.
+const MAX_FALLBACK_STEPS: usize = 8;
+
+/// The releases that introduced the blocks, materials and properties known to mcprogedit.
+///
+/// Snapshots are not considered; a block introduced in the snapshots leading
+/// up to a release is treated as unavailable until that release.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum Release {
+    /// 1.12 or earlier.
+    V1_12,
+    /// 1.13, "Update Aquatic".
+    V1_13,
+    /// 1.14, "Village & Pillage".
+    V1_14,
+    /// 1.15, "Buzzy Bees".
+    V1_15,
+    /// 1.16, "Nether Update".
+    V1_16,
+}
+
+impl Release {
+    const ALL: [Release; 5] = [
+        Release::V1_12,
+        Release::V1_13,
+        Release::V1_14,
+        Release::V1_15,
+        Release::V1_16,
+    ];
+
+    fn version(self) -> McVersion {
+        let name = match self {
+            Release::V1_12 => "1.12",
+            Release::V1_13 => "1.13",
+            Release::V1_14 => "1.14",
+            Release::V1_15 => "1.15",
+            Release::V1_16 => "1.16",
+        };
+        McVersion::from_str(name).unwrap()
+    }
+}
+
+/// Rules for exporting blocks to saves of a specific Minecraft version.
+///
+/// Blocks are checked against the target version, both for the block itself,
+/// its material, and its properties (e.g. waterlogging, which was introduced
+/// in 1.13). A block that is not available is replaced by, in order:
+///
+/// 1. Its entry in the block fallback table, if any.
+/// 2. The same block without the properties unknown to the target version.
+/// 3. The same block with its material replaced from the material fallback table.
+/// 4. Air.
+///
+/// Replacements are checked in the same way, so fallbacks can be chained.
+/// Blocks the writer does not yet know how to represent in the pre-flattening
+/// chunk format (command blocks, spawners and structure blocks) are treated as
+/// unavailable for pre-flattening targets.
+///
+/// [`Block::None`] is never replaced. [`Block::Unknown`] blocks are only
+/// available where the save can hold them, i.e. block states read from a
+/// post-flattening save, exported to a post-flattening version. Other unknown
+/// blocks would be written as air, so they are substituted and reported like
+/// any other unavailable block.
+#[derive(Clone, Debug)]
+pub struct ExportPolicy {
+    target: McVersion,
+    /// Newest release available in the target version, if any.
+    release: Option<Release>,
+    pre_flattening: bool,
+    block_fallbacks: Vec<(Block, Block)>,
+    material_fallbacks: Vec<(Material, Material)>,
+    on_unavailable: OnUnavailable,
+}
+
+/// What to do with blocks that are not available in the target version of an
+/// [`ExportPolicy`], when saving.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OnUnavailable {
+    /// Replace them according to the fallback tables.
+    #[default]
+    Substitute,
+    /// Refuse to save, with [`FormatError::UnavailableBlock`].
+    Fail,
+}
+
+impl ExportPolicy {
+    /// Creates a policy for exporting to `target`, with the default fallbacks.
+    ///
+    /// The default fallbacks correspond to what the pre-flattening writer
+    /// falls back to on its own: any wood material to oak, bamboo saplings
+    /// to oak saplings, and the 1.14 stone slab to the old (smooth) stone slab.
+    /// Carved pumpkins fall back to pumpkins.
+    pub fn new(target: McVersion) -> Self {
+        let mut policy = Self::without_fallbacks(target);
+        for material in &[
+            Material::Acacia,
+            Material::Birch,
+            Material::Crimson,
+            Material::DarkOak,
+            Material::Jungle,
+            Material::Spruce,
+            Material::Warped,
+            Material::Bamboo,
+        ] {
+            policy = policy.with_material_fallback(*material, Material::Oak);
+        }
+        for position in &[SlabVariant::Bottom, SlabVariant::Top, SlabVariant::Double] {
+            policy = policy.with_fallback(
+                Block::Slab(Slab {
+                    material: SlabMaterial::Stone,
+                    position: *position,
+                    waterlogged: false,
+                }),
+                Block::Slab(Slab {
+                    material: SlabMaterial::SmoothStone,
+                    position: *position,
+                    waterlogged: false,
+                }),
+            );
+        }
+        for facing in &[
+            Surface4::North,
+            Surface4::South,
+            Surface4::East,
+            Surface4::West,
+        ] {
+            policy = policy.with_fallback(
+                Block::CarvedPumpkin { facing: *facing },
+                Block::Pumpkin { facing: *facing },
+            );
+        }
+        policy
+    }
+
+    /// Creates a policy for exporting to `target`, with empty fallback tables.
+    ///
+    /// Unavailable blocks are then only stripped of unknown properties,
+    /// or replaced by air.
+    pub fn without_fallbacks(target: McVersion) -> Self {
+        let release = Release::ALL
+            .iter()
+            .rev()
+            .find(|release| release.version() <= target)
+            .copied();
+        Self {
+            target,
+            release,
+            pre_flattening: matches!(
+                ChunkFormat::for_version(target),
+                Ok(ChunkFormat::PreFlattening)
+            ),
+            block_fallbacks: Vec::new(),
+            material_fallbacks: Vec::new(),
+            on_unavailable: OnUnavailable::default(),
+        }
+    }
+
+    /// Adds a fallback from the exact block `from` to `to`.
+    ///
+    /// Replaces any earlier fallback for `from`. Block fallbacks take
+    /// precedence over material fallbacks.
+    pub fn with_fallback(mut self, from: Block, to: Block) -> Self {
+        self.block_fallbacks.retain(|(block, _)| *block != from);
+        self.block_fallbacks.push((from, to));
+        self
+    }
+
+    /// Adds a fallback from the material `from` to `to`, for all blocks that
+    /// have a material field that can hold `to`.
+    ///
+    /// E.g. a fallback from [`Material::Crimson`] to [`Material::DarkOak`]
+    /// turns crimson planks, stairs, doors and so on into their dark oak
+    /// counterparts. Replaces any earlier fallback for `from`.
+    pub fn with_material_fallback(mut self, from: Material, to: Material) -> Self {
+        self.material_fallbacks
+            .retain(|(material, _)| *material != from);
+        self.material_fallbacks.push((from, to));
+        self
+    }
+
+    /// Sets what to do with unavailable blocks when saving with this policy.
+    pub fn with_on_unavailable(mut self, on_unavailable: OnUnavailable) -> Self {
+        self.on_unavailable = on_unavailable;
+        self
+    }
+
+    /// The Minecraft version exported to.
+    pub fn target(&self) -> McVersion {
+        self.target
+    }
+
+    /// What to do with unavailable blocks when saving with this policy.
+    pub fn on_unavailable(&self) -> OnUnavailable {
+        self.on_unavailable
+    }
+
+    /// Returns true if `block`, with its material and properties, exists in the
+    /// target version.
+    pub fn is_available(&self, block: &Block) -> bool {
+        match block {
+            Block::None => true,
+            Block::Unknown(id) => !self.pre_flattening && id.is_some_and(block_state::is_interned),
+            Block::CommandBlock(_) | Block::Spawner | Block::StructureBlock
+                if self.pre_flattening =>
+            {
+                false
+            }
+            _ => Some(introduced_in(block)) <= self.release,
+        }
+    }
+
+    /// Returns the block that `block` is exported as.
+    pub fn substitute(&self, block: &Block) -> Block {
+        let mut block = block.clone();
+        for _ in 0..MAX_FALLBACK_STEPS {
+            if self.is_available(&block) {
+                return block;
+            }
+            block = if let Some((_, to)) =
+                self.block_fallbacks.iter().find(|(from, _)| *from == block)
+            {
+                to.clone()
+            } else if let Some(stripped) = self.without_new_properties(&block) {
+                stripped
+            } else if let Some(replaced) = self.with_fallback_material(&block) {
+                replaced
+            } else {
+                return Block::Air;
+            };
+        }
+        Block::Air
+    }
+
+    /// Lists the substitutions [`apply`](Self::apply) would make, without
+    /// changing the excerpt.
+    pub fn report(&self, excerpt: &WorldExcerpt) -> SubstitutionReport {
+        let mut substitutions = Vec::new();
+        let (x_dim, y_dim, z_dim) = excerpt.dim();
+        for x in 0..x_dim as i64 {
+            for y in 0..y_dim as i64 {
+                for z in 0..z_dim as i64 {
+                    let at = (x, y, z).into();
+                    if let Some(block) = excerpt.block_at(at) {
+                        if !self.is_available(block) {
+                            substitutions.push(Substitution {
+                                at,
+                                original: block.clone(),
+                                replacement: self.substitute(block),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        SubstitutionReport {
+            target: self.target,
+            substitutions,
+        }
+    }
+
+    /// Replaces all blocks of `excerpt` that are not available in the target
+    /// version, and returns the list of substitutions made.
+    ///
+    /// The replacements are recorded in the excerpt's journal, if enabled.
+    pub fn apply(&self, excerpt: &mut WorldExcerpt) -> SubstitutionReport {
+        let report = self.report(excerpt);
+        for substitution in report.substitutions() {
+            excerpt.set_block_at(substitution.at, substitution.replacement.clone());
+        }
+        report
+    }
+
+    /// Applies the policy to `excerpt` as configured by
+    /// [`with_on_unavailable`](Self::with_on_unavailable).
+    ///
+    /// # Errors
+    /// Returns [`FormatError::UnavailableBlock`] for the first unavailable
+    /// block, without changing the excerpt, if the policy is set to
+    /// [`OnUnavailable::Fail`].
+    pub fn try_apply(&self, excerpt: &mut WorldExcerpt) -> Result<SubstitutionReport, FormatError> {
+        if self.on_unavailable == OnUnavailable::Fail {
+            let report = self.report(excerpt);
+            if let Some(substitution) = report.substitutions().first() {
+                return Err(FormatError::UnavailableBlock {
+                    at: substitution.at,
+                    block: Box::new(substitution.original.clone()),
+                    target: self.target,
+                });
+            }
+            return Ok(report);
+        }
+        Ok(self.apply(excerpt))
+    }
+
+    /// Returns the block with the properties unknown to the target version
+    /// reset, or `None` if there was nothing to reset.
+    fn without_new_properties(&self, block: &Block) -> Option<Block> {
+        let mut block = block.clone();
+        let changed = if self.release < Some(Release::V1_13) {
+            match &mut block {
+                Block::Leaves {
+                    distance_to_trunk, ..
+                } => distance_to_trunk.take().is_some(),
+                Block::Log(log) => std::mem::replace(&mut log.stripped, false),
+                Block::ShulkerBox(shulker_box) if shulker_box.colour.is_none() => {
+                    // The undyed shulker box looks like the purple one
+                    shulker_box.colour = Some(Colour::Purple);
+                    true
+                }
+                _ => match waterlogged_mut(&mut block) {
+                    Some(waterlogged) => std::mem::replace(waterlogged, false),
+                    None => false,
+                },
+            }
+        } else {
+            false
+        };
+        if changed {
+            Some(block)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the block with its material replaced according to the material
+    /// fallback table, or `None` if there is no applicable fallback.
+    fn with_fallback_material(&self, block: &Block) -> Option<Block> {
+        let from = block.material()?;
+        let (_, to) = self
+            .material_fallbacks
+            .iter()
+            .find(|(material, _)| *material == from)?;
+        with_material(block, *to)
+    }
+}
+
+/// A single replaced block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Substitution {
+    /// Coordinates of the block, relative to the excerpt.
+    pub at: BlockCoord,
+    /// The block that was not available in the target version.
+    pub original: Block,
+    /// The block it was replaced with.
+    pub replacement: Block,
+}
+
+/// All substitutions made when downgrading an excerpt to a target version.
+#[derive(Clone, Debug)]
+pub struct SubstitutionReport {
+    target: McVersion,
+    substitutions: Vec<Substitution>,
+}
+
+impl SubstitutionReport {
+    /// The Minecraft version the excerpt was downgraded to.
+    pub fn target(&self) -> McVersion {
+        self.target
+    }
+
+    /// The substituted blocks, ordered by x, then y, then z coordinate.
+    pub fn substitutions(&self) -> &[Substitution] {
+        &self.substitutions
+    }
+
+    /// Returns true if no blocks were substituted.
+    pub fn is_empty(&self) -> bool {
+        self.substitutions.is_empty()
+    }
+
+    /// Number of substituted blocks.
+    pub fn len(&self) -> usize {
+        self.substitutions.len()
+    }
+
+    /// Number of substitutions of each kind, as (original, replacement, count),
+    /// in order of first occurrence.
+    pub fn summary(&self) -> Vec<(&Block, &Block, usize)> {
+        let mut summary: Vec<(&Block, &Block, usize)> = Vec::new();
+        for substitution in &self.substitutions {
+            match summary.iter_mut().find(|(original, replacement, _)| {
+                **original == substitution.original && **replacement == substitution.replacement
+            }) {
+                Some((_, _, count)) => *count += 1,
+                None => summary.push((&substitution.original, &substitution.replacement, 1)),
+            }
+        }
+        summary
+    }
+}
+
+impl fmt::Display for SubstitutionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{} block(s) substituted for {}", self.len(), self.target)?;
+        for (original, replacement, count) in self.summary() {
+            writeln!(f, "{:>8} x {:?} -> {:?}", count, original, replacement)?;
+        }
+        Ok(())
+    }
+}
+
+/// The release that introduced `block`, with its material and properties.
+fn introduced_in(block: &Block) -> Release {
+    use Release::*;
+
+    let properties = if block.is_waterlogged() {
+        V1_13
+    } else {
+        match block {
+            Block::Leaves {
+                distance_to_trunk: Some(_),
+                ..
+            } => V1_13,
+            Block::Log(Log { stripped: true, .. }) => V1_13,
+            Block::ShulkerBox(shulker_box) if shulker_box.colour.is_none() => V1_13,
+            _ => V1_12,
+        }
+    };
+    let material = block
+        .material()
+        .map_or(V1_12, |material| material_introduced_in(block, material));
+
+    let variant = match block {
+        Block::BlueIce
+        | Block::BubbleColumn { .. }
+        | Block::CarvedPumpkin { .. }
+        | Block::CaveAir
+        | Block::Conduit { .. }
+        | Block::Coral { .. }
+        | Block::CoralBlock { .. }
+        | Block::CoralFan { .. }
+        | Block::DriedKelpBlock
+        | Block::Kelp { .. }
+        | Block::SeaPickle { .. }
+        | Block::Seagrass { .. }
+        | Block::SmoothQuartz
+        | Block::TurtleEgg { .. } => V1_13,
+
+        Block::Bamboo { .. }
+        | Block::Barrel { .. }
+        | Block::Bell { .. }
+        | Block::BlastFurnace(_)
+        | Block::Campfire { .. }
+        | Block::CartographyTable
+        | Block::Composter { .. }
+        | Block::FletchingTable
+        | Block::GrindStone(_)
+        | Block::JigsawBlock { .. }
+        | Block::Lantern { .. }
+        | Block::Lectern { .. }
+        | Block::Loom { .. }
+        | Block::Scaffolding { .. }
+        | Block::SmithingTable
+        | Block::Smoker(_)
+        | Block::SmoothStone
+        | Block::StoneCutter { .. }
+        | Block::SweetBerryBush { .. }
+        | Block::Flower(Flower::Cornflower | Flower::LilyOfTheValley | Flower::WitherRose) => V1_14,
+        Block::FlowerPot(FlowerPot { plant: Some(plant) }) => match plant {
+            PottedPlant::Bamboo
+            | PottedPlant::Cornflower
+            | PottedPlant::LilyOfTheValley
+            | PottedPlant::WitherRose => V1_14,
+            PottedPlant::CrimsonFungus
+            | PottedPlant::CrimsonRoots
+            | PottedPlant::WarpedFungus
+            | PottedPlant::Warpedroots => V1_16,
+            _ => V1_12,
+        },
+
+        Block::Beehive { .. }
+        | Block::BeeNest { .. }
+        | Block::HoneyBlock
+        | Block::HoneycombBlock => V1_15,
+
+        Block::AncientDebris
+        | Block::Basalt { .. }
+        | Block::Blackstone
+        | Block::BlockOfNetherite
+        | Block::ChiseledNetherBricks
+        | Block::ChiseledPolishedBlackstone
+        | Block::CrackedNetherBricks
+        | Block::CrackedPolishedBlackstoneBricks
+        | Block::CrimsonFungus
+        | Block::CrimsonNylium
+        | Block::CrimsonRoots
+        | Block::CryingObsidian
+        | Block::GildedBlackstone
+        | Block::LodeStone
+        | Block::NetherGoldOre
+        | Block::NetherSprouts
+        | Block::PolishedBasalt { .. }
+        | Block::PolishedBlackstone
+        | Block::PolishedBlackstoneBricks
+        | Block::QuartzBricks
+        | Block::RespawnAnchor { .. }
+        | Block::Shroomlight
+        | Block::SoulCampfire { .. }
+        | Block::SoulFire { .. }
+        | Block::SoulLantern { .. }
+        | Block::SoulSoil
+        | Block::SoulTorch { .. }
+        | Block::Target
+        | Block::TwistingVines { .. }
+        | Block::TwistingVinesPlant
+        | Block::WarpedFungus
+        | Block::WarpedNyliym
+        | Block::WarpedRoots
+        | Block::WarpedWartBlock
+        | Block::WeepingVines { .. }
+        | Block::WeepingVinesPlant => V1_16,
+
+        _ => V1_12,
+    };
+
+    variant.max(material).max(properties)
+}
+
+/// The release that introduced `material` for blocks of the same kind as `block`.
+fn material_introduced_in(block: &Block, material: Material) -> Release {
+    use Release::*;
+
+    match material {
+        Material::Crimson
+        | Material::Warped
+        | Material::Blackstone
+        | Material::PolishedBlackstone
+        | Material::PolishedBlackstoneBrick => return V1_16,
+        Material::Bamboo => return V1_14,
+        _ => (),
+    }
+
+    let is_wood = WoodMaterial::try_from(material).is_ok();
+    match block {
+        // Only oak variants existed before the flattening
+        Block::Button(..) | Block::PressurePlate { .. } | Block::Trapdoor(_) if is_wood => {
+            if material == Material::Oak {
+                V1_12
+            } else {
+                V1_13
+            }
+        }
+        Block::Sign(_) if material != Material::Oak => V1_14,
+        Block::Slab(_) | Block::Stairs(_) => match material {
+            Material::Prismarine | Material::PrismarineBrick | Material::DarkPrismarine => V1_13,
+            Material::Andesite
+            | Material::Diorite
+            | Material::EndStoneBrick
+            | Material::Granite
+            | Material::MossyCobblestone
+            | Material::MossyStoneBrick
+            | Material::PolishedAndesite
+            | Material::PolishedDiorite
+            | Material::PolishedGranite
+            | Material::RedNetherBrick
+            | Material::SmoothQuartz
+            | Material::SmoothRedSandstone
+            | Material::SmoothSandstone
+            | Material::CutRedSandstone
+            | Material::CutSandstone => V1_14,
+            // The plain stone slab and stairs, not the old smooth stone slab
+            Material::Stone => V1_14,
+            _ => V1_12,
+        },
+        Block::Wall { .. } => match material {
+            Material::Cobblestone | Material::MossyCobblestone => V1_12,
+            _ => V1_14,
+        },
+        _ => V1_12,
+    }
+}
+
+/// Returns `block` made of `material` instead, if it can be made of `material`.
+fn with_material(block: &Block, material: Material) -> Option<Block> {
+    let mut block = block.clone();
+    match &mut block {
+        Block::Button(old, _) => *old = ButtonMaterial::try_from(material).ok()?,
+        Block::Door(door) => door.material = DoorMaterial::try_from(material).ok()?,
+        Block::Fence { material: old, .. } => *old = FenceMaterial::try_from(material).ok()?,
+        Block::FenceGate { material: old, .. } => *old = WoodMaterial::try_from(material).ok()?,
+        Block::Log(log) => log.material = WoodMaterial::try_from(material).ok()?,
+        Block::Planks { material: old } => *old = WoodMaterial::try_from(material).ok()?,
+        Block::PressurePlate { material: old } => {
+            *old = PressurePlateMaterial::try_from(material).ok()?
+        }
+        Block::Sapling { material: old, .. } => *old = SaplingMaterial::try_from(material).ok()?,
+        Block::Sign(sign) => sign.material = WoodMaterial::try_from(material).ok()?,
+        Block::Slab(slab) => slab.material = SlabMaterial::try_from(material).ok()?,
+        Block::Stairs(stair) => stair.material = StairMaterial::try_from(material).ok()?,
+        Block::Trapdoor(trapdoor) => trapdoor.material = DoorMaterial::try_from(material).ok()?,
+        Block::Wall { material: old, .. } => *old = WallMaterial::try_from(material).ok()?,
+        _ => return None,
+    }
+    Some(block)
+}
+
+/// The waterlogged property of `block`, for blocks that have one.
+fn waterlogged_mut(block: &mut Block) -> Option<&mut bool> {
+    match block {
+        Block::Campfire { waterlogged, .. }
+        | Block::Conduit { waterlogged }
+        | Block::Coral { waterlogged, .. }
+        | Block::CoralFan { waterlogged, .. }
+        | Block::EnderChest { waterlogged, .. }
+        | Block::Fence { waterlogged, .. }
+        | Block::GlassPane { waterlogged, .. }
+        | Block::IronBars { waterlogged }
+        | Block::Ladder { waterlogged, .. }
+        | Block::Scaffolding { waterlogged }
+        | Block::SeaPickle { waterlogged, .. }
+        | Block::SoulCampfire { waterlogged, .. }
+        | Block::Wall { waterlogged, .. } => Some(waterlogged),
+        Block::Chest(chest) | Block::TrappedChest(chest) => Some(&mut chest.waterlogged),
+        Block::Head(head) => Some(&mut head.waterlogged),
+        Block::Hopper(hopper) => Some(&mut hopper.waterlogged),
+        Block::Sign(sign) => Some(&mut sign.waterlogged),
+        Block::Slab(slab) => Some(&mut slab.waterlogged),
+        Block::Stairs(stair) => Some(&mut stair.waterlogged),
+        Block::Trapdoor(trapdoor) => Some(&mut trapdoor.waterlogged),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(name: &str) -> McVersion {
+        McVersion::from_str(name).unwrap()
+    }
+
+    #[test]
+    fn blocks_are_available_from_their_release() {
+        let policy_1_12 = ExportPolicy::new(version("1.12.2"));
+        let policy_1_16 = ExportPolicy::new(version("1.16.5"));
+        let crimson_planks = Block::Planks {
+            material: WoodMaterial::Crimson,
+        };
+        assert!(!policy_1_12.is_available(&crimson_planks));
+        assert!(policy_1_16.is_available(&crimson_planks));
+        assert!(!policy_1_12.is_available(&Block::BlueIce));
+        assert!(policy_1_12.is_available(&Block::Stone));
+        assert!(!ExportPolicy::new(version("1.15.2")).is_available(&Block::Target));
+        assert!(!policy_1_12.is_available(&Block::Spawner));
+        assert!(policy_1_16.is_available(&Block::Spawner));
+    }
+
+    #[test]
+    fn fallbacks_are_applied_in_order() {
+        let crimson_planks = Block::Planks {
+            material: WoodMaterial::Crimson,
+        };
+        let policy = ExportPolicy::new(version("1.12.2"));
+        assert_eq!(
+            policy.substitute(&crimson_planks),
+            Block::Planks {
+                material: WoodMaterial::Oak
+            }
+        );
+
+        let policy = policy.with_material_fallback(Material::Crimson, Material::DarkOak);
+        assert_eq!(
+            policy.substitute(&crimson_planks),
+            Block::Planks {
+                material: WoodMaterial::DarkOak
+            }
+        );
+
+        let policy = policy.with_fallback(crimson_planks.clone(), Block::NetherWartBlock);
+        assert_eq!(policy.substitute(&crimson_planks), Block::NetherWartBlock);
+
+        let policy = ExportPolicy::without_fallbacks(version("1.12.2"));
+        assert_eq!(policy.substitute(&crimson_planks), Block::Air);
+    }
+
+    #[test]
+    fn new_properties_are_stripped() {
+        let policy = ExportPolicy::without_fallbacks(version("1.12.2"));
+        let waterlogged = Block::Slab(Slab {
+            material: SlabMaterial::Cobblestone,
+            position: SlabVariant::Bottom,
+            waterlogged: true,
+        });
+        assert_eq!(
+            policy.substitute(&waterlogged),
+            Block::Slab(Slab {
+                material: SlabMaterial::Cobblestone,
+                position: SlabVariant::Bottom,
+                waterlogged: false,
+            })
+        );
+        assert!(ExportPolicy::new(version("1.13")).is_available(&waterlogged));
+    }
+
+    #[test]
+    fn cyclic_fallbacks_end_in_air() {
+        let policy = ExportPolicy::without_fallbacks(version("1.12.2"))
+            .with_fallback(Block::BlueIce, Block::Target)
+            .with_fallback(Block::Target, Block::BlueIce);
+        assert_eq!(policy.substitute(&Block::BlueIce), Block::Air);
+    }
+
+    #[test]
+    fn apply_reports_every_substitution() {
+        let mut excerpt = WorldExcerpt::new(2, 1, 2);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Stone);
+        excerpt.set_block_at((0, 0, 1).into(), Block::BlueIce);
+        excerpt.set_block_at((1, 0, 0).into(), Block::BlueIce);
+        excerpt.set_block_at((1, 0, 1).into(), Block::Shroomlight);
+        let policy =
+            ExportPolicy::new(version("1.12.2")).with_fallback(Block::BlueIce, Block::PackedIce);
+
+        let report = policy.apply(&mut excerpt);
+        assert_eq!(report.len(), 3);
+        assert_eq!(
+            report.summary(),
+            vec![
+                (&Block::BlueIce, &Block::PackedIce, 2),
+                (&Block::Shroomlight, &Block::Air, 1),
+            ]
+        );
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::PackedIce));
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Stone));
+        assert!(policy.report(&excerpt).is_empty());
+    }
+
+    #[test]
+    fn unknown_blocks_are_substituted_where_they_cannot_be_saved() {
+        let modded = block_state::BlockState {
+            name: "othermod:machine".into(),
+            properties: Default::default(),
+        }
+        .to_block(version("1.16.5"));
+        assert!(matches!(modded, Block::Unknown(Some(_))));
+        assert!(ExportPolicy::new(version("1.16.5")).is_available(&modded));
+        assert!(!ExportPolicy::new(version("1.16.5")).is_available(&Block::Unknown(Some(7))));
+        assert!(!ExportPolicy::new(version("1.16.5")).is_available(&Block::Unknown(None)));
+
+        let mut excerpt = WorldExcerpt::new(1, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), modded.clone());
+        let policy = ExportPolicy::new(version("1.12.2"));
+        let report = policy.report(&excerpt);
+        assert_eq!(report.summary(), vec![(&modded, &Block::Air, 1)]);
+
+        let policy = policy.with_on_unavailable(OnUnavailable::Fail);
+        assert!(matches!(
+            policy.try_apply(&mut excerpt),
+            Err(FormatError::UnavailableBlock { .. })
+        ));
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&modded));
+    }
+}
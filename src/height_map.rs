@@ -1,6 +1,162 @@
 use std::fmt;
 
-use crate::utils::vec_i32_into_vec_u32;
+use crate::block::Block;
+use crate::utils::{self, vec_i32_into_vec_u32};
+
+/// The kinds of height maps, each tracking the highest block of some sort.
+///
+/// Pre-flattening chunks store a single height map, of blocks affecting sky
+/// light. From 1.13, chunks store several height maps, used by the game for
+/// e.g. spawning, weather, and world generation.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum HeightMapKind {
+    /// Blocks affecting sky light (the pre-flattening "HeightMap", and "LIGHT_BLOCKING" in 1.13).
+    LightBlocking,
+    /// Blocks that block motion, or contain a fluid.
+    MotionBlocking,
+    /// Blocks that block motion, or contain a fluid, except leaves.
+    MotionBlockingNoLeaves,
+    /// Blocks that block motion.
+    OceanFloor,
+    /// Blocks that block motion, as tracked during world generation.
+    OceanFloorWg,
+    /// Any block other than air.
+    WorldSurface,
+    /// Any block other than air, as tracked during world generation.
+    WorldSurfaceWg,
+}
+
+impl HeightMapKind {
+    /// The height maps stored in fully generated post-flattening chunks.
+    pub const FULL_CHUNK: [Self; 4] = [
+        Self::MotionBlocking,
+        Self::MotionBlockingNoLeaves,
+        Self::OceanFloor,
+        Self::WorldSurface,
+    ];
+
+    /// The name of the height map in post-flattening chunks, e.g. "MOTION_BLOCKING".
+    pub fn nbt_name(&self) -> &'static str {
+        match self {
+            Self::LightBlocking => "LIGHT_BLOCKING",
+            Self::MotionBlocking => "MOTION_BLOCKING",
+            Self::MotionBlockingNoLeaves => "MOTION_BLOCKING_NO_LEAVES",
+            Self::OceanFloor => "OCEAN_FLOOR",
+            Self::OceanFloorWg => "OCEAN_FLOOR_WG",
+            Self::WorldSurface => "WORLD_SURFACE",
+            Self::WorldSurfaceWg => "WORLD_SURFACE_WG",
+        }
+    }
+
+    /// The kind of height map with the given post-flattening name, if any.
+    pub fn from_nbt_name(name: &str) -> Option<Self> {
+        match name {
+            "LIGHT_BLOCKING" => Some(Self::LightBlocking),
+            "MOTION_BLOCKING" => Some(Self::MotionBlocking),
+            "MOTION_BLOCKING_NO_LEAVES" => Some(Self::MotionBlockingNoLeaves),
+            "OCEAN_FLOOR" => Some(Self::OceanFloor),
+            "OCEAN_FLOOR_WG" => Some(Self::OceanFloorWg),
+            "WORLD_SURFACE" => Some(Self::WorldSurface),
+            "WORLD_SURFACE_WG" => Some(Self::WorldSurfaceWg),
+            _ => None,
+        }
+    }
+
+    /// Returns true if `block` is of the sort tracked by this kind of height map.
+    pub fn counts(&self, block: &Block) -> bool {
+        match self {
+            Self::LightBlocking => block.is_affecting_sky_light_old(),
+            Self::MotionBlocking => blocks_motion(block) || has_fluid(block),
+            Self::MotionBlockingNoLeaves => {
+                (blocks_motion(block) || has_fluid(block)) && !matches!(block, Block::Leaves { .. })
+            }
+            Self::OceanFloor | Self::OceanFloorWg => blocks_motion(block),
+            Self::WorldSurface | Self::WorldSurfaceWg => {
+                !matches!(block, Block::None | Block::Air | Block::CaveAir)
+            }
+        }
+    }
+}
+
+/// Returns true if `block` is of a material that blocks movement.
+fn blocks_motion(block: &Block) -> bool {
+    !matches!(
+        block,
+        Block::None
+            | Block::Air
+            | Block::CaveAir
+            | Block::Beetroots { .. }
+            | Block::BrownMushroom
+            | Block::BubbleColumn { .. }
+            | Block::Button(..)
+            | Block::Carpet { .. }
+            | Block::Carrots { .. }
+            | Block::Cobweb
+            | Block::CocoaBeans { .. }
+            | Block::Coral { .. }
+            | Block::CoralFan { .. }
+            | Block::CrimsonFungus
+            | Block::CrimsonRoots
+            | Block::DeadBush
+            | Block::EndGateway
+            | Block::EndPortal
+            | Block::Fire { .. }
+            | Block::Flower(_)
+            | Block::FlowerPot(_)
+            | Block::Grass(_)
+            | Block::Kelp { .. }
+            | Block::Ladder { .. }
+            | Block::Lava { .. }
+            | Block::LavaSource
+            | Block::Lever(..)
+            | Block::LilyPad
+            | Block::MelonStem { .. }
+            | Block::NetherPortal { .. }
+            | Block::NetherSprouts
+            | Block::NetherWart { .. }
+            | Block::Potatoes { .. }
+            | Block::PumpkinStem { .. }
+            | Block::Rail { .. }
+            | Block::RedMushroom
+            | Block::RedstoneTorch { .. }
+            | Block::RedstoneWire
+            | Block::Sapling { .. }
+            | Block::Seagrass { .. }
+            | Block::Snow { .. }
+            | Block::SoulFire { .. }
+            | Block::SoulTorch { .. }
+            | Block::StructureVoid
+            | Block::SugarCane { .. }
+            | Block::SweetBerryBush { .. }
+            | Block::Torch { .. }
+            | Block::Tripwire
+            | Block::TripwireHook { .. }
+            | Block::TwistingVines { .. }
+            | Block::TwistingVinesPlant
+            | Block::Vines(_)
+            | Block::WarpedFungus
+            | Block::WarpedRoots
+            | Block::Water { .. }
+            | Block::WaterSource
+            | Block::WeepingVines { .. }
+            | Block::WeepingVinesPlant
+            | Block::Wheat { .. }
+    )
+}
+
+/// Returns true if `block` is, or contains, water or lava.
+fn has_fluid(block: &Block) -> bool {
+    matches!(
+        block,
+        Block::BubbleColumn { .. }
+            | Block::Kelp { .. }
+            | Block::Lava { .. }
+            | Block::LavaSource
+            | Block::Seagrass { .. }
+            | Block::Water { .. }
+            | Block::WaterSource
+    ) || block.is_waterlogged()
+}
 
 //#[derive(Debug)]
 #[derive(Clone)]
@@ -21,12 +177,39 @@ impl HeightMap {
         }
     }
 
-    /// Create a new heightmap from a HeightMap NBT tag
+    /// Create a new heightmap of the given dimensions from a HeightMap NBT tag
     /// * NB Pre-flattening heightmap NBT format!
-    pub fn from_nbt_value(nbt: nbt::Value) -> Self {
-        let (x_dim, z_dim) = (16, 16);
+    pub fn from_nbt_value_with_dim(nbt: nbt::Value, (x_dim, z_dim): (usize, usize)) -> Self {
         if let nbt::Value::IntArray(heights) = nbt {
-            let heights = vec_i32_into_vec_u32(heights);
+            let mut heights = vec_i32_into_vec_u32(heights);
+            heights.resize(x_dim * z_dim, 0);
+
+            Self {
+                heights,
+                x_dim,
+                z_dim,
+            }
+        } else {
+            panic!("Unexpected nbt value type for height map.");
+        }
+    }
+
+    /// Create a new 16x16 heightmap from one of the height map tags of a
+    /// post-flattening chunk, e.g. "Heightmaps/MOTION_BLOCKING".
+    ///
+    /// `chunk_height` is the number of blocks in the height span of the chunk,
+    /// and `padded` tells if the heights are packed with padding (from 20w17a).
+    pub fn from_packed_nbt_value(nbt: nbt::Value, chunk_height: usize, padded: bool) -> Self {
+        let (x_dim, z_dim) = (16, 16);
+        if let nbt::Value::LongArray(packed) = nbt {
+            let packed: Vec<u64> = packed.iter().map(|long| *long as u64).collect();
+            let bits = Self::bits_per_height(chunk_height);
+            let mut heights: Vec<u32> = if padded {
+                utils::paddedly_unpacked(&packed, bits)
+            } else {
+                utils::tightly_unpacked(&packed, bits)
+            };
+            heights.resize(x_dim * z_dim, 0);
 
             Self {
                 heights,
@@ -38,6 +221,24 @@ impl HeightMap {
         }
     }
 
+    /// The heightmap as a post-flattening height map tag.
+    ///
+    /// See [`from_packed_nbt_value()`](Self::from_packed_nbt_value) for the arguments.
+    pub fn to_packed_nbt_value(&self, chunk_height: usize, padded: bool) -> nbt::Value {
+        let bits = Self::bits_per_height(chunk_height);
+        let packed = if padded {
+            utils::paddedly_packed(&self.heights, bits)
+        } else {
+            utils::tightly_packed(&self.heights, bits)
+        };
+        nbt::Value::LongArray(packed.into_iter().map(|long| long as i64).collect())
+    }
+
+    /// Number of bits needed for storing heights from 0 through `chunk_height`.
+    fn bits_per_height(chunk_height: usize) -> usize {
+        (usize::BITS - chunk_height.leading_zeros()) as usize
+    }
+
     pub fn dim(&self) -> (usize, usize) {
         (self.x_dim, self.z_dim)
     }
@@ -95,3 +296,59 @@ impl fmt::Debug for HeightMap {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_cuboid::BlockCuboid;
+    use crate::material::LeavesMaterial;
+    use crate::positioning::Surface5;
+
+    #[test]
+    fn kinds_track_different_blocks() {
+        let leaves = Block::Leaves {
+            material: LeavesMaterial::Oak,
+            distance_to_trunk: None,
+            persistent: true,
+        };
+        let mut blocks = BlockCuboid::new_filled((2, 6, 1), Block::Air);
+        // A pond with a torch on a leaf block in it
+        blocks.insert((0, 0, 0), Block::Stone);
+        blocks.insert((0, 1, 0), Block::WaterSource);
+        blocks.insert((1, 0, 0), Block::Stone);
+        blocks.insert((1, 1, 0), leaves);
+        blocks.insert(
+            (1, 2, 0),
+            Block::Torch {
+                attached: Surface5::Down,
+            },
+        );
+
+        let heights = |kind| blocks.height_map_of(kind).to_vec_u32();
+        assert_eq!(heights(HeightMapKind::WorldSurface), vec![2, 3]);
+        assert_eq!(heights(HeightMapKind::MotionBlocking), vec![2, 2]);
+        assert_eq!(heights(HeightMapKind::MotionBlockingNoLeaves), vec![2, 1]);
+        assert_eq!(heights(HeightMapKind::OceanFloor), vec![1, 2]);
+        assert_eq!(heights(HeightMapKind::LightBlocking), vec![2, 2]);
+    }
+
+    #[test]
+    fn packed_heights_round_trip() {
+        let mut height_map = HeightMap::new((16, 16));
+        for x in 0..16 {
+            for z in 0..16 {
+                height_map.set_height((x, z), (x * 16 + z) as u32 % 385);
+            }
+        }
+        for &(chunk_height, padded) in &[(256, false), (256, true), (384, true)] {
+            let nbt = height_map.to_packed_nbt_value(chunk_height, padded);
+            if let nbt::Value::LongArray(longs) = &nbt {
+                // 9 bits per height, for 256 heights
+                let expected_len = if padded { 37 } else { 36 };
+                assert_eq!(longs.len(), expected_len);
+            }
+            let read = HeightMap::from_packed_nbt_value(nbt, chunk_height, padded);
+            assert_eq!(read.to_vec_u32(), height_map.to_vec_u32());
+        }
+    }
+}
@@ -1,8 +1,10 @@
 //! Inventory
 
-use crate::item::Item;
+use crate::item::{self, Item};
+use crate::mc_version::McVersion;
 use crate::nbt_lookup::*;
 
+use serde_derive::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 // TODO / FUTURE WORK
@@ -15,7 +17,7 @@ use std::collections::HashMap;
 // e.g. furnace has "fuel" slot, "cooking" slot and "cooked" slot,
 // and animals have "saddle" slot, "armor" slot, "chest" slots, etc.
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Inventory {
     slots: HashMap<i8, ItemStack>,
 }
@@ -36,13 +38,32 @@ impl Inventory {
         Inventory { slots }
     }
 
-    pub(crate) fn to_nbt_value(&self) -> nbt::Value {
+    /// Generates the NBT list of the items, as stored in saves of the given
+    /// Minecraft version.
+    ///
+    /// Items that do not exist in that version are skipped, with a warning.
+    pub(crate) fn to_nbt_value(&self, data_version: McVersion) -> nbt::Value {
         let mut items = Vec::with_capacity(self.slots.len());
 
         for (slot, stack) in &self.slots {
-            let mut item = stack.item.to_nbt_value();
+            let mut item = match stack.item.to_nbt_value(data_version) {
+                Some(item) => item,
+                None => {
+                    eprintln!(
+                        "[warning] Skipping {:?}, which does not exist in Minecraft {}",
+                        stack.item.kind(),
+                        data_version
+                    );
+                    continue;
+                }
+            };
             if let nbt::Value::Compound(ref mut item) = item {
-                item.insert("Count".into(), nbt::Value::Byte(stack.count));
+                // The count is an int named "count" from 1.20.5
+                if item::has_components(data_version) {
+                    item.insert("count".into(), nbt::Value::Int(stack.count.into()));
+                } else {
+                    item.insert("Count".into(), nbt::Value::Byte(stack.count));
+                }
                 item.insert("Slot".into(), nbt::Value::Byte(*slot));
             }
             items.push(item);
@@ -51,14 +72,22 @@ impl Inventory {
         nbt::Value::List(items)
     }
 
+    /// Puts `count` of `item` in the given slot, replacing any previous contents.
+    pub fn insert(&mut self, slot: i8, item: Item, count: i8) {
+        self.slots.insert(slot, ItemStack { item, count });
+    }
+
+    /// Iterates over the items of the occupied slots, along with their counts.
+    pub fn items(&self) -> impl Iterator<Item = (&Item, i8)> + '_ {
+        self.slots.values().map(|stack| (&stack.item, stack.count))
+    }
+
     // TODO / FUTURE WORK
     // Interface:
-    // Put stack in slot
     // Get stack from slot
     // Get capacity?
     // Get number of empty slots?
     // Put stack somewhere (if space)
-    // Iterate over slots?
 }
 
 impl Default for Inventory {
@@ -67,7 +96,7 @@ impl Default for Inventory {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 struct ItemStack {
     item: Item,
     count: i8,
@@ -75,7 +104,9 @@ struct ItemStack {
 
 impl ItemStack {
     pub fn from_nbt_value(value: &nbt::Value) -> Self {
-        let count = nbt_value_lookup_byte(value, "Count").unwrap();
+        let count = nbt_value_lookup_byte(value, "Count")
+            .or_else(|| nbt_value_lookup_int(value, "count").map(|count| count as i8))
+            .unwrap_or(1);
         let item = Item::from_nbt_value(value);
 
         Self { item, count }
@@ -90,3 +121,33 @@ impl ItemStack {
     // Split stack (returns the half taken off the stac)
     // Replace stack (returns the original stack)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::item::ItemKind;
+    use std::str::FromStr;
+
+    #[test]
+    fn counts_get_the_format_of_the_version() {
+        let mut inventory = Inventory::new();
+        inventory.insert(3, Item::new_with_kind(ItemKind::Apple), 12);
+
+        for (data_version, count) in &[
+            ("1.20.4", nbt::Value::Byte(12)),
+            ("1.21.7", nbt::Value::Int(12)),
+        ] {
+            let list = match inventory.to_nbt_value(McVersion::from_str(data_version).unwrap()) {
+                nbt::Value::List(list) => list,
+                _ => panic!("Inventory is not a list"),
+            };
+            let key = if *data_version == "1.20.4" {
+                "Count"
+            } else {
+                "count"
+            };
+            assert_eq!(nbt_value_lookup(&list[0], key).as_ref(), Some(count));
+            assert_eq!(Inventory::from_nbt_value_vec(&list), inventory);
+        }
+    }
+}
@@ -1,17 +1,69 @@
 //! Items.
 
+use std::collections::HashMap;
+use std::mem::Discriminant;
+use std::str::FromStr;
+
+use once_cell::sync::Lazy;
+use serde_derive::{Deserialize, Serialize};
+
 use crate::block::{AnvilDamage, Flower};
+use crate::block_state::{
+    flower_from_name, flower_name, name_of, parsed_name, BUTTON_MATERIALS, COLOURS, DOOR_MATERIALS,
+    FENCE_MATERIALS, PRESSURE_PLATE_MATERIALS, SAPLING_MATERIALS, SLAB_MATERIALS, STAIR_MATERIALS,
+    WOOD_MATERIALS,
+};
 use crate::colour::Colour;
 use crate::enchantment::Enchantment;
 use crate::material::*;
+use crate::mc_version::McVersion;
 use crate::nbt_lookup::*;
+use crate::potion::PotionType;
+use crate::status_effect::{StatusEffect, StatusEffectInstance};
+use crate::text::{
+    as_json_text, book_pages, json_text, plain_text, text_from_nbt, text_list, text_to_nbt,
+};
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Item {
+    /// Custom name, as a JSON text component.
     custom_name: Option<String>,
-    enchantments: Vec<Enchantment>,
+    /// Lines of lore, as JSON text components.
+    lore: Vec<String>,
+    enchantments: Vec<(Enchantment, i16)>,
+    /// Enchantments held by an enchanted book, for applying to other items.
+    stored_enchantments: Vec<(Enchantment, i16)>,
+    /// Enchantments and stored enchantments unknown to mcprogedit (e.g. those
+    /// of mods), by their ids as read, kept for writing them back.
+    unknown_enchantments: Vec<(String, i16)>,
+    unknown_stored_enchantments: Vec<(String, i16)>,
+    unbreakable: bool,
+    /// Levels added to the cost of using the item in an anvil.
+    repair_cost: Option<i32>,
+    /// Colour of dyed leather armour, as RGB.
+    dye_colour: Option<u32>,
+    attribute_modifiers: Vec<AttributeModifier>,
+    /// Base type of potions and tipped arrows.
+    potion: Option<PotionType>,
+    /// Effects of potions and tipped arrows, in addition to those of the base type.
+    custom_effects: Vec<StatusEffectInstance>,
+    /// Colour of potions and tipped arrows, as RGB, if not that of their effects.
+    potion_colour: Option<u32>,
+    /// Effects of suspicious stew, with their durations in game ticks.
+    stew_effects: Vec<(StatusEffect, i32)>,
+    /// Pages of a book and quill or written book, as JSON text components.
+    pages: Vec<String>,
+    /// Title, author and generation of a written book.
+    title: Option<String>,
+    author: Option<String>,
+    generation: Option<BookGeneration>,
     damage: Option<i16>,
     kind: ItemKind,
+    /// Id of an item of unknown kind, as read, for writing it back unchanged.
+    unknown_id: Option<String>,
+    /// Tags of the "tag" compound (or data components, from 1.20.5) not
+    /// handled by mcprogedit, kept as read.
+    other_tags: nbt::Map<String, nbt::Value>,
 }
 
 impl Default for Item {
@@ -24,38 +76,1421 @@ impl Item {
     pub fn new() -> Self {
         Self {
             custom_name: None,
+            lore: Vec::new(),
             enchantments: Vec::new(),
+            stored_enchantments: Vec::new(),
+            unknown_enchantments: Vec::new(),
+            unknown_stored_enchantments: Vec::new(),
+            unbreakable: false,
+            repair_cost: None,
+            dye_colour: None,
+            attribute_modifiers: Vec::new(),
+            potion: None,
+            custom_effects: Vec::new(),
+            potion_colour: None,
+            stew_effects: Vec::new(),
+            pages: Vec::new(),
+            title: None,
+            author: None,
+            generation: None,
             damage: None,
             kind: ItemKind::Apple,
+            unknown_id: None,
+            other_tags: nbt::Map::new(),
+        }
+    }
+
+    /// Creates an item of the given kind, without name, enchantments, etc.
+    pub fn new_with_kind(kind: ItemKind) -> Self {
+        Self {
+            kind,
+            ..Self::new()
         }
     }
 
     pub fn new_record(recording: Recording) -> Self {
+        Self::new_with_kind(ItemKind::Record(recording))
+    }
+
+    /// Creates a book and quill holding the given plain text, split into pages.
+    pub fn new_writable_book(text: &str) -> Self {
+        Self::new_with_kind(ItemKind::BookAndQuill).with_pages(&book_pages(text))
+    }
+
+    /// Creates an original written book with the given title and author,
+    /// holding the given plain text, split into pages.
+    pub fn new_written_book(title: &str, author: &str, text: &str) -> Self {
         Self {
-            custom_name: None,
-            enchantments: Vec::new(),
-            damage: None,
-            kind: ItemKind::Record(recording),
+            title: Some(title.to_string()),
+            author: Some(author.to_string()),
+            generation: Some(BookGeneration::Original),
+            ..Self::new_with_kind(ItemKind::WrittenBook)
         }
+        .with_pages(&book_pages(text))
+    }
+
+    /// Returns what kind of item this is.
+    pub fn kind(&self) -> &ItemKind {
+        &self.kind
+    }
+
+    /// Returns the custom name of the item, as plain text.
+    pub fn custom_name(&self) -> Option<String> {
+        self.custom_name.as_deref().map(plain_text)
+    }
+
+    /// Gives the item a custom name, from plain text.
+    pub fn with_custom_name(mut self, name: &str) -> Self {
+        self.custom_name = Some(json_text(name));
+        self
+    }
+
+    /// Returns the lines of lore of the item, as plain text.
+    pub fn lore(&self) -> Vec<String> {
+        self.lore.iter().map(|line| plain_text(line)).collect()
+    }
+
+    /// Gives the item the given lines of lore, from plain text.
+    pub fn with_lore(mut self, lines: &[&str]) -> Self {
+        self.lore = lines.iter().map(|line| json_text(line)).collect();
+        self
+    }
+
+    /// Returns the enchantments of the item, along with their levels.
+    pub fn enchantments(&self) -> &[(Enchantment, i16)] {
+        &self.enchantments
+    }
+
+    /// Enchants the item, replacing any previous level of the same enchantment.
+    pub fn with_enchantment(mut self, enchantment: Enchantment, level: i16) -> Self {
+        set_enchantment(&mut self.enchantments, enchantment, level);
+        self
+    }
+
+    /// Returns the enchantments stored in an enchanted book, along with their levels.
+    pub fn stored_enchantments(&self) -> &[(Enchantment, i16)] {
+        &self.stored_enchantments
+    }
+
+    /// Stores an enchantment in the item, as done for enchanted books.
+    pub fn with_stored_enchantment(mut self, enchantment: Enchantment, level: i16) -> Self {
+        set_enchantment(&mut self.stored_enchantments, enchantment, level);
+        self
+    }
+
+    /// Returns how much durability the item has lost, if any.
+    pub fn damage(&self) -> Option<i16> {
+        self.damage
+    }
+
+    pub fn with_damage(mut self, damage: i16) -> Self {
+        self.damage = Some(damage);
+        self
+    }
+
+    pub fn is_unbreakable(&self) -> bool {
+        self.unbreakable
+    }
+
+    pub fn with_unbreakable(mut self, unbreakable: bool) -> Self {
+        self.unbreakable = unbreakable;
+        self
+    }
+
+    /// Returns the number of levels added to the cost of using the item in an anvil.
+    pub fn repair_cost(&self) -> Option<i32> {
+        self.repair_cost
+    }
+
+    pub fn with_repair_cost(mut self, repair_cost: i32) -> Self {
+        self.repair_cost = Some(repair_cost);
+        self
+    }
+
+    /// Returns the colour of dyed leather armour, as RGB.
+    pub fn dye_colour(&self) -> Option<u32> {
+        self.dye_colour
+    }
+
+    pub fn with_dye_colour(mut self, rgb: u32) -> Self {
+        self.dye_colour = Some(rgb);
+        self
+    }
+
+    pub fn attribute_modifiers(&self) -> &[AttributeModifier] {
+        &self.attribute_modifiers
+    }
+
+    pub fn with_attribute_modifier(mut self, modifier: AttributeModifier) -> Self {
+        self.attribute_modifiers.push(modifier);
+        self
+    }
+
+    /// Returns the base type of a potion or tipped arrow.
+    pub fn potion(&self) -> Option<PotionType> {
+        self.potion
+    }
+
+    pub fn with_potion(mut self, potion: PotionType) -> Self {
+        self.potion = Some(potion);
+        self
+    }
+
+    /// Returns the effects of a potion or tipped arrow, in addition to those
+    /// of its base type.
+    pub fn custom_effects(&self) -> &[StatusEffectInstance] {
+        &self.custom_effects
+    }
+
+    pub fn with_custom_effect(mut self, effect: StatusEffectInstance) -> Self {
+        self.custom_effects.push(effect);
+        self
+    }
+
+    /// Returns the colour of a potion or tipped arrow, as RGB, if it is not
+    /// that of its effects.
+    pub fn potion_colour(&self) -> Option<u32> {
+        self.potion_colour
+    }
+
+    pub fn with_potion_colour(mut self, rgb: u32) -> Self {
+        self.potion_colour = Some(rgb);
+        self
+    }
+
+    /// Returns the effects of suspicious stew, with their durations in game ticks.
+    pub fn stew_effects(&self) -> &[(StatusEffect, i32)] {
+        &self.stew_effects
+    }
+
+    pub fn with_stew_effect(mut self, effect: StatusEffect, duration: i32) -> Self {
+        self.stew_effects.push((effect, duration));
+        self
+    }
+
+    /// Returns the pages of a book, as plain text.
+    pub fn pages(&self) -> Vec<String> {
+        self.pages.iter().map(|page| plain_text(page)).collect()
+    }
+
+    /// Gives a book the given pages, from plain text.
+    pub fn with_pages<S: AsRef<str>>(mut self, pages: &[S]) -> Self {
+        self.pages = pages.iter().map(|page| json_text(page.as_ref())).collect();
+        self
+    }
+
+    /// Returns the title of a written book.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    /// Returns the author of a written book.
+    pub fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    /// Returns whether a written book is the original or a copy.
+    pub fn generation(&self) -> Option<BookGeneration> {
+        self.generation
+    }
+
+    pub fn with_generation(mut self, generation: BookGeneration) -> Self {
+        self.generation = Some(generation);
+        self
     }
 
     // Source: https://minecraft.gamepedia.com/Java_Edition_data_value/Pre-flattening#Item_IDs
     // Implemented block items
     // Next item to implement: pumpkin_pie (from beginning) or beetroot_soup (from end)
-    // Missing:
-    // * lingering_potion
-    // * potion
-    // * splash_potion
-    // * tipped_arrow
-    // * spectral_arrow
-    // * dragon_breath
+    //
+    // Items of saves from before the flattening are told apart by their top level
+    // "Damage" tag, which moved into the "tag" compound with the flattening.
+    // Items of 1.20.5 and later have data components instead of the "tag" compound.
     pub fn from_nbt_value(value: &nbt::Value) -> Self {
         let id = nbt_value_lookup_string(value, "id").unwrap(); // mandatory
-        let damage = nbt_value_lookup_short(value, "Damage").unwrap_or(0);
-        // TODO Some items must store data from a "tag" field as well.
-        // let tag = nbt_value_lookup(&value, "tag"); // optional
+        if let Some(nbt::Value::Compound(components)) = nbt_value_lookup(value, "components") {
+            return Self::from_components(id, components);
+        }
+        let legacy_damage = nbt_value_lookup_short(value, "Damage");
+        let mut tag = match nbt_value_lookup(value, "tag") {
+            Some(nbt::Value::Compound(tag)) => tag,
+            _ => nbt::Map::new(),
+        };
+
+        let kind = match legacy_damage {
+            Some(damage) => ItemKind::from_legacy_id(&id, damage),
+            None => id
+                .strip_prefix("minecraft:")
+                .and_then(ItemKind::from_modern_name)
+                .unwrap_or_else(|| ItemKind::from_legacy_id(&id, 0)),
+        };
+        let damage = match legacy_damage {
+            // Before the flattening, the damage value is also used for subtypes
+            Some(damage) if damage != 0 && (kind.is_damageable() || kind == ItemKind::Unknown) => {
+                Some(damage)
+            }
+            Some(_) => None,
+            None => match tag.remove("Damage") {
+                Some(nbt::Value::Int(damage)) => Some(damage as i16),
+                Some(other) => {
+                    tag.insert("Damage".into(), other);
+                    None
+                }
+                None => None,
+            },
+        };
+        let unknown_id = if kind == ItemKind::Unknown {
+            Some(id)
+        } else {
+            None
+        };
+
+        let mut item = Self {
+            damage,
+            kind,
+            unknown_id,
+            ..Self::new()
+        };
+        item.read_tag(tag, legacy_damage.is_some());
+        item
+    }
+
+    /// Creates an item from the id and data components used from 1.20.5.
+    fn from_components(id: String, mut components: nbt::Map<String, nbt::Value>) -> Self {
+        let kind = id
+            .strip_prefix("minecraft:")
+            .and_then(ItemKind::from_modern_name)
+            .unwrap_or(ItemKind::Unknown);
+        let damage = match components.remove("minecraft:damage") {
+            Some(nbt::Value::Int(damage)) => Some(damage as i16),
+            Some(other) => {
+                components.insert("minecraft:damage".into(), other);
+                None
+            }
+            None => None,
+        };
+        let unknown_id = if kind == ItemKind::Unknown {
+            Some(id)
+        } else {
+            None
+        };
+
+        let mut item = Self {
+            damage,
+            kind,
+            unknown_id,
+            ..Self::new()
+        };
+        item.read_components(components);
+        item
+    }
+
+    /// Reads the data components of the item, keeping any components not
+    /// handled as they are.
+    fn read_components(&mut self, mut components: nbt::Map<String, nbt::Value>) {
+        // Text is JSON before 1.21.5, and NBT from 1.21.5
+        let text = |value: &nbt::Value| match value {
+            nbt::Value::String(text) => as_json_text(text),
+            other => text_from_nbt(other),
+        };
+        if let Some(name) = components.remove("minecraft:custom_name") {
+            self.custom_name = Some(text(&name));
+        }
+        if let Some(nbt::Value::List(lore)) = components.remove("minecraft:lore") {
+            self.lore = lore.iter().map(text).collect();
+        }
+
+        if let Some(value) = components.remove("minecraft:enchantments") {
+            let (known, unknown) = enchantments_from_component(&value);
+            self.enchantments = known;
+            self.unknown_enchantments = unknown;
+        }
+        if let Some(value) = components.remove("minecraft:stored_enchantments") {
+            let (known, unknown) = enchantments_from_component(&value);
+            self.stored_enchantments = known;
+            self.unknown_stored_enchantments = unknown;
+        }
+        if components.remove("minecraft:unbreakable").is_some() {
+            self.unbreakable = true;
+        }
+        if let Some(nbt::Value::Int(repair_cost)) = components.remove("minecraft:repair_cost") {
+            self.repair_cost = Some(repair_cost);
+        }
+        self.dye_colour = match components.remove("minecraft:dyed_color") {
+            Some(nbt::Value::Int(rgb)) => Some(rgb as u32),
+            Some(value) => nbt_value_lookup_int(&value, "rgb").map(|rgb| rgb as u32),
+            None => None,
+        };
+        let modifiers = match components.remove("minecraft:attribute_modifiers") {
+            Some(nbt::Value::List(list)) => list,
+            Some(value) => nbt_value_lookup_list(&value, "modifiers").unwrap_or_default(),
+            None => Vec::new(),
+        };
+        self.attribute_modifiers = modifiers
+            .iter()
+            .filter_map(|value| {
+                let modifier = AttributeModifier::from_component_value(value);
+                if modifier.is_none() {
+                    eprintln!("[warning] Skipping invalid attribute modifier: {:?}", value);
+                }
+                modifier
+            })
+            .collect();
+
+        if let Some(value) = components.remove("minecraft:potion_contents") {
+            let potion = match &value {
+                nbt::Value::String(potion) => Some(potion.clone()),
+                value => nbt_value_lookup_string(value, "potion"),
+            };
+            match potion.as_deref().map(PotionType::from_name) {
+                // Unknown potion types are kept with the other components
+                Some(None) => {
+                    components.insert("minecraft:potion_contents".into(), value);
+                }
+                potion => {
+                    self.potion = potion.flatten();
+                    if let Some(colour) = nbt_value_lookup_int(&value, "custom_color") {
+                        self.potion_colour = Some(colour as u32);
+                    }
+                    let effects =
+                        nbt_value_lookup_list(&value, "custom_effects").unwrap_or_default();
+                    self.custom_effects = effects
+                        .iter()
+                        .filter_map(|value| {
+                            let effect = StatusEffectInstance::from_nbt_value(value);
+                            if effect.is_none() {
+                                eprintln!("[warning] Skipping unknown status effect: {:?}", value);
+                            }
+                            effect
+                        })
+                        .collect();
+                }
+            }
+        }
+        if let Some(nbt::Value::List(list)) = components.remove("minecraft:suspicious_stew_effects")
+        {
+            self.stew_effects = list.iter().filter_map(stew_effect_from_nbt_value).collect();
+        }
+
+        // Pages and titles are either text, or compounds with the text as "raw"
+        let raw = |value: &nbt::Value| match nbt_value_lookup(value, "raw") {
+            Some(raw) => raw,
+            None => value.clone(),
+        };
+        if let Some(value) = components.remove("minecraft:writable_book_content") {
+            let pages = nbt_value_lookup_list(&value, "pages").unwrap_or_default();
+            self.pages = pages
+                .iter()
+                .filter_map(|page| match raw(page) {
+                    nbt::Value::String(page) => Some(json_text(&page)),
+                    _ => None,
+                })
+                .collect();
+        }
+        if let Some(value) = components.remove("minecraft:written_book_content") {
+            let pages = nbt_value_lookup_list(&value, "pages").unwrap_or_default();
+            self.pages = pages.iter().map(|page| text(&raw(page))).collect();
+            self.title = match nbt_value_lookup(&value, "title").as_ref().map(raw) {
+                Some(nbt::Value::String(title)) => Some(title),
+                _ => None,
+            };
+            self.author = nbt_value_lookup_string(&value, "author");
+            self.generation = nbt_value_lookup_int(&value, "generation").map(BookGeneration::from);
+        }
+
+        self.other_tags = components;
+    }
+
+    /// Reads the data held in the "tag" compound of the item, keeping any tags
+    /// not handled as they are.
+    fn read_tag(&mut self, mut tag: nbt::Map<String, nbt::Value>, pre_flattening: bool) {
+        if let Some(nbt::Value::Compound(mut display)) = tag.remove("display") {
+            // Names are JSON text from the flattening, and lore lines from 1.14.
+            // Lore lines of 1.13 are plain text, unless they look like JSON text.
+            if let Some(nbt::Value::String(name)) = display.remove("Name") {
+                self.custom_name = Some(if pre_flattening {
+                    json_text(&name)
+                } else {
+                    name
+                });
+            }
+            if let Some(nbt::Value::List(lore)) = display.remove("Lore") {
+                self.lore = lore
+                    .iter()
+                    .filter_map(|line| match line {
+                        nbt::Value::String(line) if pre_flattening => Some(json_text(line)),
+                        nbt::Value::String(line) => Some(as_json_text(line)),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            if let Some(nbt::Value::Int(colour)) = display.remove("color") {
+                self.dye_colour = Some(colour as u32);
+            }
+            if !display.is_empty() {
+                tag.insert("display".into(), nbt::Value::Compound(display));
+            }
+        }
+
+        for key in &["ench", "Enchantments"] {
+            if let Some(nbt::Value::List(list)) = tag.remove(*key) {
+                let (known, unknown) = enchantments_from_nbt_list(&list);
+                self.enchantments.extend(known);
+                self.unknown_enchantments.extend(unknown);
+            }
+        }
+        if let Some(nbt::Value::List(list)) = tag.remove("StoredEnchantments") {
+            let (known, unknown) = enchantments_from_nbt_list(&list);
+            self.stored_enchantments = known;
+            self.unknown_stored_enchantments = unknown;
+        }
+        if let Some(nbt::Value::Byte(unbreakable)) = tag.remove("Unbreakable") {
+            self.unbreakable = unbreakable != 0;
+        }
+        if let Some(nbt::Value::Int(repair_cost)) = tag.remove("RepairCost") {
+            self.repair_cost = Some(repair_cost);
+        }
+        if let Some(nbt::Value::List(list)) = tag.remove("AttributeModifiers") {
+            self.attribute_modifiers = list
+                .iter()
+                .filter_map(|value| {
+                    let modifier = AttributeModifier::from_nbt_value(value);
+                    if modifier.is_none() {
+                        eprintln!("[warning] Skipping invalid attribute modifier: {:?}", value);
+                    }
+                    modifier
+                })
+                .collect();
+        }
+
+        // Unknown potion types are kept with the other tags
+        let potion = match tag.get("Potion") {
+            Some(nbt::Value::String(potion)) => PotionType::from_name(potion),
+            _ => None,
+        };
+        if potion.is_some() {
+            tag.remove("Potion");
+            self.potion = potion;
+        }
+        for key in &["CustomPotionEffects", "custom_potion_effects"] {
+            if let Some(nbt::Value::List(list)) = tag.remove(*key) {
+                self.custom_effects.extend(list.iter().filter_map(|value| {
+                    let effect = StatusEffectInstance::from_nbt_value(value);
+                    if effect.is_none() {
+                        eprintln!("[warning] Skipping unknown status effect: {:?}", value);
+                    }
+                    effect
+                }));
+            }
+        }
+        if let Some(nbt::Value::Int(colour)) = tag.remove("CustomPotionColor") {
+            self.potion_colour = Some(colour as u32);
+        }
+        if self.kind == ItemKind::Bowl(Some(BowlContents::SuspiciousStew)) {
+            for key in &["Effects", "effects"] {
+                if let Some(nbt::Value::List(list)) = tag.remove(*key) {
+                    self.stew_effects
+                        .extend(list.iter().filter_map(stew_effect_from_nbt_value));
+                }
+            }
+        }
+
+        // Pages of written books are JSON text, and of books and quills plain text
+        if self.kind == ItemKind::BookAndQuill || self.kind == ItemKind::WrittenBook {
+            let written = self.kind == ItemKind::WrittenBook;
+            if let Some(nbt::Value::List(pages)) = tag.remove("pages") {
+                self.pages = pages
+                    .iter()
+                    .filter_map(|page| match page {
+                        nbt::Value::String(page) if written => Some(as_json_text(page)),
+                        nbt::Value::String(page) => Some(json_text(page)),
+                        _ => None,
+                    })
+                    .collect();
+            }
+        }
+        if self.kind == ItemKind::WrittenBook {
+            if let Some(nbt::Value::String(title)) = tag.remove("title") {
+                self.title = Some(title);
+            }
+            if let Some(nbt::Value::String(author)) = tag.remove("author") {
+                self.author = Some(author);
+            }
+            if let Some(nbt::Value::Int(generation)) = tag.remove("generation") {
+                self.generation = Some(BookGeneration::from(generation));
+            }
+        }
+
+        self.other_tags = tag;
+    }
+
+    /// Generates the NBT compound of the item, without count and slot, as stored
+    /// in saves of the given Minecraft version.
+    ///
+    /// Returns `None` if the item does not exist in that version.
+    pub(crate) fn to_nbt_value(&self, data_version: McVersion) -> Option<nbt::Value> {
+        let mut item: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(4);
+        let components = has_components(data_version);
+
+        // Unhandled tags can only be written back in the layout they were read in
+        let mut tag: nbt::Map<String, nbt::Value> = self
+            .other_tags
+            .iter()
+            .filter(|(key, _)| {
+                let kept = is_component(key) == components;
+                if !kept {
+                    eprintln!(
+                        "[warning] Skipping item tag {:?}, which Minecraft {} does not use",
+                        key, data_version
+                    );
+                }
+                kept
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+
+        if data_version < McVersion::from_str("17w47a").unwrap() {
+            let (id, damage) = match (&self.kind, &self.unknown_id) {
+                (ItemKind::Unknown, Some(id)) => (id.clone(), self.damage.unwrap_or(0)),
+                (kind, _) => {
+                    let (id, subtype) = kind.legacy_id()?;
+                    (format!("minecraft:{}", id), self.damage.unwrap_or(subtype))
+                }
+            };
+            item.insert("id".into(), nbt::Value::String(id));
+            item.insert("Damage".into(), nbt::Value::Short(damage));
+        } else {
+            let id = match (&self.kind, &self.unknown_id) {
+                (ItemKind::Unknown, Some(id)) => id.clone(),
+                (kind, _) => format!("minecraft:{}", kind.modern_name(data_version)?),
+            };
+            item.insert("id".into(), nbt::Value::String(id));
+            if let Some(damage) = self.damage {
+                let key = if components {
+                    "minecraft:damage"
+                } else {
+                    "Damage"
+                };
+                tag.insert(key.into(), nbt::Value::Int(damage.into()));
+            }
+        }
+        if components {
+            self.write_components(&mut tag, data_version);
+        } else {
+            self.write_tag(&mut tag, data_version);
+        }
+
+        if !tag.is_empty() {
+            let key = if components { "components" } else { "tag" };
+            item.insert(key.into(), nbt::Value::Compound(tag));
+        }
+        Some(nbt::Value::Compound(item))
+    }
+
+    /// Adds the data of the item to `components`, as the data components of
+    /// the given Minecraft version (1.20.5 or later).
+    fn write_components(
+        &self,
+        components: &mut nbt::Map<String, nbt::Value>,
+        data_version: McVersion,
+    ) {
+        // Text is stored as NBT rather than JSON, and whether data is shown in
+        // tooltips is no longer part of each component, from 1.21.5
+        let modern = data_version >= McVersion::from_str("1.21.5").unwrap();
+        let text = |json: &String| {
+            if modern {
+                text_to_nbt(json)
+            } else {
+                nbt::Value::String(json.clone())
+            }
+        };
+        let with_tooltip = |key: &str, value: nbt::Value| {
+            if modern {
+                value
+            } else {
+                let mut compound = nbt::Map::new();
+                compound.insert(key.into(), value);
+                nbt::Value::Compound(compound)
+            }
+        };
+
+        if let Some(name) = &self.custom_name {
+            components.insert("minecraft:custom_name".into(), text(name));
+        }
+        if !self.lore.is_empty() {
+            let lore = text_list(self.lore.iter().map(text).collect());
+            components.insert("minecraft:lore".into(), lore);
+        }
+
+        if !self.enchantments.is_empty() || !self.unknown_enchantments.is_empty() {
+            let levels =
+                enchantment_levels(&self.enchantments, &self.unknown_enchantments, data_version);
+            components.insert(
+                "minecraft:enchantments".into(),
+                with_tooltip("levels", levels),
+            );
+        }
+        if !self.stored_enchantments.is_empty() || !self.unknown_stored_enchantments.is_empty() {
+            let levels = enchantment_levels(
+                &self.stored_enchantments,
+                &self.unknown_stored_enchantments,
+                data_version,
+            );
+            components.insert(
+                "minecraft:stored_enchantments".into(),
+                with_tooltip("levels", levels),
+            );
+        }
+        if self.unbreakable {
+            components.insert(
+                "minecraft:unbreakable".into(),
+                nbt::Value::Compound(nbt::Map::new()),
+            );
+        }
+        if let Some(repair_cost) = self.repair_cost {
+            components.insert("minecraft:repair_cost".into(), nbt::Value::Int(repair_cost));
+        }
+        if let Some(colour) = self.dye_colour {
+            components.insert(
+                "minecraft:dyed_color".into(),
+                with_tooltip("rgb", nbt::Value::Int(colour as i32)),
+            );
+        }
+        if !self.attribute_modifiers.is_empty() {
+            let modifiers = self
+                .attribute_modifiers
+                .iter()
+                .map(|modifier| modifier.to_component_value(data_version))
+                .collect();
+            components.insert(
+                "minecraft:attribute_modifiers".into(),
+                with_tooltip("modifiers", nbt::Value::List(modifiers)),
+            );
+        }
+
+        if self.potion.is_some() || !self.custom_effects.is_empty() || self.potion_colour.is_some()
+        {
+            let mut contents = nbt::Map::new();
+            if let Some(potion) = self.potion {
+                contents.insert("potion".into(), nbt::Value::String(potion.name()));
+            }
+            if let Some(colour) = self.potion_colour {
+                contents.insert("custom_color".into(), nbt::Value::Int(colour as i32));
+            }
+            if !self.custom_effects.is_empty() {
+                let effects = self
+                    .custom_effects
+                    .iter()
+                    .map(|effect| effect.to_nbt_value(data_version))
+                    .collect();
+                contents.insert("custom_effects".into(), nbt::Value::List(effects));
+            }
+            components.insert(
+                "minecraft:potion_contents".into(),
+                nbt::Value::Compound(contents),
+            );
+        }
+        if !self.stew_effects.is_empty() {
+            let effects = self
+                .stew_effects
+                .iter()
+                .map(|(effect, duration)| {
+                    stew_effect_to_nbt_value(*effect, *duration, data_version)
+                })
+                .collect();
+            components.insert(
+                "minecraft:suspicious_stew_effects".into(),
+                nbt::Value::List(effects),
+            );
+        }
+
+        let raw = |value: nbt::Value| {
+            let mut compound = nbt::Map::new();
+            compound.insert("raw".into(), value);
+            nbt::Value::Compound(compound)
+        };
+        if self.kind == ItemKind::WrittenBook {
+            let pages = self.pages.iter().map(|page| raw(text(page))).collect();
+            let mut content = nbt::Map::new();
+            content.insert("pages".into(), nbt::Value::List(pages));
+            let title = self.title.clone().unwrap_or_default();
+            content.insert("title".into(), raw(nbt::Value::String(title)));
+            let author = self.author.clone().unwrap_or_default();
+            content.insert("author".into(), nbt::Value::String(author));
+            if let Some(generation) = self.generation {
+                content.insert("generation".into(), nbt::Value::Int(generation.into()));
+            }
+            components.insert(
+                "minecraft:written_book_content".into(),
+                nbt::Value::Compound(content),
+            );
+        } else if !self.pages.is_empty() {
+            let pages = self
+                .pages
+                .iter()
+                .map(|page| raw(nbt::Value::String(plain_text(page))))
+                .collect();
+            let mut content = nbt::Map::new();
+            content.insert("pages".into(), nbt::Value::List(pages));
+            components.insert(
+                "minecraft:writable_book_content".into(),
+                nbt::Value::Compound(content),
+            );
+        }
+    }
+
+    /// Adds the data held in the "tag" compound of the item to `tag`, in the
+    /// format of the given Minecraft version.
+    fn write_tag(&self, tag: &mut nbt::Map<String, nbt::Value>, data_version: McVersion) {
+        let pre_flattening = data_version < McVersion::from_str("17w47a").unwrap();
+
+        let mut display = match tag.remove("display") {
+            Some(nbt::Value::Compound(display)) => display,
+            _ => nbt::Map::new(),
+        };
+        if let Some(name) = &self.custom_name {
+            let name = if pre_flattening {
+                plain_text(name)
+            } else {
+                name.clone()
+            };
+            display.insert("Name".into(), nbt::Value::String(name));
+        }
+        if !self.lore.is_empty() {
+            let json_lore = data_version >= McVersion::from_str("18w43a").unwrap();
+            let lore = self
+                .lore
+                .iter()
+                .map(|line| {
+                    nbt::Value::String(if json_lore {
+                        line.clone()
+                    } else {
+                        plain_text(line)
+                    })
+                })
+                .collect();
+            display.insert("Lore".into(), nbt::Value::List(lore));
+        }
+        if let Some(colour) = self.dye_colour {
+            display.insert("color".into(), nbt::Value::Int(colour as i32));
+        }
+        if !display.is_empty() {
+            tag.insert("display".into(), nbt::Value::Compound(display));
+        }
+
+        if !self.enchantments.is_empty() || !self.unknown_enchantments.is_empty() {
+            let key = if pre_flattening {
+                "ench"
+            } else {
+                "Enchantments"
+            };
+            tag.insert(
+                key.into(),
+                enchantments_to_nbt_list(
+                    &self.enchantments,
+                    &self.unknown_enchantments,
+                    data_version,
+                ),
+            );
+        }
+        if !self.stored_enchantments.is_empty() || !self.unknown_stored_enchantments.is_empty() {
+            tag.insert(
+                "StoredEnchantments".into(),
+                enchantments_to_nbt_list(
+                    &self.stored_enchantments,
+                    &self.unknown_stored_enchantments,
+                    data_version,
+                ),
+            );
+        }
+        if self.unbreakable {
+            tag.insert("Unbreakable".into(), nbt::Value::Byte(1));
+        }
+        if let Some(repair_cost) = self.repair_cost {
+            tag.insert("RepairCost".into(), nbt::Value::Int(repair_cost));
+        }
+        if !self.attribute_modifiers.is_empty() {
+            let modifiers = self
+                .attribute_modifiers
+                .iter()
+                .map(|modifier| modifier.to_nbt_value(data_version))
+                .collect();
+            tag.insert("AttributeModifiers".into(), nbt::Value::List(modifiers));
+        }
+
+        // Effects got named ids and snake case tag names with 1.20.2
+        let named_effects = data_version >= McVersion::from_str("23w31a").unwrap();
+        if let Some(potion) = self.potion {
+            tag.insert("Potion".into(), nbt::Value::String(potion.name()));
+        }
+        if !self.custom_effects.is_empty() {
+            let key = if named_effects {
+                "custom_potion_effects"
+            } else {
+                "CustomPotionEffects"
+            };
+            let effects = self
+                .custom_effects
+                .iter()
+                .map(|effect| effect.to_nbt_value(data_version))
+                .collect();
+            tag.insert(key.into(), nbt::Value::List(effects));
+        }
+        if let Some(colour) = self.potion_colour {
+            tag.insert("CustomPotionColor".into(), nbt::Value::Int(colour as i32));
+        }
+        if !self.stew_effects.is_empty() {
+            let key = if named_effects { "effects" } else { "Effects" };
+            let effects = self
+                .stew_effects
+                .iter()
+                .map(|(effect, duration)| {
+                    stew_effect_to_nbt_value(*effect, *duration, data_version)
+                })
+                .collect();
+            tag.insert(key.into(), nbt::Value::List(effects));
+        }
+
+        if !self.pages.is_empty() {
+            let written = self.kind == ItemKind::WrittenBook;
+            let pages = self
+                .pages
+                .iter()
+                .map(|page| {
+                    nbt::Value::String(if written {
+                        page.clone()
+                    } else {
+                        plain_text(page)
+                    })
+                })
+                .collect();
+            tag.insert("pages".into(), nbt::Value::List(pages));
+        }
+        if let Some(title) = &self.title {
+            tag.insert("title".into(), nbt::Value::String(title.clone()));
+        }
+        if let Some(author) = &self.author {
+            tag.insert("author".into(), nbt::Value::String(author.clone()));
+        }
+        if let Some(generation) = self.generation {
+            tag.insert("generation".into(), nbt::Value::Int(generation.into()));
+        }
+    }
+}
+
+/// Returns true for versions storing item data as data components, i.e. 1.20.5
+/// and later, rather than in the "tag" compound.
+pub(crate) fn has_components(data_version: McVersion) -> bool {
+    data_version >= McVersion::from_str("1.20.5").unwrap()
+}
+
+/// Returns true for the namespaced keys of data components, as opposed to the
+/// tags of the "tag" compound used before 1.20.5.
+fn is_component(key: &str) -> bool {
+    key.contains(':')
+}
+
+/// Whether a written book is the original or a copy, and of what.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum BookGeneration {
+    Original,
+    CopyOfOriginal,
+    CopyOfCopy,
+    Tattered,
+}
+
+impl From<i32> for BookGeneration {
+    fn from(generation: i32) -> Self {
+        match generation {
+            0 => BookGeneration::Original,
+            1 => BookGeneration::CopyOfOriginal,
+            2 => BookGeneration::CopyOfCopy,
+            _ => BookGeneration::Tattered,
+        }
+    }
+}
+
+impl From<BookGeneration> for i32 {
+    fn from(generation: BookGeneration) -> i32 {
+        match generation {
+            BookGeneration::Original => 0,
+            BookGeneration::CopyOfOriginal => 1,
+            BookGeneration::CopyOfCopy => 2,
+            BookGeneration::Tattered => 3,
+        }
+    }
+}
 
-        let kind = match id.as_str() {
+/// Sets the level of `enchantment` in `enchantments`, adding it if not present.
+fn set_enchantment(
+    enchantments: &mut Vec<(Enchantment, i16)>,
+    enchantment: Enchantment,
+    level: i16,
+) {
+    match enchantments
+        .iter_mut()
+        .find(|(existing, _)| *existing == enchantment)
+    {
+        Some((_, existing_level)) => *existing_level = level,
+        None => enchantments.push((enchantment, level)),
+    }
+}
+
+/// Reads a list of enchantment compounds, with either the numeric ids used
+/// before the flattening or the named ids used after it.
+///
+/// Returns the known enchantments, and the ids and levels of unknown ones,
+/// with numeric ids as decimal strings.
+fn enchantments_from_nbt_list(list: &[nbt::Value]) -> EnchantmentLevels {
+    let levels = list
+        .iter()
+        .filter_map(|value| {
+            let id = match nbt_value_lookup(value, "id") {
+                Some(nbt::Value::Short(id)) => id.to_string(),
+                Some(nbt::Value::Int(id)) => id.to_string(),
+                Some(nbt::Value::String(id)) => id,
+                _ => {
+                    eprintln!("[warning] Skipping enchantment without id: {:?}", value);
+                    return None;
+                }
+            };
+            let level = match nbt_value_lookup(value, "lvl") {
+                Some(nbt::Value::Short(level)) => level,
+                Some(nbt::Value::Int(level)) => level as i16,
+                _ => 1,
+            };
+            Some((id, level))
+        })
+        .collect();
+    known_enchantments(levels)
+}
+
+/// Reads an enchantments component, with or without the "levels" compound
+/// used before 1.21.5.
+///
+/// Returns the known enchantments, and the ids and levels of unknown ones.
+fn enchantments_from_component(value: &nbt::Value) -> EnchantmentLevels {
+    let levels = match nbt_value_lookup(value, "levels") {
+        Some(nbt::Value::Compound(levels)) => levels,
+        _ => match value {
+            nbt::Value::Compound(levels) => levels.clone(),
+            _ => nbt::Map::new(),
+        },
+    };
+    // Compounds have no order, so enchantments are ordered by id
+    let mut levels: Vec<(String, i16)> = levels
+        .into_iter()
+        .filter_map(|(id, level)| match level {
+            nbt::Value::Int(level) => Some((id, level as i16)),
+            _ => None,
+        })
+        .collect();
+    levels.sort();
+    known_enchantments(levels)
+}
+
+/// Known enchantments, and the ids and levels of unknown ones.
+type EnchantmentLevels = (Vec<(Enchantment, i16)>, Vec<(String, i16)>);
+
+/// Splits enchantment ids and levels into known enchantments and unknown ones.
+fn known_enchantments(levels: Vec<(String, i16)>) -> EnchantmentLevels {
+    let mut known = Vec::new();
+    let mut unknown = Vec::new();
+    for (id, level) in levels {
+        let enchantment = match id.parse::<i16>() {
+            Ok(legacy_id) => Enchantment::from_legacy_id(legacy_id),
+            Err(_) => Enchantment::from_name(&id),
+        };
+        match enchantment {
+            Some(enchantment) => known.push((enchantment, level)),
+            None => unknown.push((id, level)),
+        }
+    }
+    (known, unknown)
+}
+
+/// Generates the list of enchantment compounds for the given Minecraft version.
+///
+/// Enchantments that do not exist in that version, and unknown enchantments
+/// with ids of the wrong kind (numeric or named), are skipped, with a warning.
+fn enchantments_to_nbt_list(
+    enchantments: &[(Enchantment, i16)],
+    unknown_enchantments: &[(String, i16)],
+    data_version: McVersion,
+) -> nbt::Value {
+    let pre_flattening = data_version < McVersion::from_str("17w47a").unwrap();
+    let known = enchantments.iter().filter_map(|(enchantment, level)| {
+        let id = if pre_flattening {
+            match enchantment.legacy_id() {
+                Some(id) => nbt::Value::Short(id),
+                None => {
+                    eprintln!(
+                        "[warning] Skipping {:?}, which does not exist in Minecraft {}",
+                        enchantment, data_version
+                    );
+                    return None;
+                }
+            }
+        } else {
+            nbt::Value::String(enchantment.name(data_version))
+        };
+        Some((id, *level))
+    });
+    let unknown = unknown_enchantments.iter().filter_map(|(id, level)| {
+        let id = match (id.parse::<i16>(), pre_flattening) {
+            (Ok(legacy_id), true) => nbt::Value::Short(legacy_id),
+            (Err(_), false) => nbt::Value::String(id.clone()),
+            _ => {
+                eprintln!(
+                    "[warning] Skipping unknown enchantment {}, which cannot be written for Minecraft {}",
+                    id, data_version
+                );
+                return None;
+            }
+        };
+        Some((id, *level))
+    });
+    let list = known
+        .chain(unknown)
+        .map(|(id, level)| {
+            let mut compound = nbt::Map::new();
+            compound.insert("id".into(), id);
+            compound.insert("lvl".into(), nbt::Value::Short(level));
+            nbt::Value::Compound(compound)
+        })
+        .collect();
+    nbt::Value::List(list)
+}
+
+/// Generates the compound of enchantment levels by id, as used in the
+/// enchantment components from 1.20.5.
+///
+/// Unknown enchantments with numeric ids are skipped, with a warning.
+fn enchantment_levels(
+    enchantments: &[(Enchantment, i16)],
+    unknown_enchantments: &[(String, i16)],
+    data_version: McVersion,
+) -> nbt::Value {
+    let mut levels = nbt::Map::new();
+    for (enchantment, level) in enchantments {
+        levels.insert(
+            enchantment.name(data_version),
+            nbt::Value::Int((*level).into()),
+        );
+    }
+    for (id, level) in unknown_enchantments {
+        if id.parse::<i16>().is_ok() {
+            eprintln!(
+                "[warning] Skipping unknown enchantment {}, which cannot be written for Minecraft {}",
+                id, data_version
+            );
+            continue;
+        }
+        levels.insert(id.clone(), nbt::Value::Int((*level).into()));
+    }
+    nbt::Value::Compound(levels)
+}
+
+/// Reads a suspicious stew effect compound, of any version.
+///
+/// Unknown effects are skipped, with a warning.
+fn stew_effect_from_nbt_value(value: &nbt::Value) -> Option<(StatusEffect, i32)> {
+    let effect = match nbt_value_lookup(value, "EffectId").or_else(|| nbt_value_lookup(value, "id"))
+    {
+        Some(nbt::Value::Byte(id)) => StatusEffect::from_id((id as u8).into()),
+        Some(nbt::Value::Int(id)) => StatusEffect::from_id(id),
+        Some(nbt::Value::String(id)) => StatusEffect::from_name(&id),
+        _ => None,
+    };
+    // Effects of suspicious stew last 8 seconds, unless given otherwise
+    let duration = nbt_value_lookup_int(value, "EffectDuration")
+        .or_else(|| nbt_value_lookup_int(value, "duration"))
+        .unwrap_or(160);
+    if effect.is_none() {
+        eprintln!("[warning] Skipping unknown status effect: {:?}", value);
+    }
+    Some((effect?, duration))
+}
+
+/// Generates a suspicious stew effect compound for the given Minecraft version.
+fn stew_effect_to_nbt_value(
+    effect: StatusEffect,
+    duration: i32,
+    data_version: McVersion,
+) -> nbt::Value {
+    let mut compound = nbt::Map::new();
+    // Effects got named ids and snake case tag names with 1.20.2
+    if data_version >= McVersion::from_str("23w31a").unwrap() {
+        let id = format!("minecraft:{}", effect.name());
+        compound.insert("id".into(), nbt::Value::String(id));
+        compound.insert("duration".into(), nbt::Value::Int(duration));
+    } else {
+        let id = i32::from(effect) as i8;
+        compound.insert("EffectId".into(), nbt::Value::Byte(id));
+        compound.insert("EffectDuration".into(), nbt::Value::Int(duration));
+    }
+    nbt::Value::Compound(compound)
+}
+
+/// A change to an attribute (e.g. attack damage) of whoever holds or wears the item.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AttributeModifier {
+    /// Attribute to change, e.g. "generic.attack_damage".
+    pub attribute: String,
+    /// Name of the modifier. From 1.21, modifiers have a namespaced id instead
+    /// of a name and UUID, which is the name where namespaced, and made from
+    /// the UUID otherwise.
+    pub name: String,
+    pub amount: f64,
+    pub operation: AttributeOperation,
+    /// Tells the modifier apart from other modifiers of the same attribute.
+    pub uuid: u128,
+    /// Slot the item must be in for the modifier to apply (e.g. "mainhand"),
+    /// or `None` for any slot.
+    pub slot: Option<String>,
+}
+
+impl AttributeModifier {
+    /// Reads an attribute modifier compound, with either the camel case
+    /// attribute names and split UUIDs used before 1.16, or those used after.
+    ///
+    /// Returns `None` if the attribute, amount or operation is missing.
+    fn from_nbt_value(value: &nbt::Value) -> Option<Self> {
+        let attribute = nbt_value_lookup_string(value, "AttributeName")?;
+        let uuid = match nbt_value_lookup_int_array(value, "UUID") {
+            Some(parts) if parts.len() == 4 => parts
+                .iter()
+                .fold(0, |uuid, part| uuid << 32 | (*part as u32 as u128)),
+            _ => {
+                let most = nbt_value_lookup_long(value, "UUIDMost").unwrap_or(0) as u64;
+                let least = nbt_value_lookup_long(value, "UUIDLeast").unwrap_or(0) as u64;
+                (most as u128) << 64 | least as u128
+            }
+        };
+        Some(Self {
+            attribute: snake_case_attribute(&attribute),
+            name: nbt_value_lookup_string(value, "Name").unwrap_or_default(),
+            amount: nbt_value_lookup_double(value, "Amount")?,
+            operation: match nbt_value_lookup_int(value, "Operation")? {
+                0 => AttributeOperation::Add,
+                1 => AttributeOperation::AddMultipliedBase,
+                2 => AttributeOperation::AddMultipliedTotal,
+                _ => return None,
+            },
+            uuid,
+            slot: nbt_value_lookup_string(value, "Slot"),
+        })
+    }
+
+    /// Reads an attribute modifier of an attribute modifiers component, as used
+    /// from 1.20.5.
+    ///
+    /// Returns `None` if the attribute, amount or operation is missing.
+    fn from_component_value(value: &nbt::Value) -> Option<Self> {
+        let attribute = nbt_value_lookup_string(value, "type")?;
+        let (name, uuid) = match nbt_value_lookup_string(value, "id") {
+            Some(id) => {
+                let uuid = uuid_from_id(&id).unwrap_or(0);
+                (id, uuid)
+            }
+            None => (
+                nbt_value_lookup_string(value, "name").unwrap_or_default(),
+                nbt_value_lookup_int_array(value, "uuid")
+                    .filter(|parts| parts.len() == 4)
+                    .map(|parts| {
+                        parts
+                            .iter()
+                            .fold(0, |uuid, part| uuid << 32 | (*part as u32 as u128))
+                    })
+                    .unwrap_or(0),
+            ),
+        };
+        Some(Self {
+            attribute: prefixed_attribute(attribute.trim_start_matches("minecraft:")),
+            name,
+            amount: nbt_value_lookup_double(value, "amount")?,
+            operation: match nbt_value_lookup_string(value, "operation")?.as_str() {
+                "add_value" => AttributeOperation::Add,
+                "add_multiplied_base" => AttributeOperation::AddMultipliedBase,
+                "add_multiplied_total" => AttributeOperation::AddMultipliedTotal,
+                _ => return None,
+            },
+            uuid,
+            slot: nbt_value_lookup_string(value, "slot").filter(|slot| slot != "any"),
+        })
+    }
+
+    /// Generates the attribute modifier of an attribute modifiers component,
+    /// for the given Minecraft version (1.20.5 or later).
+    fn to_component_value(&self, data_version: McVersion) -> nbt::Value {
+        // Attributes lost their "generic." (or other) prefix with 1.21.2
+        let attribute = if data_version < McVersion::from_str("1.21.2").unwrap() {
+            self.attribute.as_str()
+        } else {
+            self.attribute
+                .split_once('.')
+                .map_or(self.attribute.as_str(), |(_, attribute)| attribute)
+        };
+        let operation = match self.operation {
+            AttributeOperation::Add => "add_value",
+            AttributeOperation::AddMultipliedBase => "add_multiplied_base",
+            AttributeOperation::AddMultipliedTotal => "add_multiplied_total",
+        };
+
+        let mut compound = nbt::Map::new();
+        compound.insert(
+            "type".into(),
+            nbt::Value::String(format!("minecraft:{}", attribute)),
+        );
+        compound.insert("amount".into(), nbt::Value::Double(self.amount));
+        compound.insert("operation".into(), nbt::Value::String(operation.into()));
+        if data_version < McVersion::from_str("1.21").unwrap() {
+            let parts = (0..4)
+                .rev()
+                .map(|part| (self.uuid >> (32 * part)) as u32 as i32)
+                .collect();
+            compound.insert("uuid".into(), nbt::Value::IntArray(parts));
+            compound.insert("name".into(), nbt::Value::String(self.name.clone()));
+        } else {
+            let id = if self.name.contains(':') {
+                self.name.clone()
+            } else {
+                format!("minecraft:{}", uuid_string(self.uuid))
+            };
+            compound.insert("id".into(), nbt::Value::String(id));
+        }
+        if let Some(slot) = &self.slot {
+            compound.insert("slot".into(), nbt::Value::String(slot.clone()));
+        }
+        nbt::Value::Compound(compound)
+    }
+
+    /// Generates the attribute modifier compound for the given Minecraft version.
+    fn to_nbt_value(&self, data_version: McVersion) -> nbt::Value {
+        let attribute = if data_version < McVersion::from_str("20w17a").unwrap() {
+            camel_case_attribute(&self.attribute)
+        } else {
+            format!("minecraft:{}", self.attribute)
+        };
+        let operation = match self.operation {
+            AttributeOperation::Add => 0,
+            AttributeOperation::AddMultipliedBase => 1,
+            AttributeOperation::AddMultipliedTotal => 2,
+        };
+
+        let mut compound = nbt::Map::new();
+        compound.insert("AttributeName".into(), nbt::Value::String(attribute));
+        compound.insert("Name".into(), nbt::Value::String(self.name.clone()));
+        compound.insert("Amount".into(), nbt::Value::Double(self.amount));
+        compound.insert("Operation".into(), nbt::Value::Int(operation));
+        // UUIDs are stored as four ints from 20w12a
+        if data_version < McVersion::from_str("20w12a").unwrap() {
+            let most = (self.uuid >> 64) as u64 as i64;
+            let least = self.uuid as u64 as i64;
+            compound.insert("UUIDMost".into(), nbt::Value::Long(most));
+            compound.insert("UUIDLeast".into(), nbt::Value::Long(least));
+        } else {
+            let parts = (0..4)
+                .rev()
+                .map(|part| (self.uuid >> (32 * part)) as u32 as i32)
+                .collect();
+            compound.insert("UUID".into(), nbt::Value::IntArray(parts));
+        }
+        if let Some(slot) = &self.slot {
+            compound.insert("Slot".into(), nbt::Value::String(slot.clone()));
+        }
+        nbt::Value::Compound(compound)
+    }
+}
+
+/// How the amount of an attribute modifier is applied to the attribute.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AttributeOperation {
+    /// The amount is added to the base value.
+    Add,
+    /// The base value is multiplied by one plus the amount.
+    AddMultipliedBase,
+    /// The final value is multiplied by one plus the amount.
+    AddMultipliedTotal,
+}
+
+/// Attribute name without namespace in the snake case used from 20w17a,
+/// e.g. "generic.attack_damage" for "generic.attackDamage".
+fn snake_case_attribute(name: &str) -> String {
+    let mut attribute = String::with_capacity(name.len());
+    for c in name.trim_start_matches("minecraft:").chars() {
+        if c.is_ascii_uppercase() {
+            attribute.push('_');
+        }
+        attribute.push(c.to_ascii_lowercase());
+    }
+    attribute
+}
+
+/// Attribute name with the prefix dropped with 1.21.2 added back,
+/// e.g. "generic.attack_damage" for "attack_damage".
+fn prefixed_attribute(name: &str) -> String {
+    if name.contains('.') {
+        return name.to_string();
+    }
+    let prefix = match name {
+        "block_break_speed"
+        | "block_interaction_range"
+        | "entity_interaction_range"
+        | "mining_efficiency"
+        | "sneaking_speed"
+        | "submerged_mining_speed"
+        | "sweeping_damage_ratio" => "player",
+        "spawn_reinforcements" => "zombie",
+        _ => "generic",
+    };
+    format!("{}.{}", prefix, name)
+}
+
+/// UUID in its usual hyphenated form, e.g. "01234567-89ab-cdef-fedc-ba9876543210".
+fn uuid_string(uuid: u128) -> String {
+    let hex = format!("{:032x}", uuid);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/// UUID of a namespaced id made from a UUID, as by [`uuid_string`].
+fn uuid_from_id(id: &str) -> Option<u128> {
+    let hex: String = id.rsplit(':').next()?.split('-').collect();
+    if hex.len() != 32 {
+        return None;
+    }
+    u128::from_str_radix(&hex, 16).ok()
+}
+
+/// Attribute name in the camel case used before 20w17a,
+/// e.g. "generic.attackDamage" for "generic.attack_damage".
+fn camel_case_attribute(attribute: &str) -> String {
+    let mut name = String::with_capacity(attribute.len());
+    let mut capitalise = false;
+    for c in attribute.chars() {
+        match c {
+            '_' => capitalise = true,
+            c if capitalise => {
+                name.push(c.to_ascii_uppercase());
+                capitalise = false;
+            }
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+impl ItemKind {
+    /// Kind of a pre-flattening item with the given id and damage value.
+    fn from_legacy_id(id: &str, damage: i16) -> Self {
+        match id {
             "minecraft:acacia_boat" => ItemKind::Boat(WoodMaterial::Acacia),
             "minecraft:acacia_door" => ItemKind::Door(DoorMaterial::Acacia),
             "minecraft:acacia_fence" => ItemKind::Fence(FenceMaterial::Acacia),
@@ -140,13 +1575,15 @@ impl Item {
                 _ => ItemKind::Unknown,
             },
             "minecraft:command_block" => ItemKind::CommandBlock,
-            "minecraft:command_block_minecraft" => {
+            "minecraft:command_block_minecart" => {
                 ItemKind::Minecart(Some(MinecartContents::CommandBlock))
             }
             "minecraft:comparator" => ItemKind::RedstoneComparator,
             "minecraft:compass" => ItemKind::Compass,
-            "minecraft:concrete" => ItemKind::Concrete(None),
-            "minecraft:concrete_powder" => ItemKind::ConcretePowder(None),
+            "minecraft:concrete" => ItemKind::Concrete(Some(Colour::from(damage as i32))),
+            "minecraft:concrete_powder" => {
+                ItemKind::ConcretePowder(Some(Colour::from(damage as i32)))
+            }
             "minecraft:cooked_beef" => ItemKind::Steak,
             "minecraft:cooked_chicken" => ItemKind::CookedChicken,
             "minecraft:cooked_fish" => ItemKind::Fish, // TODO Damage denotes type of fish
@@ -198,7 +1635,7 @@ impl Item {
                 }
             }
             "minecraft:dragon_egg" => ItemKind::DragonEgg,
-            "minecraft:dragons_breath" => ItemKind::DragonBreath,
+            "minecraft:dragon_breath" => ItemKind::DragonBreath,
             "minecraft:dropper" => ItemKind::Dropper,
             "minecraft:dye" => match damage {
                 0 => ItemKind::InkSac,
@@ -248,7 +1685,7 @@ impl Item {
             "minecraft:fishing_rod" => ItemKind::FishingRod,
             "minecraft:flint" => ItemKind::Flint,
             "minecraft:flint_and_steel" => ItemKind::FlintAndSteel,
-            "minecraft:flower_pod" => ItemKind::FlowerPot,
+            "minecraft:flower_pot" => ItemKind::FlowerPot,
             "minecraft:furnace" => ItemKind::Furnace,
             "minecraft:furnace_minecart" => ItemKind::Minecart(Some(MinecartContents::Furnace)),
             "minecraft:ghast_tear" => ItemKind::GhastTear,
@@ -292,7 +1729,7 @@ impl Item {
                 ItemKind::PressurePlate(PressurePlateMaterial::Iron)
             }
             "minecraft:hopper" => ItemKind::Hopper,
-            "minecrart:hopper_minecart" => ItemKind::Minecart(Some(MinecartContents::Hopper)),
+            "minecraft:hopper_minecart" => ItemKind::Minecart(Some(MinecartContents::Hopper)),
             "minecraft:ice" => ItemKind::Ice,
             "minecraft:iron_axe" => ItemKind::Axe(ToolMaterial::Iron),
             "minecraft:iron_bars" => ItemKind::IronBars,
@@ -354,6 +1791,7 @@ impl Item {
             }
             "minecraft:lime_glazed_terracotta" => ItemKind::GlazedTerracotta(Some(Colour::Lime)),
             "minecraft:lime_shulker_box" => ItemKind::ShulkerBox(Some(Colour::Lime)),
+            "minecraft:lingering_potion" => ItemKind::LingeringPotion,
             "minecraft:lit_pumpkin" => ItemKind::JackOLantern,
             "minecraft:log" => match damage {
                 0 => ItemKind::Log(WoodMaterial::Oak),
@@ -431,6 +1869,7 @@ impl Item {
             },
             "minecraft:porkchop" => ItemKind::Porkchop { cooked: false },
             "minecraft:potato" => ItemKind::Potato,
+            "minecraft:potion" => ItemKind::Potion,
             "minecraft:prismarine" => match damage {
                 0 => ItemKind::Prismarine,
                 1 => ItemKind::DarkPrismarine,
@@ -556,7 +1995,9 @@ impl Item {
             "minecraft:soul_sand" => ItemKind::SoulSand,
             "minecraft:spawn_egg" => ItemKind::SpawnEgg,
             "minecraft:speckled_melon" => ItemKind::GlisteringMelon,
+            "minecraft:spectral_arrow" => ItemKind::SpectralArrow,
             "minecraft:spider_eye" => ItemKind::SpiderEye,
+            "minecraft:splash_potion" => ItemKind::SplashPotion,
             "minecraft:sponge" => match damage {
                 0 => ItemKind::Sponge,
                 1 => ItemKind::WetSponge,
@@ -606,6 +2047,7 @@ impl Item {
             "minecraft:stone_slab" => match damage {
                 0 => ItemKind::Slab(SlabMaterial::SmoothStone),
                 1 => ItemKind::Slab(SlabMaterial::Sandstone),
+                2 => ItemKind::Slab(SlabMaterial::PetrifiedOak),
                 3 => ItemKind::Slab(SlabMaterial::Cobblestone),
                 4 => ItemKind::Slab(SlabMaterial::Brick),
                 5 => ItemKind::Slab(SlabMaterial::StoneBrick),
@@ -621,6 +2063,7 @@ impl Item {
             "minecraft:structure_void" => ItemKind::StructureVoid,
             "minecraft:sugar" => ItemKind::Sugar,
             "minecraft:tallgrass" => ItemKind::Grass,
+            "minecraft:tipped_arrow" => ItemKind::TippedArrow,
             "minecraft:tnt" => ItemKind::TNT,
             "minecraft:tnt_minecart" => ItemKind::Minecart(Some(MinecartContents::TNT)),
             "minecraft:torch" => ItemKind::Torch,
@@ -637,6 +2080,7 @@ impl Item {
             "minecraft:white_glazed_terracotta" => ItemKind::GlazedTerracotta(Some(Colour::White)),
             "minecraft:white_shulker_box" => ItemKind::ShulkerBox(Some(Colour::White)),
             "minecraft:wooden_axe" => ItemKind::Axe(ToolMaterial::Wood),
+            "minecraft:wooden_button" => ItemKind::Button(ButtonMaterial::Oak),
             "minecraft:wooden_door" => ItemKind::Door(DoorMaterial::Oak),
             "minecraft:wooden_hoe" => ItemKind::Hoe(ToolMaterial::Wood),
             "minecraft:wooden_pickaxe" => ItemKind::Pickaxe(ToolMaterial::Wood),
@@ -655,6 +2099,7 @@ impl Item {
             },
             "minecraft:wooden_sword" => ItemKind::Sword(ToolMaterial::Wood),
             "minecraft:wool" => ItemKind::Wool(Colour::from(damage as i32)),
+            "minecraft:writable_book" => ItemKind::BookAndQuill,
             "minecraft:written_book" => ItemKind::WrittenBook,
             "minecraft:yellow_flower" => ItemKind::Flower(Flower::Dandelion),
             "minecraft:yellow_glazed_terracotta" => {
@@ -662,26 +2107,959 @@ impl Item {
             }
             "minecraft:yellow_shulker_box" => ItemKind::ShulkerBox(Some(Colour::Yellow)),
             _ => ItemKind::Unknown,
+        }
+    }
+
+    /// Id, without namespace, and damage value of the item before the flattening,
+    /// or `None` if there was no such item.
+    fn legacy_id(&self) -> Option<(String, i16)> {
+        let colour = |colour: &Colour| u8::from(*colour) as i16;
+        let (id, damage) = match self {
+            ItemKind::Unknown | ItemKind::Dye => return None,
+
+            // Tools and armour are named the same before and after the flattening
+            ItemKind::Axe(ToolMaterial::Netherite)
+            | ItemKind::Hoe(ToolMaterial::Netherite)
+            | ItemKind::Pickaxe(ToolMaterial::Netherite)
+            | ItemKind::Shovel(ToolMaterial::Netherite)
+            | ItemKind::Sword(ToolMaterial::Netherite)
+            | ItemKind::Boots(ArmourMaterial::Netherite)
+            | ItemKind::Chestplate(ArmourMaterial::Netherite)
+            | ItemKind::Helmet(ArmourMaterial::Netherite)
+            | ItemKind::Leggings(ArmourMaterial::Netherite) => return None,
+            ItemKind::Axe(_)
+            | ItemKind::Hoe(_)
+            | ItemKind::Pickaxe(_)
+            | ItemKind::Shovel(_)
+            | ItemKind::Sword(_)
+            | ItemKind::Boots(_)
+            | ItemKind::Chestplate(_)
+            | ItemKind::Helmet(_)
+            | ItemKind::Leggings(_) => return Some((self.equipment_name()?, 0)),
+
+            ItemKind::Apple => ("apple", 0),
+            ItemKind::Arrow => ("arrow", 0),
+            ItemKind::ArmorStand => ("armor_stand", 0),
+            ItemKind::BakedPotato => ("baked_potato", 0),
+            ItemKind::Banner => ("banner", 15),
+            ItemKind::Bed(c) => ("bed", colour(c)),
+            ItemKind::Beef | ItemKind::RawBeef => ("beef", 0),
+            ItemKind::Beetroot => ("beetroot", 0),
+            ItemKind::BlazePowder => ("blaze_powder", 0),
+            ItemKind::BlazeRod => ("blaze_rod", 0),
+            ItemKind::Boat(material) => match material {
+                WoodMaterial::Acacia => ("acacia_boat", 0),
+                WoodMaterial::Birch => ("birch_boat", 0),
+                WoodMaterial::DarkOak => ("dark_oak_boat", 0),
+                WoodMaterial::Jungle => ("jungle_boat", 0),
+                WoodMaterial::Oak => ("boat", 0),
+                WoodMaterial::Spruce => ("spruce_boat", 0),
+                WoodMaterial::Crimson | WoodMaterial::Warped => return None,
+            },
+            ItemKind::Bone => ("bone", 0),
+            ItemKind::BoneMeal => ("dye", 15),
+            ItemKind::Book => ("book", 0),
+            ItemKind::BookAndQuill => ("writable_book", 0),
+            ItemKind::BottleOEnchanting => ("experience_bottle", 0),
+            ItemKind::Bow => ("bow", 0),
+            ItemKind::Bowl(contents) => match contents {
+                None => ("bowl", 0),
+                Some(BowlContents::BeetrootSoup) => ("beetroot_soup", 0),
+                Some(BowlContents::MushroomStew) => ("mushroom_stew", 0),
+                Some(BowlContents::RabbitStew) => ("rabbit_stew", 0),
+                Some(BowlContents::SuspiciousStew) => return None,
+            },
+            ItemKind::Bread => ("bread", 0),
+            ItemKind::BrewingStand => ("brewing_stand", 0),
+            ItemKind::Brick => ("brick", 0),
+            ItemKind::Bucket(contents) => match contents {
+                None => ("bucket", 0),
+                Some(BucketContents::Empty) => ("bucket", 0),
+                Some(BucketContents::Lava) => ("lava_bucket", 0),
+                Some(BucketContents::Milk) => ("milk_bucket", 0),
+                Some(BucketContents::Water) => ("water_bucket", 0),
+            },
+            ItemKind::CactusGreen => ("dye", 2),
+            ItemKind::Cake => ("cake", 0),
+            ItemKind::Carrot => ("carrot", 0),
+            ItemKind::CarrotOnAStick => ("carrot_on_a_stick", 0),
+            ItemKind::Cauldron => ("cauldron", 0),
+            ItemKind::Charcoal => ("coal", 1),
+            ItemKind::Chicken | ItemKind::RawChicken => ("chicken", 0),
+            ItemKind::ChorusFruit => ("chorus_fruit", 0),
+            ItemKind::Clay => ("clay_ball", 0),
+            ItemKind::Clock => ("clock", 0),
+            ItemKind::Coal => ("coal", 0),
+            ItemKind::CocoaBeans => ("dye", 3),
+            ItemKind::Compass => ("compass", 0),
+            ItemKind::CookedChicken => ("cooked_chicken", 0),
+            ItemKind::CookedMutton => ("cooked_mutton", 0),
+            ItemKind::CookedRabbit => ("cooked_rabbit", 0),
+            ItemKind::Cookie => ("cookie", 0),
+            ItemKind::CyanDye => ("dye", 6),
+            ItemKind::DandelionYellow => ("dye", 11),
+            ItemKind::Diamond => ("diamond", 0),
+            ItemKind::Door(material) => match material {
+                DoorMaterial::Acacia => ("acacia_door", 0),
+                DoorMaterial::Birch => ("birch_door", 0),
+                DoorMaterial::DarkOak => ("dark_oak_door", 0),
+                DoorMaterial::Iron => ("iron_door", 0),
+                DoorMaterial::Jungle => ("jungle_door", 0),
+                DoorMaterial::Oak => ("wooden_door", 0),
+                DoorMaterial::Spruce => ("spruce_door", 0),
+                DoorMaterial::Crimson | DoorMaterial::Warped => return None,
+            },
+            ItemKind::DragonBreath => ("dragon_breath", 0),
+            ItemKind::Egg => ("egg", 0),
+            ItemKind::Elytra => ("elytra", 0),
+            ItemKind::Emerald => ("emerald", 0),
+            ItemKind::EmptyMap => ("map", 0),
+            ItemKind::EnchantedBook => ("enchanted_book", 0),
+            ItemKind::EnchantedGoldenApple => ("golden_apple", 1),
+            ItemKind::EndCrystal => ("end_crystal", 0),
+            ItemKind::EnderEye => ("ender_eye", 0),
+            ItemKind::EnderPearl => ("ender_pearl", 0),
+            ItemKind::Feather => ("feather", 0),
+            ItemKind::FermentedSpiderEye => ("fermented_spider_eye", 0),
+            ItemKind::FireCharge => ("fire_charge", 0),
+            ItemKind::FireworkRocket | ItemKind::Fireworks => ("fireworks", 0),
+            ItemKind::FireworkStar => ("firework_charge", 0),
+            ItemKind::Fish => ("fish", 0),
+            ItemKind::FishingRod => ("fishing_rod", 0),
+            ItemKind::Flint => ("flint", 0),
+            ItemKind::FlintAndSteel => ("flint_and_steel", 0),
+            ItemKind::FlowerPot => ("flower_pot", 0),
+            ItemKind::GhastTear => ("ghast_tear", 0),
+            ItemKind::GlassBottle => ("glass_bottle", 0),
+            ItemKind::GlisteringMelon => ("speckled_melon", 0),
+            ItemKind::GlowstoneDust => ("glowstone_dust", 0),
+            ItemKind::GoldenApple => ("golden_apple", 0),
+            ItemKind::GoldenCarrot => ("golden_carrot", 0),
+            ItemKind::GrayDye => ("dye", 8),
+            ItemKind::Gunpowder => ("gunpowder", 0),
+            ItemKind::HorseArmor(material) => match material {
+                HorseArmorMaterial::Diamond => ("diamond_horse_armor", 0),
+                HorseArmorMaterial::Gold => ("golden_horse_armor", 0),
+                HorseArmorMaterial::Iron => ("iron_horse_armor", 0),
+            },
+            ItemKind::Ingot(material) => match material {
+                IngotMaterial::Gold => ("gold_ingot", 0),
+                IngotMaterial::Iron => ("iron_ingot", 0),
+                IngotMaterial::Netherite => return None,
+            },
+            ItemKind::InkSac => ("dye", 0),
+            ItemKind::ItemFrame => ("item_frame", 0),
+            ItemKind::KnowledgeBook => ("knowledge_book", 0),
+            ItemKind::LapisLazuli => ("dye", 4),
+            ItemKind::Lead => ("lead", 0),
+            ItemKind::Leather => ("leather", 0),
+            ItemKind::LightBlueDye => ("dye", 12),
+            ItemKind::LightGrayDye => ("dye", 7),
+            ItemKind::LimeDye => ("dye", 10),
+            ItemKind::LingeringPotion => ("lingering_potion", 0),
+            ItemKind::MagentaDye => ("dye", 13),
+            ItemKind::MagmaCream => ("magma_cream", 0),
+            ItemKind::Map => ("filled_map", 0),
+            ItemKind::Melon => ("melon", 0),
+            ItemKind::Minecart(contents) => match contents {
+                None => ("minecart", 0),
+                Some(MinecartContents::Chest) => ("chest_minecart", 0),
+                Some(MinecartContents::CommandBlock) => ("command_block_minecart", 0),
+                Some(MinecartContents::Furnace) => ("furnace_minecart", 0),
+                Some(MinecartContents::Hopper) => ("hopper_minecart", 0),
+                Some(MinecartContents::TNT) => ("tnt_minecart", 0),
+            },
+            ItemKind::MobHead | ItemKind::Scull => ("skull", 0),
+            ItemKind::Mutton | ItemKind::RawMutton => ("mutton", 0),
+            ItemKind::NameTag => ("name_tag", 0),
+            ItemKind::NetherBrick => ("netherbrick", 0),
+            ItemKind::NetherQuartz => ("quartz", 0),
+            ItemKind::NetherStar => ("nether_star", 0),
+            ItemKind::NetherWart => ("nether_wart", 0),
+            ItemKind::Nugget(material) => match material {
+                NuggetMaterial::Gold => ("gold_nugget", 0),
+                NuggetMaterial::Iron => ("iron_nugget", 0),
+            },
+            ItemKind::OrangeDye => ("dye", 14),
+            ItemKind::Painting => ("painting", 0),
+            ItemKind::Paper => ("paper", 0),
+            ItemKind::PinkDye => ("dye", 9),
+            ItemKind::PoppedChorusFruit => ("chorus_fruit_popped", 0),
+            ItemKind::Porkchop { cooked: false } => ("porkchop", 0),
+            ItemKind::Porkchop { cooked: true } => ("cooked_porkchop", 0),
+            ItemKind::Potion => ("potion", 0),
+            ItemKind::Potato => ("potato", 0),
+            ItemKind::PrismarineCrystals => ("prismarine_crystals", 0),
+            ItemKind::PrismarineShard => ("prismarine_shard", 0),
+            ItemKind::PumpkinPie => ("pumpkin_pie", 0),
+            ItemKind::PurpleDye => ("dye", 5),
+            ItemKind::Rabbit | ItemKind::RawRabbit => ("rabbit", 0),
+            ItemKind::RabbitFoot => ("rabbit_foot", 0),
+            ItemKind::RabbitHide => ("rabbit_hide", 0),
+            ItemKind::Record(recording) => match recording {
+                Recording::Blocks => ("record_blocks", 0),
+                Recording::Cat => ("record_cat", 0),
+                Recording::Chirp => ("record_chirp", 0),
+                Recording::Eleven => ("record_11", 0),
+                Recording::Far => ("record_far", 0),
+                Recording::Mall => ("record_mall", 0),
+                Recording::Mellohi => ("record_mellohi", 0),
+                Recording::Stal => ("record_stal", 0),
+                Recording::Strad => ("record_strad", 0),
+                Recording::Thirteen => ("record_13", 0),
+                Recording::Ward => ("record_ward", 0),
+                Recording::Wait => ("record_wait", 0),
+            },
+            ItemKind::Redstone => ("redstone", 0),
+            ItemKind::RedstoneComparator => ("comparator", 0),
+            ItemKind::RedstoneRepeater => ("repeater", 0),
+            ItemKind::RoseRed => ("dye", 1),
+            ItemKind::RottenFlesh => ("rotten_flesh", 0),
+            ItemKind::Saddle => ("saddle", 0),
+            ItemKind::Seeds(material) => match material {
+                SeedMaterial::Beetroot => ("beetroot_seeds", 0),
+                SeedMaterial::Melon => ("melon_seeds", 0),
+                SeedMaterial::Pumpkin => ("pumpkin_seeds", 0),
+                SeedMaterial::Wheat => ("wheat_seeds", 0),
+            },
+            ItemKind::Shears => ("shears", 0),
+            ItemKind::Shield => ("shield", 0),
+            ItemKind::ShulkerShell => ("shulker_shell", 0),
+            ItemKind::Sign(WoodMaterial::Oak) => ("sign", 0),
+            ItemKind::Sign(_) => return None,
+            ItemKind::Slimeball => ("slime_ball", 0),
+            ItemKind::Snowball => ("snowball", 0),
+            ItemKind::SpawnEgg => ("spawn_egg", 0),
+            ItemKind::SpectralArrow => ("spectral_arrow", 0),
+            ItemKind::SpiderEye => ("spider_eye", 0),
+            ItemKind::SplashPotion => ("splash_potion", 0),
+            ItemKind::Steak => ("cooked_beef", 0),
+            ItemKind::Stick => ("stick", 0),
+            ItemKind::String => ("string", 0),
+            ItemKind::Sugar => ("sugar", 0),
+            ItemKind::SugarCane => ("reeds", 0),
+            ItemKind::TippedArrow => ("tipped_arrow", 0),
+            ItemKind::TotemOfUndying => ("totem_of_undying", 0),
+            ItemKind::Wheat => ("wheat", 0),
+            ItemKind::WrittenBook => ("written_book", 0),
+
+            ItemKind::AcaciaLeaves => ("leaves2", 0),
+            ItemKind::ActivatorRail => ("activator_rail", 0),
+            ItemKind::Andesite => ("stone", 5),
+            ItemKind::Anvil(damage) => match damage {
+                AnvilDamage::Intact => ("anvil", 0),
+                AnvilDamage::SlightlyDamaged => ("anvil", 1),
+                AnvilDamage::VeryDamaged => ("anvil", 2),
+            },
+            ItemKind::Barrier => ("barrier", 0),
+            ItemKind::Beacon => ("beacon", 0),
+            ItemKind::Bedrock => ("bedrock", 0),
+            ItemKind::BirchLeaves => ("leaves", 2),
+            ItemKind::BlockOfCoal => ("coal_block", 0),
+            ItemKind::BlockOfDiamond => ("diamond_block", 0),
+            ItemKind::BlockOfEmerald => ("emerald_block", 0),
+            ItemKind::BlockOfGold => ("gold_block", 0),
+            ItemKind::BlockOfIron => ("iron_block", 0),
+            ItemKind::BlockOfQuartz => ("quartz_block", 0),
+            ItemKind::BlockOfRedstone => ("redstone_block", 0),
+            ItemKind::BoneBlock => ("bone_block", 0),
+            ItemKind::Bookshelf => ("bookshelf", 0),
+            ItemKind::BrickBlock => ("brick_block", 0),
+            ItemKind::BrownMushroom => ("brown_mushroom", 0),
+            ItemKind::BrownMushroomBlock => ("brown_mushroom_block", 0),
+            ItemKind::Button(ButtonMaterial::Oak) => ("wooden_button", 0),
+            ItemKind::Button(ButtonMaterial::Stone) => ("stone_button", 0),
+            ItemKind::Button(_) => return None,
+            ItemKind::Cactus => ("cactus", 0),
+            ItemKind::Carpet(c) => ("carpet", colour(c)),
+            ItemKind::ChainCommandBlock => ("chain_command_block", 0),
+            ItemKind::Chest => ("chest", 0),
+            ItemKind::ChiseledQuartzBlock => ("quartz_block", 1),
+            ItemKind::ChiseledRedSandstone => ("red_sandstone", 1),
+            ItemKind::ChiseledSandstone => ("sandstone", 1),
+            ItemKind::ChiseledStoneBricks => ("stonebrick", 3),
+            ItemKind::ChorusFlower => ("chorus_flower", 0),
+            ItemKind::ChorusPlant => ("chorus_plant", 0),
+            ItemKind::ClayBlock => ("clay", 0),
+            ItemKind::CoalOre => ("coal_ore", 0),
+            ItemKind::CoarseDirt => ("dirt", 1),
+            ItemKind::Cobblestone => ("cobblestone", 0),
+            ItemKind::CobblestoneWall => ("cobblestone_wall", 0),
+            ItemKind::Cobweb => ("web", 0),
+            ItemKind::CommandBlock => ("command_block", 0),
+            ItemKind::Concrete(c) => ("concrete", c.as_ref().map_or(0, colour)),
+            ItemKind::ConcretePowder(c) => ("concrete_powder", c.as_ref().map_or(0, colour)),
+            ItemKind::CrackedStoneBricks => ("stonebrick", 2),
+            ItemKind::CraftingTable => ("crafting_table", 0),
+            ItemKind::CutRedSandstone => ("red_sandstone", 2),
+            ItemKind::CutSandstone => ("sandstone", 2),
+            ItemKind::DarkOakLeaves => ("leaves2", 1),
+            ItemKind::DarkPrismarine => ("prismarine", 1),
+            ItemKind::DaylightDetector => ("daylight_detector", 0),
+            ItemKind::DeadBush => ("deadbush", 0),
+            ItemKind::DetectorRail => ("detector_rail", 0),
+            ItemKind::DiamondOre => ("diamond_ore", 0),
+            ItemKind::Diorite => ("stone", 3),
+            ItemKind::Dirt => ("dirt", 0),
+            ItemKind::Dispenser => ("dispenser", 0),
+            ItemKind::DoubleTallgrass => ("double_plant", 2),
+            ItemKind::DragonEgg => ("dragon_egg", 0),
+            ItemKind::Dropper => ("dropper", 0),
+            ItemKind::EmeraldOre => ("emerald_ore", 0),
+            ItemKind::EnchantingTable => ("enchanting_table", 0),
+            ItemKind::EndBricks => ("end_bricks", 0),
+            ItemKind::EnderChest => ("ender_chest", 0),
+            ItemKind::EndPortalFrame => ("end_portal_frame", 0),
+            ItemKind::EndRod => ("end_rod", 0),
+            ItemKind::EndStone => ("end_stone", 0),
+            ItemKind::Farmland => ("farmland", 0),
+            ItemKind::Fence(material) => match material {
+                FenceMaterial::Acacia => ("acacia_fence", 0),
+                FenceMaterial::Birch => ("birch_fence", 0),
+                FenceMaterial::DarkOak => ("dark_oak_fence", 0),
+                FenceMaterial::Jungle => ("jungle_fence", 0),
+                FenceMaterial::NetherBrick => ("nether_brick_fence", 0),
+                FenceMaterial::Oak => ("fence", 0),
+                FenceMaterial::Spruce => ("spruce_fence", 0),
+                FenceMaterial::Crimson | FenceMaterial::Warped => return None,
+            },
+            ItemKind::FenceGate(material) => match material {
+                WoodMaterial::Acacia => ("acacia_fence_gate", 0),
+                WoodMaterial::Birch => ("birch_fence_gate", 0),
+                WoodMaterial::DarkOak => ("dark_oak_fence_gate", 0),
+                WoodMaterial::Jungle => ("jungle_fence_gate", 0),
+                WoodMaterial::Oak => ("fence_gate", 0),
+                WoodMaterial::Spruce => ("spruce_fence_gate", 0),
+                WoodMaterial::Crimson | WoodMaterial::Warped => return None,
+            },
+            ItemKind::Flower(flower) => match flower {
+                Flower::Dandelion => ("yellow_flower", 0),
+                Flower::Poppy => ("red_flower", 0),
+                Flower::BlueOrchid => ("red_flower", 1),
+                Flower::Allium => ("red_flower", 2),
+                Flower::AzureBluet => ("red_flower", 3),
+                Flower::TulipRed => ("red_flower", 4),
+                Flower::TulipOrange => ("red_flower", 5),
+                Flower::TulipWhite => ("red_flower", 6),
+                Flower::TulipPink => ("red_flower", 7),
+                Flower::OxeyeDaisy => ("red_flower", 8),
+                Flower::SunflowerBottom | Flower::SunflowerTop => ("double_plant", 0),
+                Flower::LilacBottom | Flower::LilacTop => ("double_plant", 1),
+                Flower::RoseBushBottom | Flower::RoseBushTop => ("double_plant", 4),
+                Flower::PeonyBottom | Flower::PeonyTop => ("double_plant", 5),
+                Flower::Cornflower | Flower::LilyOfTheValley | Flower::WitherRose => return None,
+            },
+            ItemKind::Furnace => ("furnace", 0),
+            ItemKind::Glass(None) => ("glass", 0),
+            ItemKind::Glass(Some(c)) => ("stained_glass", colour(c)),
+            ItemKind::GlassPane(None) => ("glass_pane", 0),
+            ItemKind::GlassPane(Some(c)) => ("stained_glass_pane", colour(c)),
+            ItemKind::GlazedTerracotta(c) => match c.unwrap_or(Colour::White) {
+                Colour::White => ("white_glazed_terracotta", 0),
+                Colour::Orange => ("orange_glazed_terracotta", 0),
+                Colour::Magenta => ("magenta_glazed_terracotta", 0),
+                Colour::LightBlue => ("light_blue_glazed_terracotta", 0),
+                Colour::Yellow => ("yellow_glazed_terracotta", 0),
+                Colour::Lime => ("lime_glazed_terracotta", 0),
+                Colour::Pink => ("pink_glazed_terracotta", 0),
+                Colour::Gray => ("gray_glazed_terracotta", 0),
+                Colour::LightGray => ("silver_glazed_terracotta", 0),
+                Colour::Cyan => ("cyan_glazed_terracotta", 0),
+                Colour::Purple => ("purple_glazed_terracotta", 0),
+                Colour::Blue => ("blue_glazed_terracotta", 0),
+                Colour::Brown => ("brown_glazed_terracotta", 0),
+                Colour::Green => ("green_glazed_terracotta", 0),
+                Colour::Red => ("red_glazed_terracotta", 0),
+                Colour::Black => ("black_glazed_terracotta", 0),
+            },
+            ItemKind::Glowstone => ("glowstone", 0),
+            ItemKind::GoldOre => ("gold_ore", 0),
+            ItemKind::Granite => ("stone", 1),
+            ItemKind::Grass => ("tallgrass", 1),
+            ItemKind::GrassPath => ("grass_path", 0),
+            ItemKind::GrassBlock => ("grass", 0),
+            ItemKind::Gravel => ("gravel", 0),
+            ItemKind::HayBale => ("hay_block", 0),
+            ItemKind::Hopper => ("hopper", 0),
+            ItemKind::Ice => ("ice", 0),
+            ItemKind::InfestedChiseledStoneBricks => ("monster_egg", 5),
+            ItemKind::InfestedCobblestone => ("monster_egg", 1),
+            ItemKind::InfestedCrackedStoneBricks => ("monster_egg", 4),
+            ItemKind::InfestedMossyStoneBricks => ("monster_egg", 3),
+            ItemKind::InfestedStone => ("monster_egg", 0),
+            ItemKind::InfestedStoneBricks => ("monster_egg", 2),
+            ItemKind::IronBars => ("iron_bars", 0),
+            ItemKind::IronOre => ("iron_ore", 0),
+            ItemKind::JackOLantern => ("lit_pumpkin", 0),
+            ItemKind::JungleLeaves => ("leaves", 3),
+            ItemKind::Jukebox => ("jukebox", 0),
+            ItemKind::Ladder => ("ladder", 0),
+            ItemKind::LapisLazuliBlock => ("lapis_block", 0),
+            ItemKind::LapisLazuliOre => ("lapis_ore", 0),
+            ItemKind::LargeFern => ("double_plant", 3),
+            ItemKind::Lever => ("lever", 0),
+            ItemKind::LilyPad => ("waterlily", 0),
+            ItemKind::Log(material) => match material {
+                WoodMaterial::Oak => ("log", 0),
+                WoodMaterial::Spruce => ("log", 1),
+                WoodMaterial::Birch => ("log", 2),
+                WoodMaterial::Jungle => ("log", 3),
+                WoodMaterial::Acacia => ("log2", 0),
+                WoodMaterial::DarkOak => ("log2", 1),
+                WoodMaterial::Crimson | WoodMaterial::Warped => return None,
+            },
+            ItemKind::Magma => ("magma", 0),
+            ItemKind::MelonBlock => ("melon_block", 0),
+            ItemKind::MobSpawner => ("mob_spawner", 0),
+            ItemKind::MossyCobblestone => ("mossy_cobblestone", 0),
+            ItemKind::MossyCobblestoneWall => ("cobblestone_wall", 1),
+            ItemKind::MossyStoneBricks => ("stonebrick", 1),
+            ItemKind::Mycelium => ("mycelium", 0),
+            ItemKind::NetherBrickBlock => ("nether_brick", 0),
+            ItemKind::Netherrack => ("netherrack", 0),
+            ItemKind::NetherWartBlock => ("nether_wart_block", 0),
+            ItemKind::Noteblock => ("noteblock", 0),
+            ItemKind::OakLeaves => ("leaves", 0),
+            ItemKind::Observer => ("observer", 0),
+            ItemKind::Obsidian => ("obsidian", 0),
+            ItemKind::PackedIce => ("packed_ice", 0),
+            ItemKind::PillarQuartzBlock => ("quartz_block", 2),
+            ItemKind::Piston => ("piston", 0),
+            ItemKind::Planks(material) => match material {
+                WoodMaterial::Oak => ("planks", 0),
+                WoodMaterial::Spruce => ("planks", 1),
+                WoodMaterial::Birch => ("planks", 2),
+                WoodMaterial::Jungle => ("planks", 3),
+                WoodMaterial::Acacia => ("planks", 4),
+                WoodMaterial::DarkOak => ("planks", 5),
+                WoodMaterial::Crimson | WoodMaterial::Warped => return None,
+            },
+            ItemKind::Podzol => ("dirt", 2),
+            ItemKind::PolishedAndesite => ("stone", 6),
+            ItemKind::PolishedDiorite => ("stone", 4),
+            ItemKind::PolishedGranite => ("stone", 2),
+            ItemKind::PoweredRail => ("golden_rail", 0),
+            ItemKind::PressurePlate(material) => match material {
+                PressurePlateMaterial::Gold => ("light_weighted_pressure_plate", 0),
+                PressurePlateMaterial::Iron => ("heavy_weighted_pressure_plate", 0),
+                PressurePlateMaterial::Oak => ("wooden_pressure_plate", 0),
+                PressurePlateMaterial::Stone => ("stone_pressure_plate", 0),
+                _ => return None,
+            },
+            ItemKind::Prismarine => ("prismarine", 0),
+            ItemKind::PrismarineBricks => ("prismarine", 2),
+            ItemKind::Pumpkin => ("pumpkin", 0),
+            ItemKind::PurpurBlock => ("purpur_block", 0),
+            ItemKind::PurpurPillar => ("purpur_pillar", 0),
+            ItemKind::QuartzOre => ("quartz_ore", 0),
+            ItemKind::Rail => ("rail", 0),
+            ItemKind::RedMushroom => ("red_mushroom", 0),
+            ItemKind::RedMushroomBlock => ("red_mushroom_block", 0),
+            ItemKind::RedNetherBrick => ("red_nether_brick", 0),
+            ItemKind::RedSand => ("sand", 1),
+            ItemKind::RedSandstone => ("red_sandstone", 0),
+            ItemKind::RedstoneLamp => ("redstone_lamp", 0),
+            ItemKind::RedstoneOre => ("redstone_ore", 0),
+            ItemKind::RedstoneTorch => ("redstone_torch", 0),
+            ItemKind::RepeatingCommandBlock => ("repeating_command_block", 0),
+            ItemKind::Sand => ("sand", 0),
+            ItemKind::Sandstone => ("sandstone", 0),
+            ItemKind::Sapling(material) => match material {
+                SaplingMaterial::Oak => ("sapling", 0),
+                SaplingMaterial::Spruce => ("sapling", 1),
+                SaplingMaterial::Birch => ("sapling", 2),
+                SaplingMaterial::Jungle => ("sapling", 3),
+                SaplingMaterial::Acacia => ("sapling", 4),
+                SaplingMaterial::DarkOak => ("sapling", 5),
+                SaplingMaterial::Bamboo => return None,
+            },
+            ItemKind::SeaLantern => ("sea_lantern", 0),
+            // Uncoloured shulker boxes were introduced with the flattening
+            ItemKind::ShulkerBox(c) => match c.unwrap_or(Colour::Purple) {
+                Colour::White => ("white_shulker_box", 0),
+                Colour::Orange => ("orange_shulker_box", 0),
+                Colour::Magenta => ("magenta_shulker_box", 0),
+                Colour::LightBlue => ("light_blue_shulker_box", 0),
+                Colour::Yellow => ("yellow_shulker_box", 0),
+                Colour::Lime => ("lime_shulker_box", 0),
+                Colour::Pink => ("pink_shulker_box", 0),
+                Colour::Gray => ("gray_shulker_box", 0),
+                Colour::LightGray => ("silver_shulker_box", 0),
+                Colour::Cyan => ("cyan_shulker_box", 0),
+                Colour::Purple => ("purple_shulker_box", 0),
+                Colour::Blue => ("blue_shulker_box", 0),
+                Colour::Brown => ("brown_shulker_box", 0),
+                Colour::Green => ("green_shulker_box", 0),
+                Colour::Red => ("red_shulker_box", 0),
+                Colour::Black => ("black_shulker_box", 0),
+            },
+            ItemKind::Slab(material) => match material {
+                SlabMaterial::SmoothStone => ("stone_slab", 0),
+                SlabMaterial::Sandstone => ("stone_slab", 1),
+                SlabMaterial::PetrifiedOak => ("stone_slab", 2),
+                SlabMaterial::Cobblestone => ("stone_slab", 3),
+                SlabMaterial::Brick => ("stone_slab", 4),
+                SlabMaterial::StoneBrick => ("stone_slab", 5),
+                SlabMaterial::Quartz => ("stone_slab", 6),
+                SlabMaterial::NetherBrick => ("stone_slab", 7),
+                SlabMaterial::RedSandstone => ("stone_slab2", 0),
+                SlabMaterial::Purpur => ("purpur_slab", 0),
+                SlabMaterial::Oak => ("wooden_slab", 0),
+                SlabMaterial::Spruce => ("wooden_slab", 1),
+                SlabMaterial::Birch => ("wooden_slab", 2),
+                SlabMaterial::Jungle => ("wooden_slab", 3),
+                SlabMaterial::Acacia => ("wooden_slab", 4),
+                SlabMaterial::DarkOak => ("wooden_slab", 5),
+                _ => return None,
+            },
+            ItemKind::SlimeBlock => ("slime", 0),
+            ItemKind::SmoothQuartzBlock => ("quartz_block", 3),
+            ItemKind::SmoothRedSandstone => ("red_sandstone", 3),
+            ItemKind::SmoothSandstone => ("sandstone", 3),
+            ItemKind::SmoothStoneBricks => ("stonebrick", 4),
+            ItemKind::Snow => ("snow", 0),
+            ItemKind::SnowLayer => ("snow_layer", 0),
+            ItemKind::SoulSand => ("soul_sand", 0),
+            ItemKind::Sponge => ("sponge", 0),
+            ItemKind::SpruceLeaves => ("leaves", 1),
+            ItemKind::Stairs(material) => match material {
+                StairMaterial::Acacia => ("acacia_stairs", 0),
+                StairMaterial::Birch => ("birch_stairs", 0),
+                StairMaterial::Brick => ("brick_stairs", 0),
+                StairMaterial::Cobblestone => ("stone_stairs", 0),
+                StairMaterial::DarkOak => ("dark_oak_stairs", 0),
+                StairMaterial::Jungle => ("jungle_stairs", 0),
+                StairMaterial::NetherBrick => ("nether_brick_stairs", 0),
+                StairMaterial::Oak => ("oak_stairs", 0),
+                StairMaterial::Purpur => ("purpur_stairs", 0),
+                StairMaterial::Quartz => ("quartz_stairs", 0),
+                StairMaterial::RedSandstone => ("red_sandstone_stairs", 0),
+                StairMaterial::Sandstone => ("sandstone_stairs", 0),
+                StairMaterial::Spruce => ("spruce_stairs", 0),
+                StairMaterial::StoneBrick => ("stone_brick_stairs", 0),
+                _ => return None,
+            },
+            ItemKind::StickyPiston => ("sticky_piston", 0),
+            ItemKind::Stone => ("stone", 0),
+            ItemKind::StoneBricks => ("stonebrick", 0),
+            ItemKind::StructureBlock => ("structure_block", 0),
+            ItemKind::StructureVoid => ("structure_void", 0),
+            ItemKind::Terracotta(None) => ("hardened_clay", 0),
+            ItemKind::Terracotta(Some(c)) => ("stained_hardened_clay", colour(c)),
+            ItemKind::TNT => ("tnt", 0),
+            ItemKind::Torch => ("torch", 0),
+            ItemKind::Trapdoor(DoorMaterial::Oak) => ("trapdoor", 0),
+            ItemKind::Trapdoor(DoorMaterial::Iron) => ("iron_trapdoor", 0),
+            ItemKind::Trapdoor(_) => return None,
+            ItemKind::TrappedChest => ("trapped_chest", 0),
+            ItemKind::TripwireHook => ("tripwire_hook", 0),
+            ItemKind::Vines => ("vine", 0),
+            ItemKind::WetSponge => ("sponge", 1),
+            ItemKind::Wool(c) => ("wool", colour(c)),
         };
+        Some((id.into(), damage))
+    }
 
-        Self {
-            custom_name: None,        // TODO
-            enchantments: Vec::new(), // TODO
-            damage: None,             // TODO
-            kind,
+    /// Id, without namespace, of the item in post-flattening saves of the given
+    /// Minecraft version, or `None` if there is no such item.
+    ///
+    /// Items introduced after the flattening are not checked against the version.
+    fn modern_name(&self, data_version: McVersion) -> Option<String> {
+        if let Some(name) = SIMPLE_ITEM_NAMES.get(&std::mem::discriminant(self)) {
+            return Some(name.to_string());
         }
+        if let Some(name) = self.equipment_name() {
+            return Some(name);
+        }
+
+        let version_of = |name: &str| McVersion::from_str(name).unwrap();
+        Some(match self {
+            ItemKind::Unknown | ItemKind::Dye | ItemKind::SpawnEgg => return None,
+
+            // Placeholder kinds
+            ItemKind::Beef => "beef".into(),
+            ItemKind::Chicken => "chicken".into(),
+            ItemKind::FireworkRocket => "firework_rocket".into(),
+            ItemKind::Fish => "cod".into(),
+            ItemKind::Mutton => "mutton".into(),
+            ItemKind::Rabbit => "rabbit".into(),
+            ItemKind::Scull => "skeleton_skull".into(),
+
+            // Renamed after the flattening
+            ItemKind::CactusGreen if data_version < version_of("18w43a") => "cactus_green".into(),
+            ItemKind::CactusGreen => "green_dye".into(),
+            ItemKind::DandelionYellow if data_version < version_of("18w43a") => {
+                "dandelion_yellow".into()
+            }
+            ItemKind::DandelionYellow => "yellow_dye".into(),
+            ItemKind::RoseRed if data_version < version_of("18w43a") => "rose_red".into(),
+            ItemKind::RoseRed => "red_dye".into(),
+            ItemKind::Grass if data_version < version_of("23w40a") => "grass".into(),
+            ItemKind::Grass => "short_grass".into(),
+            ItemKind::GrassPath if data_version < version_of("20w45a") => "grass_path".into(),
+            ItemKind::GrassPath => "dirt_path".into(),
+            ItemKind::Sign(WoodMaterial::Oak) if data_version < version_of("19w08a") => {
+                "sign".into()
+            }
+            ItemKind::Sign(_) if data_version < version_of("19w08a") => return None,
+            ItemKind::Slab(SlabMaterial::SmoothStone) if data_version < version_of("18w43a") => {
+                "stone_slab".into()
+            }
+            ItemKind::Slab(SlabMaterial::Stone) if data_version < version_of("18w43a") => {
+                return None
+            }
+
+            ItemKind::Anvil(damage) => match damage {
+                AnvilDamage::Intact => "anvil".into(),
+                AnvilDamage::SlightlyDamaged => "chipped_anvil".into(),
+                AnvilDamage::VeryDamaged => "damaged_anvil".into(),
+            },
+            ItemKind::Banner => "white_banner".into(),
+            ItemKind::Bed(colour) => format!("{}_bed", name_of(colour)),
+            ItemKind::Boat(WoodMaterial::Crimson) | ItemKind::Boat(WoodMaterial::Warped) => {
+                return None
+            }
+            ItemKind::Boat(material) => format!("{}_boat", name_of(material)),
+            ItemKind::Bowl(None) => "bowl".into(),
+            ItemKind::Bowl(Some(contents)) => name_of(contents),
+            ItemKind::Bucket(None) | ItemKind::Bucket(Some(BucketContents::Empty)) => {
+                "bucket".into()
+            }
+            ItemKind::Bucket(Some(contents)) => format!("{}_bucket", name_of(contents)),
+            ItemKind::Button(material) => format!("{}_button", name_of(material)),
+            ItemKind::Carpet(colour) => format!("{}_carpet", name_of(colour)),
+            ItemKind::Concrete(colour) => {
+                format!("{}_concrete", name_of(colour.unwrap_or(Colour::White)))
+            }
+            ItemKind::ConcretePowder(colour) => {
+                format!(
+                    "{}_concrete_powder",
+                    name_of(colour.unwrap_or(Colour::White))
+                )
+            }
+            ItemKind::Door(material) => format!("{}_door", name_of(material)),
+            ItemKind::Fence(material) => format!("{}_fence", name_of(material)),
+            ItemKind::FenceGate(material) => format!("{}_fence_gate", name_of(material)),
+            ItemKind::Flower(flower) => flower_name(*flower).0,
+            ItemKind::Glass(None) => "glass".into(),
+            ItemKind::Glass(Some(colour)) => format!("{}_stained_glass", name_of(colour)),
+            ItemKind::GlassPane(None) => "glass_pane".into(),
+            ItemKind::GlassPane(Some(colour)) => {
+                format!("{}_stained_glass_pane", name_of(colour))
+            }
+            ItemKind::GlazedTerracotta(colour) => {
+                format!(
+                    "{}_glazed_terracotta",
+                    name_of(colour.unwrap_or(Colour::White))
+                )
+            }
+            ItemKind::HorseArmor(HorseArmorMaterial::Gold) => "golden_horse_armor".into(),
+            ItemKind::HorseArmor(material) => format!("{}_horse_armor", name_of(material)),
+            ItemKind::Ingot(material) => format!("{}_ingot", name_of(material)),
+            ItemKind::Log(material @ WoodMaterial::Crimson)
+            | ItemKind::Log(material @ WoodMaterial::Warped) => {
+                format!("{}_stem", name_of(material))
+            }
+            ItemKind::Log(material) => format!("{}_log", name_of(material)),
+            ItemKind::Minecart(None) => "minecart".into(),
+            ItemKind::Minecart(Some(MinecartContents::TNT)) => "tnt_minecart".into(),
+            ItemKind::Minecart(Some(contents)) => format!("{}_minecart", name_of(contents)),
+            ItemKind::Nugget(material) => format!("{}_nugget", name_of(material)),
+            ItemKind::Planks(material) => format!("{}_planks", name_of(material)),
+            ItemKind::Porkchop { cooked: false } => "porkchop".into(),
+            ItemKind::Porkchop { cooked: true } => "cooked_porkchop".into(),
+            ItemKind::PressurePlate(PressurePlateMaterial::Gold) => {
+                "light_weighted_pressure_plate".into()
+            }
+            ItemKind::PressurePlate(PressurePlateMaterial::Iron) => {
+                "heavy_weighted_pressure_plate".into()
+            }
+            ItemKind::PressurePlate(material) => format!("{}_pressure_plate", name_of(material)),
+            ItemKind::Record(recording) => format!("music_disc_{}", recording_name(recording)),
+            ItemKind::Sapling(SaplingMaterial::Bamboo) => "bamboo".into(),
+            ItemKind::Sapling(material) => format!("{}_sapling", name_of(material)),
+            ItemKind::Seeds(material) => format!("{}_seeds", name_of(material)),
+            ItemKind::ShulkerBox(None) => "shulker_box".into(),
+            ItemKind::ShulkerBox(Some(colour)) => format!("{}_shulker_box", name_of(colour)),
+            ItemKind::Sign(material) => format!("{}_sign", name_of(material)),
+            ItemKind::Slab(material) => format!("{}_slab", name_of(material)),
+            ItemKind::Stairs(material) => format!("{}_stairs", name_of(material)),
+            ItemKind::Terracotta(None) => "terracotta".into(),
+            ItemKind::Terracotta(Some(colour)) => format!("{}_terracotta", name_of(colour)),
+            ItemKind::Trapdoor(material) => format!("{}_trapdoor", name_of(material)),
+            ItemKind::Wool(colour) => format!("{}_wool", name_of(colour)),
+            _ => return None,
+        })
     }
 
-    pub(crate) fn to_nbt_value(&self) -> nbt::Value {
-        let mut item: nbt::Map<String, nbt::Value> = nbt::Map::with_capacity(4);
-        //TODO fill "id" and "tag" fields
-        // For now: everything is an apple!
-        item.insert("id".into(), nbt::Value::String("minecraft:apple".into()));
-        nbt::Value::Compound(item)
+    /// Kind of a post-flattening item with the given id, without namespace, as
+    /// named in any version from the flattening onwards.
+    ///
+    /// "stone_slab" is read as the stone slab of 18w43a onwards, not as the
+    /// smooth stone slab it was before that.
+    fn from_modern_name(name: &str) -> Option<Self> {
+        if let Some(kind) = SIMPLE_ITEMS_BY_NAME.get(name) {
+            return Some((*kind).clone());
+        }
+        if let Some(kind) = Self::from_equipment_name(name) {
+            return Some(kind);
+        }
+
+        let kind = match name {
+            "cactus_green" | "green_dye" => ItemKind::CactusGreen,
+            "dandelion_yellow" | "yellow_dye" => ItemKind::DandelionYellow,
+            "rose_red" | "red_dye" => ItemKind::RoseRed,
+            "grass" | "short_grass" => ItemKind::Grass,
+            "grass_path" | "dirt_path" => ItemKind::GrassPath,
+            "sign" => ItemKind::Sign(WoodMaterial::Oak),
+            "anvil" => ItemKind::Anvil(AnvilDamage::Intact),
+            "chipped_anvil" => ItemKind::Anvil(AnvilDamage::SlightlyDamaged),
+            "damaged_anvil" => ItemKind::Anvil(AnvilDamage::VeryDamaged),
+            "bowl" => ItemKind::Bowl(None),
+            "beetroot_soup" => ItemKind::Bowl(Some(BowlContents::BeetrootSoup)),
+            "mushroom_stew" => ItemKind::Bowl(Some(BowlContents::MushroomStew)),
+            "rabbit_stew" => ItemKind::Bowl(Some(BowlContents::RabbitStew)),
+            "suspicious_stew" => ItemKind::Bowl(Some(BowlContents::SuspiciousStew)),
+            "bucket" => ItemKind::Bucket(None),
+            "lava_bucket" => ItemKind::Bucket(Some(BucketContents::Lava)),
+            "milk_bucket" => ItemKind::Bucket(Some(BucketContents::Milk)),
+            "water_bucket" => ItemKind::Bucket(Some(BucketContents::Water)),
+            "glass" => ItemKind::Glass(None),
+            "glass_pane" => ItemKind::GlassPane(None),
+            "golden_horse_armor" => ItemKind::HorseArmor(HorseArmorMaterial::Gold),
+            "light_weighted_pressure_plate" => ItemKind::PressurePlate(PressurePlateMaterial::Gold),
+            "heavy_weighted_pressure_plate" => ItemKind::PressurePlate(PressurePlateMaterial::Iron),
+            "minecart" => ItemKind::Minecart(None),
+            "chest_minecart" => ItemKind::Minecart(Some(MinecartContents::Chest)),
+            "command_block_minecart" => ItemKind::Minecart(Some(MinecartContents::CommandBlock)),
+            "furnace_minecart" => ItemKind::Minecart(Some(MinecartContents::Furnace)),
+            "hopper_minecart" => ItemKind::Minecart(Some(MinecartContents::Hopper)),
+            "tnt_minecart" => ItemKind::Minecart(Some(MinecartContents::TNT)),
+            "porkchop" => ItemKind::Porkchop { cooked: false },
+            "cooked_porkchop" => ItemKind::Porkchop { cooked: true },
+            "bamboo" => ItemKind::Sapling(SaplingMaterial::Bamboo),
+            "shulker_box" => ItemKind::ShulkerBox(None),
+            "terracotta" => ItemKind::Terracotta(None),
+            _ => return Self::from_modern_name_by_suffix(name),
+        };
+        Some(kind)
+    }
+
+    fn from_modern_name_by_suffix(name: &str) -> Option<Self> {
+        if let Some(flower) = flower_from_name(name, false) {
+            return Some(ItemKind::Flower(flower));
+        }
+        if let Some(recording) = name.strip_prefix("music_disc_") {
+            return Some(ItemKind::Record(
+                RECORDINGS
+                    .iter()
+                    .find(|candidate| recording_name(candidate) == recording)?
+                    .clone(),
+            ));
+        }
+
+        // Coloured items
+        if let Some(colour) = name.strip_suffix("_stained_glass_pane") {
+            return Some(ItemKind::GlassPane(Some(parsed_name(colour, &COLOURS)?)));
+        }
+        if let Some(colour) = name.strip_suffix("_stained_glass") {
+            return Some(ItemKind::Glass(Some(parsed_name(colour, &COLOURS)?)));
+        }
+        if let Some(colour) = name.strip_suffix("_glazed_terracotta") {
+            return Some(ItemKind::GlazedTerracotta(Some(parsed_name(
+                colour, &COLOURS,
+            )?)));
+        }
+        if let Some(colour) = name.strip_suffix("_terracotta") {
+            return Some(ItemKind::Terracotta(Some(parsed_name(colour, &COLOURS)?)));
+        }
+        if let Some(colour) = name.strip_suffix("_concrete_powder") {
+            return Some(ItemKind::ConcretePowder(Some(parsed_name(
+                colour, &COLOURS,
+            )?)));
+        }
+        if let Some(colour) = name.strip_suffix("_concrete") {
+            return Some(ItemKind::Concrete(Some(parsed_name(colour, &COLOURS)?)));
+        }
+        if let Some(colour) = name.strip_suffix("_carpet") {
+            return Some(ItemKind::Carpet(parsed_name(colour, &COLOURS)?));
+        }
+        if let Some(colour) = name.strip_suffix("_wool") {
+            return Some(ItemKind::Wool(parsed_name(colour, &COLOURS)?));
+        }
+        if let Some(colour) = name.strip_suffix("_shulker_box") {
+            return Some(ItemKind::ShulkerBox(Some(parsed_name(colour, &COLOURS)?)));
+        }
+        if let Some(colour) = name.strip_suffix("_bed") {
+            return Some(ItemKind::Bed(parsed_name(colour, &COLOURS)?));
+        }
+        if let Some(colour) = name.strip_suffix("_banner") {
+            parsed_name(colour, &COLOURS)?;
+            return Some(ItemKind::Banner);
+        }
+
+        // Items of different materials
+        if let Some(material) = name.strip_suffix("_boat") {
+            return Some(ItemKind::Boat(parsed_name(material, &WOOD_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_button") {
+            return Some(ItemKind::Button(parsed_name(material, &BUTTON_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_door") {
+            return Some(ItemKind::Door(parsed_name(material, &DOOR_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_trapdoor") {
+            return Some(ItemKind::Trapdoor(parsed_name(material, &DOOR_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_fence_gate") {
+            return Some(ItemKind::FenceGate(parsed_name(material, &WOOD_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_fence") {
+            return Some(ItemKind::Fence(parsed_name(material, &FENCE_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_horse_armor") {
+            return Some(ItemKind::HorseArmor(parsed_name(
+                material,
+                &HORSE_ARMOR_MATERIALS,
+            )?));
+        }
+        if let Some(material) = name.strip_suffix("_ingot") {
+            return Some(ItemKind::Ingot(parsed_name(material, &INGOT_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_log") {
+            return Some(ItemKind::Log(parsed_name(material, &WOOD_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_stem") {
+            return Some(ItemKind::Log(parsed_name(material, &WOOD_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_nugget") {
+            return Some(ItemKind::Nugget(parsed_name(material, &NUGGET_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_planks") {
+            return Some(ItemKind::Planks(parsed_name(material, &WOOD_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_pressure_plate") {
+            return Some(ItemKind::PressurePlate(parsed_name(
+                material,
+                &PRESSURE_PLATE_MATERIALS,
+            )?));
+        }
+        if let Some(material) = name.strip_suffix("_sapling") {
+            return Some(ItemKind::Sapling(parsed_name(
+                material,
+                &SAPLING_MATERIALS,
+            )?));
+        }
+        if let Some(material) = name.strip_suffix("_seeds") {
+            return Some(ItemKind::Seeds(parsed_name(material, &SEED_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_sign") {
+            return Some(ItemKind::Sign(parsed_name(material, &WOOD_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_slab") {
+            return Some(ItemKind::Slab(parsed_name(material, &SLAB_MATERIALS)?));
+        }
+        if let Some(material) = name.strip_suffix("_stairs") {
+            return Some(ItemKind::Stairs(parsed_name(material, &STAIR_MATERIALS)?));
+        }
+        None
+    }
+
+    /// Id, without namespace, of a tool or a piece of armour, e.g. "golden_axe".
+    fn equipment_name(&self) -> Option<String> {
+        let (material, piece) = match self {
+            ItemKind::Axe(material) => (tool_material_name(*material), "axe"),
+            ItemKind::Hoe(material) => (tool_material_name(*material), "hoe"),
+            ItemKind::Pickaxe(material) => (tool_material_name(*material), "pickaxe"),
+            ItemKind::Shovel(material) => (tool_material_name(*material), "shovel"),
+            ItemKind::Sword(material) => (tool_material_name(*material), "sword"),
+            ItemKind::Boots(material) => (armour_material_name(*material), "boots"),
+            ItemKind::Chestplate(material) => (armour_material_name(*material), "chestplate"),
+            ItemKind::Helmet(material) => (armour_material_name(*material), "helmet"),
+            ItemKind::Leggings(material) => (armour_material_name(*material), "leggings"),
+            _ => return None,
+        };
+        Some(format!("{}_{}", material, piece))
+    }
+
+    fn from_equipment_name(name: &str) -> Option<Self> {
+        let index = name.rfind('_')?;
+        let (material, piece) = (&name[..index], &name[index + 1..]);
+        let tool = || {
+            TOOL_MATERIALS
+                .iter()
+                .find(|candidate| tool_material_name(**candidate) == material)
+                .copied()
+        };
+        let armour = || {
+            ARMOUR_MATERIALS
+                .iter()
+                .find(|candidate| armour_material_name(**candidate) == material)
+                .copied()
+        };
+        Some(match piece {
+            "axe" => ItemKind::Axe(tool()?),
+            "hoe" => ItemKind::Hoe(tool()?),
+            "pickaxe" => ItemKind::Pickaxe(tool()?),
+            "shovel" => ItemKind::Shovel(tool()?),
+            "sword" => ItemKind::Sword(tool()?),
+            "boots" => ItemKind::Boots(armour()?),
+            "chestplate" => ItemKind::Chestplate(armour()?),
+            "helmet" => ItemKind::Helmet(armour()?),
+            "leggings" => ItemKind::Leggings(armour()?),
+            _ => return None,
+        })
+    }
+
+    /// Whether the item wears down with use, with its damage stored as a
+    /// damage value.
+    fn is_damageable(&self) -> bool {
+        matches!(
+            self,
+            ItemKind::Axe(_)
+                | ItemKind::Boots(_)
+                | ItemKind::Bow
+                | ItemKind::CarrotOnAStick
+                | ItemKind::Chestplate(_)
+                | ItemKind::Elytra
+                | ItemKind::FishingRod
+                | ItemKind::FlintAndSteel
+                | ItemKind::Helmet(_)
+                | ItemKind::Hoe(_)
+                | ItemKind::Leggings(_)
+                | ItemKind::Pickaxe(_)
+                | ItemKind::Shears
+                | ItemKind::Shield
+                | ItemKind::Shovel(_)
+                | ItemKind::Sword(_)
+        )
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+fn tool_material_name(material: ToolMaterial) -> String {
+    match material {
+        ToolMaterial::Gold => "golden".into(),
+        ToolMaterial::Wood => "wooden".into(),
+        _ => name_of(material),
+    }
+}
+
+fn armour_material_name(material: ArmourMaterial) -> String {
+    match material {
+        ArmourMaterial::Gold => "golden".into(),
+        _ => name_of(material),
+    }
+}
+
+fn recording_name(recording: &Recording) -> String {
+    match recording {
+        Recording::Eleven => "11".into(),
+        Recording::Thirteen => "13".into(),
+        _ => name_of(recording),
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum BowlContents {
     BeetrootSoup,
     MushroomStew,
@@ -689,7 +3067,7 @@ pub enum BowlContents {
     SuspiciousStew,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum BucketContents {
     Empty,
     Lava,
@@ -697,7 +3075,7 @@ pub enum BucketContents {
     Water,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum Recording {
     Blocks,
     Cat,
@@ -713,7 +3091,7 @@ pub enum Recording {
     Wait,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum MinecartContents {
     Chest,
     CommandBlock,
@@ -722,14 +3100,14 @@ pub enum MinecartContents {
     TNT,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub enum ItemKind {
     // mcprogedit "items"
     Unknown,
 
     // "Non-block" items
     Apple,
-    Arrow, // spectral, tipped (multiple),
+    Arrow,
     ArmorStand,
     Axe(ToolMaterial),
     BakedPotato,
@@ -818,6 +3196,7 @@ pub enum ItemKind {
     LightBlueDye,
     LightGrayDye,
     LimeDye,
+    LingeringPotion,
     MagentaDye,
     MagmaCream,
     Map, // empty? filled?
@@ -838,7 +3217,7 @@ pub enum ItemKind {
     PinkDye,
     PoppedChorusFruit,
     Porkchop { cooked: bool },
-    Potion, // type / splash? / lingering?
+    Potion,
     Potato, // poisonous?
     PrismarineCrystals,
     PrismarineShard,
@@ -868,13 +3247,16 @@ pub enum ItemKind {
     Slimeball,
     Snowball,
     SpawnEgg, // type
+    SpectralArrow,
     SpiderEye,
+    SplashPotion,
     Steak,
     Stick,
     String,
     Sugar,
     SugarCane,
     Sword(ToolMaterial),
+    TippedArrow,
     TotemOfUndying,
     Wheat,
     WrittenBook,
@@ -1051,3 +3433,857 @@ pub enum ItemKind {
     WetSponge,
     Wool(Colour),
 }
+
+const TOOL_MATERIALS: [ToolMaterial; 6] = [
+    ToolMaterial::Diamond,
+    ToolMaterial::Gold,
+    ToolMaterial::Iron,
+    ToolMaterial::Netherite,
+    ToolMaterial::Stone,
+    ToolMaterial::Wood,
+];
+const ARMOUR_MATERIALS: [ArmourMaterial; 6] = [
+    ArmourMaterial::Chainmail,
+    ArmourMaterial::Diamond,
+    ArmourMaterial::Gold,
+    ArmourMaterial::Iron,
+    ArmourMaterial::Leather,
+    ArmourMaterial::Netherite,
+];
+const HORSE_ARMOR_MATERIALS: [HorseArmorMaterial; 3] = [
+    HorseArmorMaterial::Gold,
+    HorseArmorMaterial::Iron,
+    HorseArmorMaterial::Diamond,
+];
+const INGOT_MATERIALS: [IngotMaterial; 3] = [
+    IngotMaterial::Gold,
+    IngotMaterial::Iron,
+    IngotMaterial::Netherite,
+];
+const NUGGET_MATERIALS: [NuggetMaterial; 2] = [NuggetMaterial::Gold, NuggetMaterial::Iron];
+const SEED_MATERIALS: [SeedMaterial; 4] = [
+    SeedMaterial::Beetroot,
+    SeedMaterial::Melon,
+    SeedMaterial::Pumpkin,
+    SeedMaterial::Wheat,
+];
+const RECORDINGS: [Recording; 12] = [
+    Recording::Blocks,
+    Recording::Cat,
+    Recording::Chirp,
+    Recording::Eleven,
+    Recording::Far,
+    Recording::Mall,
+    Recording::Mellohi,
+    Recording::Stal,
+    Recording::Strad,
+    Recording::Thirteen,
+    Recording::Ward,
+    Recording::Wait,
+];
+
+/// Items without variants, by their post-flattening name.
+static SIMPLE_ITEMS: [(&str, ItemKind); 265] = [
+    ("apple", ItemKind::Apple),
+    ("arrow", ItemKind::Arrow),
+    ("armor_stand", ItemKind::ArmorStand),
+    ("baked_potato", ItemKind::BakedPotato),
+    ("beetroot", ItemKind::Beetroot),
+    ("blaze_powder", ItemKind::BlazePowder),
+    ("blaze_rod", ItemKind::BlazeRod),
+    ("bone", ItemKind::Bone),
+    ("bone_meal", ItemKind::BoneMeal),
+    ("book", ItemKind::Book),
+    ("writable_book", ItemKind::BookAndQuill),
+    ("experience_bottle", ItemKind::BottleOEnchanting),
+    ("bow", ItemKind::Bow),
+    ("bread", ItemKind::Bread),
+    ("brewing_stand", ItemKind::BrewingStand),
+    ("brick", ItemKind::Brick),
+    ("cake", ItemKind::Cake),
+    ("carrot", ItemKind::Carrot),
+    ("carrot_on_a_stick", ItemKind::CarrotOnAStick),
+    ("cauldron", ItemKind::Cauldron),
+    ("charcoal", ItemKind::Charcoal),
+    ("chorus_fruit", ItemKind::ChorusFruit),
+    ("clay_ball", ItemKind::Clay),
+    ("clock", ItemKind::Clock),
+    ("coal", ItemKind::Coal),
+    ("cocoa_beans", ItemKind::CocoaBeans),
+    ("compass", ItemKind::Compass),
+    ("cooked_chicken", ItemKind::CookedChicken),
+    ("cooked_mutton", ItemKind::CookedMutton),
+    ("cooked_rabbit", ItemKind::CookedRabbit),
+    ("cookie", ItemKind::Cookie),
+    ("cyan_dye", ItemKind::CyanDye),
+    ("diamond", ItemKind::Diamond),
+    ("dragon_breath", ItemKind::DragonBreath),
+    ("egg", ItemKind::Egg),
+    ("elytra", ItemKind::Elytra),
+    ("emerald", ItemKind::Emerald),
+    ("map", ItemKind::EmptyMap),
+    ("enchanted_book", ItemKind::EnchantedBook),
+    ("enchanted_golden_apple", ItemKind::EnchantedGoldenApple),
+    ("end_crystal", ItemKind::EndCrystal),
+    ("ender_eye", ItemKind::EnderEye),
+    ("ender_pearl", ItemKind::EnderPearl),
+    ("feather", ItemKind::Feather),
+    ("fermented_spider_eye", ItemKind::FermentedSpiderEye),
+    ("fire_charge", ItemKind::FireCharge),
+    ("firework_rocket", ItemKind::Fireworks),
+    ("firework_star", ItemKind::FireworkStar),
+    ("fishing_rod", ItemKind::FishingRod),
+    ("flint", ItemKind::Flint),
+    ("flint_and_steel", ItemKind::FlintAndSteel),
+    ("flower_pot", ItemKind::FlowerPot),
+    ("ghast_tear", ItemKind::GhastTear),
+    ("glass_bottle", ItemKind::GlassBottle),
+    ("glistering_melon_slice", ItemKind::GlisteringMelon),
+    ("glowstone_dust", ItemKind::GlowstoneDust),
+    ("golden_apple", ItemKind::GoldenApple),
+    ("golden_carrot", ItemKind::GoldenCarrot),
+    ("gray_dye", ItemKind::GrayDye),
+    ("gunpowder", ItemKind::Gunpowder),
+    ("ink_sac", ItemKind::InkSac),
+    ("item_frame", ItemKind::ItemFrame),
+    ("knowledge_book", ItemKind::KnowledgeBook),
+    ("lapis_lazuli", ItemKind::LapisLazuli),
+    ("lead", ItemKind::Lead),
+    ("leather", ItemKind::Leather),
+    ("light_blue_dye", ItemKind::LightBlueDye),
+    ("light_gray_dye", ItemKind::LightGrayDye),
+    ("lime_dye", ItemKind::LimeDye),
+    ("lingering_potion", ItemKind::LingeringPotion),
+    ("magenta_dye", ItemKind::MagentaDye),
+    ("magma_cream", ItemKind::MagmaCream),
+    ("filled_map", ItemKind::Map),
+    ("melon_slice", ItemKind::Melon),
+    ("skeleton_skull", ItemKind::MobHead),
+    ("name_tag", ItemKind::NameTag),
+    ("nether_brick", ItemKind::NetherBrick),
+    ("quartz", ItemKind::NetherQuartz),
+    ("nether_star", ItemKind::NetherStar),
+    ("nether_wart", ItemKind::NetherWart),
+    ("orange_dye", ItemKind::OrangeDye),
+    ("painting", ItemKind::Painting),
+    ("paper", ItemKind::Paper),
+    ("pink_dye", ItemKind::PinkDye),
+    ("popped_chorus_fruit", ItemKind::PoppedChorusFruit),
+    ("potion", ItemKind::Potion),
+    ("potato", ItemKind::Potato),
+    ("prismarine_crystals", ItemKind::PrismarineCrystals),
+    ("prismarine_shard", ItemKind::PrismarineShard),
+    ("pumpkin_pie", ItemKind::PumpkinPie),
+    ("purple_dye", ItemKind::PurpleDye),
+    ("rabbit_foot", ItemKind::RabbitFoot),
+    ("rabbit_hide", ItemKind::RabbitHide),
+    ("beef", ItemKind::RawBeef),
+    ("chicken", ItemKind::RawChicken),
+    ("mutton", ItemKind::RawMutton),
+    ("rabbit", ItemKind::RawRabbit),
+    ("redstone", ItemKind::Redstone),
+    ("comparator", ItemKind::RedstoneComparator),
+    ("repeater", ItemKind::RedstoneRepeater),
+    ("rotten_flesh", ItemKind::RottenFlesh),
+    ("saddle", ItemKind::Saddle),
+    ("shears", ItemKind::Shears),
+    ("shield", ItemKind::Shield),
+    ("shulker_shell", ItemKind::ShulkerShell),
+    ("slime_ball", ItemKind::Slimeball),
+    ("snowball", ItemKind::Snowball),
+    ("spectral_arrow", ItemKind::SpectralArrow),
+    ("spider_eye", ItemKind::SpiderEye),
+    ("splash_potion", ItemKind::SplashPotion),
+    ("cooked_beef", ItemKind::Steak),
+    ("stick", ItemKind::Stick),
+    ("string", ItemKind::String),
+    ("sugar", ItemKind::Sugar),
+    ("sugar_cane", ItemKind::SugarCane),
+    ("tipped_arrow", ItemKind::TippedArrow),
+    ("totem_of_undying", ItemKind::TotemOfUndying),
+    ("wheat", ItemKind::Wheat),
+    ("written_book", ItemKind::WrittenBook),
+    ("acacia_leaves", ItemKind::AcaciaLeaves),
+    ("activator_rail", ItemKind::ActivatorRail),
+    ("andesite", ItemKind::Andesite),
+    ("barrier", ItemKind::Barrier),
+    ("beacon", ItemKind::Beacon),
+    ("bedrock", ItemKind::Bedrock),
+    ("birch_leaves", ItemKind::BirchLeaves),
+    ("coal_block", ItemKind::BlockOfCoal),
+    ("diamond_block", ItemKind::BlockOfDiamond),
+    ("emerald_block", ItemKind::BlockOfEmerald),
+    ("gold_block", ItemKind::BlockOfGold),
+    ("iron_block", ItemKind::BlockOfIron),
+    ("quartz_block", ItemKind::BlockOfQuartz),
+    ("redstone_block", ItemKind::BlockOfRedstone),
+    ("bone_block", ItemKind::BoneBlock),
+    ("bookshelf", ItemKind::Bookshelf),
+    ("bricks", ItemKind::BrickBlock),
+    ("brown_mushroom", ItemKind::BrownMushroom),
+    ("brown_mushroom_block", ItemKind::BrownMushroomBlock),
+    ("cactus", ItemKind::Cactus),
+    ("chain_command_block", ItemKind::ChainCommandBlock),
+    ("chest", ItemKind::Chest),
+    ("chiseled_quartz_block", ItemKind::ChiseledQuartzBlock),
+    ("chiseled_red_sandstone", ItemKind::ChiseledRedSandstone),
+    ("chiseled_sandstone", ItemKind::ChiseledSandstone),
+    ("chiseled_stone_bricks", ItemKind::ChiseledStoneBricks),
+    ("chorus_flower", ItemKind::ChorusFlower),
+    ("chorus_plant", ItemKind::ChorusPlant),
+    ("clay", ItemKind::ClayBlock),
+    ("coal_ore", ItemKind::CoalOre),
+    ("coarse_dirt", ItemKind::CoarseDirt),
+    ("cobblestone", ItemKind::Cobblestone),
+    ("cobblestone_wall", ItemKind::CobblestoneWall),
+    ("cobweb", ItemKind::Cobweb),
+    ("command_block", ItemKind::CommandBlock),
+    ("cracked_stone_bricks", ItemKind::CrackedStoneBricks),
+    ("crafting_table", ItemKind::CraftingTable),
+    ("cut_red_sandstone", ItemKind::CutRedSandstone),
+    ("cut_sandstone", ItemKind::CutSandstone),
+    ("dark_oak_leaves", ItemKind::DarkOakLeaves),
+    ("dark_prismarine", ItemKind::DarkPrismarine),
+    ("daylight_detector", ItemKind::DaylightDetector),
+    ("dead_bush", ItemKind::DeadBush),
+    ("detector_rail", ItemKind::DetectorRail),
+    ("diamond_ore", ItemKind::DiamondOre),
+    ("diorite", ItemKind::Diorite),
+    ("dirt", ItemKind::Dirt),
+    ("dispenser", ItemKind::Dispenser),
+    ("tall_grass", ItemKind::DoubleTallgrass),
+    ("dragon_egg", ItemKind::DragonEgg),
+    ("dropper", ItemKind::Dropper),
+    ("emerald_ore", ItemKind::EmeraldOre),
+    ("enchanting_table", ItemKind::EnchantingTable),
+    ("end_stone_bricks", ItemKind::EndBricks),
+    ("ender_chest", ItemKind::EnderChest),
+    ("end_portal_frame", ItemKind::EndPortalFrame),
+    ("end_rod", ItemKind::EndRod),
+    ("end_stone", ItemKind::EndStone),
+    ("farmland", ItemKind::Farmland),
+    ("furnace", ItemKind::Furnace),
+    ("glowstone", ItemKind::Glowstone),
+    ("gold_ore", ItemKind::GoldOre),
+    ("granite", ItemKind::Granite),
+    ("grass_block", ItemKind::GrassBlock),
+    ("gravel", ItemKind::Gravel),
+    ("hay_block", ItemKind::HayBale),
+    ("hopper", ItemKind::Hopper),
+    ("ice", ItemKind::Ice),
+    (
+        "infested_chiseled_stone_bricks",
+        ItemKind::InfestedChiseledStoneBricks,
+    ),
+    ("infested_cobblestone", ItemKind::InfestedCobblestone),
+    (
+        "infested_cracked_stone_bricks",
+        ItemKind::InfestedCrackedStoneBricks,
+    ),
+    (
+        "infested_mossy_stone_bricks",
+        ItemKind::InfestedMossyStoneBricks,
+    ),
+    ("infested_stone", ItemKind::InfestedStone),
+    ("infested_stone_bricks", ItemKind::InfestedStoneBricks),
+    ("iron_bars", ItemKind::IronBars),
+    ("iron_ore", ItemKind::IronOre),
+    ("jack_o_lantern", ItemKind::JackOLantern),
+    ("jungle_leaves", ItemKind::JungleLeaves),
+    ("jukebox", ItemKind::Jukebox),
+    ("ladder", ItemKind::Ladder),
+    ("lapis_block", ItemKind::LapisLazuliBlock),
+    ("lapis_ore", ItemKind::LapisLazuliOre),
+    ("large_fern", ItemKind::LargeFern),
+    ("lever", ItemKind::Lever),
+    ("lily_pad", ItemKind::LilyPad),
+    ("magma_block", ItemKind::Magma),
+    ("melon", ItemKind::MelonBlock),
+    ("spawner", ItemKind::MobSpawner),
+    ("mossy_cobblestone", ItemKind::MossyCobblestone),
+    ("mossy_cobblestone_wall", ItemKind::MossyCobblestoneWall),
+    ("mossy_stone_bricks", ItemKind::MossyStoneBricks),
+    ("mycelium", ItemKind::Mycelium),
+    ("nether_bricks", ItemKind::NetherBrickBlock),
+    ("netherrack", ItemKind::Netherrack),
+    ("nether_wart_block", ItemKind::NetherWartBlock),
+    ("note_block", ItemKind::Noteblock),
+    ("oak_leaves", ItemKind::OakLeaves),
+    ("observer", ItemKind::Observer),
+    ("obsidian", ItemKind::Obsidian),
+    ("packed_ice", ItemKind::PackedIce),
+    ("quartz_pillar", ItemKind::PillarQuartzBlock),
+    ("piston", ItemKind::Piston),
+    ("podzol", ItemKind::Podzol),
+    ("polished_andesite", ItemKind::PolishedAndesite),
+    ("polished_diorite", ItemKind::PolishedDiorite),
+    ("polished_granite", ItemKind::PolishedGranite),
+    ("powered_rail", ItemKind::PoweredRail),
+    ("prismarine", ItemKind::Prismarine),
+    ("prismarine_bricks", ItemKind::PrismarineBricks),
+    ("pumpkin", ItemKind::Pumpkin),
+    ("purpur_block", ItemKind::PurpurBlock),
+    ("purpur_pillar", ItemKind::PurpurPillar),
+    ("nether_quartz_ore", ItemKind::QuartzOre),
+    ("rail", ItemKind::Rail),
+    ("red_mushroom", ItemKind::RedMushroom),
+    ("red_mushroom_block", ItemKind::RedMushroomBlock),
+    ("red_nether_bricks", ItemKind::RedNetherBrick),
+    ("red_sand", ItemKind::RedSand),
+    ("red_sandstone", ItemKind::RedSandstone),
+    ("redstone_lamp", ItemKind::RedstoneLamp),
+    ("redstone_ore", ItemKind::RedstoneOre),
+    ("redstone_torch", ItemKind::RedstoneTorch),
+    ("repeating_command_block", ItemKind::RepeatingCommandBlock),
+    ("sand", ItemKind::Sand),
+    ("sandstone", ItemKind::Sandstone),
+    ("sea_lantern", ItemKind::SeaLantern),
+    ("slime_block", ItemKind::SlimeBlock),
+    ("smooth_quartz", ItemKind::SmoothQuartzBlock),
+    ("smooth_red_sandstone", ItemKind::SmoothRedSandstone),
+    ("smooth_sandstone", ItemKind::SmoothSandstone),
+    ("snow_block", ItemKind::Snow),
+    ("snow", ItemKind::SnowLayer),
+    ("soul_sand", ItemKind::SoulSand),
+    ("sponge", ItemKind::Sponge),
+    ("spruce_leaves", ItemKind::SpruceLeaves),
+    ("sticky_piston", ItemKind::StickyPiston),
+    ("stone", ItemKind::Stone),
+    ("stone_bricks", ItemKind::StoneBricks),
+    ("structure_block", ItemKind::StructureBlock),
+    ("structure_void", ItemKind::StructureVoid),
+    ("tnt", ItemKind::TNT),
+    ("torch", ItemKind::Torch),
+    ("trapped_chest", ItemKind::TrappedChest),
+    ("tripwire_hook", ItemKind::TripwireHook),
+    ("vine", ItemKind::Vines),
+    ("wet_sponge", ItemKind::WetSponge),
+];
+
+static SIMPLE_ITEMS_BY_NAME: Lazy<HashMap<&'static str, &'static ItemKind>> = Lazy::new(|| {
+    SIMPLE_ITEMS
+        .iter()
+        .map(|(name, kind)| (*name, kind))
+        .collect()
+});
+
+static SIMPLE_ITEM_NAMES: Lazy<HashMap<Discriminant<ItemKind>, &'static str>> = Lazy::new(|| {
+    SIMPLE_ITEMS
+        .iter()
+        .map(|(name, kind)| (std::mem::discriminant(kind), *name))
+        .collect()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(kind: ItemKind) -> Item {
+        Item {
+            kind,
+            ..Item::new()
+        }
+    }
+
+    fn version(name: &str) -> McVersion {
+        McVersion::from_str(name).unwrap()
+    }
+
+    fn representative_kinds() -> Vec<ItemKind> {
+        vec![
+            ItemKind::Apple,
+            ItemKind::Anvil(AnvilDamage::SlightlyDamaged),
+            ItemKind::Bed(Colour::Cyan),
+            ItemKind::Boat(WoodMaterial::DarkOak),
+            ItemKind::Bowl(Some(BowlContents::RabbitStew)),
+            ItemKind::Bucket(Some(BucketContents::Lava)),
+            ItemKind::CocoaBeans,
+            ItemKind::Door(DoorMaterial::Oak),
+            ItemKind::EnchantedGoldenApple,
+            ItemKind::Flower(Flower::TulipPink),
+            ItemKind::Flower(Flower::PeonyBottom),
+            ItemKind::Glass(Some(Colour::LightGray)),
+            ItemKind::GlazedTerracotta(Some(Colour::LightGray)),
+            ItemKind::Helmet(ArmourMaterial::Chainmail),
+            ItemKind::Log(WoodMaterial::Acacia),
+            ItemKind::Minecart(Some(MinecartContents::Hopper)),
+            ItemKind::Pickaxe(ToolMaterial::Gold),
+            ItemKind::Planks(WoodMaterial::Birch),
+            ItemKind::Porkchop { cooked: true },
+            ItemKind::PressurePlate(PressurePlateMaterial::Iron),
+            ItemKind::Record(Recording::Eleven),
+            ItemKind::RoseRed,
+            ItemKind::Sapling(SaplingMaterial::Jungle),
+            ItemKind::Seeds(SeedMaterial::Pumpkin),
+            ItemKind::ShulkerBox(Some(Colour::Black)),
+            ItemKind::Sign(WoodMaterial::Oak),
+            ItemKind::Slab(SlabMaterial::SmoothStone),
+            ItemKind::Stairs(StairMaterial::StoneBrick),
+            ItemKind::Terracotta(None),
+            ItemKind::Wool(Colour::Red),
+        ]
+    }
+
+    #[test]
+    fn items_survive_pre_flattening_round_trip() {
+        for kind in representative_kinds() {
+            let value = item(kind.clone()).to_nbt_value(version("1.12.2")).unwrap();
+            assert_eq!(Item::from_nbt_value(&value).kind, kind);
+        }
+    }
+
+    #[test]
+    fn items_survive_post_flattening_round_trip() {
+        for data_version in &["1.14.4", "1.16.5", "1.21.7"] {
+            for kind in representative_kinds() {
+                let value = item(kind.clone())
+                    .to_nbt_value(version(data_version))
+                    .unwrap();
+                assert_eq!(Item::from_nbt_value(&value).kind, kind);
+            }
+        }
+        for (_, kind) in SIMPLE_ITEMS.iter() {
+            let value = item(kind.clone()).to_nbt_value(version("1.21.7")).unwrap();
+            assert_eq!(&Item::from_nbt_value(&value).kind, kind);
+        }
+    }
+
+    #[test]
+    fn items_get_the_ids_of_the_version() {
+        let id = |kind: ItemKind, data_version: &str| {
+            let value = item(kind).to_nbt_value(version(data_version)).unwrap();
+            (
+                nbt_value_lookup_string(&value, "id").unwrap(),
+                nbt_value_lookup_short(&value, "Damage"),
+            )
+        };
+        assert_eq!(
+            id(ItemKind::Wool(Colour::Red), "1.12.2"),
+            ("minecraft:wool".into(), Some(14))
+        );
+        assert_eq!(
+            id(ItemKind::Wool(Colour::Red), "1.13.2"),
+            ("minecraft:red_wool".into(), None)
+        );
+        assert_eq!(
+            id(ItemKind::RoseRed, "1.13.2"),
+            ("minecraft:rose_red".into(), None)
+        );
+        assert_eq!(
+            id(ItemKind::RoseRed, "1.14.4"),
+            ("minecraft:red_dye".into(), None)
+        );
+        assert_eq!(
+            item(ItemKind::Sword(ToolMaterial::Netherite)).to_nbt_value(version("1.12.2")),
+            None
+        );
+    }
+
+    #[test]
+    fn damage_is_kept_for_tools() {
+        let mut sword = item(ItemKind::Sword(ToolMaterial::Iron));
+        sword.damage = Some(42);
+        for data_version in &["1.12.2", "1.16.5"] {
+            let value = sword.to_nbt_value(version(data_version)).unwrap();
+            assert_eq!(Item::from_nbt_value(&value), sword);
+        }
+    }
+
+    #[test]
+    fn items_get_data_components_from_1_20_5() {
+        let mut sword = item(ItemKind::Sword(ToolMaterial::Iron));
+        sword.damage = Some(42);
+        for data_version in &["1.20.4", "1.20.5", "1.21.7"] {
+            let value = sword.to_nbt_value(version(data_version)).unwrap();
+            assert_eq!(Item::from_nbt_value(&value), sword);
+        }
+
+        let value = sword.to_nbt_value(version("1.20.4")).unwrap();
+        assert_eq!(nbt_value_lookup_int(&value, "tag/Damage"), Some(42));
+        let value = sword.to_nbt_value(version("1.21.7")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_int(&value, "components/minecraft:damage"),
+            Some(42)
+        );
+        assert_eq!(nbt_value_lookup(&value, "tag"), None);
+
+        // Unhandled components are kept, but not written in the "tag" compound
+        let mut components = nbt::Map::new();
+        components.insert("minecraft:map_id".into(), nbt::Value::Int(3));
+        let mut compound = nbt::Map::new();
+        compound.insert(
+            "id".into(),
+            nbt::Value::String("minecraft:filled_map".into()),
+        );
+        compound.insert("components".into(), nbt::Value::Compound(components));
+        let map = Item::from_nbt_value(&nbt::Value::Compound(compound.clone()));
+        let value = map.to_nbt_value(version("1.21.7")).unwrap();
+        assert_eq!(value, nbt::Value::Compound(compound));
+        let value = map.to_nbt_value(version("1.20.4")).unwrap();
+        assert_eq!(nbt_value_lookup(&value, "tag"), None);
+    }
+
+    #[test]
+    fn unknown_items_keep_their_ids() {
+        let mut compound = nbt::Map::new();
+        compound.insert("id".into(), nbt::Value::String("minecraft:trident".into()));
+        let unknown = Item::from_nbt_value(&nbt::Value::Compound(compound));
+        assert_eq!(unknown.kind, ItemKind::Unknown);
+        let value = unknown.to_nbt_value(version("1.16.5")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "id").as_deref(),
+            Some("minecraft:trident")
+        );
+    }
+
+    fn sword_of_doom() -> Item {
+        Item::new_with_kind(ItemKind::Sword(ToolMaterial::Diamond))
+            .with_custom_name("Sword of Doom")
+            .with_lore(&["Forged in lava", "Handle with care!"])
+            .with_enchantment(Enchantment::Looting, 3)
+            .with_enchantment(Enchantment::Sharpness, 5)
+            .with_damage(12)
+            .with_unbreakable(true)
+            .with_repair_cost(7)
+            .with_attribute_modifier(AttributeModifier {
+                attribute: "generic.attack_damage".into(),
+                name: "Doom".into(),
+                amount: 4.5,
+                operation: AttributeOperation::Add,
+                uuid: 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210,
+                slot: Some("mainhand".into()),
+            })
+    }
+
+    #[test]
+    fn tag_data_survives_round_trip() {
+        let book = Item::new_with_kind(ItemKind::EnchantedBook)
+            .with_stored_enchantment(Enchantment::Mending, 1);
+        let boots = Item::new_with_kind(ItemKind::Boots(ArmourMaterial::Leather))
+            .with_dye_colour(0x00a0_6540);
+        for data_version in &["1.12.2", "1.13.2", "1.14.4", "1.16.5", "1.20.5", "1.21.7"] {
+            for item in &[sword_of_doom(), book.clone(), boots.clone()] {
+                let value = item.to_nbt_value(version(data_version)).unwrap();
+                let mut expected = item.clone();
+                // Attribute modifiers have ids made from their UUIDs from 1.21
+                if *data_version == "1.21.7" {
+                    for modifier in &mut expected.attribute_modifiers {
+                        modifier.name = "minecraft:01234567-89ab-cdef-fedc-ba9876543210".into();
+                    }
+                }
+                assert_eq!(Item::from_nbt_value(&value), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_enchantments_are_kept() {
+        let enchantment = |id: nbt::Value, level: i16| {
+            let mut compound = nbt::Map::new();
+            compound.insert("id".into(), id);
+            compound.insert("lvl".into(), nbt::Value::Short(level));
+            nbt::Value::Compound(compound)
+        };
+        let mut tag = nbt::Map::new();
+        tag.insert(
+            "Enchantments".into(),
+            nbt::Value::List(vec![
+                enchantment(nbt::Value::String("minecraft:sharpness".into()), 2),
+                enchantment(nbt::Value::String("othermod:frostbite".into()), 4),
+            ]),
+        );
+        let mut compound = nbt::Map::new();
+        compound.insert(
+            "id".into(),
+            nbt::Value::String("minecraft:iron_sword".into()),
+        );
+        compound.insert("tag".into(), nbt::Value::Compound(tag));
+
+        let sword = Item::from_nbt_value(&nbt::Value::Compound(compound));
+        assert_eq!(sword.enchantments(), &[(Enchantment::Sharpness, 2)]);
+        let value = sword.to_nbt_value(version("1.16.5")).unwrap();
+        let enchantments = nbt_value_lookup_list(&value, "tag/Enchantments").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&enchantments[1], "id").as_deref(),
+            Some("othermod:frostbite")
+        );
+        assert_eq!(Item::from_nbt_value(&value), sword);
+
+        let value = sword.to_nbt_value(version("1.21.7")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_int(
+                &value,
+                "components/minecraft:enchantments/othermod:frostbite"
+            ),
+            Some(4)
+        );
+        assert_eq!(Item::from_nbt_value(&value), sword);
+    }
+
+    #[test]
+    fn tag_data_gets_the_format_of_the_version() {
+        let sword = sword_of_doom();
+
+        let value = sword.to_nbt_value(version("1.12.2")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "tag/display/Name").as_deref(),
+            Some("Sword of Doom")
+        );
+        let enchantments = nbt_value_lookup_list(&value, "tag/ench").unwrap();
+        assert_eq!(nbt_value_lookup_short(&enchantments[0], "id"), Some(21));
+        let modifiers = nbt_value_lookup_list(&value, "tag/AttributeModifiers").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "AttributeName").as_deref(),
+            Some("generic.attackDamage")
+        );
+
+        let value = sword.to_nbt_value(version("1.16.5")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "tag/display/Name").as_deref(),
+            Some(r#"{"text":"Sword of Doom"}"#)
+        );
+        let enchantments = nbt_value_lookup_list(&value, "tag/Enchantments").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&enchantments[0], "id").as_deref(),
+            Some("minecraft:looting")
+        );
+        let modifiers = nbt_value_lookup_list(&value, "tag/AttributeModifiers").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "AttributeName").as_deref(),
+            Some("minecraft:generic.attack_damage")
+        );
+        assert_eq!(
+            nbt_value_lookup_int_array(&modifiers[0], "UUID"),
+            Some(vec![
+                0x0123_4567,
+                0x89ab_cdef_u32 as i32,
+                0xfedc_ba98_u32 as i32,
+                0x7654_3210
+            ])
+        );
+
+        let value = sword
+            .clone()
+            .with_dye_colour(0x0012_3456)
+            .to_nbt_value(version("1.20.5"))
+            .unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "components/minecraft:custom_name").as_deref(),
+            Some(r#"{"text":"Sword of Doom"}"#)
+        );
+        assert_eq!(
+            nbt_value_lookup_int(
+                &value,
+                "components/minecraft:enchantments/levels/minecraft:looting"
+            ),
+            Some(3)
+        );
+        assert_eq!(
+            nbt_value_lookup_int(&value, "components/minecraft:dyed_color/rgb"),
+            Some(0x0012_3456)
+        );
+        let modifiers =
+            nbt_value_lookup_list(&value, "components/minecraft:attribute_modifiers/modifiers")
+                .unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "type").as_deref(),
+            Some("minecraft:generic.attack_damage")
+        );
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "operation").as_deref(),
+            Some("add_value")
+        );
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "name").as_deref(),
+            Some("Doom")
+        );
+
+        let value = sword
+            .with_dye_colour(0x0012_3456)
+            .to_nbt_value(version("1.21.7"))
+            .unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "components/minecraft:custom_name/text").as_deref(),
+            Some("Sword of Doom")
+        );
+        assert_eq!(
+            nbt_value_lookup_int(
+                &value,
+                "components/minecraft:enchantments/minecraft:looting"
+            ),
+            Some(3)
+        );
+        assert_eq!(
+            nbt_value_lookup_int(&value, "components/minecraft:dyed_color"),
+            Some(0x0012_3456)
+        );
+        assert!(nbt_value_lookup(&value, "components/minecraft:unbreakable").is_some());
+        let modifiers =
+            nbt_value_lookup_list(&value, "components/minecraft:attribute_modifiers").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "type").as_deref(),
+            Some("minecraft:attack_damage")
+        );
+        assert_eq!(
+            nbt_value_lookup_string(&modifiers[0], "id").as_deref(),
+            Some("minecraft:01234567-89ab-cdef-fedc-ba9876543210")
+        );
+    }
+
+    #[test]
+    fn unhandled_tags_are_kept() {
+        let mut display = nbt::Map::new();
+        display.insert("Name".into(), nbt::Value::String("Map of Treasure".into()));
+        display.insert("MapColor".into(), nbt::Value::Int(0x00ff_0000));
+        let mut tag = nbt::Map::new();
+        tag.insert("display".into(), nbt::Value::Compound(display));
+        tag.insert("map".into(), nbt::Value::Int(3));
+        let mut compound = nbt::Map::new();
+        compound.insert(
+            "id".into(),
+            nbt::Value::String("minecraft:filled_map".into()),
+        );
+        compound.insert("Damage".into(), nbt::Value::Short(0));
+        compound.insert("tag".into(), nbt::Value::Compound(tag));
+
+        let map = Item::from_nbt_value(&nbt::Value::Compound(compound));
+        assert_eq!(map.custom_name().as_deref(), Some("Map of Treasure"));
+        let value = map.to_nbt_value(version("1.16.5")).unwrap();
+        assert_eq!(nbt_value_lookup_int(&value, "tag/map"), Some(3));
+        assert_eq!(
+            nbt_value_lookup_int(&value, "tag/display/MapColor"),
+            Some(0x00ff_0000)
+        );
+    }
+
+    #[test]
+    fn potions_survive_round_trip() {
+        let potion = Item::new_with_kind(ItemKind::SplashPotion)
+            .with_potion(PotionType::StrongHealing)
+            .with_custom_effect(StatusEffectInstance::new(StatusEffect::Speed, 1, 600))
+            .with_potion_colour(0x0020_80ff);
+        let mut quiet_effect = StatusEffectInstance::new(StatusEffect::Poison, 0, 100);
+        quiet_effect.show_particles = false;
+        let arrow = Item::new_with_kind(ItemKind::TippedArrow)
+            .with_potion(PotionType::Water)
+            .with_custom_effect(quiet_effect);
+        for data_version in &["1.12.2", "1.16.5", "1.20.4", "1.20.5", "1.21.7"] {
+            for item in &[potion.clone(), arrow.clone()] {
+                let value = item.to_nbt_value(version(data_version)).unwrap();
+                assert_eq!(&Item::from_nbt_value(&value), item);
+            }
+        }
+
+        let stew = Item::new_with_kind(ItemKind::Bowl(Some(BowlContents::SuspiciousStew)))
+            .with_stew_effect(StatusEffect::NightVision, 100);
+        for data_version in &["1.14.4", "1.20.4", "1.21.7"] {
+            let value = stew.to_nbt_value(version(data_version)).unwrap();
+            assert_eq!(Item::from_nbt_value(&value), stew);
+        }
+    }
+
+    #[test]
+    fn effects_get_the_format_of_the_version() {
+        let potion = Item::new_with_kind(ItemKind::Potion)
+            .with_potion(PotionType::LongSwiftness)
+            .with_custom_effect(StatusEffectInstance::new(StatusEffect::JumpBoost, 2, 200));
+
+        let value = potion.to_nbt_value(version("1.12.2")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "tag/Potion").as_deref(),
+            Some("minecraft:long_swiftness")
+        );
+        let effects = nbt_value_lookup_list(&value, "tag/CustomPotionEffects").unwrap();
+        assert_eq!(nbt_value_lookup_byte(&effects[0], "Id"), Some(8));
+        assert_eq!(nbt_value_lookup_byte(&effects[0], "Amplifier"), Some(2));
+
+        let value = potion.to_nbt_value(version("1.20.4")).unwrap();
+        let effects = nbt_value_lookup_list(&value, "tag/custom_potion_effects").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&effects[0], "id").as_deref(),
+            Some("minecraft:jump_boost")
+        );
+        assert_eq!(nbt_value_lookup_int(&effects[0], "duration"), Some(200));
+
+        let value = potion.to_nbt_value(version("1.21.7")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&value, "components/minecraft:potion_contents/potion")
+                .as_deref(),
+            Some("minecraft:long_swiftness")
+        );
+        let effects = nbt_value_lookup_list(
+            &value,
+            "components/minecraft:potion_contents/custom_effects",
+        )
+        .unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&effects[0], "id").as_deref(),
+            Some("minecraft:jump_boost")
+        );
+        assert_eq!(nbt_value_lookup(&value, "tag"), None);
+
+        let stew = Item::new_with_kind(ItemKind::Bowl(Some(BowlContents::SuspiciousStew)))
+            .with_stew_effect(StatusEffect::Saturation, 7);
+        let value = stew.to_nbt_value(version("1.21.7")).unwrap();
+        let effects =
+            nbt_value_lookup_list(&value, "components/minecraft:suspicious_stew_effects").unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&effects[0], "id").as_deref(),
+            Some("minecraft:saturation")
+        );
+        assert_eq!(nbt_value_lookup_int(&effects[0], "duration"), Some(7));
+    }
+
+    #[test]
+    fn books_survive_round_trip() {
+        let text = "It was a dark and stormy night. ".repeat(30);
+        let written = Item::new_written_book("Stormy", "Anonymous", &text)
+            .with_generation(BookGeneration::CopyOfOriginal);
+        assert!(written.pages().len() > 1);
+        assert_eq!(written.pages().concat(), text);
+        let writable = Item::new_writable_book("Shopping list:\nBread\nMilk");
+        assert_eq!(writable.pages(), vec!["Shopping list:\nBread\nMilk"]);
+
+        for data_version in &["1.12.2", "1.16.5", "1.20.5", "1.21.7"] {
+            for item in &[written.clone(), writable.clone()] {
+                let value = item.to_nbt_value(version(data_version)).unwrap();
+                assert_eq!(&Item::from_nbt_value(&value), item);
+            }
+        }
+
+        let value = writable.to_nbt_value(version("1.16.5")).unwrap();
+        let pages = nbt_value_lookup_list(&value, "tag/pages").unwrap();
+        assert_eq!(
+            pages[0],
+            nbt::Value::String("Shopping list:\nBread\nMilk".into())
+        );
+        let value = written.to_nbt_value(version("1.16.5")).unwrap();
+        assert_eq!(nbt_value_lookup_int(&value, "tag/generation"), Some(1));
+        assert_eq!(
+            nbt_value_lookup_string(&value, "tag/title").as_deref(),
+            Some("Stormy")
+        );
+
+        let value = writable.to_nbt_value(version("1.21.7")).unwrap();
+        let pages =
+            nbt_value_lookup_list(&value, "components/minecraft:writable_book_content/pages")
+                .unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(&pages[0], "raw").as_deref(),
+            Some("Shopping list:\nBread\nMilk")
+        );
+        let value = written.to_nbt_value(version("1.21.7")).unwrap();
+        assert_eq!(
+            nbt_value_lookup_string(
+                &value,
+                "components/minecraft:written_book_content/title/raw"
+            )
+            .as_deref(),
+            Some("Stormy")
+        );
+        let pages =
+            nbt_value_lookup_list(&value, "components/minecraft:written_book_content/pages")
+                .unwrap();
+        assert!(nbt_value_lookup_string(&pages[0], "raw/text").is_some());
+    }
+}
@@ -0,0 +1,199 @@
+//! Change journal, for undoing and redoing edits of a world excerpt.
+//!
+//! The journal records the blocks changed by each edit, as pairs of the block
+//! before and after the change, along with the entities and scheduled ticks
+//! added. Edits are grouped by checkpoints; undo and redo work on one group at
+//! a time.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::block::Block;
+use crate::block_cuboid::BlockCuboid;
+use crate::entity::Entity;
+use crate::scheduled_tick::ScheduledTick;
+
+/// A position in the edit history of a [`WorldExcerpt`](crate::world_excerpt::WorldExcerpt).
+///
+/// Returned from [`WorldExcerpt::checkpoint()`](crate::world_excerpt::WorldExcerpt::checkpoint),
+/// and used for rolling back (or forward) to the state the excerpt had at that point.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Checkpoint {
+    /// Identifier of the journal the checkpoint was taken from.
+    journal: u64,
+    /// Identifier of the last edit group done at the time of the checkpoint,
+    /// or `None` if no edits had been done.
+    last_edit: Option<u64>,
+}
+
+/// Identifier of the next journal created, so that checkpoints of one journal
+/// are never mistaken for those of another, e.g. one restarted by a rotation.
+static NEXT_JOURNAL_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Clone, Debug)]
+enum Change {
+    Block {
+        at: (usize, usize, usize),
+        before: Block,
+        after: Block,
+    },
+    EntityAdded(Entity),
+    TickAdded(ScheduledTick),
+}
+
+#[derive(Clone, Debug)]
+struct Edit {
+    id: u64,
+    changes: Vec<Change>,
+}
+
+/// The parts of a world excerpt changed by undoing and redoing edits.
+pub(crate) struct Contents<'a> {
+    pub blocks: &'a mut BlockCuboid,
+    pub entities: &'a mut Vec<Entity>,
+    pub scheduled_ticks: &'a mut Vec<ScheduledTick>,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Journal {
+    id: u64,
+    done: Vec<Edit>,
+    pending: Vec<Change>,
+    undone: Vec<Edit>,
+    next_id: u64,
+}
+
+impl Journal {
+    pub fn new() -> Self {
+        Self {
+            id: NEXT_JOURNAL_ID.fetch_add(1, Ordering::Relaxed),
+            done: Vec::new(),
+            pending: Vec::new(),
+            undone: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// Records that the block at `at` was changed from `before` to `after`.
+    pub fn record(&mut self, at: (usize, usize, usize), before: &Block, after: &Block) {
+        if before != after {
+            self.push(Change::Block {
+                at,
+                before: before.clone(),
+                after: after.clone(),
+            });
+        }
+    }
+
+    /// Records that `entity` was added.
+    pub fn record_entity_added(&mut self, entity: &Entity) {
+        self.push(Change::EntityAdded(entity.clone()));
+    }
+
+    /// Records that `tick` was scheduled.
+    pub fn record_tick_added(&mut self, tick: &ScheduledTick) {
+        self.push(Change::TickAdded(tick.clone()));
+    }
+
+    fn push(&mut self, change: Change) {
+        // A new change makes the undone edits unreachable.
+        self.undone.clear();
+        self.pending.push(change);
+    }
+
+    /// Closes the current edit group, and returns a checkpoint for the current state.
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.close_pending();
+        Checkpoint {
+            journal: self.id,
+            last_edit: self.done.last().map(|edit| edit.id),
+        }
+    }
+
+    /// Reverts the most recent edit group. Returns false if there was nothing to undo.
+    pub fn undo(&mut self, contents: &mut Contents) -> bool {
+        self.close_pending();
+        if let Some(edit) = self.done.pop() {
+            for change in edit.changes.iter().rev() {
+                match change {
+                    Change::Block { at, before, .. } => contents.blocks.insert(*at, before.clone()),
+                    Change::EntityAdded(entity) => remove_last(contents.entities, entity),
+                    Change::TickAdded(tick) => remove_last(contents.scheduled_ticks, tick),
+                }
+            }
+            self.undone.push(edit);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Reapplies the most recently undone edit group. Returns false if there was
+    /// nothing to redo.
+    pub fn redo(&mut self, contents: &mut Contents) -> bool {
+        self.close_pending();
+        if let Some(edit) = self.undone.pop() {
+            for change in &edit.changes {
+                match change {
+                    Change::Block { at, after, .. } => contents.blocks.insert(*at, after.clone()),
+                    Change::EntityAdded(entity) => contents.entities.push(entity.clone()),
+                    Change::TickAdded(tick) => contents.scheduled_ticks.push(tick.clone()),
+                }
+            }
+            self.done.push(edit);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Undoes or redoes edit groups until the state at `checkpoint` is reached.
+    ///
+    /// Returns false, without changing anything, if the checkpoint is no longer
+    /// reachable, e.g. because it was undone and then replaced by other edits,
+    /// or because it was taken from another journal.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint, contents: &mut Contents) -> bool {
+        self.close_pending();
+        if checkpoint.journal != self.id {
+            return false;
+        }
+        match checkpoint.last_edit {
+            None => {
+                while self.undo(contents) {}
+                true
+            }
+            Some(id) => {
+                if self.done.iter().any(|edit| edit.id == id) {
+                    while self.done.last().map(|edit| edit.id) != Some(id) {
+                        self.undo(contents);
+                    }
+                    true
+                } else if self.undone.iter().any(|edit| edit.id == id) {
+                    while self.done.last().map(|edit| edit.id) != Some(id) {
+                        self.redo(contents);
+                    }
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    fn close_pending(&mut self) {
+        if !self.pending.is_empty() {
+            let changes = std::mem::take(&mut self.pending);
+            self.done.push(Edit {
+                id: self.next_id,
+                changes,
+            });
+            self.next_id += 1;
+        }
+    }
+}
+
+/// Removes the last element of `list` that equals `element`, if any.
+fn remove_last<T: PartialEq>(list: &mut Vec<T>, element: &T) {
+    if let Some(index) = list.iter().rposition(|candidate| candidate == element) {
+        list.remove(index);
+    }
+}
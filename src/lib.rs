@@ -5,24 +5,42 @@ extern crate static_assertions;
 
 pub mod arguments;
 pub mod biome;
+pub mod bill_of_materials;
 pub mod block;
 mod block_cuboid;
 mod block_entity;
+mod block_state;
 pub mod bounded_ints;
 pub mod bounds;
 mod chunk;
 pub mod colour;
+pub mod connections;
 pub mod coordinates;
 pub mod enchantment;
+pub mod entity;
+pub mod excerpt_diff;
+pub mod export_policy;
 pub mod height_map;
 pub mod inventory;
 pub mod item;
+pub mod journal;
 mod light_cuboid;
+mod lighting;
 pub mod material;
 pub mod mc_version;
 mod nbt_lookup;
 pub mod positioning;
+pub mod potion;
 pub mod region;
+pub mod save_format;
+pub mod save_transaction;
+pub mod scheduled_tick;
+pub mod selection;
+pub mod shape;
+pub mod statistics;
 pub mod status_effect;
+mod text;
+pub mod upgrade;
+pub mod validation;
 mod utils;
 pub mod world_excerpt;
@@ -1,5 +1,6 @@
 use crate::block::light::*;
 use crate::coordinates::BlockCoord;
+use crate::positioning::{Axis2, Rotation};
 
 #[derive(Clone, Debug)]
 pub struct LightCuboid {
@@ -123,6 +124,42 @@ impl LightCuboid {
         cuboid
     }
 
+    /// Returns a copy of the light cuboid rotated around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let (x_dim, z_dim) = rotation.rotated_dim((self.x_dim, self.z_dim));
+        let mut rotated = Self::new((x_dim, self.y_dim, z_dim));
+        for x in 0..self.x_dim {
+            for z in 0..self.z_dim {
+                let (to_x, to_z) = rotation.rotated_position((x, z), (self.x_dim, self.z_dim));
+                for y in 0..self.y_dim as i64 {
+                    if let Some(light_level) = self.light_level_at((x as i64, y, z as i64).into()) {
+                        rotated.set_light_level_at((to_x as i64, y, to_z as i64).into(), light_level);
+                    }
+                }
+            }
+        }
+        rotated
+    }
+
+    /// Returns a copy of the light cuboid mirrored along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        let mut mirrored = Self::new(self.dim());
+        for x in 0..self.x_dim {
+            for z in 0..self.z_dim {
+                let (to_x, to_z) = match axis {
+                    Axis2::X => (self.x_dim - 1 - x, z),
+                    Axis2::Z => (x, self.z_dim - 1 - z),
+                };
+                for y in 0..self.y_dim as i64 {
+                    if let Some(light_level) = self.light_level_at((x as i64, y, z as i64).into()) {
+                        mirrored.set_light_level_at((to_x as i64, y, to_z as i64).into(), light_level);
+                    }
+                }
+            }
+        }
+        mirrored
+    }
+
     fn index(&self, BlockCoord(x, y, z): BlockCoord) -> Option<usize> {
         if x < 0
         || x >= self.x_dim as i64
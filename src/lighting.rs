@@ -0,0 +1,306 @@
+//! Recomputation of block light and sky light levels, by flood filling light
+//! from light emitting blocks and from the sky.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use crate::block::light::LightBlockingProperty;
+use crate::block::Block;
+use crate::block_cuboid::BlockCuboid;
+use crate::coordinates::BlockCoord;
+use crate::light_cuboid::LightCuboid;
+use crate::mc_version::McVersion;
+
+/// Which light blocking properties blocks have.
+///
+/// The light engine was rewritten for Minecraft 1.14, changing how several
+/// blocks (e.g. water, ice and slabs) affect light.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum LightRules {
+    /// Light blocking properties of versions before 1.14.
+    Old,
+    /// Light blocking properties of version 1.14 and later.
+    New,
+}
+
+impl LightRules {
+    pub(crate) fn for_version(version: McVersion) -> Self {
+        if version < McVersion::from_str("1.14").unwrap() {
+            Self::Old
+        } else {
+            Self::New
+        }
+    }
+
+    /// How much light is dimmed by passing into `block`, in addition to the
+    /// normal loss of one light level per block travelled.
+    ///
+    /// Directional blocks (e.g. slabs and stairs) are treated as opaque.
+    /// Blocks with unknown properties are treated as dimming light by one.
+    fn opacity(self, block: &Block) -> u8 {
+        let property = match self {
+            Self::Old => block.light_blocking_property_old(),
+            Self::New => block.light_blocking_property_new(),
+        };
+        match property {
+            LightBlockingProperty::Transparent => 0,
+            LightBlockingProperty::FilterSkyLight | LightBlockingProperty::Unknown => 1,
+            LightBlockingProperty::DoubleFilter => 2,
+            LightBlockingProperty::Directional | LightBlockingProperty::Opaque => MAX_LEVEL,
+        }
+    }
+}
+
+const MAX_LEVEL: u8 = 15;
+
+/// Computes the block light and sky light levels of `blocks`.
+///
+/// `border` gives the block light and sky light levels (in that order) just
+/// outside of the cuboid, by coordinates relative to the cuboid, or `None` where
+/// no light enters from outside. E.g. returning `Some((0, 15))` above the
+/// cuboid, lights it as standing under open sky.
+pub(crate) fn light<F>(
+    blocks: &BlockCuboid,
+    rules: LightRules,
+    border: F,
+) -> (LightCuboid, LightCuboid)
+where
+    F: Fn(BlockCoord) -> Option<(u8, u8)>,
+{
+    let grid = Grid::new(blocks, rules);
+
+    let mut block_light = Flood::new(&grid, false);
+    let mut sky_light = Flood::new(&grid, true);
+
+    for (at, block) in blocks.iter() {
+        if let Some(emission) = block.light_emission() {
+            block_light.raise(at, emission);
+        }
+    }
+
+    for (outside, inside, downwards) in grid.border() {
+        if let Some((block_level, sky_level)) = border(outside) {
+            block_light.enter(inside, block_level, downwards);
+            sky_light.enter(inside, sky_level, downwards);
+        }
+    }
+
+    (block_light.spread(), sky_light.spread())
+}
+
+/// Dimensions and block opacities of the cuboid being lit.
+struct Grid {
+    dim: (usize, usize, usize),
+    opacities: Vec<u8>,
+}
+
+impl Grid {
+    fn new(blocks: &BlockCuboid, rules: LightRules) -> Self {
+        let dim = blocks.dim();
+        let mut opacities = vec![0; dim.0 * dim.1 * dim.2];
+        for (at, block) in blocks.iter() {
+            opacities[Self::index(dim, at)] = rules.opacity(block);
+        }
+        Self { dim, opacities }
+    }
+
+    fn index((_, y_dim, z_dim): (usize, usize, usize), (x, y, z): (usize, usize, usize)) -> usize {
+        (x * z_dim + z) * y_dim + y
+    }
+
+    fn opacity(&self, at: (usize, usize, usize)) -> u8 {
+        self.opacities[Self::index(self.dim, at)]
+    }
+
+    /// The positions next to `at` within the cuboid, each paired with whether
+    /// it is directly below `at`.
+    fn neighbours(&self, (x, y, z): (usize, usize, usize)) -> Vec<((usize, usize, usize), bool)> {
+        let (x_dim, y_dim, z_dim) = self.dim;
+        let mut neighbours = Vec::with_capacity(6);
+        if x > 0 {
+            neighbours.push(((x - 1, y, z), false));
+        }
+        if x + 1 < x_dim {
+            neighbours.push(((x + 1, y, z), false));
+        }
+        if y > 0 {
+            neighbours.push(((x, y - 1, z), true));
+        }
+        if y + 1 < y_dim {
+            neighbours.push(((x, y + 1, z), false));
+        }
+        if z > 0 {
+            neighbours.push(((x, y, z - 1), false));
+        }
+        if z + 1 < z_dim {
+            neighbours.push(((x, y, z + 1), false));
+        }
+        neighbours
+    }
+
+    /// Every position just outside of the cuboid, paired with the position
+    /// inside of the cuboid it faces, and whether light enters downwards.
+    fn border(&self) -> Vec<(BlockCoord, (usize, usize, usize), bool)> {
+        let (x_dim, y_dim, z_dim) = self.dim;
+        let (x_max, y_max, z_max) = (x_dim as i64, y_dim as i64, z_dim as i64);
+        let mut border = Vec::new();
+        if x_dim == 0 || y_dim == 0 || z_dim == 0 {
+            return border;
+        }
+        for x in 0..x_dim {
+            for z in 0..z_dim {
+                let (ox, oz) = (x as i64, z as i64);
+                border.push(((ox, y_max, oz).into(), (x, y_dim - 1, z), true));
+                border.push(((ox, -1, oz).into(), (x, 0, z), false));
+            }
+        }
+        for y in 0..y_dim {
+            for z in 0..z_dim {
+                let (oy, oz) = (y as i64, z as i64);
+                border.push(((-1, oy, oz).into(), (0, y, z), false));
+                border.push(((x_max, oy, oz).into(), (x_dim - 1, y, z), false));
+            }
+            for x in 0..x_dim {
+                let (ox, oy) = (x as i64, y as i64);
+                border.push(((ox, oy, -1).into(), (x, y, 0), false));
+                border.push(((ox, oy, z_max).into(), (x, y, z_dim - 1), false));
+            }
+        }
+        border
+    }
+}
+
+/// Light levels of one kind of light, spreading through a grid.
+struct Flood<'a> {
+    grid: &'a Grid,
+    /// Sky light passes straight down at full strength, through transparent blocks.
+    sky: bool,
+    levels: Vec<u8>,
+    queue: VecDeque<(usize, usize, usize)>,
+}
+
+impl<'a> Flood<'a> {
+    fn new(grid: &'a Grid, sky: bool) -> Self {
+        Self {
+            grid,
+            sky,
+            levels: vec![0; grid.opacities.len()],
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// The light level reaching `to` from a neighbouring block at `level`.
+    fn passed_on(&self, level: u8, to: (usize, usize, usize), downwards: bool) -> u8 {
+        let opacity = self.grid.opacity(to);
+        if self.sky && downwards && level == MAX_LEVEL && opacity == 0 {
+            MAX_LEVEL
+        } else {
+            level.saturating_sub(u8::max(1, opacity))
+        }
+    }
+
+    /// Sets the light level at `at` to `level`, unless it is lit more already.
+    fn raise(&mut self, at: (usize, usize, usize), level: u8) {
+        let index = Grid::index(self.grid.dim, at);
+        let level = u8::min(level, MAX_LEVEL);
+        if level > self.levels[index] {
+            self.levels[index] = level;
+            self.queue.push_back(at);
+        }
+    }
+
+    /// Lets light at `level` enter `at` from just outside of the grid.
+    fn enter(&mut self, at: (usize, usize, usize), level: u8, downwards: bool) {
+        let level = self.passed_on(level, at, downwards);
+        self.raise(at, level);
+    }
+
+    /// Spreads the light to its surroundings, and returns the resulting light levels.
+    fn spread(mut self) -> LightCuboid {
+        while let Some(at) = self.queue.pop_front() {
+            let level = self.levels[Grid::index(self.grid.dim, at)];
+            for (neighbour, downwards) in self.grid.neighbours(at) {
+                let passed_on = self.passed_on(level, neighbour, downwards);
+                self.raise(neighbour, passed_on);
+            }
+        }
+
+        let mut cuboid = LightCuboid::new(self.grid.dim);
+        let (x_dim, y_dim, z_dim) = self.grid.dim;
+        for x in 0..x_dim {
+            for y in 0..y_dim {
+                for z in 0..z_dim {
+                    let level = self.levels[Grid::index(self.grid.dim, (x, y, z))];
+                    cuboid.set_light_level_at((x as i64, y as i64, z as i64).into(), level);
+                }
+            }
+        }
+        cuboid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_sky(y_dim: usize) -> impl Fn(BlockCoord) -> Option<(u8, u8)> {
+        move |at: BlockCoord| {
+            if at.1 == y_dim as i64 {
+                Some((0, 15))
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn sky_light_falls_straight_down_and_spreads_under_roofs() {
+        let mut blocks = BlockCuboid::new_filled((3, 3, 1), Block::Air);
+        // Roof over x = 0 and x = 1
+        blocks.insert((0, 2, 0), Block::Stone);
+        blocks.insert((1, 2, 0), Block::Stone);
+        let (_, sky_light) = light(&blocks, LightRules::Old, open_sky(3));
+
+        assert_eq!(sky_light.light_level_at((2, 0, 0).into()), Some(15));
+        assert_eq!(sky_light.light_level_at((1, 0, 0).into()), Some(14));
+        assert_eq!(sky_light.light_level_at((0, 0, 0).into()), Some(13));
+        assert_eq!(sky_light.light_level_at((0, 2, 0).into()), Some(0));
+    }
+
+    #[test]
+    fn block_light_fades_with_distance() {
+        let mut blocks = BlockCuboid::new_filled((5, 1, 1), Block::Air);
+        blocks.insert((0, 0, 0), Block::Glowstone);
+        blocks.insert((3, 0, 0), Block::Stone);
+        let (block_light, sky_light) = light(&blocks, LightRules::New, |_| None);
+
+        assert_eq!(block_light.light_level_at((0, 0, 0).into()), Some(15));
+        assert_eq!(block_light.light_level_at((2, 0, 0).into()), Some(13));
+        assert_eq!(block_light.light_level_at((3, 0, 0).into()), Some(0));
+        assert_eq!(block_light.light_level_at((4, 0, 0).into()), Some(0));
+        assert_eq!(sky_light.light_level_at((2, 0, 0).into()), Some(0));
+    }
+
+    #[test]
+    fn border_light_enters_from_the_sides() {
+        let blocks = BlockCuboid::new_filled((3, 1, 1), Block::Air);
+        let border = |at: BlockCoord| if at.0 == -1 { Some((10, 12)) } else { None };
+        let (block_light, sky_light) = light(&blocks, LightRules::New, border);
+
+        assert_eq!(block_light.light_level_at((0, 0, 0).into()), Some(9));
+        assert_eq!(block_light.light_level_at((2, 0, 0).into()), Some(7));
+        assert_eq!(sky_light.light_level_at((2, 0, 0).into()), Some(9));
+    }
+
+    #[test]
+    fn water_dims_sky_light() {
+        let mut blocks = BlockCuboid::new_filled((1, 4, 1), Block::WaterSource);
+        blocks.insert((0, 3, 0), Block::Air);
+        let (_, old) = light(&blocks, LightRules::Old, open_sky(4));
+        let (_, new) = light(&blocks, LightRules::New, open_sky(4));
+
+        assert_eq!(old.light_level_at((0, 3, 0).into()), Some(15));
+        assert_eq!(old.light_level_at((0, 1, 0).into()), Some(11));
+        assert_eq!(new.light_level_at((0, 1, 0).into()), Some(13));
+    }
+}
@@ -1,10 +1,11 @@
 //! For describing material variants of blocks and items.
 
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 
 /// Materials for Armour.
 //TODO add descriptions for when materials were first introduced
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ArmourMaterial {
     Chainmail,
     Diamond,
@@ -61,7 +62,7 @@ impl TryFrom<Material> for ArmourMaterial {
 /// // From 20w15a
 /// mcprogedit::material::ButtonMaterial::PolishedBlackstone;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ButtonMaterial {
     Acacia,
     Birch,
@@ -107,7 +108,7 @@ impl TryFrom<Material> for ButtonMaterial {
 /// mcprogedit::material::CoralMaterial::Horn;
 /// mcprogedit::material::CoralMaterial::Tube;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum CoralMaterial {
     /// Purple coral variant
     Bubble,
@@ -175,7 +176,7 @@ impl TryFrom<Material> for CoralMaterial {
 /// mcprogedit::material::DoorMaterial::Crimson; // Door, Trapdoor
 /// mcprogedit::material::DoorMaterial::Warped;  // Door, Trapdoor
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum DoorMaterial {
     Acacia,
     Birch,
@@ -235,7 +236,7 @@ impl TryFrom<Material> for DoorMaterial {
 /// mcprogedit::material::FenceMaterial::Crimson;
 /// mcprogedit::material::FenceMaterial::Warped;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum FenceMaterial {
     Acacia,
     Birch,
@@ -269,7 +270,7 @@ impl TryFrom<Material> for FenceMaterial {
 
 /// Materials for Horse Armor.
 //TODO add descriptions for when materials were first introduced
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum HorseArmorMaterial {
     Gold,
     Iron,
@@ -303,7 +304,7 @@ impl TryFrom<Material> for HorseArmorMaterial {
 /// // From 20w06a
 /// mcprogedit::material::IngotMaterial::Netherite;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum IngotMaterial {
     Gold,
     Iron,
@@ -346,7 +347,7 @@ impl TryFrom<Material> for IngotMaterial {
 /// mcprogedit::material::LeavesMaterial::Acacia;
 /// mcprogedit::material::LeavesMaterial::DarkOak;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum LeavesMaterial {
     Acacia,
     Birch,
@@ -391,7 +392,7 @@ impl TryFrom<WoodMaterial> for LeavesMaterial {
 /// All materials.
 ///
 /// Convertible to and from all other material types.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Material {
     Acacia,
     Andesite,
@@ -452,6 +453,71 @@ pub enum Material {
     Wood,
 }
 
+impl Material {
+    /// Returns the name of the material in snake case, e.g. "dark_oak".
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Acacia => "acacia",
+            Self::Andesite => "andesite",
+            Self::Bamboo => "bamboo",
+            Self::Beetroot => "beetroot",
+            Self::Birch => "birch",
+            Self::Blackstone => "blackstone",
+            Self::Brain => "brain",
+            Self::Brick => "brick",
+            Self::Bubble => "bubble",
+            Self::Chainmail => "chainmail",
+            Self::Cobblestone => "cobblestone",
+            Self::Crimson => "crimson",
+            Self::CutRedSandstone => "cut_red_sandstone",
+            Self::CutSandstone => "cut_sandstone",
+            Self::DarkOak => "dark_oak",
+            Self::DarkPrismarine => "dark_prismarine",
+            Self::Diamond => "diamond",
+            Self::Diorite => "diorite",
+            Self::EndStoneBrick => "end_stone_brick",
+            Self::Fire => "fire",
+            Self::Gold => "gold",
+            Self::Granite => "granite",
+            Self::Horn => "horn",
+            Self::Iron => "iron",
+            Self::Jungle => "jungle",
+            Self::Leather => "leather",
+            Self::Melon => "melon",
+            Self::MossyCobblestone => "mossy_cobblestone",
+            Self::MossyStoneBrick => "mossy_stone_brick",
+            Self::NetherBrick => "nether_brick",
+            Self::Netherite => "netherite",
+            Self::Oak => "oak",
+            Self::PetrifiedOak => "petrified_oak",
+            Self::PolishedAndesite => "polished_andesite",
+            Self::PolishedBlackstone => "polished_blackstone",
+            Self::PolishedBlackstoneBrick => "polished_blackstone_brick",
+            Self::PolishedDiorite => "polished_diorite",
+            Self::PolishedGranite => "polished_granite",
+            Self::Prismarine => "prismarine",
+            Self::PrismarineBrick => "prismarine_brick",
+            Self::Pumpkin => "pumpkin",
+            Self::Purpur => "purpur",
+            Self::Quartz => "quartz",
+            Self::RedNetherBrick => "red_nether_brick",
+            Self::RedSandstone => "red_sandstone",
+            Self::Sandstone => "sandstone",
+            Self::SmoothQuartz => "smooth_quartz",
+            Self::SmoothRedSandstone => "smooth_red_sandstone",
+            Self::SmoothSandstone => "smooth_sandstone",
+            Self::SmoothStone => "smooth_stone",
+            Self::Spruce => "spruce",
+            Self::Stone => "stone",
+            Self::StoneBrick => "stone_brick",
+            Self::Tube => "tube",
+            Self::Warped => "warped",
+            Self::Wheat => "wheat",
+            Self::Wood => "wood",
+        }
+    }
+}
+
 impl From<ArmourMaterial> for Material {
     fn from(item: ArmourMaterial) -> Self {
         match item {
@@ -767,7 +833,7 @@ impl From<WoodMaterial> for Material {
 /// // Since 16w50a
 /// mcprogedit::material::NuggetMaterial::Iron;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum NuggetMaterial {
     Gold,
     Iron,
@@ -818,7 +884,7 @@ impl TryFrom<Material> for NuggetMaterial {
 /// // From 20w15a
 /// mcprogedit::material::PressurePlateMaterial::PolishedBlackstone;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum PressurePlateMaterial {
     Acacia,
     Birch,
@@ -879,7 +945,7 @@ impl TryFrom<Material> for PressurePlateMaterial {
 /// mcprogedit::material::SaplingMaterial::Acacia;
 /// mcprogedit::material::SaplingMaterial::DarkOak;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SaplingMaterial {
     Acacia,
     Bamboo,
@@ -924,7 +990,7 @@ impl TryFrom<Material> for SaplingMaterial {
 /// // From 15w31a
 /// mcprogedit::material::SeedMaterial::Beetroot;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SeedMaterial {
     Beetroot,
     Melon,
@@ -1036,7 +1102,7 @@ impl TryFrom<Material> for SeedMaterial {
 /// mcprogedit::material::SlabMaterial::PolishedBlackstone;
 /// mcprogedit::material::SlabMaterial::PolishedBlackstoneBrick;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SlabMaterial {
     Acacia,
     Andesite,
@@ -1222,7 +1288,7 @@ impl TryFrom<Material> for SlabMaterial {
 /// mcprogedit::material::StairMaterial::PolishedBlackstone;
 /// mcprogedit::material::StairMaterial::PolishedBlackstoneBrick;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum StairMaterial {
     Acacia,
     Andesite,
@@ -1325,7 +1391,7 @@ impl TryFrom<Material> for StairMaterial {
 /// // From 20w06a
 /// mcprogedit::material::ToolMaterial::Netherite;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ToolMaterial {
     Diamond,
     Gold,
@@ -1383,7 +1449,7 @@ impl TryFrom<Material> for ToolMaterial {
 /// mcprogedit::material::WallMaterial::PolishedBlackstone;
 /// mcprogedit::material::WallMaterial::PolishedBlackstoneBrick;
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum WallMaterial {
     Andesite,
     Blackstone,
@@ -1497,7 +1563,7 @@ impl TryFrom<Material> for WallMaterial {
 /// mcprogedit::material::WoodMaterial::Crimson; // FenceGate, Log, Planks, Sign
 /// mcprogedit::material::WoodMaterial::Warped;  // FenceGate, Log, Planks, Sign
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum WoodMaterial {
     Acacia,
     Birch,
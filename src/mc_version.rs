@@ -671,6 +671,16 @@ impl McVersion {
     }
 }
 
+impl std::fmt::Display for McVersion {
+    /// Formats the version name, or the data version for unknown versions.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match ID2STR.get(&self.id) {
+            Some(name) => write!(f, "{}", name),
+            None => write!(f, "data version {}", self.id),
+        }
+    }
+}
+
 impl std::str::FromStr for McVersion {
     type Err = ();
 
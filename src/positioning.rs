@@ -1,8 +1,11 @@
 //! For describing positioning of blocks within their voxel (placement, rotation, etc.)
 
+use serde_derive::{Deserialize, Serialize};
 use std::convert::TryFrom;
 use thiserror::Error;
 
+use crate::coordinates::BlockCoord;
+
 // TODO Consider adding door placement data structure to this file...
 // Door (8) - hinged at one of 4 corners, + pointing in one of 2 directions from that corner
 //
@@ -23,7 +26,7 @@ pub enum DirectionError {
 /// Bells can be rotated in four directions. On top of that they can hang
 /// form the block above, be mounted to one side, hang between two blocks
 /// (one on either side), or be mounted on the block below.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum BellPosition {
     UpEast,
     UpNorth,
@@ -43,10 +46,68 @@ pub enum BellPosition {
     DownWest,
 }
 
+impl BellPosition {
+    /// Returns the position after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let (attachment, facing) = self.parts();
+        Self::from_parts(attachment, facing.rotated(rotation))
+    }
+
+    /// Returns the position after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        let (attachment, facing) = self.parts();
+        Self::from_parts(attachment, facing.mirrored(axis))
+    }
+
+    /// Splits the position into how the bell is attached (0 through 3 for
+    /// Up, Side, DoubleSide and Down) and the direction it is facing.
+    fn parts(&self) -> (u8, Surface4) {
+        match self {
+            Self::UpEast => (0, Surface4::East),
+            Self::UpNorth => (0, Surface4::North),
+            Self::UpSouth => (0, Surface4::South),
+            Self::UpWest => (0, Surface4::West),
+            Self::SideEast => (1, Surface4::East),
+            Self::SideNorth => (1, Surface4::North),
+            Self::SideSouth => (1, Surface4::South),
+            Self::SideWest => (1, Surface4::West),
+            Self::DoubleSideEast => (2, Surface4::East),
+            Self::DoubleSideNorth => (2, Surface4::North),
+            Self::DoubleSideSouth => (2, Surface4::South),
+            Self::DoubleSideWest => (2, Surface4::West),
+            Self::DownEast => (3, Surface4::East),
+            Self::DownNorth => (3, Surface4::North),
+            Self::DownSouth => (3, Surface4::South),
+            Self::DownWest => (3, Surface4::West),
+        }
+    }
+
+    fn from_parts(attachment: u8, facing: Surface4) -> Self {
+        match (attachment, facing) {
+            (0, Surface4::East) => Self::UpEast,
+            (0, Surface4::North) => Self::UpNorth,
+            (0, Surface4::South) => Self::UpSouth,
+            (0, Surface4::West) => Self::UpWest,
+            (1, Surface4::East) => Self::SideEast,
+            (1, Surface4::North) => Self::SideNorth,
+            (1, Surface4::South) => Self::SideSouth,
+            (1, Surface4::West) => Self::SideWest,
+            (2, Surface4::East) => Self::DoubleSideEast,
+            (2, Surface4::North) => Self::DoubleSideNorth,
+            (2, Surface4::South) => Self::DoubleSideSouth,
+            (2, Surface4::West) => Self::DoubleSideWest,
+            (_, Surface4::East) => Self::DownEast,
+            (_, Surface4::North) => Self::DownNorth,
+            (_, Surface4::South) => Self::DownSouth,
+            (_, Surface4::West) => Self::DownWest,
+        }
+    }
+}
+
 /// All directions.
 ///
 /// Convertible to and from direction, edge and surface data types.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Direction {
     Down,
     DownEast,
@@ -77,6 +138,23 @@ pub enum Direction {
 }
 
 impl Direction {
+    /// Returns the offset to the neighbouring block in this direction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the direction is not one of the six sides of a block.
+    pub fn offset(&self) -> BlockCoord {
+        match self {
+            Self::Down => BlockCoord(0, -1, 0),
+            Self::Up => BlockCoord(0, 1, 0),
+            Self::East => BlockCoord(1, 0, 0),
+            Self::West => BlockCoord(-1, 0, 0),
+            Self::North => BlockCoord(0, 0, -1),
+            Self::South => BlockCoord(0, 0, 1),
+            _ => panic!("Not a side direction: {:?}", self),
+        }
+    }
+
     /// Returns an instance of the opposite direction.
     pub fn opposite(&self) -> Self {
         match self {
@@ -108,6 +186,88 @@ impl Direction {
             Self::WestSouthWest => Self::EastNorthEast,
         }
     }
+
+    /// Returns the direction after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let mut direction = *self;
+        for _ in 0..rotation.clockwise_quarter_turns() {
+            direction = direction.turned_clockwise();
+        }
+        direction
+    }
+
+    /// Returns the direction after mirroring along the given axis.
+    ///
+    /// Mirroring along the X axis swaps east and west,
+    /// mirroring along the Z axis swaps north and south.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        match axis {
+            Axis2::X => self.mirrored_east_west(),
+            Axis2::Z => self.mirrored_east_west().rotated(Rotation::Clockwise180),
+        }
+    }
+
+    fn turned_clockwise(&self) -> Self {
+        match self {
+            Self::Down => Self::Down,
+            Self::DownEast => Self::DownSouth,
+            Self::DownNorth => Self::DownEast,
+            Self::DownSouth => Self::DownWest,
+            Self::DownWest => Self::DownNorth,
+            Self::East => Self::South,
+            Self::EastNorthEast => Self::SouthSouthEast,
+            Self::EastSouthEast => Self::SouthSouthWest,
+            Self::North => Self::East,
+            Self::NorthEast => Self::SouthEast,
+            Self::NorthNorthEast => Self::EastSouthEast,
+            Self::NorthNorthWest => Self::EastNorthEast,
+            Self::NorthWest => Self::NorthEast,
+            Self::South => Self::West,
+            Self::SouthEast => Self::SouthWest,
+            Self::SouthSouthEast => Self::WestSouthWest,
+            Self::SouthSouthWest => Self::WestNorthWest,
+            Self::SouthWest => Self::NorthWest,
+            Self::Up => Self::Up,
+            Self::UpEast => Self::UpSouth,
+            Self::UpNorth => Self::UpEast,
+            Self::UpSouth => Self::UpWest,
+            Self::UpWest => Self::UpNorth,
+            Self::West => Self::North,
+            Self::WestNorthWest => Self::NorthNorthEast,
+            Self::WestSouthWest => Self::NorthNorthWest,
+        }
+    }
+
+    fn mirrored_east_west(&self) -> Self {
+        match self {
+            Self::Down => Self::Down,
+            Self::DownEast => Self::DownWest,
+            Self::DownNorth => Self::DownNorth,
+            Self::DownSouth => Self::DownSouth,
+            Self::DownWest => Self::DownEast,
+            Self::East => Self::West,
+            Self::EastNorthEast => Self::WestNorthWest,
+            Self::EastSouthEast => Self::WestSouthWest,
+            Self::North => Self::North,
+            Self::NorthEast => Self::NorthWest,
+            Self::NorthNorthEast => Self::NorthNorthWest,
+            Self::NorthNorthWest => Self::NorthNorthEast,
+            Self::NorthWest => Self::NorthEast,
+            Self::South => Self::South,
+            Self::SouthEast => Self::SouthWest,
+            Self::SouthSouthEast => Self::SouthSouthWest,
+            Self::SouthSouthWest => Self::SouthSouthEast,
+            Self::SouthWest => Self::SouthEast,
+            Self::Up => Self::Up,
+            Self::UpEast => Self::UpWest,
+            Self::UpNorth => Self::UpNorth,
+            Self::UpSouth => Self::UpSouth,
+            Self::UpWest => Self::UpEast,
+            Self::West => Self::East,
+            Self::WestNorthWest => Self::EastNorthEast,
+            Self::WestSouthWest => Self::EastSouthEast,
+        }
+    }
 }
 
 impl Default for Direction {
@@ -264,7 +424,7 @@ impl From<SurfaceRotation12> for Direction {
 
 /// Describes the rotation of blocks or entities that can be positioned in
 /// 16 different directions, by what direction they are facing.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Direction16 {
     South = 0,
     SouthSouthWest = 1,
@@ -306,6 +466,21 @@ impl Direction16 {
             Self::WestSouthWest => Self::EastNorthEast,
         }
     }
+
+    /// Returns the direction after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let number = u8::from(*self) + 4 * rotation.clockwise_quarter_turns();
+        Self::from((number % 16) as i8)
+    }
+
+    /// Returns the direction after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        let number = u8::from(*self);
+        match axis {
+            Axis2::X => Self::from(((16 - number) % 16) as i8),
+            Axis2::Z => Self::from(((24 - number) % 16) as i8),
+        }
+    }
 }
 
 impl Default for Direction16 {
@@ -387,7 +562,7 @@ impl TryFrom<Direction> for Direction16 {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct DirectionFlags6 {
     pub east: bool,
     pub down: bool,
@@ -397,6 +572,39 @@ pub struct DirectionFlags6 {
     pub west: bool,
 }
 
+impl DirectionFlags6 {
+    /// Returns the flags after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        let mut flags = *self;
+        for _ in 0..rotation.clockwise_quarter_turns() {
+            flags = Self {
+                east: flags.north,
+                south: flags.east,
+                west: flags.south,
+                north: flags.west,
+                ..flags
+            };
+        }
+        flags
+    }
+
+    /// Returns the flags after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        match axis {
+            Axis2::X => Self {
+                east: self.west,
+                west: self.east,
+                ..*self
+            },
+            Axis2::Z => Self {
+                north: self.south,
+                south: self.north,
+                ..*self
+            },
+        }
+    }
+}
+
 /// Position and rotation for blocks that can either be put on top of the block below,
 /// or attached to the side of a horizontally adjacent block.
 ///
@@ -413,7 +621,7 @@ pub struct DirectionFlags6 {
 /// // A block attached to its neighbouring block to the south (i.e. facing north).
 /// let placement = WallOrRotatedOnFloor::Wall(Surface4::South);
 /// ```
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum WallOrRotatedOnFloor {
     /// Block rests on top of the block below it, and may face 16 different directions.
     Floor(Direction16),
@@ -429,6 +637,22 @@ impl WallOrRotatedOnFloor {
     pub fn is_on_wall(&self) -> bool {
         !self.is_on_floor()
     }
+
+    /// Returns the placement after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        match self {
+            Self::Floor(direction) => Self::Floor(direction.rotated(rotation)),
+            Self::Wall(surface) => Self::Wall(surface.rotated(rotation)),
+        }
+    }
+
+    /// Returns the placement after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        match self {
+            Self::Floor(direction) => Self::Floor(direction.mirrored(axis)),
+            Self::Wall(surface) => Self::Wall(surface.mirrored(axis)),
+        }
+    }
 }
 
 impl Default for WallOrRotatedOnFloor {
@@ -438,7 +662,7 @@ impl Default for WallOrRotatedOnFloor {
 }
 
 /// Alignment along one of the 2 horizontal axes.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Axis2 {
     /// East-West orientation
     X,
@@ -461,8 +685,19 @@ impl Default for Axis2 {
     }
 }
 
+impl Axis2 {
+    /// Returns the alignment after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        match (self, rotation.clockwise_quarter_turns() % 2) {
+            (Self::X, 1) => Self::Z,
+            (Self::Z, 1) => Self::X,
+            _ => *self,
+        }
+    }
+}
+
 /// Alignment along an axis.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Axis3 {
     /// East-West orientation
     X,
@@ -489,8 +724,19 @@ impl Default for Axis3 {
     }
 }
 
+impl Axis3 {
+    /// Returns the alignment after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        match (self, rotation.clockwise_quarter_turns() % 2) {
+            (Self::X, 1) => Self::Z,
+            (Self::Z, 1) => Self::X,
+            _ => *self,
+        }
+    }
+}
+
 /// The top and bottom surfaces of the voxel volume populated by the block.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Surface2 {
     Down,
     Up,
@@ -515,7 +761,7 @@ impl TryFrom<Direction> for Surface2 {
 }
 
 /// The four side surfaces of the voxel volume populated by the block.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Surface4 {
     East,
     North,
@@ -529,6 +775,18 @@ impl Default for Surface4 {
     }
 }
 
+impl Surface4 {
+    /// Returns the surface after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        Self::try_from(Direction::from(*self).rotated(rotation)).unwrap()
+    }
+
+    /// Returns the surface after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        Self::try_from(Direction::from(*self).mirrored(axis)).unwrap()
+    }
+}
+
 impl TryFrom<Direction> for Surface4 {
     type Error = DirectionError;
 
@@ -544,7 +802,7 @@ impl TryFrom<Direction> for Surface4 {
 }
 
 /// The bottom and four side surfaces of the voxel volume populated by the block..
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Surface5 {
     Down,
     East,
@@ -559,6 +817,18 @@ impl Default for Surface5 {
     }
 }
 
+impl Surface5 {
+    /// Returns the surface after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        Self::try_from(Direction::from(*self).rotated(rotation)).unwrap()
+    }
+
+    /// Returns the surface after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        Self::try_from(Direction::from(*self).mirrored(axis)).unwrap()
+    }
+}
+
 impl TryFrom<Direction> for Surface5 {
     type Error = DirectionError;
 
@@ -575,7 +845,7 @@ impl TryFrom<Direction> for Surface5 {
 }
 
 /// All six surfaces of the voxel volume populated by the block.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Surface6 {
     Down,
     East,
@@ -591,6 +861,18 @@ impl Default for Surface6 {
     }
 }
 
+impl Surface6 {
+    /// Returns the surface after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        Self::try_from(Direction::from(*self).rotated(rotation)).unwrap()
+    }
+
+    /// Returns the surface after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        Self::try_from(Direction::from(*self).mirrored(axis)).unwrap()
+    }
+}
+
 impl TryFrom<Direction> for Surface6 {
     type Error = DirectionError;
 
@@ -608,7 +890,7 @@ impl TryFrom<Direction> for Surface6 {
 }
 
 /// The four top-most and four bottom-most edges of the voxel volume populated by the block.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum Edge8 {
     DownEast,
     DownNorth,
@@ -626,6 +908,18 @@ impl Default for Edge8 {
     }
 }
 
+impl Edge8 {
+    /// Returns the edge after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        Self::try_from(Direction::from(*self).rotated(rotation)).unwrap()
+    }
+
+    /// Returns the edge after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        Self::try_from(Direction::from(*self).mirrored(axis)).unwrap()
+    }
+}
+
 impl From<i8> for Edge8 {
     fn from(edge_number: i8) -> Self {
         match edge_number {
@@ -677,7 +971,7 @@ impl TryFrom<Direction> for Edge8 {
 
 /// All six surfaces of the voxel volume populated by the block,
 /// with rotation towards a cardinal direction for the Up and Down surfaces.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum SurfaceRotation12 {
     DownFacingEast,
     DownFacingNorth,
@@ -699,6 +993,18 @@ impl Default for SurfaceRotation12 {
     }
 }
 
+impl SurfaceRotation12 {
+    /// Returns the placement after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        Self::try_from(Direction::from(*self).rotated(rotation)).unwrap()
+    }
+
+    /// Returns the placement after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        Self::try_from(Direction::from(*self).mirrored(axis)).unwrap()
+    }
+}
+
 impl TryFrom<Direction> for SurfaceRotation12 {
     type Error = DirectionError;
 
@@ -725,7 +1031,7 @@ impl TryFrom<Direction> for SurfaceRotation12 {
 ///
 /// Please don't ask. The terminology is taken directly from the Minecraft save format.
 /// I have no idea what it means.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum JigsawBlockOrientation {
     DownEast,
     DownNorth,
@@ -740,3 +1046,104 @@ pub enum JigsawBlockOrientation {
     UpWest,
     WestUp,
 }
+
+impl JigsawBlockOrientation {
+    /// Returns the orientation after rotating around the vertical axis.
+    pub fn rotated(&self, rotation: Rotation) -> Self {
+        Self::from_direction(self.direction().rotated(rotation))
+    }
+
+    /// Returns the orientation after mirroring along the given axis.
+    pub fn mirrored(&self, axis: Axis2) -> Self {
+        Self::from_direction(self.direction().mirrored(axis))
+    }
+
+    /// The orientation as a single direction, with the horizontal part being
+    /// the facing for vertical orientations, and the top for horizontal ones.
+    fn direction(&self) -> Direction {
+        match self {
+            Self::DownEast => Direction::DownEast,
+            Self::DownNorth => Direction::DownNorth,
+            Self::DownSouth => Direction::DownSouth,
+            Self::DownWest => Direction::DownWest,
+            Self::EastUp => Direction::East,
+            Self::NorthUp => Direction::North,
+            Self::SouthUp => Direction::South,
+            Self::UpEast => Direction::UpEast,
+            Self::UpNorth => Direction::UpNorth,
+            Self::UpSouth => Direction::UpSouth,
+            Self::UpWest => Direction::UpWest,
+            Self::WestUp => Direction::West,
+        }
+    }
+
+    fn from_direction(direction: Direction) -> Self {
+        match direction {
+            Direction::DownEast => Self::DownEast,
+            Direction::DownNorth => Self::DownNorth,
+            Direction::DownSouth => Self::DownSouth,
+            Direction::DownWest => Self::DownWest,
+            Direction::East => Self::EastUp,
+            Direction::North => Self::NorthUp,
+            Direction::South => Self::SouthUp,
+            Direction::UpEast => Self::UpEast,
+            Direction::UpNorth => Self::UpNorth,
+            Direction::UpSouth => Self::UpSouth,
+            Direction::UpWest => Self::UpWest,
+            Direction::West => Self::WestUp,
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Rotation around the vertical axis, clockwise as seen from above.
+///
+/// # Examples
+/// ```
+/// use mcprogedit::positioning::{Direction, Rotation};
+///
+/// assert_eq!(Direction::North.rotated(Rotation::Clockwise90), Direction::East);
+/// assert_eq!(Direction::UpNorth.rotated(Rotation::Counterclockwise90), Direction::UpWest);
+/// ```
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum Rotation {
+    None,
+    Clockwise90,
+    Clockwise180,
+    Counterclockwise90,
+}
+
+impl Rotation {
+    /// Number of clockwise quarter turns making up the rotation.
+    pub(crate) fn clockwise_quarter_turns(&self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Clockwise90 => 1,
+            Self::Clockwise180 => 2,
+            Self::Counterclockwise90 => 3,
+        }
+    }
+
+    /// Returns where the horizontal position `(x, z)` ends up in a rotated
+    /// area of `(x_dim, z_dim)` blocks, relative to the rotated area.
+    pub(crate) fn rotated_position(
+        &self,
+        (x, z): (usize, usize),
+        (x_dim, z_dim): (usize, usize),
+    ) -> (usize, usize) {
+        match self {
+            Self::None => (x, z),
+            Self::Clockwise90 => (z_dim - 1 - z, x),
+            Self::Clockwise180 => (x_dim - 1 - x, z_dim - 1 - z),
+            Self::Counterclockwise90 => (z, x_dim - 1 - x),
+        }
+    }
+
+    /// Returns the horizontal dimensions of a rotated area of `(x_dim, z_dim)` blocks.
+    pub(crate) fn rotated_dim(&self, (x_dim, z_dim): (usize, usize)) -> (usize, usize) {
+        match self {
+            Self::None | Self::Clockwise180 => (x_dim, z_dim),
+            Self::Clockwise90 | Self::Counterclockwise90 => (z_dim, x_dim),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+//! Potion types, the base of potions, splash potions, lingering potions and
+//! tipped arrows.
+
+use serde_derive::{Deserialize, Serialize};
+
+/// Base type of a potion, giving its default effects and colour.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PotionType {
+    Water,
+    Mundane,
+    Thick,
+    Awkward,
+    NightVision,
+    LongNightVision,
+    Invisibility,
+    LongInvisibility,
+    Leaping,
+    LongLeaping,
+    StrongLeaping,
+    FireResistance,
+    LongFireResistance,
+    Swiftness,
+    LongSwiftness,
+    StrongSwiftness,
+    Slowness,
+    LongSlowness,
+    StrongSlowness,
+    TurtleMaster,
+    LongTurtleMaster,
+    StrongTurtleMaster,
+    WaterBreathing,
+    LongWaterBreathing,
+    Healing,
+    StrongHealing,
+    Harming,
+    StrongHarming,
+    Poison,
+    LongPoison,
+    StrongPoison,
+    Regeneration,
+    LongRegeneration,
+    StrongRegeneration,
+    Strength,
+    LongStrength,
+    StrongStrength,
+    Weakness,
+    LongWeakness,
+    Luck,
+    SlowFalling,
+    LongSlowFalling,
+    WindCharged,
+    Weaving,
+    Oozing,
+    Infested,
+}
+
+impl PotionType {
+    /// Potion type of the given id, with or without namespace.
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim_start_matches("minecraft:");
+        POTION_TYPES
+            .iter()
+            .find(|(_, potion_name)| *potion_name == name)
+            .map(|(potion_type, _)| *potion_type)
+    }
+
+    /// Namespaced id of the potion type.
+    pub(crate) fn name(self) -> String {
+        let name = POTION_TYPES
+            .iter()
+            .find(|(potion_type, _)| *potion_type == self)
+            .map(|(_, name)| *name)
+            .unwrap();
+        format!("minecraft:{}", name)
+    }
+}
+
+/// Every potion type, with its id.
+const POTION_TYPES: [(PotionType, &str); 46] = [
+    (PotionType::Water, "water"),
+    (PotionType::Mundane, "mundane"),
+    (PotionType::Thick, "thick"),
+    (PotionType::Awkward, "awkward"),
+    (PotionType::NightVision, "night_vision"),
+    (PotionType::LongNightVision, "long_night_vision"),
+    (PotionType::Invisibility, "invisibility"),
+    (PotionType::LongInvisibility, "long_invisibility"),
+    (PotionType::Leaping, "leaping"),
+    (PotionType::LongLeaping, "long_leaping"),
+    (PotionType::StrongLeaping, "strong_leaping"),
+    (PotionType::FireResistance, "fire_resistance"),
+    (PotionType::LongFireResistance, "long_fire_resistance"),
+    (PotionType::Swiftness, "swiftness"),
+    (PotionType::LongSwiftness, "long_swiftness"),
+    (PotionType::StrongSwiftness, "strong_swiftness"),
+    (PotionType::Slowness, "slowness"),
+    (PotionType::LongSlowness, "long_slowness"),
+    (PotionType::StrongSlowness, "strong_slowness"),
+    (PotionType::TurtleMaster, "turtle_master"),
+    (PotionType::LongTurtleMaster, "long_turtle_master"),
+    (PotionType::StrongTurtleMaster, "strong_turtle_master"),
+    (PotionType::WaterBreathing, "water_breathing"),
+    (PotionType::LongWaterBreathing, "long_water_breathing"),
+    (PotionType::Healing, "healing"),
+    (PotionType::StrongHealing, "strong_healing"),
+    (PotionType::Harming, "harming"),
+    (PotionType::StrongHarming, "strong_harming"),
+    (PotionType::Poison, "poison"),
+    (PotionType::LongPoison, "long_poison"),
+    (PotionType::StrongPoison, "strong_poison"),
+    (PotionType::Regeneration, "regeneration"),
+    (PotionType::LongRegeneration, "long_regeneration"),
+    (PotionType::StrongRegeneration, "strong_regeneration"),
+    (PotionType::Strength, "strength"),
+    (PotionType::LongStrength, "long_strength"),
+    (PotionType::StrongStrength, "strong_strength"),
+    (PotionType::Weakness, "weakness"),
+    (PotionType::LongWeakness, "long_weakness"),
+    (PotionType::Luck, "luck"),
+    (PotionType::SlowFalling, "slow_falling"),
+    (PotionType::LongSlowFalling, "long_slow_falling"),
+    (PotionType::WindCharged, "wind_charged"),
+    (PotionType::Weaving, "weaving"),
+    (PotionType::Oozing, "oozing"),
+    (PotionType::Infested, "infested"),
+];
@@ -1,28 +1,47 @@
 use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
 
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs::{File, OpenOptions};
 use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::time::SystemTime;
 
 use crate::chunk::RawChunkData;
 use crate::coordinates::ChunkCoord;
+use crate::save_transaction::SaveTransaction;
 
 const SECTOR_LEN_BYTES: usize = 4096;
-
+/// Largest number of sectors a chunk can occupy within the region file. Larger
+/// chunks are stored in a separate chunk file, named "c.<x>.<z>.mcc".
+const MAX_SECTOR_COUNT: usize = 255;
+/// Flag of the compression byte, set for chunks stored in a separate chunk file.
+const EXTERNAL_CHUNK_FLAG: u8 = 0x80;
+
+/// Contents of a region file, holding up to 32 by 32 chunks.
+///
+/// A region may be backed by its file on disk, in which case only the header is
+/// parsed up front, and chunk data is read from the file when first requested.
 pub struct Region {
     chunks: HashMap<ChunkCoord, InternalChunkData>,
+    source: Option<RegionSource>,
 }
 
 impl Region {
     pub fn new() -> Self {
         Self {
             chunks: HashMap::new(),
+            source: None,
         }
     }
 
-    pub fn load_from_file(region_file_path: &std::path::Path) -> Self {
+    /// Opens a region file for random access reading.
+    ///
+    /// Only the 8 KiB header (chunk locations and timestamps) is read. The data of
+    /// individual chunks is fetched from the file when requested through
+    /// [`chunk_data`](Self::chunk_data).
+    pub fn open(region_file_path: &std::path::Path) -> Self {
         let mut region_file = File::open(region_file_path)
             .unwrap_or_else(|_| panic!("Unable to open region file {:?}", region_file_path));
 
@@ -39,68 +58,176 @@ impl Region {
             timestamps.push(region_file.read_u32::<BigEndian>().unwrap());
         }
 
-        let mut chunk_data = Vec::<u8>::with_capacity(0);
-        let _ = region_file.read_to_end(&mut chunk_data);
+        Self {
+            chunks: HashMap::new(),
+            source: Some(RegionSource {
+                path: region_file_path.to_path_buf(),
+                file: RefCell::new(region_file),
+                locations,
+                timestamps,
+            }),
+        }
+    }
 
-        let mut chunks = HashMap::with_capacity(1024);
-        for x in 0..32 {
-            for z in 0..32 {
-                let index = Self::index_from_chunk_coords(ChunkCoord::from((x, z)));
-                let timestamp = timestamps[index];
-                let location = locations[index];
+    /// Reads a region file and all the chunk data within it into memory.
+    pub fn load_from_file(region_file_path: &std::path::Path) -> Self {
+        let mut region = Self::open(region_file_path);
+        region.load_all_chunks();
+        region
+    }
 
-                // If the location field is all 0, then there is no chunk.
-                if location.offset == 0 {
-                    continue;
+    /// Lists the chunks present in the region, with timestamps and sizes.
+    ///
+    /// For a region opened from file, this only uses the region file header and
+    /// the headers of the chunks, and does not read any chunk data. Chunks are
+    /// listed in index order.
+    pub fn existing_chunks(&self) -> Vec<ChunkInfo> {
+        let chunk_file = |index: usize| {
+            self.source
+                .as_ref()
+                .and_then(|source| external_chunk_path(&source.path, index))
+        };
+        (0..1024)
+            .filter_map(|index| {
+                let coordinates = Self::chunk_coords_from_index(index);
+                if let Some(InternalChunkData { timestamp, data }) = self.chunks.get(&coordinates) {
+                    let length = match data {
+                        RawChunkData::Empty => return None,
+                        RawChunkData::GZip(data)
+                        | RawChunkData::ZLib(data)
+                        | RawChunkData::Uncompressed(data) => data.len(),
+                    };
+                    let external = Self::sector_count(length) > MAX_SECTOR_COUNT;
+                    Some(ChunkInfo {
+                        coordinates,
+                        timestamp: *timestamp,
+                        sector_count: if external {
+                            Self::sector_count(0) as u8
+                        } else {
+                            Self::sector_count(length) as u8
+                        },
+                        chunk_file: if external { chunk_file(index) } else { None },
+                    })
+                } else if let Some(source) = &self.source {
+                    let location = source.locations[index];
+                    if location.offset == 0 {
+                        None
+                    } else {
+                        Some(ChunkInfo {
+                            coordinates,
+                            timestamp: source.timestamps[index],
+                            sector_count: location.sector_count,
+                            chunk_file: if source.is_external(index) {
+                                chunk_file(index)
+                            } else {
+                                None
+                            },
+                        })
+                    }
+                } else {
+                    None
                 }
+            })
+            .collect()
+    }
 
-                let data_offset_bytes = location.offset as usize * SECTOR_LEN_BYTES;
-
-                region_file
-                    .seek(SeekFrom::Start(data_offset_bytes as u64))
-                    .unwrap_or_else(|_| panic!("Could not seek to {}", data_offset_bytes));
-
-                // Read length and prepare data buffer
-                let chunk_len = region_file.read_u32::<BigEndian>().unwrap();
-                let mut chunk_data: Vec<u8> = Vec::with_capacity((chunk_len - 1) as usize);
-
-                // Read compression
-                let chunk_compression = region_file.read_u8().unwrap();
+    /// Reads the data of every chunk not yet read from the backing region file.
+    fn load_all_chunks(&mut self) {
+        let unread_chunks = self.unread_chunks();
+        self.chunks.extend(unread_chunks);
+        self.source = None;
+    }
 
-                // check bit 7
-                if (chunk_compression & 0x80) == 0x80 {
-                    todo!("Import data from separate chunk file!");
-                } else if let Ok(chunk_file) = region_file.try_clone() {
-                    chunk_file
-                        .take((chunk_len - 1) as u64)
-                        .read_to_end(&mut chunk_data)
-                        .unwrap();
+    /// Reads and returns the chunks present in the backing region file,
+    /// that have not yet been read or replaced.
+    fn unread_chunks(&self) -> HashMap<ChunkCoord, InternalChunkData> {
+        let mut unread_chunks = HashMap::new();
+        if let Some(source) = &self.source {
+            for index in 0..1024 {
+                let coordinates = Self::chunk_coords_from_index(index);
+                if self.chunks.contains_key(&coordinates) {
+                    continue;
+                }
+                if let Some(data) = source.chunk_data(index) {
+                    unread_chunks.insert(
+                        coordinates,
+                        InternalChunkData {
+                            timestamp: source.timestamps[index],
+                            data,
+                        },
+                    );
                 }
-
-                // Deliver data in the correct compression type
-                let data = match chunk_compression & 3 {
-                    0x01 => RawChunkData::GZip(chunk_data),
-                    0x02 => RawChunkData::ZLib(chunk_data),
-                    0x03 => RawChunkData::Uncompressed(chunk_data),
-                    _ => panic!("Unknown compression format: {}", chunk_compression & 0x03),
-                };
-
-                chunks.insert(
-                    (x as i64, z as i64).into(),
-                    InternalChunkData { timestamp, data },
-                );
             }
         }
-
-        Self { chunks }
+        unread_chunks
     }
 
+    /// Writes the region to the given region file.
+    ///
+    /// Chunks too large for the region file are written to separate chunk files
+    /// in the same directory. Chunk files left over from chunks that now fit in
+    /// the region file are removed.
     pub fn save_to_file(&self, region_file_path: &std::path::Path) {
+        self.write(
+            region_file_path,
+            region_file_path,
+            |chunk_file_path, data| match data {
+                Some(data) => std::fs::write(chunk_file_path, data).unwrap_or_else(|err| {
+                    panic!("Unable to write chunk file {:?}: {}", chunk_file_path, err)
+                }),
+                None => std::fs::remove_file(chunk_file_path).unwrap_or_else(|err| {
+                    panic!("Unable to remove chunk file {:?}: {}", chunk_file_path, err)
+                }),
+            },
+        );
+    }
+
+    /// Writes the region as part of `transaction`, to replace the region file
+    /// at `region_file_path` when the transaction is committed.
+    ///
+    /// Separate chunk files are written and removed through the transaction as
+    /// well, so that they are backed up and replaced together with the region file.
+    pub(crate) fn save_in_transaction(
+        &self,
+        region_file_path: &std::path::Path,
+        transaction: &mut SaveTransaction,
+    ) {
+        let staging_path = transaction.staging_path(region_file_path);
+        self.write(
+            &staging_path,
+            region_file_path,
+            |chunk_file_path, data| match data {
+                Some(data) => {
+                    let staging_path = transaction.staging_path(chunk_file_path);
+                    std::fs::write(&staging_path, data).unwrap_or_else(|err| {
+                        panic!("Unable to write chunk file {:?}: {}", staging_path, err)
+                    })
+                }
+                None => transaction.stage_removal(chunk_file_path),
+            },
+        );
+    }
+
+    /// Writes the region to `output_path`, naming any separate chunk files
+    /// after the region file at `region_file_path`.
+    ///
+    /// `chunk_file` is called with the path and data of each chunk file to
+    /// write, and with the path and `None` of each existing chunk file whose
+    /// chunk is now stored in the region file, or no longer stored at all.
+    fn write<F>(&self, output_path: &Path, region_file_path: &Path, mut chunk_file: F)
+    where
+        F: FnMut(&Path, Option<&[u8]>),
+    {
+        // Chunks not yet read from the backing file must be read before writing,
+        // as the region may be written back to the same file it was opened from.
+        let unread_chunks = self.unread_chunks();
+
         let mut region_file = OpenOptions::new()
             .write(true)
             .create(true)
-            .open(region_file_path)
-            .unwrap_or_else(|_| panic!("Unable to create region file {:?}", region_file_path));
+            .open(output_path)
+            .unwrap_or_else(|_| panic!("Unable to create region file {:?}", output_path));
+        let mut external_indices = Vec::new();
 
         let mut next_chunk_offset_sections = 2;
 
@@ -110,7 +237,7 @@ impl Region {
                 timestamp,
                 data: raw_chunk_data,
             },
-        ) in &self.chunks
+        ) in self.chunks.iter().chain(unread_chunks.iter())
         {
             // Calculate the index for location data and timestamp data for this chunk.
             let index = Self::index_from_chunk_coords(*local_chunk_coordinates);
@@ -123,6 +250,20 @@ impl Region {
                 RawChunkData::Uncompressed(data) => (3, data),
             };
 
+            // Chunks too large for the region file go to a separate chunk file,
+            // leaving only the chunk header in the region file.
+            let (compression, data) = if Self::sector_count(data.len()) > MAX_SECTOR_COUNT {
+                let chunk_file_path =
+                    external_chunk_path(region_file_path, index).unwrap_or_else(|| {
+                        panic!("Unable to name chunk file for {:?}", region_file_path)
+                    });
+                chunk_file(&chunk_file_path, Some(&data[..]));
+                external_indices.push(index);
+                (compression | EXTERNAL_CHUNK_FLAG, &[][..])
+            } else {
+                (compression, &data[..])
+            };
+
             // Figure out the total size of the chunk, when stored in the region file.
             const CHUNK_HEADER_BYTES: usize = 5;
             let chunk_len_bytes = CHUNK_HEADER_BYTES + data.len();
@@ -172,12 +313,30 @@ impl Region {
             // Update chunk offset, for next chunk to be written directly after this one.
             next_chunk_offset_sections += chunk_len_sections as u32;
         }
+
+        // Remove chunk files no longer referred to by the region file.
+        for index in (0..1024).filter(|index| !external_indices.contains(index)) {
+            if let Some(chunk_file_path) = external_chunk_path(region_file_path, index) {
+                if chunk_file_path.is_file() {
+                    chunk_file(&chunk_file_path, None);
+                }
+            }
+        }
     }
 
+    /// Returns the raw data of the chunk at the given region local coordinates.
+    ///
+    /// For a region opened from file, the chunk data is read from the file on demand.
     pub fn chunk_data(&self, local_chunk_coordinates: &ChunkCoord) -> RawChunkData {
         match self.chunks.get(local_chunk_coordinates) {
             Some(chunk) => chunk.data.clone(),
-            None => RawChunkData::Empty,
+            None => self
+                .source
+                .as_ref()
+                .and_then(|source| {
+                    source.chunk_data(Self::index_from_chunk_coords(*local_chunk_coordinates))
+                })
+                .unwrap_or(RawChunkData::Empty),
         }
     }
 
@@ -192,7 +351,14 @@ impl Region {
         );
     }
 
-    fn _chunk_coords_from_index(index: usize) -> ChunkCoord {
+    /// Number of 4 KiB sectors needed for storing chunk data of the given length.
+    fn sector_count(data_len_bytes: usize) -> usize {
+        const CHUNK_HEADER_BYTES: usize = 5;
+        let chunk_len_bytes = CHUNK_HEADER_BYTES + data_len_bytes;
+        chunk_len_bytes.div_ceil(SECTOR_LEN_BYTES)
+    }
+
+    fn chunk_coords_from_index(index: usize) -> ChunkCoord {
         ChunkCoord::from((index as i64 % 32, index as i64 / 32))
     }
 
@@ -207,6 +373,123 @@ impl Default for Region {
     }
 }
 
+/// Summary information about a chunk stored in a region.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkInfo {
+    /// Coordinates of the chunk, relative to the region.
+    pub coordinates: ChunkCoord,
+    /// Time of last modification, in seconds since the Unix epoch.
+    pub timestamp: u32,
+    /// Number of 4 KiB sectors the chunk occupies in the region file.
+    ///
+    /// For a chunk stored in a separate chunk file, this only covers the chunk
+    /// header left in the region file.
+    pub sector_count: u8,
+    /// Separate chunk file holding the data of a chunk too large for the region
+    /// file, where the region is backed by a region file.
+    pub chunk_file: Option<PathBuf>,
+}
+
+/// Backing region file, for reading chunk data on demand.
+struct RegionSource {
+    path: PathBuf,
+    file: RefCell<File>,
+    locations: Vec<Location>,
+    timestamps: Vec<u32>,
+}
+
+impl RegionSource {
+    /// Returns true if the chunk with the given index is stored in a separate
+    /// chunk file, according to its chunk header in the region file.
+    fn is_external(&self, index: usize) -> bool {
+        let location = self.locations[index];
+        if location.offset == 0 {
+            return false;
+        }
+
+        let mut region_file = self.file.borrow_mut();
+        // The compression byte follows the four byte chunk length.
+        let compression_offset_bytes = location.offset as usize * SECTOR_LEN_BYTES + 4;
+        region_file
+            .seek(SeekFrom::Start(compression_offset_bytes as u64))
+            .unwrap_or_else(|_| panic!("Could not seek to {}", compression_offset_bytes));
+        let compression = region_file.read_u8().unwrap();
+        (compression & EXTERNAL_CHUNK_FLAG) == EXTERNAL_CHUNK_FLAG
+    }
+
+    /// Reads the data of the chunk with the given index from the region file,
+    /// or returns `None` if there is no such chunk.
+    fn chunk_data(&self, index: usize) -> Option<RawChunkData> {
+        let location = self.locations[index];
+
+        // If the location field is all 0, then there is no chunk.
+        if location.offset == 0 {
+            return None;
+        }
+
+        let mut region_file = self.file.borrow_mut();
+
+        let data_offset_bytes = location.offset as usize * SECTOR_LEN_BYTES;
+        region_file
+            .seek(SeekFrom::Start(data_offset_bytes as u64))
+            .unwrap_or_else(|_| panic!("Could not seek to {}", data_offset_bytes));
+
+        // Read length and prepare data buffer
+        let chunk_len = region_file.read_u32::<BigEndian>().unwrap();
+        let mut chunk_data: Vec<u8> = Vec::with_capacity((chunk_len - 1) as usize);
+
+        // Read compression
+        let chunk_compression = region_file.read_u8().unwrap();
+
+        if (chunk_compression & EXTERNAL_CHUNK_FLAG) == EXTERNAL_CHUNK_FLAG {
+            let chunk_file_path = external_chunk_path(&self.path, index)?;
+            match std::fs::read(&chunk_file_path) {
+                Ok(data) => chunk_data = data,
+                Err(err) => {
+                    eprintln!(
+                        "[warning] Skipping chunk, as its chunk file {:?} could not be read: {}",
+                        chunk_file_path, err
+                    );
+                    return None;
+                }
+            }
+        } else {
+            (&mut *region_file)
+                .take((chunk_len - 1) as u64)
+                .read_to_end(&mut chunk_data)
+                .unwrap();
+        }
+
+        // Deliver data in the correct compression type
+        Some(match chunk_compression & 3 {
+            0x01 => RawChunkData::GZip(chunk_data),
+            0x02 => RawChunkData::ZLib(chunk_data),
+            0x03 => RawChunkData::Uncompressed(chunk_data),
+            _ => panic!("Unknown compression format: {}", chunk_compression & 0x03),
+        })
+    }
+}
+
+/// Path of the separate chunk file of the chunk with the given index, for the
+/// region file at `region_file_path`, or `None` if the region file is not named
+/// after its region coordinates, as in "r.<x>.<z>.mca".
+fn external_chunk_path(region_file_path: &Path, index: usize) -> Option<PathBuf> {
+    let file_name = region_file_path.file_name()?.to_str()?;
+    let mut parts = file_name.split('.');
+    if parts.next() != Some("r") {
+        return None;
+    }
+    let region_x: i64 = parts.next()?.parse().ok()?;
+    let region_z: i64 = parts.next()?.parse().ok()?;
+    let local = Region::chunk_coords_from_index(index);
+    let chunk_file_name = format!(
+        "c.{}.{}.mcc",
+        region_x * 32 + local.0,
+        region_z * 32 + local.1
+    );
+    Some(region_file_path.with_file_name(chunk_file_name))
+}
+
 struct InternalChunkData {
     timestamp: u32,
     data: RawChunkData,
@@ -245,20 +528,17 @@ mod tests {
 
     #[test]
     fn test_chunk_coords_from_index() {
+        assert_eq!(Region::chunk_coords_from_index(0), ChunkCoord::from((0, 0)),);
         assert_eq!(
-            Region::_chunk_coords_from_index(0),
-            ChunkCoord::from((0, 0)),
-        );
-        assert_eq!(
-            Region::_chunk_coords_from_index(31),
+            Region::chunk_coords_from_index(31),
             ChunkCoord::from((31, 0))
         );
         assert_eq!(
-            Region::_chunk_coords_from_index(992),
+            Region::chunk_coords_from_index(992),
             ChunkCoord::from((0, 31))
         );
         assert_eq!(
-            Region::_chunk_coords_from_index(1023),
+            Region::chunk_coords_from_index(1023),
             ChunkCoord::from((31, 31))
         );
     }
@@ -280,6 +560,125 @@ mod tests {
         );
     }
 
+    #[test]
+    fn existing_chunks_are_listed_from_header() {
+        let path = std::path::Path::new("tests/saves/1_12_2/region/r.0.0.mca");
+        let region = Region::open(path);
+        let existing = region.existing_chunks();
+        assert!(!existing.is_empty());
+        for info in &existing {
+            assert!(info.sector_count > 0);
+            assert!(info.timestamp > 0);
+        }
+    }
+
+    #[test]
+    fn chunks_read_on_demand_equal_eagerly_loaded_chunks() {
+        let path = std::path::Path::new("tests/saves/1_12_2/region/r.0.0.mca");
+        let lazy_region = Region::open(path);
+        let eager_region = Region::load_from_file(path);
+
+        let lazy_chunks = lazy_region.existing_chunks();
+        let eager_chunks = eager_region.existing_chunks();
+        assert_eq!(lazy_chunks.len(), eager_chunks.len());
+
+        for (lazy_info, eager_info) in lazy_chunks.iter().zip(eager_chunks.iter()) {
+            assert_eq!(lazy_info.coordinates, eager_info.coordinates);
+            assert_eq!(lazy_info.timestamp, eager_info.timestamp);
+            match (
+                lazy_region.chunk_data(&lazy_info.coordinates),
+                eager_region.chunk_data(&eager_info.coordinates),
+            ) {
+                (RawChunkData::ZLib(lazy_data), RawChunkData::ZLib(eager_data)) => {
+                    assert_eq!(lazy_data, eager_data)
+                }
+                _ => panic!(
+                    "Unexpected chunk compression at {:?}",
+                    lazy_info.coordinates
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn oversized_chunks_are_stored_in_chunk_files() {
+        let directory =
+            std::env::temp_dir().join(format!("mcprogedit-chunk-file-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        std::fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("r.-1.2.mca");
+
+        let large: Vec<u8> = (0..1_100_000).map(|i| (i % 251) as u8).collect();
+        let small = vec![1, 2, 3];
+        let mut region = Region::new();
+        region.set_chunk_data(&(3, 4).into(), RawChunkData::ZLib(large.clone()));
+        region.set_chunk_data(&(5, 6).into(), RawChunkData::ZLib(small.clone()));
+        region.save_to_file(&path);
+
+        assert!(directory.join("c.-29.68.mcc").is_file());
+        let region = Region::open(&path);
+        match region.chunk_data(&(3, 4).into()) {
+            RawChunkData::ZLib(data) => assert_eq!(data, large),
+            _ => panic!("Unexpected chunk compression"),
+        }
+        match region.chunk_data(&(5, 6).into()) {
+            RawChunkData::ZLib(data) => assert_eq!(data, small),
+            _ => panic!("Unexpected chunk compression"),
+        }
+
+        // A missing chunk file makes the chunk absent, rather than failing
+        std::fs::remove_file(directory.join("c.-29.68.mcc")).unwrap();
+        let region = Region::open(&path);
+        assert!(matches!(
+            region.chunk_data(&(3, 4).into()),
+            RawChunkData::Empty
+        ));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn chunk_files_are_replaced_and_removed_through_transactions() {
+        use crate::save_transaction::SaveTransaction;
+
+        let world =
+            std::env::temp_dir().join(format!("mcprogedit-chunk-files-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&world);
+        std::fs::create_dir_all(world.join("region")).unwrap();
+        let path = world.join("region").join("r.0.0.mca");
+        let chunk_file = world.join("region").join("c.1.0.mcc");
+
+        let large: Vec<u8> = (0..1_100_000).map(|i| (i % 251) as u8).collect();
+        let mut region = Region::new();
+        region.set_chunk_data(&(1, 0).into(), RawChunkData::ZLib(large.clone()));
+        let mut transaction = SaveTransaction::new(&world);
+        region.save_in_transaction(&path, &mut transaction);
+        assert!(!chunk_file.exists());
+        transaction.commit();
+        assert!(chunk_file.is_file());
+
+        let existing = Region::open(&path).existing_chunks();
+        assert_eq!(existing.len(), 1);
+        assert_eq!(existing[0].sector_count, 1);
+        assert_eq!(existing[0].chunk_file, Some(chunk_file.clone()));
+
+        // The chunk shrinks back into the region file
+        let mut region = Region::open(&path);
+        region.set_chunk_data(&(1, 0).into(), RawChunkData::ZLib(vec![1, 2, 3]));
+        let mut transaction = SaveTransaction::new_with_backup(&world);
+        region.save_in_transaction(&path, &mut transaction);
+        let backup = transaction.commit().unwrap();
+        assert!(!chunk_file.exists());
+        assert_eq!(Region::open(&path).existing_chunks()[0].chunk_file, None);
+
+        backup.restore();
+        assert!(chunk_file.is_file());
+        match Region::open(&path).chunk_data(&(1, 0).into()) {
+            RawChunkData::ZLib(data) => assert_eq!(data, large),
+            _ => panic!("Unexpected chunk compression"),
+        }
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
     // TODO Needs testing of writing region. Can get a half-decent test through
     // writing chunks, then use the load test on them.
     /*
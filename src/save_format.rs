@@ -0,0 +1,106 @@
+//! Chunk formats of the Minecraft versions that saves can be read from or written to.
+//!
+//! The chunk format used is decided by the data version of each chunk when
+//! reading, and by the data version of the world (from level.dat) when
+//! writing new chunks.
+
+use thiserror::Error;
+
+use crate::block::Block;
+use crate::coordinates::BlockCoord;
+use crate::mc_version::McVersion;
+
+/// The layout of chunk data in region files.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChunkFormat {
+    /// Numeric block ids and data values, used up to Minecraft 1.12.2.
+    PreFlattening,
+    /// Block state palettes in a "Level" compound, used from 1.13 through 1.17.1.
+    Flattening,
+    /// Block state palettes with sections below y = 0, used from 1.18.
+    ExtendedHeight,
+}
+
+impl ChunkFormat {
+    /// Returns the chunk format for reading chunks saved by the given version.
+    ///
+    /// # Errors
+    /// Returns [`FormatError::UnsupportedVersion`] for versions without a
+    /// supported chunk format, i.e. the 1.18 experimental snapshots and
+    /// versions newer than the latest known one.
+    pub fn for_version(version: McVersion) -> Result<Self, FormatError> {
+        match version.id() {
+            // 15w32a through 17w46a
+            100..=1450 => Ok(ChunkFormat::PreFlattening),
+            // 17w47a through 1.17.1 and the following experimental snapshots
+            1451..=2833 => Ok(ChunkFormat::Flattening),
+            // 21w43a through 1.21.7
+            2844..=4438 => Ok(ChunkFormat::ExtendedHeight),
+            _ => Err(FormatError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Returns the chunk format for writing chunks of the given version.
+    ///
+    /// Writing is only supported from the first release using each format:
+    /// 1.12 for the pre-flattening format, 1.13 for the flattening format,
+    /// and 1.18 for the extended height format, up to the latest known version.
+    ///
+    /// # Errors
+    /// Returns [`FormatError::UnsupportedVersion`] for any other version.
+    pub fn for_writing(version: McVersion) -> Result<Self, FormatError> {
+        match version.id() {
+            1139..=1450 => Ok(ChunkFormat::PreFlattening),
+            1519..=2833 => Ok(ChunkFormat::Flattening),
+            2860..=4438 => Ok(ChunkFormat::ExtendedHeight),
+            _ => Err(FormatError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Returns true for the formats with 1.18 and later world heights.
+    pub fn has_extended_height(&self) -> bool {
+        *self == ChunkFormat::ExtendedHeight
+    }
+}
+
+/// Reason a save could not be read or written in its own format.
+#[derive(Error, Debug)]
+pub enum FormatError {
+    #[error("Unsupported Minecraft version: {0}")]
+    UnsupportedVersion(McVersion),
+    #[error("Block {block:?} at {at:?} is not available in Minecraft {target}")]
+    UnavailableBlock {
+        at: BlockCoord,
+        block: Box<Block>,
+        target: McVersion,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn formats_follow_data_versions() {
+        let format = |name| ChunkFormat::for_version(McVersion::from_str(name).unwrap()).unwrap();
+        assert_eq!(format("1.12.2"), ChunkFormat::PreFlattening);
+        assert_eq!(format("1.13"), ChunkFormat::Flattening);
+        assert_eq!(format("1.17.1"), ChunkFormat::Flattening);
+        assert_eq!(format("1.18"), ChunkFormat::ExtendedHeight);
+        assert_eq!(format("1.21.7"), ChunkFormat::ExtendedHeight);
+    }
+
+    #[test]
+    fn unsupported_versions_are_refused() {
+        let snapshot = McVersion::from_str("21w37a").unwrap();
+        assert!(ChunkFormat::for_version(snapshot).is_err());
+        let future = McVersion::from_id(9999);
+        let error = ChunkFormat::for_writing(future).unwrap_err();
+        assert!(error.to_string().contains("9999"));
+        let flattening_snapshot = McVersion::from_str("17w47a").unwrap();
+        assert!(ChunkFormat::for_version(flattening_snapshot).is_ok());
+        assert!(ChunkFormat::for_writing(flattening_snapshot).is_err());
+    }
+}
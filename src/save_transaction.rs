@@ -0,0 +1,351 @@
+//! Transactional writing of region files, with optional backup and rollback.
+//!
+//! Region files are first written to temporary files next to the files they are
+//! to replace. Only when every region file has been written and flushed to disk,
+//! are the temporary files renamed into place. A crash or panic before that point
+//! leaves the world save untouched. Separate chunk files (".mcc") of oversized
+//! chunks are written and removed the same way.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+const BACKUP_DIRECTORY: &str = "mcprogedit_backups";
+const CREATED_REGIONS_FILE: &str = "created_regions";
+const STAGING_SUFFIX: &str = ".mcprogedit.tmp";
+/// Directories of the world save holding region files, of blocks and of entities.
+const REGION_DIRECTORIES: [&str; 2] = ["region", "entities"];
+
+/// A set of region files to be replaced together.
+pub struct SaveTransaction {
+    world_directory: PathBuf,
+    backup: bool,
+    staged: Vec<PathBuf>,
+    removed: Vec<PathBuf>,
+    committed: bool,
+}
+
+impl SaveTransaction {
+    /// Starts a new transaction for writing to the world at `world_directory`.
+    pub fn new(world_directory: &Path) -> Self {
+        Self {
+            world_directory: world_directory.to_path_buf(),
+            backup: false,
+            staged: Vec::new(),
+            removed: Vec::new(),
+            committed: false,
+        }
+    }
+
+    /// Starts a new transaction that, on commit, keeps a backup of every
+    /// region file it replaces.
+    pub fn new_with_backup(world_directory: &Path) -> Self {
+        let mut transaction = Self::new(world_directory);
+        transaction.backup = true;
+        transaction
+    }
+
+    /// Returns the temporary path to write the new contents of `target` to.
+    ///
+    /// The file at the returned path replaces `target` when the transaction is
+    /// committed, and is removed if the transaction is dropped uncommitted.
+    pub fn staging_path(&mut self, target: &Path) -> PathBuf {
+        if !self.staged.iter().any(|staged| staged == target) {
+            self.staged.push(target.to_path_buf());
+        }
+        Self::staging_path_for(target)
+    }
+
+    /// Marks `target` for removal when the transaction is committed.
+    ///
+    /// The file is kept in the backup, if any, so that it is restored along
+    /// with the files replaced by the transaction.
+    pub fn stage_removal(&mut self, target: &Path) {
+        if !self.removed.iter().any(|removed| removed == target) {
+            self.removed.push(target.to_path_buf());
+        }
+    }
+
+    /// Moves all staged files into place, and removes the files staged for removal.
+    ///
+    /// Returns the backup of the replaced files, if the transaction was created
+    /// with [`new_with_backup`](Self::new_with_backup).
+    pub fn commit(mut self) -> Option<Backup> {
+        // Make sure the new contents are on disk before anything is replaced.
+        for target in &self.staged {
+            let staging_path = Self::staging_path_for(target);
+            fs::File::open(&staging_path)
+                .and_then(|file| file.sync_all())
+                .unwrap_or_else(|err| panic!("Unable to sync {:?}: {}", staging_path, err));
+        }
+
+        let backup = if self.backup {
+            let targets: Vec<PathBuf> = self
+                .staged
+                .iter()
+                .chain(self.removed.iter())
+                .cloned()
+                .collect();
+            Some(Backup::create(&self.world_directory, &targets))
+        } else {
+            None
+        };
+
+        for target in &self.staged {
+            let staging_path = Self::staging_path_for(target);
+            fs::rename(&staging_path, target).unwrap_or_else(|err| {
+                panic!("Unable to move {:?} to {:?}: {}", staging_path, target, err)
+            });
+            sync_parent_directory(target);
+        }
+        for target in &self.removed {
+            match fs::remove_file(target) {
+                Ok(()) => sync_parent_directory(target),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => (),
+                Err(err) => panic!("Unable to remove {:?}: {}", target, err),
+            }
+        }
+
+        self.committed = true;
+        backup
+    }
+
+    fn staging_path_for(target: &Path) -> PathBuf {
+        let mut file_name = target.file_name().unwrap_or_default().to_os_string();
+        file_name.push(STAGING_SUFFIX);
+        target.with_file_name(file_name)
+    }
+}
+
+impl Drop for SaveTransaction {
+    fn drop(&mut self) {
+        if !self.committed {
+            for target in &self.staged {
+                let _ = fs::remove_file(Self::staging_path_for(target));
+            }
+        }
+    }
+}
+
+/// Backup of the region files replaced by one save.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Backup {
+    world_directory: PathBuf,
+    directory: PathBuf,
+    timestamp: u64,
+}
+
+impl Backup {
+    /// Lists the backups kept for the world at `world_directory`, oldest first.
+    pub fn list(world_directory: &Path) -> Vec<Self> {
+        let backups_directory = world_directory.join(BACKUP_DIRECTORY);
+        let mut backups: Vec<Self> = match fs::read_dir(&backups_directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.path().is_dir())
+                .filter_map(|entry| {
+                    let name = entry.file_name().into_string().ok()?;
+                    let timestamp = name.split('-').next()?.parse().ok()?;
+                    Some(Self {
+                        world_directory: world_directory.to_path_buf(),
+                        directory: entry.path(),
+                        timestamp,
+                    })
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        backups.sort_by(|a, b| (a.timestamp, &a.directory).cmp(&(b.timestamp, &b.directory)));
+        backups
+    }
+
+    /// Returns the most recent backup kept for the world at `world_directory`.
+    pub fn latest(world_directory: &Path) -> Option<Self> {
+        Self::list(world_directory).pop()
+    }
+
+    /// Time the backup was made, in seconds since the Unix epoch.
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    /// Directory holding the backed up files.
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Rolls the world back to the state it had before the save that made this backup.
+    ///
+    /// Region files replaced by the save are restored from the backup, and region
+    /// files created by the save are removed. The restore is itself transactional.
+    pub fn restore(&self) {
+        let mut transaction = SaveTransaction::new(&self.world_directory);
+        for region_directory in REGION_DIRECTORIES.iter() {
+            let backup_region_directory = self.directory.join(region_directory);
+            if let Ok(entries) = fs::read_dir(&backup_region_directory) {
+                for entry in entries.filter_map(|entry| entry.ok()) {
+                    let target = self
+                        .world_directory
+                        .join(region_directory)
+                        .join(entry.file_name());
+                    let staging_path = transaction.staging_path(&target);
+                    fs::copy(entry.path(), &staging_path).unwrap_or_else(|err| {
+                        panic!("Unable to restore {:?}: {}", entry.path(), err)
+                    });
+                }
+            }
+        }
+        transaction.commit();
+
+        // The manifest lists the files created by the save, relative to the world directory.
+        let created_regions = self.directory.join(CREATED_REGIONS_FILE);
+        if let Ok(created_regions) = fs::read_to_string(created_regions) {
+            for path in created_regions.lines().filter(|line| !line.is_empty()) {
+                let _ = fs::remove_file(self.world_directory.join(path));
+            }
+        }
+    }
+
+    /// Copies the current version of each of the given region files into a new backup.
+    fn create(world_directory: &Path, targets: &[PathBuf]) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // Several saves may happen within the same second.
+        let backups_directory = world_directory.join(BACKUP_DIRECTORY);
+        let mut directory = backups_directory.join(timestamp.to_string());
+        let mut sequence_number = 1;
+        while directory.exists() {
+            directory = backups_directory.join(format!("{}-{}", timestamp, sequence_number));
+            sequence_number += 1;
+        }
+
+        fs::create_dir_all(&directory)
+            .unwrap_or_else(|err| panic!("Unable to create {:?}: {}", directory, err));
+
+        let mut created_regions = String::new();
+        for target in targets {
+            let relative_path = relative_region_path(target);
+            if target.is_file() {
+                let backup_path = directory.join(&relative_path);
+                if let Some(parent) = backup_path.parent() {
+                    fs::create_dir_all(parent)
+                        .unwrap_or_else(|err| panic!("Unable to create {:?}: {}", parent, err));
+                }
+                fs::copy(target, backup_path)
+                    .unwrap_or_else(|err| panic!("Unable to back up {:?}: {}", target, err));
+            } else {
+                created_regions.push_str(&relative_path);
+                created_regions.push('\n');
+            }
+        }
+        fs::write(directory.join(CREATED_REGIONS_FILE), created_regions)
+            .unwrap_or_else(|err| panic!("Unable to write backup manifest: {}", err));
+
+        Self {
+            world_directory: world_directory.to_path_buf(),
+            directory,
+            timestamp,
+        }
+    }
+}
+
+/// Path of a region file relative to the world directory, e.g.
+/// "entities/r.0.0.mca". Files outside of the known region directories are
+/// treated as block region files.
+fn relative_region_path(target: &Path) -> String {
+    let file_name = target.file_name().unwrap_or_default().to_string_lossy();
+    let region_directory = target
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .and_then(|name| name.to_str())
+        .filter(|name| REGION_DIRECTORIES.contains(name))
+        .unwrap_or("region");
+    format!("{}/{}", region_directory, file_name)
+}
+
+/// Flushes the directory entry of a renamed file, where the platform supports it.
+fn sync_parent_directory(path: &Path) {
+    if let Some(parent) = path.parent() {
+        if let Ok(directory) = fs::File::open(parent) {
+            let _ = directory.sync_all();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("mcprogedit-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(directory.join("region")).unwrap();
+        directory
+    }
+
+    #[test]
+    fn uncommitted_transaction_leaves_files_untouched() {
+        let world = scratch_directory("uncommitted");
+        let target = world.join("region").join("r.0.0.mca");
+        fs::write(&target, b"old").unwrap();
+
+        {
+            let mut transaction = SaveTransaction::new(&world);
+            let staging_path = transaction.staging_path(&target);
+            fs::write(&staging_path, b"new").unwrap();
+        }
+
+        assert_eq!(fs::read(&target).unwrap(), b"old");
+        assert!(!SaveTransaction::staging_path_for(&target).exists());
+        fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn backup_restores_replaced_and_removes_created_regions() {
+        let world = scratch_directory("backup");
+        let replaced = world.join("region").join("r.0.0.mca");
+        let created = world.join("region").join("r.1.0.mca");
+        fs::write(&replaced, b"old").unwrap();
+
+        let mut transaction = SaveTransaction::new_with_backup(&world);
+        fs::write(transaction.staging_path(&replaced), b"new").unwrap();
+        fs::write(transaction.staging_path(&created), b"new").unwrap();
+        let backup = transaction.commit().unwrap();
+
+        assert_eq!(fs::read(&replaced).unwrap(), b"new");
+        assert!(created.is_file());
+        assert_eq!(Backup::latest(&world), Some(backup.clone()));
+
+        backup.restore();
+        assert_eq!(fs::read(&replaced).unwrap(), b"old");
+        assert!(!created.exists());
+        fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn backup_keeps_block_and_entity_regions_apart() {
+        let world = scratch_directory("entities");
+        fs::create_dir_all(world.join("entities")).unwrap();
+        let blocks = world.join("region").join("r.0.0.mca");
+        let entities = world.join("entities").join("r.0.0.mca");
+        let created = world.join("entities").join("r.1.0.mca");
+        fs::write(&blocks, b"old blocks").unwrap();
+        fs::write(&entities, b"old entities").unwrap();
+
+        let mut transaction = SaveTransaction::new_with_backup(&world);
+        fs::write(transaction.staging_path(&blocks), b"new").unwrap();
+        fs::write(transaction.staging_path(&entities), b"new").unwrap();
+        fs::write(transaction.staging_path(&created), b"new").unwrap();
+        let backup = transaction.commit().unwrap();
+
+        backup.restore();
+        assert_eq!(fs::read(&blocks).unwrap(), b"old blocks");
+        assert_eq!(fs::read(&entities).unwrap(), b"old entities");
+        assert!(!created.exists());
+        fs::remove_dir_all(&world).unwrap();
+    }
+}
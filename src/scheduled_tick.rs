@@ -0,0 +1,274 @@
+//! Scheduled ticks, i.e. pending block updates (e.g. of redstone components
+//! and falling blocks) and fluid updates (e.g. of flowing water).
+
+use crate::block::Block;
+use crate::block_state::BlockState;
+use crate::chunk::Chunk;
+use crate::coordinates::BlockCoord;
+use crate::mc_version::McVersion;
+use crate::positioning::{Axis2, Rotation};
+use crate::save_format::ChunkFormat;
+
+/// Whether a scheduled tick updates a block or a fluid.
+///
+/// Pre-flattening saves schedule both as block ticks.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TickKind {
+    Block,
+    Fluid,
+}
+
+/// The block or fluid a scheduled tick updates. The tick is dropped by the
+/// game if a different block or fluid is found at its position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TickTarget {
+    /// A block, or the fluid of a block, named as in the Minecraft version of
+    /// the save it is written to.
+    Block(Block),
+    /// Namespaced id of a block or fluid, e.g. "minecraft:repeater" or
+    /// "minecraft:flowing_water". Ids are kept as read, and not converted
+    /// between Minecraft versions.
+    Id(String),
+}
+
+/// An update scheduled to happen to the block or fluid at some position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScheduledTick {
+    pub kind: TickKind,
+    pub target: TickTarget,
+    /// Position of the block or fluid to be updated.
+    pub at: BlockCoord,
+    /// Number of game ticks until the update happens.
+    pub delay: i32,
+    /// Order of updates happening in the same game tick, lowest first.
+    pub priority: i32,
+}
+
+impl ScheduledTick {
+    /// Creates a tick scheduled `delay` game ticks from now, with normal priority,
+    /// targeting the block or fluid with the namespaced id `target`.
+    pub fn new(kind: TickKind, target: &str, at: BlockCoord, delay: i32) -> Self {
+        Self {
+            kind,
+            target: TickTarget::Id(target.to_string()),
+            at,
+            delay,
+            priority: 0,
+        }
+    }
+
+    /// Creates a tick for updating `block` at `at`.
+    ///
+    /// Water and lava, including waterlogged blocks, get a fluid tick. Other
+    /// blocks get a block tick. Returns `None` for air and empty blocks.
+    pub fn for_block(block: &Block, at: BlockCoord, delay: i32) -> Option<Self> {
+        let kind = match block {
+            Block::None | Block::Air => return None,
+            Block::WaterSource | Block::Water { .. } => TickKind::Fluid,
+            Block::LavaSource | Block::Lava { .. } => TickKind::Fluid,
+            block if block.is_waterlogged() => TickKind::Fluid,
+            _ => TickKind::Block,
+        };
+        Some(Self {
+            kind,
+            target: TickTarget::Block(block.clone()),
+            at,
+            delay,
+            priority: 0,
+        })
+    }
+
+    /// Namespaced id of the block or fluid targeted, in saves of the given
+    /// version.
+    ///
+    /// Fluids are named "flowing" if flowing, except before the flattening,
+    /// when fluid ticks always target the flowing fluid blocks.
+    pub fn target_id(&self, version: McVersion) -> String {
+        let block = match &self.target {
+            TickTarget::Id(id) => return id.clone(),
+            TickTarget::Block(block) => block,
+        };
+        let pre_flattening = matches!(
+            ChunkFormat::for_version(version),
+            Ok(ChunkFormat::PreFlattening)
+        );
+        let fluid = match (self.kind, block) {
+            (TickKind::Block, _) => None,
+            (_, Block::LavaSource) if !pre_flattening => Some("minecraft:lava"),
+            (_, Block::LavaSource) | (_, Block::Lava { .. }) => Some("minecraft:flowing_lava"),
+            (_, Block::Water { .. }) => Some("minecraft:flowing_water"),
+            _ if pre_flattening => Some("minecraft:flowing_water"),
+            _ => Some("minecraft:water"),
+        };
+        match fluid {
+            Some(fluid) => fluid.to_string(),
+            None if pre_flattening => Chunk::pre_flattening_block_name(block),
+            None => BlockState::from_block(block, version).name,
+        }
+    }
+
+    /// Reads a scheduled tick compound, as stored in chunks of all versions.
+    ///
+    /// Returns `None` if the target or position is missing.
+    pub(crate) fn from_nbt_value(kind: TickKind, value: &nbt::Value) -> Option<Self> {
+        let compound = match value {
+            nbt::Value::Compound(compound) => compound,
+            _ => return None,
+        };
+        let int = |name: &str| match compound.get(name) {
+            Some(nbt::Value::Int(value)) => Some(*value),
+            _ => None,
+        };
+        let target = match compound.get("i") {
+            Some(nbt::Value::String(target)) => target.clone(),
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            target: TickTarget::Id(target),
+            at: (int("x")? as i64, int("y")? as i64, int("z")? as i64).into(),
+            delay: int("t").unwrap_or(0),
+            priority: int("p").unwrap_or(0),
+        })
+    }
+
+    /// Generates a scheduled tick compound, for saves of the given version.
+    pub(crate) fn to_nbt_value(&self, version: McVersion) -> nbt::Value {
+        let mut compound = nbt::Map::new();
+        compound.insert("i".into(), nbt::Value::String(self.target_id(version)));
+        compound.insert("x".into(), nbt::Value::Int(self.at.0 as i32));
+        compound.insert("y".into(), nbt::Value::Int(self.at.1 as i32));
+        compound.insert("z".into(), nbt::Value::Int(self.at.2 as i32));
+        compound.insert("t".into(), nbt::Value::Int(self.delay));
+        compound.insert("p".into(), nbt::Value::Int(self.priority));
+        nbt::Value::Compound(compound)
+    }
+
+    /// Rotates the position of the tick, as part of rotating an area of
+    /// `(x_dim, z_dim)` blocks with its corner at the origin.
+    pub(crate) fn rotate(&mut self, rotation: Rotation, (x_dim, z_dim): (usize, usize)) {
+        let (x_dim, z_dim) = (x_dim as i64, z_dim as i64);
+        let BlockCoord(x, y, z) = self.at;
+        self.at = match rotation {
+            Rotation::None => (x, y, z),
+            Rotation::Clockwise90 => (z_dim - 1 - z, y, x),
+            Rotation::Clockwise180 => (x_dim - 1 - x, y, z_dim - 1 - z),
+            Rotation::Counterclockwise90 => (z, y, x_dim - 1 - x),
+        }
+        .into();
+    }
+
+    /// Mirrors the position of the tick, as part of mirroring an area of
+    /// `(x_dim, z_dim)` blocks with its corner at the origin.
+    pub(crate) fn mirror(&mut self, axis: Axis2, (x_dim, z_dim): (usize, usize)) {
+        let BlockCoord(x, y, z) = self.at;
+        self.at = match axis {
+            Axis2::X => (x_dim as i64 - 1 - x, y, z),
+            Axis2::Z => (x, y, z_dim as i64 - 1 - z),
+        }
+        .into();
+    }
+}
+
+/// Reads the scheduled ticks of an NBT list of tick compounds.
+///
+/// Ticks that cannot be read are skipped, with a warning.
+pub(crate) fn ticks_from_nbt_list(kind: TickKind, list: &[nbt::Value]) -> Vec<ScheduledTick> {
+    list.iter()
+        .filter_map(|value| {
+            let tick = ScheduledTick::from_nbt_value(kind, value);
+            if tick.is_none() {
+                eprintln!(
+                    "[warning] Skipping scheduled tick without target or position: {:?}",
+                    value
+                );
+            }
+            tick
+        })
+        .collect()
+}
+
+/// Generates an NBT list of the tick compounds of the ticks of the given kind,
+/// or of all ticks if `kind` is `None`, for saves of the given version.
+pub(crate) fn ticks_to_nbt_list(
+    ticks: &[ScheduledTick],
+    kind: Option<TickKind>,
+    version: McVersion,
+) -> nbt::Value {
+    nbt::Value::List(
+        ticks
+            .iter()
+            .filter(|tick| kind.is_none() || kind == Some(tick.kind))
+            .map(|tick| tick.to_nbt_value(version))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::block::RedstoneRepeater;
+    use crate::bounded_ints::{Int1Through4, Int1Through7};
+    use crate::positioning::Surface4;
+
+    #[test]
+    fn ticks_survive_nbt_round_trip() {
+        let mut tick = ScheduledTick::new(
+            TickKind::Block,
+            "minecraft:repeater",
+            (-3, 64, 17).into(),
+            2,
+        );
+        tick.priority = -1;
+        let value = tick.to_nbt_value(McVersion::from_str("1.16.5").unwrap());
+        assert_eq!(
+            ScheduledTick::from_nbt_value(TickKind::Block, &value),
+            Some(tick)
+        );
+    }
+
+    #[test]
+    fn fluids_get_fluid_ticks() {
+        let at = BlockCoord(0, 0, 0);
+        let latest = McVersion::from_str("1.21.7").unwrap();
+        let flowing = Block::Water {
+            falling: false,
+            level: Int1Through7::new_saturating(3),
+        };
+        let tick = ScheduledTick::for_block(&flowing, at, 5).unwrap();
+        assert_eq!(tick.kind, TickKind::Fluid);
+        assert_eq!(tick.target_id(latest), "minecraft:flowing_water");
+        let tick = ScheduledTick::for_block(&Block::WaterSource, at, 5).unwrap();
+        assert_eq!(tick.target_id(latest), "minecraft:water");
+
+        let tick = ScheduledTick::for_block(&Block::Sand, at, 2).unwrap();
+        assert_eq!(tick.kind, TickKind::Block);
+        assert_eq!(tick.target_id(latest), "minecraft:sand");
+
+        assert_eq!(ScheduledTick::for_block(&Block::Air, at, 1), None);
+    }
+
+    #[test]
+    fn targets_are_named_for_the_version() {
+        let at = BlockCoord(0, 0, 0);
+        let v1_12_2 = McVersion::from_str("1.12.2").unwrap();
+        let v1_13_2 = McVersion::from_str("1.13.2").unwrap();
+
+        let tick = ScheduledTick::for_block(&Block::WaterSource, at, 5).unwrap();
+        assert_eq!(tick.target_id(v1_12_2), "minecraft:flowing_water");
+        assert_eq!(tick.target_id(v1_13_2), "minecraft:water");
+        let tick = ScheduledTick::for_block(&Block::LavaSource, at, 5).unwrap();
+        assert_eq!(tick.target_id(v1_12_2), "minecraft:flowing_lava");
+        assert_eq!(tick.target_id(v1_13_2), "minecraft:lava");
+
+        let repeater = Block::RedstoneRepeater(RedstoneRepeater {
+            facing: Surface4::North,
+            delay: Int1Through4::new_saturating(2),
+        });
+        let tick = ScheduledTick::for_block(&repeater, at, 2).unwrap();
+        assert_eq!(tick.target_id(v1_12_2), "minecraft:unpowered_repeater");
+        assert_eq!(tick.target_id(v1_13_2), "minecraft:repeater");
+    }
+}
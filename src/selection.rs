@@ -0,0 +1,506 @@
+//! Selections of block positions, of arbitrary shape.
+//!
+//! A [`Selection`] decides which block positions it contains, and can be
+//! combined with other selections through [`union`](Selection::union),
+//! [`intersection`](Selection::intersection) and
+//! [`difference`](Selection::difference). The shapes of the
+//! [`shape`](crate::shape) module are all selections, as are
+//! [`BoundingBox`], [`ConvexHull`] and [`VoxelMask`].
+//!
+//! Selections can be used for editing world excerpts, through
+//! [`WorldExcerpt::replace_within()`](crate::world_excerpt::WorldExcerpt::replace_within),
+//! [`WorldExcerpt::from_selection()`](crate::world_excerpt::WorldExcerpt::from_selection),
+//! and [`Shape::fill()`](crate::shape::Shape::fill).
+//!
+//! # Examples
+//! ```
+//! use mcprogedit::coordinates::BlockCoord;
+//! use mcprogedit::selection::Selection;
+//! use mcprogedit::shape::{Cylinder, Sphere};
+//!
+//! // A ball with a vertical hole through it
+//! let ball = Sphere::new((0, 0, 0).into(), 4.0);
+//! let hole = Cylinder::new((0, -4, 0).into(), 1.0, 9);
+//! let bead = ball.difference(hole);
+//!
+//! assert!(bead.contains((3, 0, 0).into()));
+//! assert!(!bead.contains((0, 0, 0).into()));
+//! assert!(bead.iter().all(|at: BlockCoord| at.0.abs() <= 4));
+//! ```
+
+use std::collections::BTreeSet;
+use std::iter::FromIterator;
+
+use crate::bounds::BoundingBox;
+use crate::coordinates::BlockCoord;
+
+/// A set of block positions.
+pub trait Selection {
+    /// Returns true if the position `at` is part of the selection.
+    fn contains(&self, at: BlockCoord) -> bool;
+
+    /// Returns a bounding box holding every position of the selection,
+    /// or `None` if the selection is empty.
+    fn bounds(&self) -> Option<BoundingBox>;
+
+    /// Iterates over all positions of the selection.
+    ///
+    /// The positions are ordered by x, then y, then z coordinate.
+    fn iter(&self) -> Box<dyn Iterator<Item = BlockCoord> + '_> {
+        match self.bounds() {
+            Some(bounds) => Box::new(
+                (bounds.x_min..=bounds.x_max)
+                    .flat_map(move |x| {
+                        (bounds.y_min..=bounds.y_max).flat_map(move |y| {
+                            (bounds.z_min..=bounds.z_max).map(move |z| BlockCoord(x, y, z))
+                        })
+                    })
+                    .filter(move |at| self.contains(*at)),
+            ),
+            None => Box::new(std::iter::empty()),
+        }
+    }
+
+    /// Returns a selection of the positions in either of the selections.
+    fn union<S: Selection>(self, other: S) -> Union<Self, S>
+    where
+        Self: Sized,
+    {
+        Union(self, other)
+    }
+
+    /// Returns a selection of the positions in both of the selections.
+    fn intersection<S: Selection>(self, other: S) -> Intersection<Self, S>
+    where
+        Self: Sized,
+    {
+        Intersection(self, other)
+    }
+
+    /// Returns a selection of the positions in this selection, but not in `other`.
+    fn difference<S: Selection>(self, other: S) -> Difference<Self, S>
+    where
+        Self: Sized,
+    {
+        Difference(self, other)
+    }
+}
+
+impl<S: Selection + ?Sized> Selection for &S {
+    fn contains(&self, at: BlockCoord) -> bool {
+        (**self).contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        (**self).bounds()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = BlockCoord> + '_> {
+        (**self).iter()
+    }
+}
+
+impl<S: Selection + ?Sized> Selection for Box<S> {
+    fn contains(&self, at: BlockCoord) -> bool {
+        (**self).contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        (**self).bounds()
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = BlockCoord> + '_> {
+        (**self).iter()
+    }
+}
+
+impl Selection for BoundingBox {
+    fn contains(&self, at: BlockCoord) -> bool {
+        BoundingBox::contains(self, at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(*self)
+    }
+}
+
+/// The positions of either of two selections.
+#[derive(Clone, Debug)]
+pub struct Union<A, B>(A, B);
+
+impl<A: Selection, B: Selection> Selection for Union<A, B> {
+    fn contains(&self, at: BlockCoord) -> bool {
+        self.0.contains(at) || self.1.contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        match (self.0.bounds(), self.1.bounds()) {
+            (Some(a), Some(b)) => Some(a.enclosing(&b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// The positions of both of two selections.
+#[derive(Clone, Debug)]
+pub struct Intersection<A, B>(A, B);
+
+impl<A: Selection, B: Selection> Selection for Intersection<A, B> {
+    fn contains(&self, at: BlockCoord) -> bool {
+        self.0.contains(at) && self.1.contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        match (self.0.bounds(), self.1.bounds()) {
+            (Some(a), Some(b)) => a.overlap(&b),
+            _ => None,
+        }
+    }
+}
+
+/// The positions of one selection, except those of another.
+#[derive(Clone, Debug)]
+pub struct Difference<A, B>(A, B);
+
+impl<A: Selection, B: Selection> Selection for Difference<A, B> {
+    fn contains(&self, at: BlockCoord) -> bool {
+        self.0.contains(at) && !self.1.contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        self.0.bounds()
+    }
+}
+
+/// The smallest convex volume holding all of a set of points.
+///
+/// Positions are contained if they are inside the volume, or on its surface.
+/// The hull may also be flat, a line or a single point, if the points lie in
+/// a common plane or on a common line.
+///
+/// Building the hull takes time proportional to the fourth power of the number
+/// of points, so it is meant for shapes given by a modest number of corners.
+#[derive(Clone, Debug)]
+pub struct ConvexHull {
+    /// Half-spaces `normal · at <= limit`, all of which contain the hull.
+    half_spaces: Vec<([i128; 3], i128)>,
+    bounds: Option<BoundingBox>,
+}
+
+impl ConvexHull {
+    pub fn new(points: &[BlockCoord]) -> Self {
+        let points: Vec<[i128; 3]> = points
+            .iter()
+            .map(|point| [point.0 as i128, point.1 as i128, point.2 as i128])
+            .collect();
+        let bounds = points
+            .iter()
+            .fold(None, |bounds: Option<BoundingBox>, point| {
+                let point = BlockCoord(point[0] as i64, point[1] as i64, point[2] as i64);
+                let point_box = BoundingBox::from_block_coords(point, point);
+                Some(match bounds {
+                    Some(bounds) => bounds.enclosing(&point_box),
+                    None => point_box,
+                })
+            });
+
+        let mut half_spaces = Vec::new();
+        let mut plane_normal = None;
+
+        // Every plane through three of the points, with all points on one side,
+        // bounds the hull.
+        for (i, a) in points.iter().enumerate() {
+            for (j, b) in points.iter().enumerate().skip(i + 1) {
+                for c in points.iter().skip(j + 1) {
+                    let normal = cross(sub(*b, *a), sub(*c, *a));
+                    if normal == [0; 3] {
+                        continue;
+                    }
+                    plane_normal.get_or_insert(normal);
+                    if let Some(half_space) = supporting_half_space(&points, normal, *a) {
+                        half_spaces.push(half_space);
+                    }
+                }
+            }
+        }
+
+        let spans_volume = match plane_normal {
+            Some(normal) => points
+                .iter()
+                .any(|point| dot(normal, *point) != dot(normal, points[0])),
+            None => false,
+        };
+        if !spans_volume && !points.is_empty() {
+            half_spaces.clear();
+            let origin = points[0];
+            match plane_normal {
+                Some(normal) => {
+                    // Flat hull: the common plane, bounded by the lines through
+                    // pairs of points that have all points on one side.
+                    half_spaces.push((normal, dot(normal, origin)));
+                    half_spaces.push((neg(normal), -dot(normal, origin)));
+                    for (i, a) in points.iter().enumerate() {
+                        for b in points.iter().skip(i + 1) {
+                            let edge_normal = cross(normal, sub(*b, *a));
+                            if edge_normal == [0; 3] {
+                                continue;
+                            }
+                            if let Some(half_space) =
+                                supporting_half_space(&points, edge_normal, *a)
+                            {
+                                half_spaces.push(half_space);
+                            }
+                        }
+                    }
+                }
+                None => {
+                    // The points are all on a line, or are all the same point.
+                    let direction = points
+                        .iter()
+                        .map(|point| sub(*point, origin))
+                        .find(|direction| *direction != [0; 3]);
+                    let normals = match direction {
+                        Some(direction) => {
+                            let across = [[1, 0, 0], [0, 1, 0], [0, 0, 1]]
+                                .iter()
+                                .map(|axis| cross(direction, *axis))
+                                .find(|across| *across != [0; 3])
+                                .unwrap();
+                            vec![across, cross(direction, across)]
+                        }
+                        None => vec![[1, 0, 0], [0, 1, 0], [0, 0, 1]],
+                    };
+                    for normal in normals {
+                        half_spaces.push((normal, dot(normal, origin)));
+                        half_spaces.push((neg(normal), -dot(normal, origin)));
+                    }
+                    // The ends of the line are limited by the bounds.
+                }
+            }
+        }
+
+        Self {
+            half_spaces,
+            bounds,
+        }
+    }
+}
+
+impl Selection for ConvexHull {
+    fn contains(&self, at: BlockCoord) -> bool {
+        let within_bounds = matches!(self.bounds, Some(bounds) if bounds.contains(at));
+        let at = [at.0 as i128, at.1 as i128, at.2 as i128];
+        within_bounds
+            && self
+                .half_spaces
+                .iter()
+                .all(|(normal, limit)| dot(*normal, at) <= *limit)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        self.bounds
+    }
+}
+
+/// Returns the half-space with the given normal through `on`,
+/// if it holds all of `points`.
+fn supporting_half_space(
+    points: &[[i128; 3]],
+    normal: [i128; 3],
+    on: [i128; 3],
+) -> Option<([i128; 3], i128)> {
+    let limit = dot(normal, on);
+    let sides: Vec<i128> = points
+        .iter()
+        .map(|point| (dot(normal, *point) - limit).signum())
+        .collect();
+    if sides.iter().all(|side| *side == 0) {
+        None
+    } else if sides.iter().all(|side| *side <= 0) {
+        Some((normal, limit))
+    } else if sides.iter().all(|side| *side >= 0) {
+        Some((neg(normal), -limit))
+    } else {
+        None
+    }
+}
+
+fn sub(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn neg(a: [i128; 3]) -> [i128; 3] {
+    [-a[0], -a[1], -a[2]]
+}
+
+fn dot(a: [i128; 3], b: [i128; 3]) -> i128 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [i128; 3], b: [i128; 3]) -> [i128; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+/// An explicit set of block positions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VoxelMask {
+    positions: BTreeSet<BlockCoord>,
+}
+
+impl VoxelMask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mask of every position of `selection`.
+    pub fn from_selection<S: Selection + ?Sized>(selection: &S) -> Self {
+        selection.iter().collect()
+    }
+
+    /// Adds the position `at` to the mask.
+    ///
+    /// Returns true if the position was not already part of the mask.
+    pub fn insert(&mut self, at: BlockCoord) -> bool {
+        self.positions.insert(at)
+    }
+
+    /// Removes the position `at` from the mask.
+    ///
+    /// Returns true if the position was part of the mask.
+    pub fn remove(&mut self, at: BlockCoord) -> bool {
+        self.positions.remove(&at)
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+}
+
+impl FromIterator<BlockCoord> for VoxelMask {
+    fn from_iter<I: IntoIterator<Item = BlockCoord>>(iter: I) -> Self {
+        Self {
+            positions: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Selection for VoxelMask {
+    fn contains(&self, at: BlockCoord) -> bool {
+        self.positions.contains(&at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        let mut positions = self.positions.iter();
+        let first = *positions.next()?;
+        Some(positions.fold(
+            BoundingBox::from_block_coords(first, first),
+            |bounds, at| bounds.enclosing(&BoundingBox::from_block_coords(*at, *at)),
+        ))
+    }
+
+    fn iter(&self) -> Box<dyn Iterator<Item = BlockCoord> + '_> {
+        Box::new(self.positions.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shape::{Cuboid, Sphere};
+
+    #[test]
+    fn set_operations() {
+        let a = Cuboid::new((0, 0, 0).into(), (3, 0, 0).into());
+        let b = Cuboid::new((2, 0, 0).into(), (5, 0, 0).into());
+        let xs =
+            |selection: &dyn Selection| -> Vec<i64> { selection.iter().map(|at| at.0).collect() };
+
+        assert_eq!(xs(&a.union(b)), vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(xs(&a.intersection(b)), vec![2, 3]);
+        assert_eq!(xs(&a.difference(b)), vec![0, 1]);
+        assert_eq!(xs(&b.difference(a)), vec![4, 5]);
+
+        let apart = Cuboid::new((10, 0, 0).into(), (11, 0, 0).into());
+        assert!(a.intersection(apart).bounds().is_none());
+        assert_eq!(a.intersection(apart).iter().count(), 0);
+
+        let boxed: Box<dyn Selection> = Box::new(Sphere::new((0, 0, 0).into(), 1.0));
+        assert_eq!(boxed.union(a).iter().count(), 19 + 2);
+    }
+
+    #[test]
+    fn convex_hull_of_tetrahedron() {
+        let hull = ConvexHull::new(&[
+            (0, 0, 0).into(),
+            (4, 0, 0).into(),
+            (0, 4, 0).into(),
+            (0, 0, 4).into(),
+        ]);
+        assert!(hull.contains((0, 0, 0).into()));
+        assert!(hull.contains((1, 1, 1).into()));
+        assert!(hull.contains((2, 2, 0).into()));
+        assert!(!hull.contains((2, 2, 1).into()));
+        assert!(!hull.contains((-1, 0, 0).into()));
+        // Positions with x + y + z <= 4, and no negative coordinates
+        assert_eq!(hull.iter().count(), 35);
+    }
+
+    #[test]
+    fn degenerate_convex_hulls() {
+        let square = ConvexHull::new(&[
+            (0, 2, 0).into(),
+            (2, 2, 0).into(),
+            (0, 2, 2).into(),
+            (2, 2, 2).into(),
+            (1, 2, 1).into(),
+        ]);
+        assert_eq!(square.iter().count(), 9);
+        assert!(!square.contains((1, 1, 1).into()));
+
+        let line = ConvexHull::new(&[(0, 0, 0).into(), (4, 2, 0).into()]);
+        assert_eq!(
+            line.iter().collect::<Vec<_>>(),
+            vec![
+                BlockCoord(0, 0, 0),
+                BlockCoord(2, 1, 0),
+                BlockCoord(4, 2, 0)
+            ]
+        );
+
+        let point = ConvexHull::new(&[(1, 2, 3).into()]);
+        assert_eq!(point.iter().collect::<Vec<_>>(), vec![BlockCoord(1, 2, 3)]);
+
+        assert_eq!(ConvexHull::new(&[]).iter().count(), 0);
+    }
+
+    #[test]
+    fn voxel_mask() {
+        let mut mask = VoxelMask::new();
+        assert!(mask.bounds().is_none());
+        assert!(mask.insert((3, 0, 0).into()));
+        assert!(mask.insert((-1, 2, 5).into()));
+        assert!(!mask.insert((3, 0, 0).into()));
+        assert_eq!(mask.len(), 2);
+        assert_eq!(
+            mask.bounds(),
+            Some(BoundingBox::from_coords((-1, 0, 0), (3, 2, 5)))
+        );
+        assert!(mask.remove((3, 0, 0).into()));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![BlockCoord(-1, 2, 5)]);
+
+        let sphere = Sphere::new((0, 0, 0).into(), 2.0);
+        let mask = VoxelMask::from_selection(&sphere);
+        assert_eq!(
+            mask.iter().collect::<Vec<_>>(),
+            sphere.iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,571 @@
+//! Geometric shapes, for drawing into world excerpts.
+//!
+//! Every shape lists the positions of the blocks it is made of, relative to
+//! the world excerpt it is drawn into. Shapes are drawn with a [`Pattern`],
+//! which is either a single [`Block`] or a closure choosing the block for
+//! each position. Parts of a shape outside of the excerpt are left out.
+//!
+//! All shapes except [`Line`] are also [`Selection`]s,
+//! and every selection can be drawn as a shape.
+//!
+//! # Examples
+//! ```
+//! use mcprogedit::block::Block;
+//! use mcprogedit::colour::Colour;
+//! use mcprogedit::coordinates::BlockCoord;
+//! use mcprogedit::shape::{HollowBox, Line, Shape};
+//! use mcprogedit::world_excerpt::WorldExcerpt;
+//!
+//! let mut excerpt = WorldExcerpt::new(8, 8, 8);
+//!
+//! // A glass box, with a striped diagonal through it
+//! HollowBox::new((0, 0, 0).into(), (7, 7, 7).into()).fill(&mut excerpt, &Block::Glass { colour: None });
+//! let stripes = |at: BlockCoord| {
+//!     if at.1 % 2 == 0 {
+//!         Block::concrete_with_colour(Colour::Yellow)
+//!     } else {
+//!         Block::concrete_with_colour(Colour::Black)
+//!     }
+//! };
+//! Line::new((1, 1, 1).into(), (6, 6, 6).into()).fill(&mut excerpt, &stripes);
+//!
+//! assert_eq!(excerpt.block_at((3, 3, 3).into()), Some(&Block::concrete_with_colour(Colour::Black)));
+//! ```
+
+use std::collections::HashSet;
+
+use crate::block::Block;
+use crate::bounds::BoundingBox;
+use crate::coordinates::BlockCoord;
+use crate::selection::Selection;
+use crate::world_excerpt::WorldExcerpt;
+
+/// Chooses what block to draw at each position of a shape.
+///
+/// Implemented for [`Block`], for drawing with a single block, and for
+/// closures taking the position and returning the block to draw there.
+pub trait Pattern {
+    fn block_at(&self, at: BlockCoord) -> Block;
+}
+
+impl Pattern for Block {
+    fn block_at(&self, _at: BlockCoord) -> Block {
+        self.clone()
+    }
+}
+
+impl<F> Pattern for F
+where
+    F: Fn(BlockCoord) -> Block,
+{
+    fn block_at(&self, at: BlockCoord) -> Block {
+        self(at)
+    }
+}
+
+/// A set of block positions that can be drawn into a world excerpt.
+pub trait Shape {
+    /// Returns the positions of all blocks making up the shape.
+    fn positions(&self) -> Vec<BlockCoord>;
+
+    /// Sets every block of the shape, according to `pattern`.
+    fn fill<P: Pattern>(&self, excerpt: &mut WorldExcerpt, pattern: &P) {
+        for at in self.positions() {
+            if excerpt.block_at(at).is_some() {
+                excerpt.set_block_at(at, pattern.block_at(at));
+            }
+        }
+    }
+
+    /// Replaces the blocks of the shape that are equal to `search_for`,
+    /// according to `pattern`.
+    fn replace<P: Pattern>(&self, excerpt: &mut WorldExcerpt, search_for: &Block, pattern: &P) {
+        for at in self.positions() {
+            if excerpt.block_at(at) == Some(search_for) {
+                excerpt.set_block_at(at, pattern.block_at(at));
+            }
+        }
+    }
+}
+
+/// Every selection is a shape, made of the blocks it contains.
+impl<S: Selection> Shape for S {
+    fn positions(&self) -> Vec<BlockCoord> {
+        self.iter().collect()
+    }
+}
+
+/// A straight line of blocks, including both end points.
+///
+/// The blocks are chosen with Bresenham's line algorithm,
+/// so that every block touches the next one with a face, edge or corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Line {
+    pub from: BlockCoord,
+    pub to: BlockCoord,
+}
+
+impl Line {
+    pub fn new(from: BlockCoord, to: BlockCoord) -> Self {
+        Self { from, to }
+    }
+}
+
+impl Shape for Line {
+    fn positions(&self) -> Vec<BlockCoord> {
+        let from = [self.from.0, self.from.1, self.from.2];
+        let delta = [
+            (self.to.0 - self.from.0).abs(),
+            (self.to.1 - self.from.1).abs(),
+            (self.to.2 - self.from.2).abs(),
+        ];
+        let step = [
+            (self.to.0 - self.from.0).signum(),
+            (self.to.1 - self.from.1).signum(),
+            (self.to.2 - self.from.2).signum(),
+        ];
+
+        // Step one block at a time along the axis of greatest change,
+        // and along the other axes whenever their error term overflows.
+        let main = (0..3).max_by_key(|axis| delta[*axis]).unwrap();
+        let mut position = from;
+        let mut error = [0i64; 3];
+        let mut positions = Vec::with_capacity(delta[main] as usize + 1);
+        positions.push(BlockCoord(position[0], position[1], position[2]));
+        for _ in 0..delta[main] {
+            position[main] += step[main];
+            for axis in (0..3).filter(|axis| *axis != main) {
+                error[axis] += 2 * delta[axis];
+                if error[axis] > delta[main] {
+                    position[axis] += step[axis];
+                    error[axis] -= 2 * delta[main];
+                }
+            }
+            positions.push(BlockCoord(position[0], position[1], position[2]));
+        }
+        positions
+    }
+}
+
+/// A solid box of blocks, with corners `p1` and `p2`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cuboid {
+    pub p1: BlockCoord,
+    pub p2: BlockCoord,
+}
+
+impl Cuboid {
+    pub fn new(p1: BlockCoord, p2: BlockCoord) -> Self {
+        Self { p1, p2 }
+    }
+}
+
+impl Selection for Cuboid {
+    fn contains(&self, at: BlockCoord) -> bool {
+        BoundingBox::from_block_coords(self.p1, self.p2).contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::from_block_coords(self.p1, self.p2))
+    }
+}
+
+/// The six faces of a box with corners `p1` and `p2`, one block thick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HollowBox {
+    pub p1: BlockCoord,
+    pub p2: BlockCoord,
+}
+
+impl HollowBox {
+    pub fn new(p1: BlockCoord, p2: BlockCoord) -> Self {
+        Self { p1, p2 }
+    }
+}
+
+impl Selection for HollowBox {
+    fn contains(&self, at: BlockCoord) -> bool {
+        let bounds = BoundingBox::from_block_coords(self.p1, self.p2);
+        bounds.contains(at)
+            && (at.0 == bounds.x_min
+                || at.0 == bounds.x_max
+                || at.1 == bounds.y_min
+                || at.1 == bounds.y_max
+                || at.2 == bounds.z_min
+                || at.2 == bounds.z_max)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::from_block_coords(self.p1, self.p2))
+    }
+}
+
+/// The four vertical sides of a box with corners `p1` and `p2`, one block thick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Walls {
+    pub p1: BlockCoord,
+    pub p2: BlockCoord,
+}
+
+impl Walls {
+    pub fn new(p1: BlockCoord, p2: BlockCoord) -> Self {
+        Self { p1, p2 }
+    }
+}
+
+impl Selection for Walls {
+    fn contains(&self, at: BlockCoord) -> bool {
+        let bounds = BoundingBox::from_block_coords(self.p1, self.p2);
+        bounds.contains(at)
+            && (at.0 == bounds.x_min
+                || at.0 == bounds.x_max
+                || at.2 == bounds.z_min
+                || at.2 == bounds.z_max)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Some(BoundingBox::from_block_coords(self.p1, self.p2))
+    }
+}
+
+/// A solid ball of blocks.
+///
+/// A sphere of radius 0 is a single block, one of radius 1 is three blocks across.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+    pub centre: BlockCoord,
+    pub radius: f64,
+}
+
+impl Sphere {
+    pub fn new(centre: BlockCoord, radius: f64) -> Self {
+        Self { centre, radius }
+    }
+
+    fn as_ellipsoid(&self) -> Ellipsoid {
+        Ellipsoid::new(self.centre, (self.radius, self.radius, self.radius))
+    }
+}
+
+impl Selection for Sphere {
+    fn contains(&self, at: BlockCoord) -> bool {
+        self.as_ellipsoid().contains(at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        self.as_ellipsoid().bounds()
+    }
+}
+
+/// A solid ellipsoid of blocks, with separate radii along the x, y and z axes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ellipsoid {
+    pub centre: BlockCoord,
+    pub radii: (f64, f64, f64),
+}
+
+impl Ellipsoid {
+    pub fn new(centre: BlockCoord, radii: (f64, f64, f64)) -> Self {
+        Self { centre, radii }
+    }
+}
+
+impl Selection for Ellipsoid {
+    fn contains(&self, at: BlockCoord) -> bool {
+        // Half a block is added to the radii, so that blocks whose centre is
+        // at exactly the given radius are included.
+        let (x, y, z) = (
+            (at.0 - self.centre.0) as f64 / (self.radii.0 + 0.5),
+            (at.1 - self.centre.1) as f64 / (self.radii.1 + 0.5),
+            (at.2 - self.centre.2) as f64 / (self.radii.2 + 0.5),
+        );
+        x * x + y * y + z * z <= 1.0
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        let reach = BlockCoord(
+            self.radii.0.ceil() as i64,
+            self.radii.1.ceil() as i64,
+            self.radii.2.ceil() as i64,
+        );
+        Some(BoundingBox::from_block_coords(
+            self.centre - reach,
+            self.centre + reach,
+        ))
+    }
+}
+
+/// A solid vertical cylinder of blocks, standing on the block layer of `base_centre`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cylinder {
+    pub base_centre: BlockCoord,
+    pub radius: f64,
+    /// Number of block layers.
+    pub height: i64,
+}
+
+impl Cylinder {
+    pub fn new(base_centre: BlockCoord, radius: f64, height: i64) -> Self {
+        Self {
+            base_centre,
+            radius,
+            height,
+        }
+    }
+}
+
+impl Selection for Cylinder {
+    fn contains(&self, at: BlockCoord) -> bool {
+        let layer = at.1 - self.base_centre.1;
+        (0..self.height).contains(&layer) && within_circle(self.base_centre, self.radius, at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        if self.height < 1 {
+            return None;
+        }
+        let reach = self.radius.ceil() as i64;
+        Some(BoundingBox::from_coords(
+            (
+                self.base_centre.0 - reach,
+                self.base_centre.1,
+                self.base_centre.2 - reach,
+            ),
+            (
+                self.base_centre.0 + reach,
+                self.base_centre.1 + self.height - 1,
+                self.base_centre.2 + reach,
+            ),
+        ))
+    }
+}
+
+/// A solid upright cone of blocks, with its circular base on the block layer
+/// of `base_centre`, narrowing towards the apex `height` layers up.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Cone {
+    pub base_centre: BlockCoord,
+    pub radius: f64,
+    /// Number of block layers.
+    pub height: i64,
+}
+
+impl Cone {
+    pub fn new(base_centre: BlockCoord, radius: f64, height: i64) -> Self {
+        Self {
+            base_centre,
+            radius,
+            height,
+        }
+    }
+}
+
+impl Selection for Cone {
+    fn contains(&self, at: BlockCoord) -> bool {
+        let layer = at.1 - self.base_centre.1;
+        if !(0..self.height).contains(&layer) {
+            return false;
+        }
+        let radius = self.radius * (self.height - layer) as f64 / self.height as f64;
+        within_circle(self.base_centre, radius, at)
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        Cylinder::new(self.base_centre, self.radius, self.height).bounds()
+    }
+}
+
+/// A solid stepped pyramid of blocks, with a square base centered at
+/// `base_centre`, going in one block on every side for each layer.
+///
+/// The base is `2 * half_width + 1` blocks wide, and the pyramid is
+/// `half_width + 1` layers high.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pyramid {
+    pub base_centre: BlockCoord,
+    pub half_width: i64,
+}
+
+impl Pyramid {
+    pub fn new(base_centre: BlockCoord, half_width: i64) -> Self {
+        Self {
+            base_centre,
+            half_width,
+        }
+    }
+}
+
+impl Selection for Pyramid {
+    fn contains(&self, at: BlockCoord) -> bool {
+        let layer = at.1 - self.base_centre.1;
+        let reach = self.half_width - layer;
+        layer >= 0
+            && (at.0 - self.base_centre.0).abs() <= reach
+            && (at.2 - self.base_centre.2).abs() <= reach
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        if self.half_width < 0 {
+            return None;
+        }
+        let reach = BlockCoord(self.half_width, 0, self.half_width);
+        Some(BoundingBox::from_block_coords(
+            self.base_centre - reach,
+            self.base_centre + reach + BlockCoord(0, self.half_width, 0),
+        ))
+    }
+}
+
+/// A polygon in the horizontal plane, extruded vertically from `y_min` to `y_max`.
+///
+/// The polygon is given by the (x, z) positions of its corners, in order.
+/// Blocks on the polygon edges are included, as well as all blocks inside.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Prism {
+    corners: Vec<(i64, i64)>,
+    y_min: i64,
+    y_max: i64,
+    edges: HashSet<(i64, i64)>,
+}
+
+impl Prism {
+    /// # Panics
+    /// Panics if no corners are given.
+    pub fn new(corners: Vec<(i64, i64)>, y_min: i64, y_max: i64) -> Self {
+        if corners.is_empty() {
+            panic!("A prism needs at least one corner");
+        }
+        let mut edges = HashSet::new();
+        for (index, from) in corners.iter().enumerate() {
+            let to = corners[(index + 1) % corners.len()];
+            let line = Line::new(BlockCoord(from.0, 0, from.1), BlockCoord(to.0, 0, to.1));
+            edges.extend(line.positions().iter().map(|at| (at.0, at.2)));
+        }
+        Self {
+            corners,
+            y_min: i64::min(y_min, y_max),
+            y_max: i64::max(y_min, y_max),
+            edges,
+        }
+    }
+
+    /// Even-odd rule test of whether (x, z) is inside the polygon.
+    fn is_inside(&self, x: i64, z: i64) -> bool {
+        let (x, z) = (x as f64, z as f64);
+        let mut inside = false;
+        for (index, from) in self.corners.iter().enumerate() {
+            let to = self.corners[(index + 1) % self.corners.len()];
+            let (x1, z1) = (from.0 as f64, from.1 as f64);
+            let (x2, z2) = (to.0 as f64, to.1 as f64);
+            if (z1 > z) != (z2 > z) && x < x1 + (z - z1) * (x2 - x1) / (z2 - z1) {
+                inside = !inside;
+            }
+        }
+        inside
+    }
+}
+
+impl Selection for Prism {
+    fn contains(&self, at: BlockCoord) -> bool {
+        (self.y_min..=self.y_max).contains(&at.1)
+            && (self.edges.contains(&(at.0, at.2)) || self.is_inside(at.0, at.2))
+    }
+
+    fn bounds(&self) -> Option<BoundingBox> {
+        let x_min = self.corners.iter().map(|corner| corner.0).min().unwrap();
+        let x_max = self.corners.iter().map(|corner| corner.0).max().unwrap();
+        let z_min = self.corners.iter().map(|corner| corner.1).min().unwrap();
+        let z_max = self.corners.iter().map(|corner| corner.1).max().unwrap();
+        Some(BoundingBox::from_coords(
+            (x_min, self.y_min, z_min),
+            (x_max, self.y_max, z_max),
+        ))
+    }
+}
+
+fn within_circle(centre: BlockCoord, radius: f64, at: BlockCoord) -> bool {
+    let (x, z) = ((at.0 - centre.0) as f64, (at.2 - centre.2) as f64);
+    x * x + z * z <= (radius + 0.5) * (radius + 0.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_connects_end_points() {
+        let line = Line::new((0, 0, 0).into(), (5, -2, 3).into());
+        let positions = line.positions();
+        assert_eq!(positions.len(), 6);
+        assert_eq!(positions.first(), Some(&BlockCoord(0, 0, 0)));
+        assert_eq!(positions.last(), Some(&BlockCoord(5, -2, 3)));
+        for pair in positions.windows(2) {
+            let step = pair[1] - pair[0];
+            assert!(step.0.abs() <= 1 && step.1.abs() <= 1 && step.2.abs() <= 1);
+        }
+
+        let point = Line::new((2, 2, 2).into(), (2, 2, 2).into());
+        assert_eq!(point.positions(), vec![BlockCoord(2, 2, 2)]);
+    }
+
+    #[test]
+    fn shapes_have_expected_sizes() {
+        let (p1, p2) = ((0, 0, 0).into(), (3, 3, 3).into());
+        assert_eq!(Cuboid::new(p1, p2).positions().len(), 64);
+        assert_eq!(HollowBox::new(p1, p2).positions().len(), 64 - 8);
+        assert_eq!(Walls::new(p1, p2).positions().len(), 4 * 12);
+
+        assert_eq!(Sphere::new((0, 0, 0).into(), 0.0).positions().len(), 1);
+        assert_eq!(Sphere::new((0, 0, 0).into(), 1.0).positions().len(), 19);
+        assert_eq!(
+            Cylinder::new((0, 0, 0).into(), 1.0, 3).positions().len(),
+            27
+        );
+        assert_eq!(
+            Pyramid::new((0, 0, 0).into(), 2).positions().len(),
+            25 + 9 + 1
+        );
+
+        let cone = Cone::new((0, 0, 0).into(), 3.0, 4);
+        assert!(cone.contains((3, 0, 0).into()));
+        assert!(!cone.contains((3, 2, 0).into()));
+        assert!(cone.contains((0, 3, 0).into()));
+        assert!(!cone.contains((0, 4, 0).into()));
+    }
+
+    #[test]
+    fn prism_includes_edges_and_inside() {
+        let triangle = Prism::new(vec![(0, 0), (6, 0), (0, 6)], 0, 1);
+        assert!(triangle.contains((0, 0, 0).into()));
+        assert!(triangle.contains((3, 1, 3).into()));
+        assert!(triangle.contains((1, 1, 1).into()));
+        assert!(!triangle.contains((4, 0, 4).into()));
+        assert!(!triangle.contains((1, 2, 1).into()));
+        assert_eq!(triangle.positions().len(), 2 * 28);
+    }
+
+    #[test]
+    fn fill_and_replace_follow_pattern() {
+        let mut excerpt = WorldExcerpt::new(4, 1, 4);
+        let cuboid = Cuboid::new((-2, 0, -2).into(), (1, 0, 1).into());
+        cuboid.fill(&mut excerpt, &Block::Stone);
+        assert_eq!(excerpt.block_at((1, 0, 1).into()), Some(&Block::Stone));
+        assert_eq!(excerpt.block_at((2, 0, 1).into()), Some(&Block::None));
+
+        let checkers = |at: BlockCoord| {
+            if (at.0 + at.2) % 2 == 0 {
+                Block::Dirt
+            } else {
+                Block::Gravel
+            }
+        };
+        Cuboid::new((0, 0, 0).into(), (3, 0, 3).into()).replace(
+            &mut excerpt,
+            &Block::Stone,
+            &checkers,
+        );
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Dirt));
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::Gravel));
+        assert_eq!(excerpt.block_at((3, 0, 3).into()), Some(&Block::None));
+    }
+}
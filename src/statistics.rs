@@ -0,0 +1,174 @@
+//! Block statistics of world excerpts, for auditing builds and generated terrain.
+//!
+//! [`Statistics`] counts the blocks of an excerpt in several ways, and can be
+//! serialised e.g. to JSON with `serde_json`.
+//!
+//! Blocks are named after their block states in the latest supported Minecraft
+//! version, such as `minecraft:oak_stairs[facing=east,half=top,...]`. The
+//! "kind" of a block is the name without properties, e.g. `minecraft:oak_stairs`,
+//! so that blocks differing only in orientation or other state are counted together.
+//! Empty positions ([`Block::None`]) are not counted.
+
+use std::collections::BTreeMap;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::block::Block;
+use crate::block_state::BlockState;
+use crate::world_excerpt::WorldExcerpt;
+
+/// Block counts of a world excerpt.
+///
+/// Created with [`WorldExcerpt::statistics()`](crate::world_excerpt::WorldExcerpt::statistics).
+///
+/// # Examples
+/// ```
+/// use mcprogedit::block::Block;
+/// use mcprogedit::colour::Colour;
+/// use mcprogedit::world_excerpt::WorldExcerpt;
+///
+/// let mut excerpt = WorldExcerpt::new(2, 2, 1);
+/// excerpt.set_block_at((0, 0, 0).into(), Block::Wool { colour: Colour::Red });
+/// excerpt.set_block_at((1, 0, 0).into(), Block::Wool { colour: Colour::Blue });
+///
+/// let statistics = excerpt.statistics();
+/// assert_eq!(statistics.total, 2);
+/// assert_eq!(statistics.by_kind["minecraft:red_wool"], 1);
+/// assert_eq!(statistics.by_colour["blue"], 1);
+///
+/// let json = serde_json::to_string(&statistics).unwrap();
+/// assert!(json.contains("\"minecraft:blue_wool\":1"));
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Statistics {
+    /// Number of blocks counted.
+    pub total: u64,
+    /// Block counts by full block state.
+    pub by_block: BTreeMap<String, u64>,
+    /// Block counts by block name, ignoring block properties such as orientation.
+    pub by_kind: BTreeMap<String, u64>,
+    /// Block counts by material name, e.g. `dark_oak`, for blocks made of a material.
+    pub by_material: BTreeMap<String, u64>,
+    /// Block counts by colour name, e.g. `light_blue`, for coloured blocks.
+    pub by_colour: BTreeMap<String, u64>,
+    /// Block counts by kind for each horizontal layer, from the bottom up.
+    pub by_y: Vec<BTreeMap<String, u64>>,
+    /// Block entity counts by block entity id, e.g. `minecraft:chest`.
+    pub block_entities: BTreeMap<String, u64>,
+}
+
+impl Statistics {
+    /// Counts the blocks of `excerpt`.
+    pub fn of(excerpt: &WorldExcerpt) -> Self {
+        let mut statistics = Self {
+            by_y: vec![BTreeMap::new(); excerpt.dim().1],
+            ..Default::default()
+        };
+
+        for (at, block) in excerpt.iter() {
+            if let Block::None = block {
+                continue;
+            }
+            let state = BlockState::latest(block);
+            statistics.total += 1;
+            count(&mut statistics.by_block, state.to_string());
+            count(&mut statistics.by_kind, state.name.clone());
+            count(&mut statistics.by_y[at.1 as usize], state.name);
+            if let Some(material) = block.material() {
+                count(&mut statistics.by_material, material.name().to_string());
+            }
+            if let Some(colour) = block.colour() {
+                count(&mut statistics.by_colour, colour.name().to_string());
+            }
+            if let Some(id) = block_entity_id(block) {
+                count(&mut statistics.block_entities, id.to_string());
+            }
+        }
+
+        statistics
+    }
+}
+
+fn count(counts: &mut BTreeMap<String, u64>, key: String) {
+    *counts.entry(key).or_insert(0) += 1;
+}
+
+/// Returns the id of the block entity of `block`, if it has one in the latest
+/// supported Minecraft version.
+fn block_entity_id(block: &Block) -> Option<&'static str> {
+    match block {
+        Block::Banner(_) => Some("minecraft:banner"),
+        Block::Barrel { .. } => Some("minecraft:barrel"),
+        Block::Beacon(_) => Some("minecraft:beacon"),
+        Block::Bed(_) => Some("minecraft:bed"),
+        Block::Beehive { .. } | Block::BeeNest { .. } => Some("minecraft:beehive"),
+        Block::Bell { .. } => Some("minecraft:bell"),
+        Block::BlastFurnace(_) => Some("minecraft:blast_furnace"),
+        Block::BrewingStand(_) => Some("minecraft:brewing_stand"),
+        Block::Campfire { .. } | Block::SoulCampfire { .. } => Some("minecraft:campfire"),
+        Block::Chest(_) => Some("minecraft:chest"),
+        Block::CommandBlock(_) => Some("minecraft:command_block"),
+        Block::Conduit { .. } => Some("minecraft:conduit"),
+        Block::DaylightDetector => Some("minecraft:daylight_detector"),
+        Block::Dispenser(_) => Some("minecraft:dispenser"),
+        Block::Dropper(_) => Some("minecraft:dropper"),
+        Block::EnchantingTable { .. } => Some("minecraft:enchanting_table"),
+        Block::EndGateway => Some("minecraft:end_gateway"),
+        Block::EndPortal => Some("minecraft:end_portal"),
+        Block::EnderChest { .. } => Some("minecraft:ender_chest"),
+        Block::Furnace(_) => Some("minecraft:furnace"),
+        Block::Head(_) => Some("minecraft:skull"),
+        Block::Hopper(_) => Some("minecraft:hopper"),
+        Block::JigsawBlock { .. } => Some("minecraft:jigsaw"),
+        Block::Jukebox(_) => Some("minecraft:jukebox"),
+        Block::Lectern { .. } => Some("minecraft:lectern"),
+        Block::RedstoneComparator { .. } | Block::RedstoneSubtractor { .. } => {
+            Some("minecraft:comparator")
+        }
+        Block::ShulkerBox(_) => Some("minecraft:shulker_box"),
+        Block::Sign(_) => Some("minecraft:sign"),
+        Block::Smoker(_) => Some("minecraft:smoker"),
+        Block::Spawner => Some("minecraft:mob_spawner"),
+        Block::StructureBlock => Some("minecraft:structure_block"),
+        Block::TrappedChest(_) => Some("minecraft:trapped_chest"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::positioning::{Axis3, Surface4};
+
+    #[test]
+    fn blocks_are_counted_in_all_groupings() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::oak_log(Axis3::X));
+        excerpt.set_block_at((1, 0, 0).into(), Block::oak_log(Axis3::Y));
+        excerpt.set_block_at((2, 0, 0).into(), Block::oak_planks());
+        excerpt.set_block_at((0, 1, 0).into(), Block::Air);
+        excerpt.set_block_at(
+            (1, 1, 0).into(),
+            Block::EnderChest {
+                facing: Surface4::North,
+                waterlogged: false,
+            },
+        );
+
+        let statistics = excerpt.statistics();
+        assert_eq!(statistics.total, 5);
+        assert_eq!(statistics.by_block["minecraft:oak_log[axis=x]"], 1);
+        assert_eq!(statistics.by_block["minecraft:oak_log[axis=y]"], 1);
+        assert_eq!(statistics.by_kind["minecraft:oak_log"], 2);
+        assert_eq!(statistics.by_material["oak"], 3);
+        assert!(statistics.by_colour.is_empty());
+        assert_eq!(statistics.by_y.len(), 2);
+        assert_eq!(statistics.by_y[0]["minecraft:oak_log"], 2);
+        assert_eq!(statistics.by_y[1]["minecraft:air"], 1);
+        assert_eq!(statistics.block_entities["minecraft:ender_chest"], 1);
+
+        let json = serde_json::to_string(&statistics).unwrap();
+        let parsed: Statistics = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, statistics);
+    }
+}
@@ -1,4 +1,10 @@
-#[derive(Clone, Copy, Debug, PartialEq)]
+use std::str::FromStr;
+
+use serde_derive::{Deserialize, Serialize};
+
+use crate::mc_version::McVersion;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub enum StatusEffect {
     Speed = 1,
     Slowness = 2,
@@ -32,6 +38,13 @@ pub enum StatusEffect {
     DolphinsGrace = 30,
     BadOmen = 31,
     HeroOfTheVillage = 32,
+    Darkness = 33,
+    TrialOmen = 34,
+    RaidOmen = 35,
+    WindCharged = 36,
+    Weaving = 37,
+    Oozing = 38,
+    Infested = 39,
 }
 
 impl From<i32> for StatusEffect {
@@ -69,6 +82,13 @@ impl From<i32> for StatusEffect {
             30 => StatusEffect::DolphinsGrace,
             31 => StatusEffect::BadOmen,
             32 => StatusEffect::HeroOfTheVillage,
+            33 => StatusEffect::Darkness,
+            34 => StatusEffect::TrialOmen,
+            35 => StatusEffect::RaidOmen,
+            36 => StatusEffect::WindCharged,
+            37 => StatusEffect::Weaving,
+            38 => StatusEffect::Oozing,
+            39 => StatusEffect::Infested,
             _ => panic!("Invalid status effect number: {}", effect_number),
         }
     }
@@ -109,51 +129,125 @@ impl From<StatusEffect> for i32 {
             StatusEffect::DolphinsGrace => 30,
             StatusEffect::BadOmen => 31,
             StatusEffect::HeroOfTheVillage => 32,
+            StatusEffect::Darkness => 33,
+            StatusEffect::TrialOmen => 34,
+            StatusEffect::RaidOmen => 35,
+            StatusEffect::WindCharged => 36,
+            StatusEffect::Weaving => 37,
+            StatusEffect::Oozing => 38,
+            StatusEffect::Infested => 39,
         }
     }
 }
 
 impl From<&str> for StatusEffect {
     fn from(effect_name: &str) -> Self {
-        match effect_name {
-            "speed" => StatusEffect::Speed,
-            "slowness" => StatusEffect::Slowness,
-            "haste" => StatusEffect::Haste,
-            "mining_fatigue" => StatusEffect::MiningFatigue,
-            "strength" => StatusEffect::Strength,
-            "instant_health" => StatusEffect::InstantHealth,
-            "instant_damage" => StatusEffect::InstantDamage,
-            "jump_boost" => StatusEffect::JumpBoost,
-            "nausea" => StatusEffect::Nausea,
-            "regeneration" => StatusEffect::Regeneration,
-            "resistance" => StatusEffect::Resistance,
-            "fire_resistance" => StatusEffect::FireResistance,
-            "water_breathing" => StatusEffect::WaterBreathing,
-            "invisibility" => StatusEffect::Invisibility,
-            "blindness" => StatusEffect::Blindness,
-            "night_vision" => StatusEffect::NightVision,
-            "hunger" => StatusEffect::Hunger,
-            "weakness" => StatusEffect::Weakness,
-            "poison" => StatusEffect::Poison,
-            "wither" => StatusEffect::Wither,
-            "health_boost" => StatusEffect::HealthBoost,
-            "absorption" => StatusEffect::Absorption,
-            "saturation" => StatusEffect::Saturation,
-            "glowing" => StatusEffect::Glowing,
-            "levitation" => StatusEffect::Levitation,
-            "luck" => StatusEffect::Luck,
-            "unluck" => StatusEffect::BadLuck,
-            "slow_falling" => StatusEffect::SlowFalling,
-            "conduit_power" => StatusEffect::ConduitPower,
-            "dolphins_grace" => StatusEffect::DolphinsGrace,
-            "bad_omen" => StatusEffect::BadOmen,
-            "hero_of_the_village" => StatusEffect::HeroOfTheVillage,
-            _ => panic!("Invalid status effect name: {}", effect_name),
-        }
+        Self::from_name(effect_name)
+            .unwrap_or_else(|| panic!("Invalid status effect name: {}", effect_name))
     }
 }
 
 impl StatusEffect {
+    /// Status effect of the given numeric id, as used before 1.20.2.
+    pub(crate) fn from_id(effect_number: i32) -> Option<Self> {
+        if (1..=39).contains(&effect_number) {
+            Some(Self::from(effect_number))
+        } else {
+            None
+        }
+    }
+
+    /// Status effect of the given id, with or without namespace.
+    pub(crate) fn from_name(effect_name: &str) -> Option<Self> {
+        match effect_name.trim_start_matches("minecraft:") {
+            "speed" => Some(StatusEffect::Speed),
+            "slowness" => Some(StatusEffect::Slowness),
+            "haste" => Some(StatusEffect::Haste),
+            "mining_fatigue" => Some(StatusEffect::MiningFatigue),
+            "strength" => Some(StatusEffect::Strength),
+            "instant_health" => Some(StatusEffect::InstantHealth),
+            "instant_damage" => Some(StatusEffect::InstantDamage),
+            "jump_boost" => Some(StatusEffect::JumpBoost),
+            "nausea" => Some(StatusEffect::Nausea),
+            "regeneration" => Some(StatusEffect::Regeneration),
+            "resistance" => Some(StatusEffect::Resistance),
+            "fire_resistance" => Some(StatusEffect::FireResistance),
+            "water_breathing" => Some(StatusEffect::WaterBreathing),
+            "invisibility" => Some(StatusEffect::Invisibility),
+            "blindness" => Some(StatusEffect::Blindness),
+            "night_vision" => Some(StatusEffect::NightVision),
+            "hunger" => Some(StatusEffect::Hunger),
+            "weakness" => Some(StatusEffect::Weakness),
+            "poison" => Some(StatusEffect::Poison),
+            "wither" => Some(StatusEffect::Wither),
+            "health_boost" => Some(StatusEffect::HealthBoost),
+            "absorption" => Some(StatusEffect::Absorption),
+            "saturation" => Some(StatusEffect::Saturation),
+            "glowing" => Some(StatusEffect::Glowing),
+            "levitation" => Some(StatusEffect::Levitation),
+            "luck" => Some(StatusEffect::Luck),
+            "unluck" => Some(StatusEffect::BadLuck),
+            "slow_falling" => Some(StatusEffect::SlowFalling),
+            "conduit_power" => Some(StatusEffect::ConduitPower),
+            "dolphins_grace" => Some(StatusEffect::DolphinsGrace),
+            "bad_omen" => Some(StatusEffect::BadOmen),
+            "hero_of_the_village" => Some(StatusEffect::HeroOfTheVillage),
+            "darkness" => Some(StatusEffect::Darkness),
+            "trial_omen" => Some(StatusEffect::TrialOmen),
+            "raid_omen" => Some(StatusEffect::RaidOmen),
+            "wind_charged" => Some(StatusEffect::WindCharged),
+            "weaving" => Some(StatusEffect::Weaving),
+            "oozing" => Some(StatusEffect::Oozing),
+            "infested" => Some(StatusEffect::Infested),
+            _ => None,
+        }
+    }
+
+    /// Id of the status effect, without namespace.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            StatusEffect::Speed => "speed",
+            StatusEffect::Slowness => "slowness",
+            StatusEffect::Haste => "haste",
+            StatusEffect::MiningFatigue => "mining_fatigue",
+            StatusEffect::Strength => "strength",
+            StatusEffect::InstantHealth => "instant_health",
+            StatusEffect::InstantDamage => "instant_damage",
+            StatusEffect::JumpBoost => "jump_boost",
+            StatusEffect::Nausea => "nausea",
+            StatusEffect::Regeneration => "regeneration",
+            StatusEffect::Resistance => "resistance",
+            StatusEffect::FireResistance => "fire_resistance",
+            StatusEffect::WaterBreathing => "water_breathing",
+            StatusEffect::Invisibility => "invisibility",
+            StatusEffect::Blindness => "blindness",
+            StatusEffect::NightVision => "night_vision",
+            StatusEffect::Hunger => "hunger",
+            StatusEffect::Weakness => "weakness",
+            StatusEffect::Poison => "poison",
+            StatusEffect::Wither => "wither",
+            StatusEffect::HealthBoost => "health_boost",
+            StatusEffect::Absorption => "absorption",
+            StatusEffect::Saturation => "saturation",
+            StatusEffect::Glowing => "glowing",
+            StatusEffect::Levitation => "levitation",
+            StatusEffect::Luck => "luck",
+            StatusEffect::BadLuck => "unluck",
+            StatusEffect::SlowFalling => "slow_falling",
+            StatusEffect::ConduitPower => "conduit_power",
+            StatusEffect::DolphinsGrace => "dolphins_grace",
+            StatusEffect::BadOmen => "bad_omen",
+            StatusEffect::HeroOfTheVillage => "hero_of_the_village",
+            StatusEffect::Darkness => "darkness",
+            StatusEffect::TrialOmen => "trial_omen",
+            StatusEffect::RaidOmen => "raid_omen",
+            StatusEffect::WindCharged => "wind_charged",
+            StatusEffect::Weaving => "weaving",
+            StatusEffect::Oozing => "oozing",
+            StatusEffect::Infested => "infested",
+        }
+    }
+
     pub fn is_neutral(&self) -> bool {
         matches!(self, StatusEffect::Glowing)
     }
@@ -173,6 +267,11 @@ impl StatusEffect {
                 | StatusEffect::Levitation
                 | StatusEffect::BadLuck
                 | StatusEffect::BadOmen
+                | StatusEffect::Darkness
+                | StatusEffect::WindCharged
+                | StatusEffect::Weaving
+                | StatusEffect::Oozing
+                | StatusEffect::Infested
         )
     }
 
@@ -180,3 +279,99 @@ impl StatusEffect {
         !self.is_neutral() && !self.is_negative()
     }
 }
+
+/// A status effect of some strength and duration, as given by potions.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StatusEffectInstance {
+    pub effect: StatusEffect,
+    /// Level of the effect, minus one.
+    pub amplifier: i8,
+    /// Duration in game ticks.
+    pub duration: i32,
+    /// Whether the effect is shown with fewer particles, as for effects of beacons.
+    pub ambient: bool,
+    pub show_particles: bool,
+    pub show_icon: bool,
+}
+
+impl StatusEffectInstance {
+    /// Creates an effect with the given amplifier and duration, shown as usual.
+    pub fn new(effect: StatusEffect, amplifier: i8, duration: i32) -> Self {
+        Self {
+            effect,
+            amplifier,
+            duration,
+            ambient: false,
+            show_particles: true,
+            show_icon: true,
+        }
+    }
+
+    /// Reads an effect compound, with either the numeric ids and capitalised
+    /// tag names used before 1.20.2, or the named ids and tag names used after.
+    ///
+    /// Returns `None` if the effect is missing or unknown.
+    pub(crate) fn from_nbt_value(value: &nbt::Value) -> Option<Self> {
+        let compound = match value {
+            nbt::Value::Compound(compound) => compound,
+            _ => return None,
+        };
+        let tag = |legacy_name: &str, name: &str| {
+            compound.get(legacy_name).or_else(|| compound.get(name))
+        };
+        let flag = |legacy_name: &str, name: &str, default: bool| match tag(legacy_name, name) {
+            Some(nbt::Value::Byte(flag)) => *flag != 0,
+            _ => default,
+        };
+
+        let effect = match tag("Id", "id")? {
+            nbt::Value::Byte(id) => StatusEffect::from_id((*id as u8).into())?,
+            nbt::Value::Int(id) => StatusEffect::from_id(*id)?,
+            nbt::Value::String(id) => StatusEffect::from_name(id)?,
+            _ => return None,
+        };
+        Some(Self {
+            effect,
+            amplifier: match tag("Amplifier", "amplifier") {
+                Some(nbt::Value::Byte(amplifier)) => *amplifier,
+                _ => 0,
+            },
+            duration: match tag("Duration", "duration") {
+                Some(nbt::Value::Int(duration)) => *duration,
+                _ => 1,
+            },
+            ambient: flag("Ambient", "ambient", false),
+            show_particles: flag("ShowParticles", "show_particles", true),
+            show_icon: flag("ShowIcon", "show_icon", true),
+        })
+    }
+
+    /// Generates the effect compound for the given Minecraft version.
+    pub(crate) fn to_nbt_value(&self, data_version: McVersion) -> nbt::Value {
+        let mut compound = nbt::Map::new();
+        if data_version < McVersion::from_str("23w31a").unwrap() {
+            let id = i32::from(self.effect) as i8;
+            compound.insert("Id".into(), nbt::Value::Byte(id));
+            compound.insert("Amplifier".into(), nbt::Value::Byte(self.amplifier));
+            compound.insert("Duration".into(), nbt::Value::Int(self.duration));
+            compound.insert("Ambient".into(), nbt::Value::Byte(self.ambient as i8));
+            compound.insert(
+                "ShowParticles".into(),
+                nbt::Value::Byte(self.show_particles as i8),
+            );
+            compound.insert("ShowIcon".into(), nbt::Value::Byte(self.show_icon as i8));
+        } else {
+            let id = format!("minecraft:{}", self.effect.name());
+            compound.insert("id".into(), nbt::Value::String(id));
+            compound.insert("amplifier".into(), nbt::Value::Byte(self.amplifier));
+            compound.insert("duration".into(), nbt::Value::Int(self.duration));
+            compound.insert("ambient".into(), nbt::Value::Byte(self.ambient as i8));
+            compound.insert(
+                "show_particles".into(),
+                nbt::Value::Byte(self.show_particles as i8),
+            );
+            compound.insert("show_icon".into(), nbt::Value::Byte(self.show_icon as i8));
+        }
+        nbt::Value::Compound(compound)
+    }
+}
@@ -0,0 +1,283 @@
+//! Conversion between plain text and JSON text components, as used for item
+//! names, lore and book pages, and layout of text on book pages.
+
+use std::convert::TryFrom;
+
+/// Width of the text area of a book page, in pixels of the default font.
+const PAGE_WIDTH: u32 = 114;
+/// Number of lines of text fitting on a book page.
+const LINES_PER_PAGE: usize = 14;
+
+/// Plain text as a JSON text component, as used for names from 1.13.
+pub(crate) fn json_text(text: &str) -> String {
+    serde_json::json!({ "text": text }).to_string()
+}
+
+/// Text that may be either plain text or a JSON text component, as a JSON text
+/// component.
+///
+/// Only JSON strings, objects and arrays are taken to be JSON text, so that
+/// plain text such as "42" is not mistaken for it.
+pub(crate) fn as_json_text(text: &str) -> String {
+    match serde_json::from_str(text) {
+        Ok(serde_json::Value::String(_))
+        | Ok(serde_json::Value::Object(_))
+        | Ok(serde_json::Value::Array(_)) => text.to_string(),
+        _ => json_text(text),
+    }
+}
+
+/// The plain text of a JSON text component, with all formatting removed.
+///
+/// Text that is not valid JSON is returned as it is.
+pub(crate) fn plain_text(json: &str) -> String {
+    match serde_json::from_str(json) {
+        Ok(value) => {
+            let mut text = String::new();
+            push_plain_text(&value, &mut text);
+            text
+        }
+        Err(_) => json.to_string(),
+    }
+}
+
+fn push_plain_text(value: &serde_json::Value, text: &mut String) {
+    match value {
+        serde_json::Value::String(string) => text.push_str(string),
+        serde_json::Value::Array(components) => {
+            for component in components {
+                push_plain_text(component, text);
+            }
+        }
+        serde_json::Value::Object(component) => {
+            if let Some(serde_json::Value::String(string)) = component.get("text") {
+                text.push_str(string);
+            }
+            if let Some(extra) = component.get("extra") {
+                push_plain_text(extra, text);
+            }
+        }
+        serde_json::Value::Null => (),
+        other => text.push_str(&other.to_string()),
+    }
+}
+
+/// A JSON text component as the NBT text component stored in item data from
+/// 1.21.5.
+///
+/// Text that is not valid JSON is kept as a plain string.
+pub(crate) fn text_to_nbt(json: &str) -> nbt::Value {
+    match serde_json::from_str(json) {
+        Ok(value) => json_to_nbt(&value),
+        Err(_) => nbt::Value::String(json.to_string()),
+    }
+}
+
+/// An NBT text component, as stored in item data from 1.21.5, as a JSON text
+/// component.
+pub(crate) fn text_from_nbt(value: &nbt::Value) -> String {
+    nbt_to_json(value).to_string()
+}
+
+/// NBT text components as a list. NBT lists hold values of a single type, so
+/// plain strings are turned into text compounds where mixed with compounds.
+pub(crate) fn text_list(values: Vec<nbt::Value>) -> nbt::Value {
+    let mixed = values
+        .iter()
+        .any(|value| matches!(value, nbt::Value::String(_)))
+        && values
+            .iter()
+            .any(|value| !matches!(value, nbt::Value::String(_)));
+    if !mixed {
+        return nbt::Value::List(values);
+    }
+    let values = values
+        .into_iter()
+        .map(|value| match value {
+            nbt::Value::String(text) => {
+                let mut compound = nbt::Map::new();
+                compound.insert("text".into(), nbt::Value::String(text));
+                nbt::Value::Compound(compound)
+            }
+            other => other,
+        })
+        .collect();
+    nbt::Value::List(values)
+}
+
+fn json_to_nbt(value: &serde_json::Value) -> nbt::Value {
+    match value {
+        serde_json::Value::Null => nbt::Value::String(String::new()),
+        serde_json::Value::Bool(flag) => nbt::Value::Byte(*flag as i8),
+        serde_json::Value::Number(number) => match number.as_i64() {
+            Some(int) if i32::try_from(int).is_ok() => nbt::Value::Int(int as i32),
+            Some(long) => nbt::Value::Long(long),
+            None => nbt::Value::Double(number.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(text) => nbt::Value::String(text.clone()),
+        serde_json::Value::Array(values) => text_list(values.iter().map(json_to_nbt).collect()),
+        serde_json::Value::Object(compound) => nbt::Value::Compound(
+            compound
+                .iter()
+                .map(|(key, value)| (key.clone(), json_to_nbt(value)))
+                .collect(),
+        ),
+    }
+}
+
+fn nbt_to_json(value: &nbt::Value) -> serde_json::Value {
+    match value {
+        // Bytes of text components are flags, such as "bold"
+        nbt::Value::Byte(flag) => serde_json::Value::Bool(*flag != 0),
+        nbt::Value::Short(number) => (*number).into(),
+        nbt::Value::Int(number) => (*number).into(),
+        nbt::Value::Long(number) => (*number).into(),
+        nbt::Value::Float(number) => (*number).into(),
+        nbt::Value::Double(number) => (*number).into(),
+        nbt::Value::String(text) => serde_json::Value::String(text.clone()),
+        nbt::Value::List(values) => values.iter().map(nbt_to_json).collect(),
+        nbt::Value::Compound(compound) => serde_json::Value::Object(
+            compound
+                .iter()
+                .map(|(key, value)| (key.clone(), nbt_to_json(value)))
+                .collect(),
+        ),
+        nbt::Value::ByteArray(values) => values.iter().map(|value| *value as i64).collect(),
+        nbt::Value::IntArray(values) => values.iter().map(|value| *value as i64).collect(),
+        nbt::Value::LongArray(values) => values.iter().copied().collect(),
+    }
+}
+
+/// Splits plain text into book pages, wrapping lines at spaces as the game does.
+///
+/// Line breaks in the text are kept, except at the top of a page. Words too
+/// long for a line are split.
+pub(crate) fn book_pages(text: &str) -> Vec<String> {
+    let mut pages = Vec::new();
+    let mut page = String::new();
+    let mut lines_on_page = 0;
+
+    for paragraph in text.split('\n') {
+        let lines = wrapped_lines(paragraph);
+        let line_count = lines.len();
+        for (index, line) in lines.into_iter().enumerate() {
+            if lines_on_page == LINES_PER_PAGE {
+                pages.push(page.trim_end_matches('\n').to_string());
+                page.clear();
+                lines_on_page = 0;
+            }
+            if lines_on_page == 0 && line.is_empty() && !pages.is_empty() {
+                continue;
+            }
+            page.push_str(&line);
+            if index + 1 == line_count {
+                page.push('\n');
+            }
+            lines_on_page += 1;
+        }
+    }
+    if !page.is_empty() || pages.is_empty() {
+        pages.push(page.trim_end_matches('\n').to_string());
+    }
+    pages
+}
+
+/// The lines of a paragraph of text, as wrapped on a book page. Lines wrapped
+/// at a space end with that space.
+fn wrapped_lines(paragraph: &str) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+
+    for (index, word) in paragraph.split(' ').enumerate() {
+        let word_width: u32 = word.chars().map(char_width).sum();
+        if index > 0 {
+            line.push(' ');
+            width += char_width(' ');
+            if width + word_width > PAGE_WIDTH {
+                lines.push(line.clone());
+                line.clear();
+                width = 0;
+            }
+        }
+        for c in word.chars() {
+            if width + char_width(c) > PAGE_WIDTH {
+                lines.push(line.clone());
+                line.clear();
+                width = 0;
+            }
+            line.push(c);
+            width += char_width(c);
+        }
+    }
+    lines.push(line);
+    lines
+}
+
+/// Width of a character in the default font, in pixels, including spacing.
+fn char_width(c: char) -> u32 {
+    match c {
+        '!' | '\'' | ',' | '.' | ':' | ';' | 'i' | '|' => 2,
+        '`' | 'l' => 3,
+        ' ' | 'I' | '[' | ']' | 't' => 4,
+        '"' | '(' | ')' | '*' | '<' | '>' | 'f' | 'k' | '{' | '}' => 5,
+        '@' | '~' => 7,
+        _ => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nbt_lookup::nbt_value_lookup_string;
+
+    #[test]
+    fn plain_text_survives_json_round_trip() {
+        for text in &["Excalibur", "\"quoted\"", "§cRed", ""] {
+            assert_eq!(plain_text(&json_text(text)), *text);
+        }
+    }
+
+    #[test]
+    fn book_pages_are_wrapped() {
+        assert_eq!(book_pages(""), vec![String::new()]);
+        assert_eq!(book_pages("Once upon a time"), vec!["Once upon a time"]);
+
+        let text = "word ".repeat(200);
+        let pages = book_pages(text.trim_end());
+        assert!(pages.len() > 1);
+        assert_eq!(pages.concat(), text.trim_end());
+        assert!(pages.iter().all(|page| !page.contains('\n')));
+
+        // Paragraph breaks are kept, but not at the top of a page
+        let pages = book_pages(&"Chapter\n".repeat(20));
+        assert_eq!(pages[0], vec!["Chapter"; 14].join("\n"));
+        assert!(pages[1].starts_with("Chapter"));
+    }
+
+    #[test]
+    fn text_survives_nbt_round_trip() {
+        let json = r#"{"bold":true,"extra":["of",{"text":"Doom"}],"text":"Sword "}"#;
+        let nbt = text_to_nbt(json);
+        assert_eq!(
+            nbt_value_lookup_string(&nbt, "text").as_deref(),
+            Some("Sword ")
+        );
+        assert_eq!(
+            text_from_nbt(&nbt),
+            json.replace(r#"["of","#, r#"[{"text":"of"},"#)
+        );
+        assert_eq!(
+            text_from_nbt(&text_to_nbt(&json_text("Excalibur"))),
+            json_text("Excalibur")
+        );
+    }
+
+    #[test]
+    fn plain_text_joins_extra_components() {
+        let json = r#"{"text":"Sword ","extra":[{"text":"of","bold":true}," Doom"]}"#;
+        assert_eq!(plain_text(json), "Sword of Doom");
+        assert_eq!(as_json_text(json), json);
+        assert_eq!(as_json_text("42"), json_text("42"));
+    }
+}
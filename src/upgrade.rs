@@ -0,0 +1,643 @@
+//! Conversion of whole Minecraft 1.12.2 worlds to later versions.
+//!
+//! The world is upgraded chunk by chunk, without involving the game's own
+//! upgrader: each chunk is read with the pre-flattening reader, and written
+//! anew in the chunk format of the target version. Block entities, items and
+//! entities are carried over from the original chunk, with their ids and
+//! tags upgraded where the flattening (or later versions) changed them.
+//!
+//! ```no_run
+//! use std::str::FromStr;
+//! use mcprogedit::mc_version::McVersion;
+//! use mcprogedit::upgrade::upgrade_world;
+//!
+//! let report = upgrade_world(
+//!     std::path::Path::new("saves/MyOldWorld"),
+//!     McVersion::from_str("1.16.5").unwrap(),
+//! )
+//! .unwrap();
+//! println!("Upgraded {} chunks", report.chunks);
+//! ```
+
+mod items;
+
+use std::path::Path;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::block::Block;
+use crate::chunk::{Chunk, RawChunkData};
+use crate::coordinates::BlockColumnCoord;
+use crate::entity;
+use crate::mc_version::McVersion;
+use crate::nbt_lookup::*;
+use crate::positioning::Surface4;
+use crate::region::Region;
+use crate::save_format::ChunkFormat;
+use crate::save_transaction::SaveTransaction;
+use crate::text::json_text;
+use crate::world_excerpt::entity_chunk_with_entities;
+
+/// Region directories of the overworld, the nether and the end, and whether
+/// they get the extended world height of 1.18.
+const DIMENSIONS: [(&str, bool); 3] = [
+    ("region", true),
+    ("DIM-1/region", false),
+    ("DIM1/region", false),
+];
+
+/// Reason a world could not be upgraded.
+#[derive(Error, Debug)]
+pub enum UpgradeError {
+    #[error("Not a pre-flattening world: saved by {0}")]
+    NotPreFlattening(McVersion),
+    #[error("Cannot upgrade worlds to {0}")]
+    UnsupportedTarget(McVersion),
+}
+
+/// Summary of an upgraded world.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UpgradeReport {
+    /// Number of region files written.
+    pub regions: usize,
+    /// Number of chunks upgraded.
+    pub chunks: usize,
+    /// Number of block entities carried over.
+    pub block_entities: usize,
+    /// Number of entities carried over.
+    pub entities: usize,
+    /// Whether the world generation settings of level.dat are valid for the
+    /// target.
+    ///
+    /// The world generation settings are not rewritten, so for targets from
+    /// 20w20a, and for flat or otherwise customized world generation, they are
+    /// left as they were, with a warning.
+    pub generator_settings_upgraded: bool,
+}
+
+/// Upgrades the Minecraft 1.12.2 world saved at `world_directory` to `target`.
+///
+/// All region files of the overworld, the nether and the end are rewritten,
+/// and replaced together once every one of them has been written. Chunks that
+/// are already saved by a post-flattening version are kept as they are.
+///
+/// For targets with the extended world height of 1.18, the blocks below y = 0
+/// of upgraded overworld chunks are air. For targets from 20w45a, entities are
+/// moved to the entity region files of the dimension.
+///
+/// # Errors
+/// Returns [`UpgradeError::NotPreFlattening`] if the world was not saved by a
+/// pre-flattening version, or [`UpgradeError::UnsupportedTarget`] for targets
+/// before 1.13, or from 24w09a where items got a new format.
+///
+/// # Panics
+/// Panics if `world_directory` does not hold a readable world save.
+pub fn upgrade_world(
+    world_directory: &Path,
+    target: McVersion,
+) -> Result<UpgradeReport, UpgradeError> {
+    let level_dat_file = world_directory.join("level.dat");
+    if !level_dat_file.is_file() {
+        panic!("Not a valid world.dat file: {:?}", level_dat_file);
+    }
+    let mut level_dat = std::fs::File::open(&level_dat_file).expect("Unable to open level.dat");
+    let level_dat_blob =
+        nbt::Blob::from_gzip_reader(&mut level_dat).expect("Unable to parse level.dat contents");
+
+    // Worlds from before 15w32a have no data version at all
+    let source =
+        McVersion::from_id(nbt_blob_lookup_int(&level_dat_blob, "Data/DataVersion").unwrap_or(100));
+    if ChunkFormat::for_version(source).ok() != Some(ChunkFormat::PreFlattening) {
+        return Err(UpgradeError::NotPreFlattening(source));
+    }
+    let format = match ChunkFormat::for_writing(target) {
+        Ok(ChunkFormat::PreFlattening) | Err(_) => {
+            return Err(UpgradeError::UnsupportedTarget(target))
+        }
+        Ok(_) if target >= version("24w09a") => {
+            return Err(UpgradeError::UnsupportedTarget(target))
+        }
+        Ok(format) => format,
+    };
+
+    let mut report = UpgradeReport::default();
+    let mut transaction = SaveTransaction::new(world_directory);
+
+    for (region_directory, overworld) in &DIMENSIONS {
+        let (min_y, height) = if format.has_extended_height() && *overworld {
+            (-64, 384)
+        } else {
+            (0, 256)
+        };
+
+        let region_directory = world_directory.join(region_directory);
+        let entries = match std::fs::read_dir(&region_directory) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let region_file = entry.path();
+            let name = region_file.file_name().and_then(|name| name.to_str());
+            if !matches!(name, Some(name) if name.starts_with("r.") && name.ends_with(".mca")) {
+                continue;
+            }
+
+            let old_region = Region::open(&region_file);
+            let mut new_region = Region::new();
+            let mut entity_region: Option<Region> = None;
+            for chunk_info in old_region.existing_chunks() {
+                let raw_chunk = old_region.chunk_data(&chunk_info.coordinates);
+                let new_chunk = match upgraded_chunk(
+                    &raw_chunk,
+                    target,
+                    format,
+                    (min_y, height),
+                    &mut report,
+                ) {
+                    Some((nbt, entity_chunk)) => {
+                        if let Some(entity_chunk) = entity_chunk {
+                            entity_region
+                                .get_or_insert_with(Region::new)
+                                .set_chunk_data(&chunk_info.coordinates, entity_chunk);
+                        }
+                        RawChunkData::new_zlib(&nbt)
+                    }
+                    None => raw_chunk,
+                };
+                new_region.set_chunk_data(&chunk_info.coordinates, new_chunk);
+            }
+            new_region.save_in_transaction(&region_file, &mut transaction);
+            report.regions += 1;
+
+            if let Some(entity_region) = entity_region {
+                let entity_directory = region_directory.with_file_name("entities");
+                std::fs::create_dir_all(&entity_directory).unwrap_or_else(|err| {
+                    panic!("Unable to create {:?}: {}", entity_directory, err)
+                });
+                let entity_region_file = entity_directory.join(entry.file_name());
+                entity_region.save_in_transaction(&entity_region_file, &mut transaction);
+            }
+        }
+    }
+
+    let generator =
+        nbt_blob_lookup_string(&level_dat_blob, "Data/generatorName").unwrap_or_default();
+    report.generator_settings_upgraded = target < version("20w20a")
+        && (generator == "default" || generator == "largeBiomes" || generator == "amplified");
+    if !report.generator_settings_upgraded {
+        eprintln!(
+            "[warning] World generation settings (generator {:?}) not upgraded to {}",
+            generator, target
+        );
+    }
+
+    let level_dat_blob = upgraded_level_dat(&level_dat_blob, target);
+    let staging_path = transaction.staging_path(&level_dat_file);
+    let mut file = std::fs::File::create(&staging_path)
+        .unwrap_or_else(|err| panic!("Unable to create {:?}: {}", staging_path, err));
+    level_dat_blob
+        .to_gzip_writer(&mut file)
+        .unwrap_or_else(|err| panic!("Unable to write {:?}: {}", staging_path, err));
+
+    transaction.commit();
+    Ok(report)
+}
+
+/// Upgrades a single chunk, or returns `None` if it is not a pre-flattening chunk.
+///
+/// For targets that store entities in entity region files (from 20w45a), the
+/// entity chunk holding the entities of the chunk is returned along with it.
+fn upgraded_chunk(
+    raw_chunk: &RawChunkData,
+    target: McVersion,
+    format: ChunkFormat,
+    (min_y, height): (i64, usize),
+    report: &mut UpgradeReport,
+) -> Option<(nbt::Blob, Option<RawChunkData>)> {
+    let original = raw_chunk.to_nbt();
+    let source = McVersion::from_id(nbt_blob_lookup_int(&original, "DataVersion").unwrap_or(100));
+    if ChunkFormat::for_version(source).ok() != Some(ChunkFormat::PreFlattening) {
+        return None;
+    }
+
+    let mut chunk = Chunk::from_pre_flattening_nbt(&original, source);
+    chunk.retarget(target, min_y, height);
+    // All pumpkins had a carved face before the flattening
+    for facing in &[
+        Surface4::East,
+        Surface4::North,
+        Surface4::South,
+        Surface4::West,
+    ] {
+        chunk.blocks.replace(
+            &Block::Pumpkin { facing: *facing },
+            &Block::CarvedPumpkin { facing: *facing },
+        );
+    }
+    let mut nbt = chunk.to_nbt();
+
+    // Block entities and entities are taken from the original chunk, rather
+    // than regenerated, so that nothing mcprogedit does not model is lost.
+    let block_entities: Vec<nbt::Value> = nbt_blob_lookup_list(&original, "Level/TileEntities")
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|block_entity| upgraded_block_entity(block_entity, &chunk, target))
+        .collect();
+    report.block_entities += block_entities.len();
+
+    let entities: Vec<nbt::Value> = nbt_blob_lookup_list(&original, "Level/Entities")
+        .unwrap_or_default()
+        .iter()
+        .map(|entity| upgraded_entity(entity, target))
+        .collect();
+    report.entities += entities.len();
+    let (entities, entity_chunk) = if entity::is_stored_separately(target) {
+        let entity_chunk = if entities.is_empty() {
+            None
+        } else {
+            Some(entity_chunk_with_entities(
+                RawChunkData::Empty,
+                &chunk,
+                |_| false,
+                entity::entities_from_nbt_list(&entities),
+            ))
+        };
+        (Vec::new(), entity_chunk)
+    } else {
+        (entities, None)
+    };
+
+    match format {
+        ChunkFormat::Flattening => {
+            if let Some(nbt::Value::Compound(level)) = nbt.get("Level") {
+                let mut level = level.clone();
+                level.insert("TileEntities".into(), nbt::Value::List(block_entities));
+                level.insert("Entities".into(), nbt::Value::List(entities));
+                nbt.insert("Level", nbt::Value::Compound(level)).unwrap();
+            }
+        }
+        _ => {
+            nbt.insert("block_entities", nbt::Value::List(block_entities))
+                .unwrap();
+        }
+    }
+
+    report.chunks += 1;
+    Some((nbt, entity_chunk))
+}
+
+/// Returns the upgraded block entity, or `None` for block entities that were
+/// replaced by block states with the flattening.
+fn upgraded_block_entity(
+    value: &nbt::Value,
+    chunk: &Chunk,
+    target: McVersion,
+) -> Option<nbt::Value> {
+    let mut block_entity = match value {
+        nbt::Value::Compound(block_entity) => block_entity.clone(),
+        _ => return None,
+    };
+    let id = nbt_value_lookup_string(value, "id").unwrap_or_default();
+    if id == "minecraft:flower_pot" || id == "minecraft:noteblock" {
+        return None;
+    }
+
+    // Trapped chests got their own block entity id with the flattening
+    if id == "minecraft:chest" {
+        if let Some(Block::TrappedChest(_)) = block_at(value, chunk) {
+            block_entity.insert(
+                "id".into(),
+                nbt::Value::String("minecraft:trapped_chest".into()),
+            );
+        }
+    }
+
+    if let Some(nbt::Value::String(name)) = block_entity.get_mut("CustomName") {
+        *name = json_text(name);
+    }
+    if let Some(value) = block_entity.get_mut("Items") {
+        *value = items::upgraded_items(value, target);
+    }
+    if let Some(value) = block_entity.get_mut("RecordItem") {
+        *value = items::upgraded_item(value, target);
+    }
+    if id == "minecraft:sign" && target >= version("23w12a") {
+        upgrade_sign_text(&mut block_entity);
+    }
+
+    Some(nbt::Value::Compound(block_entity))
+}
+
+/// The block holding the block entity `value`, as read into `chunk`.
+fn block_at<'a>(value: &nbt::Value, chunk: &'a Chunk) -> Option<&'a Block> {
+    let x = nbt_value_lookup_int(value, "x")? as i64;
+    let y = nbt_value_lookup_int(value, "y")? as i64;
+    let z = nbt_value_lookup_int(value, "z")? as i64;
+    let chunk_offset: BlockColumnCoord = (*chunk.chunk_coordinates()).into();
+    chunk.blocks.block_at((
+        (x - chunk_offset.0) as usize,
+        (y - chunk.min_y()) as usize,
+        (z - chunk_offset.1) as usize,
+    ))
+}
+
+/// Moves sign text into the front side of the sign, as introduced with 23w12a.
+fn upgrade_sign_text(sign: &mut nbt::Map<String, nbt::Value>) {
+    let messages: Vec<nbt::Value> = ["Text1", "Text2", "Text3", "Text4"]
+        .iter()
+        .map(|key| {
+            sign.remove(*key)
+                .unwrap_or_else(|| nbt::Value::String("\"\"".into()))
+        })
+        .collect();
+    let colour = match sign.remove("Color") {
+        Some(nbt::Value::String(colour)) => colour,
+        _ => "black".into(),
+    };
+    let glowing = sign.remove("GlowingText").unwrap_or(nbt::Value::Byte(0));
+
+    let side = |messages: Vec<nbt::Value>, colour: String, glowing: nbt::Value| {
+        let mut side = nbt::Map::new();
+        side.insert("messages".into(), nbt::Value::List(messages));
+        side.insert("color".into(), nbt::Value::String(colour));
+        side.insert("has_glowing_text".into(), glowing);
+        nbt::Value::Compound(side)
+    };
+    let empty = vec![nbt::Value::String("\"\"".into()); 4];
+    sign.insert("front_text".into(), side(messages, colour, glowing));
+    sign.insert(
+        "back_text".into(),
+        side(empty, "black".into(), nbt::Value::Byte(0)),
+    );
+    sign.insert("is_waxed".into(), nbt::Value::Byte(0));
+}
+
+/// Returns the upgraded entity, with the items it holds and its riders upgraded.
+fn upgraded_entity(value: &nbt::Value, target: McVersion) -> nbt::Value {
+    let mut entity = match value {
+        nbt::Value::Compound(entity) => entity.clone(),
+        other => return other.clone(),
+    };
+
+    if let Some(nbt::Value::String(id)) = entity.get_mut("id") {
+        if id == "minecraft:zombie_pigman" && target >= version("20w09a") {
+            *id = "minecraft:zombified_piglin".into();
+        }
+    }
+    if let Some(nbt::Value::String(name)) = entity.get_mut("CustomName") {
+        *name = json_text(name);
+    }
+    for key in &["Item", "SaddleItem", "ArmorItem", "DecorItem"] {
+        if let Some(value) = entity.get_mut(*key) {
+            *value = items::upgraded_item(value, target);
+        }
+    }
+    for key in &["Items", "HandItems", "ArmorItems", "Inventory"] {
+        if let Some(value) = entity.get_mut(*key) {
+            *value = items::upgraded_items(value, target);
+        }
+    }
+    if let Some(nbt::Value::List(passengers)) = entity.get_mut("Passengers") {
+        for passenger in passengers.iter_mut() {
+            *passenger = upgraded_entity(passenger, target);
+        }
+    }
+
+    nbt::Value::Compound(entity)
+}
+
+/// Returns level.dat with the version of `target`, and the single player's
+/// items upgraded. World generation settings are kept as they are.
+fn upgraded_level_dat(level_dat: &nbt::Blob, target: McVersion) -> nbt::Blob {
+    let mut data = match level_dat.get("Data") {
+        Some(nbt::Value::Compound(data)) => data.clone(),
+        _ => panic!("level.dat has no Data compound"),
+    };
+
+    let mut version_tag = nbt::Map::new();
+    version_tag.insert("Id".into(), nbt::Value::Int(target.id()));
+    version_tag.insert("Name".into(), nbt::Value::String(target.to_string()));
+    let snapshot = target.name().contains('w') || target.name().contains('-');
+    version_tag.insert("Snapshot".into(), nbt::Value::Byte(snapshot as i8));
+    data.insert("Version".into(), nbt::Value::Compound(version_tag));
+    data.insert("DataVersion".into(), nbt::Value::Int(target.id()));
+
+    // The single player's items are upgraded along with level.dat
+    if let Some(nbt::Value::Compound(player)) = data.get_mut("Player") {
+        for key in &["Inventory", "EnderItems"] {
+            if let Some(value) = player.get_mut(*key) {
+                *value = items::upgraded_items(value, target);
+            }
+        }
+    }
+
+    let mut level_dat = level_dat.clone();
+    level_dat
+        .insert("Data", nbt::Value::Compound(data))
+        .unwrap();
+    level_dat
+}
+
+fn version(name: &str) -> McVersion {
+    McVersion::from_str(name).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bounded_ints::Int0Through7;
+    use crate::entity::Entity;
+    use crate::positioning::Axis2;
+    use crate::world_excerpt::WorldExcerpt;
+
+    fn copy_of_1_12_2_save(name: &str) -> std::path::PathBuf {
+        let world =
+            std::env::temp_dir().join(format!("mcprogedit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&world);
+        std::fs::create_dir_all(world.join("region")).unwrap();
+        std::fs::copy("tests/saves/1_12_2/level.dat", world.join("level.dat")).unwrap();
+        std::fs::copy(
+            "tests/saves/1_12_2/region/r.0.0.mca",
+            world.join("region").join("r.0.0.mca"),
+        )
+        .unwrap();
+        world
+    }
+
+    /// The block as expected after the upgrade, where the modern formats
+    /// have no room for the 1.12 block, or items are not compared.
+    fn upgraded(block: Option<&Block>) -> Option<Block> {
+        match block? {
+            Block::Leaves {
+                material,
+                distance_to_trunk: None,
+                persistent,
+            } => Some(Block::Leaves {
+                material: *material,
+                distance_to_trunk: Some(Int0Through7::new_saturating(1)),
+                persistent: *persistent,
+            }),
+            Block::NetherPortal { alignment: None } => Some(Block::NetherPortal {
+                alignment: Some(Axis2::X),
+            }),
+            Block::Pumpkin { facing } => Some(Block::CarvedPumpkin { facing: *facing }),
+            Block::Jukebox(_) => Some(Block::jukebox()),
+            block => Some(block.clone()),
+        }
+    }
+
+    fn assert_same_blocks(before: &WorldExcerpt, after: &WorldExcerpt) {
+        let (x_dim, y_dim, z_dim) = before.dim();
+        for x in 0..x_dim as i64 {
+            for y in 0..y_dim as i64 {
+                for z in 0..z_dim as i64 {
+                    let after = match after.block_at((x, y, z).into()) {
+                        Some(Block::Jukebox(_)) => Some(Block::jukebox()),
+                        block => block.cloned(),
+                    };
+                    assert_eq!(
+                        upgraded(before.block_at((x, y, z).into())),
+                        after,
+                        "at {:?}",
+                        (x, y, z),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_upgrade(name: &str, target: &str) {
+        let world = copy_of_1_12_2_save(name);
+        let (p1, p2) = ((0, 55, 0).into(), (255, 66, 15).into());
+        let before = WorldExcerpt::from_save(p1, p2, &world);
+
+        let report = upgrade_world(&world, version(target)).unwrap();
+        assert_eq!(report.regions, 1);
+        assert!(report.chunks > 0);
+        assert!(report.block_entities > 0);
+
+        let after = WorldExcerpt::from_save(p1, p2, &world);
+        assert_same_blocks(&before, &after);
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn upgrade_to_1_16_5_keeps_blocks() {
+        check_upgrade("upgrade-1-16-5", "1.16.5");
+    }
+
+    #[test]
+    fn upgrade_to_1_18_2_keeps_blocks() {
+        check_upgrade("upgrade-1-18-2", "1.18.2");
+    }
+
+    fn set_generator_name(world: &Path, name: &str) {
+        let mut level_dat = std::fs::File::open(world.join("level.dat")).unwrap();
+        let mut level_dat = nbt::Blob::from_gzip_reader(&mut level_dat).unwrap();
+        let mut data = match level_dat.get("Data") {
+            Some(nbt::Value::Compound(data)) => data.clone(),
+            _ => panic!("level.dat Data not found"),
+        };
+        data.insert("generatorName".into(), nbt::Value::String(name.into()));
+        level_dat
+            .insert("Data", nbt::Value::Compound(data))
+            .unwrap();
+        let mut file = std::fs::File::create(world.join("level.dat")).unwrap();
+        level_dat.to_gzip_writer(&mut file).unwrap();
+    }
+
+    #[test]
+    fn upgrade_updates_level_dat() {
+        let data_version = |world: &Path| {
+            let mut level_dat = std::fs::File::open(world.join("level.dat")).unwrap();
+            let level_dat = nbt::Blob::from_gzip_reader(&mut level_dat).unwrap();
+            (
+                nbt_blob_lookup_int(&level_dat, "Data/DataVersion"),
+                nbt_blob_lookup_string(&level_dat, "Data/Version/Name"),
+            )
+        };
+
+        // Flat world generator settings are not upgraded, but the version is
+        let world = copy_of_1_12_2_save("upgrade-level-dat");
+        let report = upgrade_world(&world, version("1.14.4")).unwrap();
+        assert!(!report.generator_settings_upgraded);
+        assert_eq!(
+            data_version(&world),
+            (Some(version("1.14.4").id()), Some("1.14.4".into()))
+        );
+
+        let world = copy_of_1_12_2_save("upgrade-level-dat");
+        set_generator_name(&world, "default");
+        let report = upgrade_world(&world, version("1.14.4")).unwrap();
+        assert!(report.generator_settings_upgraded);
+
+        let world = copy_of_1_12_2_save("upgrade-level-dat");
+        set_generator_name(&world, "default");
+        let report = upgrade_world(&world, version("1.16.5")).unwrap();
+        assert!(!report.generator_settings_upgraded);
+        assert_eq!(
+            data_version(&world),
+            (Some(version("1.16.5").id()), Some("1.16.5".into()))
+        );
+
+        // The world is no longer a pre-flattening world
+        assert!(matches!(
+            upgrade_world(&world, version("1.15.2")),
+            Err(UpgradeError::NotPreFlattening(_))
+        ));
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn upgrade_to_1_17_1_moves_entities_to_entity_regions() {
+        let world = copy_of_1_12_2_save("upgrade-entities");
+        let (p1, p2) = ((0, 55, 0).into(), (31, 66, 15).into());
+        let mut excerpt = WorldExcerpt::from_save(p1, p2, &world);
+        let mut cow = Entity::new("minecraft:cow", (2.5, 5.0, 3.5));
+        cow.uuid = Some(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        cow.custom_name = Some("Daisy".into());
+        excerpt.entities_mut().push(cow.clone());
+        excerpt.to_save(p1, &world).unwrap();
+
+        let report = upgrade_world(&world, version("1.17.1")).unwrap();
+        assert!(report.entities > 0);
+        assert!(world.join("entities").join("r.0.0.mca").is_file());
+
+        let after = WorldExcerpt::from_save(p1, p2, &world);
+        cow.custom_name = Some(json_text("Daisy"));
+        assert!(after.entities().contains(&cow));
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn upgrade_to_unsupported_target_is_refused() {
+        let world = copy_of_1_12_2_save("upgrade-unsupported");
+        for target in &["1.12.2", "1.20.5"] {
+            assert!(matches!(
+                upgrade_world(&world, version(target)),
+                Err(UpgradeError::UnsupportedTarget(_))
+            ));
+        }
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn sign_text_moves_to_front_side() {
+        let mut sign = nbt::Map::new();
+        sign.insert("Text1".into(), nbt::Value::String(json_text("Hello")));
+        upgrade_sign_text(&mut sign);
+        let sign = nbt::Value::Compound(sign);
+        let messages = nbt_value_lookup_list(&sign, "front_text/messages").unwrap();
+        assert_eq!(messages.len(), 4);
+        assert_eq!(
+            messages[0],
+            nbt::Value::String(r#"{"text":"Hello"}"#.into())
+        );
+        assert_eq!(nbt_value_lookup_string(&sign, "Text1"), None);
+        assert_eq!(nbt_value_lookup_byte(&sign, "is_waxed"), Some(0));
+    }
+}
@@ -0,0 +1,382 @@
+//! Upgrading of item stacks saved by Minecraft 1.12.2.
+//!
+//! Before the flattening, many items shared an id and were told apart by their
+//! damage value. From 1.13 every variant has its own id, and the damage value
+//! is only used for durability, stored in the item's tag compound.
+
+use std::str::FromStr;
+
+use crate::mc_version::McVersion;
+use crate::nbt_lookup::*;
+use crate::text::json_text;
+
+/// Colours in the order of their pre-flattening data values.
+const COLOURS: [&str; 16] = [
+    "white",
+    "orange",
+    "magenta",
+    "light_blue",
+    "yellow",
+    "lime",
+    "pink",
+    "gray",
+    "light_gray",
+    "cyan",
+    "purple",
+    "blue",
+    "brown",
+    "green",
+    "red",
+    "black",
+];
+
+/// Wood types in the order of their pre-flattening data values.
+const WOODS: [&str; 6] = ["oak", "spruce", "birch", "jungle", "acacia", "dark_oak"];
+
+/// Pre-flattening item ids that were renamed with the flattening, independent of damage value.
+const FLATTENING_RENAMES: [(&str, &str); 48] = [
+    ("boat", "oak_boat"),
+    ("brick_block", "bricks"),
+    ("chorus_fruit_popped", "popped_chorus_fruit"),
+    ("deadbush", "dead_bush"),
+    ("end_bricks", "end_stone_bricks"),
+    ("fence", "oak_fence"),
+    ("fence_gate", "oak_fence_gate"),
+    ("firework_charge", "firework_star"),
+    ("fireworks", "firework_rocket"),
+    ("grass", "grass_block"),
+    ("hardened_clay", "terracotta"),
+    ("lit_pumpkin", "jack_o_lantern"),
+    ("magma", "magma_block"),
+    ("melon", "melon_slice"),
+    ("melon_block", "melon"),
+    ("mob_spawner", "spawner"),
+    ("nether_brick", "nether_bricks"),
+    ("netherbrick", "nether_brick"),
+    ("noteblock", "note_block"),
+    ("pumpkin", "carved_pumpkin"),
+    ("quartz_ore", "nether_quartz_ore"),
+    ("record_11", "music_disc_11"),
+    ("record_13", "music_disc_13"),
+    ("record_blocks", "music_disc_blocks"),
+    ("record_cat", "music_disc_cat"),
+    ("record_chirp", "music_disc_chirp"),
+    ("record_far", "music_disc_far"),
+    ("record_mall", "music_disc_mall"),
+    ("record_mellohi", "music_disc_mellohi"),
+    ("record_stal", "music_disc_stal"),
+    ("record_strad", "music_disc_strad"),
+    ("record_wait", "music_disc_wait"),
+    ("record_ward", "music_disc_ward"),
+    ("red_nether_brick", "red_nether_bricks"),
+    ("reeds", "sugar_cane"),
+    ("silver_glazed_terracotta", "light_gray_glazed_terracotta"),
+    ("silver_shulker_box", "light_gray_shulker_box"),
+    ("slime", "slime_block"),
+    ("snow", "snow_block"),
+    ("snow_layer", "snow"),
+    ("speckled_melon", "glistering_melon_slice"),
+    ("stone_stairs", "cobblestone_stairs"),
+    ("trapdoor", "oak_trapdoor"),
+    ("waterlily", "lily_pad"),
+    ("web", "cobweb"),
+    ("wooden_button", "oak_button"),
+    ("wooden_door", "oak_door"),
+    ("wooden_pressure_plate", "oak_pressure_plate"),
+];
+
+/// Returns the upgraded version of the item stack compound `value`, as saved by 1.12.2.
+///
+/// All tags other than the id, damage value and the tags handled here (e.g.
+/// "Slot" and "Count") are kept as they are.
+pub(crate) fn upgraded_item(value: &nbt::Value, target: McVersion) -> nbt::Value {
+    let mut item = match value {
+        nbt::Value::Compound(item) => item.clone(),
+        other => return other.clone(),
+    };
+    let id = nbt_value_lookup_string(value, "id").unwrap_or_default();
+    let damage = nbt_value_lookup_short(value, "Damage").unwrap_or(0);
+    let mut tag = match item.remove("tag") {
+        Some(nbt::Value::Compound(tag)) => tag,
+        _ => nbt::Map::new(),
+    };
+    item.remove("Damage");
+
+    let name = id.trim_start_matches("minecraft:");
+    let new_name = if name == "spawn_egg" {
+        spawn_egg_name(&tag)
+    } else if let Some(new_name) = flattened_name(name, damage) {
+        new_name
+    } else {
+        // Damage is durability from here on, or the map number of filled maps
+        if damage != 0 {
+            let key = if name == "filled_map" {
+                "map"
+            } else {
+                "Damage"
+            };
+            tag.insert(key.into(), nbt::Value::Int(damage.into()));
+        }
+        FLATTENING_RENAMES
+            .iter()
+            .find(|(old, _)| *old == name)
+            .map_or(name, |(_, new)| new)
+            .to_string()
+    };
+    let new_id = renamed_for(&format!("minecraft:{}", new_name), target);
+    item.insert("id".into(), nbt::Value::String(new_id));
+
+    upgrade_tag(&mut tag, target);
+    if !tag.is_empty() {
+        item.insert("tag".into(), nbt::Value::Compound(tag));
+    }
+    nbt::Value::Compound(item)
+}
+
+/// Upgrades each item stack in the list `value`.
+pub(crate) fn upgraded_items(value: &nbt::Value, target: McVersion) -> nbt::Value {
+    match value {
+        nbt::Value::List(items) => nbt::Value::List(
+            items
+                .iter()
+                .map(|item| upgraded_item(item, target))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// Upgrades display names, lore and the contents of shulker boxes held in `tag`.
+fn upgrade_tag(tag: &mut nbt::Map<String, nbt::Value>, target: McVersion) {
+    if let Some(nbt::Value::Compound(display)) = tag.get_mut("display") {
+        if let Some(nbt::Value::String(name)) = display.get_mut("Name") {
+            *name = json_text(name);
+        }
+        // Lore lines became JSON text with 1.14
+        if target >= McVersion::from_str("18w43a").unwrap() {
+            if let Some(nbt::Value::List(lore)) = display.get_mut("Lore") {
+                for line in lore.iter_mut() {
+                    if let nbt::Value::String(line) = line {
+                        *line = json_text(line);
+                    }
+                }
+            }
+        }
+    }
+    if let Some(nbt::Value::Compound(block_entity)) = tag.get_mut("BlockEntityTag") {
+        if let Some(items) = block_entity.get_mut("Items") {
+            *items = upgraded_items(items, target);
+        }
+    }
+}
+
+/// The 1.13 name of items whose pre-flattening variant was given by the damage value.
+fn flattened_name(name: &str, damage: i16) -> Option<String> {
+    let pick =
+        |names: &[&str]| -> String { names.get(damage as usize).unwrap_or(&names[0]).to_string() };
+    let colour = COLOURS.get(damage as usize).copied().unwrap_or(COLOURS[0]);
+
+    Some(match name {
+        "anvil" => pick(&["anvil", "chipped_anvil", "damaged_anvil"]),
+        "banner" => format!("{}_banner", COLOURS[15 - (damage as usize & 15)]),
+        "bed" => format!("{}_bed", colour),
+        "brown_mushroom_block" | "red_mushroom_block" => match damage {
+            10 | 15 => "mushroom_stem".to_string(),
+            _ => name.to_string(),
+        },
+        "carpet" => format!("{}_carpet", colour),
+        "coal" => pick(&["coal", "charcoal"]),
+        "cobblestone_wall" => pick(&["cobblestone_wall", "mossy_cobblestone_wall"]),
+        "concrete" => format!("{}_concrete", colour),
+        "concrete_powder" => format!("{}_concrete_powder", colour),
+        "cooked_fish" => pick(&["cooked_cod", "cooked_salmon"]),
+        "dirt" => pick(&["dirt", "coarse_dirt", "podzol"]),
+        "double_plant" => pick(&[
+            "sunflower",
+            "lilac",
+            "tall_grass",
+            "large_fern",
+            "rose_bush",
+            "peony",
+        ]),
+        "dye" => pick(&[
+            "ink_sac",
+            "rose_red",
+            "cactus_green",
+            "cocoa_beans",
+            "lapis_lazuli",
+            "purple_dye",
+            "cyan_dye",
+            "light_gray_dye",
+            "gray_dye",
+            "pink_dye",
+            "lime_dye",
+            "dandelion_yellow",
+            "light_blue_dye",
+            "magenta_dye",
+            "orange_dye",
+            "bone_meal",
+        ]),
+        "fish" => pick(&["cod", "salmon", "tropical_fish", "pufferfish"]),
+        "golden_apple" => pick(&["golden_apple", "enchanted_golden_apple"]),
+        "leaves" => format!("{}_leaves", pick(&WOODS[..4])),
+        "leaves2" => format!("{}_leaves", pick(&WOODS[4..])),
+        "log" => format!("{}_log", pick(&WOODS[..4])),
+        "log2" => format!("{}_log", pick(&WOODS[4..])),
+        "monster_egg" => pick(&[
+            "infested_stone",
+            "infested_cobblestone",
+            "infested_stone_bricks",
+            "infested_mossy_stone_bricks",
+            "infested_cracked_stone_bricks",
+            "infested_chiseled_stone_bricks",
+        ]),
+        "planks" => format!("{}_planks", pick(&WOODS)),
+        "prismarine" => pick(&["prismarine", "prismarine_bricks", "dark_prismarine"]),
+        "quartz_block" => pick(&["quartz_block", "chiseled_quartz_block", "quartz_pillar"]),
+        "red_flower" => pick(&[
+            "poppy",
+            "blue_orchid",
+            "allium",
+            "azure_bluet",
+            "red_tulip",
+            "orange_tulip",
+            "white_tulip",
+            "pink_tulip",
+            "oxeye_daisy",
+        ]),
+        "red_sandstone" => pick(&[
+            "red_sandstone",
+            "chiseled_red_sandstone",
+            "cut_red_sandstone",
+        ]),
+        "sand" => pick(&["sand", "red_sand"]),
+        "sandstone" => pick(&["sandstone", "chiseled_sandstone", "cut_sandstone"]),
+        "sapling" => format!("{}_sapling", pick(&WOODS)),
+        "skull" => pick(&[
+            "skeleton_skull",
+            "wither_skeleton_skull",
+            "zombie_head",
+            "player_head",
+            "creeper_head",
+            "dragon_head",
+        ]),
+        "sponge" => pick(&["sponge", "wet_sponge"]),
+        "stained_glass" => format!("{}_stained_glass", colour),
+        "stained_glass_pane" => format!("{}_stained_glass_pane", colour),
+        "stained_hardened_clay" => format!("{}_terracotta", colour),
+        "stone" => pick(&[
+            "stone",
+            "granite",
+            "polished_granite",
+            "diorite",
+            "polished_diorite",
+            "andesite",
+            "polished_andesite",
+        ]),
+        "stone_slab" => pick(&[
+            "stone_slab",
+            "sandstone_slab",
+            "petrified_oak_slab",
+            "cobblestone_slab",
+            "brick_slab",
+            "stone_brick_slab",
+            "nether_brick_slab",
+            "quartz_slab",
+        ]),
+        "stone_slab2" => "red_sandstone_slab".to_string(),
+        "stonebrick" => pick(&[
+            "stone_bricks",
+            "mossy_stone_bricks",
+            "cracked_stone_bricks",
+            "chiseled_stone_bricks",
+        ]),
+        "tallgrass" => pick(&["dead_bush", "grass", "fern"]),
+        "wooden_slab" => format!("{}_slab", pick(&WOODS)),
+        "wool" => format!("{}_wool", colour),
+        _ => return None,
+    })
+}
+
+/// The 1.13 name of a spawn egg, from the entity it spawns.
+fn spawn_egg_name(tag: &nbt::Map<String, nbt::Value>) -> String {
+    let entity = match tag.get("EntityTag") {
+        Some(entity_tag) => nbt_value_lookup_string(entity_tag, "id"),
+        None => None,
+    };
+    match entity {
+        Some(entity) => format!("{}_spawn_egg", entity.trim_start_matches("minecraft:")),
+        None => "pig_spawn_egg".to_string(),
+    }
+}
+
+/// Translates a 1.13 item id into the corresponding id of `version`.
+fn renamed_for(id: &str, version: McVersion) -> String {
+    let since = |name| version >= McVersion::from_str(name).unwrap();
+    let renamed = match id {
+        "minecraft:stone_slab" if since("18w43a") => "minecraft:smooth_stone_slab",
+        "minecraft:sign" if since("19w08a") => "minecraft:oak_sign",
+        "minecraft:rose_red" if since("19w08a") => "minecraft:red_dye",
+        "minecraft:dandelion_yellow" if since("19w08a") => "minecraft:yellow_dye",
+        "minecraft:cactus_green" if since("19w08a") => "minecraft:green_dye",
+        "minecraft:zombie_pigman_spawn_egg" if since("20w09a") => {
+            "minecraft:zombified_piglin_spawn_egg"
+        }
+        "minecraft:grass_path" if since("20w45a") => "minecraft:dirt_path",
+        "minecraft:grass" if since("23w40a") => "minecraft:short_grass",
+        _ => id,
+    };
+    renamed.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, damage: i16) -> nbt::Value {
+        let mut item = nbt::Map::new();
+        item.insert("id".into(), nbt::Value::String(id.into()));
+        item.insert("Damage".into(), nbt::Value::Short(damage));
+        item.insert("Count".into(), nbt::Value::Byte(3));
+        nbt::Value::Compound(item)
+    }
+
+    fn upgraded_id(id: &str, damage: i16, version: &str) -> String {
+        let upgraded = upgraded_item(&item(id, damage), McVersion::from_str(version).unwrap());
+        nbt_value_lookup_string(&upgraded, "id").unwrap()
+    }
+
+    #[test]
+    fn damage_variants_get_their_own_ids() {
+        assert_eq!(
+            upgraded_id("minecraft:wool", 14, "1.13"),
+            "minecraft:red_wool"
+        );
+        assert_eq!(
+            upgraded_id("minecraft:log2", 1, "1.13"),
+            "minecraft:dark_oak_log"
+        );
+        assert_eq!(
+            upgraded_id("minecraft:banner", 0, "1.13"),
+            "minecraft:black_banner"
+        );
+        assert_eq!(
+            upgraded_id("minecraft:dye", 1, "1.13"),
+            "minecraft:rose_red"
+        );
+        assert_eq!(upgraded_id("minecraft:dye", 1, "1.14"), "minecraft:red_dye");
+        assert_eq!(upgraded_id("minecraft:web", 0, "1.13"), "minecraft:cobweb");
+        assert_eq!(upgraded_id("minecraft:apple", 0, "1.13"), "minecraft:apple");
+    }
+
+    #[test]
+    fn durability_moves_into_the_tag() {
+        let upgraded = upgraded_item(
+            &item("minecraft:iron_sword", 17),
+            McVersion::from_str("1.13").unwrap(),
+        );
+        assert_eq!(nbt_value_lookup_int(&upgraded, "tag/Damage"), Some(17));
+        assert_eq!(nbt_value_lookup_short(&upgraded, "Damage"), None);
+        assert_eq!(nbt_value_lookup_byte(&upgraded, "Count"), Some(3));
+    }
+}
@@ -87,7 +87,7 @@ pub(crate) fn tightly_packed<T>(unpacked_array: &[T], bits_per_value: usize) ->
     for long_index in 0 .. packed_len {
         let mut long = 0u64;
         let packed_long_bit_index = long_index * 64;
-        let low_unpacked_index = (packed_long_bit_index + bits_per_value - 1) / bits_per_value;
+        let low_unpacked_index = packed_long_bit_index / bits_per_value;
         let high_unpacked_index = std::cmp::min(unpacked_len, low_unpacked_index + values_overlapping_u64_max);
 
         #[allow(clippy::needless_range_loop)]
@@ -209,7 +209,7 @@ mod tests {
 
     #[test]
     fn test_tight_unpacking() {
-        assert_eq!(UNPACKED_U8, tightly_unpacked(&TIGHTLY_PACKED_5, 5).as_slice()[..26]);
+        assert_eq!(UNPACKED_U8, tightly_unpacked::<u8>(&TIGHTLY_PACKED_5, 5).as_slice()[..26]);
     }
 
     #[test]
@@ -219,6 +219,6 @@ mod tests {
 
     #[test]
     fn test_padded_unpacking() {
-        assert_eq!(UNPACKED_U8, paddedly_unpacked(&PADDEDLY_PACKED_5, 5).as_slice()[..26]);
+        assert_eq!(UNPACKED_U8, paddedly_unpacked::<u8>(&PADDEDLY_PACKED_5, 5).as_slice()[..26]);
     }
 }
@@ -0,0 +1,434 @@
+//! Consistency checks for blocks that make up structures of several blocks.
+//!
+//! Doors, beds, tall plants, pistons and double chests each consist of two
+//! blocks, that the game expects to find together. A structure missing one of
+//! its parts, e.g. after cutting an excerpt through it, may break or behave
+//! strangely in game.
+//!
+//! [`validate()`] lists such problems, and [`auto_fix()`] repairs them, by
+//! completing the structure if there is room for the missing part within the
+//! excerpt, and otherwise removing (or, for pistons and chests, adjusting) the
+//! remaining part.
+
+use std::fmt;
+
+use crate::block::*;
+use crate::coordinates::BlockCoord;
+use crate::positioning::Direction;
+use crate::world_excerpt::WorldExcerpt;
+
+/// A structure found incomplete by [`validate()`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Problem {
+    /// Coordinates of the block with the problem, relative to the excerpt.
+    pub at: BlockCoord,
+    pub kind: ProblemKind,
+}
+
+/// The kinds of problems found by [`validate()`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ProblemKind {
+    /// A door half without a matching other half above or below it.
+    LoneDoorHalf,
+    /// A bed end without a matching other end.
+    LoneBedEnd,
+    /// The top or bottom of a two block tall plant, without its other half.
+    LonePlantHalf,
+    /// A piston head without an extended piston behind it.
+    LonePistonHead,
+    /// An extended piston without a piston head in front of it.
+    HeadlessPiston,
+    /// One half of a double chest, without a matching other half facing the same way.
+    MismatchedChest,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self.kind {
+            ProblemKind::LoneDoorHalf => "Door half without its other half",
+            ProblemKind::LoneBedEnd => "Bed end without its other end",
+            ProblemKind::LonePlantHalf => "Tall plant half without its other half",
+            ProblemKind::LonePistonHead => "Piston head without a piston",
+            ProblemKind::HeadlessPiston => "Extended piston without a piston head",
+            ProblemKind::MismatchedChest => "Double chest half without a matching other half",
+        };
+        write!(
+            f,
+            "{} at ({}, {}, {})",
+            description, self.at.0, self.at.1, self.at.2
+        )
+    }
+}
+
+/// Lists the incomplete structures of `excerpt`, ordered by x, then z, then y coordinate.
+///
+/// # Examples
+/// ```
+/// use mcprogedit::block::{Block, Door, DoorHalf, Hinge};
+/// use mcprogedit::material::DoorMaterial;
+/// use mcprogedit::positioning::Surface4;
+/// use mcprogedit::validation::ProblemKind;
+/// use mcprogedit::world_excerpt::WorldExcerpt;
+///
+/// let mut excerpt = WorldExcerpt::new(1, 2, 1);
+/// excerpt.set_block_at((0, 0, 0).into(), Block::Door(Door {
+///     material: DoorMaterial::Oak,
+///     facing: Surface4::North,
+///     half: DoorHalf::Lower,
+///     hinged_at: Hinge::Left,
+///     open: false,
+/// }));
+///
+/// let problems = excerpt.validate();
+/// assert_eq!(problems.len(), 1);
+/// assert_eq!(problems[0].kind, ProblemKind::LoneDoorHalf);
+///
+/// // There is room for the upper half, so the door is completed
+/// excerpt.auto_fix();
+/// assert!(excerpt.validate().is_empty());
+/// ```
+pub fn validate(excerpt: &WorldExcerpt) -> Vec<Problem> {
+    excerpt
+        .iter()
+        .filter_map(|(at, block)| problem_with(excerpt, at, block).map(|kind| Problem { at, kind }))
+        .collect()
+}
+
+/// Repairs the incomplete structures of `excerpt`, and returns the problems repaired.
+///
+/// Missing parts are added where there is room for them within the excerpt,
+/// that is where the excerpt holds air or no block. Otherwise the part that
+/// is there is removed, except for extended pistons, which are retracted, and
+/// double chest halves, which are made single chests.
+pub fn auto_fix(excerpt: &mut WorldExcerpt) -> Vec<Problem> {
+    let problems = validate(excerpt);
+    for problem in &problems {
+        // Earlier repairs may have completed the structure already
+        let block = match excerpt.block_at(problem.at) {
+            Some(block) => block.clone(),
+            None => continue,
+        };
+        if problem_with(excerpt, problem.at, &block).is_some() {
+            fix(excerpt, problem.at, block);
+        }
+    }
+    problems
+}
+
+fn problem_with(excerpt: &WorldExcerpt, at: BlockCoord, block: &Block) -> Option<ProblemKind> {
+    let kind = match block {
+        Block::Door(_) => ProblemKind::LoneDoorHalf,
+        Block::Bed(_) => ProblemKind::LoneBedEnd,
+        Block::Flower(_) | Block::Grass(_) | Block::Seagrass { .. } => ProblemKind::LonePlantHalf,
+        Block::PistonHead { .. } | Block::StickyPistonHead { .. } => ProblemKind::LonePistonHead,
+        Block::Piston { .. } | Block::StickyPiston { .. } => ProblemKind::HeadlessPiston,
+        Block::Chest(_) | Block::TrappedChest(_) => ProblemKind::MismatchedChest,
+        _ => return None,
+    };
+    let (other_at, other) = other_part(at, block)?;
+    if is_matching_part(excerpt.block_at(other_at), &other) {
+        None
+    } else {
+        Some(kind)
+    }
+}
+
+/// Returns true if `found` is `expected`, ignoring properties that may
+/// differ between the parts of a structure.
+fn is_matching_part(found: Option<&Block>, expected: &Block) -> bool {
+    match (found, expected) {
+        // Only the first of a door's halves decides the open state in game
+        (Some(Block::Door(found)), Block::Door(expected)) => {
+            found.material == expected.material
+                && found.facing == expected.facing
+                && found.half == expected.half
+        }
+        (Some(Block::Chest(found)), Block::Chest(expected))
+        | (Some(Block::TrappedChest(found)), Block::TrappedChest(expected)) => {
+            found.facing == expected.facing && found.variant == expected.variant
+        }
+        (found, expected) => found == Some(expected),
+    }
+}
+
+/// The location and block of the other part of the structure that `block`
+/// at `at` is part of, or `None` if `block` stands on its own.
+fn other_part(at: BlockCoord, block: &Block) -> Option<(BlockCoord, Block)> {
+    match block {
+        Block::Door(door) => {
+            let (direction, half) = match door.half {
+                DoorHalf::Lower => (Direction::Up, DoorHalf::Upper),
+                DoorHalf::Upper => (Direction::Down, DoorHalf::Lower),
+            };
+            Some((
+                at + direction.offset(),
+                Block::Door(Door {
+                    half,
+                    ..door.clone()
+                }),
+            ))
+        }
+        Block::Bed(bed) => {
+            let (direction, end) = match bed.end {
+                BedEnd::Foot => (Direction::from(bed.facing), BedEnd::Head),
+                BedEnd::Head => (Direction::from(bed.facing).opposite(), BedEnd::Foot),
+            };
+            Some((
+                at + direction.offset(),
+                Block::Bed(Bed { end, ..bed.clone() }),
+            ))
+        }
+        Block::Flower(_) | Block::Grass(_) | Block::Seagrass { .. } => {
+            let (upper, other) = other_plant_half(block)?;
+            let direction = if upper {
+                Direction::Down
+            } else {
+                Direction::Up
+            };
+            Some((at + direction.offset(), other))
+        }
+        Block::PistonHead { facing } => Some((
+            at + Direction::from(*facing).opposite().offset(),
+            Block::Piston {
+                facing: *facing,
+                extended: true,
+            },
+        )),
+        Block::StickyPistonHead { facing } => Some((
+            at + Direction::from(*facing).opposite().offset(),
+            Block::StickyPiston {
+                facing: *facing,
+                extended: true,
+            },
+        )),
+        Block::Piston {
+            facing,
+            extended: true,
+        } => Some((
+            at + Direction::from(*facing).offset(),
+            Block::PistonHead { facing: *facing },
+        )),
+        Block::StickyPiston {
+            facing,
+            extended: true,
+        } => Some((
+            at + Direction::from(*facing).offset(),
+            Block::StickyPistonHead { facing: *facing },
+        )),
+        Block::Chest(chest) | Block::TrappedChest(chest) => {
+            let side = chest.other_half_side(chest.variant.as_ref()?)?;
+            let variant = match chest.variant {
+                Some(ChestVariant::Left) => ChestVariant::Right,
+                _ => ChestVariant::Left,
+            };
+            let other_at = at + Direction::from(side).offset();
+            let other = Box::new(Chest {
+                facing: chest.facing,
+                variant: Some(variant),
+                waterlogged: false,
+                custom_name: None,
+                lock: None,
+                items: Default::default(),
+            });
+            let other = match block {
+                Block::TrappedChest(_) => Block::TrappedChest(other),
+                _ => Block::Chest(other),
+            };
+            Some((other_at, other))
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether `block` is the upper half of a two block tall plant, and
+/// its other half, or `None` if it is not part of a tall plant.
+fn other_plant_half(block: &Block) -> Option<(bool, Block)> {
+    let (upper, other) = match block {
+        Block::Flower(Flower::LilacBottom) => (false, Block::Flower(Flower::LilacTop)),
+        Block::Flower(Flower::LilacTop) => (true, Block::Flower(Flower::LilacBottom)),
+        Block::Flower(Flower::PeonyBottom) => (false, Block::Flower(Flower::PeonyTop)),
+        Block::Flower(Flower::PeonyTop) => (true, Block::Flower(Flower::PeonyBottom)),
+        Block::Flower(Flower::RoseBushBottom) => (false, Block::Flower(Flower::RoseBushTop)),
+        Block::Flower(Flower::RoseBushTop) => (true, Block::Flower(Flower::RoseBushBottom)),
+        Block::Flower(Flower::SunflowerBottom) => (false, Block::Flower(Flower::SunflowerTop)),
+        Block::Flower(Flower::SunflowerTop) => (true, Block::Flower(Flower::SunflowerBottom)),
+        Block::Grass(Grass::LargeFernBottom) => (false, Block::Grass(Grass::LargeFernTop)),
+        Block::Grass(Grass::LargeFernTop) => (true, Block::Grass(Grass::LargeFernBottom)),
+        Block::Grass(Grass::TallGrassBottom) => (false, Block::Grass(Grass::TallGrassTop)),
+        Block::Grass(Grass::TallGrassTop) => (true, Block::Grass(Grass::TallGrassBottom)),
+        Block::Seagrass {
+            variant: Seagrass::TallSeagrassBottom,
+        } => (
+            false,
+            Block::Seagrass {
+                variant: Seagrass::TallSeagrassTop,
+            },
+        ),
+        Block::Seagrass {
+            variant: Seagrass::TallSeagrassTop,
+        } => (
+            true,
+            Block::Seagrass {
+                variant: Seagrass::TallSeagrassBottom,
+            },
+        ),
+        _ => return None,
+    };
+    Some((upper, other))
+}
+
+/// Repairs the structure that `block` at `at` is part of.
+fn fix(excerpt: &mut WorldExcerpt, at: BlockCoord, block: Block) {
+    let (other_at, other) = match other_part(at, &block) {
+        Some(other_part) => other_part,
+        None => return,
+    };
+
+    let replacement = match block {
+        Block::Chest(mut chest) => Block::Chest(single(&mut chest)),
+        Block::TrappedChest(mut chest) => Block::TrappedChest(single(&mut chest)),
+        _ if has_room_at(excerpt, other_at) => {
+            excerpt.set_block_at(other_at, other);
+            return;
+        }
+        Block::Piston { facing, .. } => Block::Piston {
+            facing,
+            extended: false,
+        },
+        Block::StickyPiston { facing, .. } => Block::StickyPiston {
+            facing,
+            extended: false,
+        },
+        _ => Block::Air,
+    };
+    excerpt.set_block_at(at, replacement);
+}
+
+fn single(chest: &mut Box<Chest>) -> Box<Chest> {
+    chest.variant = Some(ChestVariant::Single);
+    chest.clone()
+}
+
+/// Returns true if `at` is within the excerpt, and holds air or no block.
+fn has_room_at(excerpt: &WorldExcerpt, at: BlockCoord) -> bool {
+    matches!(
+        excerpt.block_at(at),
+        Some(Block::Air) | Some(Block::CaveAir) | Some(Block::None)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::colour::Colour;
+    use crate::inventory::Inventory;
+    use crate::positioning::{Surface4, Surface6};
+
+    fn bed(end: BedEnd) -> Block {
+        Block::Bed(Bed {
+            colour: Colour::Blue,
+            facing: Surface4::East,
+            end,
+        })
+    }
+
+    fn chest(facing: Surface4, variant: ChestVariant) -> Block {
+        Block::Chest(Box::new(Chest {
+            facing,
+            variant: Some(variant),
+            waterlogged: false,
+            custom_name: None,
+            lock: None,
+            items: Inventory::new(),
+        }))
+    }
+
+    #[test]
+    fn complete_structures_are_valid() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 2);
+        excerpt.set_block_at((0, 0, 0).into(), bed(BedEnd::Foot));
+        excerpt.set_block_at((1, 0, 0).into(), bed(BedEnd::Head));
+        excerpt.set_block_at((2, 0, 0).into(), Block::Flower(Flower::PeonyBottom));
+        excerpt.set_block_at((2, 1, 0).into(), Block::Flower(Flower::PeonyTop));
+        excerpt.set_block_at(
+            (0, 1, 0).into(),
+            Block::Piston {
+                facing: Surface6::East,
+                extended: true,
+            },
+        );
+        excerpt.set_block_at(
+            (1, 1, 0).into(),
+            Block::PistonHead {
+                facing: Surface6::East,
+            },
+        );
+        // A chest facing north has its left half to the west
+        excerpt.set_block_at((0, 0, 1).into(), chest(Surface4::North, ChestVariant::Left));
+        excerpt.set_block_at(
+            (1, 0, 1).into(),
+            chest(Surface4::North, ChestVariant::Right),
+        );
+        assert_eq!(excerpt.validate(), Vec::new());
+    }
+
+    #[test]
+    fn broken_structures_are_found_and_fixed() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 2);
+        excerpt.set_block_at((0, 0, 0).into(), bed(BedEnd::Head));
+        excerpt.set_block_at((1, 1, 0).into(), Block::Flower(Flower::LilacTop));
+        excerpt.set_block_at((1, 0, 0).into(), Block::Stone);
+        excerpt.set_block_at(
+            (2, 0, 0).into(),
+            Block::StickyPiston {
+                facing: Surface6::Up,
+                extended: true,
+            },
+        );
+        excerpt.set_block_at((2, 1, 0).into(), Block::Stone);
+        excerpt.set_block_at(
+            (0, 0, 1).into(),
+            chest(Surface4::North, ChestVariant::Right),
+        );
+        excerpt.set_block_at((1, 0, 1).into(), chest(Surface4::South, ChestVariant::Left));
+
+        let problems = excerpt.validate();
+        let kinds: Vec<(BlockCoord, ProblemKind)> = problems
+            .iter()
+            .map(|problem| (problem.at, problem.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ((0, 0, 0).into(), ProblemKind::LoneBedEnd),
+                ((0, 0, 1).into(), ProblemKind::MismatchedChest),
+                ((1, 1, 0).into(), ProblemKind::LonePlantHalf),
+                ((1, 0, 1).into(), ProblemKind::MismatchedChest),
+                ((2, 0, 0).into(), ProblemKind::HeadlessPiston),
+            ]
+        );
+        assert_eq!(
+            problems[0].to_string(),
+            "Bed end without its other end at (0, 0, 0)"
+        );
+
+        assert_eq!(excerpt.auto_fix(), problems);
+        assert_eq!(excerpt.validate(), Vec::new());
+        // The bed head's foot is outside of the excerpt, so the head is removed
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Air));
+        // The lilac top has stone below, so it is removed
+        assert_eq!(excerpt.block_at((1, 1, 0).into()), Some(&Block::Air));
+        // The piston has stone in front of it, so it is retracted
+        assert_eq!(
+            excerpt.block_at((2, 0, 0).into()),
+            Some(&Block::StickyPiston {
+                facing: Surface6::Up,
+                extended: false,
+            })
+        );
+        assert_eq!(
+            excerpt.block_at((0, 0, 1).into()),
+            Some(&chest(Surface4::North, ChestVariant::Single))
+        );
+    }
+}
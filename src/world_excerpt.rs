@@ -1,22 +1,74 @@
 //! A piece of a Minecraft world.
 
-use crate::block::Block;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::bill_of_materials::BillOfMaterials;
+use crate::block::{Block, BlockPredicate};
 use crate::block_cuboid::BlockCuboid;
+use crate::bounds::BoundingBox;
 use crate::chunk::{Chunk, RawChunkData};
+use crate::colour::Colour;
+use crate::connections::ConnectionStates;
 use crate::coordinates::*;
-use crate::height_map::HeightMap;
+use crate::entity::{self, Entity};
+use crate::excerpt_diff::ExcerptDiff;
+use crate::export_policy::{ExportPolicy, SubstitutionReport};
+use crate::height_map::{HeightMap, HeightMapKind};
+use crate::journal::{Checkpoint, Contents, Journal};
 use crate::light_cuboid::LightCuboid;
+use crate::lighting::{self, LightRules};
+use crate::material::Material;
+use crate::mc_version::McVersion;
 use crate::nbt_lookup::*;
+use crate::positioning::{Axis2, Rotation};
 use crate::region::Region;
+use crate::save_format::{ChunkFormat, FormatError};
+use crate::save_transaction::{Backup, SaveTransaction};
+use crate::scheduled_tick::ScheduledTick;
+use crate::selection::Selection;
+use crate::statistics::Statistics;
+use crate::validation::{self, Problem};
 
 extern crate nbt;
 
+/// Options for [`WorldExcerpt::to_save_with_options`].
+#[derive(Clone, Debug, Default)]
+pub struct SaveOptions {
+    /// Keep a backup of the region files that are replaced.
+    pub backup: bool,
+    /// Write block states derived from neighbouring blocks, as computed by
+    /// [`WorldExcerpt::resolve_connections`], to post-flattening chunks.
+    pub resolve_connections: bool,
+    /// Downgrade the saved blocks according to this policy. The excerpt
+    /// itself is left unchanged.
+    pub export_policy: Option<ExportPolicy>,
+    /// Give the saved entities new UUIDs, as for
+    /// [`Entity::renew_uuids`](crate::entity::Entity::renew_uuids), e.g. when
+    /// saving copies of entities that are still found elsewhere in the world.
+    /// The excerpt itself is left unchanged.
+    pub new_entity_uuids: bool,
+}
+
+/// Outcome of [`WorldExcerpt::to_save_with_options`].
+#[derive(Clone, Debug)]
+pub struct SaveReport {
+    /// Backup of the replaced region files, if [`SaveOptions::backup`] was set.
+    pub backup: Option<Backup>,
+    /// Blocks substituted on export, if [`SaveOptions::export_policy`] was set.
+    pub substitutions: Option<SubstitutionReport>,
+}
+
 /// Structure for holding blocks and entities, representing a piece of a Minecraft world.
 #[derive(Clone, Debug)]
 pub struct WorldExcerpt {
     blocks: BlockCuboid,
     pub(crate) block_light: LightCuboid,
     pub(crate) sky_light: LightCuboid,
+    /// Entities, positioned relative to the excerpt.
+    entities: Vec<Entity>,
+    /// Scheduled block and fluid ticks, positioned relative to the excerpt.
+    scheduled_ticks: Vec<ScheduledTick>,
+    journal: Option<Journal>,
 }
 
 impl WorldExcerpt {
@@ -26,6 +78,9 @@ impl WorldExcerpt {
             blocks: BlockCuboid::new((x, y, z)),
             block_light: LightCuboid::new((x, y, z)),
             sky_light: LightCuboid::new((x, y, z)),
+            entities: Vec::new(),
+            scheduled_ticks: Vec::new(),
+            journal: None,
         }
     }
 
@@ -37,6 +92,11 @@ impl WorldExcerpt {
     ///
     /// Imports from the world saved at `world_directory` the blocks and entities
     /// from within the bounding box defined by `p1` and `p2`.
+    ///
+    /// Each chunk is read in the chunk format of its own data version.
+    ///
+    /// # Panics
+    /// Panics if the world contains chunks of unsupported Minecraft versions.
     pub fn from_save(p1: BlockCoord, p2: BlockCoord, world_directory: &std::path::Path) -> Self {
         // Check that the world directory exists.
         if !world_directory.is_dir() {
@@ -113,6 +173,12 @@ impl WorldExcerpt {
             ),
         };
 
+        let global_min = BlockCoord(
+            global_block_bounds.x.0,
+            global_block_bounds.y.0,
+            global_block_bounds.z.0,
+        );
+
         // Iterate through the (existing) region files within the bound
         for region_x in region_bounds.x.0..=region_bounds.x.1 {
             for region_z in region_bounds.z.0..=region_bounds.z.1 {
@@ -127,9 +193,19 @@ impl WorldExcerpt {
                         "Region file {} does not exist, continuing.",
                         &region_file_name
                     );
+                    continue;
                 }
 
-                let region = Region::load_from_file(&region_file);
+                // Only the chunks overlapping with the excerpt are read from the file.
+                let region = Region::open(&region_file);
+
+                // From 20w45a, entities are stored in separate region files.
+                let entity_region_file = world_directory.join("entities/").join(&region_file_name);
+                let entity_region = if entity_region_file.is_file() {
+                    Some(Region::open(&entity_region_file))
+                } else {
+                    None
+                };
 
                 // Figure out what chunks overlaps with the bounding box,
                 // expressed in chunk coordinates relative to the region.
@@ -160,7 +236,7 @@ impl WorldExcerpt {
                         let chunk_offset: BlockCoord = chunk.chunk_coordinates().into();
                         let chunk_offset_in_blocks = (
                             chunk_offset.0 - global_block_bounds.x.0,
-                            chunk_offset.1 - global_block_bounds.y.0,
+                            chunk.min_y() - global_block_bounds.y.0,
                             chunk_offset.2 - global_block_bounds.z.0,
                         );
                         world_excerpt
@@ -173,7 +249,32 @@ impl WorldExcerpt {
                             .sky_light
                             .paste(chunk_offset_in_blocks.into(), &chunk.sky_light);
 
-                        // TODO Move or copy the entities from the chunk
+                        // Copy the entities within the excerpt bounds
+                        let mut entities = chunk.entities.clone();
+                        if let Some(entity_region) = &entity_region {
+                            let entity_data = entity_region.chunk_data(&(chunk_x, chunk_z).into());
+                            if let Some(list) =
+                                nbt_blob_lookup_list(&entity_data.to_nbt(), "Entities")
+                            {
+                                entities.extend(entity::entities_from_nbt_list(&list));
+                            }
+                        }
+                        for mut entity in entities {
+                            entity.translate(BlockCoord(0, 0, 0) - global_min);
+                            if world_excerpt.contains(entity.block_position()) {
+                                world_excerpt.entities.push(entity);
+                            }
+                        }
+
+                        // Copy the scheduled ticks within the excerpt bounds
+                        for tick in &chunk.scheduled_ticks {
+                            let at = tick.at - global_min;
+                            if world_excerpt.contains(at) {
+                                world_excerpt
+                                    .scheduled_ticks
+                                    .push(ScheduledTick { at, ..tick.clone() });
+                            }
+                        }
                     }
                 }
             }
@@ -188,7 +289,118 @@ impl WorldExcerpt {
     /// Pastes the contents of the world excerpt into a world saved at `world_directory`,
     /// positioned so that the excerpt corner with the lowest integer coordinates are
     /// put at world block coordinates `p`.
-    pub fn to_save(&self, p: BlockCoord, world_directory: &std::path::Path) {
+    ///
+    /// The affected region files are replaced only after all of them have been
+    /// written successfully, so a failed save leaves the world unchanged.
+    ///
+    /// New chunks are written in the chunk format of the world's Minecraft
+    /// version, as found in level.dat, while existing chunks keep the format
+    /// of their own data version.
+    ///
+    /// Pre-flattening chunks store light levels, which are recomputed for the
+    /// written chunks and for the saved chunks around them. These chunks are
+    /// all held in memory until the save is done.
+    ///
+    /// # Errors
+    /// Returns [`FormatError::UnsupportedVersion`], without changing the world,
+    /// if the world or any of the affected chunks is of a Minecraft version
+    /// that cannot be written.
+    pub fn to_save(
+        &self,
+        p: BlockCoord,
+        world_directory: &std::path::Path,
+    ) -> Result<(), FormatError> {
+        self.to_save_with_options(p, world_directory, SaveOptions::default())?;
+        Ok(())
+    }
+
+    /// Writes the contents of the WorldExcerpt to a Minecraft world save,
+    /// keeping a backup of the region files it replaces.
+    ///
+    /// Works like [`to_save`](Self::to_save). The returned [`Backup`] can be
+    /// used for rolling the world back to how it was before the save.
+    pub fn to_save_with_backup(
+        &self,
+        p: BlockCoord,
+        world_directory: &std::path::Path,
+    ) -> Result<Backup, FormatError> {
+        let options = SaveOptions {
+            backup: true,
+            ..Default::default()
+        };
+        Ok(self
+            .to_save_with_options(p, world_directory, options)?
+            .backup
+            .unwrap_or_else(|| panic!("No backup made for save to {:?}", world_directory)))
+    }
+
+    /// Writes the contents of the WorldExcerpt to a Minecraft world save,
+    /// with the given options.
+    ///
+    /// Works like [`to_save`](Self::to_save). Returns a [`Backup`] of the
+    /// replaced region files if `options.backup` is set, and the blocks
+    /// substituted on export if `options.export_policy` is set.
+    ///
+    /// # Errors
+    /// Also returns [`FormatError::UnavailableBlock`], without changing the
+    /// world, if the export policy is set to fail on unavailable blocks and
+    /// the excerpt has any.
+    pub fn to_save_with_options(
+        &self,
+        p: BlockCoord,
+        world_directory: &std::path::Path,
+        options: SaveOptions,
+    ) -> Result<SaveReport, FormatError> {
+        if let Some(policy) = &options.export_policy {
+            let mut downgraded = self.clone();
+            downgraded.disable_journal();
+            let substitutions = policy.try_apply(&mut downgraded)?;
+            let options = SaveOptions {
+                export_policy: None,
+                ..options
+            };
+            let report = downgraded.to_save_with_options(p, world_directory, options)?;
+            return Ok(SaveReport {
+                substitutions: Some(substitutions),
+                ..report
+            });
+        }
+
+        if options.new_entity_uuids {
+            let mut renewed = self.clone();
+            renewed.disable_journal();
+            renewed.entities.iter_mut().for_each(Entity::renew_uuids);
+            let options = SaveOptions {
+                new_entity_uuids: false,
+                ..options
+            };
+            return renewed.to_save_with_options(p, world_directory, options);
+        }
+
+        let mut transaction = if options.backup {
+            SaveTransaction::new_with_backup(world_directory)
+        } else {
+            SaveTransaction::new(world_directory)
+        };
+        let connection_states = if options.resolve_connections {
+            self.resolve_connections()
+        } else {
+            ConnectionStates::default()
+        };
+        self.write_to_save(p, world_directory, &connection_states, &mut transaction)?;
+        Ok(SaveReport {
+            backup: transaction.commit(),
+            substitutions: None,
+        })
+    }
+
+    fn write_to_save(
+        &self,
+        p: BlockCoord,
+        world_directory: &std::path::Path,
+        connection_states: &ConnectionStates,
+        transaction: &mut SaveTransaction,
+    ) -> Result<(), FormatError> {
         // Check that the world directory exists.
         if !world_directory.is_dir() {
             panic!("Not a world save directory: {:?}", world_directory);
@@ -205,8 +417,10 @@ impl WorldExcerpt {
         let level_dat_blob = nbt::Blob::from_gzip_reader(&mut level_dat)
             .expect("Unable to parse level.dat contents");
 
-        let _data_version = nbt_blob_lookup_int(&level_dat_blob, "Data/DataVersion")
+        let data_version = nbt_blob_lookup_int(&level_dat_blob, "Data/DataVersion")
+            .map(McVersion::from_id)
             .unwrap_or_else(|| panic!("level.dat Data/DataVersion not found"));
+        ChunkFormat::for_writing(data_version)?;
 
         let (dx, dy, dz) = self.dim();
         let (dx, dy, dz) = (dx as i64, dy as i64, dz as i64);
@@ -273,6 +487,11 @@ impl WorldExcerpt {
         //      - put chunk back
         // - write region
 
+        // Block regions are kept until all chunks are written, as relighting
+        // pre-flattening chunks may change chunks in neighbouring regions.
+        let mut regions: BTreeMap<RegionCoord, Region> = BTreeMap::new();
+        let mut relit_chunks: BTreeMap<ChunkCoord, Chunk> = BTreeMap::new();
+
         // Iterate through region files within the bound, creating new ones as needed.
         for region_x in region_bounds.x.0..=region_bounds.x.1 {
             for region_z in region_bounds.z.0..=region_bounds.z.1 {
@@ -282,11 +501,20 @@ impl WorldExcerpt {
                 let region_file_name = format!("r.{}.{}.mca", region_x, region_z);
                 let region_file = world_directory.join("region/").join(&region_file_name);
 
-                let mut region = if region_file.is_file() {
-                    Region::load_from_file(&region_file)
-                } else {
-                    Region::new()
-                };
+                let region = regions
+                    .entry((region_x, region_z).into())
+                    .or_insert_with(|| {
+                        if region_file.is_file() {
+                            Region::load_from_file(&region_file)
+                        } else {
+                            Region::new()
+                        }
+                    });
+
+                // From 20w45a, entities are stored in separate region files,
+                // which are only loaded if there are such chunks to write.
+                let entity_region_file = world_directory.join("entities/").join(&region_file_name);
+                let mut entity_region: Option<Region> = None;
 
                 // TODO copypaste from from_save(), consider refactoring.
                 // Figure out what chunks overlaps with the bounding box,
@@ -311,34 +539,119 @@ impl WorldExcerpt {
                     for chunk_z in in_region_chunk_bounds.z.0..=in_region_chunk_bounds.z.1 {
                         // Get the chunk, or create a new one if empty
                         let chunk_data = region.chunk_data(&(chunk_x, chunk_z).into());
+                        let chunk_coordinates: ChunkCoord = (chunk_x, chunk_z).into();
+                        let chunk_coordinates = chunk_coordinates + chunk_offset;
                         let mut chunk = match chunk_data {
-                            RawChunkData::Empty => Chunk::new((chunk_x, chunk_z).into()),
+                            RawChunkData::Empty => Chunk::new(chunk_coordinates, data_version),
                             _ => Chunk::from_raw_chunk_data(&chunk_data),
                         };
+                        ChunkFormat::for_writing(chunk.data_version())?;
 
                         // Paste blocks into chunk
-                        let chunk_coordinates: ChunkCoord = (chunk_x, chunk_z).into();
-                        let chunk_coordinates = chunk_coordinates + chunk_offset;
                         let chunk_block_coordinates: BlockColumnCoord = chunk_coordinates.into();
                         let offset = (
                             global_block_bounds.x.0 - chunk_block_coordinates.0,
-                            global_block_bounds.y.0,
+                            global_block_bounds.y.0 - chunk.min_y(),
                             global_block_bounds.z.0 - chunk_block_coordinates.1,
                         );
                         chunk.blocks.paste(offset, &self.blocks);
+                        chunk.paste_connection_states(offset, connection_states);
 
-                        // TODO Move or copy entities into the chunk
+                        // Replace the entities within the excerpt bounds
+                        let within_global_bounds = |at: BlockCoord| {
+                            (global_block_bounds.x.0..=global_block_bounds.x.1).contains(&at.0)
+                                && (global_block_bounds.y.0..=global_block_bounds.y.1)
+                                    .contains(&at.1)
+                                && (global_block_bounds.z.0..=global_block_bounds.z.1)
+                                    .contains(&at.2)
+                        };
+                        let within_bounds =
+                            |entity: &Entity| within_global_bounds(entity.block_position());
+                        let pasted_entities: Vec<Entity> = self
+                            .entities
+                            .iter()
+                            .filter(|entity| self.contains(entity.block_position()))
+                            .map(|entity| {
+                                let mut entity = entity.clone();
+                                entity.translate(p);
+                                entity
+                            })
+                            .filter(|entity| {
+                                let column: BlockColumnCoord = entity.block_position().into();
+                                let chunk: ChunkCoord = column.into();
+                                chunk == chunk_coordinates
+                            })
+                            .collect();
+
+                        if entity::is_stored_separately(chunk.data_version()) {
+                            let entity_region = entity_region.get_or_insert_with(|| {
+                                if entity_region_file.is_file() {
+                                    Region::load_from_file(&entity_region_file)
+                                } else {
+                                    Region::new()
+                                }
+                            });
+                            let local_coordinates: ChunkCoord = (chunk_x, chunk_z).into();
+                            let entity_data = entity_region.chunk_data(&local_coordinates);
+                            let entity_data = entity_chunk_with_entities(
+                                entity_data,
+                                &chunk,
+                                within_bounds,
+                                pasted_entities,
+                            );
+                            entity_region.set_chunk_data(&local_coordinates, entity_data);
+                        } else {
+                            chunk.entities.retain(|entity| !within_bounds(entity));
+                            chunk.entities.extend(pasted_entities);
+                        }
+
+                        // Replace the scheduled ticks within the excerpt bounds
+                        chunk
+                            .scheduled_ticks
+                            .retain(|tick| !within_global_bounds(tick.at));
+                        chunk.scheduled_ticks.extend(
+                            self.scheduled_ticks
+                                .iter()
+                                .map(|tick| ScheduledTick {
+                                    at: tick.at + p,
+                                    ..tick.clone()
+                                })
+                                .filter(|tick| {
+                                    let column: BlockColumnCoord = tick.at.into();
+                                    let chunk: ChunkCoord = column.into();
+                                    self.contains(tick.at - p) && chunk == chunk_coordinates
+                                }),
+                        );
 
-                        // Put chunk back into region
-                        let chunk_data = chunk.raw_chunk_zlib();
-                        region.set_chunk_data(&(chunk_x, chunk_z).into(), chunk_data);
+                        // Put chunk back into region, or keep it for relighting
+                        if is_pre_flattening(&chunk) {
+                            relit_chunks.insert(chunk_coordinates, chunk);
+                        } else {
+                            let chunk_data = chunk.raw_chunk_zlib();
+                            region.set_chunk_data(&(chunk_x, chunk_z).into(), chunk_data);
+                        }
                     }
                 }
 
-                // TODO write back region
-                region.save_to_file(&region_file);
+                if let Some(entity_region) = entity_region {
+                    std::fs::create_dir_all(world_directory.join("entities/")).unwrap_or_else(
+                        |err| panic!("Unable to create entities directory: {}", err),
+                    );
+                    entity_region.save_in_transaction(&entity_region_file, transaction);
+                }
             }
         }
+
+        // Pre-flattening chunks are saved with their light levels, which must
+        // match the pasted blocks, also where light crosses chunk borders.
+        let region_directory = world_directory.join("region/");
+        relight_chunks(relit_chunks, &mut regions, &region_directory);
+        for (region_coordinates, region) in &regions {
+            let region_file_name =
+                format!("r.{}.{}.mca", region_coordinates.0, region_coordinates.1);
+            region.save_in_transaction(&region_directory.join(region_file_name), transaction);
+        }
+        Ok(())
     }
 
     /// Creates a new `WorldExcerpt` from a schematic file.
@@ -354,17 +667,189 @@ impl WorldExcerpt {
     ) -> Self {
         let p1_coord = BlockCoord(p1.0 as i64, p1.1 as i64, p1.2 as i64);
         let p2_coord = BlockCoord(p2.0 as i64, p2.1 as i64, p2.2 as i64);
-        Self {
+        let mut excerpt = Self {
             blocks: BlockCuboid::from_block_cuboid(p1, p2, &other.blocks),
             block_light: LightCuboid::from_light_cuboid(p1_coord, p2_coord, &other.block_light),
             sky_light: LightCuboid::from_light_cuboid(p1_coord, p2_coord, &other.sky_light),
+            entities: Vec::new(),
+            scheduled_ticks: Vec::new(),
+            journal: None,
+        };
+        let offset = BlockCoord(
+            usize::min(p1.0, p2.0) as i64,
+            usize::min(p1.1, p2.1) as i64,
+            usize::min(p1.2, p2.2) as i64,
+        );
+        excerpt.add_contents_from(other, offset, false, |_| true);
+        excerpt
+    }
+
+    /// Creates a new `WorldExcerpt` from the positions of `other` that are part of `selection`.
+    ///
+    /// The new excerpt covers the bounds of the selection, clipped to `other`, with
+    /// its lowest numbered corner at the lowest numbered corner of those bounds.
+    /// Positions within the bounds that are not selected are left empty
+    /// ([`Block::None`](crate::block::Block::None)), so that they are skipped
+    /// when the excerpt is pasted.
+    pub fn from_selection<S: Selection + ?Sized>(selection: &S, other: &WorldExcerpt) -> Self {
+        let (x_dim, y_dim, z_dim) = other.dim();
+        let bounds = if x_dim == 0 || y_dim == 0 || z_dim == 0 {
+            None
+        } else {
+            let other_bounds = BoundingBox::from_coords(
+                (0, 0, 0),
+                (x_dim as i64 - 1, y_dim as i64 - 1, z_dim as i64 - 1),
+            );
+            selection
+                .bounds()
+                .and_then(|bounds| bounds.overlap(&other_bounds))
+        };
+        let bounds = match bounds {
+            Some(bounds) => bounds,
+            None => return Self::new(0, 0, 0),
+        };
+
+        let offset = bounds.min();
+        let size = bounds.max() - offset + BlockCoord(1, 1, 1);
+        let mut excerpt = Self::new(size.0 as usize, size.1 as usize, size.2 as usize);
+        for at in selection.iter().filter(|at| bounds.contains(*at)) {
+            let to = at - offset;
+            if let Some(block) = other.block_at(at) {
+                excerpt.set_block_at(to, block.clone());
+            }
+            if let Some(light) = other.block_light.light_level_at(at) {
+                excerpt.block_light.set_light_level_at(to, light);
+            }
+            if let Some(light) = other.sky_light.light_level_at(at) {
+                excerpt.sky_light.set_light_level_at(to, light);
+            }
+        }
+        excerpt.add_contents_from(other, offset, false, |at| selection.contains(at));
+        excerpt
+    }
+
+    /// Adds copies of the entities and scheduled ticks of `other` that are at
+    /// positions selected by `filter`, and within this excerpt when the corner
+    /// of this excerpt is aligned at `offset` relative to `other`.
+    ///
+    /// The copied entities are given new UUIDs if `new_uuids` is set.
+    fn add_contents_from<F>(
+        &mut self,
+        other: &WorldExcerpt,
+        offset: BlockCoord,
+        new_uuids: bool,
+        filter: F,
+    ) where
+        F: Fn(BlockCoord) -> bool,
+    {
+        for entity in &other.entities {
+            if !filter(entity.block_position()) {
+                continue;
+            }
+            let mut entity = entity.clone();
+            entity.translate(BlockCoord(0, 0, 0) - offset);
+            if new_uuids {
+                entity.renew_uuids();
+            }
+            if self.contains(entity.block_position()) {
+                if let Some(journal) = &mut self.journal {
+                    journal.record_entity_added(&entity);
+                }
+                self.entities.push(entity);
+            }
+        }
+        for tick in &other.scheduled_ticks {
+            let at = tick.at - offset;
+            if filter(tick.at) && self.contains(at) {
+                let tick = ScheduledTick { at, ..tick.clone() };
+                if let Some(journal) = &mut self.journal {
+                    journal.record_tick_added(&tick);
+                }
+                self.scheduled_ticks.push(tick);
+            }
+        }
+    }
+
+    /// Returns true if `at` is within the bounds of the excerpt.
+    fn contains(&self, at: BlockCoord) -> bool {
+        let (x_dim, y_dim, z_dim) = self.dim();
+        (0..x_dim as i64).contains(&at.0)
+            && (0..y_dim as i64).contains(&at.1)
+            && (0..z_dim as i64).contains(&at.2)
+    }
+
+    /// The entities of the excerpt, positioned relative to the excerpt.
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    /// The entities of the excerpt, for adding, removing or changing entities.
+    ///
+    /// Entities positioned outside of the excerpt are left out when the excerpt
+    /// is written to a save. Changes made through the returned list are not
+    /// recorded in the journal.
+    pub fn entities_mut(&mut self) -> &mut Vec<Entity> {
+        &mut self.entities
+    }
+
+    /// The scheduled block and fluid ticks of the excerpt, positioned relative
+    /// to the excerpt.
+    pub fn scheduled_ticks(&self) -> &[ScheduledTick] {
+        &self.scheduled_ticks
+    }
+
+    /// The scheduled ticks of the excerpt, for adding, removing or changing ticks.
+    ///
+    /// Ticks positioned outside of the excerpt are left out when the excerpt
+    /// is written to a save. Changes made through the returned list are not
+    /// recorded in the journal.
+    pub fn scheduled_ticks_mut(&mut self) -> &mut Vec<ScheduledTick> {
+        &mut self.scheduled_ticks
+    }
+
+    /// Schedules an update of the block at `at`, to happen `delay` game ticks
+    /// after the world is loaded, e.g. for making pasted water start flowing.
+    ///
+    /// Water and lava (including waterlogged blocks) get a fluid tick, and
+    /// other blocks a block tick. Returns false, scheduling nothing, if `at` is
+    /// outside of the excerpt or holds no block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mcprogedit::block::Block;
+    /// # use mcprogedit::scheduled_tick::TickKind;
+    /// # use mcprogedit::world_excerpt::WorldExcerpt;
+    /// let mut excerpt = WorldExcerpt::new(1, 1, 1);
+    /// excerpt.set_block_at((0, 0, 0).into(), Block::WaterSource);
+    /// assert!(excerpt.schedule_tick((0, 0, 0).into(), 5));
+    /// assert_eq!(excerpt.scheduled_ticks()[0].kind, TickKind::Fluid);
+    /// ```
+    pub fn schedule_tick(&mut self, at: BlockCoord, delay: i32) -> bool {
+        let tick = self
+            .block_at(at)
+            .and_then(|block| ScheduledTick::for_block(block, at, delay));
+        match tick {
+            Some(tick) => {
+                if let Some(journal) = &mut self.journal {
+                    journal.record_tick_added(&tick);
+                }
+                self.scheduled_ticks.push(tick);
+                true
+            }
+            None => false,
         }
     }
 
     /// Set the block at location `at` to the provided block.
     pub fn set_block_at(&mut self, at: BlockCoord, block: Block) {
-        self.blocks
-            .insert((at.0 as usize, at.1 as usize, at.2 as usize), block);
+        let at = (at.0 as usize, at.1 as usize, at.2 as usize);
+        if let Some(journal) = &mut self.journal {
+            if let Some(old_block) = self.blocks.block_at(at) {
+                journal.record(at, old_block, &block);
+            }
+        }
+        self.blocks.insert(at, block);
     }
 
     /// Get a copy of the block at location `at`.
@@ -373,11 +858,173 @@ impl WorldExcerpt {
             .block_at((at.0 as usize, at.1 as usize, at.2 as usize))
     }
 
+    /// Iterates over the positions and blocks of the world excerpt.
+    ///
+    /// Blocks are visited in the order they are stored; by x, then z, then y
+    /// coordinate. This is faster than looking up each position with
+    /// [`block_at()`](Self::block_at).
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::world_excerpt::WorldExcerpt;
+    ///
+    /// let mut excerpt = WorldExcerpt::new(4, 4, 4);
+    /// excerpt.set_block_at((1, 2, 3).into(), Block::Stone);
+    ///
+    /// let stones: Vec<_> = excerpt
+    ///     .iter()
+    ///     .filter(|(_, block)| **block == Block::Stone)
+    ///     .map(|(at, _)| at)
+    ///     .collect();
+    /// assert_eq!(stones, vec![(1, 2, 3).into()]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (BlockCoord, &Block)> + '_ {
+        self.blocks
+            .iter()
+            .map(|(at, block)| (block_coord(at), block))
+    }
+
+    /// Iterates over the positions and blocks of the world excerpt, allowing
+    /// the blocks to be changed in place.
+    ///
+    /// Blocks are visited in the same order as for [`iter()`](Self::iter).
+    /// Changes made through the iterator are not recorded, so the journal,
+    /// if enabled, starts over with no recorded changes.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (BlockCoord, &mut Block)> + '_ {
+        self.restart_journal();
+        self.blocks
+            .iter_mut()
+            .map(|(at, block)| (block_coord(at), block))
+    }
+
+    /// Iterates over the positions and blocks of the world excerpt, in parallel.
+    ///
+    /// Requires the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    pub fn par_iter(
+        &self,
+    ) -> impl rayon::iter::IndexedParallelIterator<Item = (BlockCoord, &Block)> + '_ {
+        use rayon::prelude::*;
+
+        self.blocks
+            .par_iter()
+            .map(|(at, block)| (block_coord(at), block))
+    }
+
+    /// Iterates over the positions and blocks of the world excerpt that are
+    /// part of `selection`, in the order given by the selection.
+    ///
+    /// Selected positions outside of the world excerpt are skipped.
+    pub fn iter_region<'a, S: Selection + ?Sized>(
+        &'a self,
+        selection: &'a S,
+    ) -> impl Iterator<Item = (BlockCoord, &'a Block)> + 'a {
+        selection
+            .iter()
+            .filter_map(move |at| self.block_at(at).map(|block| (at, block)))
+    }
+
+    /// Iterates over the vertical columns of the world excerpt, by x, then z
+    /// coordinate.
+    ///
+    /// Each column yields its positions and blocks from the bottom up.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = (BlockCoord, &Block)>> + '_ {
+        self.blocks.columns().map(|((x, z), column)| {
+            column
+                .iter()
+                .enumerate()
+                .map(move |(y, block)| (block_coord((x, y, z)), block))
+        })
+    }
+
+    /// Iterates over the horizontal layers of the world excerpt, from the bottom up.
+    ///
+    /// Each layer yields its positions and blocks by x, then z coordinate.
+    pub fn layers(&self) -> impl Iterator<Item = impl Iterator<Item = (BlockCoord, &Block)>> + '_ {
+        let (_, y_dim, _) = self.dim();
+        (0..y_dim).map(move |y| {
+            self.blocks
+                .layer(y)
+                .map(move |((x, z), block)| (block_coord((x, y, z)), block))
+        })
+    }
+
+    /// Get the block light level at location `at`.
+    pub fn block_light_at(&self, at: BlockCoord) -> Option<u8> {
+        self.block_light.light_level_at(at)
+    }
+
+    /// Get the sky light level at location `at`.
+    pub fn sky_light_at(&self, at: BlockCoord) -> Option<u8> {
+        self.sky_light.light_level_at(at)
+    }
+
+    /// Recomputes the block light and sky light levels of the excerpt from its blocks.
+    ///
+    /// Light levels are not updated as blocks are changed, so this should be
+    /// called after editing, if light levels are of interest. The excerpt is lit
+    /// as if under open sky, with no other light entering from its sides or
+    /// from below. Light blocking properties of Minecraft 1.14 and later are used.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::world_excerpt::WorldExcerpt;
+    ///
+    /// let mut excerpt = WorldExcerpt::new(3, 2, 1);
+    /// excerpt.set_block_at((0, 1, 0).into(), Block::Stone);
+    /// excerpt.set_block_at((1, 1, 0).into(), Block::Stone);
+    /// excerpt.set_block_at((2, 0, 0).into(), Block::Glowstone);
+    /// excerpt.recompute_light();
+    ///
+    /// assert_eq!(excerpt.sky_light_at((1, 0, 0).into()), Some(14));
+    /// assert_eq!(excerpt.block_light_at((1, 0, 0).into()), Some(14));
+    /// assert_eq!(excerpt.block_light_at((0, 0, 0).into()), Some(13));
+    /// ```
+    pub fn recompute_light(&mut self) {
+        let (_, y_dim, _) = self.dim();
+        let open_sky = |at: BlockCoord| {
+            if at.1 == y_dim as i64 {
+                Some((0, 15))
+            } else {
+                None
+            }
+        };
+        let (block_light, sky_light) = lighting::light(&self.blocks, LightRules::New, open_sky);
+        self.block_light = block_light;
+        self.sky_light = sky_light;
+    }
+
     /// Generate and return a height map of the world excerpt.
     pub fn height_map(&self) -> HeightMap {
         self.blocks.height_map()
     }
 
+    /// Generate and return a height map of the given kind, of the world excerpt.
+    ///
+    /// Each height is the y coordinate just above the highest block tracked by
+    /// the kind of height map, or 0 if there is no such block in the column.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::height_map::HeightMapKind;
+    /// use mcprogedit::world_excerpt::WorldExcerpt;
+    ///
+    /// let mut excerpt = WorldExcerpt::new(1, 4, 1);
+    /// excerpt.set_block_at((0, 0, 0).into(), Block::Stone);
+    /// excerpt.set_block_at((0, 1, 0).into(), Block::WaterSource);
+    ///
+    /// let ocean_floor = excerpt.height_map_of(HeightMapKind::OceanFloor);
+    /// assert_eq!(ocean_floor.height_at((0, 0)), Some(1));
+    /// let surface = excerpt.height_map_of(HeightMapKind::MotionBlocking);
+    /// assert_eq!(surface.height_at((0, 0)), Some(2));
+    /// ```
+    pub fn height_map_of(&self, kind: HeightMapKind) -> HeightMap {
+        self.blocks.height_map_of(kind)
+    }
+
     /// Paste the contents of a different WorldExcerpt into this WorldExcerpt.
     ///
     /// The corner of `other` with the lowest numbered coordinates, is aligned at block
@@ -386,25 +1033,556 @@ impl WorldExcerpt {
     ///
     /// Empty blocks ([`Block::None`](crate::block::Block::None)) are not copied over,
     /// allowing for pasting other selection shapes than rectangular cuboids.
+    ///
+    /// The pasted entities are given new UUIDs, as for
+    /// [`Entity::renew_uuids`](crate::entity::Entity::renew_uuids).
     pub fn paste(&mut self, at: BlockCoord, other: &WorldExcerpt) {
-        self.blocks.paste((at.0, at.1, at.2), &other.blocks);
-        // TODO also handle / copy entities within the world excerpts
+        if let Some(journal) = &mut self.journal {
+            self.blocks
+                .paste_observed((at.0, at.1, at.2), &other.blocks, |at, before, after| {
+                    journal.record(at, before, after)
+                });
+        } else {
+            self.blocks.paste((at.0, at.1, at.2), &other.blocks);
+        }
+        self.add_contents_from(other, BlockCoord(0, 0, 0) - at, true, |_| true);
     }
 
     /// Replace all occurrences of the given block with the given replacement.
     pub fn replace(&mut self, search_for: &Block, replace_with: &Block) {
-        self.blocks.replace(search_for, replace_with);
+        if let Some(journal) = &mut self.journal {
+            self.blocks
+                .replace_observed(search_for, replace_with, |at, before, after| {
+                    journal.record(at, before, after)
+                });
+        } else {
+            self.blocks.replace(search_for, replace_with);
+        }
+    }
+
+    /// Replace every block matching `predicate` with the block returned by `mapper`.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::{Block, BlockQuery};
+    /// use mcprogedit::material::Material;
+    /// use mcprogedit::positioning::Axis3;
+    /// use mcprogedit::world_excerpt::WorldExcerpt;
+    ///
+    /// let mut excerpt = WorldExcerpt::new(2, 1, 1);
+    /// excerpt.set_block_at((0, 0, 0).into(), Block::oak_planks());
+    /// excerpt.set_block_at((1, 0, 0).into(), Block::oak_log(Axis3::Y));
+    ///
+    /// let oak_but_logs = BlockQuery::Material(Material::Oak).and(!BlockQuery::Is(Block::is_log));
+    /// excerpt.replace_where(&oak_but_logs, |_| Block::spruce_planks());
+    ///
+    /// assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::spruce_planks()));
+    /// assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::oak_log(Axis3::Y)));
+    /// ```
+    pub fn replace_where<P, F>(&mut self, predicate: P, mut mapper: F)
+    where
+        P: BlockPredicate,
+        F: FnMut(&Block) -> Block,
+    {
+        let replacements: Vec<(BlockCoord, Block)> = self
+            .find_where(predicate)
+            .map(|(at, block)| (at, mapper(block)))
+            .collect();
+        for (at, block) in replacements {
+            if self.block_at(at) != Some(&block) {
+                self.set_block_at(at, block);
+            }
+        }
+    }
+
+    /// Changes the material of every block made of `from` to `to`.
+    ///
+    /// Orientation and other state of the blocks are kept, and blocks of a
+    /// kind that has no `to` variant are left unchanged.
+    /// See [`Block::material_swapped()`](crate::block::Block::material_swapped).
+    pub fn swap_material(&mut self, from: Material, to: Material) {
+        self.replace_where(
+            |block: &Block| block.has_material_of(from),
+            |block| block.material_swapped(from, to),
+        );
+    }
+
+    /// Changes the colour of every block coloured `from` to `to`.
+    ///
+    /// Orientation and other state of the blocks are kept, including block
+    /// entity data such as banner patterns.
+    /// See [`Block::colour_swapped()`](crate::block::Block::colour_swapped).
+    pub fn swap_colour(&mut self, from: Colour, to: Colour) {
+        self.replace_where(|_: &Block| true, |block| block.colour_swapped(from, to));
+    }
+
+    /// Iterates over the positions and blocks of all blocks matching `predicate`.
+    ///
+    /// The blocks are ordered as for [`iter()`](Self::iter).
+    pub fn find_where<'a, P>(
+        &'a self,
+        predicate: P,
+    ) -> impl Iterator<Item = (BlockCoord, &'a Block)> + 'a
+    where
+        P: BlockPredicate + 'a,
+    {
+        self.iter()
+            .filter(move |(_, block)| predicate.matches(block))
+    }
+
+    /// Replace the occurrences of the given block with the given replacement,
+    /// for the positions that are part of `selection`.
+    pub fn replace_within<S: Selection + ?Sized>(
+        &mut self,
+        selection: &S,
+        search_for: &Block,
+        replace_with: &Block,
+    ) {
+        for at in selection.iter() {
+            if self.block_at(at) == Some(search_for) {
+                self.set_block_at(at, replace_with.clone());
+            }
+        }
+    }
+
+    /// Rotates the excerpt around the vertical axis, clockwise as seen from above.
+    ///
+    /// The orientation of every block is rotated along with its position, so
+    /// that e.g. stairs, doors and rails keep facing the same way relative to
+    /// the rest of the excerpt. Quarter turns swap the x and z dimensions.
+    ///
+    /// The journal, if enabled, starts over with no recorded changes.
+    ///
+    /// # Examples
+    /// ```
+    /// use mcprogedit::block::Block;
+    /// use mcprogedit::positioning::{Direction, Rotation};
+    /// use mcprogedit::world_excerpt::WorldExcerpt;
+    ///
+    /// let mut excerpt = WorldExcerpt::new(3, 1, 2);
+    /// excerpt.set_block_at((2, 0, 0).into(), Block::oak_fence_gate(Direction::North));
+    /// excerpt.rotate(Rotation::Clockwise90);
+    ///
+    /// assert_eq!(excerpt.dim(), (2, 1, 3));
+    /// assert_eq!(
+    ///     excerpt.block_at((1, 0, 2).into()),
+    ///     Some(&Block::oak_fence_gate(Direction::East)),
+    /// );
+    /// ```
+    pub fn rotate(&mut self, rotation: Rotation) {
+        let (x_dim, _, z_dim) = self.dim();
+        for entity in &mut self.entities {
+            entity.rotate(rotation, (x_dim, z_dim));
+        }
+        for tick in &mut self.scheduled_ticks {
+            tick.rotate(rotation, (x_dim, z_dim));
+        }
+        self.blocks = self.blocks.rotated(rotation);
+        self.block_light = self.block_light.rotated(rotation);
+        self.sky_light = self.sky_light.rotated(rotation);
+        self.restart_journal();
+    }
+
+    /// Mirrors the excerpt along the given axis.
+    ///
+    /// Mirroring along the X axis swaps east and west, mirroring along the Z
+    /// axis swaps north and south. The orientation of every block is mirrored
+    /// along with its position.
+    ///
+    /// The journal, if enabled, starts over with no recorded changes.
+    pub fn mirror(&mut self, axis: Axis2) {
+        let (x_dim, _, z_dim) = self.dim();
+        for entity in &mut self.entities {
+            entity.mirror(axis, (x_dim, z_dim));
+        }
+        for tick in &mut self.scheduled_ticks {
+            tick.mirror(axis, (x_dim, z_dim));
+        }
+        self.blocks = self.blocks.mirrored(axis);
+        self.block_light = self.block_light.mirrored(axis);
+        self.sky_light = self.sky_light.mirrored(axis);
+        self.restart_journal();
+    }
+
+    /// Computes the block state properties that follow from neighbouring
+    /// blocks, such as fence connections and stair shapes.
+    ///
+    /// See [`ConnectionStates`](crate::connections::ConnectionStates) for the
+    /// blocks covered. The properties are written to post-flattening saves
+    /// with [`to_save_with_options`](Self::to_save_with_options), if
+    /// [`SaveOptions::resolve_connections`] is set.
+    pub fn resolve_connections(&self) -> ConnectionStates {
+        ConnectionStates::of(self)
+    }
+
+    /// Lists the items needed to place every block of the excerpt.
+    ///
+    /// If `include_inventories` is set, the contents of chests and other
+    /// blocks holding items are included. See
+    /// [`BillOfMaterials`](crate::bill_of_materials::BillOfMaterials) for details.
+    pub fn bill_of_materials(&self, include_inventories: bool) -> BillOfMaterials {
+        BillOfMaterials::of(self, include_inventories)
+    }
+
+    /// Lists doors, beds, tall plants, pistons and double chests missing one of their parts.
+    ///
+    /// See [`validation::validate()`] for details.
+    pub fn validate(&self) -> Vec<Problem> {
+        validation::validate(self)
+    }
+
+    /// Completes or removes the broken structures found by [`validate()`](Self::validate).
+    ///
+    /// Returns the problems repaired. See [`validation::auto_fix()`] for details.
+    pub fn auto_fix(&mut self) -> Vec<Problem> {
+        validation::auto_fix(self)
+    }
+
+    /// Counts the blocks of the excerpt, grouped in several ways.
+    ///
+    /// See [`Statistics`](crate::statistics::Statistics) for the groupings.
+    pub fn statistics(&self) -> Statistics {
+        Statistics::of(self)
+    }
+
+    /// Lists the blocks that differ between this excerpt and `other`.
+    ///
+    /// The returned diff holds the blocks of this excerpt as the "before" state,
+    /// and the blocks of `other` as the "after" state. It can be applied to
+    /// another excerpt with [`ExcerptDiff::patch()`], or to a world save with
+    /// [`ExcerptDiff::patch_save()`].
+    ///
+    /// # Panics
+    /// Panics if the excerpts are not of the same size.
+    pub fn diff(&self, other: &WorldExcerpt) -> ExcerptDiff {
+        ExcerptDiff::between(self, other)
+    }
+
+    /// Starts recording changes made through [`set_block_at`](Self::set_block_at),
+    /// [`paste`](Self::paste) and [`replace`](Self::replace), so that they can be undone.
+    ///
+    /// Only the changed blocks are recorded, not copies of the whole excerpt,
+    /// along with the entities and scheduled ticks added by pasting and by
+    /// [`schedule_tick`](Self::schedule_tick).
+    /// Does nothing if the journal is already enabled.
+    pub fn enable_journal(&mut self) {
+        if self.journal.is_none() {
+            self.journal = Some(Journal::new());
+        }
+    }
+
+    /// Stops recording changes, and forgets all recorded changes.
+    pub fn disable_journal(&mut self) {
+        self.journal = None;
+    }
+
+    fn restart_journal(&mut self) {
+        if self.journal.is_some() {
+            self.journal = Some(Journal::new());
+        }
+    }
+
+    /// Returns true if changes to the excerpt are being recorded.
+    pub fn is_journal_enabled(&self) -> bool {
+        self.journal.is_some()
+    }
+
+    /// Marks the end of the current group of changes, and returns a checkpoint
+    /// that the excerpt can later be rolled back to.
+    ///
+    /// Enables the journal if it is not already enabled.
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        self.journal.get_or_insert_with(Journal::new).checkpoint()
+    }
+
+    /// Reverts the changes made since the previous checkpoint, or the group of
+    /// changes before that if no changes have been made since.
+    ///
+    /// Returns false if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.journal_and_contents() {
+            Some((journal, mut contents)) => journal.undo(&mut contents),
+            None => false,
+        }
+    }
+
+    /// Reapplies the most recently undone group of changes.
+    ///
+    /// Returns false if there was nothing to redo. Making new changes after
+    /// undoing discards the undone changes.
+    pub fn redo(&mut self) -> bool {
+        match self.journal_and_contents() {
+            Some((journal, mut contents)) => journal.redo(&mut contents),
+            None => false,
+        }
+    }
+
+    /// Undoes (or redoes) changes until the excerpt is back to its state at `checkpoint`.
+    ///
+    /// Returns false, leaving the excerpt unchanged, if the checkpoint can no
+    /// longer be reached, e.g. because the journal started over since.
+    pub fn rollback_to(&mut self, checkpoint: Checkpoint) -> bool {
+        match self.journal_and_contents() {
+            Some((journal, mut contents)) => journal.rollback_to(checkpoint, &mut contents),
+            None => false,
+        }
+    }
+
+    /// The journal, if enabled, along with the parts of the excerpt it changes.
+    fn journal_and_contents(&mut self) -> Option<(&mut Journal, Contents<'_>)> {
+        let journal = self.journal.as_mut()?;
+        Some((
+            journal,
+            Contents {
+                blocks: &mut self.blocks,
+                entities: &mut self.entities,
+                scheduled_ticks: &mut self.scheduled_ticks,
+            },
+        ))
     }
 
     //TODO functions for:
     // - exporting the WorldExcerpt to a schematic file
 }
 
+/// Replaces the entities of an entity chunk (as stored in the entity region
+/// files of 20w45a and later) that are `within_bounds`, with `pasted_entities`.
+/// An entity chunk is created for `chunk` if there is none.
+pub(crate) fn entity_chunk_with_entities<F>(
+    entity_data: RawChunkData,
+    chunk: &Chunk,
+    within_bounds: F,
+    pasted_entities: Vec<Entity>,
+) -> RawChunkData
+where
+    F: Fn(&Entity) -> bool,
+{
+    let data_version = chunk.data_version();
+    let mut nbt = match entity_data {
+        RawChunkData::Empty => {
+            let mut nbt = nbt::Blob::new();
+            nbt.insert("DataVersion", data_version.id()).unwrap();
+            let position = chunk.chunk_coordinates();
+            nbt.insert(
+                "Position",
+                nbt::Value::IntArray(vec![position.0 as i32, position.1 as i32]),
+            )
+            .unwrap();
+            nbt
+        }
+        entity_data => entity_data.to_nbt(),
+    };
+
+    let mut entities =
+        entity::entities_from_nbt_list(&nbt_blob_lookup_list(&nbt, "Entities").unwrap_or_default());
+    entities.retain(|entity| !within_bounds(entity));
+    entities.extend(pasted_entities);
+    nbt.insert(
+        "Entities",
+        entity::entities_to_nbt_list(&entities, data_version),
+    )
+    .unwrap();
+    RawChunkData::new_zlib(&nbt)
+}
+
+/// Offsets of the chunks to the west, east, north and south, in the order
+/// taken by [`Chunk::recompute_light`].
+const NEIGHBOUR_OFFSETS: [ChunkCoord; 4] = [
+    ChunkCoord(-1, 0),
+    ChunkCoord(1, 0),
+    ChunkCoord(0, -1),
+    ChunkCoord(0, 1),
+];
+
+fn is_pre_flattening(chunk: &Chunk) -> bool {
+    matches!(
+        ChunkFormat::for_version(chunk.data_version()),
+        Ok(ChunkFormat::PreFlattening)
+    )
+}
+
+/// Recomputes the light of the pre-flattening `chunks` written by a save, and
+/// of the saved chunks surrounding them, and puts them into their `regions`.
+///
+/// Light reaches at most 15 blocks, so it never reaches past the surrounding
+/// chunks, but may cross two chunk borders near a corner. Each chunk is
+/// therefore first lit on its own, taking light only from the unchanged
+/// chunks further out, and light is then spread between the chunks in two
+/// more rounds. Of the surrounding chunks only the light is written, the rest
+/// of their data is kept as it was.
+fn relight_chunks(
+    mut chunks: BTreeMap<ChunkCoord, Chunk>,
+    regions: &mut BTreeMap<RegionCoord, Region>,
+    region_directory: &std::path::Path,
+) {
+    let mut saved_chunks = SavedChunks::new(region_directory);
+    let mut surrounding: BTreeMap<ChunkCoord, RawChunkData> = BTreeMap::new();
+    let pasted: Vec<ChunkCoord> = chunks.keys().copied().collect();
+    for at in &pasted {
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                let neighbour = *at + ChunkCoord(dx, dz);
+                if chunks.contains_key(&neighbour) {
+                    continue;
+                }
+                if let Some(chunk) = saved_chunks.take(neighbour) {
+                    chunks.insert(neighbour, chunk);
+                    surrounding.insert(neighbour, saved_chunks.raw_chunk_data(neighbour));
+                }
+            }
+        }
+    }
+
+    let coordinates: Vec<ChunkCoord> = chunks.keys().copied().collect();
+    for round in 0..3 {
+        for at in &coordinates {
+            for offset in &NEIGHBOUR_OFFSETS {
+                if !chunks.contains_key(&(*at + *offset)) {
+                    saved_chunks.load(*at + *offset);
+                }
+            }
+            let mut chunk = chunks.remove(at).unwrap();
+            let neighbour = |offset: &ChunkCoord| {
+                let at = *at + *offset;
+                if chunks.contains_key(&at) {
+                    // Light from chunks that are being relit, once lit on their own
+                    if round == 0 {
+                        None
+                    } else {
+                        chunks.get(&at)
+                    }
+                } else {
+                    saved_chunks.get(at)
+                }
+            };
+            chunk.recompute_light([
+                neighbour(&NEIGHBOUR_OFFSETS[0]),
+                neighbour(&NEIGHBOUR_OFFSETS[1]),
+                neighbour(&NEIGHBOUR_OFFSETS[2]),
+                neighbour(&NEIGHBOUR_OFFSETS[3]),
+            ]);
+            chunks.insert(*at, chunk);
+        }
+    }
+
+    for (at, chunk) in chunks {
+        let chunk_data = match surrounding.remove(&at) {
+            Some(raw_chunk_data) => RawChunkData::new_zlib(
+                &chunk.pre_flattening_nbt_with_light(raw_chunk_data.to_nbt()),
+            ),
+            None => chunk.raw_chunk_zlib(),
+        };
+        let region_coordinates: RegionCoord = at.into();
+        let region_offset: ChunkCoord = region_coordinates.into();
+        let region_file = region_directory.join(format!(
+            "r.{}.{}.mca",
+            region_coordinates.0, region_coordinates.1
+        ));
+        regions
+            .entry(region_coordinates)
+            .or_insert_with(|| Region::load_from_file(&region_file))
+            .set_chunk_data(
+                &(at.0 - region_offset.0, at.1 - region_offset.1).into(),
+                chunk_data,
+            );
+    }
+}
+
+/// Pre-flattening chunks of a world save, read on demand from any region.
+struct SavedChunks<'a> {
+    region_directory: &'a std::path::Path,
+    regions: HashMap<RegionCoord, Option<Region>>,
+    chunks: HashMap<ChunkCoord, Option<Chunk>>,
+}
+
+impl<'a> SavedChunks<'a> {
+    fn new(region_directory: &'a std::path::Path) -> Self {
+        Self {
+            region_directory,
+            regions: HashMap::new(),
+            chunks: HashMap::new(),
+        }
+    }
+
+    /// The saved data of the chunk at global chunk coordinates `at`.
+    fn raw_chunk_data(&mut self, at: ChunkCoord) -> RawChunkData {
+        let region_coordinates: RegionCoord = at.into();
+        let region_offset: ChunkCoord = region_coordinates.into();
+        let region_directory = self.region_directory;
+        let region = self.regions.entry(region_coordinates).or_insert_with(|| {
+            let region_file = region_directory.join(format!(
+                "r.{}.{}.mca",
+                region_coordinates.0, region_coordinates.1
+            ));
+            if region_file.is_file() {
+                Some(Region::open(&region_file))
+            } else {
+                None
+            }
+        });
+        match region {
+            Some(region) => {
+                region.chunk_data(&(at.0 - region_offset.0, at.1 - region_offset.1).into())
+            }
+            None => RawChunkData::Empty,
+        }
+    }
+
+    /// Reads the chunk at `at`, if not already read.
+    fn load(&mut self, at: ChunkCoord) {
+        if !self.chunks.contains_key(&at) {
+            let chunk = match self.raw_chunk_data(at) {
+                RawChunkData::Empty => None,
+                raw_chunk_data => {
+                    Some(Chunk::from_raw_chunk_data(&raw_chunk_data)).filter(is_pre_flattening)
+                }
+            };
+            self.chunks.insert(at, chunk);
+        }
+    }
+
+    /// The chunk at `at`, if read and found.
+    fn get(&self, at: ChunkCoord) -> Option<&Chunk> {
+        self.chunks.get(&at).and_then(Option::as_ref)
+    }
+
+    /// Reads the chunk at `at`, and hands it over to the caller.
+    fn take(&mut self, at: ChunkCoord) -> Option<Chunk> {
+        self.load(at);
+        self.chunks.get_mut(&at).and_then(Option::take)
+    }
+}
+
+fn block_coord((x, y, z): (usize, usize, usize)) -> BlockCoord {
+    BlockCoord(x as i64, y as i64, z as i64)
+}
+
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
     use super::*;
-    use crate::block::Log;
+    use crate::block::{Log, RedstoneRepeater};
+    use crate::bounded_ints::Int1Through4;
+    use crate::export_policy::OnUnavailable;
     use crate::material::WoodMaterial;
+    use crate::positioning::{Direction, Surface4};
+    use crate::scheduled_tick::TickTarget;
+
+    /// A scratch copy of the 1.12.2 test save, in the temporary directory.
+    fn copy_of_1_12_2_save(name: &str) -> std::path::PathBuf {
+        let world =
+            std::env::temp_dir().join(format!("mcprogedit-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&world);
+        std::fs::create_dir_all(world.join("region")).unwrap();
+        std::fs::copy("tests/saves/1_12_2/level.dat", world.join("level.dat")).unwrap();
+        std::fs::copy(
+            "tests/saves/1_12_2/region/r.0.0.mca",
+            world.join("region").join("r.0.0.mca"),
+        )
+        .unwrap();
+        world
+    }
 
     #[test]
     fn test_basic_functionality() {
@@ -435,4 +1613,555 @@ mod tests {
         run_test_at(&mut excerpt, (0, 2, 2).into(), Block::GrassBlock);
         run_test_at(&mut excerpt, (2, 2, 2).into(), Block::Ice);
     }
+
+    #[test]
+    fn test_undo_redo_and_rollback() {
+        let mut excerpt = WorldExcerpt::new(2, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Stone);
+
+        let start = excerpt.checkpoint();
+        excerpt.set_block_at((0, 0, 0).into(), Block::Dirt);
+        excerpt.set_block_at((1, 0, 0).into(), Block::Dirt);
+        let dirt = excerpt.checkpoint();
+        excerpt.replace(&Block::Dirt, &Block::Sand);
+
+        assert!(excerpt.undo());
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::Dirt));
+        assert!(excerpt.redo());
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::Sand));
+
+        assert!(excerpt.rollback_to(start));
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Stone));
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::None));
+        assert!(!excerpt.undo());
+
+        assert!(excerpt.rollback_to(dirt));
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Dirt));
+
+        // New changes after undoing make the undone states unreachable.
+        let mut other = WorldExcerpt::new(1, 1, 1);
+        other.set_block_at((0, 0, 0).into(), Block::Gravel);
+        excerpt.undo();
+        excerpt.paste((1, 0, 0).into(), &other);
+        assert!(!excerpt.rollback_to(dirt));
+        assert!(excerpt.rollback_to(start));
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::None));
+    }
+
+    #[test]
+    fn checkpoints_from_before_a_rotation_are_unreachable() {
+        let mut excerpt = WorldExcerpt::new(2, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Stone);
+        let before_edits = excerpt.checkpoint();
+        excerpt.set_block_at((1, 0, 0).into(), Block::Dirt);
+        let stone_and_dirt = excerpt.checkpoint();
+
+        excerpt.rotate(Rotation::Clockwise180);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Sand);
+        excerpt.checkpoint();
+        excerpt.set_block_at((1, 0, 0).into(), Block::Gravel);
+
+        assert!(!excerpt.rollback_to(stone_and_dirt));
+        assert!(!excerpt.rollback_to(before_edits));
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Sand));
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::Gravel));
+    }
+
+    #[test]
+    fn undo_removes_pasted_entities_and_ticks() {
+        let mut pool = WorldExcerpt::new(1, 1, 1);
+        pool.set_block_at((0, 0, 0).into(), Block::WaterSource);
+        pool.schedule_tick((0, 0, 0).into(), 5);
+        pool.entities_mut()
+            .push(Entity::new("minecraft:cod", (0.5, 0.0, 0.5)));
+
+        let mut excerpt = WorldExcerpt::new(2, 1, 1);
+        let start = excerpt.checkpoint();
+        excerpt.paste((1, 0, 0).into(), &pool);
+        assert_eq!(excerpt.entities().len(), 1);
+        assert_eq!(excerpt.scheduled_ticks().len(), 1);
+
+        assert!(excerpt.undo());
+        assert!(excerpt.entities().is_empty());
+        assert!(excerpt.scheduled_ticks().is_empty());
+        assert_eq!(excerpt.block_at((1, 0, 0).into()), Some(&Block::None));
+
+        assert!(excerpt.redo());
+        assert_eq!(excerpt.entities().len(), 1);
+        assert_eq!(excerpt.scheduled_ticks()[0].at, (1, 0, 0).into());
+
+        assert!(excerpt.rollback_to(start));
+        assert!(excerpt.entities().is_empty());
+        assert!(excerpt.scheduled_ticks().is_empty());
+    }
+
+    #[test]
+    fn save_with_backup_can_be_rolled_back() {
+        let world = copy_of_1_12_2_save("excerpt-rollback");
+        let region_file = world.join("region").join("r.0.0.mca");
+        let original_region = std::fs::read(&region_file).unwrap();
+
+        let original_block = WorldExcerpt::from_save((3, 3, 3).into(), (3, 3, 3).into(), &world)
+            .block_at((0, 0, 0).into())
+            .cloned();
+        assert_ne!(original_block, Some(Block::Sponge));
+
+        let mut excerpt = WorldExcerpt::new(1, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Sponge);
+        let backup = excerpt
+            .to_save_with_backup((3, 3, 3).into(), &world)
+            .unwrap();
+
+        let saved_excerpt = WorldExcerpt::from_save((3, 3, 3).into(), (3, 3, 3).into(), &world);
+        assert_eq!(
+            saved_excerpt.block_at((0, 0, 0).into()),
+            Some(&Block::Sponge)
+        );
+
+        backup.restore();
+        assert_eq!(std::fs::read(&region_file).unwrap(), original_region);
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn rotation_and_mirroring_move_and_turn_blocks() {
+        let mut excerpt = WorldExcerpt::new(3, 2, 2);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Stone);
+        excerpt.set_block_at((2, 1, 1).into(), Block::oak_fence_gate(Direction::West));
+        excerpt.block_light.set_light_level_at((2, 1, 1).into(), 7);
+        let original = excerpt.clone();
+
+        excerpt.rotate(Rotation::Counterclockwise90);
+        assert_eq!(excerpt.dim(), (2, 2, 3));
+        assert_eq!(excerpt.block_at((0, 0, 2).into()), Some(&Block::Stone));
+        assert_eq!(
+            excerpt.block_at((1, 1, 0).into()),
+            Some(&Block::oak_fence_gate(Direction::South))
+        );
+        assert_eq!(
+            excerpt.block_light.light_level_at((1, 1, 0).into()),
+            Some(7)
+        );
+
+        excerpt.rotate(Rotation::Clockwise90);
+        assert!(original.diff(&excerpt).is_empty());
+
+        excerpt.mirror(Axis2::X);
+        assert_eq!(excerpt.block_at((2, 0, 0).into()), Some(&Block::Stone));
+        assert_eq!(
+            excerpt.block_at((0, 1, 1).into()),
+            Some(&Block::oak_fence_gate(Direction::East))
+        );
+        excerpt.mirror(Axis2::X);
+        assert!(original.diff(&excerpt).is_empty());
+    }
+
+    #[test]
+    fn selections_limit_replace_and_copy() {
+        use crate::shape::{Cuboid, Shape, Sphere};
+
+        let mut excerpt = WorldExcerpt::new(5, 5, 5);
+        Cuboid::new((0, 0, 0).into(), (4, 4, 4).into()).fill(&mut excerpt, &Block::Stone);
+        excerpt.enable_journal();
+
+        let ball = Sphere::new((2, 2, 2).into(), 1.0);
+        excerpt.replace_within(&ball, &Block::Stone, &Block::Dirt);
+        assert_eq!(excerpt.block_at((2, 2, 2).into()), Some(&Block::Dirt));
+        assert_eq!(excerpt.block_at((3, 2, 2).into()), Some(&Block::Dirt));
+        assert_eq!(excerpt.block_at((3, 3, 3).into()), Some(&Block::Stone));
+        assert!(excerpt.undo());
+        assert_eq!(excerpt.block_at((2, 2, 2).into()), Some(&Block::Stone));
+
+        let corner = Sphere::new((0, 0, 0).into(), 1.0);
+        let copy = WorldExcerpt::from_selection(&corner, &excerpt);
+        assert_eq!(copy.dim(), (2, 2, 2));
+        assert_eq!(copy.block_at((1, 0, 0).into()), Some(&Block::Stone));
+        assert_eq!(copy.block_at((1, 1, 1).into()), Some(&Block::None));
+
+        let outside = Sphere::new((10, 10, 10).into(), 1.0);
+        assert_eq!(
+            WorldExcerpt::from_selection(&outside, &excerpt).dim(),
+            (0, 0, 0)
+        );
+    }
+
+    #[test]
+    fn material_and_colour_swaps() {
+        let mut excerpt = WorldExcerpt::new(3, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::oak_fence_gate(Direction::East));
+        excerpt.set_block_at(
+            (1, 0, 0).into(),
+            Block::Wool {
+                colour: Colour::Red,
+            },
+        );
+        excerpt.set_block_at((2, 0, 0).into(), Block::concrete_with_colour(Colour::Red));
+
+        excerpt.swap_material(Material::Oak, Material::Acacia);
+        excerpt.swap_colour(Colour::Red, Colour::Lime);
+        assert_eq!(
+            excerpt.block_at((0, 0, 0).into()),
+            Some(&Block::acacia_fence_gate(Direction::East))
+        );
+        assert_eq!(
+            excerpt.block_at((1, 0, 0).into()),
+            Some(&Block::Wool {
+                colour: Colour::Lime
+            })
+        );
+        assert_eq!(
+            excerpt.block_at((2, 0, 0).into()),
+            Some(&Block::concrete_with_colour(Colour::Lime))
+        );
+    }
+
+    #[test]
+    fn iterators_visit_every_block() {
+        use crate::shape::Cuboid;
+
+        let mut excerpt = WorldExcerpt::new(2, 3, 4);
+        excerpt.set_block_at((1, 2, 3).into(), Block::Stone);
+        excerpt.set_block_at((0, 1, 2).into(), Block::Dirt);
+        excerpt.enable_journal();
+        excerpt.set_block_at((1, 0, 0).into(), Block::Gravel);
+
+        let visited: Vec<BlockCoord> = excerpt.iter().map(|(at, _)| at).collect();
+        assert_eq!(visited.len(), 2 * 3 * 4);
+        for (at, block) in excerpt.iter() {
+            assert_eq!(excerpt.block_at(at), Some(block));
+        }
+
+        for (_, block) in excerpt.iter_mut() {
+            if *block == Block::None {
+                *block = Block::Air;
+            }
+        }
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&Block::Air));
+        assert_eq!(excerpt.block_at((1, 2, 3).into()), Some(&Block::Stone));
+        assert!(!excerpt.undo());
+
+        let columns: Vec<Vec<(BlockCoord, &Block)>> =
+            excerpt.columns().map(|column| column.collect()).collect();
+        assert_eq!(columns.len(), 2 * 4);
+        assert_eq!(columns[2 * 4 - 1][2], (BlockCoord(1, 2, 3), &Block::Stone));
+
+        let layers: Vec<Vec<(BlockCoord, &Block)>> =
+            excerpt.layers().map(|layer| layer.collect()).collect();
+        assert_eq!(layers.len(), 3);
+        assert_eq!(layers[1][2], (BlockCoord(0, 1, 2), &Block::Dirt));
+        assert!(layers[0].iter().all(|(at, _)| at.1 == 0));
+
+        let region = Cuboid::new((1, 0, 0).into(), (5, 0, 0).into());
+        assert_eq!(
+            excerpt.iter_region(&region).collect::<Vec<_>>(),
+            vec![(BlockCoord(1, 0, 0), &Block::Gravel)]
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_iteration_matches_sequential() {
+        use rayon::prelude::*;
+
+        let mut excerpt = WorldExcerpt::new(3, 3, 3);
+        excerpt.set_block_at((2, 1, 0).into(), Block::Stone);
+        let stones: Vec<BlockCoord> = excerpt
+            .par_iter()
+            .filter(|(_, block)| **block == Block::Stone)
+            .map(|(at, _)| at)
+            .collect();
+        assert_eq!(stones, vec![BlockCoord(2, 1, 0)]);
+        assert_eq!(excerpt.par_iter().count(), excerpt.iter().count());
+    }
+
+    #[test]
+    fn diff_patches_save_unless_conflicting() {
+        let world = copy_of_1_12_2_save("excerpt-patch");
+
+        let before = WorldExcerpt::from_save((2, 2, 2).into(), (5, 5, 5).into(), &world);
+        let mut after = before.clone();
+        after.set_block_at((1, 1, 1).into(), Block::Sponge);
+        after.set_block_at((3, 2, 0).into(), Block::Glowstone);
+        let diff = before.diff(&after);
+
+        diff.patch_save((2, 2, 2).into(), &world).unwrap();
+        let saved = WorldExcerpt::from_save((2, 2, 2).into(), (5, 5, 5).into(), &world);
+        assert!(saved.diff(&after).is_empty());
+
+        // The save no longer matches the "before" state of the diff.
+        assert!(diff.patch_save((2, 2, 2).into(), &world).is_err());
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn light_crosses_chunk_and_region_borders_on_save() {
+        let world = copy_of_1_12_2_save("excerpt-border-light");
+        let light_at = |at: BlockCoord| {
+            WorldExcerpt::from_save(at, at, &world).block_light_at((0, 0, 0).into())
+        };
+        let mut glowstone = WorldExcerpt::new(1, 1, 1);
+        glowstone.set_block_at((0, 0, 0).into(), Block::Glowstone);
+        let mut air = WorldExcerpt::new(1, 1, 1);
+        air.set_block_at((0, 0, 0).into(), Block::Air);
+
+        // Light from a chunk edge reaches into the neighbouring chunk,
+        // and is gone from it again when the light source is removed.
+        glowstone.to_save((15, 100, 8).into(), &world).unwrap();
+        assert_eq!(light_at((16, 100, 8).into()), Some(14));
+        assert_eq!(light_at((17, 100, 7).into()), Some(12));
+        air.to_save((15, 100, 8).into(), &world).unwrap();
+        assert_eq!(light_at((16, 100, 8).into()), Some(0));
+
+        // Likewise between chunks in different region files.
+        air.to_save((513, 100, 8).into(), &world).unwrap();
+        glowstone.to_save((511, 100, 8).into(), &world).unwrap();
+        assert_eq!(light_at((512, 100, 8).into()), Some(14));
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn diff_patch_keeps_entities_and_ticks_in_save() {
+        let world = copy_of_1_12_2_save("excerpt-patch-entities");
+
+        let mut pool = WorldExcerpt::new(2, 1, 1);
+        pool.set_block_at((0, 0, 0).into(), Block::WaterSource);
+        pool.schedule_tick((0, 0, 0).into(), 5);
+        let mut cow = Entity::new("minecraft:cow", (1.5, 0.0, 0.5));
+        cow.uuid = Some(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        pool.entities_mut().push(cow.clone());
+        pool.to_save((20, 100, 20).into(), &world).unwrap();
+
+        let before = WorldExcerpt::from_save((20, 100, 20).into(), (21, 100, 20).into(), &world);
+        let mut after = before.clone();
+        after.set_block_at((1, 0, 0).into(), Block::Sponge);
+        before
+            .diff(&after)
+            .patch_save((20, 100, 20).into(), &world)
+            .unwrap();
+
+        let saved = WorldExcerpt::from_save((20, 100, 20).into(), (21, 100, 20).into(), &world);
+        assert_eq!(saved.block_at((1, 0, 0).into()), Some(&Block::Sponge));
+        assert_eq!(saved.entities().len(), 1);
+        assert_eq!(saved.entities()[0].id, "minecraft:cow");
+        assert_eq!(saved.entities()[0].uuid, cow.uuid);
+        assert_eq!(saved.scheduled_ticks().len(), 1);
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn copied_entities_get_new_uuids() {
+        let original = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210;
+        let mut pen = WorldExcerpt::new(1, 1, 1);
+        let mut cow = Entity::new("minecraft:cow", (0.5, 0.0, 0.5));
+        cow.uuid = Some(original);
+        pen.entities_mut().push(cow);
+
+        let mut excerpt = WorldExcerpt::new(2, 1, 1);
+        excerpt.paste((0, 0, 0).into(), &pen);
+        excerpt.paste((1, 0, 0).into(), &pen);
+        let mut uuids: Vec<u128> = excerpt
+            .entities()
+            .iter()
+            .map(|entity| entity.uuid.unwrap())
+            .collect();
+
+        // Saving keeps the UUIDs, unless asked otherwise.
+        let world = copy_of_1_12_2_save("excerpt-uuids");
+        pen.to_save((20, 100, 20).into(), &world).unwrap();
+        let options = SaveOptions {
+            new_entity_uuids: true,
+            ..Default::default()
+        };
+        pen.to_save_with_options((22, 100, 20).into(), &world, options)
+            .unwrap();
+        let saved = WorldExcerpt::from_save((20, 100, 20).into(), (22, 100, 20).into(), &world);
+        assert_eq!(saved.entities().len(), 2);
+        assert_eq!(saved.entities()[0].uuid, Some(original));
+        assert_eq!(pen.entities()[0].uuid, Some(original));
+        uuids.push(saved.entities()[1].uuid.unwrap());
+
+        uuids.push(original);
+        uuids.sort_unstable();
+        uuids.dedup();
+        assert_eq!(uuids.len(), 4);
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn save_of_unsupported_version_is_refused() {
+        let world = copy_of_1_12_2_save("excerpt-unsupported");
+        let region_file = world.join("region").join("r.0.0.mca");
+        let original_region = std::fs::read(&region_file).unwrap();
+
+        // A level.dat claiming to be from a Minecraft version yet to be released
+        let mut level_dat = std::fs::File::open("tests/saves/1_12_2/level.dat").unwrap();
+        let mut level_dat = nbt::Blob::from_gzip_reader(&mut level_dat).unwrap();
+        let mut data = match level_dat.get("Data") {
+            Some(nbt::Value::Compound(data)) => data.clone(),
+            _ => panic!("level.dat Data not found"),
+        };
+        data.insert("DataVersion".into(), nbt::Value::Int(99999));
+        level_dat
+            .insert("Data", nbt::Value::Compound(data))
+            .unwrap();
+        let mut file = std::fs::File::create(world.join("level.dat")).unwrap();
+        level_dat.to_gzip_writer(&mut file).unwrap();
+
+        let mut excerpt = WorldExcerpt::new(1, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::Sponge);
+        let error = excerpt.to_save((3, 3, 3).into(), &world).unwrap_err();
+        assert!(matches!(error, FormatError::UnsupportedVersion(_)));
+        assert_eq!(std::fs::read(&region_file).unwrap(), original_region);
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn entities_are_carried_through_paste_and_save() {
+        let mut pig_pen = WorldExcerpt::new(2, 2, 2);
+        pig_pen
+            .entities_mut()
+            .push(Entity::new("minecraft:pig", (1.5, 0.0, 0.5)));
+        pig_pen
+            .entities_mut()
+            .push(Entity::new("minecraft:cow", (5.5, 0.0, 0.5)));
+
+        let mut excerpt = WorldExcerpt::new(4, 4, 4);
+        excerpt.paste((2, 1, 2).into(), &pig_pen);
+        assert_eq!(excerpt.entities().len(), 1);
+        assert_eq!(excerpt.entities()[0].position, (3.5, 1.0, 2.5));
+
+        let world = copy_of_1_12_2_save("excerpt-entities");
+
+        excerpt.to_save((20, 60, 20).into(), &world).unwrap();
+        let saved = WorldExcerpt::from_save((20, 60, 20).into(), (23, 63, 23).into(), &world);
+        assert_eq!(saved.entities().len(), 1);
+        assert_eq!(saved.entities()[0].id, "minecraft:pig");
+        assert_eq!(saved.entities()[0].position, (3.5, 1.0, 2.5));
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn scheduled_ticks_are_carried_through_paste_and_save() {
+        let mut pool = WorldExcerpt::new(1, 1, 1);
+        pool.set_block_at((0, 0, 0).into(), Block::WaterSource);
+        assert!(pool.schedule_tick((0, 0, 0).into(), 5));
+        assert!(!pool.schedule_tick((1, 0, 0).into(), 5));
+
+        let mut excerpt = WorldExcerpt::new(3, 3, 3);
+        excerpt.paste((1, 2, 1).into(), &pool);
+        assert_eq!(excerpt.scheduled_ticks().len(), 1);
+        assert_eq!(excerpt.scheduled_ticks()[0].at, (1, 2, 1).into());
+
+        let world = copy_of_1_12_2_save("excerpt-ticks");
+
+        excerpt.to_save((30, 70, 30).into(), &world).unwrap();
+        let saved = WorldExcerpt::from_save((30, 70, 30).into(), (32, 72, 32).into(), &world);
+        assert_eq!(saved.scheduled_ticks().len(), 1);
+        assert_eq!(
+            saved.scheduled_ticks()[0].target,
+            TickTarget::Id("minecraft:flowing_water".into())
+        );
+        assert_eq!(saved.scheduled_ticks()[0].at, (1, 2, 1).into());
+        assert_eq!(saved.scheduled_ticks()[0].delay, 5);
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn scheduled_ticks_are_named_for_pre_flattening_saves() {
+        let repeater = Block::RedstoneRepeater(RedstoneRepeater {
+            facing: Surface4::East,
+            delay: Int1Through4::new_saturating(1),
+        });
+        let mut excerpt = WorldExcerpt::new(2, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), Block::LavaSource);
+        excerpt.set_block_at((1, 0, 0).into(), repeater);
+        assert!(excerpt.schedule_tick((0, 0, 0).into(), 30));
+        assert!(excerpt.schedule_tick((1, 0, 0).into(), 2));
+
+        let world = copy_of_1_12_2_save("excerpt-tick-names");
+
+        excerpt.to_save((30, 70, 30).into(), &world).unwrap();
+        let saved = WorldExcerpt::from_save((30, 70, 30).into(), (31, 70, 30).into(), &world);
+        let mut targets: Vec<_> = saved
+            .scheduled_ticks()
+            .iter()
+            .map(|tick| (tick.at, tick.target.clone()))
+            .collect();
+        targets.sort_by_key(|(at, _)| at.0);
+        assert_eq!(
+            targets,
+            vec![
+                (
+                    (0, 0, 0).into(),
+                    TickTarget::Id("minecraft:flowing_lava".into())
+                ),
+                (
+                    (1, 0, 0).into(),
+                    TickTarget::Id("minecraft:unpowered_repeater".into())
+                ),
+            ]
+        );
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
+
+    #[test]
+    fn export_policy_is_applied_on_save() {
+        let world = copy_of_1_12_2_save("excerpt-export");
+        let region_file = world.join("region").join("r.0.0.mca");
+        let original_region = std::fs::read(&region_file).unwrap();
+
+        let crimson = Block::Planks {
+            material: WoodMaterial::Crimson,
+        };
+        let mut excerpt = WorldExcerpt::new(1, 1, 1);
+        excerpt.set_block_at((0, 0, 0).into(), crimson.clone());
+        let policy = ExportPolicy::new(McVersion::from_str("1.12.2").unwrap()).with_fallback(
+            crimson.clone(),
+            Block::Planks {
+                material: WoodMaterial::DarkOak,
+            },
+        );
+
+        let options = SaveOptions {
+            export_policy: Some(policy.clone().with_on_unavailable(OnUnavailable::Fail)),
+            ..Default::default()
+        };
+        match excerpt.to_save_with_options((3, 3, 3).into(), &world, options) {
+            Err(FormatError::UnavailableBlock { at, block, .. }) => {
+                assert_eq!(at, (0, 0, 0).into());
+                assert_eq!(*block, crimson);
+            }
+            other => panic!("Expected unavailable block error, got {:?}", other),
+        }
+        assert_eq!(std::fs::read(&region_file).unwrap(), original_region);
+
+        let options = SaveOptions {
+            export_policy: Some(policy),
+            ..Default::default()
+        };
+        let report = excerpt
+            .to_save_with_options((3, 3, 3).into(), &world, options)
+            .unwrap();
+        assert!(report.backup.is_none());
+        assert_eq!(report.substitutions.map(|report| report.len()), Some(1));
+        assert_eq!(excerpt.block_at((0, 0, 0).into()), Some(&crimson));
+        let saved = WorldExcerpt::from_save((3, 3, 3).into(), (3, 3, 3).into(), &world);
+        assert_eq!(
+            saved.block_at((0, 0, 0).into()),
+            Some(&Block::Planks {
+                material: WoodMaterial::DarkOak
+            })
+        );
+
+        std::fs::remove_dir_all(&world).unwrap();
+    }
 }